@@ -0,0 +1,516 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use cargo_toml::Value;
+use clap::ValueEnum;
+
+use crate::{meta::Config, plist, profile::Profile, xcode};
+
+/// Platform sdk the binary is built for, named as `xcodebuild -sdk` ones
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Sdk {
+    #[value(name = "macosx")]
+    MacOs,
+    #[value(name = "iphoneos")]
+    Ios,
+    #[value(name = "iphonesimulator")]
+    IosSim,
+    #[value(name = "appletvos")]
+    TvOs,
+    #[value(name = "appletvsimulator")]
+    TvOsSim,
+    #[value(name = "watchos")]
+    WatchOs,
+    #[value(name = "watchsimulator")]
+    WatchOsSim,
+    #[value(name = "xros")]
+    VisionOs,
+    #[value(name = "xrsimulator")]
+    VisionOsSim,
+}
+
+impl Sdk {
+    pub(crate) fn is_macos(self) -> bool {
+        self == Self::MacOs
+    }
+
+    /// `CFBundleSupportedPlatforms` value
+    pub(crate) fn platform_name(self) -> &'static str {
+        match self {
+            Self::MacOs => "MacOSX",
+            Self::Ios => "iPhoneOS",
+            Self::IosSim => "iPhoneSimulator",
+            Self::TvOs => "AppleTVOS",
+            Self::TvOsSim => "AppleTVSimulator",
+            Self::WatchOs => "WatchOS",
+            Self::WatchOsSim => "WatchSimulator",
+            Self::VisionOs => "XROS",
+            Self::VisionOsSim => "XRSimulator",
+        }
+    }
+
+    /// `UIDeviceFamily` values, empty for macOS
+    fn device_family(self) -> &'static [i64] {
+        match self {
+            Self::MacOs => &[],
+            Self::Ios | Self::IosSim => &[1, 2],
+            Self::TvOs | Self::TvOsSim => &[3],
+            Self::WatchOs | Self::WatchOsSim => &[4],
+            Self::VisionOs | Self::VisionOsSim => &[7],
+        }
+    }
+
+    fn deployment_target(self, cfg: &Config) -> Option<&str> {
+        let d = &cfg.deployment;
+        match self {
+            Self::MacOs => d.macos.as_deref(),
+            Self::Ios | Self::IosSim => d.ios.as_deref(),
+            Self::TvOs | Self::TvOsSim => d.tvos.as_deref(),
+            Self::WatchOs | Self::WatchOsSim => d.watchos.as_deref(),
+            Self::VisionOs | Self::VisionOsSim => d.visionos.as_deref(),
+        }
+    }
+
+    /// Info.plist format Xcode produces for the sdk
+    pub(crate) fn plist_format(self) -> plist::Format {
+        if self.is_macos() {
+            plist::Format::Xml
+        } else {
+            plist::Format::Binary
+        }
+    }
+}
+
+/// Paths inside `.app` bundle.
+///
+/// macOS bundles keep everything in `Contents` with executable in `Contents/MacOS`,
+/// iOS, tvOS, watchOS and visionOS bundles are flat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Layout {
+    pub(crate) root: PathBuf,
+    pub(crate) sdk: Sdk,
+}
+
+impl Layout {
+    pub(crate) fn new(dir: &Path, product: &str, sdk: Sdk) -> Self {
+        Self {
+            root: dir.join(format!("{product}.app")),
+            sdk,
+        }
+    }
+
+    pub(crate) fn contents(&self) -> PathBuf {
+        if self.sdk.is_macos() {
+            self.root.join("Contents")
+        } else {
+            self.root.clone()
+        }
+    }
+
+    pub(crate) fn info_plist(&self) -> PathBuf {
+        self.contents().join("Info.plist")
+    }
+
+    pub(crate) fn pkg_info(&self) -> PathBuf {
+        self.contents().join("PkgInfo")
+    }
+
+    pub(crate) fn executable_dir(&self) -> PathBuf {
+        if self.sdk.is_macos() {
+            self.contents().join("MacOS")
+        } else {
+            self.root.clone()
+        }
+    }
+
+    pub(crate) fn resources_dir(&self) -> PathBuf {
+        if self.sdk.is_macos() {
+            self.contents().join("Resources")
+        } else {
+            self.root.clone()
+        }
+    }
+
+    pub(crate) fn frameworks_dir(&self) -> PathBuf {
+        self.contents().join("Frameworks")
+    }
+
+    /// macOS uses `embedded.provisionprofile` name for the same profile
+    pub(crate) fn provisioning_profile(&self) -> PathBuf {
+        if self.sdk.is_macos() {
+            self.contents().join("embedded.provisionprofile")
+        } else {
+            self.root.join("embedded.mobileprovision")
+        }
+    }
+
+    /// Entitlements are not part of the bundle, they are passed to `codesign --entitlements`
+    pub(crate) fn entitlements(&self) -> PathBuf {
+        self.root.with_extension("entitlements")
+    }
+}
+
+/// Info.plist keys Xcode generates for application target, `cfg.info` overrides them
+pub(crate) fn info(cfg: &Config, sdk: Sdk) -> BTreeMap<String, Value> {
+    let str = |s: &str| Value::String(s.to_string());
+    let mut res = BTreeMap::from([
+        ("CFBundleDevelopmentRegion".to_string(), str("en")),
+        ("CFBundleDisplayName".to_string(), str(&cfg.display_name)),
+        ("CFBundleExecutable".to_string(), str(&cfg.product)),
+        ("CFBundleIdentifier".to_string(), str(&cfg.bundle_id)),
+        ("CFBundleInfoDictionaryVersion".to_string(), str("6.0")),
+        ("CFBundleName".to_string(), str(&cfg.product)),
+        ("CFBundlePackageType".to_string(), str("APPL")),
+        ("CFBundleShortVersionString".to_string(), str(&cfg.version)),
+        (
+            "CFBundleSupportedPlatforms".to_string(),
+            Value::Array(vec![str(sdk.platform_name())]),
+        ),
+        ("CFBundleVersion".to_string(), str(&cfg.build)),
+    ]);
+    if let Some(target) = sdk.deployment_target(cfg) {
+        let key = if sdk.is_macos() {
+            "LSMinimumSystemVersion"
+        } else {
+            "MinimumOSVersion"
+        };
+        res.insert(key.to_string(), str(target));
+    }
+    if !sdk.is_macos() {
+        let family = sdk.device_family().iter().map(|&f| Value::Integer(f));
+        res.insert("UIDeviceFamily".to_string(), Value::Array(family.collect()));
+        res.insert(
+            "UILaunchScreen".to_string(),
+            Value::Table(Default::default()),
+        );
+    }
+    if matches!(sdk, Sdk::Ios | Sdk::IosSim) {
+        res.insert("LSRequiresIPhoneOS".to_string(), Value::Boolean(true));
+    }
+    res.extend(cfg.info.clone());
+    res
+}
+
+fn expand(value: &mut Value, vars: &[(&str, &str)]) {
+    match value {
+        Value::String(s) => {
+            for (var, val) in vars {
+                *s = s.replace(var, val);
+            }
+        }
+        Value::Array(arr) => arr.iter_mut().for_each(|v| expand(v, vars)),
+        Value::Table(table) => table.iter_mut().for_each(|(_, v)| expand(v, vars)),
+        _ => {}
+    }
+}
+
+/// `cfg.entitlements` with `$(AppIdentifierPrefix)` and `$(TeamIdentifierPrefix)`
+/// expanded as Xcode does, from `profile` app id or `cfg.team`
+pub(crate) fn entitlements(cfg: &Config, profile: Option<&Profile>) -> BTreeMap<String, Value> {
+    let app_prefix = profile
+        .and_then(|p| p.app_id.split_once('.'))
+        .map_or(cfg.team.as_str(), |(prefix, _)| prefix);
+    let team = profile.map_or(cfg.team.as_str(), |p| p.team_id.as_str());
+    let app_prefix = format!("{app_prefix}.");
+    let team_prefix = format!("{team}.");
+    let vars = [
+        ("$(AppIdentifierPrefix)", app_prefix.as_str()),
+        ("$(TeamIdentifierPrefix)", team_prefix.as_str()),
+    ];
+    let mut res = cfg.entitlements.clone();
+    res.values_mut().for_each(|v| expand(v, &vars));
+    res
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let dst = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dst)?;
+        } else {
+            fs::copy(entry.path(), dst)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies file or folder into `dir` keeping its name, like Xcode folder references
+fn copy_into(src: &Path, dir: &Path) -> io::Result<()> {
+    let Some(name) = src.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid path {}", src.display()),
+        ));
+    };
+    let dst = dir.join(name);
+    if src.is_dir() {
+        copy_dir(src, &dst)
+    } else {
+        fs::copy(src, dst).map(|_| ())
+    }
+}
+
+/// Unsigned `.app` bundle ready for `codesign`
+pub(crate) struct Bundle<'a> {
+    pub(crate) cfg: &'a Config,
+    pub(crate) sdk: Sdk,
+    /// Cargo built executable
+    pub(crate) binary: &'a Path,
+    pub(crate) profile: Option<&'a Path>,
+    pub(crate) plist_format: plist::Format,
+}
+
+impl<'a> Bundle<'a> {
+    pub(crate) fn new(cfg: &'a Config, sdk: Sdk, binary: &'a Path) -> Self {
+        Self {
+            cfg,
+            sdk,
+            binary,
+            profile: None,
+            plist_format: sdk.plist_format(),
+        }
+    }
+
+    /// Assembles `{product}.app` in `dir` replacing previous one
+    pub(crate) fn assemble(&self, dir: &Path) -> io::Result<Layout> {
+        let layout = Layout::new(dir, &self.cfg.product, self.sdk);
+        if layout.root.exists() {
+            fs::remove_dir_all(&layout.root)?;
+        }
+
+        let exe_dir = layout.executable_dir();
+        fs::create_dir_all(&exe_dir)?;
+        fs::copy(self.binary, exe_dir.join(&self.cfg.product))?;
+
+        let info = info(self.cfg, self.sdk);
+        fs::write(layout.info_plist(), plist::write(&info, self.plist_format))?;
+        fs::write(layout.pkg_info(), "APPL????")?;
+
+        if !self.cfg.resources.is_empty() {
+            let resources = layout.resources_dir();
+            fs::create_dir_all(&resources)?;
+            for src in &self.cfg.resources {
+                copy_into(src, &resources)?;
+            }
+        }
+
+        if !self.cfg.frameworks.is_empty() {
+            let frameworks = layout.frameworks_dir();
+            fs::create_dir_all(&frameworks)?;
+            for src in &self.cfg.frameworks {
+                copy_into(src, &frameworks)?;
+            }
+        }
+
+        let profile = match self.profile {
+            Some(path) => {
+                fs::copy(path, layout.provisioning_profile())?;
+                Some(Profile::from_path(path).map_err(io::Error::other)?)
+            }
+            None => None,
+        };
+
+        let entitlements = entitlements(self.cfg, profile.as_ref());
+        fs::write(layout.entitlements(), plist::to_xml(&entitlements))?;
+
+        Ok(layout)
+    }
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct Args {
+    #[command(flatten)]
+    pub(crate) product: xcode::ProjArgs,
+
+    /// Cargo built executable of the product
+    #[arg(long)]
+    pub(crate) binary: PathBuf,
+
+    #[arg(long, value_enum, default_value = "macosx")]
+    pub(crate) sdk: Sdk,
+
+    /// Copied as embedded.mobileprovision (embedded.provisionprofile on macOS),
+    /// its app id prefix is used in entitlements
+    #[arg(long)]
+    pub(crate) profile: Option<PathBuf>,
+
+    /// Info.plist format, binary for devices and xml for macOS by default
+    #[arg(long, value_enum)]
+    pub(crate) plist: Option<plist::Format>,
+
+    /// Output folder, target/boxes/{product}/{sdk} by default
+    #[arg(long)]
+    pub(crate) out: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: Args) {
+    let Some((dir, cfg)) = xcode::config(&args.product) else {
+        std::process::exit(1);
+    };
+    let out = args.out.unwrap_or_else(|| {
+        let sdk = args.sdk.to_possible_value().unwrap();
+        dir.join(sdk.get_name())
+    });
+
+    let mut bundle = Bundle::new(&cfg, args.sdk, &args.binary);
+    bundle.profile = args.profile.as_deref();
+    if let Some(format) = args.plist {
+        bundle.plist_format = format;
+    }
+    match bundle.assemble(&out) {
+        Ok(layout) => println!("{}", layout.root.display()),
+        Err(err) => {
+            println!("can't assemble bundle: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use cargo_toml::Value;
+
+    use super::{Bundle, Layout, Sdk};
+    use crate::{
+        meta::{Config, Deployment, fixture},
+        profile::Profile,
+    };
+
+    const PROFILE: &[u8] = include_bytes!("../box/fixtures/box.mobileprovision");
+
+    /// Shared config for iOS only, with resources and no frameworks under `dir`
+    fn config(dir: &Path) -> Config {
+        Config {
+            deployment: Deployment {
+                ios: Some("17.0".to_string()),
+                ..Default::default()
+            },
+            resources: vec![dir.join("assets")],
+            frameworks: vec![],
+            ..fixture::config()
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let mac = Layout::new(Path::new("out"), "cam", Sdk::MacOs);
+        assert_eq!(
+            mac.info_plist(),
+            Path::new("out/cam.app/Contents/Info.plist")
+        );
+        assert_eq!(
+            mac.executable_dir(),
+            Path::new("out/cam.app/Contents/MacOS")
+        );
+        assert_eq!(
+            mac.resources_dir(),
+            Path::new("out/cam.app/Contents/Resources")
+        );
+        assert_eq!(
+            mac.provisioning_profile(),
+            Path::new("out/cam.app/Contents/embedded.provisionprofile")
+        );
+        assert_eq!(mac.entitlements(), Path::new("out/cam.entitlements"));
+
+        let ios = Layout::new(Path::new("out"), "cam", Sdk::Ios);
+        assert_eq!(ios.info_plist(), Path::new("out/cam.app/Info.plist"));
+        assert_eq!(ios.executable_dir(), Path::new("out/cam.app"));
+        assert_eq!(ios.frameworks_dir(), Path::new("out/cam.app/Frameworks"));
+        assert_eq!(
+            ios.provisioning_profile(),
+            Path::new("out/cam.app/embedded.mobileprovision")
+        );
+    }
+
+    #[test]
+    fn info() {
+        let cfg = config(Path::new("."));
+        let ios = super::info(&cfg, Sdk::Ios);
+        assert_eq!(ios["CFBundleExecutable"].as_str(), Some("cam"));
+        assert_eq!(ios["MinimumOSVersion"].as_str(), Some("17.0"));
+        assert_eq!(ios["LSRequiresIPhoneOS"].as_bool(), Some(true));
+        assert!(ios.contains_key("NSCameraUsageDescription"));
+
+        let mac = super::info(&cfg, Sdk::MacOs);
+        assert!(!mac.contains_key("UIDeviceFamily"));
+        assert!(!mac.contains_key("LSMinimumSystemVersion"));
+    }
+
+    #[test]
+    fn entitlements() {
+        let mut cfg = config(Path::new("."));
+        cfg.team = "XYZ".to_string();
+        cfg.entitlements.insert(
+            "keychain-access-groups".to_string(),
+            Value::Array(vec![Value::String(
+                "$(AppIdentifierPrefix)org.cidre.cam".to_string(),
+            )]),
+        );
+        cfg.entitlements.insert(
+            "com.apple.developer.ubiquity-kvstore-identifier".to_string(),
+            Value::String("$(TeamIdentifierPrefix)org.cidre.cam".to_string()),
+        );
+        let res = super::entitlements(&cfg, None);
+        assert_eq!(
+            res["keychain-access-groups"][0].as_str(),
+            Some("XYZ.org.cidre.cam")
+        );
+
+        let profile = Profile::parse(PROFILE).unwrap();
+        let res = super::entitlements(&cfg, Some(&profile));
+        assert_eq!(
+            res["keychain-access-groups"][0].as_str(),
+            Some("ABCDE12345.org.cidre.cam")
+        );
+        assert_eq!(
+            res["com.apple.developer.ubiquity-kvstore-identifier"].as_str(),
+            Some("ABCDE12345.org.cidre.cam")
+        );
+        assert_eq!(
+            res["com.apple.security.device.camera"].as_bool(),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn assemble() {
+        let dir = std::env::temp_dir().join(format!("cargo-box-bundle-{}", std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("assets/nested")).unwrap();
+        fs::write(dir.join("assets/nested/a.txt"), "a").unwrap();
+        fs::write(dir.join("cam"), "bin").unwrap();
+        fs::write(dir.join("profile.mobileprovision"), PROFILE).unwrap();
+
+        let cfg = config(&dir);
+        let binary = dir.join("cam");
+        let profile = dir.join("profile.mobileprovision");
+        let out = dir.join("out");
+
+        let mut bundle = Bundle::new(&cfg, Sdk::Ios, &binary);
+        bundle.profile = Some(&profile);
+        let ios = bundle.assemble(&out).unwrap();
+        assert_eq!(fs::read(ios.root.join("cam")).unwrap(), b"bin");
+        assert!(fs::read(ios.info_plist()).unwrap().starts_with(b"bplist00"));
+        assert!(ios.root.join("assets/nested/a.txt").exists());
+        assert!(ios.provisioning_profile().exists());
+        assert!(ios.entitlements().exists());
+
+        let mac = Bundle::new(&cfg, Sdk::MacOs, &binary)
+            .assemble(&out)
+            .unwrap();
+        assert!(mac.executable_dir().join("cam").exists());
+        assert!(fs::read(mac.info_plist()).unwrap().starts_with(b"<?xml"));
+        assert_eq!(fs::read(mac.pkg_info()).unwrap(), b"APPL????");
+        assert!(mac.resources_dir().join("assets/nested/a.txt").exists());
+        assert!(!mac.provisioning_profile().exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
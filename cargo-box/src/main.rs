@@ -1,6 +1,12 @@
 use clap::{Parser, Subcommand};
 use std::env;
 
+mod bundle;
+mod meta;
+mod pbxproj;
+mod plist;
+mod profile;
+
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Cli {
@@ -15,9 +21,14 @@ enum Cmd {
     Runner(runner::Args),
 
     /// List dev teams on this mac
+    #[cfg(target_vendor = "apple")]
     #[command()]
     Teams,
 
+    /// List provisioning profiles and check them against .box config
+    #[command()]
+    Profiles(profile::Args),
+
     /// List connected devices on this mac
     #[command()]
     Devices,
@@ -26,6 +37,10 @@ enum Cmd {
     /// in target/boxes for runner.
     #[command()]
     Proj(xcode::ProjArgs),
+
+    /// Assemble unsigned .app bundle from cargo built binary without xcode
+    #[command()]
+    Bundle(bundle::Args),
 }
 
 fn main() {
@@ -44,9 +59,14 @@ fn main() {
     }
 
     match Cli::parse_from(args).cmd {
+        #[cfg(target_vendor = "apple")]
         Cmd::Teams => teams::list(),
+        Cmd::Profiles(args) => profile::run(args),
         Cmd::Devices => device_ctl::list_devices(),
-        Cmd::Proj(args) => xcode::proj(args),
+        Cmd::Proj(args) => {
+            xcode::proj(args);
+        }
+        Cmd::Bundle(args) => bundle::run(args),
         _ => panic!("unknown command"),
     }
 }
@@ -216,7 +236,9 @@ mod runner {
             proj_args.dep = Some(name.clone());
         }
 
-        xcode::proj(proj_args);
+        let Some(cfg) = xcode::proj(proj_args) else {
+            std::process::exit(1);
+        };
 
         let mut project = PathBuf::from("./target/boxes");
         if is_example {
@@ -251,14 +273,14 @@ mod runner {
         target.push(&format!("{config}-{sdk}"));
         target.push(&format!("{name}.app"));
 
-        let box_org_id = std::env::var("BOX_ORG_ID").unwrap();
         let device_id = std::env::var("DEVICE_ID").unwrap();
 
         device_ctl::install_app(&device_id, target.to_str().unwrap());
-        device_ctl::run_app(&device_id, &format!("{box_org_id}.{name}"), &args.args[3..]);
+        device_ctl::run_app(&device_id, &cfg.bundle_id, &args.args[3..]);
     }
 }
 
+#[cfg(target_vendor = "apple")]
 mod teams {
     use cidre::{arc, cf, sec};
 
@@ -344,28 +366,30 @@ mod cargo {
     use cargo_toml::{Manifest, Workspace};
     use std::{env, path::PathBuf};
 
-    pub(crate) fn manifests() -> Option<(PathBuf, Vec<Manifest>, Option<Workspace>)> {
-        let mut path = root()?.join("Cargo.toml");
-        if let Ok(mut res) = Manifest::from_path(&path) {
-            path.pop();
-            res.complete_from_path(&path).unwrap();
-            if let Some(ws) = res.workspace {
-                let mut vec = Vec::with_capacity(ws.members.len());
-                for member in ws.members.iter() {
-                    path = path.join(&format!("{member}/Cargo.toml"));
-                    let mut man = Manifest::from_path(&path).unwrap();
-                    path.pop();
-                    man.complete_from_path(&path).unwrap();
-                    path.pop();
-                    vec.push(man);
-                }
-                Some((path, vec, Some(ws)))
-            } else {
-                Some((path, vec![res], None))
-            }
-        } else {
-            None
+    /// Package dir with its manifest
+    pub(crate) type Package = (PathBuf, Manifest);
+
+    /// Workspace root, packages and workspace if any
+    pub(crate) fn manifests() -> Option<(PathBuf, Vec<Package>, Option<Workspace>)> {
+        let root = root()?;
+        let path = root.join("Cargo.toml");
+        let mut res = Manifest::from_path(&path).ok()?;
+        res.complete_from_path(&path).unwrap();
+        let Some(ws) = res.workspace.take() else {
+            return Some((root.clone(), vec![(root, res)], None));
+        };
+        let mut vec = Vec::with_capacity(ws.members.len() + 1);
+        for member in ws.members.iter() {
+            let dir = root.join(member);
+            let path = dir.join("Cargo.toml");
+            let mut man = Manifest::from_path(&path).unwrap();
+            man.complete_from_path(&path).unwrap();
+            vec.push((dir, man));
+        }
+        if res.package.is_some() {
+            vec.push((root.clone(), res));
         }
+        Some((root, vec, Some(ws)))
     }
 
     fn root() -> Option<PathBuf> {
@@ -389,11 +413,14 @@ mod cargo {
 }
 
 mod xcode {
-    use std::fs;
+    use std::{fs, path::PathBuf};
 
     use cargo_toml::{Manifest, Product};
 
-    use crate::cargo;
+    use crate::{
+        cargo::{self, Package},
+        meta, pbxproj,
+    };
 
     pub(crate) fn build(project: &str, platform: &str, conf: &str, target: &str) {
         std::process::Command::new("xcodebuild")
@@ -418,9 +445,9 @@ mod xcode {
     }
 
     fn find_product<'a>(
-        mans: &'a [Manifest],
+        mans: &'a [Package],
         args: &ProjArgs,
-    ) -> Option<(&'a Manifest, &'a Product)> {
+    ) -> Option<(&'a Package, &'a Product)> {
         let (is_bin, name, nothing, not_found, available) = if args.example.is_some() {
             (
                 false,
@@ -439,20 +466,15 @@ mod xcode {
             )
         };
 
-        let products = |man: &'a Manifest| -> &'a [Product] {
-            if is_bin {
-                &man.bin
-            } else {
-                &man.example
-            }
-        };
+        let products =
+            |man: &'a Manifest| -> &'a [Product] { if is_bin { &man.bin } else { &man.example } };
 
         if let Some(product_name) = name.as_ref() {
             let mut count = 0;
-            for man in mans {
-                for product in products(man) {
+            for package in mans {
+                for product in products(&package.1) {
                     if product.name.as_ref() == name {
-                        return Some((man, product));
+                        return Some((package, product));
                     }
                     count += 1;
                 }
@@ -462,7 +484,7 @@ mod xcode {
             } else {
                 println!("{not_found} `{product_name}`");
                 println!("{available}");
-                for man in mans {
+                for (_, man) in mans {
                     for product in products(man) {
                         if let Some(name) = product.name.as_ref() {
                             println!("\t{name}");
@@ -474,10 +496,10 @@ mod xcode {
             return None;
         }
 
-        for man in mans {
-            for product in man.bin.iter() {
+        for package in mans {
+            for product in package.1.bin.iter() {
                 if product.name.is_some() {
-                    return Some((man, product));
+                    return Some((package, product));
                 }
             }
         }
@@ -487,42 +509,46 @@ mod xcode {
         None
     }
 
-    pub(crate) fn proj(args: ProjArgs) {
-        let (mut path, mans, _ws) = cargo::manifests().unwrap();
-        let product = if args.dep.is_some() {
-            &Product {
-                path: None,
-                name: args.dep.clone(),
-                test: false,
-                doctest: false,
-                bench: false,
-                doc: false,
-                plugin: false,
-                proc_macro: false,
-                harness: true,
-                edition: None,
-                crate_type: vec![],
-                required_features: vec![],
-            }
+    /// Package of test or bench, dep names use `_` instead of `-`
+    fn find_dep<'a>(mans: &'a [Package], name: &str) -> Option<&'a Package> {
+        mans.iter()
+            .find(|(_, man)| {
+                man.package
+                    .as_ref()
+                    .is_some_and(|p| p.name.replace('-', "_") == name)
+            })
+            .or_else(|| mans.first())
+    }
+
+    /// Resolves product config and its folder in target/boxes
+    pub(crate) fn config(args: &ProjArgs) -> Option<(PathBuf, meta::Config)> {
+        let (mut path, mans, ws) = cargo::manifests().unwrap();
+        let ((dir, man), product_name) = if let Some(dep) = args.dep.as_ref() {
+            (find_dep(&mans, dep)?, dep.clone())
         } else {
-            let Some((_man, product)) = find_product(&mans, &args) else {
-                return;
-            };
-            product
+            let (package, product) = find_product(&mans, args)?;
+            (package, product.name.clone()?)
         };
 
         path.push(".box");
-        _ = dotenv::from_filename(".box");
+        _ = dotenv::from_filename(&path);
         path.pop();
 
-        let Ok(box_org_id) = std::env::var("BOX_ORG_ID") else {
-            println!("BOX_ORG_ID env is required. You can add it .box file");
-            return;
-        };
-        let Ok(dev_team_id) = std::env::var("DEVELOPMENT_TEAM") else {
-            println!("DEVELOPMENT_TEAM env is required. You can add it .box file");
-            println!("use cidre-box teams command to list available team ids");
-            return;
+        let package_version = man.package.as_ref().and_then(|p| p.version.get().ok());
+        let cfg = meta::Settings::from_manifest(man, ws.as_ref()).and_then(|settings| {
+            settings.resolve(
+                &product_name,
+                package_version.map(String::as_str),
+                dir,
+                |key| std::env::var(key).ok(),
+            )
+        });
+        let cfg = match cfg {
+            Ok(cfg) => cfg,
+            Err(err) => {
+                println!("{err}");
+                return None;
+            }
         };
 
         path.push("target/boxes");
@@ -532,45 +558,31 @@ mod xcode {
         if args.dep.is_some() {
             path.push("deps")
         }
-        let product_name = product.name.as_ref().unwrap();
-        path.push(product_name);
+        path.push(&product_name);
+        Some((path, cfg))
+    }
 
-        fs::create_dir_all(&path).unwrap();
-        path.push("box.entitlements");
-        fs::write(&path, include_str!("../box/box.entitlements")).unwrap();
-        path.pop();
-        path.push("cfg.xcconfig");
-        fs::write(&path, include_str!("../box/cfg.xcconfig")).unwrap();
-        path.pop();
-        path.push("box.xcconfig");
-        fs::write(
-            &path,
-            format!(
-                r#"
-PRODUCT_NAME = {product_name}
-BOX_ID = {product_name}
-DEVELOPMENT_TEAM = {dev_team_id}
-BOX_ORG_ID = {box_org_id}
-"#
-            ),
-        )
-        .unwrap();
+    /// Generates xcode project in target/boxes and returns resolved product config
+    pub(crate) fn proj(args: ProjArgs) -> Option<meta::Config> {
+        let (mut path, cfg) = config(&args)?;
+        let project = pbxproj::Project::new(&cfg);
 
-        path.pop();
+        fs::create_dir_all(&path).unwrap();
+        fs::write(path.join("box.entitlements"), &project.entitlements).unwrap();
+        let info_plist = path.join("Info.plist");
+        match &project.info_plist {
+            Some(info) => fs::write(&info_plist, info).unwrap(),
+            None => _ = fs::remove_file(&info_plist),
+        }
 
         path.push("box.xcodeproj");
         fs::create_dir_all(&path).unwrap();
-        path.push("project.pbxproj");
-        fs::write(&path, include_str!("../box/box.xcodeproj/project.pbxproj")).unwrap();
-        path.pop();
+        fs::write(path.join("project.pbxproj"), project.pbxproj.to_string()).unwrap();
         path.push("xcshareddata/xcschemes");
         fs::create_dir_all(&path).unwrap();
-        path.push("box.xcscheme");
-        fs::write(
-            &path,
-            include_str!("../box/box.xcodeproj/xcshareddata/xcschemes/box.xcscheme"),
-        )
-        .unwrap();
+        fs::write(path.join("box.xcscheme"), pbxproj::scheme(&cfg)).unwrap();
+
+        Some(cfg)
     }
 }
 
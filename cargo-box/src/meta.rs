@@ -0,0 +1,344 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use cargo_toml::{Manifest, Value, Workspace};
+use serde::Deserialize;
+
+/// `[package.metadata.box]` or `[workspace.metadata.box]`
+///
+/// ```toml
+/// [package.metadata.box]
+/// org-id = "org.cidre"
+/// deployment = { ios = "17.0", macos = "14.0" }
+/// resources = ["assets"]
+///
+/// [package.metadata.box.info]
+/// NSCameraUsageDescription = "Testing Camera"
+///
+/// [package.metadata.box.products.cam]
+/// display-name = "Camera"
+/// ```
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Settings {
+    /// `BOX_ORG_ID` from `.box` is used if not set
+    pub(crate) org_id: Option<String>,
+    /// `DEVELOPMENT_TEAM` from `.box` is used if not set
+    pub(crate) team: Option<String>,
+    /// `{org-id}.{product}` by default
+    pub(crate) bundle_id: Option<String>,
+    pub(crate) display_name: Option<String>,
+    /// `CFBundleShortVersionString`, package version by default
+    pub(crate) version: Option<String>,
+    /// `CFBundleVersion`, `1` by default
+    pub(crate) build: Option<String>,
+    #[serde(default)]
+    pub(crate) deployment: Deployment,
+    /// Extra Info.plist keys, `NSCameraUsageDescription` is set by default
+    #[serde(default)]
+    pub(crate) info: BTreeMap<String, Value>,
+    /// Sandboxed app with user selected read-only files by default
+    pub(crate) entitlements: Option<BTreeMap<String, Value>>,
+    /// Folders copied to bundle as is, relative to the package
+    #[serde(default)]
+    pub(crate) resources: Vec<PathBuf>,
+    /// Frameworks to embed, relative to the package
+    #[serde(default)]
+    pub(crate) frameworks: Vec<PathBuf>,
+    /// Per binary, example or test overrides
+    #[serde(default)]
+    pub(crate) products: BTreeMap<String, Settings>,
+}
+
+/// Deployment targets
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Deployment {
+    pub(crate) macos: Option<String>,
+    pub(crate) ios: Option<String>,
+    pub(crate) tvos: Option<String>,
+    pub(crate) watchos: Option<String>,
+    pub(crate) visionos: Option<String>,
+}
+
+impl Deployment {
+    fn merge(self, base: Self) -> Self {
+        Self {
+            macos: self.macos.or(base.macos),
+            ios: self.ios.or(base.ios),
+            tvos: self.tvos.or(base.tvos),
+            watchos: self.watchos.or(base.watchos),
+            visionos: self.visionos.or(base.visionos),
+        }
+    }
+
+    /// (build setting, version) pairs
+    pub(crate) fn build_settings(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("MACOSX_DEPLOYMENT_TARGET", &self.macos),
+            ("IPHONEOS_DEPLOYMENT_TARGET", &self.ios),
+            ("TVOS_DEPLOYMENT_TARGET", &self.tvos),
+            ("WATCHOS_DEPLOYMENT_TARGET", &self.watchos),
+            ("XROS_DEPLOYMENT_TARGET", &self.visionos),
+        ]
+        .into_iter()
+        .filter_map(|(key, v)| v.as_deref().map(|v| (key, v)))
+    }
+}
+
+impl Settings {
+    fn from_metadata(metadata: Option<&Value>) -> Result<Self, String> {
+        let Some(value) = metadata.and_then(|m| m.get("box")) else {
+            return Ok(Self::default());
+        };
+        value
+            .clone()
+            .try_into()
+            .map_err(|e| format!("invalid [metadata.box]: {e}"))
+    }
+
+    /// Settings from workspace, overridden by package ones
+    pub(crate) fn from_manifest(man: &Manifest, ws: Option<&Workspace>) -> Result<Self, String> {
+        let ws = ws.or(man.workspace.as_ref());
+        let ws = Self::from_metadata(ws.and_then(|ws| ws.metadata.as_ref()))?;
+        let package = Self::from_metadata(man.package.as_ref().and_then(|p| p.metadata.as_ref()))?;
+        Ok(package.merge(ws))
+    }
+
+    /// Fields of `self` take precedence, lists are concatenated
+    pub(crate) fn merge(self, base: Self) -> Self {
+        let mut info = base.info;
+        info.extend(self.info);
+        let entitlements = match (base.entitlements, self.entitlements) {
+            (Some(mut base), Some(ents)) => {
+                base.extend(ents);
+                Some(base)
+            }
+            (base, ents) => ents.or(base),
+        };
+        let mut products = base.products;
+        products.extend(self.products);
+        Self {
+            org_id: self.org_id.or(base.org_id),
+            team: self.team.or(base.team),
+            bundle_id: self.bundle_id.or(base.bundle_id),
+            display_name: self.display_name.or(base.display_name),
+            version: self.version.or(base.version),
+            build: self.build.or(base.build),
+            deployment: self.deployment.merge(base.deployment),
+            info,
+            entitlements,
+            resources: base.resources.into_iter().chain(self.resources).collect(),
+            frameworks: base.frameworks.into_iter().chain(self.frameworks).collect(),
+            products,
+        }
+    }
+
+    /// Resolves settings for the product.
+    ///
+    /// `env` is used for `BOX_ORG_ID` and `DEVELOPMENT_TEAM` fallbacks from `.box` file,
+    /// relative paths are resolved against `dir`.
+    pub(crate) fn resolve(
+        mut self,
+        product: &str,
+        package_version: Option<&str>,
+        dir: &Path,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Config, String> {
+        let settings = match self.products.remove(product) {
+            Some(overrides) => overrides.merge(self),
+            None => self,
+        };
+
+        let team = settings.team.or_else(|| env("DEVELOPMENT_TEAM")).ok_or(
+            "DEVELOPMENT_TEAM env or `team` metadata is required. You can add it .box file\n\
+             use cargo box teams command to list available team ids",
+        )?;
+
+        let bundle_id = match settings.bundle_id {
+            Some(id) => id,
+            None => {
+                let org_id = settings.org_id.or_else(|| env("BOX_ORG_ID")).ok_or(
+                    "BOX_ORG_ID env or `org-id` metadata is required. You can add it .box file",
+                )?;
+                format!("{org_id}.{product}")
+            }
+        };
+
+        let entitlements = settings.entitlements.unwrap_or_else(|| {
+            BTreeMap::from([
+                (
+                    "com.apple.security.app-sandbox".to_string(),
+                    Value::Boolean(true),
+                ),
+                (
+                    "com.apple.security.files.user-selected.read-only".to_string(),
+                    Value::Boolean(true),
+                ),
+            ])
+        });
+
+        // default of the former `cfg.xcconfig`, so boxes can use camera without metadata
+        let mut info = BTreeMap::from([(
+            "NSCameraUsageDescription".to_string(),
+            Value::String("Testing Camera".to_string()),
+        )]);
+        info.extend(settings.info);
+
+        Ok(Config {
+            product: product.to_string(),
+            display_name: settings.display_name.unwrap_or_else(|| product.to_string()),
+            bundle_id,
+            team,
+            version: settings
+                .version
+                .or_else(|| package_version.map(ToString::to_string))
+                .unwrap_or_else(|| "1.0.0".to_string()),
+            build: settings.build.unwrap_or_else(|| "1".to_string()),
+            deployment: settings.deployment,
+            info,
+            entitlements,
+            resources: settings.resources.iter().map(|p| dir.join(p)).collect(),
+            frameworks: settings.frameworks.iter().map(|p| dir.join(p)).collect(),
+        })
+    }
+}
+
+/// Resolved settings of the product
+#[derive(Debug, Clone)]
+pub(crate) struct Config {
+    /// Cargo binary, example or test name
+    pub(crate) product: String,
+    pub(crate) display_name: String,
+    pub(crate) bundle_id: String,
+    pub(crate) team: String,
+    pub(crate) version: String,
+    pub(crate) build: String,
+    pub(crate) deployment: Deployment,
+    pub(crate) info: BTreeMap<String, Value>,
+    pub(crate) entitlements: BTreeMap<String, Value>,
+    pub(crate) resources: Vec<PathBuf>,
+    pub(crate) frameworks: Vec<PathBuf>,
+}
+
+/// `cam` product shared by bundle, project and profile tests
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use cargo_toml::Value;
+
+    use super::{Config, Deployment};
+
+    pub(crate) fn config() -> Config {
+        Config {
+            product: "cam".to_string(),
+            display_name: "Camera Test".to_string(),
+            bundle_id: "org.cidre.cam".to_string(),
+            team: "ABCDE12345".to_string(),
+            version: "0.1.0".to_string(),
+            build: "1".to_string(),
+            deployment: Deployment {
+                ios: Some("17.0".to_string()),
+                macos: Some("14.0".to_string()),
+                ..Default::default()
+            },
+            info: BTreeMap::from([(
+                "NSCameraUsageDescription".to_string(),
+                Value::String("Testing \"Camera\"".to_string()),
+            )]),
+            entitlements: BTreeMap::from([(
+                "com.apple.security.device.camera".to_string(),
+                Value::Boolean(true),
+            )]),
+            resources: vec![PathBuf::from("/src/cam/assets")],
+            frameworks: vec![PathBuf::from("/src/cam/libs/Foo.framework")],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use cargo_toml::Manifest;
+
+    use super::Settings;
+
+    const TOML: &str = r#"
+[package]
+name = "app"
+version = "0.3.0"
+
+[package.metadata.box]
+org-id = "org.cidre"
+team = "ABCDE12345"
+deployment = { ios = "17.0", macos = "14.0" }
+resources = ["assets"]
+
+[package.metadata.box.info]
+NSCameraUsageDescription = "Testing Camera"
+
+[package.metadata.box.products.cam_test]
+display-name = "Camera"
+deployment = { ios = "18.0" }
+resources = ["cam"]
+entitlements = { "com.apple.security.device.camera" = true }
+
+[package.metadata.box.products.cam_test.info]
+NSMicrophoneUsageDescription = "Testing Mic"
+"#;
+
+    #[test]
+    fn resolve() {
+        let man = Manifest::from_slice(TOML.as_bytes()).unwrap();
+        let settings = Settings::from_manifest(&man, None).unwrap();
+        let no_env = |_: &str| None;
+
+        let cfg = settings
+            .clone()
+            .resolve("app", Some("0.3.0"), Path::new("/p"), no_env)
+            .unwrap();
+        assert_eq!(cfg.bundle_id, "org.cidre.app");
+        assert_eq!(cfg.display_name, "app");
+        assert_eq!(cfg.version, "0.3.0");
+        assert_eq!(cfg.deployment.ios.as_deref(), Some("17.0"));
+        assert_eq!(cfg.resources, [Path::new("/p/assets")]);
+        assert_eq!(cfg.info.len(), 1);
+        assert_eq!(cfg.entitlements.len(), 2);
+
+        let cfg = settings
+            .resolve("cam_test", None, Path::new("/p"), no_env)
+            .unwrap();
+        assert_eq!(cfg.bundle_id, "org.cidre.cam_test");
+        assert_eq!(cfg.display_name, "Camera");
+        assert_eq!(cfg.version, "1.0.0");
+        assert_eq!(cfg.deployment.ios.as_deref(), Some("18.0"));
+        assert_eq!(cfg.deployment.macos.as_deref(), Some("14.0"));
+        assert_eq!(cfg.resources.len(), 2);
+        assert_eq!(cfg.info.len(), 2);
+        assert_eq!(cfg.entitlements.len(), 1);
+    }
+
+    #[test]
+    fn env_fallback() {
+        let cfg = Settings::default()
+            .resolve("app", None, Path::new("."), |key| {
+                Some(format!("{key}-value"))
+            })
+            .unwrap();
+        assert_eq!(cfg.team, "DEVELOPMENT_TEAM-value");
+        assert_eq!(cfg.bundle_id, "BOX_ORG_ID-value.app");
+        assert_eq!(
+            cfg.info["NSCameraUsageDescription"].as_str(),
+            Some("Testing Camera")
+        );
+
+        let err = Settings::default()
+            .resolve("app", None, Path::new("."), |_| None)
+            .unwrap_err();
+        assert!(err.starts_with("DEVELOPMENT_TEAM"));
+    }
+}
@@ -0,0 +1,633 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    path::Path,
+};
+
+use crate::{meta::Config, plist};
+
+/// Name of the target and the scheme
+pub(crate) const TARGET: &str = "box";
+
+/// 96-bit object id.
+///
+/// Ids are derived from stable names of objects, so generated project is the same
+/// on every machine and can be diffed and golden-tested.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub(crate) struct Id(u64, u32);
+
+impl Id {
+    pub(crate) fn new(seed: &str) -> Self {
+        // FNV-1a
+        let hash = |init: u64| {
+            seed.bytes().fold(init, |h, b| {
+                (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            })
+        };
+        let hi = hash(0xcbf2_9ce4_8422_2325);
+        Self(hi, (hash(hi) >> 16) as u32)
+    }
+
+    pub(crate) fn target() -> Self {
+        Self::new("target")
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}{:08X}", self.0, self.1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    Str(String),
+    Ref(Id),
+    Array(Vec<Value>),
+    Dict(BTreeMap<String, Value>),
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<Id> for Value {
+    fn from(value: Id) -> Self {
+        Self::Ref(value)
+    }
+}
+
+impl From<Vec<Id>> for Value {
+    fn from(value: Vec<Id>) -> Self {
+        Self::Array(value.into_iter().map(Value::Ref).collect())
+    }
+}
+
+fn dict<const N: usize>(entries: [(&str, Value); N]) -> BTreeMap<String, Value> {
+    entries
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect()
+}
+
+#[derive(Debug)]
+struct Object {
+    isa: &'static str,
+    /// `/* comment */` after id and references
+    comment: String,
+    props: BTreeMap<String, Value>,
+}
+
+impl Object {
+    /// Xcode writes these objects in one line
+    fn is_compact(&self) -> bool {
+        matches!(self.isa, "PBXBuildFile" | "PBXFileReference")
+    }
+}
+
+/// `project.pbxproj` in old-style (OpenStep) plist format
+#[derive(Debug)]
+pub(crate) struct Pbxproj {
+    objects: BTreeMap<Id, Object>,
+    root: Id,
+}
+
+impl Pbxproj {
+    fn add(
+        &mut self,
+        seed: &str,
+        isa: &'static str,
+        comment: impl Into<String>,
+        props: BTreeMap<String, Value>,
+    ) -> Id {
+        let id = Id::new(seed);
+        let prev = self.objects.insert(
+            id,
+            Object {
+                isa,
+                comment: comment.into(),
+                props,
+            },
+        );
+        assert!(prev.is_none(), "duplicated pbxproj object: {seed}");
+        id
+    }
+
+    fn write_value(&self, buf: &mut String, value: &Value, level: usize, compact: bool) {
+        match value {
+            Value::Str(str) => write_str(buf, str),
+            Value::Ref(id) => self.write_ref(buf, id),
+            Value::Array(arr) => {
+                buf.push('(');
+                for v in arr {
+                    newline(buf, level + 1, compact);
+                    self.write_value(buf, v, level + 1, compact);
+                    buf.push(',');
+                    space(buf, compact);
+                }
+                newline(buf, level, compact);
+                buf.push(')');
+            }
+            Value::Dict(dict) => self.write_dict(buf, None, dict, level, compact),
+        }
+    }
+
+    fn write_dict(
+        &self,
+        buf: &mut String,
+        isa: Option<&str>,
+        dict: &BTreeMap<String, Value>,
+        level: usize,
+        compact: bool,
+    ) {
+        buf.push('{');
+        let isa = isa.map(|isa| ("isa", Value::from(isa)));
+        let isa = isa.as_ref().map(|(k, v)| (*k, v));
+        for (key, value) in isa
+            .into_iter()
+            .chain(dict.iter().map(|(k, v)| (k.as_str(), v)))
+        {
+            newline(buf, level + 1, compact);
+            write_str(buf, key);
+            buf.push_str(" = ");
+            self.write_value(buf, value, level + 1, compact);
+            buf.push(';');
+            space(buf, compact);
+        }
+        newline(buf, level, compact);
+        buf.push('}');
+    }
+
+    fn write_ref(&self, buf: &mut String, id: &Id) {
+        _ = write!(buf, "{id}");
+        match self.objects.get(id) {
+            Some(obj) if !obj.comment.is_empty() => {
+                _ = write!(buf, " /* {} */", obj.comment);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn newline(buf: &mut String, level: usize, compact: bool) {
+    if compact {
+        return;
+    }
+    buf.push('\n');
+    for _ in 0..level {
+        buf.push('\t');
+    }
+}
+
+/// In compact mode items are separated by space: `{isa = PBXBuildFile; fileRef = ...; }`
+fn space(buf: &mut String, compact: bool) {
+    if compact {
+        buf.push(' ');
+    }
+}
+
+fn write_str(buf: &mut String, str: &str) {
+    let plain = !str.is_empty()
+        && str
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '$' | '.' | '/'));
+    if plain {
+        buf.push_str(str);
+        return;
+    }
+    buf.push('"');
+    for ch in str.chars() {
+        match ch {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\t' => buf.push_str("\\t"),
+            ch => buf.push(ch),
+        }
+    }
+    buf.push('"');
+}
+
+impl fmt::Display for Pbxproj {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sections = BTreeMap::<&str, Vec<(&Id, &Object)>>::new();
+        for (id, obj) in self.objects.iter() {
+            sections.entry(obj.isa).or_default().push((id, obj));
+        }
+
+        let mut buf = String::new();
+        buf.push_str("// !$*UTF8*$!\n{\n");
+        buf.push_str("\tarchiveVersion = 1;\n\tclasses = {\n\t};\n\tobjectVersion = 77;\n");
+        buf.push_str("\tobjects = {\n");
+        for (isa, objects) in sections {
+            _ = write!(buf, "\n/* Begin {isa} section */\n");
+            for (id, obj) in objects {
+                buf.push_str("\t\t");
+                self.write_ref(&mut buf, id);
+                buf.push_str(" = ");
+                self.write_dict(&mut buf, Some(obj.isa), &obj.props, 2, obj.is_compact());
+                buf.push_str(";\n");
+            }
+            _ = writeln!(buf, "/* End {isa} section */");
+        }
+        buf.push_str("\t};\n\trootObject = ");
+        self.write_ref(&mut buf, &self.root);
+        buf.push_str(";\n}\n");
+        f.write_str(&buf)
+    }
+}
+
+fn build_settings(cfg: &Config) -> BTreeMap<String, Value> {
+    let mut res = dict([
+        ("ALLOW_TARGET_PLATFORM_SPECIALIZATION", "YES".into()),
+        ("ALWAYS_SEARCH_USER_PATHS", "NO".into()),
+        ("BOX_BIN_PATH", "$(PRODUCT_NAME)".into()),
+        ("CODE_SIGN_ENTITLEMENTS", "box.entitlements".into()),
+        ("CODE_SIGN_IDENTITY", "Apple Development".into()),
+        ("CODE_SIGN_STYLE", "Automatic".into()),
+        ("CURRENT_PROJECT_VERSION", cfg.build.as_str().into()),
+        ("DEVELOPMENT_TEAM", cfg.team.as_str().into()),
+        ("ENABLE_HARDENED_RUNTIME", "YES".into()),
+        ("EXCLUDED_ARCHS[sdk=macos*]", "x86_64".into()),
+        ("GENERATE_INFOPLIST_FILE", "YES".into()),
+        (
+            "INFOPLIST_KEY_CFBundleDisplayName",
+            cfg.display_name.as_str().into(),
+        ),
+        ("INFOPLIST_KEY_WKWatchOnly", "YES".into()),
+        (
+            "LD_RUNPATH_SEARCH_PATHS",
+            Value::Array(vec!["$(inherited)".into(), "@executable_path/Frameworks".into()]),
+        ),
+        (
+            "LD_RUNPATH_SEARCH_PATHS[sdk=macosx*]",
+            Value::Array(vec![
+                "$(inherited)".into(),
+                "@executable_path/../Frameworks".into(),
+            ]),
+        ),
+        ("MARKETING_VERSION", cfg.version.as_str().into()),
+        ("PRODUCT_BUNDLE_IDENTIFIER", cfg.bundle_id.as_str().into()),
+        ("PRODUCT_NAME", cfg.product.as_str().into()),
+        ("PROVISIONING_PROFILE_SPECIFIER", "".into()),
+        ("SDKROOT", "auto".into()),
+        (
+            "SUPPORTED_PLATFORMS",
+            "xrsimulator xros watchsimulator watchos macosx iphonesimulator iphoneos appletvsimulator appletvos".into(),
+        ),
+        ("SUPPORTS_MACCATALYST", "YES".into()),
+        ("TARGETED_DEVICE_FAMILY", "1,2,4,7".into()),
+    ]);
+    if !cfg.info.is_empty() {
+        res.insert("INFOPLIST_FILE".into(), "Info.plist".into());
+    }
+    for (key, version) in cfg.deployment.build_settings() {
+        res.insert(key.into(), version.into());
+    }
+    res
+}
+
+/// Generated `box.xcodeproj` with its side files
+pub(crate) struct Project {
+    pub(crate) pbxproj: Pbxproj,
+    pub(crate) entitlements: String,
+    /// Extra Info.plist keys, merged by Xcode with generated ones
+    pub(crate) info_plist: Option<String>,
+}
+
+impl Project {
+    pub(crate) fn new(cfg: &Config) -> Self {
+        Self {
+            pbxproj: pbxproj(cfg),
+            entitlements: plist::to_xml(&cfg.entitlements),
+            info_plist: (!cfg.info.is_empty()).then(|| plist::to_xml(&cfg.info)),
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// Project with one application target.
+///
+/// Cargo-built binary is copied into the bundle as is, Xcode only assembles and signs it.
+pub(crate) fn pbxproj(cfg: &Config) -> Pbxproj {
+    let mut proj = Pbxproj {
+        objects: BTreeMap::new(),
+        root: Id::new("project"),
+    };
+
+    let group = "<group>";
+    let absolute = "<absolute>";
+
+    let app_name = format!("{}.app", cfg.product);
+    let app = proj.add(
+        "ref/app",
+        "PBXFileReference",
+        app_name.as_str(),
+        dict([
+            ("explicitFileType", "wrapper.application".into()),
+            ("includeInIndex", "0".into()),
+            ("path", app_name.as_str().into()),
+            ("sourceTree", "BUILT_PRODUCTS_DIR".into()),
+        ]),
+    );
+    let bin = proj.add(
+        "ref/bin",
+        "PBXFileReference",
+        "bin",
+        dict([
+            ("lastKnownFileType", "compiled.mach-o.executable".into()),
+            ("name", "bin".into()),
+            ("path", "$(PRODUCT_NAME)".into()),
+            ("sourceTree", group.into()),
+        ]),
+    );
+    let entitlements = proj.add(
+        "ref/entitlements",
+        "PBXFileReference",
+        "box.entitlements",
+        dict([
+            ("lastKnownFileType", "text.plist.entitlements".into()),
+            ("path", "box.entitlements".into()),
+            ("sourceTree", group.into()),
+        ]),
+    );
+    let mut files = vec![entitlements];
+    if !cfg.info.is_empty() {
+        files.push(proj.add(
+            "ref/info",
+            "PBXFileReference",
+            "Info.plist",
+            dict([
+                ("lastKnownFileType", "text.plist.xml".into()),
+                ("path", "Info.plist".into()),
+                ("sourceTree", group.into()),
+            ]),
+        ));
+    }
+    files.push(bin);
+
+    let mut resources = vec![];
+    for path in cfg.resources.iter() {
+        let name = file_name(path);
+        let path = path.to_string_lossy();
+        let file = proj.add(
+            &format!("ref/resource/{path}"),
+            "PBXFileReference",
+            name.as_str(),
+            dict([
+                ("lastKnownFileType", "folder".into()),
+                ("name", name.as_str().into()),
+                ("path", path.as_ref().into()),
+                ("sourceTree", absolute.into()),
+            ]),
+        );
+        files.push(file);
+        resources.push(proj.add(
+            &format!("build/resource/{path}"),
+            "PBXBuildFile",
+            format!("{name} in Resources"),
+            dict([("fileRef", file.into())]),
+        ));
+    }
+
+    let mut frameworks = vec![];
+    for path in cfg.frameworks.iter() {
+        let name = file_name(path);
+        let path = path.to_string_lossy();
+        let file = proj.add(
+            &format!("ref/framework/{path}"),
+            "PBXFileReference",
+            name.as_str(),
+            dict([
+                ("lastKnownFileType", "wrapper.framework".into()),
+                ("name", name.as_str().into()),
+                ("path", path.as_ref().into()),
+                ("sourceTree", absolute.into()),
+            ]),
+        );
+        files.push(file);
+        frameworks.push(proj.add(
+            &format!("build/framework/{path}"),
+            "PBXBuildFile",
+            format!("{name} in Embed Frameworks"),
+            dict([
+                ("fileRef", file.into()),
+                (
+                    "settings",
+                    Value::Dict(dict([(
+                        "ATTRIBUTES",
+                        Value::Array(vec!["CodeSignOnCopy".into(), "RemoveHeadersOnCopy".into()]),
+                    )])),
+                ),
+            ]),
+        ));
+    }
+
+    let bin_build = proj.add(
+        "build/bin",
+        "PBXBuildFile",
+        "bin in CopyFiles",
+        dict([("fileRef", bin.into())]),
+    );
+
+    let products = proj.add(
+        "group/products",
+        "PBXGroup",
+        "Products",
+        dict([
+            ("children", vec![app].into()),
+            ("name", "Products".into()),
+            ("sourceTree", group.into()),
+        ]),
+    );
+    files.push(products);
+    let main_group = proj.add(
+        "group/main",
+        "PBXGroup",
+        "",
+        dict([("children", files.into()), ("sourceTree", group.into())]),
+    );
+
+    let phase = |files: Vec<Id>| {
+        dict([
+            ("buildActionMask", "2147483647".into()),
+            ("files", files.into()),
+            ("runOnlyForDeploymentPostprocessing", "0".into()),
+        ])
+    };
+    let mut phases = vec![
+        proj.add(
+            "phase/sources",
+            "PBXSourcesBuildPhase",
+            "Sources",
+            phase(vec![]),
+        ),
+        proj.add(
+            "phase/frameworks",
+            "PBXFrameworksBuildPhase",
+            "Frameworks",
+            phase(vec![]),
+        ),
+        proj.add(
+            "phase/resources",
+            "PBXResourcesBuildPhase",
+            "Resources",
+            phase(resources),
+        ),
+    ];
+    let mut copy_bin = phase(vec![bin_build]);
+    copy_bin.insert("dstPath".into(), "".into());
+    // executables
+    copy_bin.insert("dstSubfolderSpec".into(), "6".into());
+    phases.push(proj.add(
+        "phase/copy-bin",
+        "PBXCopyFilesBuildPhase",
+        "CopyFiles",
+        copy_bin,
+    ));
+    if !frameworks.is_empty() {
+        let mut embed = phase(frameworks);
+        embed.insert("dstPath".into(), "".into());
+        // frameworks
+        embed.insert("dstSubfolderSpec".into(), "10".into());
+        embed.insert("name".into(), "Embed Frameworks".into());
+        phases.push(proj.add(
+            "phase/embed-frameworks",
+            "PBXCopyFilesBuildPhase",
+            "Embed Frameworks",
+            embed,
+        ));
+    }
+
+    let mut config_list = |kind: &str, settings: BTreeMap<String, Value>| {
+        let configs: Vec<Id> = ["Debug", "Release"]
+            .into_iter()
+            .map(|name| {
+                proj.add(
+                    &format!("config/{kind}/{name}"),
+                    "XCBuildConfiguration",
+                    name,
+                    dict([
+                        ("buildSettings", Value::Dict(settings.clone())),
+                        ("name", name.into()),
+                    ]),
+                )
+            })
+            .collect();
+        proj.add(
+            &format!("configs/{kind}"),
+            "XCConfigurationList",
+            format!("Build configuration list for {kind} \"{TARGET}\""),
+            dict([
+                ("buildConfigurations", configs.into()),
+                ("defaultConfigurationIsVisible", "0".into()),
+                ("defaultConfigurationName", "Release".into()),
+            ]),
+        )
+    };
+    let project_configs = config_list("PBXProject", BTreeMap::new());
+    let target_configs = config_list("PBXNativeTarget", build_settings(cfg));
+
+    let target = proj.add(
+        "target",
+        "PBXNativeTarget",
+        TARGET,
+        dict([
+            ("buildConfigurationList", target_configs.into()),
+            ("buildPhases", phases.into()),
+            ("buildRules", Value::Array(vec![])),
+            ("dependencies", Value::Array(vec![])),
+            ("name", TARGET.into()),
+            ("productName", TARGET.into()),
+            ("productReference", app.into()),
+            ("productType", "com.apple.product-type.application".into()),
+        ]),
+    );
+    debug_assert_eq!(target, Id::target());
+
+    proj.add(
+        "project",
+        "PBXProject",
+        "Project object",
+        dict([
+            (
+                "attributes",
+                Value::Dict(dict([
+                    ("BuildIndependentTargetsInParallel", "1".into()),
+                    ("LastUpgradeCheck", "1620".into()),
+                ])),
+            ),
+            ("buildConfigurationList", project_configs.into()),
+            ("developmentRegion", "en".into()),
+            ("hasScannedForEncodings", "0".into()),
+            (
+                "knownRegions",
+                Value::Array(vec!["en".into(), "Base".into()]),
+            ),
+            ("mainGroup", main_group.into()),
+            ("minimizedProjectReferenceProxies", "1".into()),
+            ("preferredProjectObjectVersion", "77".into()),
+            ("productRefGroup", products.into()),
+            ("projectDirPath", "".into()),
+            ("projectRoot", "".into()),
+            ("targets", vec![target].into()),
+        ]),
+    );
+
+    proj
+}
+
+/// Shared scheme for the `box` target
+pub(crate) fn scheme(cfg: &Config) -> String {
+    const TEMPLATE_TARGET_ID: &str = "D2BD41F12CFC8A5D000CF128";
+    include_str!("../box/box.xcodeproj/xcshareddata/xcschemes/box.xcscheme")
+        .replace(TEMPLATE_TARGET_ID, &Id::target().to_string())
+        .replace("foo.app", &format!("{}.app", cfg.product))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Id, Project};
+    use crate::meta::fixture::config;
+
+    #[test]
+    fn ids() {
+        assert_eq!(Id::new("target"), Id::target());
+        assert_ne!(Id::new("a"), Id::new("b"));
+        assert_eq!(Id::new("a").to_string().len(), 24);
+    }
+
+    #[test]
+    fn golden() {
+        let proj = Project::new(&config());
+        assert_eq!(
+            proj.pbxproj.to_string(),
+            include_str!("../box/golden/project.pbxproj")
+        );
+        assert!(proj.info_plist.unwrap().contains("Testing \"Camera\""));
+        assert!(
+            proj.entitlements
+                .contains("com.apple.security.device.camera")
+        );
+    }
+
+    #[test]
+    fn deterministic() {
+        let a = Project::new(&config()).pbxproj.to_string();
+        let b = Project::new(&config()).pbxproj.to_string();
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,179 @@
+//! `.box` config values as property lists, written and read by `cidre::plist`
+
+use std::collections::BTreeMap;
+
+use cargo_toml::Value;
+use cidre::plist;
+use toml::value::{Datetime, Offset};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Xml,
+    Binary,
+}
+
+impl From<Format> for plist::Format {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::Xml => Self::XmlV1_0,
+            Format::Binary => Self::BinaryV1_0,
+        }
+    }
+}
+
+/// Root dict in `format`, binary is what Xcode uses for device bundles
+pub(crate) fn write(dict: &BTreeMap<String, Value>, format: Format) -> Vec<u8> {
+    plist::Value::Dictionary(to_dict(dict.iter()))
+        .to_data(format.into())
+        .expect("xml and binary plists fit any toml value")
+}
+
+/// XML plist with root dict, formatted the same way Xcode does
+pub(crate) fn to_xml(dict: &BTreeMap<String, Value>) -> String {
+    String::from_utf8(write(dict, Format::Xml)).expect("xml plist is utf8")
+}
+
+fn to_dict<'a>(entries: impl Iterator<Item = (&'a String, &'a Value)>) -> plist::Dictionary {
+    entries.map(|(k, v)| (k.as_str(), to_plist(v))).collect()
+}
+
+/// Local times have no absolute date and stay strings
+fn to_plist(value: &Value) -> plist::Value {
+    match value {
+        Value::String(s) => s.as_str().into(),
+        Value::Integer(i) => (*i).into(),
+        Value::Float(f) => (*f).into(),
+        Value::Boolean(b) => (*b).into(),
+        Value::Datetime(d) => match date_secs(d) {
+            Some(secs) => plist::Date(secs).into(),
+            None => d.to_string().into(),
+        },
+        Value::Array(arr) => arr.iter().map(to_plist).collect::<Vec<_>>().into(),
+        Value::Table(table) => to_dict(table.iter()).into(),
+    }
+}
+
+/// Any plist format with root dict.
+///
+/// toml has no bytes, so `<data>` and uids are skipped.
+pub(crate) fn from_data(data: &[u8]) -> Result<BTreeMap<String, Value>, String> {
+    match plist::Value::from_data(data).map_err(|e| e.to_string())? {
+        plist::Value::Dictionary(dict) => Ok(from_dict(dict).into_iter().collect()),
+        _ => Err("plist root is not a dict".to_string()),
+    }
+}
+
+fn from_dict(dict: plist::Dictionary) -> toml::Table {
+    dict.into_iter()
+        .filter_map(|(k, v)| Some((k, from_plist(v)?)))
+        .collect()
+}
+
+fn from_plist(value: plist::Value) -> Option<Value> {
+    Some(match value {
+        plist::Value::String(s) => Value::String(s),
+        plist::Value::Integer(i) => Value::Integer(i.as_i64()?),
+        plist::Value::Real(r) => Value::Float(r),
+        plist::Value::Bool(b) => Value::Boolean(b),
+        plist::Value::Date(d) => Value::Datetime(d.to_xml_string().parse().ok()?),
+        plist::Value::Array(arr) => Value::Array(arr.into_iter().filter_map(from_plist).collect()),
+        plist::Value::Dictionary(dict) => Value::Table(from_dict(dict)),
+        plist::Value::Data(_) | plist::Value::Uid(_) => return None,
+    })
+}
+
+/// Seconds since 2001-01-01 00:00:00 UTC, `None` for local times
+pub(crate) fn date_secs(d: &Datetime) -> Option<f64> {
+    let date = d.date?;
+    // days from civil, http://howardhinnant.github.io/date_algorithms.html
+    let (m, d_) = (date.month as i64, date.day as i64);
+    let y = date.year as i64 - (m <= 2) as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d_ - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    // 2001-01-01 is 11_323 days after unix epoch
+    let mut secs = (days - 11_323) as f64 * 86_400.0;
+    if let Some(t) = d.time {
+        secs += (t.hour as u32 * 3600 + t.minute as u32 * 60 + t.second as u32) as f64;
+        secs += t.nanosecond as f64 / 1e9;
+    }
+    if let Some(Offset::Custom { minutes }) = d.offset {
+        secs -= minutes as f64 * 60.0;
+    }
+    Some(secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use cargo_toml::Value;
+
+    use super::Format;
+
+    #[test]
+    fn entitlements() {
+        let mut dict = BTreeMap::new();
+        dict.insert(
+            "com.apple.security.app-sandbox".to_string(),
+            Value::Boolean(true),
+        );
+        dict.insert(
+            "keychain-access-groups".to_string(),
+            Value::Array(vec![Value::String("$(AppIdentifierPrefix)a&b".into())]),
+        );
+        assert_eq!(
+            super::to_xml(&dict),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>com.apple.security.app-sandbox</key>
+	<true/>
+	<key>keychain-access-groups</key>
+	<array>
+		<string>$(AppIdentifierPrefix)a&amp;b</string>
+	</array>
+</dict>
+</plist>
+"#
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let dict = BTreeMap::from([
+            (
+                "a&b".to_string(),
+                Value::String("<1> &#38; \"2\"".to_string()),
+            ),
+            ("empty".to_string(), Value::Table(Default::default())),
+            (
+                "nested".to_string(),
+                Value::Array(vec![
+                    Value::Integer(-1),
+                    Value::Float(0.5),
+                    Value::Boolean(false),
+                    Value::Datetime("2025-01-02T03:04:05Z".parse().unwrap()),
+                ]),
+            ),
+        ]);
+        for format in [Format::Xml, Format::Binary] {
+            let data = super::write(&dict, format);
+            assert_eq!(super::from_data(&data).unwrap(), dict, "{format:?}");
+        }
+        assert!(super::write(&dict, Format::Binary).starts_with(b"bplist00"));
+
+        let dict = super::from_data(
+            b"<plist version=\"1.0\"><dict><key>d</key><data>AAEC</data>\
+              <key>s</key><string>&#x41;&lt;</string></dict></plist>",
+        )
+        .unwrap();
+        assert_eq!(dict.get("d"), None);
+        assert_eq!(dict["s"].as_str(), Some("A<"));
+
+        assert!(super::from_data(b"<plist><array/></plist>").is_err());
+    }
+}
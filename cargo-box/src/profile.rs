@@ -0,0 +1,449 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cargo_toml::Value;
+use toml::value::Datetime;
+
+use crate::{meta::Config, plist, xcode};
+
+/// 1.2.840.113549.1.7.2
+const SIGNED_DATA_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+
+/// Seconds between unix epoch and 2001-01-01
+const REFERENCE_DATE: f64 = 978_307_200.0;
+
+/// (tag, content, rest) of the first BER element
+fn element(buf: &[u8]) -> Result<(u8, &[u8], &[u8]), String> {
+    let [tag, len, rest @ ..] = buf else {
+        return Err("unexpected end of data".to_string());
+    };
+    if tag & 0x1f == 0x1f {
+        return Err(format!("unsupported tag {tag:#x}"));
+    }
+    let (len, rest) = match *len {
+        // indefinite length, content ends with end-of-contents element
+        0x80 => {
+            let mut inner = rest;
+            while !inner.starts_with(&[0, 0]) {
+                inner = element(inner)?.2;
+            }
+            let len = rest.len() - inner.len();
+            return Ok((*tag, &rest[..len], &inner[2..]));
+        }
+        len if len < 0x80 => (len as usize, rest),
+        len => {
+            let count = (len & 0x7f) as usize;
+            if count > 8 || rest.len() < count {
+                return Err("invalid length".to_string());
+            }
+            let (bytes, rest) = rest.split_at(count);
+            let len = bytes.iter().fold(0usize, |len, b| len << 8 | *b as usize);
+            (len, rest)
+        }
+    };
+    if rest.len() < len {
+        return Err("unexpected end of data".to_string());
+    }
+    let (content, rest) = rest.split_at(len);
+    Ok((*tag, content, rest))
+}
+
+fn expect<'a>(buf: &'a [u8], tag: u8, what: &str) -> Result<(&'a [u8], &'a [u8]), String> {
+    match element(buf)? {
+        (t, content, rest) if t == tag => Ok((content, rest)),
+        (t, ..) => Err(format!("expected {what}, found tag {t:#x}")),
+    }
+}
+
+/// Primitive or chunked constructed OCTET STRING
+fn octets(buf: &[u8]) -> Result<Vec<u8>, String> {
+    match element(buf)? {
+        (0x04, content, _) => Ok(content.to_vec()),
+        (0x24, mut chunks, _) => {
+            let mut res = vec![];
+            while !chunks.is_empty() {
+                res.extend(octets(chunks)?);
+                chunks = element(chunks)?.2;
+            }
+            Ok(res)
+        }
+        (t, ..) => Err(format!("expected OCTET STRING, found tag {t:#x}")),
+    }
+}
+
+/// Encapsulated content of CMS SignedData.
+///
+/// Signature is not verified, `codesign` and device do it anyway.
+pub(crate) fn cms_content(der: &[u8]) -> Result<Vec<u8>, String> {
+    let (content_info, _) = expect(der, 0x30, "ContentInfo")?;
+    let (oid, rest) = expect(content_info, 0x06, "content type")?;
+    if oid != SIGNED_DATA_OID {
+        return Err("not a CMS SignedData".to_string());
+    }
+    let (explicit, _) = expect(rest, 0xa0, "content")?;
+    let (signed_data, _) = expect(explicit, 0x30, "SignedData")?;
+    let (_version, rest) = expect(signed_data, 0x02, "version")?;
+    let (_digest_algs, rest) = expect(rest, 0x31, "digest algorithms")?;
+    let (encap, _) = expect(rest, 0x30, "encapsulated content info")?;
+    let (_content_type, rest) = expect(encap, 0x06, "encapsulated content type")?;
+    let (explicit, _) = expect(rest, 0xa0, "encapsulated content")?;
+    octets(explicit)
+}
+
+/// Provisioning profile, `.mobileprovision` or `.provisionprofile`
+#[derive(Debug, Clone)]
+pub(crate) struct Profile {
+    pub(crate) uuid: String,
+    pub(crate) name: String,
+    pub(crate) team_id: String,
+    pub(crate) team_name: String,
+    /// `TEAMID.org.cidre.*`
+    pub(crate) app_id: String,
+    pub(crate) platforms: Vec<String>,
+    pub(crate) creation: Datetime,
+    pub(crate) expiration: Datetime,
+    /// Device UDIDs, empty for enterprise and app store profiles
+    pub(crate) devices: Vec<String>,
+    /// Enterprise profiles run on any device
+    pub(crate) all_devices: bool,
+    pub(crate) entitlements: BTreeMap<String, Value>,
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    let Some(Value::Array(arr)) = value else {
+        return vec![];
+    };
+    arr.iter()
+        .filter_map(|v| v.as_str().map(ToString::to_string))
+        .collect()
+}
+
+impl Profile {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, String> {
+        Self::from_dict(plist::from_data(&cms_content(data)?)?)
+    }
+
+    pub(crate) fn from_path(path: &Path) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::parse(&data).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    fn from_dict(mut dict: BTreeMap<String, Value>) -> Result<Self, String> {
+        let mut string = |key: &str| match dict.remove(key) {
+            Some(Value::String(s)) => Ok(s),
+            _ => Err(format!("`{key}` string is missing")),
+        };
+        let uuid = string("UUID")?;
+        let name = string("Name")?;
+        let team_name = string("TeamName").unwrap_or_default();
+
+        let mut date = |key: &str| match dict.remove(key) {
+            Some(Value::Datetime(d)) => Ok(d),
+            _ => Err(format!("`{key}` date is missing")),
+        };
+        let creation = date("CreationDate")?;
+        let expiration = date("ExpirationDate")?;
+
+        let entitlements: BTreeMap<String, Value> = match dict.remove("Entitlements") {
+            Some(Value::Table(table)) => table.into_iter().collect(),
+            _ => return Err("`Entitlements` dict is missing".to_string()),
+        };
+        let Some(app_id) = entitlements
+            .get("application-identifier")
+            .or_else(|| entitlements.get("com.apple.application-identifier"))
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+        else {
+            return Err("`application-identifier` entitlement is missing".to_string());
+        };
+        let Some(team_id) = strings(dict.get("TeamIdentifier")).into_iter().next() else {
+            return Err("`TeamIdentifier` is missing".to_string());
+        };
+
+        Ok(Self {
+            uuid,
+            name,
+            team_id,
+            team_name,
+            app_id,
+            platforms: strings(dict.get("Platform")),
+            creation,
+            expiration,
+            devices: strings(dict.get("ProvisionedDevices")),
+            all_devices: dict
+                .get("ProvisionsAllDevices")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            entitlements,
+        })
+    }
+
+    /// App id without team prefix, `org.cidre.*`
+    pub(crate) fn bundle_id_pattern(&self) -> &str {
+        self.app_id
+            .split_once('.')
+            .map_or(self.app_id.as_str(), |(_, pattern)| pattern)
+    }
+
+    pub(crate) fn matches_bundle_id(&self, bundle_id: &str) -> bool {
+        match self.bundle_id_pattern().strip_suffix('*') {
+            Some(prefix) => bundle_id.starts_with(prefix),
+            None => self.bundle_id_pattern() == bundle_id,
+        }
+    }
+
+    /// `now` is unix time in seconds
+    pub(crate) fn is_expired(&self, now: u64) -> bool {
+        plist::date_secs(&self.expiration).is_none_or(|secs| secs + REFERENCE_DATE <= now as f64)
+    }
+
+    /// Problems preventing signing of the product with this profile, empty if it fits.
+    ///
+    /// `com.apple.security.*` entitlements are sandbox ones, they don't need provisioning.
+    pub(crate) fn check(&self, cfg: &Config, now: u64) -> Vec<String> {
+        let mut res = vec![];
+        if self.team_id != cfg.team {
+            res.push(format!(
+                "team `{}` doesn't match profile team `{}`",
+                cfg.team, self.team_id
+            ));
+        }
+        if !self.matches_bundle_id(&cfg.bundle_id) {
+            res.push(format!(
+                "bundle id `{}` doesn't match `{}`",
+                cfg.bundle_id,
+                self.bundle_id_pattern()
+            ));
+        }
+        if self.is_expired(now) {
+            res.push(format!("expired at {}", self.expiration));
+        }
+        for key in cfg.entitlements.keys() {
+            if !key.starts_with("com.apple.security.") && !self.entitlements.contains_key(key) {
+                res.push(format!("entitlement `{key}` is not granted"));
+            }
+        }
+        res
+    }
+}
+
+/// Profiles installed by Xcode and Apple Configurator
+pub(crate) fn installed() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return vec![];
+    };
+    let dirs = [
+        home.join("Library/MobileDevice/Provisioning Profiles"),
+        home.join("Library/Developer/Xcode/UserData/Provisioning Profiles"),
+    ];
+    let mut res: Vec<PathBuf> = dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "mobileprovision" || ext == "provisionprofile")
+        })
+        .collect();
+    res.sort();
+    res
+}
+
+#[derive(clap::Args, Debug)]
+pub(crate) struct Args {
+    /// Profile files, installed profiles by default
+    #[arg()]
+    pub(crate) paths: Vec<PathBuf>,
+
+    /// List devices and entitlements
+    #[arg(long, short)]
+    pub(crate) verbose: bool,
+
+    /// Check profiles against .box config of binary, example or test
+    #[arg(long)]
+    pub(crate) check: bool,
+
+    #[command(flatten)]
+    pub(crate) product: xcode::ProjArgs,
+}
+
+pub(crate) fn run(args: Args) {
+    let cfg = if args.check {
+        let Some((_, cfg)) = xcode::config(&args.product) else {
+            std::process::exit(1);
+        };
+        Some(cfg)
+    } else {
+        None
+    };
+    let paths = if args.paths.is_empty() {
+        installed()
+    } else {
+        args.paths
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
+    let mut matches = 0;
+    for path in paths {
+        let profile = match Profile::from_path(&path) {
+            Ok(profile) => profile,
+            Err(err) => {
+                println!("{err}");
+                continue;
+            }
+        };
+        println!("{}: {}", profile.uuid, profile.name);
+        println!("\tteam: {} {}", profile.team_id, profile.team_name);
+        println!("\tapp id: {}", profile.app_id);
+        println!("\tplatforms: {}", profile.platforms.join(", "));
+        let expired = if profile.is_expired(now) {
+            " (expired)"
+        } else {
+            ""
+        };
+        println!("\tcreated: {}", profile.creation);
+        println!("\texpires: {}{expired}", profile.expiration);
+        if profile.all_devices {
+            println!("\tdevices: all");
+        } else {
+            println!("\tdevices: {}", profile.devices.len());
+        }
+        if args.verbose {
+            for device in &profile.devices {
+                println!("\t\t{device}");
+            }
+            println!("\tentitlements:");
+            for (key, value) in &profile.entitlements {
+                println!("\t\t{key} = {value}");
+            }
+        }
+        if let Some(cfg) = cfg.as_ref() {
+            let problems = profile.check(cfg, now);
+            if problems.is_empty() {
+                matches += 1;
+                println!("\tmatches {}", cfg.bundle_id);
+            }
+            for problem in problems {
+                println!("\t{problem}");
+            }
+        }
+    }
+
+    if let Some(cfg) = cfg.filter(|_| matches == 0) {
+        println!("no profiles found for {}", cfg.bundle_id);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use cargo_toml::Value;
+
+    use super::Profile;
+    use crate::{
+        meta::{Config, fixture},
+        plist,
+    };
+
+    const FIXTURE: &[u8] = include_bytes!("../box/fixtures/box.mobileprovision");
+
+    /// 2025-01-01
+    const NOW: u64 = 1_735_689_600;
+
+    fn config(bundle_id: &str) -> Config {
+        Config {
+            bundle_id: bundle_id.to_string(),
+            ..fixture::config()
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let profile = Profile::parse(FIXTURE).unwrap();
+        assert_eq!(profile.uuid, "5f3c1e2a-8b7d-4c6e-9a0f-1d2e3f4a5b6c");
+        assert_eq!(profile.name, "iOS Team Provisioning Profile: org.cidre.*");
+        assert_eq!(profile.team_id, "ABCDE12345");
+        assert_eq!(profile.team_name, "Cidre & Co");
+        assert_eq!(profile.app_id, "ABCDE12345.org.cidre.*");
+        assert_eq!(profile.bundle_id_pattern(), "org.cidre.*");
+        assert_eq!(profile.platforms, ["iOS", "xrOS", "visionOS"]);
+        assert_eq!(profile.expiration.to_string(), "2025-10-01T10:00:00Z");
+        assert_eq!(
+            profile.devices,
+            ["00008110-000A1B2C3D4E5F6A", "00008030-001122334455667E"]
+        );
+        assert!(!profile.all_devices);
+        assert_eq!(profile.entitlements.len(), 5);
+
+        assert!(Profile::parse(b"<plist></plist>").is_err());
+        assert!(Profile::parse(&FIXTURE[..FIXTURE.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn ber() {
+        // indefinite lengths and chunked OCTET STRING as `openssl cms -stream` writes
+        let mut data = vec![0x30, 0x80, 0x06, 0x09];
+        data.extend_from_slice(super::SIGNED_DATA_OID);
+        data.extend_from_slice(&[0xa0, 0x80, 0x30, 0x80, 0x02, 0x01, 0x01, 0x31, 0x00]);
+        data.extend_from_slice(&[0x30, 0x80, 0x06, 0x00, 0xa0, 0x80, 0x24, 0x80]);
+        data.extend_from_slice(&[0x04, 0x02, b'a', b'b', 0x04, 0x01, b'c', 0x00, 0x00]);
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(super::cms_content(&data).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn check() {
+        let profile = Profile::parse(FIXTURE).unwrap();
+        assert!(profile.matches_bundle_id("org.cidre.cam"));
+        assert!(!profile.matches_bundle_id("org.other.cam"));
+        assert!(!profile.is_expired(NOW));
+        assert!(profile.is_expired(NOW + 365 * 24 * 3600));
+
+        assert!(profile.check(&config("org.cidre.cam"), NOW).is_empty());
+
+        let mut cfg = config("org.other.cam");
+        cfg.team = "XYZ".to_string();
+        cfg.entitlements.insert(
+            "com.apple.developer.healthkit".to_string(),
+            Value::Boolean(true),
+        );
+        let problems = profile.check(&cfg, NOW + 365 * 24 * 3600);
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(problems[3].contains("com.apple.developer.healthkit"));
+    }
+
+    /// Unsigned SignedData with `content` chunked into short OCTET STRINGs
+    fn signed(content: &[u8]) -> Vec<u8> {
+        let mut data = vec![0x30, 0x80, 0x06, 0x09];
+        data.extend_from_slice(super::SIGNED_DATA_OID);
+        data.extend_from_slice(&[0xa0, 0x80, 0x30, 0x80, 0x02, 0x01, 0x01, 0x31, 0x00]);
+        data.extend_from_slice(&[0x30, 0x80, 0x06, 0x00, 0xa0, 0x80, 0x24, 0x80]);
+        for chunk in content.chunks(0x7f) {
+            data.extend_from_slice(&[0x04, chunk.len() as u8]);
+            data.extend_from_slice(chunk);
+        }
+        data.extend_from_slice(&[0; 12]);
+        data
+    }
+
+    #[test]
+    fn missing_fields() {
+        let mut dict = BTreeMap::new();
+        dict.insert("UUID".to_string(), Value::String("uuid".to_string()));
+        dict.insert("Name".to_string(), Value::String("name".to_string()));
+        let data = signed(&plist::write(&dict, plist::Format::Xml));
+        let err = Profile::parse(&data).unwrap_err();
+        assert_eq!(err, "`CreationDate` date is missing");
+
+        assert!(Profile::from_dict(BTreeMap::new()).is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    Attribute, Block, Expr, Ident, ItemFn, ItemMacro, ReturnType, Stmt, Token, Type,
+};
+
+use crate::{sel::upper_case, versions::Versions};
+
+/// Item with outer attributes split off, so availability docs go after them
+struct Item {
+    attrs: Vec<Attribute>,
+    rest: TokenStream,
+}
+
+impl Parse for Item {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            attrs: input.call(Attribute::parse_outer)?,
+            rest: input.parse()?,
+        })
+    }
+}
+
+/// Item for deployment targets below `versions`, `None` if it is available only
+/// as is (declarations, statics and other items without body).
+///
+/// - `define_cls!` and `define_cls_init!` become their weak variants
+/// - `fn() -> &'static T { unsafe { VAR } }` returns `Option` of weak static
+/// - `unsafe { fn_call(args) }` body calls weak fn
+/// - `Self::alloc()...` body returns `Option`, `None` if class is missing
+/// - other fns become `unsafe`, caller should check availability
+fn weak_item(rest: &TokenStream) -> syn::Result<Option<TokenStream>> {
+    if let Ok(mut mac) = syn::parse2::<ItemMacro>(rest.clone()) {
+        let Some(last) = mac.mac.path.segments.last_mut() else {
+            return Ok(None);
+        };
+        last.ident = match last.ident.to_string().as_str() {
+            "define_cls" => format_ident!("define_weak_cls", span = last.ident.span()),
+            "define_cls_init" => format_ident!("define_weak_cls_init", span = last.ident.span()),
+            _ => return Ok(None),
+        };
+        return Ok(Some(mac.into_token_stream()));
+    }
+    let Ok(mut f) = syn::parse2::<ItemFn>(rest.clone()) else {
+        return Ok(None);
+    };
+
+    if f.sig.inputs.is_empty() {
+        if let Some(var) = static_var(&f) {
+            let var = format_ident!("{}", upper_case(&var.to_string()), span = var.span());
+            f.block = parse_quote!({ unsafe { #var.get_var() } });
+            option_output(&mut f)?;
+        }
+    }
+
+    let call = match f.block.stmts.as_slice() {
+        [Stmt::Expr(Expr::Unsafe(block), None)] => weak_call(&block.block),
+        _ => None,
+    };
+    if let Some((func, args)) = call {
+        let func = format_ident!("{}", upper_case(&func.to_string()), span = func.span());
+        let args = args.iter();
+        f.block = parse_quote!({ unsafe { #func.get_fn().unwrap()(#(#args),*) } });
+    } else if let [Stmt::Expr(Expr::Block(_), _), ..] = f.block.stmts.as_slice() {
+        option_output(&mut f)?;
+    } else if let [Stmt::Expr(expr, None)] = f.block.stmts.as_mut_slice() {
+        if !alloc_root(expr) {
+            return Ok(Some(unsafe_fn(f)));
+        }
+        try_alloc(expr);
+        f.block = parse_quote!({ Some(#expr) });
+        option_output(&mut f)?;
+    } else {
+        return Ok(Some(unsafe_fn(f)));
+    }
+
+    Ok(Some(f.into_token_stream()))
+}
+
+fn unsafe_fn(mut f: ItemFn) -> TokenStream {
+    f.sig.unsafety.get_or_insert_with(Default::default);
+    f.into_token_stream()
+}
+
+/// `VAR` of `-> &'static T { unsafe { VAR } }`
+fn static_var(f: &ItemFn) -> Option<&Ident> {
+    let ReturnType::Type(_, ty) = &f.sig.output else {
+        return None;
+    };
+    let Type::Reference(r) = ty.as_ref() else {
+        return None;
+    };
+    if r.lifetime.as_ref()?.ident != "static" {
+        return None;
+    }
+    let [Stmt::Expr(Expr::Unsafe(block), None)] = f.block.stmts.as_slice() else {
+        return None;
+    };
+    let [Stmt::Expr(Expr::Path(path), None)] = block.block.stmts.as_slice() else {
+        return None;
+    };
+    path.path.get_ident()
+}
+
+/// `fn_call` and args of `fn_call(args)` block
+fn weak_call(block: &Block) -> Option<(&Ident, &Punctuated<Expr, Token![,]>)> {
+    let [Stmt::Expr(Expr::Call(call), None)] = block.stmts.as_slice() else {
+        return None;
+    };
+    let Expr::Path(func) = call.func.as_ref() else {
+        return None;
+    };
+    Some((func.path.get_ident()?, &call.args))
+}
+
+fn is_self_alloc(expr: &Expr) -> bool {
+    let Expr::Call(call) = expr else {
+        return false;
+    };
+    let Expr::Path(func) = call.func.as_ref() else {
+        return false;
+    };
+    let segments: Vec<_> = func.path.segments.iter().map(|s| &s.ident).collect();
+    call.args.is_empty() && matches!(segments.as_slice(), [s, a] if *s == "Self" && *a == "alloc")
+}
+
+/// Method chain starting with `Self::alloc()`
+fn alloc_root(expr: &Expr) -> bool {
+    match expr {
+        Expr::MethodCall(m) => alloc_root(&m.receiver),
+        expr => is_self_alloc(expr),
+    }
+}
+
+/// `Self::alloc()` -> `Self::alloc()?`
+fn try_alloc(expr: &mut Expr) {
+    match expr {
+        Expr::MethodCall(m) => try_alloc(&mut m.receiver),
+        expr if is_self_alloc(expr) => *expr = parse_quote!(#expr?),
+        _ => {}
+    }
+}
+
+fn option_output(f: &mut ItemFn) -> syn::Result<()> {
+    match &mut f.sig.output {
+        ReturnType::Type(_, ty) => {
+            *ty = parse_quote!(Option<#ty>);
+            Ok(())
+        }
+        ReturnType::Default => Err(syn::Error::new_spanned(
+            &f.sig,
+            "`#[api::available]` fn should return value to wrap it in `Option`",
+        )),
+    }
+}
+
+/// Item as is for deployment targets with the api and its weak variant for the rest
+pub(crate) fn expand(versions: TokenStream, body: TokenStream) -> syn::Result<TokenStream> {
+    let versions: Versions = syn::parse2(versions)?;
+    let available = versions.available_cfg();
+    if available.is_empty() {
+        return Ok(body);
+    }
+    let available_doc = versions.available_doc();
+    let Item { attrs, rest } = syn::parse2(body)?;
+
+    let mut res = quote! {
+        #available
+        #(#attrs)*
+        #available_doc
+        #rest
+    };
+
+    if let Some(weak) = weak_item(&rest)? {
+        let unavailable = versions.unavailable_cfg();
+        let unavailable_doc = versions.unavailable_doc();
+        res.extend(quote! {
+            #unavailable
+            #(#attrs)*
+            #unavailable_doc
+            #weak
+        });
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    fn weak(item: proc_macro2::TokenStream) -> String {
+        super::weak_item(&item).unwrap().unwrap().to_string()
+    }
+
+    #[test]
+    fn weak_items() {
+        let res = weak(quote!(crate::define_cls!(SC_STREAM);));
+        assert_eq!(res, quote!(crate::define_weak_cls!(SC_STREAM);).to_string());
+
+        let res = weak(quote!(
+            pub fn status() -> &'static Self {
+                unsafe { SCStreamFrameInfoStatus }
+            }
+        ));
+        assert!(res.starts_with("pub unsafe fn status () -> Option < & 'static Self >"));
+        assert!(res.contains("SCSTREAM_FRAME_INFO_STATUS . get_var ()"));
+
+        let res = weak(quote!(
+            pub fn type_id() -> cf::TypeId {
+                unsafe { CMTaggedBufferGroupGetTypeID() }
+            }
+        ));
+        assert!(res.starts_with("pub fn type_id ()"));
+        assert!(res.contains("CMTAGGED_BUFFER_GROUP_GET_TYPE_ID . get_fn () . unwrap () ()"));
+
+        let res = weak(quote!(
+            pub fn new() -> arc::R<Self> {
+                Self::alloc().init()
+            }
+        ));
+        assert!(res.contains("-> Option < arc :: R < Self > >"));
+        assert!(res.contains("Some (Self :: alloc () ? . init ())"));
+
+        let res = weak(quote!(
+            pub fn len(&self) -> usize {
+                self.count() as _
+            }
+        ));
+        assert!(res.starts_with("pub unsafe fn len"));
+
+        assert!(super::weak_item(&quote!(
+            static FOO: &'static Foo;
+        ))
+        .unwrap()
+        .is_none());
+        assert!(super::weak_item(&quote!(define_obj_type!(Foo(ns::Id));))
+            .unwrap()
+            .is_none());
+    }
+}
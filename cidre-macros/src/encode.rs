@@ -0,0 +1,162 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Data, DeriveInput, Fields, LitStr, Type};
+
+enum Repr {
+    C,
+    Transparent,
+}
+
+fn repr(input: &DeriveInput) -> syn::Result<Repr> {
+    let mut res = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                res = Some(Repr::C);
+            } else if meta.path.is_ident("transparent") {
+                res = Some(Repr::Transparent);
+            } else if meta.input.peek(syn::token::Paren) {
+                // align(8), packed(2) and friends
+                meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+    res.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "`#[derive(objc::Encode)]` requires `#[repr(C)]` or `#[repr(transparent)]`",
+        )
+    })
+}
+
+/// `#[encode(name = "CGRect")]`, defaults to `?` like anonymous C structs
+fn name(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut res = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("encode") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                res = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported encode attribute, expected `name = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(res)
+}
+
+fn is_phantom(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "PhantomData"),
+        _ => false,
+    }
+}
+
+pub(crate) fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let mut input: DeriveInput = syn::parse2(input)?;
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`#[derive(objc::Encode)]` supports only structs",
+        ));
+    };
+    // `PhantomData` markers are not part of C layout
+    let fields: Vec<Type> = match &data.fields {
+        Fields::Named(f) => f.named.iter().map(|f| f.ty.clone()).collect(),
+        Fields::Unnamed(f) => f.unnamed.iter().map(|f| f.ty.clone()).collect(),
+        Fields::Unit => vec![],
+    };
+    let fields: Vec<Type> = fields.into_iter().filter(|ty| !is_phantom(ty)).collect();
+
+    let repr = repr(&input)?;
+    let name = name(&input)?;
+
+    let encoding = match repr {
+        Repr::C => {
+            let name = name.unwrap_or_else(|| LitStr::new("?", input.ident.span()));
+            quote! {
+                objc::Encoding::Struct(#name, &[#(<#fields as objc::Encode>::ENCODING),*])
+            }
+        }
+        Repr::Transparent => {
+            if let Some(name) = name {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`#[repr(transparent)]` struct is encoded as its field, `name` is not used",
+                ));
+            }
+            let [field] = fields.as_slice() else {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`#[repr(transparent)]` struct should have exactly one non `PhantomData` field",
+                ));
+            };
+            quote!(<#field as objc::Encode>::ENCODING)
+        }
+    };
+
+    let where_clause = input.generics.make_where_clause();
+    for ty in &fields {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: objc::Encode));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        unsafe impl #impl_generics objc::Encode for #ident #ty_generics #where_clause {
+            const ENCODING: objc::Encoding = #encoding;
+        }
+
+        unsafe impl #impl_generics objc::RefEncode for #ident #ty_generics #where_clause {
+            const ENCODING_REF: objc::Encoding = objc::Encoding::Ptr(&<Self as objc::Encode>::ENCODING);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+
+    fn expand_str(str: &str) -> syn::Result<String> {
+        expand(str.parse().unwrap()).map(|ts| ts.to_string())
+    }
+
+    #[test]
+    fn structs() {
+        let res = expand_str(
+            r#"#[encode(name = "CGRect")] #[repr(C)] struct Rect { origin: Point, size: Size }"#,
+        )
+        .unwrap();
+        assert!(res.contains(r#"objc :: Encoding :: Struct ("CGRect""#));
+        assert!(res.contains("< Point as objc :: Encode > :: ENCODING"));
+
+        let res = expand_str("#[repr(C, align(16))] struct Size(usize, usize, usize);").unwrap();
+        assert!(res.contains(r#"Struct ("?""#));
+
+        let res = expand_str("#[repr(transparent)] struct Id<T>(usize, PhantomData<T>);").unwrap();
+        assert!(res.contains("< usize as objc :: Encode > :: ENCODING ;"));
+        assert!(!res.contains("PhantomData < T > : objc :: Encode"));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(expand_str("struct Rect { x: f64 }").is_err());
+        assert!(expand_str("#[repr(u8)] enum E { A }").is_err());
+        assert!(expand_str(r#"#[repr(transparent)] #[encode(name = "A")] struct A(u8);"#).is_err());
+        assert!(expand_str("#[repr(transparent)] struct A(u8, u8);").is_err());
+        assert!(expand_str(r#"#[repr(C)] #[encode(rename = "A")] struct A(u8);"#).is_err());
+    }
+}
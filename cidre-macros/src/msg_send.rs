@@ -0,0 +1,511 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    visit_mut::VisitMut,
+    Attribute, FnArg, Ident, Pat, ReturnType, Signature, Token, TraitItemFn, Type, TypePath,
+    Visibility,
+};
+
+use crate::{
+    sel::{is_attr, is_objc_attr, msg_send_attr, Sel},
+    versions::Versions,
+};
+
+/// `pub fn foo(&self, arg: Arg) -> Ret;`
+struct MsgSendFn {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    sig: Signature,
+}
+
+impl Parse for MsgSendFn {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let sig: Signature = input.parse()?;
+        if input.peek(syn::token::Brace) {
+            let body: TokenStream = input.parse()?;
+            return Err(syn::Error::new_spanned(
+                body,
+                "`#[objc::msg_send]` generates function body, end signature with `;` instead",
+            ));
+        }
+        input.parse::<Token![;]>()?;
+        Ok(Self { attrs, vis, sig })
+    }
+}
+
+/// How receiver is passed to `objc_msgSend`
+enum Recv {
+    /// `Self::cls_ptr()`
+    Cls,
+    /// `self` with its type in `objc_msgSend` signature
+    Obj(TokenStream),
+}
+
+/// Replaces first `arc::R<T>` with `arc::Rar<T>`
+struct RarReplacer(bool);
+
+impl VisitMut for RarReplacer {
+    fn visit_type_path_mut(&mut self, ty: &mut TypePath) {
+        if self.0 {
+            return;
+        }
+        if is_arc_r(ty) {
+            let last = ty.path.segments.last_mut().unwrap();
+            last.ident = Ident::new("Rar", last.ident.span());
+            self.0 = true;
+            return;
+        }
+        syn::visit_mut::visit_type_path_mut(self, ty);
+    }
+}
+
+fn is_arc_r(ty: &TypePath) -> bool {
+    let segments = &ty.path.segments;
+    let len = segments.len();
+    len >= 2
+        && segments[len - 2].ident == "arc"
+        && segments[len - 1].ident == "R"
+        && matches!(
+            segments[len - 1].arguments,
+            syn::PathArguments::AngleBracketed(_)
+        )
+}
+
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(ty) = ty else {
+        return false;
+    };
+    ty.qself.is_none() && ty.path.segments.len() == 1 && ty.path.segments[0].ident == "Option"
+}
+
+fn check_ret(output: &ReturnType) -> syn::Result<()> {
+    let ReturnType::Type(_, ty) = output else {
+        return Ok(());
+    };
+    match ty.as_ref() {
+        Type::ImplTrait(_) => Err(syn::Error::new_spanned(
+            ty,
+            "`impl Trait` can't be returned from objc_msgSend, use concrete type",
+        )),
+        Type::TraitObject(_) | Type::Infer(_) => Err(syn::Error::new_spanned(
+            ty,
+            "unsupported return type, objc_msgSend needs sized concrete type",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn check_sig(sig: &Signature) -> syn::Result<()> {
+    if let Some(t) = sig.constness {
+        return Err(syn::Error::new_spanned(
+            t,
+            "`const` functions are not supported by `#[objc::msg_send]`",
+        ));
+    }
+    if let Some(t) = sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            t,
+            "`async` functions are not supported by `#[objc::msg_send]`",
+        ));
+    }
+    if let Some(abi) = &sig.abi {
+        return Err(syn::Error::new_spanned(
+            abi,
+            "`#[objc::msg_send]` functions should not specify ABI",
+        ));
+    }
+    if let Some(v) = &sig.variadic {
+        return Err(syn::Error::new_spanned(
+            v,
+            "variadic functions are not supported by `#[objc::msg_send]`",
+        ));
+    }
+    check_ret(&sig.output)
+}
+
+/// Splits function args into receiver and arg names
+fn fn_args(sig: &Signature) -> syn::Result<(Recv, Vec<Ident>, Vec<TokenStream>)> {
+    let mut recv = Recv::Cls;
+    let mut vars = Vec::with_capacity(sig.inputs.len());
+    let mut tys = Vec::with_capacity(sig.inputs.len());
+    for (i, arg) in sig.inputs.iter().enumerate() {
+        match arg {
+            FnArg::Receiver(r) => {
+                if i != 0 {
+                    return Err(syn::Error::new_spanned(
+                        r,
+                        "`self` should be first argument",
+                    ));
+                }
+                if r.colon_token.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        r,
+                        "explicit `self` type is not supported, use `&self`, `&mut self` or `self`",
+                    ));
+                }
+                recv = match &r.reference {
+                    Some((and, lifetime)) => {
+                        let mutability = r.mutability;
+                        Recv::Obj(quote!(#and #lifetime #mutability Self))
+                    }
+                    None => Recv::Obj(quote!(Self)),
+                };
+            }
+            FnArg::Typed(arg) => {
+                let Pat::Ident(pat) = arg.pat.as_ref() else {
+                    return Err(syn::Error::new_spanned(
+                        &arg.pat,
+                        "expected argument name, patterns are not supported",
+                    ));
+                };
+                if pat.subpat.is_some() || pat.by_ref.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        pat,
+                        "expected argument name, patterns are not supported",
+                    ));
+                }
+                if let Type::ImplTrait(ty) = arg.ty.as_ref() {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        "`impl Trait` arguments are not supported, use generic parameter",
+                    ));
+                }
+                let ident = &pat.ident;
+                let ty = &arg.ty;
+                tys.push(quote!(#ident: #ty));
+                vars.push(ident.clone());
+            }
+        }
+    }
+    Ok((recv, vars, tys))
+}
+
+fn args_mismatch(sel: &Sel, sig: &Signature, count: usize) -> syn::Error {
+    let expected = sel.args_count();
+    let msg = format!(
+        "selector `{}` expects {expected} argument{}, but `{}` has {count}",
+        sel.name,
+        if expected == 1 { "" } else { "s" },
+        sig.ident
+    );
+    let args: Vec<_> = sig
+        .inputs
+        .iter()
+        .filter(|a| matches!(a, FnArg::Typed(_)))
+        .collect();
+    if args.is_empty() {
+        syn::Error::new(sig.paren_token.span.join(), msg)
+    } else {
+        syn::Error::new_spanned(quote!(#(#args)*), msg)
+    }
+}
+
+pub(crate) fn expand(
+    sel: TokenStream,
+    func: TokenStream,
+    x86_64: bool,
+    debug: bool,
+) -> syn::Result<TokenStream> {
+    let sel = Sel::parse(sel)?;
+    let MsgSendFn {
+        attrs,
+        vis,
+        mut sig,
+    } = syn::parse2(func)?;
+
+    check_sig(&sig)?;
+
+    let mut meta = Vec::with_capacity(attrs.len());
+    let mut optional_already = false;
+    let mut versions = Versions::default();
+    let mut doc_available = false;
+    for attr in attrs {
+        if is_objc_attr(&attr, "msg_send") {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "only one `#[objc::msg_send(...)]` is allowed",
+            ));
+        }
+        if is_objc_attr(&attr, "optional") {
+            optional_already = true;
+        } else if is_objc_attr(&attr, "available") || is_attr(&attr, "api", "available") {
+            versions = attr.parse_args()?;
+            continue;
+        } else if let Some(doc) = doc_str(&attr) {
+            if doc_available {
+                versions = Versions::from_doc_str(&doc);
+            }
+            doc_available = doc == " # Availability";
+        }
+        meta.push(attr);
+    }
+
+    let (recv, vars, arg_tys) = fn_args(&sig)?;
+    if vars.len() != sel.args_count() {
+        return Err(args_mismatch(&sel, &sig, vars.len()));
+    }
+
+    let fn_name = sig.ident.clone();
+    let where_clause = sig.generics.where_clause.take();
+    let generics = &sig.generics;
+    let inputs = &sig.inputs;
+    let output = &sig.output;
+    let unsafety = &sig.unsafety;
+
+    let option = matches!(output, ReturnType::Type(_, ty) if is_option(ty));
+    let mut impl_output = output.clone();
+    let gen_rar_version = !sel.name.starts_with("new") && !sel.name.starts_with("initWith") && {
+        let mut replacer = RarReplacer(false);
+        replacer.visit_return_type_mut(&mut impl_output);
+        replacer.0
+    };
+
+    if debug {
+        let ret = output.to_token_stream();
+        println!("option: {option}, gen_rar_version {gen_rar_version} ret: {ret}");
+    }
+
+    let impl_fn_name = if gen_rar_version {
+        format_ident!("{}_ar", fn_name)
+    } else {
+        fn_name.clone()
+    };
+
+    // attributes the user already wrote are kept as is
+    let doc_alias = if fn_name != sel.name && !has_doc_alias(&meta, &sel.name) {
+        let sel = &sel.name;
+        quote!(#[doc(alias = #sel)])
+    } else {
+        TokenStream::new()
+    };
+    let inline = if meta.iter().any(|a| a.path().is_ident("inline")) {
+        TokenStream::new()
+    } else {
+        quote!(#[inline])
+    };
+
+    let c_void = quote!(*const std::ffi::c_void);
+    let (recv_arg, recv_call) = match &recv {
+        Recv::Cls => (quote!(cls: #c_void), quote!(Self::cls_ptr())),
+        Recv::Obj(ty) => (quote!(id: #ty), quote!(self)),
+    };
+
+    let (sig_args, call) = if x86_64 {
+        (
+            quote!(#recv_arg, imp: #c_void, #(#arg_tys),*),
+            quote!(sig(#recv_call, x86_64_sel, #(#vars),*)),
+        )
+    } else if vars.is_empty() {
+        (quote!(#recv_arg), quote!(sig(#recv_call)))
+    } else {
+        (
+            quote!(#recv_arg, imp: #c_void, #(#arg_tys),*),
+            quote!(sig(#recv_call, std::ptr::null(), #(#vars),*)),
+        )
+    };
+
+    let impl_ret = match &impl_output {
+        ReturnType::Default => TokenStream::new(),
+        ret => ret.to_token_stream(),
+    };
+
+    let (msg_send_decl, sel_decl) = if x86_64 {
+        let c_str = sel.c_str();
+        (
+            quote! {
+                extern "C" {
+                    #[link_name = "objc_msgSend"]
+                    fn msg_send();
+                }
+                extern "C-unwind" {
+                    fn sel_registerName(name: *const i8) -> *const std::ffi::c_void;
+                }
+            },
+            quote!(let x86_64_sel = sel_registerName(#c_str.as_ptr());),
+        )
+    } else {
+        let link_name = format!("objc_msgSend${}", sel.name);
+        (
+            quote! {
+                extern "C" {
+                    #[link_name = #link_name]
+                    fn msg_send();
+                }
+            },
+            TokenStream::new(),
+        )
+    };
+
+    let call_body = quote! {
+        #sel_decl
+        let fn_ptr = msg_send as *const std::ffi::c_void;
+        let sig: extern "C" fn(#sig_args) #impl_ret = std::mem::transmute(fn_ptr);
+
+        #call
+    };
+
+    let available = versions.available_cfg();
+    let unavailable = versions.unavailable_cfg();
+
+    let mut flow = quote! {
+        #available
+        #doc_alias
+        #inline
+        #(#meta)*
+        #vis #unsafety fn #impl_fn_name #generics(#inputs) #impl_output #where_clause {
+            #msg_send_decl
+
+            unsafe {
+                #call_body
+            }
+        }
+    };
+
+    if versions.any() {
+        if !optional_already {
+            flow.extend(sel.getter(&fn_name, quote!(pub)));
+        }
+        let body = if x86_64 {
+            quote!(unsafe { #call_body })
+        } else {
+            call_body
+        };
+        flow.extend(quote! {
+            #unavailable
+            #doc_alias
+            #inline
+            #(#meta)*
+            #vis unsafe fn #impl_fn_name #generics(#inputs) #impl_output #where_clause {
+                #msg_send_decl
+
+                #body
+            }
+        });
+    }
+
+    if gen_rar_version {
+        let ar_fn_name = &impl_fn_name;
+        let recv = match recv {
+            Recv::Cls => quote!(Self::),
+            Recv::Obj(_) => quote!(self.),
+        };
+        let retain = if option {
+            quote!(arc::rar_retain_option)
+        } else {
+            quote!(arc::rar_retain)
+        };
+        let body = quote! {
+            #retain(#recv #ar_fn_name(#(#vars),*))
+        };
+        flow.extend(quote! {
+            #available
+            #doc_alias
+            #inline
+            #(#meta)*
+            #vis #unsafety fn #fn_name #generics(#inputs) #output #where_clause {
+                #body
+            }
+        });
+        if versions.any() {
+            let doc = format!(" Check availability with selector `Self::sel_{fn_name}()`");
+            flow.extend(quote! {
+                #unavailable
+                #doc_alias
+                #[doc = #doc]
+                #inline
+                #(#meta)*
+                #vis unsafe fn #fn_name #generics(#inputs) #output #where_clause {
+                    #body
+                }
+            });
+        }
+    }
+
+    if debug {
+        println!("{flow}");
+    }
+
+    Ok(flow)
+}
+
+/// `#[doc(alias = "..")]` or `#[doc(alias("..", ..))]` with `alias`
+fn has_doc_alias(attrs: &[Attribute], alias: &str) -> bool {
+    attrs.iter().any(|attr| {
+        if !attr.path().is_ident("doc") || !matches!(attr.meta, syn::Meta::List(_)) {
+            return false;
+        }
+        let mut found = false;
+        _ = attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("alias") {
+                return Ok(());
+            }
+            if meta.input.peek(Token![=]) {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                found |= lit.value() == alias;
+            } else {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let lits = content.parse_terminated(<syn::LitStr as Parse>::parse, Token![,])?;
+                found |= lits.iter().any(|lit| lit.value() == alias);
+            }
+            Ok(())
+        });
+        found
+    })
+}
+
+fn doc_str(attr: &Attribute) -> Option<String> {
+    let syn::Meta::NameValue(nv) = &attr.meta else {
+        return None;
+    };
+    if !nv.path.is_ident("doc") {
+        return None;
+    }
+    let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(s),
+        ..
+    }) = &nv.value
+    else {
+        return None;
+    };
+    Some(s.value())
+}
+
+/// Adds `sel_{fn_name}` for `#[objc::optional]` methods of protocols
+pub(crate) fn expand_optional(func: TokenStream) -> syn::Result<TokenStream> {
+    let item: TraitItemFn = syn::parse2(func.clone())?;
+    let Some(sel) = msg_send_attr(&item.attrs)? else {
+        return Err(syn::Error::new(
+            item.sig.fn_token.span(),
+            "`#[objc::optional]` should be followed by `#[objc::msg_send(...)]`",
+        ));
+    };
+    let getter = sel.getter(&item.sig.ident, TokenStream::new());
+    let mut func = func;
+    func.extend(getter);
+    Ok(func)
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{parse_quote, Attribute};
+
+    #[test]
+    fn doc_alias() {
+        let attrs: Vec<Attribute> = vec![
+            parse_quote!(#[doc(hidden)]),
+            parse_quote!(#[doc(alias("count", "length"))]),
+        ];
+        assert!(super::has_doc_alias(&attrs, "length"));
+        assert!(!super::has_doc_alias(&attrs, "size"));
+        let attrs: Vec<Attribute> = vec![
+            parse_quote!(#[doc = " size"]),
+            parse_quote!(#[doc(alias = "size")]),
+        ];
+        assert!(super::has_doc_alias(&attrs, "size"));
+    }
+}
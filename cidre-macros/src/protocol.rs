@@ -0,0 +1,232 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    spanned::Spanned, FnArg, Generics, Ident, ImplItem, ItemImpl, ItemTrait, ReturnType, TraitItem,
+    Type,
+};
+
+use crate::sel::{is_objc_attr, msg_send_attr};
+
+pub(crate) fn expand(args: TokenStream, ts: TokenStream) -> syn::Result<TokenStream> {
+    let protocol_name: Ident = syn::parse2(args).map_err(|e| {
+        syn::Error::new(
+            e.span(),
+            "objc::protocol expects protocol name as first argument",
+        )
+    })?;
+    let tr: ItemTrait = syn::parse2(ts.clone())?;
+    if tr.generics.lt_token.is_some() || tr.generics.where_clause.is_some() {
+        return Err(syn::Error::new_spanned(
+            &tr.generics,
+            "generic protocols are not supported",
+        ));
+    }
+
+    let trait_name = &tr.ident;
+    let impl_trait_name = format_ident!("{}Impl", trait_name);
+
+    let mut impl_trait_functions = Vec::with_capacity(tr.items.len());
+    let mut has_optionals = false;
+    let mut methods = vec![];
+
+    for item in &tr.items {
+        let TraitItem::Fn(f) = item else {
+            continue;
+        };
+        let is_optional = f.attrs.iter().any(|a| is_objc_attr(a, "optional"));
+        let sel = msg_send_attr(&f.attrs)?;
+        has_optionals |= is_optional;
+
+        let fn_name = &f.sig.ident;
+        let generics = &f.sig.generics;
+        let where_clause = &f.sig.generics.where_clause;
+        let output = &f.sig.output;
+        let inputs = &f.sig.inputs;
+
+        let Some(sel) = sel else {
+            if is_optional {
+                return Err(syn::Error::new(
+                    f.sig.fn_token.span(),
+                    "`#[objc::optional]` should be followed by `#[objc::msg_send(...)]`",
+                ));
+            }
+            // not objc method, just mirror it
+            let body = match &f.default {
+                Some(body) => quote!(#body),
+                None => quote!(;),
+            };
+            impl_trait_functions.push(quote! {
+                fn #fn_name #generics(#inputs) #output #where_clause #body
+            });
+            continue;
+        };
+
+        // objc passes `_cmd` right after `self`
+        let (recv, args) = match inputs.first() {
+            Some(FnArg::Receiver(r)) if r.reference.is_some() && r.colon_token.is_none() => {
+                (r, inputs.iter().skip(1))
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    f.sig.paren_token.span.join(),
+                    format!(
+                        "protocol method `{fn_name}` should take `&self` or `&mut self` as first argument"
+                    ),
+                ));
+            }
+        };
+
+        // stub of optional method ignores args, they keep user spans and would warn
+        let (lint, body) = match &f.default {
+            Some(body) => (TokenStream::new(), quote!(#body)),
+            None if is_optional => (
+                quote!(#[allow(unused_variables)]),
+                quote!({ unimplemented!() }),
+            ),
+            None => (TokenStream::new(), quote!(;)),
+        };
+
+        let types = method_types(generics, output, args.clone());
+        let impl_fn_name = format_ident!("impl_{}", fn_name);
+        let args = [quote!(#recv), quote!(_cmd: Option<&objc::Sel>)]
+            .into_iter()
+            .chain(args.map(|a| quote!(#a)));
+        impl_trait_functions.push(quote! {
+            #lint
+            extern "C" fn #impl_fn_name #generics(#(#args),*) #output #where_clause #body
+        });
+
+        if !is_optional {
+            let sel_fn_name = format_ident!("sel_{}", fn_name);
+            let reg_name = sel.reg_name();
+            impl_trait_functions.push(quote! {
+                fn #sel_fn_name() -> &'static objc::Sel { #reg_name }
+            });
+        }
+
+        methods.push((fn_name.clone(), types));
+    }
+
+    let add_methods = if has_optionals {
+        quote!(
+            fn cls_add_methods<O: objc::Obj>(cls: &objc::Class<O>);
+        )
+    } else {
+        add_methods_fn(&methods)
+    };
+
+    let name = protocol_name.to_string();
+    let doc_alias = if *trait_name != name {
+        quote!(#[doc(alias = #name)])
+    } else {
+        TokenStream::new()
+    };
+    let c_name = proc_macro2::Literal::c_string(&std::ffi::CString::new(name).unwrap());
+
+    let attrs = &tr.attrs;
+    let vis = &tr.vis;
+    let unsafety = &tr.unsafety;
+
+    let mut res = ts;
+    res.extend(quote! {
+        #doc_alias
+        #(#attrs)*
+        #vis #unsafety trait #impl_trait_name: #trait_name {
+            #(#impl_trait_functions)*
+
+            #add_methods
+
+            fn cls_add_protocol<O: objc::Obj>(cls: &objc::Class<O>) {
+                unsafe {
+                    let cls: &objc::Class<objc::Id> = std::mem::transmute(cls);
+                    if let Some(proto) = objc::objc_getProtocol(#c_name.as_ptr()) {
+                        cls.add_protocol(proto);
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(res)
+}
+
+/// `Option<CString>` with method types, `None` if some of types doesn't implement `objc::Encode`
+fn method_types<'a>(
+    generics: &Generics,
+    output: &ReturnType,
+    args: impl Iterator<Item = &'a FnArg>,
+) -> TokenStream {
+    let unknown = quote!(None::<std::ffi::CString>);
+    if !generics.params.is_empty() {
+        return unknown;
+    }
+    let probe = |ty: &Type| quote!((&objc::encode::Probe::<#ty>::new()).encoding());
+    let ret = match output {
+        ReturnType::Default => probe(&syn::parse_quote!(())),
+        ReturnType::Type(_, ty) => probe(ty),
+    };
+    let mut probes = vec![];
+    for arg in args {
+        match arg {
+            FnArg::Typed(arg) if !matches!(*arg.ty, Type::ImplTrait(_)) => {
+                probes.push(probe(&arg.ty))
+            }
+            _ => return unknown,
+        }
+    }
+    quote! {{
+        use objc::encode::{ProbeKnown as _, ProbeUnknown as _};
+        objc::encode::method_types(#ret, &[#(#probes),*])
+    }}
+}
+
+fn add_methods_fn(methods: &[(Ident, TokenStream)]) -> TokenStream {
+    let sels = methods.iter().map(|(f, _)| format_ident!("sel_{}", f));
+    let imps = methods.iter().map(|(f, _)| format_ident!("impl_{}", f));
+    let types = methods.iter().map(|(_, types)| types);
+    quote! {
+        fn cls_add_methods<O: objc::Obj>(cls: &objc::Class<O>) {
+            let cls: &objc::Class<objc::Id> = unsafe { std::mem::transmute(cls) };
+            #(
+                let sel = Self::#sels();
+                let types = #types;
+                let types = types.as_ref().map_or(std::ptr::null(), |t| t.as_ptr().cast());
+                unsafe {
+                    let imp: extern "C" fn() = std::mem::transmute(Self::#imps as *const u8);
+                    objc::class_addMethod(cls, sel, imp, types);
+                }
+            )*
+        }
+    }
+}
+
+/// Generates `cls_add_methods` for implemented methods of protocol with optional methods
+pub(crate) fn expand_add_methods(tr_impl: TokenStream) -> syn::Result<TokenStream> {
+    let mut imp: ItemImpl = syn::parse2(tr_impl)?;
+    if imp.trait_.is_none() {
+        return Err(syn::Error::new(
+            imp.impl_token.span,
+            "`#[objc::add_methods]` expects impl of protocol `Impl` trait",
+        ));
+    }
+
+    let mut methods = Vec::with_capacity(imp.items.len());
+    for item in &imp.items {
+        let ImplItem::Fn(f) = item else {
+            continue;
+        };
+        let name = f.sig.ident.to_string();
+        let Some(name) = name.strip_prefix("impl_") else {
+            return Err(syn::Error::new_spanned(
+                &f.sig.ident,
+                format!("expected `impl_` prefix, e.g. `impl_{name}`"),
+            ));
+        };
+        // skip `self` and `_cmd`
+        let types = method_types(&f.sig.generics, &f.sig.output, f.sig.inputs.iter().skip(2));
+        methods.push((Ident::new(name, f.sig.ident.span()), types));
+    }
+
+    imp.items.push(ImplItem::Verbatim(add_methods_fn(&methods)));
+    Ok(quote!(#imp))
+}
@@ -0,0 +1,176 @@
+use std::ffi::CString;
+
+use proc_macro2::{Literal, TokenStream, TokenTree};
+use quote::{format_ident, quote};
+use syn::{Attribute, Ident};
+
+/// Objective-C selector from `#[objc::msg_send(initWithFrame:options:)]`
+pub(crate) struct Sel {
+    pub name: String,
+}
+
+impl Sel {
+    pub fn parse(tokens: TokenStream) -> syn::Result<Self> {
+        let mut name = String::new();
+        let mut expect_colon = false;
+        for tt in tokens.clone() {
+            match tt {
+                TokenTree::Ident(ident) if !expect_colon => {
+                    name.push_str(&ident.to_string());
+                    expect_colon = true;
+                }
+                TokenTree::Punct(p) if p.as_char() == ':' && !name.is_empty() => {
+                    name.push(':');
+                    expect_colon = false;
+                }
+                tt => {
+                    return Err(syn::Error::new_spanned(
+                        tt,
+                        "unexpected token in selector, expected `name` or `name:arg:`",
+                    ));
+                }
+            }
+        }
+
+        if name.is_empty() {
+            return Err(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "expected selector, e.g. `#[objc::msg_send(initWithFrame:)]`",
+            ));
+        }
+
+        if expect_colon && name.contains(':') {
+            return Err(syn::Error::new_spanned(
+                tokens,
+                format!("selector `{name}` should end with `:`"),
+            ));
+        }
+
+        Ok(Self { name })
+    }
+
+    pub fn from_attr(attr: &Attribute) -> syn::Result<Self> {
+        match &attr.meta {
+            syn::Meta::List(list) => Self::parse(list.tokens.clone()),
+            meta => Err(syn::Error::new_spanned(
+                meta,
+                "expected selector, e.g. `#[objc::msg_send(initWithFrame:)]`",
+            )),
+        }
+    }
+
+    pub fn args_count(&self) -> usize {
+        self.name.matches(':').count()
+    }
+
+    pub fn c_str(&self) -> Literal {
+        Literal::c_string(&CString::new(self.name.as_str()).unwrap())
+    }
+
+    /// `unsafe { objc::sel_reg_name(c"sel".as_ptr()) }`
+    pub fn reg_name(&self) -> TokenStream {
+        let c_str = self.c_str();
+        quote!(unsafe { objc::sel_reg_name(#c_str.as_ptr()) })
+    }
+
+    /// `sel_{fn_name}` getter, so user can check selector with `responds_to_sel`
+    pub fn getter(&self, fn_name: &Ident, vis: TokenStream) -> TokenStream {
+        let doc = format!(" `@selector({})` but dynamic", self.name);
+        let sel_fn = format_ident!("sel_{}", fn_name);
+        let reg_name = self.reg_name();
+        quote! {
+            #[doc = #doc]
+            /// use this function to check if object responds to selector
+            #[inline]
+            #vis fn #sel_fn() -> &'static objc::Sel {
+                #reg_name
+            }
+        }
+    }
+}
+
+/// Checks for `#[objc::{name}]` attribute
+pub(crate) fn is_objc_attr(attr: &Attribute, name: &str) -> bool {
+    is_attr(attr, "objc", name)
+}
+
+pub(crate) fn is_attr(attr: &Attribute, ns: &str, name: &str) -> bool {
+    let segments = &attr.path().segments;
+    segments.len() == 2 && segments[0].ident == ns && segments[1].ident == name
+}
+
+/// Finds the only `#[objc::msg_send(...)]` attribute
+pub(crate) fn msg_send_attr(attrs: &[Attribute]) -> syn::Result<Option<Sel>> {
+    let mut res = None;
+    for attr in attrs {
+        if !is_objc_attr(attr, "msg_send") {
+            continue;
+        }
+        if res.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "only one `#[objc::msg_send(...)]` is allowed",
+            ));
+        }
+        res = Some(Sel::from_attr(attr)?);
+    }
+    Ok(res)
+}
+
+// Super simple, but stable upper_case impl
+pub(crate) fn upper_case(str: &str) -> String {
+    let len = str.len();
+    let mut res = Vec::<u8>::with_capacity(len + 10);
+    let bytes = str.as_bytes();
+    let mut was_lowercase = false;
+    for ch in bytes {
+        let is_upper = ch.is_ascii_uppercase();
+        if was_lowercase && is_upper {
+            res.push(b'_');
+        }
+        res.push(ch.to_ascii_uppercase());
+        was_lowercase = !is_upper;
+    }
+
+    String::from_utf8(res).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{upper_case, Sel};
+
+    fn sel(str: &str) -> syn::Result<Sel> {
+        Sel::parse(str.parse().unwrap())
+    }
+
+    #[test]
+    fn basics() {
+        let s = sel("initWithFrame: options:").unwrap();
+        assert_eq!(s.name, "initWithFrame:options:");
+        assert_eq!(s.args_count(), 2);
+
+        let s = sel("new").unwrap();
+        assert_eq!(s.name, "new");
+        assert_eq!(s.args_count(), 0);
+
+        assert_eq!(sel("setValue::").unwrap().args_count(), 2);
+
+        assert!(sel("").is_err());
+        assert!(sel("foo bar").is_err());
+        assert!(sel("foo:bar").is_err());
+        assert!(sel(":foo").is_err());
+        assert!(sel("\"foo\"").is_err());
+    }
+
+    #[test]
+    fn upper() {
+        assert_eq!(
+            upper_case("kCVPixelBufferWidthKey"),
+            "K_CVPIXEL_BUFFER_WIDTH_KEY"
+        );
+        assert_eq!(
+            upper_case("SCStreamFrameInfoStatus"),
+            "SCSTREAM_FRAME_INFO_STATUS"
+        );
+    }
+}
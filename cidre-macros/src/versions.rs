@@ -0,0 +1,212 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    Ident, Lit, Token,
+};
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Version(pub u32, pub u32);
+
+impl Version {
+    /// Parses `15.0` or `10_15` forms
+    pub fn from_str(str: &str) -> Option<Self> {
+        let (major, minor) = str.split_once('.').or_else(|| str.split_once('_'))?;
+        Some(Self(major.parse().ok()?, minor.parse().ok()?))
+    }
+}
+
+#[derive(Default, Debug, PartialEq, Eq)]
+pub(crate) struct Versions {
+    pub macos: Option<Version>,
+    pub ios: Option<Version>,
+    pub tvos: Option<Version>,
+    pub watchos: Option<Version>,
+    pub visionos: Option<Version>,
+    pub maccatalyst: Option<Version>,
+}
+
+impl Versions {
+    pub fn any(&self) -> bool {
+        self.macos.is_some()
+            || self.ios.is_some()
+            || self.tvos.is_some()
+            || self.watchos.is_some()
+            || self.visionos.is_some()
+            || self.maccatalyst.is_some()
+    }
+
+    /// (platform, target_os, target_abi, version) in the order cfgs and docs are generated
+    fn entries(&self) -> impl Iterator<Item = (&'static str, &'static str, bool, Version)> {
+        [
+            ("macos", "macos", false, self.macos),
+            ("ios", "ios", false, self.ios),
+            ("tvos", "tvos", false, self.tvos),
+            ("watchos", "watchos", false, self.watchos),
+            ("visionos", "visionos", false, self.visionos),
+            ("maccatalyst", "ios", true, self.maccatalyst),
+        ]
+        .into_iter()
+        .filter_map(|(platform, os, macabi, v)| v.map(|v| (platform, os, macabi, v)))
+    }
+
+    fn features(&self) -> Vec<String> {
+        self.entries()
+            .map(|(platform, _, _, v)| format!("{platform}_{}_{}", v.0, v.1))
+            .collect()
+    }
+
+    fn cfg(&self, available: bool) -> TokenStream {
+        let preds: Vec<_> = self
+            .entries()
+            .map(|(platform, os, macabi, v)| {
+                let feature = format!("{platform}_{}_{}", v.0, v.1);
+                let feature = if available {
+                    quote!(feature = #feature)
+                } else {
+                    quote!(not(feature = #feature))
+                };
+                if macabi {
+                    quote!(all(target_os = #os, target_abi = "macabi", #feature))
+                } else {
+                    quote!(all(target_os = #os, #feature))
+                }
+            })
+            .collect();
+
+        match preds.len() {
+            0 => TokenStream::new(),
+            1 => quote!(#[cfg(#(#preds)*)]),
+            _ => quote!(#[cfg(any(#(#preds),*))]),
+        }
+    }
+
+    pub fn available_cfg(&self) -> TokenStream {
+        self.cfg(true)
+    }
+
+    pub fn unavailable_cfg(&self) -> TokenStream {
+        self.cfg(false)
+    }
+
+    fn doc(&self, prefix: &str) -> TokenStream {
+        let features = self.features();
+        if features.is_empty() {
+            return TokenStream::new();
+        }
+        let line = format!(" {prefix}{}", features.join(", "));
+        quote! {
+            #[doc = " # Availability"]
+            #[doc = #line]
+        }
+    }
+
+    pub fn available_doc(&self) -> TokenStream {
+        self.doc("")
+    }
+
+    pub fn unavailable_doc(&self) -> TokenStream {
+        self.doc("Not ")
+    }
+
+    /// Parses versions back from doc generated by `available_doc`
+    pub fn from_doc_str(str: &str) -> Self {
+        let mut res = Self::default();
+        for str in str.split_whitespace() {
+            for str in str.split_terminator(',') {
+                if let Some(v) = str.strip_prefix("macos_") {
+                    res.macos = Version::from_str(v);
+                } else if let Some(v) = str.strip_prefix("ios_") {
+                    res.ios = Version::from_str(v);
+                } else if let Some(v) = str.strip_prefix("tvos_") {
+                    res.tvos = Version::from_str(v);
+                } else if let Some(v) = str.strip_prefix("watchos_") {
+                    res.watchos = Version::from_str(v);
+                } else if let Some(v) = str.strip_prefix("visionos_") {
+                    res.visionos = Version::from_str(v);
+                } else if let Some(v) = str.strip_prefix("maccatalyst_") {
+                    res.maccatalyst = Version::from_str(v);
+                }
+            }
+        }
+        res
+    }
+}
+
+/// `macos = 15.0, ios = 18.0`
+impl Parse for Versions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut versions = Self::default();
+        while !input.is_empty() {
+            let platform: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let lit: Lit = input.parse()?;
+            let str = match &lit {
+                Lit::Float(f) if f.suffix().is_empty() => f.base10_digits().to_string(),
+                Lit::Int(i) if i.suffix().is_empty() => i.to_string(),
+                _ => String::new(),
+            };
+            let Some(v) = Version::from_str(&str) else {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "expected version in `major.minor` form, e.g. `15.0`",
+                ));
+            };
+            let slot = match platform.to_string().as_str() {
+                "macos" => &mut versions.macos,
+                "ios" => &mut versions.ios,
+                "tvos" => &mut versions.tvos,
+                "watchos" => &mut versions.watchos,
+                "visionos" => &mut versions.visionos,
+                "maccatalyst" => &mut versions.maccatalyst,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        platform,
+                        "unsupported platform, expected one of: macos, ios, tvos, watchos, visionos, maccatalyst",
+                    ));
+                }
+            };
+            if slot.is_some() {
+                return Err(syn::Error::new_spanned(
+                    platform,
+                    "platform is already specified",
+                ));
+            }
+            *slot = Some(v);
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Version, Versions};
+
+    #[test]
+    fn parse() {
+        let v: Versions = syn::parse_str("macos = 15.0, ios = 10_3, maccatalyst = 14.5").unwrap();
+        assert_eq!(v.macos, Some(Version(15, 0)));
+        assert_eq!(v.ios, Some(Version(10, 3)));
+        assert_eq!(v.maccatalyst, Some(Version(14, 5)));
+        assert!(v.tvos.is_none());
+        assert!(v.any());
+
+        assert!(syn::parse_str::<Versions>("linux = 1.0").is_err());
+        assert!(syn::parse_str::<Versions>("macos = 15").is_err());
+        assert!(syn::parse_str::<Versions>("macos = 15.0, macos = 14.0").is_err());
+        assert!(!syn::parse_str::<Versions>("").unwrap().any());
+    }
+
+    #[test]
+    fn doc_round_trip() {
+        let v: Versions = syn::parse_str("macos = 14.0, visionos = 1.1").unwrap();
+        let doc = v.available_doc().to_string();
+        assert!(doc.contains("macos_14_0, visionos_1_1"));
+        assert_eq!(Versions::from_doc_str(" macos_14_0, visionos_1_1"), v);
+    }
+}
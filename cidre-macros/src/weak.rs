@@ -0,0 +1,84 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{Attribute, ForeignItem, Ident, ItemForeignMod, Type};
+
+use crate::{
+    sel::{is_attr, upper_case},
+    versions::Versions,
+};
+
+fn versions(attrs: &[Attribute]) -> syn::Result<Option<Versions>> {
+    let mut res = None;
+    for attr in attrs {
+        if attr.path().is_ident("available")
+            || is_attr(attr, "api", "available")
+            || is_attr(attr, "objc", "available")
+        {
+            res = Some(attr.parse_args()?);
+        }
+    }
+    Ok(res)
+}
+
+/// Adds `api::DlSym` statics for symbols which are not available on the deployment target
+pub(crate) fn expand(body: TokenStream) -> syn::Result<TokenStream> {
+    let block: ItemForeignMod = syn::parse2(body.clone()).map_err(|e| {
+        syn::Error::new(
+            e.span(),
+            "`#[api::weak]` expects `extern \"C\" { ... }` block",
+        )
+    })?;
+
+    let mut vars: Vec<(Versions, &Ident, TokenStream)> = Vec::new(); // Version, Name, Type
+    for item in &block.items {
+        match item {
+            ForeignItem::Static(s) => {
+                let Some(versions) = versions(&s.attrs)? else {
+                    continue;
+                };
+                let ty = match s.ty.as_ref() {
+                    Type::Reference(r)
+                        if r.lifetime.as_ref().is_some_and(|l| l.ident == "static") =>
+                    {
+                        r.elem.to_token_stream()
+                    }
+                    ty => {
+                        return Err(syn::Error::new_spanned(
+                            ty,
+                            "weak statics should have `&'static T` type",
+                        ));
+                    }
+                };
+                vars.push((versions, &s.ident, ty));
+            }
+            ForeignItem::Fn(f) => {
+                let Some(versions) = versions(&f.attrs)? else {
+                    continue;
+                };
+                let inputs = &f.sig.inputs;
+                let output = &f.sig.output;
+                let variadic = &f.sig.variadic;
+                vars.push((
+                    versions,
+                    &f.sig.ident,
+                    quote!(extern "C" fn(#inputs #variadic) #output),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let vars = vars.iter().map(|(versions, name, ty)| {
+        let upper_name = Ident::new(&upper_case(&name.to_string()), name.span());
+        let availability = versions.unavailable_cfg();
+        let c_name = Literal::c_string(&std::ffi::CString::new(name.to_string()).unwrap());
+        quote! {
+            #availability
+            static #upper_name: api::DlSym<#ty> = api::DlSym::new(#c_name);
+        }
+    });
+
+    let mut res = body;
+    res.extend(vars);
+    Ok(res)
+}
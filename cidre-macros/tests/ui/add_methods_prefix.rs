@@ -0,0 +1,12 @@
+use cidre_macros as objc;
+
+trait FooDelegateImpl {}
+
+struct Foo;
+
+#[objc::add_methods]
+impl FooDelegateImpl for Foo {
+    extern "C" fn foo_did_finish(&mut self, _cmd: Option<&()>, foo: usize) {}
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use cidre_macros::api_available;
+
+#[api_available(macos = 15.0)]
+pub fn shared() {
+    {}
+}
+
+fn main() {}
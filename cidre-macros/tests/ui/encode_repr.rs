@@ -0,0 +1,9 @@
+use cidre_macros::Encode;
+
+#[derive(Encode)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use cidre_macros::Encode;
+
+#[derive(Encode)]
+#[encode(name = "CGFloat")]
+#[repr(transparent)]
+struct Float(f64);
+
+fn main() {}
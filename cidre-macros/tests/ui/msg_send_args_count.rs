@@ -0,0 +1,10 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(setValue:forKey:)]
+    pub fn set_value(&mut self, value: usize);
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(initWithFrame:options)]
+    pub fn init_with_frame(&self, frame: f64, options: usize) -> usize;
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(count)]
+    #[objc::available(macos = 15, ios = 18.0)]
+    pub fn count(&self) -> usize;
+
+    #[objc::msg_send(length)]
+    #[objc::available(linux = 6.0)]
+    pub fn len(&self) -> usize;
+}
+
+fn main() {}
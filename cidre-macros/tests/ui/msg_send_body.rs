@@ -0,0 +1,12 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(count)]
+    pub fn count(&self) -> usize {
+        0
+    }
+}
+
+fn main() {}
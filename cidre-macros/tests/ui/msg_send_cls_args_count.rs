@@ -0,0 +1,10 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(stringWithString:)]
+    pub fn with_string() -> usize;
+}
+
+fn main() {}
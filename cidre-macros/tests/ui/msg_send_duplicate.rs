@@ -0,0 +1,11 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(count)]
+    #[objc::msg_send(length)]
+    pub fn count(&self) -> usize;
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(objectEnumerator)]
+    pub fn iter(&self) -> impl Iterator<Item = usize>;
+}
+
+fn main() {}
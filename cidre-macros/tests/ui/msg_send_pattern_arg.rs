@@ -0,0 +1,10 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(setSize:)]
+    pub fn set_size(&mut self, (w, h): (f64, f64));
+}
+
+fn main() {}
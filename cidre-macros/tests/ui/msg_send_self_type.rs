@@ -0,0 +1,10 @@
+use cidre_macros as objc;
+
+struct Foo;
+
+impl Foo {
+    #[objc::msg_send(count)]
+    pub fn count(self: &Self) -> usize;
+}
+
+fn main() {}
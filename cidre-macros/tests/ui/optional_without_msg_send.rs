@@ -0,0 +1,9 @@
+use cidre_macros as objc;
+
+#[objc::protocol(NSFooDelegate)]
+pub trait FooDelegate {
+    #[objc::optional]
+    fn foo_did_finish(&mut self, foo: usize);
+}
+
+fn main() {}
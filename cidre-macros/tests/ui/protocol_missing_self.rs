@@ -0,0 +1,9 @@
+use cidre_macros as objc;
+
+#[objc::protocol(NSFooDelegate)]
+pub trait FooDelegate {
+    #[objc::msg_send(fooDidFinish:)]
+    fn foo_did_finish(foo: usize);
+}
+
+fn main() {}
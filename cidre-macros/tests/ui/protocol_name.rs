@@ -0,0 +1,6 @@
+use cidre_macros as objc;
+
+#[objc::protocol("NSFooDelegate")]
+pub trait FooDelegate {}
+
+fn main() {}
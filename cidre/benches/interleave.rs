@@ -99,6 +99,21 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         })
     });
 
+    #[cfg(feature = "media")]
+    {
+        lr.fill(0.0f32);
+
+        let mut converter = cidre::media::pcm::PcmConverter::new(&src_asbd, &dst_asbd).unwrap();
+        c.bench_function("interleave with media::pcm::PcmConverter", |b| {
+            b.iter(|| unsafe {
+                converter
+                    .convert_buf_list(N as u32, &list_a, &mut list_b)
+                    .unwrap();
+            });
+            assert_eq!(lr, res);
+        });
+    }
+
     lr.fill(0.0f32);
 
     c.bench_function("interleave rust", |b| {
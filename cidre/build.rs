@@ -206,23 +206,32 @@ fn parse_deployment_targets() -> DeploymentTargets {
 
     let mut res = DeploymentTargets::default();
 
-    if let Some(v) = Version::with_table(&macos) {
+    // exposed to the crate as api::DEPLOYMENT_TARGETS
+    let export = |platform: &str, v: Version| {
+        println!(
+            "cargo:rustc-env=CIDRE_DEPLOYMENT_TARGET_{platform}={}",
+            v.to_string()
+        );
+        v
+    };
+
+    if let Some(v) = Version::with_table(&macos).map(|v| export("MACOS", v)) {
         res.macos = format!("MACOSX_DEPLOYMENT_TARGET={}", v.to_string());
     }
-    if let Some(v) = Version::with_table(&ios) {
+    if let Some(v) = Version::with_table(&ios).map(|v| export("IOS", v)) {
         res.ios = format!("IPHONEOS_DEPLOYMENT_TARGET={}", v.to_string());
     }
-    if let Some(v) = Version::with_table(&tvos) {
+    if let Some(v) = Version::with_table(&tvos).map(|v| export("TVOS", v)) {
         res.tvos = format!("TVOS_DEPLOYMENT_TARGET={}", v.to_string());
     }
-    if let Some(v) = Version::with_table(&watchos) {
+    if let Some(v) = Version::with_table(&watchos).map(|v| export("WATCHOS", v)) {
         res.watchos = format!("WATCHOS_DEPLOYMENT_TARGET={}", v.to_string());
     }
-    if let Some(v) = Version::with_table(&maccatalyst) {
+    if let Some(v) = Version::with_table(&maccatalyst).map(|v| export("MACCATALYST", v)) {
         // TODO: investigate
         res.ios = format!("IPHONEOS_DEPLOYMENT_TARGET={}", v.to_string());
     }
-    if let Some(v) = Version::with_table(&visionos) {
+    if let Some(v) = Version::with_table(&visionos).map(|v| export("VISIONOS", v)) {
         res.visionos = format!("XROS_DEPLOYMENT_TARGET={}", v.to_string());
     }
 
@@ -232,6 +241,11 @@ fn parse_deployment_targets() -> DeploymentTargets {
 fn main() {
     let versions = parse_deployment_targets();
 
+    // nothing to link or configure off Apple platforms (portable modules only)
+    if env::var("CARGO_CFG_TARGET_VENDOR").as_deref() != Ok("apple") {
+        return;
+    }
+
     let sdk = match env::var("TARGET").unwrap().as_ref() {
         "aarch64-apple-darwin" | "x86_64-apple-darwin" => "macosx",
         "aarch64-apple-ios" => "iphoneos",
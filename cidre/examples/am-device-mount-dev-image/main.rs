@@ -4,7 +4,7 @@ mod macos {
 
     use cidre::{
         am::{self, device::discovery::NotificationInfo},
-        cf,
+        gdb,
     };
 
     const DEVICE_ID: &str = "00008120-000168A20210C01E";
@@ -58,29 +58,10 @@ mod macos {
         println!("disk mounted");
         let connection = session.start_debug_server().expect("debug");
 
-        unsafe {
-            extern "C" fn cb(
-                _s: &cf::Socket,
-                _cb_type: cf::SocketCbType,
-                _address: &cf::Data,
-                _data: *const u8,
-                _info: *mut c_void,
-            ) {
-                println!("??????????")
-            }
-            let sock = cf::Socket::create_with_native(
-                connection.socket().unwrap(),
-                cf::SocketCbType::READ,
-                cb,
-                None,
-            )
-            .unwrap();
-            let source = sock.run_loop_src(0).unwrap();
-            cf::RunLoop::main().add_src(&source, cf::RunLoopMode::common());
-            // source.invalidate();
-
-            let n = connection.send(&[1]).unwrap();
-            println!("sent {}", n);
+        let mut client = gdb::Client::new(connection);
+        client.start_no_ack_mode().expect("no ack mode");
+        for (key, value) in client.host_info().expect("host info") {
+            println!("{key}: {value}");
         }
 
         // println!("pre send");
@@ -99,8 +80,6 @@ mod macos {
         //     std::ptr::null_mut(),
         // )
         // .unwrap();
-
-        cf::RunLoop::run();
     }
 }
 
@@ -140,6 +140,25 @@ impl ServiceConnection {
     }
 }
 
+/// Blocking byte stream, for protocol clients like `gdb::Client`
+impl std::io::Read for crate::arc::R<ServiceConnection> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.recv(buf)
+            .map_err(|_| std::io::Error::other("service connection receive failed"))
+    }
+}
+
+impl std::io::Write for crate::arc::R<ServiceConnection> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.send(buf)
+            .map_err(|_| std::io::Error::other("service connection send failed"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 unsafe extern "C" {
     fn AMDServiceConnectionGetSocket(connection: &ServiceConnection) -> RawFd;
 
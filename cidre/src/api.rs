@@ -89,28 +89,107 @@ impl<T> DlSym<T> {
 
 unsafe impl<T> Sync for DlSym<T> {}
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+mod availability;
+pub use availability::Availability;
+pub use availability::DEPLOYMENT_TARGETS;
+pub use availability::Versions;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct OsVersion {
     pub major: isize,
     pub minor: isize,
     pub patch: isize,
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 #[repr(u32)]
 pub enum Platform {
     MacOs = 1,
     IOs = 2,
     TvOs = 3,
     WatchOs = 4,
+    MacCatalyst = 6,
     // DriverKit = 10,
     VisionOs = 11,
 }
 
+impl Platform {
+    /// Platform of the compilation target, `None` off Apple platforms.
+    pub const CURRENT: Option<Self> = if cfg!(target_os = "macos") {
+        Some(Self::MacOs)
+    } else if cfg!(all(target_os = "ios", target_abi = "macabi")) {
+        Some(Self::MacCatalyst)
+    } else if cfg!(target_os = "ios") {
+        Some(Self::IOs)
+    } else if cfg!(target_os = "tvos") {
+        Some(Self::TvOs)
+    } else if cfg!(target_os = "watchos") {
+        Some(Self::WatchOs)
+    } else if cfg!(target_os = "visionos") {
+        Some(Self::VisionOs)
+    } else {
+        None
+    };
+}
+
 unsafe extern "C" {
     fn __isPlatformVersionAtLeast(platform: u32, major: u32, minor: u32, patch: u32) -> i32;
 }
 
 impl OsVersion {
+    #[inline]
+    pub const fn new(major: isize, minor: isize, patch: isize) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses `15`, `15.2` or `15.2.1`, usable in const context.
+    pub const fn parse(s: &str) -> Result<Self, VersionError> {
+        let bytes = s.as_bytes();
+        let mut parts = [0isize; 3];
+        let mut part = 0;
+        let mut digits = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            let b = bytes[i];
+            if b == b'.' {
+                if digits == 0 || part == 2 {
+                    return Err(VersionError);
+                }
+                part += 1;
+                digits = 0;
+            } else if b.is_ascii_digit() {
+                let Some(v) = parts[part].checked_mul(10) else {
+                    return Err(VersionError);
+                };
+                let Some(v) = v.checked_add((b - b'0') as isize) else {
+                    return Err(VersionError);
+                };
+                parts[part] = v;
+                digits += 1;
+            } else {
+                return Err(VersionError);
+            }
+            i += 1;
+        }
+        if digits == 0 {
+            return Err(VersionError);
+        }
+        Ok(Self::new(parts[0], parts[1], parts[2]))
+    }
+
+    /// Same as [`Self::parse`] but panics (fails compilation in const context)
+    /// on invalid input. Used with `stringify!` of version literals.
+    pub const fn from_lit(s: &str) -> Self {
+        match Self::parse(s) {
+            Ok(v) => v,
+            Err(_) => panic!("invalid version literal"),
+        }
+    }
+
     #[inline]
     pub fn platform_at_least(&self, platform: Platform) -> bool {
         unsafe {
@@ -161,40 +240,7 @@ impl FromStr for OsVersion {
     type Err = VersionError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let Some((major, rest)) = s.split_once('.') else {
-            let Ok(major) = s.parse::<isize>() else {
-                return Err(VersionError);
-            };
-            return Ok(Self {
-                major,
-                minor: 0,
-                patch: 0,
-            });
-        };
-        let Ok(major) = major.parse::<isize>() else {
-            return Err(VersionError);
-        };
-        if let Some((minor, patch)) = rest.split_once('.') {
-            let Ok(minor) = minor.parse::<isize>() else {
-                return Err(VersionError);
-            };
-            let Ok(patch) = patch.parse::<isize>() else {
-                return Err(VersionError);
-            };
-            return Ok(Self {
-                major,
-                minor,
-                patch,
-            });
-        };
-        let Ok(minor) = rest.parse::<isize>() else {
-            return Err(VersionError);
-        };
-        Ok(Self {
-            major,
-            minor,
-            patch: 0,
-        })
+        Self::parse(s)
     }
 }
 
@@ -249,22 +295,22 @@ pub fn maccatalyst_available(_ver: &str) -> bool {
 #[macro_export]
 macro_rules! version {
     (macos = $ver:literal) => {
-        $crate::api::macos_available(stringify!($ver))
+        $crate::api::availability!(macos = $ver).is_available()
     };
     (ios = $ver:literal) => {
-        $crate::api::ios_available(stringify!($ver))
+        $crate::api::availability!(ios = $ver).is_available()
     };
     (tvos = $ver:literal) => {
-        $crate::api::tvos_available(stringify!($ver))
+        $crate::api::availability!(tvos = $ver).is_available()
     };
     (watchos = $ver:literal) => {
-        $crate::api::watchos_available(stringify!($ver))
+        $crate::api::availability!(watchos = $ver).is_available()
     };
     (visionos = $ver:literal) => {
-        $crate::api::visionos_available(stringify!($ver))
+        $crate::api::availability!(visionos = $ver).is_available()
     };
     (maccatalyst = $ver:literal) => {
-        $crate::api::maccatalyst_available(stringify!($ver))
+        $crate::api::availability!(maccatalyst = $ver).is_available()
     };
     (
         $(macos = $macos_ver:literal)?
@@ -322,11 +368,33 @@ macro_rules! version {
         $( || $crate::api::version!(visionos = $visionos_ver))?
     };
 }
+
+/// [`Availability`] of a binding declared with the same arguments as `#[api::available]`.
+///
+/// ```
+/// let availability = cidre::api::availability!(macos = 15.0, ios = 18.0);
+/// if availability.is_available() {
+///     // call it
+/// }
+/// ```
+#[macro_export]
+macro_rules! availability {
+    ($($platform:ident = $ver:literal),+ $(,)?) => {
+        const {
+            $crate::api::Availability::current(
+                &$crate::api::Versions::new()
+                    $(.$platform($crate::api::OsVersion::from_lit(stringify!($ver))))+
+            )
+        }
+    };
+}
+
+pub use crate::availability;
 pub use cidre_macros::api_available as available;
 pub use cidre_macros::api_weak as weak;
 pub use version;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "ns"))]
 mod tests {
     use crate::{api, ns};
 
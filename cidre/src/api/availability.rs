@@ -0,0 +1,333 @@
+use std::sync::OnceLock;
+
+use super::{OsVersion, Platform};
+
+/// Minimum OS versions per platform, the `#[api::available(...)]` arguments.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Versions {
+    pub macos: Option<OsVersion>,
+    pub ios: Option<OsVersion>,
+    pub maccatalyst: Option<OsVersion>,
+    pub tvos: Option<OsVersion>,
+    pub watchos: Option<OsVersion>,
+    pub visionos: Option<OsVersion>,
+}
+
+impl Versions {
+    pub const fn new() -> Self {
+        Self {
+            macos: None,
+            ios: None,
+            maccatalyst: None,
+            tvos: None,
+            watchos: None,
+            visionos: None,
+        }
+    }
+
+    pub const fn macos(mut self, ver: OsVersion) -> Self {
+        self.macos = Some(ver);
+        self
+    }
+
+    pub const fn ios(mut self, ver: OsVersion) -> Self {
+        self.ios = Some(ver);
+        self
+    }
+
+    pub const fn maccatalyst(mut self, ver: OsVersion) -> Self {
+        self.maccatalyst = Some(ver);
+        self
+    }
+
+    pub const fn tvos(mut self, ver: OsVersion) -> Self {
+        self.tvos = Some(ver);
+        self
+    }
+
+    pub const fn watchos(mut self, ver: OsVersion) -> Self {
+        self.watchos = Some(ver);
+        self
+    }
+
+    pub const fn visionos(mut self, ver: OsVersion) -> Self {
+        self.visionos = Some(ver);
+        self
+    }
+
+    pub const fn get(&self, platform: Platform) -> Option<OsVersion> {
+        match platform {
+            Platform::MacOs => self.macos,
+            Platform::IOs => self.ios,
+            Platform::MacCatalyst => self.maccatalyst,
+            Platform::TvOs => self.tvos,
+            Platform::WatchOs => self.watchos,
+            Platform::VisionOs => self.visionos,
+        }
+    }
+}
+
+const fn target(var: Option<&str>) -> Option<OsVersion> {
+    match var {
+        Some(ver) => Some(OsVersion::from_lit(ver)),
+        None => None,
+    }
+}
+
+/// Deployment targets selected with `macos_15_0`, `ios_18_0`, ... features.
+///
+/// Bindings marked with `#[api::available]` at or below these versions are
+/// linked directly, newer ones are weak and need a runtime check.
+pub const DEPLOYMENT_TARGETS: Versions = Versions {
+    macos: target(option_env!("CIDRE_DEPLOYMENT_TARGET_MACOS")),
+    ios: target(option_env!("CIDRE_DEPLOYMENT_TARGET_IOS")),
+    maccatalyst: target(option_env!("CIDRE_DEPLOYMENT_TARGET_MACCATALYST")),
+    tvos: target(option_env!("CIDRE_DEPLOYMENT_TARGET_TVOS")),
+    watchos: target(option_env!("CIDRE_DEPLOYMENT_TARGET_WATCHOS")),
+    visionos: target(option_env!("CIDRE_DEPLOYMENT_TARGET_VISIONOS")),
+};
+
+const fn at_least(ver: OsVersion, min: OsVersion) -> bool {
+    if ver.major != min.major {
+        return ver.major > min.major;
+    }
+    if ver.minor != min.minor {
+        return ver.minor > min.minor;
+    }
+    ver.patch >= min.patch
+}
+
+const fn deployed(required: Option<OsVersion>, deployment: Option<OsVersion>) -> bool {
+    match (required, deployment) {
+        (Some(required), Some(deployment)) => at_least(deployment, required),
+        _ => false,
+    }
+}
+
+/// How a binding with given [`Versions`] can be used on a target.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Availability {
+    /// Deployment target is new enough, the binding is always there.
+    Static,
+    /// The binding is weak, running OS must be at least this version.
+    Runtime(Platform, OsVersion),
+    /// The binding does not exist on the platform.
+    Unavailable,
+}
+
+impl Availability {
+    /// Mirrors `#[api::available]`: iOS versions apply to Mac Catalyst too
+    /// when it is not listed explicitly.
+    pub const fn resolve(
+        required: &Versions,
+        platform: Option<Platform>,
+        deployment: &Versions,
+    ) -> Self {
+        let Some(platform) = platform else {
+            return Self::Unavailable;
+        };
+        if deployed(required.get(platform), deployment.get(platform)) {
+            return Self::Static;
+        }
+        let catalyst = matches!(platform, Platform::MacCatalyst);
+        if catalyst && required.maccatalyst.is_none() && deployed(required.ios, deployment.ios) {
+            return Self::Static;
+        }
+        match required.get(platform) {
+            Some(ver) => Self::Runtime(platform, ver),
+            None => match required.ios {
+                Some(ver) if catalyst => Self::Runtime(platform, ver),
+                _ => Self::Unavailable,
+            },
+        }
+    }
+
+    /// Availability for the compilation target and [`DEPLOYMENT_TARGETS`].
+    pub const fn current(required: &Versions) -> Self {
+        Self::resolve(required, Platform::CURRENT, &DEPLOYMENT_TARGETS)
+    }
+
+    #[inline]
+    pub const fn is_static(&self) -> bool {
+        matches!(self, Self::Static)
+    }
+
+    /// Decision for an OS of version `os`.
+    #[inline]
+    pub const fn is_available_on(&self, os: OsVersion) -> bool {
+        match self {
+            Self::Static => true,
+            Self::Runtime(_, min) => at_least(os, *min),
+            Self::Unavailable => false,
+        }
+    }
+
+    /// Decision for the running OS, see [`OsVersion::current`].
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        match self {
+            Self::Static => true,
+            Self::Runtime(..) => match OsVersion::current() {
+                Some(os) => self.is_available_on(os),
+                None => false,
+            },
+            Self::Unavailable => false,
+        }
+    }
+}
+
+impl OsVersion {
+    /// Version of the running OS, queried once.
+    ///
+    /// On Mac Catalyst it is the iOS version the system supports.
+    /// `None` off Apple platforms.
+    pub fn current() -> Option<Self> {
+        static CURRENT: OnceLock<Option<OsVersion>> = OnceLock::new();
+        *CURRENT.get_or_init(query)
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+fn query() -> Option<OsVersion> {
+    use std::ffi::{CStr, c_char, c_void};
+
+    unsafe extern "C" {
+        fn sysctlbyname(
+            name: *const c_char,
+            oldp: *mut c_void,
+            oldlenp: *mut usize,
+            newp: *mut c_void,
+            newlen: usize,
+        ) -> i32;
+    }
+
+    let name = if cfg!(all(target_os = "ios", target_abi = "macabi")) {
+        c"kern.iossupportversion"
+    } else {
+        c"kern.osproductversion"
+    };
+
+    let mut buf = [0u8; 32];
+    let mut len = buf.len();
+    let res = unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            &mut len,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if res != 0 {
+        return None;
+    }
+    let str = CStr::from_bytes_until_nul(&buf[..len.min(buf.len())]).ok()?;
+    OsVersion::parse(str.to_str().ok()?).ok()
+}
+
+#[cfg(not(target_vendor = "apple"))]
+fn query() -> Option<OsVersion> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{self, Availability, OsVersion, Platform, Versions};
+
+    const fn v(major: isize, minor: isize) -> OsVersion {
+        OsVersion::new(major, minor, 0)
+    }
+
+    #[test]
+    fn parse() {
+        const V: OsVersion = OsVersion::from_lit("15.2");
+        assert_eq!(V, v(15, 2));
+        assert_eq!(OsVersion::parse("15").unwrap(), v(15, 0));
+        assert_eq!(
+            OsVersion::parse("10.15.7").unwrap(),
+            OsVersion::new(10, 15, 7)
+        );
+        assert_eq!("14.4".parse::<OsVersion>().unwrap(), v(14, 4));
+        for s in [
+            "",
+            ".",
+            "15.",
+            ".1",
+            "1.2.3.4",
+            "a.1",
+            "-1",
+            "99999999999999999999",
+        ] {
+            assert!(OsVersion::parse(s).is_err(), "{s}");
+        }
+        assert!(v(10, 15) < v(11, 0));
+        assert!(OsVersion::new(15, 0, 1) > v(15, 0));
+    }
+
+    #[test]
+    fn resolve() {
+        let required = Versions::new().macos(v(15, 0)).ios(v(18, 0));
+        let deployment = Versions::new().macos(v(14, 0)).ios(v(18, 2));
+
+        let mac = Availability::resolve(&required, Some(Platform::MacOs), &deployment);
+        assert_eq!(mac, Availability::Runtime(Platform::MacOs, v(15, 0)));
+        assert!(!mac.is_static());
+        assert!(mac.is_available_on(v(15, 0)));
+        assert!(mac.is_available_on(OsVersion::new(15, 0, 1)));
+        assert!(!mac.is_available_on(v(14, 7)));
+
+        let ios = Availability::resolve(&required, Some(Platform::IOs), &deployment);
+        assert_eq!(ios, Availability::Static);
+        assert!(ios.is_available_on(v(1, 0)));
+
+        let tv = Availability::resolve(&required, Some(Platform::TvOs), &deployment);
+        assert_eq!(tv, Availability::Unavailable);
+        assert!(!tv.is_available_on(v(99, 0)));
+
+        let none = Availability::resolve(&required, None, &deployment);
+        assert_eq!(none, Availability::Unavailable);
+
+        // no deployment target means a runtime check
+        let mac = Availability::resolve(&required, Some(Platform::MacOs), &Versions::new());
+        assert_eq!(mac, Availability::Runtime(Platform::MacOs, v(15, 0)));
+
+        // ios applies to catalyst unless listed
+        let cat = Versions::new().maccatalyst(v(18, 0));
+        let res = Availability::resolve(&required, Some(Platform::MacCatalyst), &cat);
+        assert_eq!(res, Availability::Runtime(Platform::MacCatalyst, v(18, 0)));
+        let res = Availability::resolve(&required, Some(Platform::MacCatalyst), &deployment);
+        assert_eq!(res, Availability::Static);
+        let required = required.maccatalyst(v(18, 1));
+        let res = Availability::resolve(&required, Some(Platform::MacCatalyst), &cat);
+        assert_eq!(res, Availability::Runtime(Platform::MacCatalyst, v(18, 1)));
+        // listed catalyst wins over a deployed ios
+        let both = deployment.maccatalyst(v(18, 0));
+        let res = Availability::resolve(&required, Some(Platform::MacCatalyst), &both);
+        assert_eq!(res, Availability::Runtime(Platform::MacCatalyst, v(18, 1)));
+    }
+
+    #[test]
+    fn current() {
+        assert_eq!(
+            api::DEPLOYMENT_TARGETS.macos.is_some(),
+            cfg!(feature = "macos_10_0")
+        );
+        assert_eq!(
+            api::DEPLOYMENT_TARGETS.ios.is_some(),
+            cfg!(feature = "ios_8_0")
+        );
+
+        let availability = api::availability!(macos = 10.0, ios = 8.0, tvos = 9.0);
+        match Platform::CURRENT {
+            None => assert_eq!(availability, Availability::Unavailable),
+            Some(Platform::MacOs) if cfg!(feature = "macos_10_0") => {
+                assert_eq!(availability, Availability::Static)
+            }
+            Some(_) => assert!(availability.is_available()),
+        }
+        assert_eq!(
+            OsVersion::current().is_some(),
+            cfg!(target_vendor = "apple")
+        );
+    }
+}
@@ -9,6 +9,7 @@ pub use audio::ChannelDesc as AudioChannelDesc;
 pub use audio::ChannelFlags as AudioChannelFlags;
 pub use audio::ChannelLabel as AudioChannelLabel;
 pub use audio::ChannelLayout as AudioChannelLayout;
+pub use audio::ChannelLayoutN as AudioChannelLayoutN;
 pub use audio::ChannelLayoutTag as AudioChannelLayoutTag;
 pub use audio::ClassDesc as AudioClassDesc;
 pub use audio::Converter as AudioConverter;
@@ -18,8 +18,10 @@ pub use converter::Converter;
 pub use converter::ConverterRef;
 pub use converter::DitherAlgorithm;
 pub use converter::Opts as ConverterOpts;
+pub use converter::PrimeInfo as ConverterPrimeInfo;
 pub use converter::PrimeMethod as ConverterPrimeMethod;
 pub use converter::Quality as ConverterQuality;
+pub use converter::SampleRateConverterComplexity;
 
 pub mod component;
 pub use component::Component;
@@ -53,6 +53,8 @@ impl<Sig> std::ops::Deref for Block<Sig, NoEsc> {
 }
 
 impl<Sig, Attr> objc::Obj for Block<Sig, Attr> {
+    const ENCODING: objc::Encoding = objc::Encoding::Block;
+
     #[inline]
     unsafe fn retain(id: &Self) -> arc::R<Self> {
         unsafe { std::mem::transmute(_Block_copy(std::mem::transmute(id))) }
@@ -14,6 +14,7 @@ pub use audio::ClassDesc as AudioClassDesc;
 pub use audio::Format as AudioFormat;
 pub use audio::FormatFlags as AudioFormatFlags;
 pub use audio::Integer as AudioInteger;
+pub use audio::RingBuf as AudioRingBuf;
 pub use audio::TimeStamp as AudioTimeStamp;
 pub use audio::TimeStampFlags as AudioTimeStampFlags;
 pub use audio::UInteger as AudioUInteger;
@@ -4,6 +4,9 @@ pub type UInteger = usize;
 mod base_types;
 pub use base_types::*;
 
+pub mod ring_buf;
+pub use ring_buf::RingBuf;
+
 mod session_types;
 pub use session_types::ErrorCode as SessionErrorCode;
 pub use session_types::SessionId;
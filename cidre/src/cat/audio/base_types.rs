@@ -3,10 +3,22 @@ use std::{
     ptr::{slice_from_raw_parts, slice_from_raw_parts_mut},
 };
 
-use crate::{cf, define_opts, four_cc_to_str, objc::Obj, os};
+use crate::{define_opts, four_cc_to_str, os};
+
+#[cfg(feature = "cf")]
+use crate::cf;
 
 #[cfg(feature = "ns")]
-use crate::ns;
+use crate::{ns, objc::Obj};
+
+mod stream_basic_desc;
+pub use stream_basic_desc::PcmBuilder;
+pub use stream_basic_desc::PcmSample;
+pub use stream_basic_desc::StreamBasicDescError;
+pub use stream_basic_desc::StreamBasicDescParseError;
+
+mod channel_layout;
+pub use channel_layout::ChannelLayoutN;
 
 /// These are the error codes returned from the APIs found through Core Audio related frameworks.
 pub mod err {
@@ -414,30 +426,35 @@ impl Format {
     }
 }
 
+#[cfg(feature = "cf")]
 impl AsRef<cf::Number> for Format {
     fn as_ref(&self) -> &'static cf::Number {
         cf::Number::tagged_i32(self.0 as _)
     }
 }
 
+#[cfg(feature = "cf")]
 impl AsRef<cf::Type> for Format {
     fn as_ref(&self) -> &'static cf::Type {
         cf::Number::tagged_i32(self.0 as _).as_type_ref()
     }
 }
 
+#[cfg(feature = "ns")]
 impl AsRef<ns::Id> for Format {
     fn as_ref(&self) -> &'static ns::Id {
         self.to_ns_number().as_id_ref()
     }
 }
 
+#[cfg(feature = "ns")]
 impl AsRef<ns::Number> for Format {
     fn as_ref(&self) -> &'static ns::Number {
         self.to_ns_number()
     }
 }
 
+#[cfg(feature = "ns")]
 impl AsRef<ns::Id> for u32 {
     fn as_ref(&self) -> &ns::Id {
         &ns::Number::tagged_u32(*self).as_id_ref()
@@ -1082,7 +1099,7 @@ impl ChannelLabel {
 /// These constants are for use in the mChannelBitmap field of an
 /// AudioChannelLayout structure
 #[doc(alias = "AudioChannelBitmap")]
-#[derive(Debug, PartialEq, Eq, Default)]
+#[derive(Debug, PartialEq, Eq, Default, Copy, Clone)]
 #[repr(transparent)]
 pub struct ChannelBitmap(pub u32);
 
@@ -1394,30 +1411,30 @@ impl ChannelLayoutTag {
     /// L R C LFE Ls Rs Lc Rc
     pub const AUDIO_UNIT_7_1_FRONT: Self = Self::MPEG_7_1_A;
 
-    pub const AAC_3_0: Self = Self::MPEG_3_0_B;
     /// C L R
-    pub const AAC_QUADRAPHONIC: Self = Self::QUADRAPHONIC;
+    pub const AAC_3_0: Self = Self::MPEG_3_0_B;
     /// L R Ls Rs
-    pub const AAC_4_0: Self = Self::MPEG_4_0_B;
+    pub const AAC_QUADRAPHONIC: Self = Self::QUADRAPHONIC;
     /// C L R Cs
-    pub const AAC_5_0: Self = Self::MPEG_5_0_D;
+    pub const AAC_4_0: Self = Self::MPEG_4_0_B;
     /// C L R Ls Rs
-    pub const AAC_5_1: Self = Self::MPEG_5_1_D;
+    pub const AAC_5_0: Self = Self::MPEG_5_0_D;
     /// C L R Ls Rs Lfe
-    pub const AAC_6_0: Self = Self((141u32 << 16) | 6);
+    pub const AAC_5_1: Self = Self::MPEG_5_1_D;
     /// C L R Ls Rs Cs
-    pub const AAC_6_1: Self = Self((142u32 << 16) | 7);
+    pub const AAC_6_0: Self = Self((141u32 << 16) | 6);
     /// C L R Ls Rs Cs Lfe
-    pub const AAC_7_0: Self = Self((143u32 << 16) | 7);
+    pub const AAC_6_1: Self = Self((142u32 << 16) | 7);
     /// C L R Ls Rs Rls Rrs
-    pub const AAC_7_1: Self = Self::MPEG_7_1_B;
+    pub const AAC_7_0: Self = Self((143u32 << 16) | 7);
     /// C Lc Rc L R Ls Rs Lfe
-    pub const AAC_7_1_B: Self = Self((183u32 << 16) | 8);
+    pub const AAC_7_1: Self = Self::MPEG_7_1_B;
     /// C L R Ls Rs Rls Rrs LFE
-    pub const AAC_7_1_C: Self = Self((184u32 << 16) | 8);
+    pub const AAC_7_1_B: Self = Self((183u32 << 16) | 8);
     /// C L R Ls Rs LFE Vhl Vhr
-    pub const AAC_OCTAGONAL: Self = Self((144u32 << 16) | 8);
+    pub const AAC_7_1_C: Self = Self((184u32 << 16) | 8);
     /// C L R Ls Rs Rls Rrs Cs
+    pub const AAC_OCTAGONAL: Self = Self((144u32 << 16) | 8);
 
     /// L R C Vhc Lsd Rsd Ls Rs Vhl Vhr Lw Rw Csd Cs LFE1 LFE2
     pub const TMH_10_2_STD: Self = Self((145u32 << 16) | 16);
@@ -1627,7 +1644,7 @@ impl ChannelLayoutTag {
 
 /// This structure describes a single channel.
 #[doc(alias = "AudioChannelDescription")]
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 #[repr(C)]
 pub struct ChannelDesc {
     /// The AudioChannelLabel that describes the channel.
@@ -1694,11 +1711,11 @@ impl Mpeg4Object {
 
 #[cfg(test)]
 mod tests {
-    use crate::at;
+    use crate::cat;
 
     #[test]
     fn basics() {
-        let asbd = at::audio::StreamBasicDesc::common_f32(44100.0, 2, false);
+        let asbd = cat::audio::StreamBasicDesc::common_f32(44100.0, 2, false);
         assert_eq!(asbd.interleaved_channels_num(), 1);
         assert!(!asbd.is_interleaved());
         assert!(asbd.is_common_f32());
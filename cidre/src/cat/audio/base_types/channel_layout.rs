@@ -0,0 +1,687 @@
+use super::{
+    ChannelBitmap, ChannelDesc, ChannelFlags, ChannelLabel, ChannelLayout, ChannelLayoutTag,
+};
+
+const L: ChannelLabel = ChannelLabel::LEFT;
+const R: ChannelLabel = ChannelLabel::RIGHT;
+const C: ChannelLabel = ChannelLabel::CENTER;
+const LFE: ChannelLabel = ChannelLabel::LFE_SCREEN;
+const LS: ChannelLabel = ChannelLabel::LEFT_SURROUND;
+const RS: ChannelLabel = ChannelLabel::RIGHT_SURROUND;
+const LC: ChannelLabel = ChannelLabel::LEFT_CENTER;
+const RC: ChannelLabel = ChannelLabel::RIGHT_CENTER;
+const CS: ChannelLabel = ChannelLabel::CENTER_SURROUND;
+const LSD: ChannelLabel = ChannelLabel::LEFT_SURROUND_DIRECT;
+const RSD: ChannelLabel = ChannelLabel::RIGHT_SURROUND_DIRECT;
+const CSD: ChannelLabel = ChannelLabel::CENTER_SURROUND_DIRECT;
+const TS: ChannelLabel = ChannelLabel::TOP_CENTER_SURROUND;
+const VHL: ChannelLabel = ChannelLabel::VERTICAL_HEIGHT_LEFT;
+const VHC: ChannelLabel = ChannelLabel::VERTICAL_HEIGHT_CENTER;
+const VHR: ChannelLabel = ChannelLabel::VERTICAL_HEIGHT_RIGHT;
+const RLS: ChannelLabel = ChannelLabel::REAR_SURROUND_LEFT;
+const RRS: ChannelLabel = ChannelLabel::REAR_SURROUND_RIGHT;
+const LW: ChannelLabel = ChannelLabel::LEFT_WIDE;
+const RW: ChannelLabel = ChannelLabel::RIGHT_WIDE;
+const LT: ChannelLabel = ChannelLabel::LEFT_TOTAL;
+const RT: ChannelLabel = ChannelLabel::RIGHT_TOTAL;
+const LTM: ChannelLabel = ChannelLabel::LEFT_TOP_MIDDLE;
+const RTM: ChannelLabel = ChannelLabel::RIGHT_TOP_MIDDLE;
+const LTR: ChannelLabel = ChannelLabel::LEFT_TOP_REAR;
+const RTR: ChannelLabel = ChannelLabel::RIGHT_TOP_REAR;
+
+/// Label sequences of the predefined layout tags, aliases are listed once.
+///
+/// Some tags share labels and differ only in speaker placement, the MPEG and
+/// ITU ones come first so [`ChannelLayoutTag::from_labels`] prefers them.
+const LAYOUTS: &[(ChannelLayoutTag, &[ChannelLabel])] = {
+    use ChannelLabel as Label;
+    use ChannelLayoutTag as Tag;
+    &[
+        (Tag::MONO, &[Label::MONO]),
+        (Tag::STEREO, &[L, R]),
+        (
+            Tag::STEREO_HEADPHONES,
+            &[Label::HEADPHONES_LEFT, Label::HEADPHONES_RIGHT],
+        ),
+        (Tag::MATRIX_STEREO, &[LT, RT]),
+        (Tag::MID_SIDE, &[Label::MS_MID, Label::MS_SIDE]),
+        (Tag::XY, &[Label::XY_X, Label::XY_Y]),
+        (
+            Tag::BINAURAL,
+            &[Label::BINAURAL_LEFT, Label::BINAURAL_RIGHT],
+        ),
+        (
+            Tag::AMBISONIC_B_FORMAT,
+            &[
+                Label::AMBISONIC_W,
+                Label::AMBISONIC_X,
+                Label::AMBISONIC_Y,
+                Label::AMBISONIC_Z,
+            ],
+        ),
+        (Tag::MPEG_3_0_A, &[L, R, C]),
+        (Tag::MPEG_3_0_B, &[C, L, R]),
+        (Tag::MPEG_4_0_A, &[L, R, C, CS]),
+        (Tag::MPEG_4_0_B, &[C, L, R, CS]),
+        (Tag::MPEG_5_0_A, &[L, R, C, LS, RS]),
+        (Tag::MPEG_5_0_B, &[L, R, LS, RS, C]),
+        (Tag::MPEG_5_0_C, &[L, C, R, LS, RS]),
+        (Tag::MPEG_5_0_D, &[C, L, R, LS, RS]),
+        (Tag::MPEG_5_1_A, &[L, R, C, LFE, LS, RS]),
+        (Tag::MPEG_5_1_B, &[L, R, LS, RS, C, LFE]),
+        (Tag::MPEG_5_1_C, &[L, C, R, LS, RS, LFE]),
+        (Tag::MPEG_5_1_D, &[C, L, R, LS, RS, LFE]),
+        (Tag::MPEG_6_1_A, &[L, R, C, LFE, LS, RS, CS]),
+        (Tag::MPEG_7_1_A, &[L, R, C, LFE, LS, RS, LC, RC]),
+        (Tag::MPEG_7_1_B, &[C, LC, RC, L, R, LS, RS, LFE]),
+        (Tag::MPEG_7_1_C, &[L, R, C, LFE, LS, RS, RLS, RRS]),
+        (Tag::EMAGIC_DEFAULT_7_1, &[L, R, LS, RS, C, LFE, LC, RC]),
+        (Tag::SMPTE_DTV, &[L, R, C, LFE, LS, RS, LT, RT]),
+        (Tag::ITU_2_1, &[L, R, CS]),
+        (Tag::ITU_2_2, &[L, R, LS, RS]),
+        (Tag::DVD_4, &[L, R, LFE]),
+        (Tag::DVD_5, &[L, R, LFE, CS]),
+        (Tag::DVD_6, &[L, R, LFE, LS, RS]),
+        (Tag::DVD_10, &[L, R, C, LFE]),
+        (Tag::DVD_11, &[L, R, C, LFE, CS]),
+        (Tag::DVD_18, &[L, R, LS, RS, LFE]),
+        (Tag::QUADRAPHONIC, &[L, R, LS, RS]),
+        (Tag::PENTAGONAL, &[L, R, LS, RS, C]),
+        (Tag::HEXAGONAL, &[L, R, LS, RS, C, CS]),
+        (Tag::OCTAGONAL, &[L, R, LS, RS, C, CS, LW, RW]),
+        (
+            Tag::CUBE,
+            &[
+                L,
+                R,
+                LS,
+                RS,
+                VHL,
+                VHR,
+                Label::TOP_BACK_LEFT,
+                Label::TOP_BACK_RIGHT,
+            ],
+        ),
+        (Tag::AUDIO_UNIT_6_0, &[L, R, LS, RS, C, CS]),
+        (Tag::AUDIO_UNIT_7_0, &[L, R, LS, RS, C, RLS, RRS]),
+        (Tag::AUDIO_UNIT_7_0_FRONT, &[L, R, LS, RS, C, LC, RC]),
+        (Tag::AAC_6_0, &[C, L, R, LS, RS, CS]),
+        (Tag::AAC_6_1, &[C, L, R, LS, RS, CS, LFE]),
+        (Tag::AAC_7_0, &[C, L, R, LS, RS, RLS, RRS]),
+        (Tag::AAC_7_1_B, &[C, L, R, LS, RS, RLS, RRS, LFE]),
+        (Tag::AAC_7_1_C, &[C, L, R, LS, RS, LFE, VHL, VHR]),
+        (Tag::AAC_OCTAGONAL, &[C, L, R, LS, RS, RLS, RRS, CS]),
+        (
+            Tag::TMH_10_2_STD,
+            &[
+                L,
+                R,
+                C,
+                VHC,
+                LSD,
+                RSD,
+                LS,
+                RS,
+                VHL,
+                VHR,
+                LW,
+                RW,
+                CSD,
+                CS,
+                LFE,
+                Label::LFE2,
+            ],
+        ),
+        (
+            Tag::TMH_10_2_FULL,
+            &[
+                L,
+                R,
+                C,
+                VHC,
+                LSD,
+                RSD,
+                LS,
+                RS,
+                VHL,
+                VHR,
+                LW,
+                RW,
+                CSD,
+                CS,
+                LFE,
+                Label::LFE2,
+                LC,
+                RC,
+                Label::HEARING_IMPAIRED,
+                Label::NARRATION,
+                Label::HAPTIC,
+            ],
+        ),
+        (Tag::AC3_1_0_1, &[C, LFE]),
+        (Tag::AC3_3_0, &[L, C, R]),
+        (Tag::AC3_3_1, &[L, C, R, CS]),
+        (Tag::AC3_3_0_1, &[L, C, R, LFE]),
+        (Tag::AC3_2_1_1, &[L, R, CS, LFE]),
+        (Tag::AC3_3_1_1, &[L, C, R, CS, LFE]),
+        (Tag::EAC_6_0_A, &[L, C, R, LS, RS, CS]),
+        (Tag::EAC_7_0_A, &[L, C, R, LS, RS, RLS, RRS]),
+        (Tag::EAC3_6_1_A, &[L, C, R, LS, RS, LFE, CS]),
+        (Tag::EAC3_6_1_B, &[L, C, R, LS, RS, LFE, TS]),
+        (Tag::EAC3_6_1_C, &[L, C, R, LS, RS, LFE, VHC]),
+        (Tag::EAC3_7_1_A, &[L, C, R, LS, RS, LFE, RLS, RRS]),
+        (Tag::EAC3_7_1_B, &[L, C, R, LS, RS, LFE, LC, RC]),
+        (Tag::EAC3_7_1_C, &[L, C, R, LS, RS, LFE, LSD, RSD]),
+        (Tag::EAC3_7_1_D, &[L, C, R, LS, RS, LFE, LW, RW]),
+        (Tag::EAC3_7_1_E, &[L, C, R, LS, RS, LFE, VHL, VHR]),
+        (Tag::EAC3_7_1_F, &[L, C, R, LS, RS, LFE, CS, TS]),
+        (Tag::EAC3_7_1_G, &[L, C, R, LS, RS, LFE, CS, VHC]),
+        (Tag::EAC3_7_1_H, &[L, C, R, LS, RS, LFE, TS, VHC]),
+        (Tag::DTS_3_1, &[C, L, R, LFE]),
+        (Tag::DTS_4_1, &[C, L, R, CS, LFE]),
+        (Tag::DTS_6_0_A, &[LC, RC, L, R, LS, RS]),
+        (Tag::DTS_6_0_B, &[C, L, R, RLS, RRS, TS]),
+        (Tag::DTS_6_0_C, &[C, CS, L, R, RLS, RRS]),
+        (Tag::DTS_6_1_A, &[LC, RC, L, R, LS, RS, LFE]),
+        (Tag::DTS_6_1_B, &[C, L, R, RLS, RRS, TS, LFE]),
+        (Tag::DTS_6_1_C, &[C, CS, L, R, RLS, RRS, LFE]),
+        (Tag::DTS_7_0, &[LC, C, RC, L, R, LS, RS]),
+        (Tag::DTS_7_1, &[LC, C, RC, L, R, LS, RS, LFE]),
+        (Tag::DTS_8_0_A, &[LC, RC, L, R, LS, RS, RLS, RRS]),
+        (Tag::DTS_8_0_B, &[LC, C, RC, L, R, LS, CS, RS]),
+        (Tag::DTS_8_1_A, &[LC, RC, L, R, LS, RS, RLS, RRS, LFE]),
+        (Tag::DTS_8_1_B, &[LC, C, RC, L, R, LS, CS, RS, LFE]),
+        (Tag::DTS_6_1_D, &[C, L, R, LS, RS, LFE, CS]),
+        (Tag::WAVE_4_0_B, &[L, R, RLS, RRS]),
+        (Tag::WAVE_5_0_B, &[L, R, C, RLS, RRS]),
+        (Tag::WAVE_5_1_B, &[L, R, C, LFE, RLS, RRS]),
+        (Tag::WAVE_6_1, &[L, R, C, LFE, CS, LS, RS]),
+        (Tag::WAVE_7_1, &[L, R, C, LFE, RLS, RRS, LS, RS]),
+        (Tag::ATMOS_5_1_2, &[L, R, C, LFE, LS, RS, LTM, RTM]),
+        (
+            Tag::ATMOS_5_1_4,
+            &[L, R, C, LFE, LS, RS, VHL, VHR, LTR, RTR],
+        ),
+        (
+            Tag::ATMOS_7_1_2,
+            &[L, R, C, LFE, LS, RS, RLS, RRS, LTM, RTM],
+        ),
+        (
+            Tag::ATMOS_7_1_4,
+            &[L, R, C, LFE, LS, RS, RLS, RRS, VHL, VHR, LTR, RTR],
+        ),
+        (
+            Tag::ATMOS_9_1_6,
+            &[
+                L, R, C, LFE, LS, RS, RLS, RRS, LW, RW, VHL, VHR, LTM, RTM, LTR, RTR,
+            ],
+        ),
+        (Tag::LOGIC_4_0_C, &[L, R, CS, C]),
+        (Tag::LOGIC_6_0_B, &[L, R, LS, RS, CS, C]),
+        (Tag::LOGIC_6_1_B, &[L, R, LS, RS, CS, C, LFE]),
+        (Tag::LOGIC_6_1_D, &[L, C, R, LS, CS, RS, LFE]),
+        (Tag::LOGIC_7_1_B, &[L, R, LS, RS, RLS, RRS, C, LFE]),
+        (
+            Tag::LOGIC_ATMOS_7_1_4_B,
+            &[L, R, RLS, RRS, LS, RS, C, LFE, VHL, VHR, LTR, RTR],
+        ),
+        (
+            Tag::LOGIC_ATMOS_7_1_6,
+            &[L, R, RLS, RRS, LS, RS, C, LFE, VHL, VHR, LTM, RTM, LTR, RTR],
+        ),
+    ]
+};
+
+impl ChannelLabel {
+    /// The bit of this label in a [`ChannelBitmap`].
+    pub const fn bitmap(self) -> Option<ChannelBitmap> {
+        match self.0 {
+            1..=18 => Some(ChannelBitmap(1 << (self.0 - 1))),
+            49 | 51..=54 => Some(ChannelBitmap(1 << (self.0 - 28))),
+            _ => None,
+        }
+    }
+}
+
+impl ChannelBitmap {
+    pub const fn channels_num(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Labels of the set bits, from the lowest bit up.
+    pub fn labels(&self) -> Option<Vec<ChannelLabel>> {
+        let mut res = Vec::with_capacity(self.channels_num() as usize);
+        let mut bits = self.0;
+        while bits != 0 {
+            let bit = bits.trailing_zeros();
+            let label = match bit {
+                0..=17 => ChannelLabel(bit + 1),
+                21 | 23..=26 => ChannelLabel(bit + 28),
+                _ => return None,
+            };
+            res.push(label);
+            bits &= bits - 1;
+        }
+        Some(res)
+    }
+
+    /// The bitmap of `labels`, which must be unique and in bitmap order.
+    pub fn from_labels(labels: &[ChannelLabel]) -> Option<Self> {
+        let mut res = 0u32;
+        for label in labels {
+            let bit = label.bitmap()?.0;
+            if bit <= res {
+                return None;
+            }
+            res |= bit;
+        }
+        Some(Self(res))
+    }
+}
+
+impl ChannelLayoutTag {
+    /// Ordered channel labels of the tag.
+    ///
+    /// ```
+    /// use cidre::cat;
+    ///
+    /// let labels = cat::audio::ChannelLayoutTag::MPEG_5_1_A.labels().unwrap();
+    /// assert_eq!(labels[3], cat::audio::ChannelLabel::LFE_SCREEN);
+    /// ```
+    pub fn labels(&self) -> Option<Vec<ChannelLabel>> {
+        let n = self.number_of_channels();
+        let indexed = |base: ChannelLabel| (0..n).map(|i| ChannelLabel(base.0 | i)).collect();
+        match Self(self.0 & 0xFFFF0000) {
+            Self::DISCRETE_IN_ORDER => Some(indexed(ChannelLabel::DISCRETE_0)),
+            Self::HOA_ACN_SN3D => Some(indexed(ChannelLabel::HOA_SN3D)),
+            Self::HOA_ACN_N3D => Some(indexed(ChannelLabel::HOA_N3D)),
+            Self::UNKNOWN => Some(vec![ChannelLabel::UNKNOWN; n as usize]),
+            _ => LAYOUTS
+                .iter()
+                .find(|(tag, _)| tag == self)
+                .map(|(_, labels)| labels.to_vec()),
+        }
+    }
+
+    /// The predefined tag with exactly these labels in this order.
+    ///
+    /// Prefers MPEG and ITU tags where several share the labels.
+    pub fn from_labels(labels: &[ChannelLabel]) -> Option<Self> {
+        if let Some((tag, _)) = LAYOUTS.iter().find(|(_, l)| *l == labels) {
+            return Some(*tag);
+        }
+        let n = u16::try_from(labels.len()).ok()? as u32;
+        let indexed = |base: ChannelLabel| {
+            labels
+                .iter()
+                .zip(0..)
+                .all(|(label, i)| label.0 == (base.0 | i))
+        };
+        if n == 0 {
+            None
+        } else if indexed(ChannelLabel::DISCRETE_0) {
+            Some(Self(Self::DISCRETE_IN_ORDER.0 | n))
+        } else if indexed(ChannelLabel::HOA_SN3D) {
+            Some(Self(Self::HOA_ACN_SN3D.0 | n))
+        } else if indexed(ChannelLabel::HOA_N3D) {
+            Some(Self(Self::HOA_ACN_N3D.0 | n))
+        } else {
+            None
+        }
+    }
+}
+
+/// Owned `AudioChannelLayout` with any number of channel descriptions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelLayoutN {
+    pub tag: ChannelLayoutTag,
+    pub bitmap: ChannelBitmap,
+    pub descs: Vec<ChannelDesc>,
+}
+
+impl ChannelLayoutN {
+    const HEADER_SIZE: usize = 12;
+    const DESC_SIZE: usize = std::mem::size_of::<ChannelDesc>();
+
+    pub fn with_tag(tag: ChannelLayoutTag) -> Self {
+        Self {
+            tag,
+            bitmap: ChannelBitmap(0),
+            descs: Vec::new(),
+        }
+    }
+
+    pub fn with_bitmap(bitmap: ChannelBitmap) -> Self {
+        Self {
+            tag: ChannelLayoutTag::USE_CHANNEL_BITMAP,
+            bitmap,
+            descs: Vec::new(),
+        }
+    }
+
+    pub fn with_descs(descs: Vec<ChannelDesc>) -> Self {
+        Self {
+            tag: ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS,
+            bitmap: ChannelBitmap(0),
+            descs,
+        }
+    }
+
+    pub fn with_labels(labels: &[ChannelLabel]) -> Self {
+        Self::with_descs(
+            labels
+                .iter()
+                .map(|label| ChannelDesc {
+                    channel_label: *label,
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn channels_num(&self) -> u32 {
+        match self.tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS => self.descs.len() as u32,
+            ChannelLayoutTag::USE_CHANNEL_BITMAP => self.bitmap.channels_num(),
+            tag => tag.number_of_channels(),
+        }
+    }
+
+    /// Ordered channel labels regardless of the layout form.
+    pub fn labels(&self) -> Option<Vec<ChannelLabel>> {
+        match self.tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS => {
+                Some(self.descs.iter().map(|d| d.channel_label).collect())
+            }
+            ChannelLayoutTag::USE_CHANNEL_BITMAP => self.bitmap.labels(),
+            tag => tag.labels(),
+        }
+    }
+
+    /// The predefined tag describing the same channels.
+    pub fn to_tag(&self) -> Option<ChannelLayoutTag> {
+        match self.tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS | ChannelLayoutTag::USE_CHANNEL_BITMAP => {
+                if self.descs.iter().any(|d| !d.channel_flags.is_empty()) {
+                    return None;
+                }
+                ChannelLayoutTag::from_labels(&self.labels()?)
+            }
+            tag => Some(tag),
+        }
+    }
+
+    pub fn to_bitmap(&self) -> Option<ChannelBitmap> {
+        match self.tag {
+            ChannelLayoutTag::USE_CHANNEL_BITMAP => Some(self.bitmap),
+            _ => ChannelBitmap::from_labels(&self.labels()?),
+        }
+    }
+
+    pub fn to_descs(&self) -> Option<Vec<ChannelDesc>> {
+        match self.tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS => Some(self.descs.clone()),
+            _ => Some(Self::with_labels(&self.labels()?).descs),
+        }
+    }
+
+    /// For each channel of `to` the index of the same channel in `self`.
+    ///
+    /// This is the form of `kAudioConverterChannelMap`.
+    ///
+    /// ```
+    /// use cidre::cat;
+    ///
+    /// // SMPTE to film order
+    /// let smpte = cat::audio::ChannelLayoutN::with_tag(cat::audio::ChannelLayoutTag::MPEG_5_1_A);
+    /// let film = cat::audio::ChannelLayoutN::with_tag(cat::audio::ChannelLayoutTag::MPEG_5_1_C);
+    /// assert_eq!(smpte.reorder_map(&film), Some(vec![0, 2, 1, 4, 5, 3]));
+    /// ```
+    pub fn reorder_map(&self, to: &Self) -> Option<Vec<usize>> {
+        let from = self.labels()?;
+        let to = to.labels()?;
+        if from.len() != to.len() {
+            return None;
+        }
+        let mut used = vec![false; from.len()];
+        to.iter()
+            .map(|label| {
+                let i = (0..from.len()).find(|&i| !used[i] && from[i] == *label)?;
+                used[i] = true;
+                Some(i)
+            })
+            .collect()
+    }
+
+    /// Size of the `AudioChannelLayout` with `n` channel descriptions.
+    pub const fn byte_size(n: usize) -> usize {
+        Self::HEADER_SIZE + n * Self::DESC_SIZE
+    }
+
+    /// Native endian `AudioChannelLayout` bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(Self::byte_size(self.descs.len()));
+        res.extend_from_slice(&self.tag.0.to_ne_bytes());
+        res.extend_from_slice(&self.bitmap.0.to_ne_bytes());
+        res.extend_from_slice(&(self.descs.len() as u32).to_ne_bytes());
+        for desc in &self.descs {
+            res.extend_from_slice(&desc.channel_label.0.to_ne_bytes());
+            res.extend_from_slice(&desc.channel_flags.0.to_ne_bytes());
+            for c in desc.coordinates {
+                res.extend_from_slice(&c.to_ne_bytes());
+            }
+        }
+        res
+    }
+
+    /// Reads native endian `AudioChannelLayout` bytes, trailing bytes are ignored.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let u32_at = |i: usize| -> Option<u32> {
+            Some(u32::from_ne_bytes(bytes.get(i..i + 4)?.try_into().ok()?))
+        };
+        let n = u32_at(8)? as usize;
+        if bytes.len() < Self::byte_size(n) {
+            return None;
+        }
+        let descs = (0..n)
+            .map(|i| {
+                let off = Self::HEADER_SIZE + i * Self::DESC_SIZE;
+                let mut coordinates = [0f32; 3];
+                for (j, c) in coordinates.iter_mut().enumerate() {
+                    *c = f32::from_bits(u32_at(off + 8 + j * 4)?);
+                }
+                Some(ChannelDesc {
+                    channel_label: ChannelLabel(u32_at(off)?),
+                    channel_flags: ChannelFlags(u32_at(off + 4)?),
+                    coordinates,
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Self {
+            tag: ChannelLayoutTag(u32_at(0)?),
+            bitmap: ChannelBitmap(u32_at(4)?),
+            descs,
+        })
+    }
+}
+
+impl<const N: usize> From<&ChannelLayout<N>> for ChannelLayoutN {
+    fn from(value: &ChannelLayout<N>) -> Self {
+        let n = N.min(value.number_channel_descriptions as usize);
+        Self {
+            tag: value.channel_layout_tag,
+            bitmap: value.channel_bitmap,
+            descs: value.channel_descriptions[..n].to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cat::audio::{
+        ChannelBitmap, ChannelDesc, ChannelFlags, ChannelLabel, ChannelLayout, ChannelLayoutN,
+        ChannelLayoutTag,
+    };
+
+    use super::LAYOUTS;
+
+    #[test]
+    fn tags() {
+        for (tag, labels) in LAYOUTS {
+            assert_eq!(tag.number_of_channels() as usize, labels.len(), "{tag:?}");
+            let found = ChannelLayoutTag::from_labels(labels).unwrap();
+            assert_eq!(found.labels().as_deref(), Some(*labels));
+            assert_eq!(
+                LAYOUTS.iter().filter(|(t, _)| t == tag).count(),
+                1,
+                "{tag:?}"
+            );
+        }
+
+        assert_eq!(
+            ChannelLayoutTag::MPEG_5_1_A.labels(),
+            Some(vec![
+                ChannelLabel::LEFT,
+                ChannelLabel::RIGHT,
+                ChannelLabel::CENTER,
+                ChannelLabel::LFE_SCREEN,
+                ChannelLabel::LEFT_SURROUND,
+                ChannelLabel::RIGHT_SURROUND,
+            ])
+        );
+        assert_eq!(
+            ChannelLayoutTag::from_labels(&ChannelLayoutTag::PENTAGONAL.labels().unwrap()),
+            Some(ChannelLayoutTag::MPEG_5_0_B)
+        );
+        assert_eq!(ChannelLayoutTag::USE_CHANNEL_BITMAP.labels(), None);
+        assert_eq!(ChannelLayoutTag(0xABCD0002).labels(), None);
+
+        let tag = ChannelLayoutTag(ChannelLayoutTag::DISCRETE_IN_ORDER.0 | 3);
+        let labels = tag.labels().unwrap();
+        assert_eq!(
+            labels,
+            [
+                ChannelLabel::DISCRETE_0,
+                ChannelLabel::DISCRETE_1,
+                ChannelLabel::DISCRETE_2
+            ]
+        );
+        assert_eq!(ChannelLayoutTag::from_labels(&labels), Some(tag));
+
+        let tag = ChannelLayoutTag(ChannelLayoutTag::HOA_ACN_SN3D.0 | 4);
+        let labels = tag.labels().unwrap();
+        assert_eq!(labels[3], ChannelLabel::HOA_ACN_3);
+        assert_eq!(ChannelLayoutTag::from_labels(&labels), Some(tag));
+
+        let tag = ChannelLayoutTag(ChannelLayoutTag::UNKNOWN.0 | 2);
+        assert_eq!(tag.labels(), Some(vec![ChannelLabel::UNKNOWN; 2]));
+        assert_eq!(ChannelLayoutTag::from_labels(&[]), None);
+    }
+
+    #[test]
+    fn bitmaps() {
+        let bitmap = ChannelBitmap(
+            ChannelBitmap::LEFT.0
+                | ChannelBitmap::RIGHT.0
+                | ChannelBitmap::CENTER.0
+                | ChannelBitmap::LFE_SCREEN.0
+                | ChannelBitmap::LEFT_SURROUND.0
+                | ChannelBitmap::RIGHT_SURROUND.0,
+        );
+        let labels = bitmap.labels().unwrap();
+        assert_eq!(labels, ChannelLayoutTag::MPEG_5_1_A.labels().unwrap());
+        assert_eq!(ChannelBitmap::from_labels(&labels), Some(bitmap));
+
+        let bitmap =
+            ChannelBitmap(ChannelBitmap::LEFT_TOP_MIDDLE.0 | ChannelBitmap::RIGHT_TOP_REAR.0);
+        let labels = bitmap.labels().unwrap();
+        assert_eq!(
+            labels,
+            [ChannelLabel::LEFT_TOP_MIDDLE, ChannelLabel::RIGHT_TOP_REAR]
+        );
+        assert_eq!(ChannelBitmap::from_labels(&labels), Some(bitmap));
+
+        assert_eq!(ChannelBitmap(1 << 22).labels(), None);
+        assert_eq!(
+            ChannelBitmap::from_labels(&[ChannelLabel::RIGHT, ChannelLabel::LEFT]),
+            None
+        );
+        assert_eq!(
+            ChannelBitmap::from_labels(&[ChannelLabel::LEFT, ChannelLabel::LEFT]),
+            None
+        );
+        assert_eq!(ChannelBitmap::from_labels(&[ChannelLabel::MONO]), None);
+    }
+
+    #[test]
+    fn layouts() {
+        let tag = ChannelLayoutN::with_tag(ChannelLayoutTag::MPEG_5_1_A);
+        assert_eq!(tag.channels_num(), 6);
+
+        let descs = ChannelLayoutN::with_descs(tag.to_descs().unwrap());
+        assert_eq!(descs.channels_num(), 6);
+        assert_eq!(descs.to_tag(), Some(ChannelLayoutTag::MPEG_5_1_A));
+
+        let bitmap = ChannelLayoutN::with_bitmap(tag.to_bitmap().unwrap());
+        assert_eq!(bitmap.channels_num(), 6);
+        assert_eq!(bitmap.to_tag(), Some(ChannelLayoutTag::MPEG_5_1_A));
+        assert_eq!(bitmap.labels(), tag.labels());
+
+        // film order has no bitmap
+        let film = ChannelLayoutN::with_tag(ChannelLayoutTag::MPEG_5_1_C);
+        assert_eq!(film.to_bitmap(), None);
+
+        let mut placed = descs.clone();
+        placed.descs[0].channel_flags = ChannelFlags::SPHERICAL_COORDINATES;
+        placed.descs[0].coordinates = [-30.0, 0.0, 1.0];
+        assert_eq!(placed.to_tag(), None);
+
+        assert_eq!(film.reorder_map(&tag), Some(vec![0, 2, 1, 5, 3, 4]));
+        assert_eq!(tag.reorder_map(&tag), Some(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(
+            tag.reorder_map(&ChannelLayoutN::with_tag(ChannelLayoutTag::STEREO)),
+            None
+        );
+        assert_eq!(
+            tag.reorder_map(&ChannelLayoutN::with_tag(ChannelLayoutTag::SMPTE_DTV)),
+            None
+        );
+
+        let dup = ChannelLayoutN::with_labels(&[ChannelLabel::MONO, ChannelLabel::MONO]);
+        assert_eq!(dup.reorder_map(&dup), Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn bytes() {
+        let layout = ChannelLayout::<1> {
+            channel_layout_tag: ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS,
+            channel_bitmap: ChannelBitmap(0),
+            number_channel_descriptions: 1,
+            channel_descriptions: [ChannelDesc {
+                channel_label: ChannelLabel::USE_COORDINATES,
+                channel_flags: ChannelFlags::SPHERICAL_COORDINATES,
+                coordinates: [-30.0, 10.0, 1.0],
+            }],
+        };
+        let raw = unsafe {
+            std::slice::from_raw_parts(
+                &layout as *const _ as *const u8,
+                std::mem::size_of_val(&layout),
+            )
+        };
+
+        let owned = ChannelLayoutN::from(&layout);
+        assert_eq!(owned.to_bytes(), raw);
+        assert_eq!(ChannelLayoutN::from_bytes(raw), Some(owned.clone()));
+        assert_eq!(ChannelLayoutN::byte_size(1), raw.len());
+        assert_eq!(ChannelLayoutN::byte_size(0), 12);
+
+        assert_eq!(ChannelLayoutN::from_bytes(&raw[..raw.len() - 1]), None);
+        assert_eq!(ChannelLayoutN::from_bytes(&raw[..8]), None);
+
+        let tag = ChannelLayoutN::with_tag(ChannelLayoutTag::STEREO);
+        let bytes = tag.to_bytes();
+        assert_eq!(bytes.len(), 12);
+        assert_eq!(ChannelLayoutN::from_bytes(&bytes), Some(tag));
+    }
+}
@@ -0,0 +1,897 @@
+use std::{fmt, str::FromStr};
+
+use super::{Format, FormatFlags, StreamBasicDesc};
+
+/// Sample types of linear PCM streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PcmSample {
+    U8,
+    I8,
+    I16,
+    I24,
+    I32,
+    F32,
+    F64,
+}
+
+impl PcmSample {
+    /// Number of significant bits in a sample.
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        match self {
+            Self::U8 | Self::I8 => 8,
+            Self::I16 => 16,
+            Self::I24 => 24,
+            Self::I32 | Self::F32 => 32,
+            Self::F64 => 64,
+        }
+    }
+
+    /// Number of bytes a packed sample occupies.
+    #[inline]
+    pub const fn bytes(self) -> u32 {
+        self.bits() / 8
+    }
+
+    #[inline]
+    pub const fn is_float(self) -> bool {
+        matches!(self, Self::F32 | Self::F64)
+    }
+
+    #[inline]
+    pub const fn is_signed_int(self) -> bool {
+        matches!(self, Self::I8 | Self::I16 | Self::I24 | Self::I32)
+    }
+}
+
+/// Builds linear PCM [`StreamBasicDesc`]s.
+///
+/// Defaults to native endian, interleaved and packed samples.
+///
+/// ```
+/// use cidre::cat;
+///
+/// let asbd = cat::audio::StreamBasicDesc::pcm(cat::audio::PcmSample::I24, 48_000.0, 2)
+///     .aligned_high(4)
+///     .big_endian(true)
+///     .build();
+///
+/// assert_eq!(asbd.bytes_per_frame, 8);
+/// assert_eq!(asbd.bits_per_channel, 24);
+/// assert!(asbd.validate().is_ok());
+/// assert_eq!(asbd.to_string(), "BEI24in32hi@48000x2");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PcmBuilder {
+    sample: PcmSample,
+    sample_rate: f64,
+    channels: u32,
+    /// Container size in bytes and high alignment for unpacked samples.
+    container: Option<(u32, bool)>,
+    big_endian: bool,
+    interleaved: bool,
+    non_mixable: bool,
+    fraction_bits: u32,
+}
+
+impl PcmBuilder {
+    #[inline]
+    pub const fn new(sample: PcmSample, sample_rate: f64, channels: u32) -> Self {
+        Self {
+            sample,
+            sample_rate,
+            channels,
+            container: None,
+            big_endian: cfg!(target_endian = "big"),
+            interleaved: true,
+            non_mixable: false,
+            fraction_bits: 0,
+        }
+    }
+
+    #[inline]
+    pub const fn big_endian(mut self, val: bool) -> Self {
+        self.big_endian = val;
+        self
+    }
+
+    #[inline]
+    pub const fn interleaved(mut self, val: bool) -> Self {
+        self.interleaved = val;
+        self
+    }
+
+    /// Places samples into the high bits of `container_bytes` wide channels.
+    #[inline]
+    pub const fn aligned_high(mut self, container_bytes: u32) -> Self {
+        self.container = Some((container_bytes, true));
+        self
+    }
+
+    /// Places samples into the low bits of `container_bytes` wide channels.
+    #[inline]
+    pub const fn aligned_low(mut self, container_bytes: u32) -> Self {
+        self.container = Some((container_bytes, false));
+        self
+    }
+
+    /// Interprets integer samples as fixed point with `val` fractional bits.
+    #[inline]
+    pub const fn fraction_bits(mut self, val: u32) -> Self {
+        self.fraction_bits = val;
+        self
+    }
+
+    #[inline]
+    pub const fn non_mixable(mut self, val: bool) -> Self {
+        self.non_mixable = val;
+        self
+    }
+
+    pub const fn build(self) -> StreamBasicDesc {
+        let mut flags = 0;
+        if self.sample.is_float() {
+            flags |= FormatFlags::IS_FLOAT.0;
+        }
+        if self.sample.is_signed_int() {
+            flags |= FormatFlags::IS_SIGNED_INTEGER.0;
+        }
+        if self.big_endian {
+            flags |= FormatFlags::IS_BIG_ENDIAN.0;
+        }
+        if !self.interleaved {
+            flags |= FormatFlags::IS_NON_INTERLEAVED.0;
+        }
+        if self.non_mixable {
+            flags |= FormatFlags::IS_NON_MIXABLE.0;
+        }
+        flags |= (self.fraction_bits << FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_SHIFT.0)
+            & FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_MASK.0;
+
+        let sample_bytes = match self.container {
+            None => {
+                flags |= FormatFlags::IS_PACKED.0;
+                self.sample.bytes()
+            }
+            Some((bytes, high)) => {
+                if high {
+                    flags |= FormatFlags::IS_ALIGNED_HIGH.0;
+                }
+                bytes
+            }
+        };
+
+        let bytes_per_frame = if self.interleaved {
+            sample_bytes * self.channels
+        } else {
+            sample_bytes
+        };
+
+        StreamBasicDesc {
+            sample_rate: self.sample_rate,
+            format: Format::LINEAR_PCM,
+            format_flags: FormatFlags(flags),
+            bytes_per_packet: bytes_per_frame,
+            frames_per_packet: 1,
+            bytes_per_frame,
+            channels_per_frame: self.channels,
+            bits_per_channel: self.sample.bits(),
+            reserved: 0,
+        }
+    }
+}
+
+/// The reason [`StreamBasicDesc::validate`] rejected a description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamBasicDescError {
+    /// `format` is zero.
+    Format,
+    /// `sample_rate` is negative, infinite or NaN.
+    SampleRate,
+    /// Linear PCM without channels.
+    Channels,
+    /// Linear PCM with more than one frame per packet.
+    FramesPerPacket,
+    /// `bytes_per_packet` disagrees with `bytes_per_frame * frames_per_packet`.
+    BytesPerPacket,
+    /// `bytes_per_frame` is not a whole number of samples.
+    BytesPerFrame,
+    /// `bits_per_channel` does not fit the sample size or the sample type.
+    BitsPerChannel,
+    /// Contradicting or unknown `format_flags`.
+    Flags,
+}
+
+impl fmt::Display for StreamBasicDescError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::Format => "missing format",
+            Self::SampleRate => "invalid sample rate",
+            Self::Channels => "missing channels",
+            Self::FramesPerPacket => "linear PCM must have one frame per packet",
+            Self::BytesPerPacket => "bytes per packet do not match bytes per frame",
+            Self::BytesPerFrame => "bytes per frame do not match channels",
+            Self::BitsPerChannel => "bits per channel do not match the sample size",
+            Self::Flags => "inconsistent format flags",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for StreamBasicDescError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamBasicDescParseError;
+
+const PCM_FLAGS: u32 = FormatFlags::IS_FLOAT.0
+    | FormatFlags::IS_BIG_ENDIAN.0
+    | FormatFlags::IS_SIGNED_INTEGER.0
+    | FormatFlags::IS_PACKED.0
+    | FormatFlags::IS_ALIGNED_HIGH.0
+    | FormatFlags::IS_NON_INTERLEAVED.0
+    | FormatFlags::IS_NON_MIXABLE.0
+    | FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_MASK.0;
+
+impl StreamBasicDesc {
+    /// Starts a linear PCM description.
+    #[inline]
+    pub const fn pcm(sample: PcmSample, sample_rate: f64, channels: u32) -> PcmBuilder {
+        PcmBuilder::new(sample, sample_rate, channels)
+    }
+
+    /// Compressed description with variable packet sizes.
+    ///
+    /// `frames_per_packet` is 1024 for AAC LC, 2048 for HE-AAC v2, 0 if unknown.
+    #[inline]
+    pub const fn compressed(
+        format: Format,
+        sample_rate: f64,
+        channels: u32,
+        frames_per_packet: u32,
+    ) -> Self {
+        Self {
+            sample_rate,
+            format,
+            format_flags: FormatFlags(0),
+            bytes_per_packet: 0,
+            frames_per_packet,
+            bytes_per_frame: 0,
+            channels_per_frame: channels,
+            bits_per_channel: 0,
+            reserved: 0,
+        }
+    }
+
+    #[inline]
+    pub fn is_pcm(&self) -> bool {
+        self.format == Format::LINEAR_PCM
+    }
+
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        self.is_pcm() && self.format_flags.contains(FormatFlags::IS_FLOAT)
+    }
+
+    #[inline]
+    pub fn is_signed_int(&self) -> bool {
+        self.is_pcm()
+            && !self.format_flags.contains(FormatFlags::IS_FLOAT)
+            && self.format_flags.contains(FormatFlags::IS_SIGNED_INTEGER)
+    }
+
+    #[inline]
+    pub fn is_big_endian(&self) -> bool {
+        self.is_pcm() && self.format_flags.contains(FormatFlags::IS_BIG_ENDIAN)
+    }
+
+    /// Whether the samples occupy all bits of the channel, explicitly or implied
+    /// by the sizes.
+    #[inline]
+    pub fn is_packed(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_PACKED)
+            || self.sample_bytes().map(|b| b * 8) == Some(self.bits_per_channel)
+    }
+
+    #[inline]
+    pub fn is_aligned_high(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_ALIGNED_HIGH)
+    }
+
+    #[inline]
+    pub fn is_non_mixable(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_NON_MIXABLE)
+    }
+
+    /// Number of fractional bits of fixed point linear PCM.
+    #[inline]
+    pub fn fraction_bits(&self) -> u32 {
+        (self.format_flags.0 & FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_MASK.0)
+            >> FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_SHIFT.0
+    }
+
+    /// Size of one channel's sample in bytes, `None` for compressed formats.
+    #[inline]
+    pub fn sample_bytes(&self) -> Option<u32> {
+        let channels = self.interleaved_channels_num();
+        if self.bytes_per_frame == 0 || channels == 0 || self.bytes_per_frame % channels != 0 {
+            return None;
+        }
+        Some(self.bytes_per_frame / channels)
+    }
+
+    /// The [`PcmSample`] of packed linear PCM.
+    pub fn pcm_sample(&self) -> Option<PcmSample> {
+        if !self.is_pcm() || !self.is_packed() || self.fraction_bits() != 0 {
+            return None;
+        }
+        let signed = self.format_flags.contains(FormatFlags::IS_SIGNED_INTEGER);
+        Some(match (self.is_float(), signed, self.bits_per_channel) {
+            (true, _, 32) => PcmSample::F32,
+            (true, _, 64) => PcmSample::F64,
+            (false, false, 8) => PcmSample::U8,
+            (false, true, 8) => PcmSample::I8,
+            (false, true, 16) => PcmSample::I16,
+            (false, true, 24) => PcmSample::I24,
+            (false, true, 32) => PcmSample::I32,
+            _ => return None,
+        })
+    }
+
+    /// Checks that fields and flags describe one consistent format.
+    ///
+    /// ```
+    /// use cidre::cat;
+    ///
+    /// let mut asbd = cat::audio::StreamBasicDesc::common_f32(48_000.0, 2, true);
+    /// assert!(asbd.validate().is_ok());
+    ///
+    /// asbd.bytes_per_frame = 4;
+    /// assert_eq!(asbd.validate(), Err(cat::audio::StreamBasicDescError::BytesPerPacket));
+    /// ```
+    pub fn validate(&self) -> Result<(), StreamBasicDescError> {
+        use StreamBasicDescError as E;
+
+        if self.format.0 == 0 {
+            return Err(E::Format);
+        }
+        if !self.sample_rate.is_finite() || self.sample_rate < 0.0 {
+            return Err(E::SampleRate);
+        }
+        if !self.is_pcm() {
+            let frames_bytes = self.bytes_per_frame as u64 * self.frames_per_packet as u64;
+            if frames_bytes != 0
+                && self.bytes_per_packet != 0
+                && self.bytes_per_packet as u64 != frames_bytes
+            {
+                return Err(E::BytesPerPacket);
+            }
+            return Ok(());
+        }
+
+        let flags = self.format_flags;
+        if flags.0 & !PCM_FLAGS != 0 && flags != FormatFlags::ALL_CLEAR {
+            return Err(E::Flags);
+        }
+        if self.channels_per_frame == 0 {
+            return Err(E::Channels);
+        }
+        if self.frames_per_packet != 1 {
+            return Err(E::FramesPerPacket);
+        }
+        if self.bytes_per_packet != self.bytes_per_frame {
+            return Err(E::BytesPerPacket);
+        }
+        let Some(sample_bytes) = self.sample_bytes() else {
+            return Err(E::BytesPerFrame);
+        };
+        let bits = self.bits_per_channel;
+        if bits == 0 || bits > sample_bytes * 8 {
+            return Err(E::BitsPerChannel);
+        }
+        let packed = flags.contains(FormatFlags::IS_PACKED);
+        if packed && bits != sample_bytes * 8 {
+            return Err(E::BitsPerChannel);
+        }
+        if packed && flags.contains(FormatFlags::IS_ALIGNED_HIGH) {
+            return Err(E::Flags);
+        }
+        if flags.contains(FormatFlags::IS_FLOAT) {
+            if bits != 32 && bits != 64 {
+                return Err(E::BitsPerChannel);
+            }
+            if flags.contains(FormatFlags::IS_SIGNED_INTEGER) || self.fraction_bits() != 0 {
+                return Err(E::Flags);
+            }
+        } else if self.fraction_bits() >= bits {
+            return Err(E::Flags);
+        }
+        Ok(())
+    }
+
+    /// Bytes holding `frames`, rounded up to whole packets for compressed
+    /// constant bit rate formats. `None` for variable bit rate formats.
+    #[inline]
+    pub fn frames_to_bytes(&self, frames: u64) -> Option<u64> {
+        if self.bytes_per_frame != 0 {
+            return frames.checked_mul(self.bytes_per_frame as u64);
+        }
+        self.packets_to_bytes(self.frames_to_packets(frames)?)
+    }
+
+    /// Whole frames in `bytes`.
+    #[inline]
+    pub fn bytes_to_frames(&self, bytes: u64) -> Option<u64> {
+        if self.bytes_per_frame != 0 {
+            return Some(bytes / self.bytes_per_frame as u64);
+        }
+        self.packets_to_frames(self.bytes_to_packets(bytes)?)
+    }
+
+    /// Packets holding `frames`, rounded up.
+    #[inline]
+    pub fn frames_to_packets(&self, frames: u64) -> Option<u64> {
+        match self.frames_per_packet {
+            0 => None,
+            fpp => Some(frames.div_ceil(fpp as u64)),
+        }
+    }
+
+    #[inline]
+    pub fn packets_to_frames(&self, packets: u64) -> Option<u64> {
+        match self.frames_per_packet {
+            0 => None,
+            fpp => packets.checked_mul(fpp as u64),
+        }
+    }
+
+    #[inline]
+    pub fn packets_to_bytes(&self, packets: u64) -> Option<u64> {
+        match self.bytes_per_packet {
+            0 => None,
+            bpp => packets.checked_mul(bpp as u64),
+        }
+    }
+
+    /// Whole packets in `bytes`.
+    #[inline]
+    pub fn bytes_to_packets(&self, bytes: u64) -> Option<u64> {
+        match self.bytes_per_packet {
+            0 => None,
+            bpp => Some(bytes / bpp as u64),
+        }
+    }
+
+    fn fmt_pcm_head(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = self.format_flags;
+        f.write_str(if flags.contains(FormatFlags::IS_BIG_ENDIAN) {
+            "BE"
+        } else {
+            "LE"
+        })?;
+        f.write_str(if flags.contains(FormatFlags::IS_FLOAT) {
+            "F"
+        } else if flags.contains(FormatFlags::IS_SIGNED_INTEGER) {
+            "I"
+        } else {
+            "UI"
+        })?;
+        write!(f, "{}", self.bits_per_channel)?;
+        if !flags.contains(FormatFlags::IS_PACKED) {
+            let align = if self.is_aligned_high() { "hi" } else { "lo" };
+            write!(f, "in{}{align}", self.sample_bytes().unwrap_or(0) * 8)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compact description in the form of `LEF32@48000x2,deinterleaved`.
+///
+/// Valid linear PCM is written as endianness, `F`, `I` or `UI` sample type and
+/// bits per channel, followed by the container size and alignment of unpacked
+/// samples (`BEI24in32hi`). Other formats are written as their four char code
+/// with the non-zero fields as options (`aac@44100x2,fpp=1024`).
+///
+/// The output parses back into the same description.
+impl fmt::Display for StreamBasicDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pcm =
+            self.is_pcm() && self.format_flags != FormatFlags::ALL_CLEAR && self.validate().is_ok();
+
+        if pcm {
+            self.fmt_pcm_head(f)?;
+        } else {
+            let fcc = self.format.0.to_be_bytes();
+            let len = fcc.iter().rposition(|b| *b != b' ').map_or(0, |i| i + 1);
+            let fcc = &fcc[..len];
+            let printable = !fcc.is_empty()
+                && fcc
+                    .iter()
+                    .all(|b| b.is_ascii_graphic() && !matches!(b, b'@' | b',' | b'='))
+                && parse_pcm_head(fcc).is_none()
+                && !fcc.starts_with(b"0x");
+            if printable {
+                // fcc is ascii graphic
+                f.write_str(std::str::from_utf8(fcc).unwrap_or_default())?;
+            } else {
+                write!(f, "0x{:08x}", self.format.0)?;
+            }
+        }
+
+        write!(f, "@{}x{}", self.sample_rate, self.channels_per_frame)?;
+
+        if pcm {
+            if !self.is_interleaved() {
+                f.write_str(",deinterleaved")?;
+            }
+            if self.is_non_mixable() {
+                f.write_str(",nonmixable")?;
+            }
+            if self.fraction_bits() != 0 {
+                write!(f, ",frac={}", self.fraction_bits())?;
+            }
+            return Ok(());
+        }
+
+        if self.format_flags.0 != 0 {
+            write!(f, ",flags=0x{:x}", self.format_flags.0)?;
+        }
+        for (name, val) in [
+            ("fpp", self.frames_per_packet),
+            ("bpp", self.bytes_per_packet),
+            ("bpf", self.bytes_per_frame),
+            ("bits", self.bits_per_channel),
+        ] {
+            if val != 0 {
+                write!(f, ",{name}={val}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Flags, bits per channel and container bits of `LEI24in32hi` like heads.
+fn parse_pcm_head(head: &[u8]) -> Option<(u32, u32, Option<u32>)> {
+    let (mut flags, rest) = match head.split_at_checked(2)? {
+        (b"LE", rest) => (0, rest),
+        (b"BE", rest) => (FormatFlags::IS_BIG_ENDIAN.0, rest),
+        _ => return None,
+    };
+    let rest = if let Some(rest) = rest.strip_prefix(b"UI") {
+        rest
+    } else if let Some(rest) = rest.strip_prefix(b"I") {
+        flags |= FormatFlags::IS_SIGNED_INTEGER.0;
+        rest
+    } else if let Some(rest) = rest.strip_prefix(b"F") {
+        flags |= FormatFlags::IS_FLOAT.0;
+        rest
+    } else {
+        return None;
+    };
+
+    let digits = |s: &[u8]| -> Option<(u32, usize)> {
+        let n = s.iter().take_while(|b| b.is_ascii_digit()).count();
+        let val = std::str::from_utf8(&s[..n]).ok()?.parse().ok()?;
+        Some((val, n))
+    };
+
+    let (bits, n) = digits(rest)?;
+    let rest = &rest[n..];
+    if rest.is_empty() {
+        flags |= FormatFlags::IS_PACKED.0;
+        return Some((flags, bits, None));
+    }
+    let rest = rest.strip_prefix(b"in")?;
+    let (container, n) = digits(rest)?;
+    match &rest[n..] {
+        b"hi" => flags |= FormatFlags::IS_ALIGNED_HIGH.0,
+        b"lo" => {}
+        _ => return None,
+    }
+    if container % 8 != 0 {
+        return None;
+    }
+    Some((flags, bits, Some(container / 8)))
+}
+
+impl FromStr for StreamBasicDesc {
+    type Err = StreamBasicDescParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = StreamBasicDescParseError;
+        let (head, rest) = s.split_once('@').ok_or(err)?;
+        let mut opts = rest.split(',');
+        let (rate, channels) = opts.next().ok_or(err)?.rsplit_once('x').ok_or(err)?;
+        let sample_rate: f64 = rate.parse().map_err(|_| err)?;
+        let channels: u32 = channels.parse().map_err(|_| err)?;
+
+        if let Some((mut flags, bits, container)) = parse_pcm_head(head.as_bytes()) {
+            let mut interleaved = true;
+            for opt in opts {
+                match opt.split_once('=') {
+                    None if opt == "deinterleaved" => interleaved = false,
+                    None if opt == "nonmixable" => flags |= FormatFlags::IS_NON_MIXABLE.0,
+                    Some(("frac", val)) => {
+                        let val: u32 = val.parse().map_err(|_| err)?;
+                        if val > 0x3f {
+                            return Err(err);
+                        }
+                        flags |= val << FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_SHIFT.0;
+                    }
+                    _ => return Err(err),
+                }
+            }
+            if !interleaved {
+                flags |= FormatFlags::IS_NON_INTERLEAVED.0;
+            }
+            let sample_bytes = container.unwrap_or(bits.div_ceil(8));
+            let bytes_per_frame = if interleaved {
+                sample_bytes.checked_mul(channels).ok_or(err)?
+            } else {
+                sample_bytes
+            };
+            return Ok(Self {
+                sample_rate,
+                format: Format::LINEAR_PCM,
+                format_flags: FormatFlags(flags),
+                bytes_per_packet: bytes_per_frame,
+                frames_per_packet: 1,
+                bytes_per_frame,
+                channels_per_frame: channels,
+                bits_per_channel: bits,
+                reserved: 0,
+            });
+        }
+
+        let format = if let Some(hex) = head.strip_prefix("0x") {
+            u32::from_str_radix(hex, 16).map_err(|_| err)?
+        } else {
+            let bytes = head.as_bytes();
+            if bytes.is_empty() || bytes.len() > 4 || !bytes.iter().all(u8::is_ascii_graphic) {
+                return Err(err);
+            }
+            let mut fcc = [b' '; 4];
+            fcc[..bytes.len()].copy_from_slice(bytes);
+            u32::from_be_bytes(fcc)
+        };
+
+        let mut asbd = Self::compressed(Format(format), sample_rate, channels, 0);
+        for opt in opts {
+            let (name, val) = opt.split_once('=').ok_or(err)?;
+            let val = if name == "flags" {
+                let hex = val.strip_prefix("0x").ok_or(err)?;
+                u32::from_str_radix(hex, 16).map_err(|_| err)?
+            } else {
+                val.parse().map_err(|_| err)?
+            };
+            match name {
+                "flags" => asbd.format_flags = FormatFlags(val),
+                "fpp" => asbd.frames_per_packet = val,
+                "bpp" => asbd.bytes_per_packet = val,
+                "bpf" => asbd.bytes_per_frame = val,
+                "bits" => asbd.bits_per_channel = val,
+                _ => return Err(err),
+            }
+        }
+        Ok(asbd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cat::audio::{
+        Format, FormatFlags, PcmSample, StreamBasicDesc, StreamBasicDescError,
+    };
+
+    #[test]
+    fn builder() {
+        let asbd = StreamBasicDesc::pcm(PcmSample::F32, 44_100.0, 2)
+            .interleaved(false)
+            .build();
+        assert_eq!(asbd, StreamBasicDesc::common_f32(44_100.0, 2, false));
+        assert_eq!(asbd.pcm_sample(), Some(PcmSample::F32));
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::I16, 48_000.0, 2).build();
+        assert_eq!(asbd.bytes_per_frame, 4);
+        assert!(asbd.is_signed_int());
+        assert!(asbd.is_packed());
+        assert_eq!(asbd.pcm_sample(), Some(PcmSample::I16));
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::I24, 48_000.0, 6).build();
+        assert_eq!(asbd.bytes_per_frame, 18);
+        assert_eq!(asbd.bytes_per_packet, 18);
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::I24, 48_000.0, 2)
+            .aligned_high(4)
+            .build();
+        assert_eq!(asbd.bytes_per_frame, 8);
+        assert!(asbd.is_aligned_high());
+        assert!(!asbd.is_packed());
+        assert_eq!(asbd.pcm_sample(), None);
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::I32, 48_000.0, 1)
+            .fraction_bits(24)
+            .build();
+        assert_eq!(asbd.fraction_bits(), 24);
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::F64, 96_000.0, 2)
+            .big_endian(true)
+            .build();
+        assert!(asbd.is_big_endian());
+        assert!(asbd.is_float());
+        assert_eq!(asbd.bytes_per_frame, 16);
+
+        for sample in [
+            PcmSample::U8,
+            PcmSample::I8,
+            PcmSample::I16,
+            PcmSample::I24,
+            PcmSample::I32,
+            PcmSample::F32,
+            PcmSample::F64,
+        ] {
+            for interleaved in [true, false] {
+                let asbd = StreamBasicDesc::pcm(sample, 8_000.0, 3)
+                    .interleaved(interleaved)
+                    .build();
+                assert_eq!(asbd.validate(), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn validate() {
+        use StreamBasicDescError as E;
+
+        let good = StreamBasicDesc::pcm(PcmSample::I16, 48_000.0, 2).build();
+
+        let mut asbd = good;
+        asbd.bytes_per_frame = 2;
+        asbd.bytes_per_packet = 2;
+        assert_eq!(asbd.validate(), Err(E::BitsPerChannel));
+
+        let mut asbd = good;
+        asbd.bytes_per_frame = 3;
+        asbd.bytes_per_packet = 3;
+        assert_eq!(asbd.validate(), Err(E::BytesPerFrame));
+
+        let mut asbd = good;
+        asbd.bits_per_channel = 12;
+        assert_eq!(asbd.validate(), Err(E::BitsPerChannel));
+        asbd.format_flags.remove(FormatFlags::IS_PACKED);
+        assert_eq!(asbd.validate(), Ok(()));
+        asbd.format_flags.insert(FormatFlags::IS_PACKED);
+        asbd.format_flags.insert(FormatFlags::IS_ALIGNED_HIGH);
+        asbd.bits_per_channel = 16;
+        assert_eq!(asbd.validate(), Err(E::Flags));
+
+        let mut asbd = good;
+        asbd.format_flags.insert(FormatFlags::IS_FLOAT);
+        assert_eq!(asbd.validate(), Err(E::BitsPerChannel));
+
+        let mut asbd = good;
+        asbd.frames_per_packet = 2;
+        assert_eq!(asbd.validate(), Err(E::FramesPerPacket));
+
+        let mut asbd = good;
+        asbd.sample_rate = f64::NAN;
+        assert_eq!(asbd.validate(), Err(E::SampleRate));
+
+        let mut asbd = good;
+        asbd.channels_per_frame = 0;
+        assert_eq!(asbd.validate(), Err(E::Channels));
+
+        let mut asbd = good;
+        asbd.format_flags = FormatFlags(asbd.format_flags.0 | 1 << 30);
+        assert_eq!(asbd.validate(), Err(E::Flags));
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::I16, 48_000.0, 2)
+            .fraction_bits(16)
+            .build();
+        assert_eq!(asbd.validate(), Err(E::Flags));
+
+        let asbd = StreamBasicDesc::compressed(Format::MPEG4_AAC, 44_100.0, 2, 1024);
+        assert_eq!(asbd.validate(), Ok(()));
+
+        let asbd = StreamBasicDesc::compressed(Format(0), 44_100.0, 2, 1024);
+        assert_eq!(asbd.validate(), Err(E::Format));
+    }
+
+    #[test]
+    fn conversions() {
+        let asbd = StreamBasicDesc::pcm(PcmSample::I16, 48_000.0, 2).build();
+        assert_eq!(asbd.frames_to_bytes(480), Some(1920));
+        assert_eq!(asbd.bytes_to_frames(1921), Some(480));
+        assert_eq!(asbd.frames_to_packets(480), Some(480));
+        assert_eq!(asbd.packets_to_bytes(2), Some(8));
+
+        let aac = StreamBasicDesc::compressed(Format::MPEG4_AAC, 44_100.0, 2, 1024);
+        assert_eq!(aac.frames_to_packets(1025), Some(2));
+        assert_eq!(aac.packets_to_frames(3), Some(3072));
+        assert_eq!(aac.frames_to_bytes(1024), None);
+        assert_eq!(aac.bytes_to_packets(100), None);
+
+        let mut ima = StreamBasicDesc::compressed(Format::APPLE_IMA4, 44_100.0, 1, 64);
+        ima.bytes_per_packet = 34;
+        assert_eq!(ima.frames_to_bytes(65), Some(68));
+        assert_eq!(ima.bytes_to_frames(68), Some(128));
+        assert_eq!(ima.bytes_to_frames(67), Some(64));
+    }
+
+    #[test]
+    fn strings() {
+        let asbd = StreamBasicDesc::common_f32(48_000.0, 2, false);
+        assert_eq!(asbd.to_string(), "LEF32@48000x2,deinterleaved");
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::U8, 8_000.0, 1)
+            .non_mixable(true)
+            .build();
+        assert_eq!(asbd.to_string(), "LEUI8@8000x1,nonmixable");
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::I32, 44_100.0, 2)
+            .fraction_bits(24)
+            .build();
+        assert_eq!(asbd.to_string(), "LEI32@44100x2,frac=24");
+
+        let asbd = StreamBasicDesc::pcm(PcmSample::I16, 22_050.0, 2)
+            .aligned_low(4)
+            .big_endian(true)
+            .build();
+        assert_eq!(asbd.to_string(), "BEI16in32lo@22050x2");
+
+        let aac = StreamBasicDesc::compressed(Format::MPEG4_AAC, 44_100.0, 2, 1024);
+        assert_eq!(aac.to_string(), "aac@44100x2,fpp=1024");
+
+        let mut broken = StreamBasicDesc::pcm(PcmSample::I16, 48_000.0, 2).build();
+        broken.bytes_per_packet = 0;
+        assert_eq!(
+            broken.to_string(),
+            "lpcm@48000x2,flags=0xc,fpp=1,bpf=4,bits=16"
+        );
+
+        let ima = StreamBasicDesc::compressed(Format::DVI_INTEL_IMA, 32_000.5, 1, 0);
+        assert_eq!(ima.to_string(), "0x6d730011@32000.5x1");
+
+        for asbd in [
+            StreamBasicDesc::common_f32(48_000.0, 2, true),
+            StreamBasicDesc::pcm(PcmSample::I24, 96_000.0, 8)
+                .aligned_high(4)
+                .interleaved(false)
+                .build(),
+            StreamBasicDesc::pcm(PcmSample::F64, 44_100.0, 1)
+                .big_endian(true)
+                .build(),
+            StreamBasicDesc::pcm(PcmSample::I16, 48_000.0, 2)
+                .aligned_low(2)
+                .build(),
+            aac,
+            broken,
+            ima,
+        ] {
+            let s = asbd.to_string();
+            assert_eq!(s.parse::<StreamBasicDesc>(), Ok(asbd), "{s}");
+        }
+
+        assert_eq!(
+            "LEI16@48000x2".parse::<StreamBasicDesc>(),
+            Ok(StreamBasicDesc::pcm(PcmSample::I16, 48_000.0, 2)
+                .big_endian(false)
+                .build())
+        );
+        for s in [
+            "",
+            "LEF32",
+            "LEF32@48000",
+            "LEF32@48000x",
+            "LEF32@x2",
+            "LEF32@48000x2,",
+            "LEF32@48000x2,fpp=1",
+            "LEI24in30hi@48000x2",
+            "LEI24in32@48000x2",
+            "toolong@48000x2",
+            "aac@44100x2,deinterleaved",
+            "aac@44100x2,flags=12",
+        ] {
+            assert!(s.parse::<StreamBasicDesc>().is_err(), "{s}");
+        }
+    }
+}
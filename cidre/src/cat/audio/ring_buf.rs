@@ -0,0 +1,671 @@
+//! Single producer, single consumer audio ring buffer addressed by sample time,
+//! the `CARingBuffer` of Core Audio utility classes.
+//!
+//! The writer never waits for the reader. Frames overwritten while the reader copies
+//! them are zeroed and reported as [`Error::Overload`]. Samples live in relaxed atomics,
+//! so such a race is never undefined behaviour, and time bounds are published through
+//! a queue of sequence locked slots.
+
+#[cfg(loom)]
+use loom::sync::{
+    Arc,
+    atomic::{AtomicI64, AtomicU8, AtomicU64, Ordering, fence},
+};
+#[cfg(not(loom))]
+use std::sync::{
+    Arc,
+    atomic::{AtomicI64, AtomicU8, AtomicU64, Ordering, fence},
+};
+
+use std::ops::Range;
+
+use super::{BufList, StreamBasicDesc, TimeStamp, TimeStampFlags};
+
+/// Bounds slots, the reader retries when the writer laps it
+const BOUNDS: usize = 32;
+
+/// Bounds reads before giving up
+const BOUNDS_RETRIES: usize = 8;
+
+/// Slot sequence while the writer updates it
+const UPDATING: u64 = u64::MAX;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// More frames than the ring holds.
+    #[doc(alias = "kCARingBufferError_TooMuch")]
+    TooMuch,
+
+    /// The writer overwrote frames being fetched or time bounds didn't settle.
+    #[doc(alias = "kCARingBufferError_CPUOverload")]
+    Overload,
+
+    /// Buffer count or size doesn't match the ring.
+    BufList,
+
+    /// Time stamp without a valid sample time.
+    SampleTime,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::TooMuch => "too many frames for ring buffer",
+            Self::Overload => "ring buffer overload",
+            Self::BufList => "buffer list doesn't match ring buffer",
+            Self::SampleTime => "time stamp without sample time",
+        })
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+/// Sample time of `ts` in whole frames
+pub fn sample_time(ts: &TimeStamp) -> Result<i64> {
+    if ts.flags.0 & TimeStampFlags::SAMPLE_TIME_VALID.0 == 0 || !ts.sample_time.is_finite() {
+        return Err(Error::SampleTime);
+    }
+    Ok(ts.sample_time as i64)
+}
+
+struct Bounds {
+    start: AtomicI64,
+    end: AtomicI64,
+    /// Update index stored here, `UPDATING` while written
+    seq: AtomicU64,
+}
+
+/// Storage shared by a [`Writer`] and a [`Reader`].
+pub struct RingBuf {
+    buffers: usize,
+    bytes_per_frame: usize,
+    capacity: usize,
+    /// `buffers` rings of `capacity * bytes_per_frame` bytes
+    data: Box<[AtomicU8]>,
+    bounds: [Bounds; BOUNDS],
+    /// Index of the latest bounds update
+    index: AtomicU64,
+}
+
+impl RingBuf {
+    /// Ring of `buffers` buffers holding at least `capacity` frames of `bytes_per_frame` each.
+    ///
+    /// Capacity is rounded up to a power of two.
+    #[doc(alias = "CARingBuffer::Allocate")]
+    pub fn with_capacity(
+        buffers: usize,
+        bytes_per_frame: usize,
+        capacity: usize,
+    ) -> (Writer, Reader) {
+        assert!(buffers > 0 && bytes_per_frame > 0, "empty frames");
+        let capacity = capacity.max(1).next_power_of_two();
+        let data = (0..buffers * bytes_per_frame * capacity)
+            .map(|_| AtomicU8::new(0))
+            .collect();
+        let bounds = std::array::from_fn(|_| Bounds {
+            start: AtomicI64::new(0),
+            end: AtomicI64::new(0),
+            seq: AtomicU64::new(0),
+        });
+        let ring = Arc::new(Self {
+            buffers,
+            bytes_per_frame,
+            capacity,
+            data,
+            bounds,
+            index: AtomicU64::new(0),
+        });
+        (
+            Writer {
+                ring: ring.clone(),
+                start: 0,
+                end: 0,
+                index: 0,
+            },
+            Reader { ring },
+        )
+    }
+
+    /// Ring for buffer lists of `asbd`, one buffer per channel when non-interleaved.
+    pub fn with_asbd(asbd: &StreamBasicDesc, capacity: usize) -> (Writer, Reader) {
+        let buffers = if asbd.is_interleaved() {
+            1
+        } else {
+            asbd.channels_per_frame as usize
+        };
+        Self::with_capacity(buffers, asbd.bytes_per_frame as usize, capacity)
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn buffers(&self) -> usize {
+        self.buffers
+    }
+
+    #[inline]
+    pub fn bytes_per_frame(&self) -> usize {
+        self.bytes_per_frame
+    }
+
+    /// Frames currently held, `start..end` in sample time.
+    #[doc(alias = "CARingBuffer::GetTimeBounds")]
+    pub fn time_bounds(&self) -> Result<Range<i64>> {
+        for _ in 0..BOUNDS_RETRIES {
+            let index = self.index.load(Ordering::Acquire);
+            let slot = &self.bounds[index as usize % BOUNDS];
+            if slot.seq.load(Ordering::Acquire) != index {
+                continue;
+            }
+            let start = slot.start.load(Ordering::Relaxed);
+            let end = slot.end.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if slot.seq.load(Ordering::Relaxed) == index {
+                return Ok(start..end);
+            }
+        }
+        Err(Error::Overload)
+    }
+
+    /// Byte range of `frames` frames at `time` in a buffer ring, split at the wrap point
+    fn spans(&self, buffer: usize, time: i64, frames: usize) -> [Range<usize>; 2] {
+        let base = buffer * self.capacity * self.bytes_per_frame;
+        let offset = (time as usize & (self.capacity - 1)) * self.bytes_per_frame;
+        let len = frames * self.bytes_per_frame;
+        let ring = self.capacity * self.bytes_per_frame;
+        let first = len.min(ring - offset);
+        [
+            base + offset..base + offset + first,
+            base..base + len - first,
+        ]
+    }
+
+    fn write(&self, buffer: usize, time: i64, src: &[u8]) {
+        let mut src = src.iter();
+        for span in self.spans(buffer, time, src.len() / self.bytes_per_frame) {
+            for (d, s) in self.data[span].iter().zip(&mut src) {
+                d.store(*s, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn zero(&self, time: i64, frames: usize) {
+        for buffer in 0..self.buffers {
+            for span in self.spans(buffer, time, frames) {
+                for d in &self.data[span] {
+                    d.store(0, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    fn read(&self, buffer: usize, time: i64, dst: &mut [u8]) {
+        let mut dst = dst.iter_mut();
+        for span in self.spans(buffer, time, dst.len() / self.bytes_per_frame) {
+            for (s, d) in self.data[span].iter().zip(&mut dst) {
+                *d = s.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Storing half of a [`RingBuf`], safe to use from a render callback.
+pub struct Writer {
+    ring: Arc<RingBuf>,
+    /// Latest published bounds and their index
+    start: i64,
+    end: i64,
+    index: u64,
+}
+
+impl Writer {
+    #[inline]
+    pub fn ring(&self) -> &RingBuf {
+        &self.ring
+    }
+
+    /// Frames currently held, as published to the reader.
+    #[inline]
+    pub fn time_bounds(&self) -> Range<i64> {
+        self.start..self.end
+    }
+
+    /// Copies `frames` frames of each buffer to sample time `start`.
+    ///
+    /// Writing before the current end restarts the ring at `start`, writing past it
+    /// zero fills the gap. The oldest frames are dropped when the ring is full.
+    #[doc(alias = "CARingBuffer::Store")]
+    pub fn store(&mut self, src: &[&[u8]], frames: usize, start: i64) -> Result {
+        let bpf = self.ring.bytes_per_frame;
+        if src.len() != self.ring.buffers || src.iter().any(|b| b.len() < frames * bpf) {
+            return Err(Error::BufList);
+        }
+        if frames > self.ring.capacity {
+            return Err(Error::TooMuch);
+        }
+        if frames == 0 {
+            return Ok(());
+        }
+        let end = start + frames as i64;
+        let capacity = self.ring.capacity as i64;
+
+        // readers must not see frames about to be overwritten
+        if start < self.end || self.start == self.end {
+            self.set_bounds(start, start);
+        } else if end - self.start > capacity {
+            let new_start = end - capacity;
+            self.set_bounds(new_start, self.end.max(new_start));
+        }
+        fence(Ordering::Release);
+
+        let ring = &*self.ring;
+        if start > self.end {
+            let gap = self.end.max(end - capacity)..start;
+            ring.zero(gap.start, (gap.end - gap.start) as usize);
+        }
+        for (buffer, src) in src.iter().enumerate() {
+            ring.write(buffer, start, &src[..frames * bpf]);
+        }
+
+        let new_start = self.start.max(end - capacity);
+        self.set_bounds(new_start, end);
+        Ok(())
+    }
+
+    /// Stores `frames` frames of `list` at the sample time of `ts`.
+    ///
+    /// # Safety
+    ///
+    /// Buffers must point to `data_bytes_size` readable bytes.
+    pub unsafe fn store_buf_list<const N: usize>(
+        &mut self,
+        list: &BufList<N>,
+        frames: u32,
+        ts: &TimeStamp,
+    ) -> Result {
+        let start = sample_time(ts)?;
+        if list.number_buffers as usize != N {
+            return Err(Error::BufList);
+        }
+        let src: [&[u8]; N] = std::array::from_fn(|i| {
+            let buf = &list.buffers[i];
+            unsafe { std::slice::from_raw_parts(buf.data, buf.data_bytes_size as usize) }
+        });
+        self.store(&src, frames as usize, start)
+    }
+
+    fn set_bounds(&mut self, start: i64, end: i64) {
+        self.index += 1;
+        let slot = &self.ring.bounds[self.index as usize % BOUNDS];
+        slot.seq.store(UPDATING, Ordering::Relaxed);
+        fence(Ordering::Release);
+        slot.start.store(start, Ordering::Relaxed);
+        slot.end.store(end, Ordering::Relaxed);
+        slot.seq.store(self.index, Ordering::Release);
+        self.ring.index.store(self.index, Ordering::Release);
+        self.start = start;
+        self.end = end;
+    }
+}
+
+/// Fetching half of a [`RingBuf`], safe to use from a render callback.
+pub struct Reader {
+    ring: Arc<RingBuf>,
+}
+
+impl Reader {
+    #[inline]
+    pub fn ring(&self) -> &RingBuf {
+        &self.ring
+    }
+
+    /// Frames currently held.
+    #[inline]
+    pub fn time_bounds(&self) -> Result<Range<i64>> {
+        self.ring.time_bounds()
+    }
+
+    /// Copies `frames` frames of each buffer from sample time `start`.
+    ///
+    /// Frames outside the time bounds are zero. Returns [`Error::Overload`] with
+    /// the overwritten frames zeroed when the writer catches up or goes back in time
+    /// during the copy.
+    #[doc(alias = "CARingBuffer::Fetch")]
+    pub fn fetch(&self, dst: &mut [&mut [u8]], frames: usize, start: i64) -> Result {
+        let ring = &*self.ring;
+        let bpf = ring.bytes_per_frame;
+        if dst.len() != ring.buffers || dst.iter().any(|b| b.len() < frames * bpf) {
+            return Err(Error::BufList);
+        }
+        if frames > ring.capacity {
+            return Err(Error::TooMuch);
+        }
+        if frames == 0 {
+            return Ok(());
+        }
+        let copied = self.copy(dst, frames, start)?;
+        self.check(dst, start, copied)
+    }
+
+    /// Copies frames held of `start..start + frames`, zeroes the rest.
+    fn copy(&self, dst: &mut [&mut [u8]], frames: usize, start: i64) -> Result<Range<i64>> {
+        let ring = &*self.ring;
+        let bpf = ring.bytes_per_frame;
+        let end = start + frames as i64;
+        let bounds = ring.time_bounds()?;
+        let from = start.max(bounds.start).min(end);
+        let to = end.min(bounds.end).max(from);
+        let valid = (from - start) as usize * bpf..(to - start) as usize * bpf;
+
+        for dst in dst.iter_mut() {
+            let dst = &mut dst[..frames * bpf];
+            dst[..valid.start].fill(0);
+            dst[valid.end..].fill(0);
+        }
+        for (buffer, dst) in dst.iter_mut().enumerate() {
+            ring.read(buffer, from, &mut dst[valid.clone()]);
+        }
+        Ok(from..to)
+    }
+
+    /// Zeroes `copied` frames the writer may have overwritten since the copy.
+    #[doc(alias = "CARingBuffer::CheckTimeBounds")]
+    fn check(&self, dst: &mut [&mut [u8]], start: i64, copied: Range<i64>) -> Result {
+        if copied.is_empty() {
+            return Ok(());
+        }
+        fence(Ordering::Acquire);
+        let now = self.ring.time_bounds()?;
+        let bpf = self.ring.bytes_per_frame;
+        let valid = (copied.start - start) as usize * bpf..(copied.end - start) as usize * bpf;
+        let lost = if now.end < copied.end {
+            // restarted before the copied frames, any slot may be rewritten
+            valid.end
+        } else if now.start > copied.start {
+            (now.start.min(copied.end) - start) as usize * bpf
+        } else {
+            return Ok(());
+        };
+        for dst in dst.iter_mut() {
+            dst[valid.start..lost].fill(0);
+        }
+        Err(Error::Overload)
+    }
+
+    /// Fetches `frames` frames at the sample time of `ts` into `list`
+    /// and sets the buffer sizes.
+    ///
+    /// # Safety
+    ///
+    /// Buffers must point to `data_bytes_size` writable bytes.
+    pub unsafe fn fetch_buf_list<const N: usize>(
+        &self,
+        list: &mut BufList<N>,
+        frames: u32,
+        ts: &TimeStamp,
+    ) -> Result {
+        let start = sample_time(ts)?;
+        if list.number_buffers as usize != N {
+            return Err(Error::BufList);
+        }
+        let mut dst: [&mut [u8]; N] = std::array::from_fn(|i| {
+            let buf = &list.buffers[i];
+            unsafe { std::slice::from_raw_parts_mut(buf.data, buf.data_bytes_size as usize) }
+        });
+        let res = self.fetch(&mut dst, frames as usize, start);
+        if !matches!(res, Err(Error::BufList | Error::TooMuch)) {
+            let size = frames * self.ring.bytes_per_frame as u32;
+            for buf in list.buffers.iter_mut() {
+                buf.data_bytes_size = size;
+            }
+        }
+        res
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use crate::cat::audio::{
+        BufList, StreamBasicDesc, TimeStamp,
+        ring_buf::{Error, RingBuf},
+    };
+
+    fn frames(times: std::ops::Range<u32>) -> Vec<u8> {
+        times.flat_map(u32::to_le_bytes).collect()
+    }
+
+    fn fetch(r: &crate::cat::audio::ring_buf::Reader, n: usize, start: i64) -> Vec<u8> {
+        let mut out = vec![0xffu8; n * 4];
+        r.fetch(&mut [&mut out], n, start).unwrap();
+        out
+    }
+
+    #[test]
+    fn basics() {
+        let (mut w, r) = RingBuf::with_capacity(1, 4, 6);
+        assert_eq!(r.ring().capacity(), 8);
+        assert_eq!(r.time_bounds(), Ok(0..0));
+
+        w.store(&[&frames(100..104)], 4, 100).unwrap();
+        assert_eq!(r.time_bounds(), Ok(100..104));
+        let mut expected = vec![0u8; 8];
+        expected.extend(frames(100..104));
+        assert_eq!(fetch(&r, 6, 98), expected);
+
+        // gap is zero filled
+        w.store(&[&frames(106..108)], 2, 106).unwrap();
+        assert_eq!(r.time_bounds(), Ok(100..108));
+        let mut expected = frames(102..104);
+        expected.extend([0; 8]);
+        expected.extend(frames(106..107));
+        assert_eq!(fetch(&r, 5, 102), expected);
+
+        // oldest frames are dropped
+        w.store(&[&frames(108..112)], 4, 108).unwrap();
+        assert_eq!(r.time_bounds(), Ok(104..112));
+        assert_eq!(fetch(&r, 4, 100), [0; 16]);
+        assert_eq!(fetch(&r, 2, 110), frames(110..112));
+
+        // far past the end, everything held is zero
+        w.store(&[&frames(1000..1002)], 2, 1000).unwrap();
+        assert_eq!(r.time_bounds(), Ok(994..1002));
+        assert_eq!(fetch(&r, 2, 994), [0; 8]);
+
+        // going back drops everything
+        w.store(&[&frames(50..52)], 2, 50).unwrap();
+        assert_eq!(w.time_bounds(), 50..52);
+        assert_eq!(r.time_bounds(), Ok(50..52));
+        assert_eq!(fetch(&r, 3, 50)[..8], frames(50..52));
+    }
+
+    #[test]
+    fn errors() {
+        let (mut w, r) = RingBuf::with_capacity(2, 4, 8);
+        let data = frames(0..9);
+        assert_eq!(w.store(&[&data, &data], 9, 0), Err(Error::TooMuch));
+        assert_eq!(w.store(&[&data], 1, 0), Err(Error::BufList));
+        assert_eq!(w.store(&[&data, &data[..4]], 2, 0), Err(Error::BufList));
+
+        let mut out = [0u8; 8];
+        assert_eq!(r.fetch(&mut [&mut out], 2, 0), Err(Error::BufList));
+
+        let list = BufList::<2>::default();
+        let res = unsafe { w.store_buf_list(&list, 0, &TimeStamp::invalid()) };
+        assert_eq!(res, Err(Error::SampleTime));
+    }
+
+    #[test]
+    fn buf_list() {
+        let asbd = StreamBasicDesc::common_f32(48_000.0, 2, false);
+        let (mut w, r) = RingBuf::with_asbd(&asbd, 512);
+        assert_eq!(w.ring().buffers(), 2);
+        assert_eq!(w.ring().bytes_per_frame(), 4);
+
+        let mut l: Vec<f32> = (0..256).map(|i| i as f32).collect();
+        let mut r_ch: Vec<f32> = (0..256).map(|i| -i as f32).collect();
+        let mut list = BufList::<2>::default();
+        for (buf, data) in list.buffers.iter_mut().zip([&mut l, &mut r_ch]) {
+            buf.number_channels = 1;
+            buf.data = data.as_mut_ptr() as _;
+            buf.data_bytes_size = (data.len() * 4) as u32;
+        }
+        let ts = TimeStamp::with_sample_time(480.0);
+        unsafe { w.store_buf_list(&list, 256, &ts) }.unwrap();
+        assert_eq!(r.time_bounds(), Ok(480..736));
+
+        let mut out_l = vec![1f32; 256];
+        let mut out_r = vec![1f32; 256];
+        let mut out = BufList::<2>::default();
+        for (buf, data) in out.buffers.iter_mut().zip([&mut out_l, &mut out_r]) {
+            buf.number_channels = 1;
+            buf.data = data.as_mut_ptr() as _;
+            buf.data_bytes_size = (data.len() * 4) as u32;
+        }
+        let ts = TimeStamp::with_sample_time(600.0);
+        unsafe { r.fetch_buf_list(&mut out, 128, &ts) }.unwrap();
+        assert_eq!(out.buffers[1].data_bytes_size, 512);
+        assert_eq!(out_l[0], 120.0);
+        assert_eq!(out_r[127], -247.0);
+        assert_eq!(out_l[128], 1.0);
+    }
+
+    #[test]
+    fn stress() {
+        const BLOCK: u32 = 64;
+        const BLOCKS: u32 = 5_000;
+
+        let (mut w, r) = RingBuf::with_capacity(2, 4, 256);
+        let writer = std::thread::spawn(move || {
+            // zero marks frames outside the bounds
+            let mut time = 1;
+            for _ in 0..BLOCKS {
+                let a = frames(time..time + BLOCK);
+                let b: Vec<u8> = (time..time + BLOCK)
+                    .flat_map(|t| (!t).to_le_bytes())
+                    .collect();
+                w.store(&[&a, &b], BLOCK as usize, time as i64).unwrap();
+                time += BLOCK;
+            }
+        });
+
+        let mut rng = 0x2545_f491u32;
+        let (mut read, mut overloads) = (0, 0);
+        let mut a = vec![0u8; 48 * 4];
+        let mut b = vec![0u8; 48 * 4];
+        while !writer.is_finished() || read == 0 {
+            let Ok(bounds) = r.time_bounds() else {
+                overloads += 1;
+                continue;
+            };
+            rng ^= rng << 13;
+            rng ^= rng >> 17;
+            rng ^= rng << 5;
+            let start = bounds.end - (rng % 300) as i64;
+            match r.fetch(&mut [&mut a, &mut b], 48, start) {
+                Ok(()) => read += 1,
+                Err(Error::Overload) => overloads += 1,
+                Err(e) => panic!("{e}"),
+            }
+            for (i, (a, b)) in a.chunks_exact(4).zip(b.chunks_exact(4)).enumerate() {
+                let t = (start + i as i64) as u32;
+                let a = u32::from_le_bytes(a.try_into().unwrap());
+                let b = u32::from_le_bytes(b.try_into().unwrap());
+                assert!((a, b) == (0, 0) || (a, b) == (t, !t), "frame {t}: {a} {b}");
+            }
+        }
+        writer.join().unwrap();
+        assert!(read > 0, "{overloads} overloads");
+
+        let end = (BLOCK * BLOCKS + 1) as i64;
+        assert_eq!(r.time_bounds(), Ok(end - 256..end));
+        assert_eq!(fetch_two(&r, end - 4), frames(end as u32 - 4..end as u32));
+    }
+
+    #[test]
+    fn store_back_during_fetch() {
+        let (mut w, r) = RingBuf::with_capacity(1, 4, 8);
+        w.store(&[&frames(100..108)], 8, 100).unwrap();
+
+        let mut out = vec![0xffu8; 16];
+        let copied = r.copy(&mut [&mut out], 4, 104).unwrap();
+        assert_eq!(copied, 104..108);
+        assert_eq!(out, frames(104..108));
+        assert_eq!(r.check(&mut [&mut out], 104, copied.clone()), Ok(()));
+
+        // slots of 106 and 107 are rewritten with 50 and 51
+        w.store(&[&frames(50..52)], 2, 50).unwrap();
+        assert_eq!(r.check(&mut [&mut out], 104, copied), Err(Error::Overload));
+        assert_eq!(out, [0; 16]);
+    }
+
+    fn fetch_two(r: &crate::cat::audio::ring_buf::Reader, start: i64) -> Vec<u8> {
+        let mut a = vec![0u8; 16];
+        let mut b = vec![0u8; 16];
+        r.fetch(&mut [&mut a, &mut b], 4, start).unwrap();
+        a
+    }
+}
+
+/// `RUSTFLAGS="--cfg loom" cargo test --release --lib --features cat ring_buf::loom`
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use crate::cat::audio::ring_buf::{Error, RingBuf};
+
+    #[test]
+    fn store_fetch() {
+        let mut model = loom::model::Builder::new();
+        model.preemption_bound = Some(3);
+        model.check(|| {
+            let (mut w, r) = RingBuf::with_capacity(1, 1, 2);
+            let writer = loom::thread::spawn(move || {
+                w.store(&[&[1, 2]], 2, 1).unwrap();
+                w.store(&[&[3, 4]], 2, 3).unwrap();
+            });
+
+            // frame value is its sample time or zero, never a torn mix
+            let mut out = [0xffu8; 2];
+            match r.fetch(&mut [&mut out], 2, 2) {
+                Ok(()) | Err(Error::Overload) => {}
+                Err(e) => panic!("{e}"),
+            }
+            assert!(out[0] == 0 || out[0] == 2, "{out:?}");
+            assert!(out[1] == 0 || out[1] == 3, "{out:?}");
+
+            writer.join().unwrap();
+            r.fetch(&mut [&mut out], 2, 3).unwrap();
+            assert_eq!(out, [3, 4]);
+        });
+    }
+
+    #[test]
+    fn store_back() {
+        let mut model = loom::model::Builder::new();
+        model.preemption_bound = Some(3);
+        model.check(|| {
+            let (mut w, r) = RingBuf::with_capacity(1, 1, 2);
+            w.store(&[&[1, 2]], 2, 1).unwrap();
+            let writer = loom::thread::spawn(move || {
+                // slot of frame 2
+                w.store(&[&[9]], 1, 0).unwrap();
+            });
+
+            // frame 2 is never read as 9
+            let mut out = [0xffu8; 2];
+            match r.fetch(&mut [&mut out], 2, 1) {
+                Ok(()) | Err(Error::Overload) => {}
+                Err(e) => panic!("{e}"),
+            }
+            assert!(out[0] == 0 || out[0] == 1, "{out:?}");
+            assert!(out[1] == 0 || out[1] == 2, "{out:?}");
+
+            writer.join().unwrap();
+            r.fetch(&mut [&mut out], 2, 0).unwrap();
+            assert_eq!(out, [9, 0]);
+        });
+    }
+}
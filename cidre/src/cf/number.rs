@@ -115,6 +115,17 @@ impl NumberType {
     pub const NS_INTEGER: Self = Self(15);
     pub const CG_FLOAT: Self = Self(16);
     pub const MAX: Self = Self(16);
+
+    /// Private type binary plists use for integers above `i64::MAX`
+    #[doc(alias = "kCFNumberSInt128Type")]
+    pub const I128: Self = Self(17);
+}
+
+/// Layout of `CFSInt128Struct`
+#[repr(C)]
+struct I128 {
+    high: i64,
+    low: u64,
 }
 
 define_cf_type!(
@@ -276,6 +287,18 @@ impl Number {
         }
     }
 
+    #[inline]
+    pub fn to_i128(&self) -> Option<i128> {
+        unsafe {
+            let mut value = I128 { high: 0, low: 0 };
+            if CFNumberGetValue(self, NumberType::I128, &mut value as *mut _ as *mut _) {
+                Some((value.high as i128) << 64 | value.low as i128)
+            } else {
+                None
+            }
+        }
+    }
+
     #[doc(alias = "kCFNumberPositiveInfinity")]
     #[inline]
     pub fn infinity() -> &'static Self {
@@ -405,6 +428,15 @@ impl Number {
         unsafe { Self::create_in(NumberType::I64, &val as *const _ as _, None).unwrap_unchecked() }
     }
 
+    #[inline]
+    pub fn from_i128(val: i128) -> arc::R<Self> {
+        let val = I128 {
+            high: (val >> 64) as i64,
+            low: val as u64,
+        };
+        unsafe { Self::create_in(NumberType::I128, &val as *const _ as _, None).unwrap_unchecked() }
+    }
+
     #[inline]
     pub fn from_usize(val: usize) -> arc::R<Self> {
         unsafe { Self::create_in(NumberType::I64, &val as *const _ as _, None).unwrap_unchecked() }
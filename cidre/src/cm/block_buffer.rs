@@ -242,6 +242,18 @@ impl BlockBuf {
         }
     }
 
+    /// Length-prefixed NAL units of contiguous buffer.
+    ///
+    /// `nal_unit_len` comes from `cm::VideoFormatDesc::h264_params_count_and_header_len`.
+    #[cfg(feature = "media")]
+    #[inline]
+    pub fn nal_units(&self, nal_unit_len: u8) -> os::Result<crate::media::nal::LengthPrefixed<'_>> {
+        Ok(crate::media::nal::LengthPrefixed::new(
+            self.as_slice()?,
+            nal_unit_len,
+        ))
+    }
+
     #[inline]
     pub fn as_mut_slice(&mut self) -> os::Result<&mut [u8]> {
         let mut length_at_offset_out = 0;
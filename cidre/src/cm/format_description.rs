@@ -261,6 +261,69 @@ impl FormatDesc {
         self.video_cfg(cf::str!(c"hvcC"))
     }
 
+    /// Decoded `avcC` atom
+    #[cfg(feature = "media")]
+    pub fn avc_cfg(&self) -> Option<crate::media::avc::Config> {
+        crate::media::avc::Config::parse(&self.avcc()?).ok()
+    }
+
+    /// Decoded `hvcC` atom
+    #[cfg(feature = "media")]
+    pub fn hevc_cfg(&self) -> Option<crate::media::hevc::Config> {
+        crate::media::hevc::Config::parse(&self.hvcc()?).ok()
+    }
+
+    /// `stsd` entry for fragmented MP4, `None` for unsupported codecs.
+    #[cfg(all(feature = "media", feature = "cat"))]
+    pub fn mp4_sample_entry(&self) -> Option<crate::media::mp4::SampleEntry> {
+        use crate::media::mp4::SampleEntry;
+
+        let sub_type = self.media_sub_type();
+        if sub_type == VideoCodec::H264.0 || sub_type == VideoCodec::HEVC.0 {
+            let dims = self.dimensions();
+            let (width, height) = (dims.width as u16, dims.height as u16);
+            return Some(if sub_type == VideoCodec::H264.0 {
+                SampleEntry::Avc {
+                    width,
+                    height,
+                    avcc: self.avcc()?,
+                }
+            } else {
+                SampleEntry::Hevc {
+                    width,
+                    height,
+                    hvcc: self.hvcc()?,
+                }
+            });
+        }
+        let asbd = self.stream_basic_desc()?;
+        let channels = asbd.channels_per_frame as u16;
+        match asbd.format {
+            cat::AudioFormat::MPEG4_AAC => {
+                let cookie = self.magic_cookie()?;
+                let config = crate::media::mp4::decoder_specific_info(cookie).unwrap_or(cookie);
+                Some(SampleEntry::Aac {
+                    channels,
+                    sample_rate: asbd.sample_rate as u32,
+                    config: config.to_vec(),
+                })
+            }
+            cat::AudioFormat::OPUS => {
+                // without an `OpusHead` cookie the encoder delay is unknown
+                let (pre_skip, input_sample_rate) = self
+                    .magic_cookie()
+                    .and_then(crate::media::mp4::opus_head)
+                    .unwrap_or((0, asbd.sample_rate as u32));
+                Some(SampleEntry::Opus {
+                    channels,
+                    pre_skip,
+                    input_sample_rate,
+                })
+            }
+            _ => None,
+        }
+    }
+
     pub fn verbatim_sample_desc(&self) -> Option<&cf::Data> {
         unsafe {
             let key = FormatDescExtKey::verbatim_sample_desc();
@@ -632,6 +695,19 @@ impl AudioFormatDesc {
     pub fn stream_basic_desc(&self) -> Option<&cat::audio::StreamBasicDesc> {
         unsafe { CMAudioFormatDescriptionGetStreamBasicDescription(self) }
     }
+
+    #[doc(alias = "CMAudioFormatDescriptionGetMagicCookie")]
+    pub fn magic_cookie(&self) -> Option<&[u8]> {
+        let mut size = 0;
+        unsafe {
+            let ptr = CMAudioFormatDescriptionGetMagicCookie(self, &mut size);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(std::slice::from_raw_parts(ptr.cast(), size))
+            }
+        }
+    }
 }
 
 define_cf_type!(
@@ -1031,6 +1107,11 @@ unsafe extern "C-unwind" {
         desc: &AudioFormatDesc,
     ) -> Option<&cat::audio::StreamBasicDesc>;
 
+    fn CMAudioFormatDescriptionGetMagicCookie(
+        desc: &AudioFormatDesc,
+        size_out: &mut usize,
+    ) -> *const c_void;
+
     fn CMFormatDescriptionCreate(
         allocator: Option<&cf::Allocator>,
         media_type: MediaType,
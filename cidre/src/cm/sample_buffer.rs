@@ -394,6 +394,29 @@ impl SampleBuf {
         }
     }
 
+    /// Timing, size and sync flag for fragmented MP4 in `timescale` units.
+    ///
+    /// Decode time falls back to presentation time for streams without reordering.
+    #[cfg(feature = "media")]
+    pub fn mp4_sample(&self, timescale: cm::TimeScale) -> crate::media::mp4::Sample {
+        let rounding = cm::TimeRoundingMethod::default();
+        let pts = self.pts().convert_scale(timescale, rounding);
+        let dts = self.dts();
+        let dts = if dts.is_valid() {
+            dts.convert_scale(timescale, rounding)
+        } else {
+            pts
+        };
+        let duration = self.duration().convert_scale(timescale, rounding);
+        crate::media::mp4::Sample {
+            dts: dts.value,
+            pts: pts.value,
+            duration: duration.value.clamp(0, u32::MAX as _) as u32,
+            size: self.total_sample_size() as u32,
+            is_sync: self.is_key_frame(),
+        }
+    }
+
     #[inline]
     pub unsafe fn contains_not_sync(&self) -> bool {
         let arr = unsafe { self.attaches(true).unwrap_unchecked() };
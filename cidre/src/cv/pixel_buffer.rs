@@ -361,6 +361,10 @@ impl PixelFormat {
     #[doc(alias = "kCVPixelFormatType_422YpCbCr8")]
     pub const _2VUY: Self = Self::_422_YP_CB_CR_8;
 
+    /// Component Y'CbCr 8-bit 4:2:2, ordered Y'0 Cb Y'1 Cr
+    #[doc(alias = "kCVPixelFormatType_422YpCbCr8_yuvs")]
+    pub const _422_YP_CB_CR_8_YUVS: Self = Self(os::Type::from_be_bytes(*b"yuvs"));
+
     /// Component Y'CbCrA 8-bit 4:4:4:4, ordered Cb Y' Cr A
     #[doc(alias = "kCVPixelFormatType_4444YpCbCrA8")]
     pub const _4444_YP_CB_CR_A_8: Self = Self(os::Type::from_be_bytes(*b"v408"));
@@ -0,0 +1,688 @@
+//! GDB remote serial protocol client, as spoken by `debugserver`.
+//!
+//! The protocol is plain bytes over any `Read + Write` stream, so it works with
+//! `am::ServiceConnection::start_debug_server` on macOS and with sockets anywhere.
+//!
+//! ```no_run
+//! use cidre::gdb::{Client, Event};
+//!
+//! let stream = std::net::TcpStream::connect("127.0.0.1:1234").unwrap();
+//! let mut client = Client::new(stream);
+//! client.start_no_ack_mode().unwrap();
+//! client.launch(&["/private/var/containers/Bundle/Application/cam.app/cam"]).unwrap();
+//! client.cont().unwrap();
+//! loop {
+//!     match client.wait().unwrap() {
+//!         Event::Output(out) => print!("{}", String::from_utf8_lossy(&out)),
+//!         Event::Stopped(stop) => println!("stopped with signal {}", stop.signal),
+//!         Event::Exited(code) => break println!("exited with {code}"),
+//!         Event::Signaled(sig) => break println!("killed by {sig}"),
+//!     }
+//! }
+//! ```
+
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Transport failed or timed out, see [`Error::is_timeout`]
+    Io(io::Error),
+    /// Server closed the connection
+    Closed,
+    /// Malformed packet or reply
+    Invalid(&'static str),
+    /// `Exx` reply
+    Remote(u8),
+    /// Empty reply, the server doesn't know the packet
+    Unsupported,
+    /// `qLaunchSuccess` failed with the server's message
+    Launch(String),
+}
+
+impl Error {
+    /// Read timed out on a transport with a read timeout, the client can be used again
+    pub fn is_timeout(&self) -> bool {
+        let Self::Io(e) = self else {
+            return false;
+        };
+        matches!(
+            e.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Closed => f.write_str("connection closed"),
+            Self::Invalid(what) => write!(f, "invalid {what}"),
+            Self::Remote(code) => write!(f, "remote error {code:#04x}"),
+            Self::Unsupported => f.write_str("unsupported packet"),
+            Self::Launch(msg) => write!(f, "launch failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+/// Sum of the bytes modulo 256
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, b| sum.wrapping_add(*b))
+}
+
+/// `$payload#checksum` with `#`, `$`, `}` and `*` escaped
+pub fn encode(payload: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(payload.len() + 4);
+    res.push(b'$');
+    for &b in payload {
+        if matches!(b, b'#' | b'$' | b'}' | b'*') {
+            res.extend_from_slice(&[b'}', b ^ 0x20]);
+        } else {
+            res.push(b);
+        }
+    }
+    let sum = checksum(&res[1..]);
+    res.push(b'#');
+    res.extend_from_slice(format!("{sum:02x}").as_bytes());
+    res
+}
+
+/// Payload between `$` and `#` with escapes and run-length encoding undone
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(data.len());
+    let mut iter = data.iter().copied();
+    while let Some(b) = iter.next() {
+        match b {
+            b'}' => res.push(iter.next().ok_or(Error::Invalid("escape"))? ^ 0x20),
+            // `x*n` repeats x `n - 29` more times
+            b'*' => {
+                let last = *res.last().ok_or(Error::Invalid("run length"))?;
+                let n = iter.next().ok_or(Error::Invalid("run length"))?;
+                if n < 29 {
+                    return Err(Error::Invalid("run length"));
+                }
+                res.extend(std::iter::repeat_n(last, (n - 29) as usize));
+            }
+            b => res.push(b),
+        }
+    }
+    Ok(res)
+}
+
+fn hex_encode(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn hex_decode(hex: &[u8]) -> Result<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return Err(Error::Invalid("hex"));
+    }
+    hex.chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).map_err(|_| Error::Invalid("hex"))?;
+            u8::from_str_radix(pair, 16).map_err(|_| Error::Invalid("hex"))
+        })
+        .collect()
+}
+
+fn parse_hex(hex: &[u8]) -> Result<u64> {
+    let hex = std::str::from_utf8(hex).map_err(|_| Error::Invalid("hex"))?;
+    u64::from_str_radix(hex, 16).map_err(|_| Error::Invalid("hex"))
+}
+
+/// `key:value;` pairs of `qHostInfo`, `qProcessInfo`, `qRegisterInfo` and stop replies
+fn key_values(data: &[u8]) -> Vec<(String, String)> {
+    String::from_utf8_lossy(data)
+        .split(';')
+        .filter_map(|pair| pair.split_once(':'))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// `T` stop reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stop {
+    pub signal: u8,
+    /// `key:value` pairs, registers are keyed by their hex number
+    pub fields: Vec<(String, String)>,
+}
+
+impl Stop {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn thread(&self) -> Option<u64> {
+        u64::from_str_radix(self.get("thread")?, 16).ok()
+    }
+
+    /// `signal`, `exception`, `breakpoint`, `trace` and so on
+    pub fn reason(&self) -> Option<&str> {
+        self.get("reason")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// `O` packet with inferior stdout
+    Output(Vec<u8>),
+    Stopped(Stop),
+    /// `W` exit status
+    Exited(u8),
+    /// `X` terminating signal
+    Signaled(u8),
+}
+
+impl Event {
+    fn parse(reply: &[u8]) -> Result<Self> {
+        let code = |hex: &[u8]| -> Result<u8> {
+            let end = hex.iter().position(|&b| b == b';').unwrap_or(hex.len());
+            let code = parse_hex(&hex[..end])?;
+            u8::try_from(code).map_err(|_| Error::Invalid("stop reply"))
+        };
+        match reply {
+            [b'O', hex @ ..] if !hex.is_empty() => Ok(Self::Output(hex_decode(hex)?)),
+            [b'W', rest @ ..] => Ok(Self::Exited(code(rest)?)),
+            [b'X', rest @ ..] => Ok(Self::Signaled(code(rest)?)),
+            [b'S', sig @ ..] => Ok(Self::Stopped(Stop {
+                signal: code(sig)?,
+                fields: vec![],
+            })),
+            [b'T', a, b, rest @ ..] => Ok(Self::Stopped(Stop {
+                signal: code(&[*a, *b])?,
+                fields: key_values(rest),
+            })),
+            _ => Err(Error::Invalid("stop reply")),
+        }
+    }
+}
+
+/// Register description from `qRegisterInfo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterInfo {
+    pub number: u32,
+    pub name: String,
+    pub bit_size: u32,
+    /// `key:value` pairs like `alt-name`, `set`, `generic` and `encoding`
+    pub fields: Vec<(String, String)>,
+}
+
+impl RegisterInfo {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Retransmissions before giving up on a packet the server keeps rejecting
+const MAX_RETRIES: usize = 3;
+
+pub struct Client<T: io::Read + io::Write> {
+    transport: T,
+    ack: bool,
+    /// Received bytes not consumed yet, keeps partial packets across timeouts
+    input: Vec<u8>,
+}
+
+impl<T: io::Read + io::Write> Client<T> {
+    /// Client in the initial acknowledged mode
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            ack: true,
+            input: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    #[inline]
+    pub fn is_ack_mode(&self) -> bool {
+        self.ack
+    }
+
+    fn fill(&mut self) -> Result {
+        let mut buf = [0u8; 4096];
+        let n = self.transport.read(&mut buf)?;
+        if n == 0 {
+            return Err(Error::Closed);
+        }
+        self.input.extend_from_slice(&buf[..n]);
+        Ok(())
+    }
+
+    /// Sends a packet, retransmitting while the server answers `-`
+    pub fn send(&mut self, payload: &[u8]) -> Result {
+        let frame = encode(payload);
+        for _ in 0..MAX_RETRIES {
+            self.transport.write_all(&frame)?;
+            if !self.ack {
+                return Ok(());
+            }
+            loop {
+                match self.input.first() {
+                    Some(b'+') => {
+                        self.input.remove(0);
+                        return Ok(());
+                    }
+                    Some(b'-') => {
+                        self.input.remove(0);
+                        break;
+                    }
+                    Some(_) => return Err(Error::Invalid("acknowledgment")),
+                    None => self.fill()?,
+                }
+            }
+        }
+        Err(Error::Invalid("packet rejected by server"))
+    }
+
+    /// Next packet, acknowledging it in ack mode.
+    ///
+    /// Stray acknowledgments and `%` notifications are skipped.
+    pub fn recv(&mut self) -> Result<Vec<u8>> {
+        loop {
+            let Some(start) = self.input.iter().position(|&b| b == b'$' || b == b'%') else {
+                self.input.clear();
+                self.fill()?;
+                continue;
+            };
+            let Some(hash) = self.input[start..].iter().position(|&b| b == b'#') else {
+                self.input.drain(..start);
+                self.fill()?;
+                continue;
+            };
+            let end = start + hash + 3;
+            if self.input.len() < end {
+                self.input.drain(..start);
+                self.fill()?;
+                continue;
+            }
+            let frame: Vec<u8> = self.input.drain(..end).skip(start).collect();
+            let raw = &frame[1..hash];
+            if parse_hex(&frame[hash + 1..]).ok() != Some(checksum(raw) as u64) {
+                if self.ack {
+                    self.transport.write_all(b"-")?;
+                    continue;
+                }
+                return Err(Error::Invalid("checksum"));
+            }
+            // notifications are never acknowledged
+            if frame[0] == b'%' {
+                continue;
+            }
+            if self.ack {
+                self.transport.write_all(b"+")?;
+            }
+            return decode(raw);
+        }
+    }
+
+    /// Reply without `Exx` and empty reply checks
+    pub fn request_raw(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        self.send(payload)?;
+        self.recv()
+    }
+
+    /// Sends a packet and reads the reply, mapping `Exx` and empty replies to errors
+    pub fn request(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        let reply = self.request_raw(payload)?;
+        match reply.as_slice() {
+            [] => Err(Error::Unsupported),
+            [b'E', code @ ..] if code.len() == 2 => match parse_hex(code) {
+                Ok(code) => Err(Error::Remote(code as u8)),
+                Err(_) => Ok(reply),
+            },
+            _ => Ok(reply),
+        }
+    }
+
+    fn ok(&mut self, payload: &[u8]) -> Result {
+        match self.request(payload)?.as_slice() {
+            b"OK" => Ok(()),
+            _ => Err(Error::Invalid("reply")),
+        }
+    }
+
+    /// Stops acknowledgments, worth it on the slow device connection
+    #[doc(alias = "QStartNoAckMode")]
+    pub fn start_no_ack_mode(&mut self) -> Result {
+        self.ok(b"QStartNoAckMode")?;
+        self.ack = false;
+        Ok(())
+    }
+
+    #[doc(alias = "qHostInfo")]
+    pub fn host_info(&mut self) -> Result<Vec<(String, String)>> {
+        self.request(b"qHostInfo").map(|r| key_values(&r))
+    }
+
+    #[doc(alias = "qProcessInfo")]
+    pub fn process_info(&mut self) -> Result<Vec<(String, String)>> {
+        self.request(b"qProcessInfo").map(|r| key_values(&r))
+    }
+
+    /// Environment variable for the next [`Client::launch`]
+    #[doc(alias = "QEnvironmentHexEncoded")]
+    pub fn set_env(&mut self, key: &str, value: &str) -> Result {
+        let var = hex_encode(format!("{key}={value}").as_bytes());
+        self.ok(format!("QEnvironmentHexEncoded:{var}").as_bytes())
+    }
+
+    #[doc(alias = "QSetWorkingDir")]
+    pub fn set_working_dir(&mut self, dir: &str) -> Result {
+        let dir = hex_encode(dir.as_bytes());
+        self.ok(format!("QSetWorkingDir:{dir}").as_bytes())
+    }
+
+    /// Launches `args[0]` with arguments, stopped at the entry point
+    #[doc(alias = "A")]
+    #[doc(alias = "qLaunchSuccess")]
+    pub fn launch<S: AsRef<str>>(&mut self, args: &[S]) -> Result {
+        let mut packet = String::from("A");
+        for (i, arg) in args.iter().enumerate() {
+            let arg = hex_encode(arg.as_ref().as_bytes());
+            if i > 0 {
+                packet.push(',');
+            }
+            packet.push_str(&format!("{},{i},{arg}", arg.len()));
+        }
+        self.ok(packet.as_bytes())?;
+        match self.request_raw(b"qLaunchSuccess")?.as_slice() {
+            b"OK" => Ok(()),
+            [b'E', msg @ ..] => Err(Error::Launch(String::from_utf8_lossy(msg).into())),
+            _ => Err(Error::Invalid("launch reply")),
+        }
+    }
+
+    #[doc(alias = "vAttach")]
+    pub fn attach(&mut self, pid: u32) -> Result<Event> {
+        let reply = self.request(format!("vAttach;{pid:x}").as_bytes())?;
+        Event::parse(&reply)
+    }
+
+    /// Why the process is stopped
+    #[doc(alias = "?")]
+    pub fn halt_reason(&mut self) -> Result<Event> {
+        let reply = self.request(b"?")?;
+        Event::parse(&reply)
+    }
+
+    /// Resumes all threads, follow with [`Client::wait`]
+    #[doc(alias = "c")]
+    pub fn cont(&mut self) -> Result {
+        self.send(b"c")
+    }
+
+    /// Stops the running process, [`Client::wait`] reports the stop
+    pub fn interrupt(&mut self) -> Result {
+        self.transport.write_all(&[0x03])?;
+        Ok(())
+    }
+
+    /// Next output, stop or exit while the process runs
+    pub fn wait(&mut self) -> Result<Event> {
+        let reply = self.recv()?;
+        Event::parse(&reply)
+    }
+
+    #[doc(alias = "k")]
+    pub fn kill(&mut self) -> Result<Event> {
+        let reply = self.request(b"k")?;
+        Event::parse(&reply)
+    }
+
+    #[doc(alias = "D")]
+    pub fn detach(&mut self) -> Result {
+        self.ok(b"D")
+    }
+
+    #[doc(alias = "qfThreadInfo")]
+    #[doc(alias = "qsThreadInfo")]
+    pub fn threads(&mut self) -> Result<Vec<u64>> {
+        let mut res = vec![];
+        let mut reply = self.request(b"qfThreadInfo")?;
+        loop {
+            match reply.as_slice() {
+                [b'l', ..] => return Ok(res),
+                [b'm', ids @ ..] => {
+                    for id in ids.split(|&b| b == b',') {
+                        res.push(parse_hex(id)?);
+                    }
+                }
+                _ => return Err(Error::Invalid("thread list")),
+            }
+            reply = self.request(b"qsThreadInfo")?;
+        }
+    }
+
+    #[doc(alias = "qC")]
+    pub fn current_thread(&mut self) -> Result<u64> {
+        match self.request(b"qC")?.as_slice() {
+            [b'Q', b'C', id @ ..] => parse_hex(id),
+            _ => Err(Error::Invalid("current thread")),
+        }
+    }
+
+    /// `None` past the last register
+    #[doc(alias = "qRegisterInfo")]
+    pub fn register_info(&mut self, number: u32) -> Result<Option<RegisterInfo>> {
+        let fields = match self.request(format!("qRegisterInfo{number:x}").as_bytes()) {
+            Ok(reply) => key_values(&reply),
+            Err(Error::Remote(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let field = |key| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let name = field("name")
+            .ok_or(Error::Invalid("register name"))?
+            .clone();
+        let bit_size = field("bitsize")
+            .and_then(|s| s.parse().ok())
+            .ok_or(Error::Invalid("register size"))?;
+        Ok(Some(RegisterInfo {
+            number,
+            name,
+            bit_size,
+            fields,
+        }))
+    }
+
+    pub fn registers(&mut self) -> Result<Vec<RegisterInfo>> {
+        let mut res = vec![];
+        while let Some(info) = self.register_info(res.len() as u32)? {
+            res.push(info);
+        }
+        Ok(res)
+    }
+
+    /// Raw register bytes in target byte order
+    #[doc(alias = "p")]
+    pub fn read_register(&mut self, thread: u64, number: u32) -> Result<Vec<u8>> {
+        let reply = self.request(format!("p{number:x};thread:{thread:x};").as_bytes())?;
+        hex_decode(&reply)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{io::Write, os::unix::net::UnixStream, thread, time::Duration};
+
+    use super::*;
+
+    /// Stand-in debugserver, the protocol is symmetric so it is a client too
+    fn server(script: impl FnOnce(&mut Client<UnixStream>) + Send + 'static) -> Client<UnixStream> {
+        let (a, b) = UnixStream::pair().unwrap();
+        thread::spawn(move || script(&mut Client::new(b)));
+        Client::new(a)
+    }
+
+    fn expect(server: &mut Client<UnixStream>, packet: &str) {
+        assert_eq!(String::from_utf8(server.recv().unwrap()).unwrap(), packet);
+    }
+
+    #[test]
+    fn framing() {
+        assert_eq!(encode(b"qC"), b"$qC#b4");
+        assert_eq!(encode(b"a#$}*"), b"$a}\x03}\x04}]}\x0a#c3");
+        assert_eq!(decode(b"a}\x03}\x04}]}\x0a").unwrap(), b"a#$}*");
+        assert_eq!(decode(b"0* 1").unwrap(), b"00001");
+        assert!(matches!(decode(b"*!"), Err(Error::Invalid("run length"))));
+        assert!(matches!(decode(b"a}"), Err(Error::Invalid("escape"))));
+        assert_eq!(checksum(b"OK"), 0x9a);
+
+        assert_eq!(
+            Event::parse(b"T11thread:1c03;name:main;reason:signal;").unwrap(),
+            Event::Stopped(Stop {
+                signal: 0x11,
+                fields: vec![
+                    ("thread".into(), "1c03".into()),
+                    ("name".into(), "main".into()),
+                    ("reason".into(), "signal".into()),
+                ],
+            })
+        );
+        assert_eq!(Event::parse(b"W00;process:3f2").unwrap(), Event::Exited(0));
+        assert_eq!(Event::parse(b"X09").unwrap(), Event::Signaled(9));
+        assert_eq!(
+            Event::parse(b"O6869").unwrap(),
+            Event::Output(b"hi".to_vec())
+        );
+        assert!(Event::parse(b"OK").is_err());
+    }
+
+    #[test]
+    fn session() {
+        let mut client = server(|s| {
+            // a corrupted reply is rejected and resent
+            expect(s, "QStartNoAckMode");
+            s.get_mut().write_all(b"$OK#00").unwrap();
+            let mut nak = [0u8];
+            io::Read::read_exact(s.get_mut(), &mut nak).unwrap();
+            assert_eq!(nak, *b"-");
+            s.send(b"OK").unwrap();
+            s.ack = false;
+
+            expect(s, "QEnvironmentHexEncoded:4f5f4c4f473d31");
+            s.send(b"OK").unwrap();
+            expect(s, "A16,0,2f7661722f63616d,4,1,2d76");
+            s.send(b"OK").unwrap();
+            expect(s, "qLaunchSuccess");
+            s.send(b"OK").unwrap();
+            expect(s, "?");
+            s.send(b"T11thread:1c03;").unwrap();
+            expect(s, "qfThreadInfo");
+            s.send(b"m1c03,1c04").unwrap();
+            expect(s, "qsThreadInfo");
+            s.send(b"l").unwrap();
+            expect(s, "qC");
+            s.send(b"QC1c03").unwrap();
+            expect(s, "qRegisterInfo0");
+            s.send(b"name:x0;alt-name:arg1;bitsize:64;offset:0;set:General Purpose Registers;")
+                .unwrap();
+            expect(s, "qRegisterInfo1");
+            s.send(b"E45").unwrap();
+            expect(s, "p0;thread:1c03;");
+            s.send(b"2a00000000000000").unwrap();
+            expect(s, "qProcessInfo");
+            s.send(b"").unwrap();
+
+            expect(s, "c");
+            s.send(b"O68656c6c6f0a").unwrap();
+            let mut interrupt = [0u8];
+            io::Read::read_exact(s.get_mut(), &mut interrupt).unwrap();
+            assert_eq!(interrupt, [0x03]);
+            s.send(b"T02thread:1c03;reason:signal;").unwrap();
+            expect(s, "c");
+            s.send(b"W2a").unwrap();
+        });
+
+        client.start_no_ack_mode().unwrap();
+        assert!(!client.is_ack_mode());
+        client.set_env("O_LOG", "1").unwrap();
+        client.launch(&["/var/cam", "-v"]).unwrap();
+        let Event::Stopped(stop) = client.halt_reason().unwrap() else {
+            panic!("not stopped");
+        };
+        assert_eq!((stop.signal, stop.thread()), (0x11, Some(0x1c03)));
+        assert_eq!(client.threads().unwrap(), [0x1c03, 0x1c04]);
+        assert_eq!(client.current_thread().unwrap(), 0x1c03);
+
+        let regs = client.registers().unwrap();
+        assert_eq!(regs.len(), 1);
+        assert_eq!((regs[0].name.as_str(), regs[0].bit_size), ("x0", 64));
+        assert_eq!(regs[0].get("alt-name"), Some("arg1"));
+        let x0 = client.read_register(0x1c03, 0).unwrap();
+        assert_eq!(u64::from_le_bytes(x0.try_into().unwrap()), 42);
+        assert!(matches!(client.process_info(), Err(Error::Unsupported)));
+
+        client.cont().unwrap();
+        assert_eq!(client.wait().unwrap(), Event::Output(b"hello\n".to_vec()));
+        client.interrupt().unwrap();
+        let Event::Stopped(stop) = client.wait().unwrap() else {
+            panic!("not stopped");
+        };
+        assert_eq!(stop.reason(), Some("signal"));
+        client.cont().unwrap();
+        assert_eq!(client.wait().unwrap(), Event::Exited(42));
+        assert!(matches!(client.wait(), Err(Error::Closed)));
+    }
+
+    #[test]
+    fn retransmit() {
+        let mut client = server(|s| {
+            // rejects the first copy, then fails the launch
+            let mut buf = [0u8; 8];
+            io::Read::read_exact(s.get_mut(), &mut buf[..6]).unwrap();
+            assert_eq!(&buf[..6], b"$qC#b4");
+            s.get_mut().write_all(b"-").unwrap();
+            expect(s, "qC");
+            s.send(b"E08").unwrap();
+            expect(s, "A4,0,2f78");
+            s.send(b"OK").unwrap();
+            expect(s, "qLaunchSuccess");
+            s.send(b"Eno such file").unwrap();
+        });
+        assert!(matches!(client.current_thread(), Err(Error::Remote(8))));
+        match client.launch(&["/x"]) {
+            Err(Error::Launch(msg)) => assert_eq!(msg, "no such file"),
+            res => panic!("{res:?}"),
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let (a, mut b) = UnixStream::pair().unwrap();
+        a.set_read_timeout(Some(Duration::from_millis(20))).unwrap();
+        let mut client = Client::new(a);
+        client.ack = false;
+
+        // half a packet, then the rest after the read timed out
+        b.write_all(b"$W0").unwrap();
+        assert!(client.wait().unwrap_err().is_timeout());
+        b.write_all(b"1#b8").unwrap();
+        assert_eq!(client.wait().unwrap(), Event::Exited(1));
+    }
+}
@@ -134,6 +134,22 @@ pub mod vt;
 #[cfg(feature = "vdsp")]
 pub mod vdsp;
 
+/// Pure Rust media formats
+#[cfg(feature = "media-time")]
+pub mod media;
+
+/// GDB remote serial protocol client for debugserver
+#[cfg(feature = "gdb")]
+pub mod gdb;
+
+/// Lockdown service messages and AFC file transfer
+#[cfg(feature = "lockdown")]
+pub mod lockdown;
+
+/// Pure Rust property lists
+#[cfg(feature = "plist")]
+pub mod plist;
+
 /// Screen Capture Kit
 #[cfg(all(target_os = "macos", feature = "sc"))]
 pub mod sc;
@@ -310,7 +326,7 @@ macro_rules! define_opts {
     };
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "cf"))]
 mod tests {
     use crate::cf;
 
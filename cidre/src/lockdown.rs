@@ -0,0 +1,220 @@
+//! Lockdown service protocols over any `Read + Write` transport.
+//!
+//! On macOS the transport is `am::ServiceConnection` from `Session::secure_start_service`,
+//! anywhere else it can be a socket to a forwarded port or a stand-in server.
+//!
+//! Lockdown and most services it starts exchange property lists prefixed with their
+//! big-endian length, see [`Service`]. File transfer uses its own framing, see [`afc`].
+
+use std::{fmt, io};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::plist;
+
+pub mod afc;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// Peer closed the connection
+    Closed,
+    Plist(plist::Error),
+    /// Malformed message
+    Invalid(&'static str),
+    /// `Error` value of a reply
+    Remote(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Closed => f.write_str("connection closed"),
+            Self::Plist(e) => e.fmt(f),
+            Self::Invalid(what) => write!(f, "invalid {what}"),
+            Self::Remote(msg) => write!(f, "remote error {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        if value.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Closed
+        } else {
+            Self::Io(value)
+        }
+    }
+}
+
+impl From<plist::Error> for Error {
+    fn from(value: plist::Error) -> Self {
+        Self::Plist(value)
+    }
+}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+/// Largest message accepted, service messages are far smaller
+const MAX_MESSAGE_LEN: usize = 64 << 20;
+
+/// Length-prefixed property list messages
+pub struct Service<T> {
+    io: T,
+    format: plist::Format,
+}
+
+impl<T: io::Read + io::Write> Service<T> {
+    /// Sends XML like lockdownd itself, replies may use any format
+    #[inline]
+    pub fn new(io: T) -> Self {
+        Self::with_format(io, plist::Format::XmlV1_0)
+    }
+
+    /// Format of sent messages, some services want binary
+    #[inline]
+    pub fn with_format(io: T, format: plist::Format) -> Self {
+        Self { io, format }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+
+    /// Sends a `plist::Value` or any serializable message
+    pub fn send<M: Serialize + ?Sized>(&mut self, msg: &M) -> Result {
+        let data = plist::to_vec(msg, self.format)?;
+        let len = u32::try_from(data.len()).map_err(|_| Error::Invalid("message length"))?;
+        let mut frame = Vec::with_capacity(data.len() + 4);
+        frame.extend_from_slice(&len.to_be_bytes());
+        frame.extend_from_slice(&data);
+        self.io.write_all(&frame)?;
+        self.io.flush()?;
+        Ok(())
+    }
+
+    pub fn recv<M: DeserializeOwned>(&mut self) -> Result<M> {
+        Ok(plist::from_value(self.recv_value()?)?)
+    }
+
+    fn recv_value(&mut self) -> Result<plist::Value> {
+        let mut len = [0u8; 4];
+        self.io.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_MESSAGE_LEN {
+            return Err(Error::Invalid("message length"));
+        }
+        let mut data = vec![0u8; len];
+        self.io.read_exact(&mut data)?;
+        Ok(plist::Value::from_data(&data)?)
+    }
+
+    /// Sends `msg` and reads the reply, a reply with an `Error` key fails
+    pub fn request<M: Serialize + ?Sized, R: DeserializeOwned>(&mut self, msg: &M) -> Result<R> {
+        self.send(msg)?;
+        let reply = self.recv_value()?;
+        if let Some(err) = reply.get("Error") {
+            let msg = err.as_str().unwrap_or("unknown");
+            return Err(Error::Remote(msg.to_string()));
+        }
+        Ok(plist::from_value(reply)?)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{io::Read, os::unix::net::UnixStream, thread};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::plist::{Format, Value};
+
+    #[derive(Debug, Serialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct GetValue<'a> {
+        label: &'a str,
+        request: &'a str,
+        domain: Option<&'a str>,
+        key: &'a str,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct ValueReply {
+        key: String,
+        value: String,
+    }
+
+    #[test]
+    fn messages() {
+        let (a, b) = UnixStream::pair().unwrap();
+        let server = thread::spawn(move || {
+            let mut server = Service::with_format(b, Format::BinaryV1_0);
+            let query: Value = server.recv().unwrap();
+            assert_eq!(query["Request"].as_str(), Some("QueryType"));
+            let reply: plist::Dictionary = [
+                ("Request", "QueryType"),
+                ("Type", "com.apple.mobile.lockdown"),
+            ]
+            .into_iter()
+            .collect();
+            server.send(&reply).unwrap();
+
+            let get: Value = server.recv().unwrap();
+            assert_eq!(get.get("Domain"), None);
+            assert_eq!(get["Key"].as_str(), Some("ProductVersion"));
+            let reply: plist::Dictionary = [("Key", "ProductVersion"), ("Value", "18.1")]
+                .into_iter()
+                .collect();
+            server.send(&reply).unwrap();
+
+            let _: Value = server.recv().unwrap();
+            let reply: plist::Dictionary = [("Error", "InvalidService")].into_iter().collect();
+            server.send(&reply).unwrap();
+
+            // client sends XML, server replied in binary
+            let mut raw = server.into_inner();
+            let mut len = [0u8; 4];
+            raw.read_exact(&mut len).unwrap();
+            let mut data = vec![0; u32::from_be_bytes(len) as usize];
+            raw.read_exact(&mut data).unwrap();
+            assert!(data.starts_with(b"<?xml"));
+        });
+
+        let mut client = Service::new(a);
+        let query: plist::Dictionary = [("Label", "cidre"), ("Request", "QueryType")]
+            .into_iter()
+            .collect();
+        let reply: Value = client.request(&query).unwrap();
+        assert_eq!(reply["Type"].as_str(), Some("com.apple.mobile.lockdown"));
+
+        let mut get = GetValue {
+            label: "cidre",
+            request: "GetValue",
+            domain: None,
+            key: "ProductVersion",
+        };
+        let reply: ValueReply = client.request(&get).unwrap();
+        assert_eq!(reply.value, "18.1");
+
+        get.key = "Nope";
+        match client.request::<_, ValueReply>(&get) {
+            Err(Error::Remote(msg)) => assert_eq!(msg, "InvalidService"),
+            res => panic!("{res:?}"),
+        }
+
+        client.send(&Value::from("bye")).unwrap();
+        server.join().unwrap();
+        assert!(matches!(client.recv::<Value>(), Err(Error::Closed)));
+    }
+}
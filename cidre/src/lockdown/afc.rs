@@ -0,0 +1,731 @@
+//! Apple File Conduit, file access of `com.apple.afc` and `com.apple.mobile.house_arrest`
+//!
+//! Every packet is a 40 byte little-endian header followed by operation arguments
+//! and an optional payload, replies carry either a status code or data.
+
+use std::{
+    fmt, io,
+    time::{Duration, SystemTime},
+};
+
+/// Packet header magic
+pub const MAGIC: &[u8; 8] = b"CFA6LPAA";
+
+const HEADER_LEN: usize = 40;
+
+/// Largest packet accepted from the device
+const MAX_PACKET_LEN: u64 = 64 << 20;
+
+/// Bytes per read or write packet
+const CHUNK_LEN: usize = 1 << 16;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(transparent)]
+pub struct Op(pub u64);
+
+impl Op {
+    pub const STATUS: Self = Self(0x01);
+    pub const DATA: Self = Self(0x02);
+    pub const READ_DIR: Self = Self(0x03);
+    pub const REMOVE_PATH: Self = Self(0x08);
+    pub const MAKE_DIR: Self = Self(0x09);
+    pub const GET_FILE_INFO: Self = Self(0x0a);
+    pub const GET_DEV_INFO: Self = Self(0x0b);
+    pub const FILE_OPEN: Self = Self(0x0d);
+    pub const FILE_OPEN_RES: Self = Self(0x0e);
+    pub const FILE_READ: Self = Self(0x0f);
+    pub const FILE_WRITE: Self = Self(0x10);
+    pub const FILE_SEEK: Self = Self(0x11);
+    pub const FILE_TELL: Self = Self(0x12);
+    pub const FILE_TELL_RES: Self = Self(0x13);
+    pub const FILE_CLOSE: Self = Self(0x14);
+    pub const FILE_SET_SIZE: Self = Self(0x15);
+    pub const RENAME_PATH: Self = Self(0x18);
+    pub const MAKE_LINK: Self = Self(0x1c);
+    pub const REMOVE_PATH_AND_CONTENTS: Self = Self(0x22);
+}
+
+/// Result code of a `STATUS` reply
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(transparent)]
+pub struct Status(pub u64);
+
+impl Status {
+    pub const SUCCESS: Self = Self(0);
+    pub const UNKNOWN_ERROR: Self = Self(1);
+    pub const OP_HEADER_INVALID: Self = Self(2);
+    pub const NO_RESOURCES: Self = Self(3);
+    pub const READ_ERROR: Self = Self(4);
+    pub const WRITE_ERROR: Self = Self(5);
+    pub const UNKNOWN_PACKET_TYPE: Self = Self(6);
+    pub const INVALID_ARG: Self = Self(7);
+    pub const OBJECT_NOT_FOUND: Self = Self(8);
+    pub const OBJECT_IS_DIR: Self = Self(9);
+    pub const PERM_DENIED: Self = Self(10);
+    pub const SERVICE_NOT_CONNECTED: Self = Self(11);
+    pub const OP_TIMEOUT: Self = Self(12);
+    pub const TOO_MUCH_DATA: Self = Self(13);
+    pub const END_OF_DATA: Self = Self(14);
+    pub const OP_NOT_SUPPORTED: Self = Self(15);
+    pub const OBJECT_EXISTS: Self = Self(16);
+    pub const OBJECT_BUSY: Self = Self(17);
+    pub const NO_SPACE_LEFT: Self = Self(18);
+    pub const OP_WOULD_BLOCK: Self = Self(19);
+    pub const IO_ERROR: Self = Self(20);
+    pub const OP_INTERRUPTED: Self = Self(21);
+    pub const OP_IN_PROGRESS: Self = Self(22);
+    pub const INTERNAL_ERROR: Self = Self(23);
+    pub const DIR_NOT_EMPTY: Self = Self(33);
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
+            Self::SUCCESS => "success",
+            Self::OP_HEADER_INVALID => "invalid operation header",
+            Self::NO_RESOURCES => "no resources",
+            Self::READ_ERROR => "read error",
+            Self::WRITE_ERROR => "write error",
+            Self::UNKNOWN_PACKET_TYPE => "unknown packet type",
+            Self::INVALID_ARG => "invalid argument",
+            Self::OBJECT_NOT_FOUND => "not found",
+            Self::OBJECT_IS_DIR => "is a directory",
+            Self::PERM_DENIED => "permission denied",
+            Self::TOO_MUCH_DATA => "too much data",
+            Self::END_OF_DATA => "end of data",
+            Self::OP_NOT_SUPPORTED => "not supported",
+            Self::OBJECT_EXISTS => "already exists",
+            Self::OBJECT_BUSY => "busy",
+            Self::NO_SPACE_LEFT => "no space left",
+            Self::IO_ERROR => "io error",
+            Self::DIR_NOT_EMPTY => "directory not empty",
+            _ => return write!(f, "status {}", self.0),
+        };
+        f.write_str(name)
+    }
+}
+
+/// `FILE_OPEN` modes
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(transparent)]
+pub struct Mode(pub u64);
+
+impl Mode {
+    /// `r`
+    pub const READ_ONLY: Self = Self(1);
+    /// `r+`
+    pub const READ_WRITE: Self = Self(2);
+    /// `w`, creates or truncates
+    pub const WRITE_ONLY: Self = Self(3);
+    /// `w+`
+    pub const WRITE_READ: Self = Self(4);
+    /// `a`
+    pub const APPEND: Self = Self(5);
+    /// `a+`
+    pub const READ_APPEND: Self = Self(6);
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// Peer closed the connection
+    Closed,
+    /// Malformed packet
+    Invalid(&'static str),
+    /// Failure status replied by the device
+    Status(Status),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Closed => f.write_str("connection closed"),
+            Self::Invalid(what) => write!(f, "invalid {what}"),
+            Self::Status(status) => status.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        if value.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Closed
+        } else {
+            Self::Io(value)
+        }
+    }
+}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub num: u64,
+    pub op: Op,
+    /// Operation arguments
+    pub data: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+impl Packet {
+    pub fn write<W: io::Write>(&self, io: &mut W) -> Result {
+        let this_len = (HEADER_LEN + self.data.len()) as u64;
+        let entire_len = this_len + self.payload.len() as u64;
+        let mut buf = Vec::with_capacity(entire_len as usize);
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&entire_len.to_le_bytes());
+        buf.extend_from_slice(&this_len.to_le_bytes());
+        buf.extend_from_slice(&self.num.to_le_bytes());
+        buf.extend_from_slice(&self.op.0.to_le_bytes());
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(&self.payload);
+        io.write_all(&buf)?;
+        io.flush()?;
+        Ok(())
+    }
+
+    pub fn read<R: io::Read>(io: &mut R) -> Result<Self> {
+        let mut header = [0u8; HEADER_LEN];
+        io.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(Error::Invalid("magic"));
+        }
+        let field = |i: usize| u64::from_le_bytes(header[i..i + 8].try_into().unwrap());
+        let (entire_len, this_len) = (field(8), field(16));
+        if this_len < HEADER_LEN as u64 || entire_len < this_len || entire_len > MAX_PACKET_LEN {
+            return Err(Error::Invalid("packet length"));
+        }
+        let mut data = vec![0u8; this_len as usize - HEADER_LEN];
+        io.read_exact(&mut data)?;
+        let mut payload = vec![0u8; (entire_len - this_len) as usize];
+        io.read_exact(&mut payload)?;
+        Ok(Self {
+            num: field(24),
+            op: Op(field(32)),
+            data,
+            payload,
+        })
+    }
+
+    /// Status code of a `STATUS` packet
+    pub fn status(&self) -> Option<Status> {
+        if self.op != Op::STATUS {
+            return None;
+        }
+        let code = self.data.get(..8)?;
+        Some(Status(u64::from_le_bytes(code.try_into().unwrap())))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FileKind {
+    File,
+    Dir,
+    Symlink,
+    Other,
+}
+
+/// `GET_FILE_INFO` reply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    pub kind: FileKind,
+    pub size: u64,
+    pub blocks: u64,
+    pub nlink: u64,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    pub link_target: Option<String>,
+}
+
+impl FileInfo {
+    fn parse(pairs: Vec<(String, String)>) -> Self {
+        let mut res = Self {
+            kind: FileKind::Other,
+            size: 0,
+            blocks: 0,
+            nlink: 0,
+            modified: None,
+            created: None,
+            link_target: None,
+        };
+        let time = |ns: &str| {
+            let ns = ns.parse().ok()?;
+            SystemTime::UNIX_EPOCH.checked_add(Duration::from_nanos(ns))
+        };
+        for (key, value) in pairs {
+            match key.as_str() {
+                "st_ifmt" => {
+                    res.kind = match value.as_str() {
+                        "S_IFREG" => FileKind::File,
+                        "S_IFDIR" => FileKind::Dir,
+                        "S_IFLNK" => FileKind::Symlink,
+                        _ => FileKind::Other,
+                    }
+                }
+                "st_size" => res.size = value.parse().unwrap_or_default(),
+                "st_blocks" => res.blocks = value.parse().unwrap_or_default(),
+                "st_nlink" => res.nlink = value.parse().unwrap_or_default(),
+                "st_mtime" => res.modified = time(&value),
+                "st_birthtime" => res.created = time(&value),
+                "LinkTarget" => res.link_target = Some(value),
+                _ => {}
+            }
+        }
+        res
+    }
+
+    #[inline]
+    pub fn is_dir(&self) -> bool {
+        self.kind == FileKind::Dir
+    }
+
+    #[inline]
+    pub fn is_file(&self) -> bool {
+        self.kind == FileKind::File
+    }
+}
+
+/// Open file on the device
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(transparent)]
+pub struct Handle(pub u64);
+
+/// NUL separated strings of a reply
+fn strings(data: &[u8]) -> Result<Vec<String>> {
+    let data = data.strip_suffix(&[0]).unwrap_or(data);
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    data.split(|b| *b == 0)
+        .map(|s| String::from_utf8(s.to_vec()).map_err(|_| Error::Invalid("string")))
+        .collect()
+}
+
+fn pairs(data: &[u8]) -> Result<Vec<(String, String)>> {
+    let strings = strings(data)?;
+    if strings.len() % 2 != 0 {
+        return Err(Error::Invalid("key value list"));
+    }
+    let mut iter = strings.into_iter();
+    let mut res = Vec::new();
+    while let (Some(key), Some(value)) = (iter.next(), iter.next()) {
+        res.push((key, value));
+    }
+    Ok(res)
+}
+
+fn path_arg(path: &str) -> Vec<u8> {
+    let mut res = Vec::with_capacity(path.len() + 1);
+    res.extend_from_slice(path.as_bytes());
+    res.push(0);
+    res
+}
+
+/// AFC client, one request in flight at a time
+pub struct Client<T> {
+    io: T,
+    packet_num: u64,
+}
+
+impl<T: io::Read + io::Write> Client<T> {
+    #[inline]
+    pub fn new(io: T) -> Self {
+        Self { io, packet_num: 0 }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+
+    /// Sends a packet and reads the reply, failure statuses become [`Error::Status`]
+    pub fn request(&mut self, op: Op, data: &[u8], payload: &[u8]) -> Result<Packet> {
+        let packet = Packet {
+            num: self.packet_num,
+            op,
+            data: data.to_vec(),
+            payload: payload.to_vec(),
+        };
+        self.packet_num += 1;
+        packet.write(&mut self.io)?;
+        let reply = Packet::read(&mut self.io)?;
+        match reply.status() {
+            Some(Status::SUCCESS) | None => Ok(reply),
+            Some(status) => Err(Error::Status(status)),
+        }
+    }
+
+    fn request_status(&mut self, op: Op, data: &[u8]) -> Result {
+        match self.request(op, data, &[])?.op {
+            Op::STATUS => Ok(()),
+            _ => Err(Error::Invalid("reply")),
+        }
+    }
+
+    fn request_data(&mut self, op: Op, data: &[u8]) -> Result<Vec<u8>> {
+        let reply = self.request(op, data, &[])?;
+        match reply.op {
+            Op::DATA => Ok(reply.payload),
+            _ => Err(Error::Invalid("reply")),
+        }
+    }
+
+    /// Entry names without `.` and `..`
+    pub fn read_dir(&mut self, path: &str) -> Result<Vec<String>> {
+        let mut names = strings(&self.request_data(Op::READ_DIR, &path_arg(path))?)?;
+        names.retain(|name| name != "." && name != "..");
+        Ok(names)
+    }
+
+    pub fn stat(&mut self, path: &str) -> Result<FileInfo> {
+        let data = self.request_data(Op::GET_FILE_INFO, &path_arg(path))?;
+        Ok(FileInfo::parse(pairs(&data)?))
+    }
+
+    /// Model, free space and block size of the file system
+    pub fn device_info(&mut self) -> Result<Vec<(String, String)>> {
+        pairs(&self.request_data(Op::GET_DEV_INFO, &[])?)
+    }
+
+    /// Creates missing parents too
+    pub fn mkdir(&mut self, path: &str) -> Result {
+        self.request_status(Op::MAKE_DIR, &path_arg(path))
+    }
+
+    /// Removes a file or an empty directory
+    pub fn remove(&mut self, path: &str) -> Result {
+        self.request_status(Op::REMOVE_PATH, &path_arg(path))
+    }
+
+    /// Removes a directory with everything in it
+    pub fn remove_all(&mut self, path: &str) -> Result {
+        self.request_status(Op::REMOVE_PATH_AND_CONTENTS, &path_arg(path))
+    }
+
+    pub fn rename(&mut self, from: &str, to: &str) -> Result {
+        let mut data = path_arg(from);
+        data.extend_from_slice(&path_arg(to));
+        self.request_status(Op::RENAME_PATH, &data)
+    }
+
+    pub fn open(&mut self, path: &str, mode: Mode) -> Result<Handle> {
+        let mut data = mode.0.to_le_bytes().to_vec();
+        data.extend_from_slice(&path_arg(path));
+        let reply = self.request(Op::FILE_OPEN, &data, &[])?;
+        match (reply.op, reply.data.get(..8)) {
+            (Op::FILE_OPEN_RES, Some(handle)) => {
+                Ok(Handle(u64::from_le_bytes(handle.try_into().unwrap())))
+            }
+            _ => Err(Error::Invalid("reply")),
+        }
+    }
+
+    /// Reads up to `len` bytes, empty at the end of the file
+    pub fn read(&mut self, handle: Handle, len: usize) -> Result<Vec<u8>> {
+        let mut data = handle.0.to_le_bytes().to_vec();
+        data.extend_from_slice(&(len as u64).to_le_bytes());
+        self.request_data(Op::FILE_READ, &data)
+    }
+
+    pub fn write(&mut self, handle: Handle, buf: &[u8]) -> Result {
+        for chunk in buf.chunks(CHUNK_LEN) {
+            let reply = self.request(Op::FILE_WRITE, &handle.0.to_le_bytes(), chunk)?;
+            if reply.op != Op::STATUS {
+                return Err(Error::Invalid("reply"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn close(&mut self, handle: Handle) -> Result {
+        self.request_status(Op::FILE_CLOSE, &handle.0.to_le_bytes())
+    }
+
+    /// Whole contents of the file at `path`
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        let handle = self.open(path, Mode::READ_ONLY)?;
+        let mut res = Vec::new();
+        let read = loop {
+            match self.read(handle, CHUNK_LEN) {
+                Ok(chunk) if chunk.is_empty() => break Ok(res),
+                Ok(chunk) => res.extend_from_slice(&chunk),
+                Err(e) => break Err(e),
+            }
+        };
+        let close = self.close(handle);
+        let res = read?;
+        close?;
+        Ok(res)
+    }
+
+    /// Creates or replaces the file at `path`
+    pub fn write_file(&mut self, path: &str, contents: &[u8]) -> Result {
+        let handle = self.open(path, Mode::WRITE_ONLY)?;
+        let write = self.write(handle, contents);
+        let close = self.close(handle);
+        write?;
+        close
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{collections::BTreeMap, os::unix::net::UnixStream, thread};
+
+    use super::*;
+
+    /// In-memory device file system answering client packets
+    #[derive(Default)]
+    struct Server {
+        files: BTreeMap<String, Vec<u8>>,
+        dirs: Vec<String>,
+        open: Vec<Option<(String, usize)>>,
+    }
+
+    impl Server {
+        fn status(status: Status) -> (Op, Vec<u8>, Vec<u8>) {
+            (Op::STATUS, status.0.to_le_bytes().to_vec(), Vec::new())
+        }
+
+        fn is_dir(&self, path: &str) -> bool {
+            path == "/" || self.dirs.iter().any(|d| d == path)
+        }
+
+        fn exists(&self, path: &str) -> bool {
+            self.is_dir(path) || self.files.contains_key(path)
+        }
+
+        fn children(&self, dir: &str) -> Vec<String> {
+            let prefix = format!("{}/", dir.trim_end_matches('/'));
+            self.dirs
+                .iter()
+                .chain(self.files.keys())
+                .filter_map(|p| p.strip_prefix(&prefix))
+                .filter(|name| !name.contains('/'))
+                .map(str::to_string)
+                .collect()
+        }
+
+        fn handle(&mut self, packet: Packet) -> (Op, Vec<u8>, Vec<u8>) {
+            let args = strings(&packet.data).unwrap_or_default();
+            let u64_at = |i: usize| u64::from_le_bytes(packet.data[i..i + 8].try_into().unwrap());
+            match packet.op {
+                Op::READ_DIR if self.is_dir(&args[0]) => {
+                    let mut names = vec![".".to_string(), "..".to_string()];
+                    names.extend(self.children(&args[0]));
+                    let mut payload = Vec::new();
+                    for name in names {
+                        payload.extend_from_slice(&path_arg(&name));
+                    }
+                    (Op::DATA, Vec::new(), payload)
+                }
+                Op::GET_FILE_INFO if self.exists(&args[0]) => {
+                    let info = match self.files.get(&args[0]) {
+                        Some(file) => format!("st_size\0{}\0st_ifmt\0S_IFREG\0", file.len()),
+                        None => "st_size\x0068\0st_ifmt\0S_IFDIR\0".to_string(),
+                    };
+                    let info = info + "st_nlink\x001\0st_mtime\x001700000000000000000\0";
+                    (Op::DATA, Vec::new(), info.into_bytes())
+                }
+                Op::GET_DEV_INFO => {
+                    let info = b"Model\0iPhone15,2\0FSBlockSize\x004096\0".to_vec();
+                    (Op::DATA, Vec::new(), info)
+                }
+                Op::MAKE_DIR => {
+                    let mut path = String::new();
+                    for part in args[0].split('/').filter(|p| !p.is_empty()) {
+                        path = format!("{path}/{part}");
+                        if !self.is_dir(&path) {
+                            self.dirs.push(path.clone());
+                        }
+                    }
+                    Self::status(Status::SUCCESS)
+                }
+                Op::REMOVE_PATH if self.is_dir(&args[0]) => {
+                    if !self.children(&args[0]).is_empty() {
+                        return Self::status(Status::DIR_NOT_EMPTY);
+                    }
+                    self.dirs.retain(|d| *d != args[0]);
+                    Self::status(Status::SUCCESS)
+                }
+                Op::REMOVE_PATH if self.files.remove(&args[0]).is_some() => {
+                    Self::status(Status::SUCCESS)
+                }
+                Op::RENAME_PATH if self.files.contains_key(&args[0]) => {
+                    let file = self.files.remove(&args[0]).unwrap();
+                    self.files.insert(args[1].clone(), file);
+                    Self::status(Status::SUCCESS)
+                }
+                Op::FILE_OPEN => {
+                    let path = strings(&packet.data[8..]).unwrap().remove(0);
+                    match Mode(u64_at(0)) {
+                        Mode::WRITE_ONLY => {
+                            self.files.insert(path.clone(), Vec::new());
+                        }
+                        _ if !self.files.contains_key(&path) => {
+                            return Self::status(Status::OBJECT_NOT_FOUND);
+                        }
+                        _ => {}
+                    }
+                    self.open.push(Some((path, 0)));
+                    let handle = self.open.len() as u64;
+                    (Op::FILE_OPEN_RES, handle.to_le_bytes().to_vec(), Vec::new())
+                }
+                Op::FILE_READ => {
+                    let (path, pos) = self.open[u64_at(0) as usize - 1].as_mut().unwrap();
+                    let file = &self.files[path.as_str()];
+                    let end = file.len().min(*pos + u64_at(8) as usize);
+                    let chunk = file[*pos..end].to_vec();
+                    *pos = end;
+                    (Op::DATA, Vec::new(), chunk)
+                }
+                Op::FILE_WRITE => {
+                    let (path, _) = self.open[u64_at(0) as usize - 1].as_ref().unwrap();
+                    let file = self.files.get_mut(path.as_str()).unwrap();
+                    file.extend_from_slice(&packet.payload);
+                    Self::status(Status::SUCCESS)
+                }
+                Op::FILE_CLOSE => {
+                    self.open[u64_at(0) as usize - 1] = None;
+                    Self::status(Status::SUCCESS)
+                }
+                Op::READ_DIR | Op::GET_FILE_INFO | Op::REMOVE_PATH | Op::RENAME_PATH => {
+                    Self::status(Status::OBJECT_NOT_FOUND)
+                }
+                _ => Self::status(Status::OP_NOT_SUPPORTED),
+            }
+        }
+
+        fn serve(mut self, mut io: UnixStream) -> Self {
+            let mut num = 0;
+            while let Ok(packet) = Packet::read(&mut io) {
+                assert_eq!(packet.num, num);
+                num += 1;
+                let num = packet.num;
+                let (op, data, payload) = self.handle(packet);
+                let reply = Packet {
+                    num,
+                    op,
+                    data,
+                    payload,
+                };
+                reply.write(&mut io).unwrap();
+            }
+            self
+        }
+    }
+
+    fn connect() -> (Client<UnixStream>, thread::JoinHandle<Server>) {
+        let (a, b) = UnixStream::pair().unwrap();
+        let server = thread::spawn(move || Server::default().serve(b));
+        (Client::new(a), server)
+    }
+
+    #[test]
+    fn packet() {
+        let packet = Packet {
+            num: 7,
+            op: Op::RENAME_PATH,
+            data: b"/a\0/b\0".to_vec(),
+            payload: b"xyz".to_vec(),
+        };
+        let mut buf = Vec::new();
+        packet.write(&mut buf).unwrap();
+        assert_eq!(&buf[..8], MAGIC);
+        assert_eq!(buf[8], 49);
+        assert_eq!(buf[16], 46);
+        assert_eq!(buf[24], 7);
+        assert_eq!(buf[32], 0x18);
+        assert_eq!(Packet::read(&mut &buf[..]).unwrap(), packet);
+
+        buf[0] = b'X';
+        assert!(matches!(
+            Packet::read(&mut &buf[..]),
+            Err(Error::Invalid("magic"))
+        ));
+        assert!(matches!(
+            Packet::read(&mut &b"CFA6"[..]),
+            Err(Error::Closed)
+        ));
+    }
+
+    #[test]
+    fn files() {
+        let (mut client, server) = connect();
+
+        client.mkdir("/Documents/fixtures").unwrap();
+        client
+            .write_file("/Documents/fixtures/a.txt", b"hello")
+            .unwrap();
+        let big: Vec<u8> = (0..CHUNK_LEN * 2 + 17).map(|i| i as u8).collect();
+        client.write_file("/Documents/big.bin", &big).unwrap();
+
+        assert_eq!(client.read_dir("/").unwrap(), ["Documents"]);
+        let mut names = client.read_dir("/Documents").unwrap();
+        names.sort();
+        assert_eq!(names, ["big.bin", "fixtures"]);
+
+        assert_eq!(client.read_file("/Documents/big.bin").unwrap(), big);
+        let info = client.stat("/Documents/fixtures/a.txt").unwrap();
+        assert!(info.is_file());
+        assert_eq!(info.size, 5);
+        assert_eq!(info.nlink, 1);
+        assert_eq!(
+            info.modified,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+        );
+        assert!(client.stat("/Documents").unwrap().is_dir());
+
+        client
+            .rename("/Documents/fixtures/a.txt", "/Documents/fixtures/b.txt")
+            .unwrap();
+        assert_eq!(
+            client.read_file("/Documents/fixtures/b.txt").unwrap(),
+            b"hello"
+        );
+        assert!(matches!(
+            client.read_file("/Documents/fixtures/a.txt"),
+            Err(Error::Status(Status::OBJECT_NOT_FOUND))
+        ));
+        assert!(matches!(
+            client.remove("/Documents/fixtures"),
+            Err(Error::Status(Status::DIR_NOT_EMPTY))
+        ));
+        client.remove("/Documents/fixtures/b.txt").unwrap();
+        client.remove("/Documents/fixtures").unwrap();
+        assert!(matches!(
+            client.stat("/Documents/fixtures"),
+            Err(Error::Status(Status::OBJECT_NOT_FOUND))
+        ));
+
+        let info = client.device_info().unwrap();
+        assert_eq!(info[0], ("Model".to_string(), "iPhone15,2".to_string()));
+        assert!(matches!(
+            client.remove_all("/Documents"),
+            Err(Error::Status(Status::OP_NOT_SUPPORTED))
+        ));
+
+        drop(client);
+        let server = server.join().unwrap();
+        assert!(server.open.iter().all(Option::is_none));
+        assert_eq!(server.files.len(), 1);
+    }
+
+    #[test]
+    fn strings_and_pairs() {
+        assert_eq!(strings(b"").unwrap(), Vec::<String>::new());
+        assert_eq!(strings(b"a\0bc\0").unwrap(), ["a", "bc"]);
+        assert!(pairs(b"a\0b\0c\0").is_err());
+        let info = FileInfo::parse(pairs(b"st_ifmt\0S_IFLNK\0LinkTarget\0/x\0").unwrap());
+        assert_eq!(info.kind, FileKind::Symlink);
+        assert_eq!(info.link_target.as_deref(), Some("/x"));
+    }
+}
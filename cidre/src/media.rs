@@ -0,0 +1,64 @@
+//! Pure Rust parsers and writers for media formats.
+//!
+//! Nothing here links to system frameworks, so it builds and is tested on any target.
+
+#[cfg(feature = "media")]
+mod bits;
+
+#[cfg(feature = "media")]
+mod vui;
+#[cfg(feature = "media")]
+pub use vui::Color;
+#[cfg(feature = "media")]
+pub use vui::Timing;
+#[cfg(feature = "media")]
+pub use vui::Vui;
+
+#[cfg(feature = "media")]
+pub mod aac;
+#[cfg(all(feature = "media", feature = "cat"))]
+pub mod audio_file;
+#[cfg(feature = "media")]
+pub mod avc;
+#[cfg(feature = "media")]
+pub mod hevc;
+#[cfg(feature = "media")]
+pub mod manifest;
+#[cfg(feature = "media")]
+pub mod mp4;
+#[cfg(feature = "media")]
+pub mod nal;
+#[cfg(all(feature = "media", feature = "cat"))]
+pub mod pcm;
+#[cfg(feature = "media")]
+pub mod pixel_format;
+#[cfg(feature = "media")]
+pub mod resample;
+// the only module of `media-time`, all `cm` needs
+pub mod time;
+#[cfg(feature = "media")]
+pub mod ycbcr;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended early
+    Eof,
+    /// Malformed field
+    Invalid(&'static str),
+    /// Valid but not handled
+    Unsupported(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Eof => f.write_str("unexpected end of data"),
+            Self::Invalid(what) => write!(f, "invalid {what}"),
+            Self::Unsupported(what) => write!(f, "unsupported {what}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
@@ -0,0 +1,634 @@
+//! MPEG-4 AudioSpecificConfig, `esds` descriptors and ADTS framing
+
+use super::{
+    Error, Result,
+    bits::{BitReader, BitWriter},
+    mp4,
+};
+
+#[cfg(feature = "cat")]
+use crate::cat;
+
+pub const AOT_AAC_MAIN: u8 = 1;
+pub const AOT_AAC_LC: u8 = 2;
+pub const AOT_AAC_SSR: u8 = 3;
+pub const AOT_AAC_LTP: u8 = 4;
+pub const AOT_SBR: u8 = 5;
+pub const AOT_PS: u8 = 29;
+
+pub const SAMPLE_RATES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+/// Index in [`SAMPLE_RATES`] or `0xf` for explicit rate
+fn sample_rate_index(rate: u32) -> u8 {
+    SAMPLE_RATES
+        .iter()
+        .position(|r| *r == rate)
+        .map_or(0xf, |i| i as u8)
+}
+
+fn read_sample_rate(r: &mut BitReader) -> Result<u32> {
+    match r.u8(4)? {
+        0xf => r.u32(24),
+        i => SAMPLE_RATES
+            .get(i as usize)
+            .copied()
+            .ok_or(Error::Invalid("sampling frequency index")),
+    }
+}
+
+fn write_sample_rate(w: &mut BitWriter, rate: u32) {
+    let i = sample_rate_index(rate);
+    w.bits(i as u64, 4);
+    if i == 0xf {
+        w.bits(rate as u64, 24);
+    }
+}
+
+fn read_object_type(r: &mut BitReader) -> Result<u8> {
+    match r.u8(5)? {
+        31 => Ok(32 + r.u8(6)?),
+        aot => Ok(aot),
+    }
+}
+
+fn write_object_type(w: &mut BitWriter, aot: u8) {
+    if aot >= 31 {
+        w.bits(31, 5);
+        w.bits((aot - 32) as u64, 6);
+    } else {
+        w.bits(aot as u64, 5);
+    }
+}
+
+/// Object types with GASpecificConfig
+fn is_general_audio(aot: u8) -> bool {
+    matches!(aot, 1..=4 | 6 | 7 | 17 | 19..=23)
+}
+
+/// MPEG-4 AudioSpecificConfig (ISO/IEC 14496-3 1.6.2.1)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AudioSpecificConfig {
+    /// Core object type, [`AOT_AAC_LC`] for HE-AAC streams
+    pub object_type: u8,
+    /// Core sample rate
+    pub sample_rate: u32,
+    /// 0 is defined by program config element which is not supported
+    pub channel_config: u8,
+    /// 1024 or 960
+    pub frame_length: u16,
+    /// SBR output sample rate, HE-AAC
+    pub sbr: Option<u32>,
+    /// Parametric stereo, HE-AAC v2
+    pub ps: bool,
+}
+
+impl AudioSpecificConfig {
+    pub fn lc(sample_rate: u32, channel_config: u8) -> Self {
+        Self {
+            object_type: AOT_AAC_LC,
+            sample_rate,
+            channel_config,
+            frame_length: 1024,
+            sbr: None,
+            ps: false,
+        }
+    }
+
+    /// HE-AAC with SBR at twice the core rate of `sample_rate / 2`
+    pub fn he(sample_rate: u32, channel_config: u8) -> Self {
+        Self {
+            sbr: Some(sample_rate),
+            ..Self::lc(sample_rate / 2, channel_config)
+        }
+    }
+
+    /// HE-AAC v2, mono core with parametric stereo
+    pub fn he_v2(sample_rate: u32) -> Self {
+        Self {
+            ps: true,
+            ..Self::he(sample_rate, 1)
+        }
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut r = BitReader::new(data);
+        let mut object_type = read_object_type(&mut r)?;
+        let sample_rate = read_sample_rate(&mut r)?;
+        let channel_config = r.u8(4)?;
+        let mut sbr = None;
+        let mut ps = false;
+        if object_type == AOT_SBR || object_type == AOT_PS {
+            // explicit hierarchical signalling
+            ps = object_type == AOT_PS;
+            sbr = Some(read_sample_rate(&mut r)?);
+            object_type = read_object_type(&mut r)?;
+        }
+        if !is_general_audio(object_type) {
+            return Err(Error::Unsupported("audio object type"));
+        }
+        if channel_config == 0 {
+            return Err(Error::Unsupported("program config element"));
+        }
+
+        // GASpecificConfig
+        let frame_length = if r.bit()? { 960 } else { 1024 };
+        if r.bit()? {
+            // coreCoderDelay
+            r.skip(14)?;
+        }
+        let extension = r.bit()?;
+        if object_type == 6 || object_type == 20 {
+            // layerNr
+            r.skip(3)?;
+        }
+        if extension {
+            match object_type {
+                // numOfSubFrame, layer_length
+                22 => r.skip(16)?,
+                // resilience flags
+                17 | 19 | 20 | 23 => r.skip(3)?,
+                _ => {}
+            }
+            // extensionFlag3
+            r.skip(1)?;
+        }
+
+        // backward compatible explicit signalling
+        if sbr.is_none()
+            && r.bits_left() >= 16
+            && r.u16(11)? == 0x2b7
+            && read_object_type(&mut r)? == AOT_SBR
+            && r.bit()?
+        {
+            sbr = Some(read_sample_rate(&mut r)?);
+            if r.bits_left() >= 12 && r.u16(11)? == 0x548 {
+                ps = r.bit()?;
+            }
+        }
+
+        Ok(Self {
+            object_type,
+            sample_rate,
+            channel_config,
+            frame_length,
+            sbr,
+            ps,
+        })
+    }
+
+    /// Hierarchical signalling for HE-AAC as written by encoders for `esds`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = BitWriter::new();
+        match self.sbr {
+            Some(sbr_rate) => {
+                write_object_type(&mut w, if self.ps { AOT_PS } else { AOT_SBR });
+                write_sample_rate(&mut w, self.sample_rate);
+                w.bits(self.channel_config as u64, 4);
+                write_sample_rate(&mut w, sbr_rate);
+                write_object_type(&mut w, self.object_type);
+            }
+            None => {
+                write_object_type(&mut w, self.object_type);
+                write_sample_rate(&mut w, self.sample_rate);
+                w.bits(self.channel_config as u64, 4);
+            }
+        }
+        // frameLengthFlag, dependsOnCoreCoder, extensionFlag
+        w.bits((self.frame_length == 960) as u64, 1);
+        w.bits(0, 2);
+        w.finish()
+    }
+
+    /// Object type of the whole stream, SBR or PS for HE-AAC
+    pub fn stream_object_type(&self) -> u8 {
+        match (self.sbr, self.ps) {
+            (Some(_), true) => AOT_PS,
+            (Some(_), false) => AOT_SBR,
+            _ => self.object_type,
+        }
+    }
+
+    /// RFC 6381 codec string, `mp4a.40.2`, `mp4a.40.5`, `mp4a.40.29`
+    pub fn codec_string(&self) -> String {
+        format!("mp4a.40.{}", self.stream_object_type())
+    }
+
+    /// Decoded sample rate
+    pub fn output_sample_rate(&self) -> u32 {
+        self.sbr.unwrap_or(self.sample_rate)
+    }
+
+    /// Decoded channel count
+    pub fn output_channels(&self) -> u32 {
+        match self.channel_config {
+            1 if self.ps => 2,
+            1..=6 => self.channel_config as u32,
+            7 | 12 | 14 => 8,
+            11 => 7,
+            13 => 24,
+            _ => 0,
+        }
+    }
+
+    /// Decoded frames per packet
+    pub fn frames_per_packet(&self) -> u32 {
+        let n = self.frame_length as u32;
+        if self.sbr.is_some() { n * 2 } else { n }
+    }
+
+    /// `ES_Descriptor` as in `esds` box and AudioToolbox AAC magic cookies.
+    pub fn es_descriptor(&self) -> Vec<u8> {
+        mp4::es_descriptor(&self.to_bytes())
+    }
+
+    /// From `esds` payload or magic cookie.
+    pub fn with_es_descriptor(es: &[u8]) -> Result<Self> {
+        match mp4::decoder_specific_info(es) {
+            Some(asc) => Self::parse(asc),
+            None => Err(Error::Invalid("es descriptor")),
+        }
+    }
+
+    /// ADTS header for raw AAC packet of `payload_len` bytes.
+    pub fn adts_header(&self, payload_len: usize) -> Result<AdtsHeader> {
+        if !(AOT_AAC_MAIN..=AOT_AAC_LTP).contains(&self.object_type) {
+            return Err(Error::Unsupported("adts object type"));
+        }
+        let sample_rate_index = sample_rate_index(self.sample_rate);
+        if sample_rate_index == 0xf {
+            return Err(Error::Unsupported("adts sample rate"));
+        }
+        if self.channel_config > 7 {
+            return Err(Error::Unsupported("adts channel config"));
+        }
+        let frame_len = payload_len + 7;
+        if frame_len >= 1 << 13 {
+            return Err(Error::Invalid("adts frame length"));
+        }
+        Ok(AdtsHeader {
+            object_type: self.object_type,
+            sample_rate_index,
+            channel_config: self.channel_config,
+            frame_len: frame_len as u16,
+            has_crc: false,
+            raw_blocks: 1,
+        })
+    }
+}
+
+/// Fixed and variable ADTS header (ISO/IEC 13818-7)
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AdtsHeader {
+    pub object_type: u8,
+    pub sample_rate_index: u8,
+    pub channel_config: u8,
+    /// Including header
+    pub frame_len: u16,
+    /// `protection_absent` is not set, two more header bytes
+    pub has_crc: bool,
+    pub raw_blocks: u8,
+}
+
+impl AdtsHeader {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut r = BitReader::new(data);
+        if r.u16(12)? != 0xfff {
+            return Err(Error::Invalid("adts sync word"));
+        }
+        // id, layer
+        r.skip(3)?;
+        let has_crc = !r.bit()?;
+        let object_type = r.u8(2)? + 1;
+        let sample_rate_index = r.u8(4)?;
+        if sample_rate_index as usize >= SAMPLE_RATES.len() {
+            return Err(Error::Invalid("sampling frequency index"));
+        }
+        // private_bit
+        r.skip(1)?;
+        let channel_config = r.u8(3)?;
+        // original_copy, home, copyright id bit and start
+        r.skip(4)?;
+        let frame_len = r.u16(13)?;
+        // adts_buffer_fullness
+        r.skip(11)?;
+        let raw_blocks = r.u8(2)? + 1;
+        let res = Self {
+            object_type,
+            sample_rate_index,
+            channel_config,
+            frame_len,
+            has_crc,
+            raw_blocks,
+        };
+        if (frame_len as usize) < res.header_len() {
+            return Err(Error::Invalid("adts frame length"));
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        if self.has_crc { 9 } else { 7 }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        SAMPLE_RATES[self.sample_rate_index as usize]
+    }
+
+    pub fn config(&self) -> AudioSpecificConfig {
+        AudioSpecificConfig {
+            object_type: self.object_type,
+            sample_rate: self.sample_rate(),
+            channel_config: self.channel_config,
+            frame_length: 1024,
+            sbr: None,
+            ps: false,
+        }
+    }
+
+    /// 7 bytes without CRC, buffer fullness is VBR.
+    pub fn to_bytes(&self) -> [u8; 7] {
+        let mut w = BitWriter::new();
+        w.bits(0xfff, 12);
+        // MPEG-4, layer 0, protection_absent
+        w.bits(0b0001, 4);
+        w.bits((self.object_type - 1) as u64, 2);
+        w.bits(self.sample_rate_index as u64, 4);
+        w.bits(0, 1);
+        w.bits(self.channel_config as u64, 3);
+        w.bits(0, 4);
+        w.bits(self.frame_len as u64, 13);
+        w.bits(0x7ff, 11);
+        w.bits((self.raw_blocks - 1) as u64, 2);
+        w.finish().try_into().unwrap()
+    }
+}
+
+/// Splits ADTS stream into headers and raw AAC payloads.
+///
+/// Yields an error and stops on lost sync or truncated frame.
+#[derive(Debug, Clone)]
+pub struct AdtsFrames<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AdtsFrames<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+}
+
+impl<'a> Iterator for AdtsFrames<'a> {
+    type Item = Result<(AdtsHeader, &'a [u8])>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let res = AdtsHeader::parse(self.data).and_then(|h| {
+            let frame = self.data.get(..h.frame_len as usize).ok_or(Error::Eof)?;
+            self.data = &self.data[frame.len()..];
+            Ok((h, &frame[h.header_len()..]))
+        });
+        if res.is_err() {
+            self.data = &[];
+        }
+        Some(res)
+    }
+}
+
+/// Prefixes every packet with ADTS header.
+#[cfg(feature = "cat")]
+pub fn write_adts(
+    config: &AudioSpecificConfig,
+    data: &[u8],
+    packets: &[cat::audio::StreamPacketDesc],
+    out: &mut Vec<u8>,
+) -> Result {
+    for p in packets {
+        let start = p.start_offset as usize;
+        let Some(payload) = data.get(start..start + p.data_byte_size as usize) else {
+            return Err(Error::Eof);
+        };
+        out.extend_from_slice(&config.adts_header(payload.len())?.to_bytes());
+        out.extend_from_slice(payload);
+    }
+    Ok(())
+}
+
+/// Strips ADTS headers, appends raw packets to `out` and their descriptions to `packets`.
+#[cfg(feature = "cat")]
+pub fn strip_adts(
+    data: &[u8],
+    out: &mut Vec<u8>,
+    packets: &mut Vec<cat::audio::StreamPacketDesc>,
+) -> Result<Option<AudioSpecificConfig>> {
+    let mut config = None;
+    for frame in AdtsFrames::new(data) {
+        let (header, payload) = frame?;
+        if header.raw_blocks != 1 {
+            return Err(Error::Unsupported("multiple raw data blocks"));
+        }
+        config.get_or_insert(header.config());
+        packets.push(cat::audio::StreamPacketDesc {
+            start_offset: out.len() as i64,
+            variable_frames_in_packet: 0,
+            data_byte_size: payload.len() as u32,
+        });
+        out.extend_from_slice(payload);
+    }
+    Ok(config)
+}
+
+#[cfg(feature = "cat")]
+impl AudioSpecificConfig {
+    /// Compressed stream description like AudioToolbox derives from `esds`.
+    pub fn stream_basic_desc(&self) -> cat::audio::StreamBasicDesc {
+        let format = match (self.sbr, self.ps) {
+            (Some(_), true) => cat::AudioFormat::MPEG4_AAC_HE_V2,
+            (Some(_), false) => cat::AudioFormat::MPEG4_AAC_HE,
+            _ => cat::AudioFormat::MPEG4_AAC,
+        };
+        cat::audio::StreamBasicDesc {
+            sample_rate: self.output_sample_rate() as f64,
+            format,
+            format_flags: cat::AudioFormatFlags(self.stream_object_type() as u32),
+            bytes_per_packet: 0,
+            frames_per_packet: self.frames_per_packet(),
+            bytes_per_frame: 0,
+            channels_per_frame: self.output_channels(),
+            bits_per_channel: 0,
+            reserved: 0,
+        }
+    }
+
+    /// Layout of decoded channels in AAC order
+    pub fn channel_layout_tag(&self) -> Option<cat::AudioChannelLayoutTag> {
+        use cat::AudioChannelLayoutTag as Tag;
+        Some(match self.channel_config {
+            1 if self.ps => Tag::STEREO,
+            1 => Tag::MONO,
+            2 => Tag::STEREO,
+            3 => Tag::AAC_3_0,
+            4 => Tag::AAC_4_0,
+            5 => Tag::AAC_5_0,
+            6 => Tag::AAC_5_1,
+            7 => Tag::AAC_7_1,
+            11 => Tag::AAC_6_1,
+            12 => Tag::AAC_7_1_B,
+            14 => Tag::AAC_7_1_C,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        Error,
+        aac::{AdtsFrames, AdtsHeader, AudioSpecificConfig},
+    };
+
+    #[test]
+    fn audio_specific_config() {
+        let lc = AudioSpecificConfig::parse(&[0x12, 0x10]).unwrap();
+        assert_eq!(lc, AudioSpecificConfig::lc(44100, 2));
+        assert_eq!(lc.to_bytes(), [0x12, 0x10]);
+        assert_eq!(lc.codec_string(), "mp4a.40.2");
+        assert_eq!(lc.frames_per_packet(), 1024);
+
+        // hierarchical HE-AAC 48k stereo
+        let he = AudioSpecificConfig::parse(&[0x2b, 0x11, 0x88, 0x00]).unwrap();
+        assert_eq!(he, AudioSpecificConfig::he(48000, 2));
+        assert_eq!(he.to_bytes(), [0x2b, 0x11, 0x88, 0x00]);
+        assert_eq!(he.codec_string(), "mp4a.40.5");
+        assert_eq!(
+            (he.output_sample_rate(), he.frames_per_packet()),
+            (48000, 2048)
+        );
+
+        // HE-AAC v2
+        let v2 = AudioSpecificConfig::he_v2(44100);
+        let bytes = v2.to_bytes();
+        assert_eq!(bytes[0] >> 3, 29);
+        assert_eq!(AudioSpecificConfig::parse(&bytes).unwrap(), v2);
+        assert_eq!(v2.output_channels(), 2);
+
+        // backward compatible signalling: LC 24k mono, SBR 48k, PS
+        let compat =
+            AudioSpecificConfig::parse(&[0x13, 0x08, 0x56, 0xe5, 0x9d, 0x48, 0x80]).unwrap();
+        assert_eq!(compat, AudioSpecificConfig::he_v2(48000));
+
+        // explicit rate, 960 frames
+        let odd = AudioSpecificConfig {
+            frame_length: 960,
+            ..AudioSpecificConfig::lc(37800, 1)
+        };
+        assert_eq!(AudioSpecificConfig::parse(&odd.to_bytes()).unwrap(), odd);
+
+        assert_eq!(
+            AudioSpecificConfig::parse(&[0x10, 0x00]),
+            Err(Error::Unsupported("program config element"))
+        );
+        assert_eq!(AudioSpecificConfig::parse(&[0x12]), Err(Error::Eof));
+    }
+
+    #[test]
+    fn es_descriptor() {
+        let config = AudioSpecificConfig::he(44100, 2);
+        let es = config.es_descriptor();
+        assert_eq!(es[0], 0x03);
+        assert_eq!(
+            AudioSpecificConfig::with_es_descriptor(&es).unwrap(),
+            config
+        );
+        assert_eq!(
+            AudioSpecificConfig::with_es_descriptor(&es[..4]),
+            Err(Error::Invalid("es descriptor"))
+        );
+    }
+
+    #[test]
+    fn adts() {
+        let config = AudioSpecificConfig::lc(48000, 2);
+        let header = config.adts_header(100).unwrap();
+        let bytes = header.to_bytes();
+        assert_eq!(bytes, [0xff, 0xf1, 0x4c, 0x80, 0x0d, 0x7f, 0xfc]);
+        assert_eq!(AdtsHeader::parse(&bytes).unwrap(), header);
+        assert_eq!(header.config(), config);
+
+        let mut stream = Vec::new();
+        for payload in [&[1u8, 2, 3][..], &[4, 5]] {
+            stream.extend_from_slice(&config.adts_header(payload.len()).unwrap().to_bytes());
+            stream.extend_from_slice(payload);
+        }
+        let frames: Vec<_> = AdtsFrames::new(&stream).map(|f| f.unwrap().1).collect();
+        assert_eq!(frames, [&[1, 2, 3][..], &[4, 5]]);
+
+        let frames: Vec<_> = AdtsFrames::new(&stream[..stream.len() - 1]).collect();
+        assert_eq!(frames[1], Err(Error::Eof));
+        assert_eq!(
+            AdtsHeader::parse(&[0xff, 0x00, 0, 0, 0, 0, 0]),
+            Err(Error::Invalid("adts sync word"))
+        );
+        assert_eq!(
+            AudioSpecificConfig::he(48000, 2)
+                .adts_header(1)
+                .unwrap()
+                .sample_rate(),
+            24000
+        );
+        assert!(AudioSpecificConfig::lc(37800, 2).adts_header(1).is_err());
+    }
+
+    #[cfg(feature = "cat")]
+    #[test]
+    fn stream_basic_desc() {
+        use crate::{
+            cat,
+            media::aac::{self, AudioSpecificConfig},
+        };
+
+        let asbd = AudioSpecificConfig::he(48000, 6).stream_basic_desc();
+        assert_eq!(asbd.format, cat::AudioFormat::MPEG4_AAC_HE);
+        assert_eq!(asbd.sample_rate, 48000.0);
+        assert_eq!(asbd.frames_per_packet, 2048);
+        assert_eq!(asbd.channels_per_frame, 6);
+        assert_eq!(
+            AudioSpecificConfig::lc(44100, 6).channel_layout_tag(),
+            Some(cat::AudioChannelLayoutTag::AAC_5_1)
+        );
+        assert_eq!(
+            AudioSpecificConfig::he_v2(44100).channel_layout_tag(),
+            Some(cat::AudioChannelLayoutTag::STEREO)
+        );
+
+        let config = AudioSpecificConfig::lc(44100, 2);
+        let packets = [
+            cat::audio::StreamPacketDesc {
+                start_offset: 0,
+                variable_frames_in_packet: 0,
+                data_byte_size: 3,
+            },
+            cat::audio::StreamPacketDesc {
+                start_offset: 3,
+                variable_frames_in_packet: 0,
+                data_byte_size: 1,
+            },
+        ];
+        let mut adts = Vec::new();
+        aac::write_adts(&config, &[1, 2, 3, 4], &packets, &mut adts).unwrap();
+        assert_eq!(adts.len(), 2 * 7 + 4);
+
+        let mut raw = Vec::new();
+        let mut descs = Vec::new();
+        let parsed = aac::strip_adts(&adts, &mut raw, &mut descs).unwrap();
+        assert_eq!(parsed, Some(config));
+        assert_eq!(raw, [1, 2, 3, 4]);
+        assert_eq!(descs.len(), 2);
+        assert_eq!(descs[1].start_offset, 3);
+        assert_eq!(descs[1].data_byte_size, 1);
+    }
+}
@@ -0,0 +1,582 @@
+//! WAV, AIFF and CAF files in memory with `AudioFileID` concepts
+
+use std::collections::BTreeMap;
+
+use crate::cat::audio::{ChannelLayoutN, Format, FormatFlags, StreamBasicDesc, StreamPacketDesc};
+
+use super::{Error, Result};
+
+mod aiff;
+mod caf;
+mod wav;
+
+#[doc(alias = "kAFInfoDictionary_Title")]
+pub const INFO_TITLE: &str = "title";
+#[doc(alias = "kAFInfoDictionary_Artist")]
+pub const INFO_ARTIST: &str = "artist";
+#[doc(alias = "kAFInfoDictionary_Comments")]
+pub const INFO_COMMENTS: &str = "comments";
+#[doc(alias = "kAFInfoDictionary_Copyright")]
+pub const INFO_COPYRIGHT: &str = "copyright";
+#[doc(alias = "kAFInfoDictionary_Genre")]
+pub const INFO_GENRE: &str = "genre";
+#[doc(alias = "kAFInfoDictionary_RecordedDate")]
+pub const INFO_RECORDED_DATE: &str = "recorded date";
+#[doc(alias = "kAFInfoDictionary_EncodingApplication")]
+pub const INFO_ENCODING_APPLICATION: &str = "encoding application";
+
+/// Container of [`AudioFile`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FileType {
+    /// RIFF WAVE, written as RF64 when data doesn't fit 32-bit sizes
+    Wave,
+    /// EBU Tech 3306 WAVE with 64-bit sizes
+    Rf64,
+    Aiff,
+    Aifc,
+    Caf,
+}
+
+impl FileType {
+    /// `AudioFileTypeID` of the container
+    pub const fn type_id(self) -> u32 {
+        u32::from_be_bytes(match self {
+            Self::Wave => *b"WAVE",
+            Self::Rf64 => *b"RF64",
+            Self::Aiff => *b"AIFF",
+            Self::Aifc => *b"AIFC",
+            Self::Caf => *b"caff",
+        })
+    }
+
+    pub fn with_type_id(type_id: u32) -> Option<Self> {
+        [Self::Wave, Self::Rf64, Self::Aiff, Self::Aifc, Self::Caf]
+            .into_iter()
+            .find(|t| t.type_id() == type_id)
+    }
+
+    /// Sniffs the file header.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        let id = |at: usize| -> [u8; 4] {
+            data.get(at..at + 4)
+                .map_or([0; 4], |s| s.try_into().unwrap())
+        };
+        match (&id(0), &id(8)) {
+            (b"RIFF", b"WAVE") => Some(Self::Wave),
+            (b"RF64" | b"BW64", b"WAVE") => Some(Self::Rf64),
+            (b"FORM", b"AIFF") => Some(Self::Aiff),
+            (b"FORM", b"AIFC") => Some(Self::Aifc),
+            (b"caff", _) => Some(Self::Caf),
+            _ => None,
+        }
+    }
+
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Wave | Self::Rf64 => "wav",
+            Self::Aiff => "aiff",
+            Self::Aifc => "aifc",
+            Self::Caf => "caf",
+        }
+    }
+}
+
+#[cfg(feature = "at")]
+impl From<FileType> for crate::at::audio::FileTypeId {
+    fn from(value: FileType) -> Self {
+        Self(value.type_id())
+    }
+}
+
+/// Audio file read and written without AudioToolbox.
+///
+/// Mirrors `at::audio::FileId`: data format, packet reads and writes with
+/// [`StreamPacketDesc`], magic cookie, channel layout and info dictionary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFile {
+    pub file_type: FileType,
+    pub format: StreamBasicDesc,
+    pub magic_cookie: Option<Vec<u8>>,
+    pub channel_layout: Option<ChannelLayoutN>,
+    /// `kAudioFilePropertyInfoDictionary`, keys like [`INFO_TITLE`]
+    pub info: BTreeMap<String, String>,
+    /// Encoder delay frames, stored in CAF only
+    pub priming_frames: u32,
+    /// Padding frames at the end, stored in CAF only
+    pub remainder_frames: u32,
+    /// Variable bit rate packets, offsets into `data`
+    pub packets: Vec<StreamPacketDesc>,
+    pub data: Vec<u8>,
+}
+
+impl AudioFile {
+    /// Empty file, fails if the container can't hold `format`.
+    pub fn create(file_type: FileType, format: StreamBasicDesc) -> Result<Self> {
+        let res = Self {
+            file_type,
+            format,
+            magic_cookie: None,
+            channel_layout: None,
+            info: BTreeMap::new(),
+            priming_frames: 0,
+            remainder_frames: 0,
+            packets: Vec::new(),
+            data: Vec::new(),
+        };
+        res.check_format()?;
+        Ok(res)
+    }
+
+    pub fn open(data: &[u8]) -> Result<Self> {
+        match FileType::detect(data) {
+            Some(FileType::Wave | FileType::Rf64) => wav::read(data),
+            Some(FileType::Aiff | FileType::Aifc) => aiff::read(data),
+            Some(FileType::Caf) => caf::read(data),
+            None => Err(Error::Unsupported("audio file type")),
+        }
+    }
+
+    fn check_format(&self) -> Result {
+        match self.file_type {
+            FileType::Wave | FileType::Rf64 => wav::check_format(&self.format).map(|_| ()),
+            FileType::Aiff | FileType::Aifc => {
+                aiff::check_format(self.file_type, &self.format).map(|_| ())
+            }
+            FileType::Caf => caf::check_format(&self.format),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        match self.file_type {
+            FileType::Wave | FileType::Rf64 => wav::write(self, &mut out)?,
+            FileType::Aiff | FileType::Aifc => aiff::write(self, &mut out)?,
+            FileType::Caf => caf::write(self, &mut out)?,
+        }
+        Ok(out)
+    }
+
+    #[inline]
+    pub fn is_vbr(&self) -> bool {
+        self.format.bytes_per_packet == 0 || self.format.frames_per_packet == 0
+    }
+
+    pub fn packet_count(&self) -> u64 {
+        if self.is_vbr() {
+            self.packets.len() as u64
+        } else {
+            self.data.len() as u64 / self.format.bytes_per_packet as u64
+        }
+    }
+
+    /// Decoded frames including priming and remainder
+    fn total_frames(&self) -> u64 {
+        match self.format.frames_per_packet {
+            0 => self
+                .packets
+                .iter()
+                .map(|p| p.variable_frames_in_packet as u64)
+                .sum(),
+            fpp => self.packet_count() * fpp as u64,
+        }
+    }
+
+    /// Playable frames without priming and remainder
+    pub fn valid_frames(&self) -> u64 {
+        self.total_frames()
+            .saturating_sub(self.priming_frames as u64 + self.remainder_frames as u64)
+    }
+
+    /// Seconds
+    #[doc(alias = "kAudioFilePropertyEstimatedDuration")]
+    pub fn estimated_duration(&self) -> f64 {
+        if self.format.sample_rate > 0.0 {
+            self.valid_frames() as f64 / self.format.sample_rate
+        } else {
+            0.0
+        }
+    }
+
+    fn packet(&self, index: u64) -> Option<StreamPacketDesc> {
+        if self.is_vbr() {
+            return self.packets.get(index as usize).copied();
+        }
+        if index >= self.packet_count() {
+            return None;
+        }
+        let size = self.format.bytes_per_packet;
+        Some(StreamPacketDesc {
+            start_offset: (index * size as u64) as i64,
+            variable_frames_in_packet: 0,
+            data_byte_size: size,
+        })
+    }
+
+    /// Appends up to `num_packets` from `start` to `out` and returns how many were read.
+    ///
+    /// `descs` gets descriptions with offsets into `out`.
+    #[doc(alias = "AudioFileReadPacketData")]
+    pub fn read_packets(
+        &self,
+        start: u64,
+        num_packets: u32,
+        out: &mut Vec<u8>,
+        mut descs: Option<&mut Vec<StreamPacketDesc>>,
+    ) -> Result<u32> {
+        let mut n = 0;
+        while n < num_packets {
+            let Some(p) = self.packet(start + n as u64) else {
+                break;
+            };
+            let offset = p.start_offset as usize;
+            let Some(bytes) = self.data.get(offset..offset + p.data_byte_size as usize) else {
+                return Err(Error::Eof);
+            };
+            if let Some(descs) = descs.as_deref_mut() {
+                descs.push(StreamPacketDesc {
+                    start_offset: out.len() as i64,
+                    ..p
+                });
+            }
+            out.extend_from_slice(bytes);
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    /// Appends packets of `data`.
+    ///
+    /// `descs` with offsets into `data` are required for variable bit rate formats,
+    /// constant bit rate data without them must hold whole packets.
+    #[doc(alias = "AudioFileWritePackets")]
+    pub fn write_packets(&mut self, data: &[u8], descs: &[StreamPacketDesc]) -> Result {
+        if descs.is_empty() {
+            if self.is_vbr() {
+                return Err(Error::Invalid("missing packet descriptions"));
+            }
+            if data.len() % self.format.bytes_per_packet as usize != 0 {
+                return Err(Error::Invalid("partial packet"));
+            }
+            self.data.extend_from_slice(data);
+            return Ok(());
+        }
+        for p in descs {
+            let offset = p.start_offset as usize;
+            let Some(bytes) = data.get(offset..offset + p.data_byte_size as usize) else {
+                return Err(Error::Eof);
+            };
+            if self.is_vbr() {
+                if self.format.frames_per_packet == 0 && p.variable_frames_in_packet == 0 {
+                    return Err(Error::Invalid("variable frames in packet"));
+                }
+                self.packets.push(StreamPacketDesc {
+                    start_offset: self.data.len() as i64,
+                    ..*p
+                });
+            } else if p.data_byte_size != self.format.bytes_per_packet {
+                return Err(Error::Invalid("packet size"));
+            }
+            self.data.extend_from_slice(bytes);
+        }
+        Ok(())
+    }
+}
+
+/// Interleaved linear PCM, samples in high bits when `bits` doesn't fill `container` bytes
+fn pcm_format(
+    sample_rate: f64,
+    channels: u32,
+    bits: u32,
+    container: u32,
+    float: bool,
+    signed: bool,
+    big_endian: bool,
+) -> Result<StreamBasicDesc> {
+    if container == 0 || container > 8 {
+        return Err(Error::Invalid("sample size"));
+    }
+    let Some(bytes_per_frame) = container.checked_mul(channels) else {
+        return Err(Error::Invalid("bytes per frame"));
+    };
+    let mut flags = if float {
+        FormatFlags::IS_FLOAT.0
+    } else if signed {
+        FormatFlags::IS_SIGNED_INTEGER.0
+    } else {
+        0
+    };
+    if big_endian {
+        flags |= FormatFlags::IS_BIG_ENDIAN.0;
+    }
+    flags |= if bits == container * 8 {
+        FormatFlags::IS_PACKED.0
+    } else {
+        FormatFlags::IS_ALIGNED_HIGH.0
+    };
+    Ok(StreamBasicDesc {
+        sample_rate,
+        format: Format::LINEAR_PCM,
+        format_flags: FormatFlags(flags),
+        bytes_per_packet: bytes_per_frame,
+        frames_per_packet: 1,
+        bytes_per_frame,
+        channels_per_frame: channels,
+        bits_per_channel: bits,
+        reserved: 0,
+    })
+}
+
+/// Bytes per sample of linear PCM the containers store
+fn pcm_container(format: &StreamBasicDesc) -> Result<u32> {
+    if format.channels_per_frame > 1 && !format.is_interleaved() {
+        return Err(Error::Unsupported("non interleaved samples"));
+    }
+    let Some(container) = format.sample_bytes() else {
+        return Err(Error::Invalid("bytes per frame"));
+    };
+    if format.bits_per_channel == 0 || format.bits_per_channel > container * 8 {
+        return Err(Error::Invalid("bits per channel"));
+    }
+    if !format.is_packed() && !format.is_aligned_high() {
+        return Err(Error::Unsupported("low aligned samples"));
+    }
+    if format.fraction_bits() != 0 {
+        return Err(Error::Unsupported("fixed point samples"));
+    }
+    Ok(container)
+}
+
+/// Text up to the first nul
+fn zstring(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cat::audio::{
+            ChannelBitmap, ChannelLayoutN, ChannelLayoutTag, Format, FormatFlags, PcmBuilder,
+            PcmSample, StreamBasicDesc, StreamPacketDesc,
+        },
+        media::{
+            Error,
+            audio_file::{AudioFile, FileType, INFO_ARTIST, INFO_TITLE},
+        },
+    };
+
+    fn pcm(file_type: FileType, format: StreamBasicDesc, frames: usize) -> AudioFile {
+        let mut file = AudioFile::create(file_type, format).unwrap();
+        let data: Vec<u8> = (0..frames * format.bytes_per_frame as usize)
+            .map(|i| i as u8)
+            .collect();
+        file.write_packets(&data, &[]).unwrap();
+        file
+    }
+
+    #[test]
+    fn wave() {
+        let format = PcmBuilder::new(PcmSample::I16, 44_100.0, 2)
+            .big_endian(false)
+            .build();
+        let mut file = pcm(FileType::Wave, format, 4);
+        file.info.insert(INFO_TITLE.to_string(), "Tone".to_string());
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(
+            &bytes[12..36],
+            &[
+                b'f', b'm', b't', b' ', 16, 0, 0, 0, 1, 0, 2, 0, 0x44, 0xac, 0, 0, 0x10, 0xb1, 2,
+                0, 4, 0, 16, 0
+            ]
+        );
+        assert_eq!(
+            u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+            bytes.len() - 8
+        );
+        assert_eq!(AudioFile::open(&bytes).unwrap(), file);
+        assert_eq!(file.estimated_duration(), 4.0 / 44_100.0);
+
+        // extensible with channel mask and high aligned samples
+        let format = PcmBuilder::new(PcmSample::I24, 48_000.0, 6)
+            .big_endian(false)
+            .aligned_high(4)
+            .build();
+        let mut file = pcm(FileType::Wave, format, 3);
+        file.channel_layout = Some(ChannelLayoutN::with_bitmap(ChannelBitmap(0x3f)));
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(&bytes[20..22], &[0xfe, 0xff]);
+        assert_eq!(AudioFile::open(&bytes).unwrap(), file);
+
+        let rf64 = AudioFile {
+            file_type: FileType::Rf64,
+            ..file
+        };
+        let bytes = rf64.to_bytes().unwrap();
+        assert_eq!(&bytes[..4], b"RF64");
+        assert_eq!(&bytes[12..16], b"ds64");
+        assert_eq!(AudioFile::open(&bytes).unwrap(), rf64);
+
+        let float = StreamBasicDesc::common_f32(8_000.0, 1, true);
+        let file = pcm(FileType::Wave, float, 2);
+        assert_eq!(
+            AudioFile::open(&file.to_bytes().unwrap()).unwrap().format,
+            float
+        );
+
+        let big = PcmBuilder::new(PcmSample::I16, 44_100.0, 2)
+            .big_endian(true)
+            .build();
+        assert_eq!(
+            AudioFile::create(FileType::Wave, big),
+            Err(Error::Unsupported("big endian wave samples"))
+        );
+    }
+
+    #[test]
+    fn aiff() {
+        let format = PcmBuilder::new(PcmSample::I16, 44_100.0, 1)
+            .big_endian(true)
+            .build();
+        let mut file = pcm(FileType::Aiff, format, 5);
+        file.info
+            .insert(INFO_ARTIST.to_string(), "cidre".to_string());
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(&bytes[8..12], b"AIFF");
+        // 44100 as 80-bit extended
+        let comm = bytes.windows(4).position(|w| w == b"COMM").unwrap();
+        assert_eq!(
+            &bytes[comm + 16..comm + 26],
+            &[0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(AudioFile::open(&bytes).unwrap(), file);
+
+        let le = PcmBuilder::new(PcmSample::I24, 96_000.0, 2)
+            .big_endian(false)
+            .build();
+        assert_eq!(
+            AudioFile::create(FileType::Aiff, le),
+            Err(Error::Unsupported("little endian aiff samples"))
+        );
+        let file = pcm(FileType::Aifc, le, 3);
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(&bytes[8..12], b"AIFC");
+        assert!(bytes.windows(4).any(|w| w == b"sowt"));
+        assert_eq!(AudioFile::open(&bytes).unwrap(), file);
+
+        let float = PcmBuilder::new(PcmSample::F64, 22_050.0, 2)
+            .big_endian(true)
+            .build();
+        let file = pcm(FileType::Aifc, float, 2);
+        assert_eq!(AudioFile::open(&file.to_bytes().unwrap()).unwrap(), file);
+    }
+
+    #[test]
+    fn caf() {
+        let format = PcmBuilder::new(PcmSample::F32, 48_000.0, 2)
+            .big_endian(false)
+            .build();
+        let mut file = pcm(FileType::Caf, format, 3);
+        file.channel_layout = Some(ChannelLayoutN::with_tag(ChannelLayoutTag::STEREO));
+        file.info
+            .insert(INFO_TITLE.to_string(), "Float".to_string());
+        let bytes = file.to_bytes().unwrap();
+        assert_eq!(&bytes[..8], b"caff\x00\x01\x00\x00");
+        assert_eq!(AudioFile::open(&bytes).unwrap(), file);
+
+        // AAC with priming and packet table
+        let aac = StreamBasicDesc {
+            sample_rate: 44_100.0,
+            format: Format::MPEG4_AAC,
+            format_flags: FormatFlags(2),
+            bytes_per_packet: 0,
+            frames_per_packet: 1024,
+            bytes_per_frame: 0,
+            channels_per_frame: 2,
+            bits_per_channel: 0,
+            reserved: 0,
+        };
+        let mut file = AudioFile::create(FileType::Caf, aac).unwrap();
+        file.magic_cookie = Some(vec![0x03, 0x80, 0x01]);
+        file.priming_frames = 2112;
+        file.remainder_frames = 100;
+        let sizes = [6u32, 300, 2];
+        let mut descs = Vec::new();
+        let mut offset = 0;
+        for size in sizes {
+            descs.push(StreamPacketDesc {
+                start_offset: offset,
+                variable_frames_in_packet: 0,
+                data_byte_size: size,
+            });
+            offset += size as i64;
+        }
+        let data: Vec<u8> = (0..offset).map(|i| i as u8).collect();
+        assert_eq!(
+            file.write_packets(&data, &[]),
+            Err(Error::Invalid("missing packet descriptions"))
+        );
+        file.write_packets(&data, &descs).unwrap();
+        assert_eq!(file.packet_count(), 3);
+        assert_eq!(file.valid_frames(), 3 * 1024 - 2212);
+
+        let bytes = file.to_bytes().unwrap();
+        let opened = AudioFile::open(&bytes).unwrap();
+        assert_eq!(opened, file);
+
+        let mut out = vec![0xff];
+        let mut read = Vec::new();
+        assert_eq!(opened.read_packets(1, 5, &mut out, Some(&mut read)), Ok(2));
+        assert_eq!(out.len(), 1 + 302);
+        assert_eq!(read[0].start_offset, 1);
+        assert_eq!(read[1].start_offset, 301);
+        assert_eq!(read[1].data_byte_size, 2);
+
+        assert_eq!(AudioFile::open(&bytes[..bytes.len() - 1]), Err(Error::Eof));
+    }
+
+    #[test]
+    fn bad_sample_size() {
+        let caf = |bytes_per_packet: u32, channels: u32| {
+            let mut out = b"caff\x00\x01\x00\x00desc".to_vec();
+            out.extend_from_slice(&32u64.to_be_bytes());
+            out.extend_from_slice(&44_100f64.to_bits().to_be_bytes());
+            for v in [
+                u32::from_be_bytes(*b"lpcm"),
+                0,
+                bytes_per_packet,
+                1,
+                channels,
+                16,
+            ] {
+                out.extend_from_slice(&v.to_be_bytes());
+            }
+            AudioFile::open(&out)
+        };
+        let invalid = Err(Error::Invalid("sample size"));
+        assert_eq!(caf(0, 2), invalid);
+        assert_eq!(caf(0x8000_0000, 1), invalid);
+        assert_eq!(caf(u32::MAX, 3), invalid);
+
+        let mut wav = b"RIFF\x24\x00\x00\x00WAVEfmt \x10\x00\x00\x00".to_vec();
+        for v in [1u16, 1, 0xac44, 0, 0, 0, 16, 16] {
+            wav.extend_from_slice(&v.to_le_bytes());
+        }
+        assert_eq!(AudioFile::open(&wav), invalid);
+    }
+
+    #[test]
+    fn file_type() {
+        assert_eq!(FileType::Caf.type_id(), u32::from_be_bytes(*b"caff"));
+        assert_eq!(
+            FileType::with_type_id(u32::from_be_bytes(*b"AIFC")),
+            Some(FileType::Aifc)
+        );
+        assert_eq!(FileType::detect(b"RIFF\0\0\0\0WAVE"), Some(FileType::Wave));
+        assert_eq!(FileType::detect(b"RIFF"), None);
+        assert_eq!(
+            AudioFile::open(b"OggS"),
+            Err(Error::Unsupported("audio file type"))
+        );
+    }
+}
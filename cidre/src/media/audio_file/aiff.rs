@@ -0,0 +1,252 @@
+//! AIFF and AIFF-C
+
+use std::collections::BTreeMap;
+
+use crate::{
+    cat::audio::{Format, FormatFlags, StreamBasicDesc},
+    media::{Error, Result, bits::ByteReader},
+};
+
+use super::{
+    AudioFile, FileType, INFO_ARTIST, INFO_COMMENTS, INFO_COPYRIGHT, INFO_TITLE, pcm_container,
+    pcm_format, zstring,
+};
+
+/// `FVER` timestamp of AIFF-C version 1
+const AIFC_VERSION_1: u32 = 0xa280_5140;
+
+const INFO: [(&[u8; 4], &str); 4] = [
+    (b"NAME", INFO_TITLE),
+    (b"AUTH", INFO_ARTIST),
+    (b"(c) ", INFO_COPYRIGHT),
+    (b"ANNO", INFO_COMMENTS),
+];
+
+/// 80-bit IEEE 754 extended, the `COMM` sample rate
+fn f64_to_extended(v: f64) -> [u8; 10] {
+    let mut res = [0u8; 10];
+    if v == 0.0 || !v.is_finite() {
+        return res;
+    }
+    let bits = v.to_bits();
+    let sign = ((bits >> 63) as u16) << 15;
+    let exp = ((bits >> 52) & 0x7ff) as u16 + 16383 - 1023;
+    let mantissa = (1 << 63) | ((bits & ((1 << 52) - 1)) << 11);
+    res[..2].copy_from_slice(&(sign | exp).to_be_bytes());
+    res[2..].copy_from_slice(&mantissa.to_be_bytes());
+    res
+}
+
+fn extended_to_f64(v: [u8; 10]) -> f64 {
+    let exp = u16::from_be_bytes([v[0], v[1]]);
+    let mantissa = u64::from_be_bytes(v[2..].try_into().unwrap());
+    let res = mantissa as f64 * 2f64.powi((exp & 0x7fff) as i32 - 16383 - 63);
+    if exp & 0x8000 != 0 { -res } else { res }
+}
+
+/// AIFF-C compression type and name for `format`
+pub(super) fn check_format(
+    file_type: FileType,
+    format: &StreamBasicDesc,
+) -> Result<(&'static [u8; 4], &'static str)> {
+    if format.channels_per_frame == 0 || format.channels_per_frame > i16::MAX as u32 {
+        return Err(Error::Invalid("channels per frame"));
+    }
+    let res = match format.format {
+        Format::U_LAW => (b"ulaw", "uLaw 2:1"),
+        Format::A_LAW => (b"alaw", "aLaw 2:1"),
+        Format::LINEAR_PCM => {
+            let container = pcm_container(format)?;
+            if format.is_float() {
+                if !format.is_big_endian() {
+                    return Err(Error::Unsupported("little endian aiff float samples"));
+                }
+                match format.bits_per_channel {
+                    32 => (b"fl32", "32-bit floating point"),
+                    _ => (b"fl64", "64-bit floating point"),
+                }
+            } else {
+                if !format.format_flags.contains(FormatFlags::IS_SIGNED_INTEGER) {
+                    return Err(Error::Unsupported("unsigned aiff samples"));
+                }
+                if container != format.bits_per_channel.div_ceil(8) {
+                    return Err(Error::Unsupported("aiff sample container"));
+                }
+                if container == 1 || format.is_big_endian() {
+                    (b"NONE", "not compressed")
+                } else if file_type == FileType::Aiff {
+                    return Err(Error::Unsupported("little endian aiff samples"));
+                } else {
+                    (b"sowt", "")
+                }
+            }
+        }
+        _ => return Err(Error::Unsupported("aiff format")),
+    };
+    if file_type == FileType::Aiff && res.0 != b"NONE" {
+        return Err(Error::Unsupported("aiff compression"));
+    }
+    Ok(res)
+}
+
+pub(super) fn read(data: &[u8]) -> Result<AudioFile> {
+    let mut r = ByteReader::new(data);
+    // FORM and its size
+    r.skip(8)?;
+    let file_type = if r.array::<4>()? == *b"AIFC" {
+        FileType::Aifc
+    } else {
+        FileType::Aiff
+    };
+
+    let mut comm = None;
+    let mut audio = None;
+    let mut info = BTreeMap::new();
+    while r.remaining().len() >= 8 {
+        let id = r.array::<4>()?;
+        let size = (r.u32()? as usize).min(r.remaining().len());
+        let body = r.bytes(size)?;
+        match &id {
+            b"COMM" => comm = Some(body),
+            b"SSND" => {
+                let mut ssnd = ByteReader::new(body);
+                let offset = ssnd.u32()? as usize;
+                // block size
+                ssnd.skip(4)?;
+                ssnd.skip(offset)?;
+                audio = Some(ssnd.remaining());
+            }
+            _ => {
+                if let Some((_, key)) = INFO.iter().find(|(i, _)| **i == id) {
+                    info.insert(key.to_string(), zstring(body));
+                }
+            }
+        }
+        if size % 2 == 1 {
+            r.skip(1).ok();
+        }
+    }
+
+    let Some(comm) = comm else {
+        return Err(Error::Invalid("missing COMM chunk"));
+    };
+    let mut c = ByteReader::new(comm);
+    let channels = c.u16()? as u32;
+    let frames = c.u32()? as usize;
+    let bits = c.u16()? as u32;
+    let sample_rate = extended_to_f64(c.array()?);
+    let compression = if file_type == FileType::Aifc {
+        c.array::<4>()?
+    } else {
+        *b"NONE"
+    };
+    if channels == 0 || bits == 0 {
+        return Err(Error::Invalid("COMM chunk"));
+    }
+    let law = |format| StreamBasicDesc {
+        sample_rate,
+        format,
+        format_flags: FormatFlags(0),
+        bytes_per_packet: channels,
+        frames_per_packet: 1,
+        bytes_per_frame: channels,
+        channels_per_frame: channels,
+        bits_per_channel: 8,
+        reserved: 0,
+    };
+    let int = |big_endian| {
+        pcm_format(
+            sample_rate,
+            channels,
+            bits,
+            bits.div_ceil(8),
+            false,
+            true,
+            big_endian,
+        )
+    };
+    let float = |bits| pcm_format(sample_rate, channels, bits, bits / 8, true, false, true);
+    let format = match &compression {
+        b"NONE" | b"twos" => int(true)?,
+        b"sowt" => int(false)?,
+        b"fl32" | b"FL32" => float(32)?,
+        b"fl64" | b"FL64" => float(64)?,
+        b"ulaw" | b"ULAW" => law(Format::U_LAW),
+        b"alaw" | b"ALAW" => law(Format::A_LAW),
+        _ => return Err(Error::Unsupported("aiff compression")),
+    };
+
+    let mut file = AudioFile::create(file_type, format)?;
+    let audio = audio.unwrap_or_default();
+    let len = audio.len().min(frames * format.bytes_per_frame as usize);
+    file.data = audio[..len - len % format.bytes_per_frame as usize].to_vec();
+    file.info = info;
+    Ok(file)
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+pub(super) fn write(file: &AudioFile, out: &mut Vec<u8>) -> Result {
+    let format = &file.format;
+    let (compression, name) = check_format(file.file_type, format)?;
+    let frames = file.data.len() / format.bytes_per_frame as usize;
+    let Ok(frames) = u32::try_from(frames) else {
+        return Err(Error::Unsupported("aiff size"));
+    };
+
+    let mut comm = Vec::with_capacity(40);
+    comm.extend_from_slice(&(format.channels_per_frame as u16).to_be_bytes());
+    comm.extend_from_slice(&frames.to_be_bytes());
+    comm.extend_from_slice(&(format.bits_per_channel as u16).to_be_bytes());
+    comm.extend_from_slice(&f64_to_extended(format.sample_rate));
+    if file.file_type == FileType::Aifc {
+        comm.extend_from_slice(compression);
+        // pascal string padded to even size
+        comm.push(name.len() as u8);
+        comm.extend_from_slice(name.as_bytes());
+        if name.len() % 2 == 0 {
+            comm.push(0);
+        }
+    }
+
+    let start = out.len();
+    out.extend_from_slice(b"FORM");
+    out.extend_from_slice(&[0; 4]);
+    if file.file_type == FileType::Aifc {
+        out.extend_from_slice(b"AIFC");
+        chunk(out, b"FVER", &AIFC_VERSION_1.to_be_bytes());
+    } else {
+        out.extend_from_slice(b"AIFF");
+    }
+    chunk(out, b"COMM", &comm);
+    for (id, key) in INFO {
+        if let Some(value) = file.info.get(key) {
+            chunk(out, id, value.as_bytes());
+        }
+    }
+    let ssnd_size = 8 + file.data.len();
+    let Ok(ssnd_size) = u32::try_from(ssnd_size) else {
+        return Err(Error::Unsupported("aiff size"));
+    };
+    out.extend_from_slice(b"SSND");
+    out.extend_from_slice(&ssnd_size.to_be_bytes());
+    // offset and block size
+    out.extend_from_slice(&[0; 8]);
+    out.extend_from_slice(&file.data);
+    if file.data.len() % 2 == 1 {
+        out.push(0);
+    }
+
+    let Ok(form_size) = u32::try_from(out.len() - start - 8) else {
+        return Err(Error::Unsupported("aiff size"));
+    };
+    out[start + 4..start + 8].copy_from_slice(&form_size.to_be_bytes());
+    Ok(())
+}
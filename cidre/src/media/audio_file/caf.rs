@@ -0,0 +1,324 @@
+//! Core Audio Format
+
+use std::collections::BTreeMap;
+
+use crate::{
+    cat::audio::{
+        ChannelBitmap, ChannelDesc, ChannelFlags, ChannelLabel, ChannelLayoutN, ChannelLayoutTag,
+        Format, FormatFlags, StreamBasicDesc, StreamPacketDesc,
+    },
+    media::{Error, Result, bits::ByteReader},
+};
+
+use super::{AudioFile, FileType, pcm_container, pcm_format, zstring};
+
+/// `kCAFLinearPCMFormatFlagIsFloat`
+const LPCM_FLOAT: u32 = 1 << 0;
+/// `kCAFLinearPCMFormatFlagIsLittleEndian`
+const LPCM_LITTLE_ENDIAN: u32 = 1 << 1;
+
+pub(super) fn check_format(format: &StreamBasicDesc) -> Result {
+    if format.format.0 == 0 {
+        return Err(Error::Invalid("format"));
+    }
+    if format.channels_per_frame == 0 {
+        return Err(Error::Invalid("channels per frame"));
+    }
+    if format.is_pcm() {
+        pcm_container(format)?;
+        if !format.is_float() && !format.is_signed_int() {
+            return Err(Error::Unsupported("unsigned caf samples"));
+        }
+    }
+    Ok(())
+}
+
+fn read_desc(body: &[u8]) -> Result<StreamBasicDesc> {
+    let mut r = ByteReader::new(body);
+    let sample_rate = f64::from_bits(r.u64()?);
+    let format = Format(r.u32()?);
+    let flags = r.u32()?;
+    let bytes_per_packet = r.u32()?;
+    let frames_per_packet = r.u32()?;
+    let channels_per_frame = r.u32()?;
+    let bits_per_channel = r.u32()?;
+    if format != Format::LINEAR_PCM {
+        return Ok(StreamBasicDesc {
+            sample_rate,
+            format,
+            format_flags: FormatFlags(flags),
+            bytes_per_packet,
+            frames_per_packet,
+            bytes_per_frame: if frames_per_packet == 1 {
+                bytes_per_packet
+            } else {
+                0
+            },
+            channels_per_frame,
+            bits_per_channel,
+            reserved: 0,
+        });
+    }
+    if channels_per_frame == 0 || bytes_per_packet % channels_per_frame != 0 {
+        return Err(Error::Invalid("caf desc chunk"));
+    }
+    pcm_format(
+        sample_rate,
+        channels_per_frame,
+        bits_per_channel,
+        bytes_per_packet / channels_per_frame,
+        flags & LPCM_FLOAT != 0,
+        true,
+        flags & LPCM_LITTLE_ENDIAN == 0,
+    )
+}
+
+fn write_desc(format: &StreamBasicDesc, out: &mut Vec<u8>) {
+    let flags = if format.is_pcm() {
+        let mut flags = 0;
+        if format.is_float() {
+            flags |= LPCM_FLOAT;
+        }
+        if !format.is_big_endian() {
+            flags |= LPCM_LITTLE_ENDIAN;
+        }
+        flags
+    } else {
+        format.format_flags.0
+    };
+    out.extend_from_slice(&format.sample_rate.to_bits().to_be_bytes());
+    for v in [
+        format.format.0,
+        flags,
+        format.bytes_per_packet,
+        format.frames_per_packet,
+        format.channels_per_frame,
+        format.bits_per_channel,
+    ] {
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+}
+
+/// Big-endian `AudioChannelLayout`
+fn read_chan(body: &[u8]) -> Result<ChannelLayoutN> {
+    let mut r = ByteReader::new(body);
+    let tag = ChannelLayoutTag(r.u32()?);
+    let bitmap = ChannelBitmap(r.u32()?);
+    let n = r.u32()?;
+    let mut descs = Vec::with_capacity(n.min(64) as usize);
+    for _ in 0..n {
+        let channel_label = ChannelLabel(r.u32()?);
+        let channel_flags = ChannelFlags(r.u32()?);
+        let mut coordinates = [0f32; 3];
+        for c in &mut coordinates {
+            *c = f32::from_bits(r.u32()?);
+        }
+        descs.push(ChannelDesc {
+            channel_label,
+            channel_flags,
+            coordinates,
+        });
+    }
+    Ok(ChannelLayoutN { tag, bitmap, descs })
+}
+
+fn write_chan(layout: &ChannelLayoutN, out: &mut Vec<u8>) {
+    out.extend_from_slice(&layout.tag.0.to_be_bytes());
+    out.extend_from_slice(&layout.bitmap.0.to_be_bytes());
+    out.extend_from_slice(&(layout.descs.len() as u32).to_be_bytes());
+    for desc in &layout.descs {
+        out.extend_from_slice(&desc.channel_label.0.to_be_bytes());
+        out.extend_from_slice(&desc.channel_flags.0.to_be_bytes());
+        for c in desc.coordinates {
+            out.extend_from_slice(&c.to_bits().to_be_bytes());
+        }
+    }
+}
+
+fn read_info(body: &[u8]) -> Result<BTreeMap<String, String>> {
+    let mut r = ByteReader::new(body);
+    let n = r.u32()?;
+    let mut strings = r.remaining().split(|b| *b == 0);
+    let mut res = BTreeMap::new();
+    for _ in 0..n {
+        let (Some(key), Some(value)) = (strings.next(), strings.next()) else {
+            return Err(Error::Eof);
+        };
+        res.insert(zstring(key), zstring(value));
+    }
+    Ok(res)
+}
+
+/// Packet table integer, 7 bits per byte, high bit set on all but the last
+fn read_varint(r: &mut ByteReader) -> Result<u64> {
+    let mut res = 0u64;
+    for _ in 0..10 {
+        let b = r.u8()?;
+        res = (res << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            return Ok(res);
+        }
+    }
+    Err(Error::Invalid("packet table integer"))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    let mut buf = [0u8; 10];
+    let mut i = buf.len();
+    loop {
+        i -= 1;
+        buf[i] = (v & 0x7f) as u8 | if i == buf.len() - 1 { 0 } else { 0x80 };
+        v >>= 7;
+        if v == 0 {
+            break;
+        }
+    }
+    out.extend_from_slice(&buf[i..]);
+}
+
+fn read_pakt(body: &[u8], file: &mut AudioFile) -> Result {
+    let mut r = ByteReader::new(body);
+    let packets = r.u64()?;
+    // valid frames, derived from the rest
+    r.skip(8)?;
+    file.priming_frames = r.u32()?;
+    file.remainder_frames = r.u32()?;
+    if !file.is_vbr() {
+        return Ok(());
+    }
+    let mut offset = 0u64;
+    for _ in 0..packets {
+        let size = match file.format.bytes_per_packet {
+            0 => read_varint(&mut r)?,
+            bpp => bpp as u64,
+        };
+        let frames = match file.format.frames_per_packet {
+            0 => read_varint(&mut r)?,
+            _ => 0,
+        };
+        if offset + size > file.data.len() as u64 {
+            return Err(Error::Invalid("packet table"));
+        }
+        file.packets.push(StreamPacketDesc {
+            start_offset: offset as i64,
+            variable_frames_in_packet: frames as u32,
+            data_byte_size: size as u32,
+        });
+        offset += size;
+    }
+    Ok(())
+}
+
+pub(super) fn read(data: &[u8]) -> Result<AudioFile> {
+    let mut r = ByteReader::new(data);
+    r.skip(4)?;
+    if r.u16()? != 1 {
+        return Err(Error::Unsupported("caf version"));
+    }
+    // flags
+    r.skip(2)?;
+
+    let mut desc = None;
+    let mut chan = None;
+    let mut cookie = None;
+    let mut info = BTreeMap::new();
+    let mut pakt = None;
+    let mut audio = None;
+    while r.remaining().len() >= 12 {
+        let id = r.array::<4>()?;
+        let size = r.u64()?;
+        let body = if id == *b"data" && size == u64::MAX {
+            // unknown size of the last chunk
+            r.bytes(r.remaining().len())?
+        } else {
+            r.bytes(usize::try_from(size).map_err(|_| Error::Eof)?)?
+        };
+        match &id {
+            b"desc" => desc = Some(read_desc(body)?),
+            b"chan" => chan = Some(read_chan(body)?),
+            b"kuki" => cookie = Some(body.to_vec()),
+            b"info" => info = read_info(body)?,
+            b"pakt" => pakt = Some(body),
+            // edit count
+            b"data" => audio = Some(body.get(4..).ok_or(Error::Eof)?),
+            _ => {}
+        }
+    }
+
+    let Some(desc) = desc else {
+        return Err(Error::Invalid("missing desc chunk"));
+    };
+    let mut file = AudioFile::create(FileType::Caf, desc)?;
+    file.data = audio.unwrap_or_default().to_vec();
+    if !file.is_vbr() {
+        let whole = file.data.len() - file.data.len() % desc.bytes_per_packet as usize;
+        file.data.truncate(whole);
+    }
+    file.channel_layout = chan;
+    file.magic_cookie = cookie;
+    file.info = info;
+    match pakt {
+        Some(pakt) => read_pakt(pakt, &mut file)?,
+        None if file.is_vbr() => return Err(Error::Invalid("missing pakt chunk")),
+        None => {}
+    }
+    Ok(file)
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], f: impl FnOnce(&mut Vec<u8>)) {
+    out.extend_from_slice(id);
+    let pos = out.len();
+    out.extend_from_slice(&[0; 8]);
+    f(out);
+    let size = (out.len() - pos - 8) as u64;
+    out[pos..pos + 8].copy_from_slice(&size.to_be_bytes());
+}
+
+pub(super) fn write(file: &AudioFile, out: &mut Vec<u8>) -> Result {
+    check_format(&file.format)?;
+    out.extend_from_slice(b"caff");
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes());
+    chunk(out, b"desc", |out| write_desc(&file.format, out));
+    if let Some(layout) = &file.channel_layout {
+        chunk(out, b"chan", |out| write_chan(layout, out));
+    }
+    if let Some(cookie) = &file.magic_cookie {
+        chunk(out, b"kuki", |out| out.extend_from_slice(cookie));
+    }
+    if !file.info.is_empty() {
+        chunk(out, b"info", |out| {
+            out.extend_from_slice(&(file.info.len() as u32).to_be_bytes());
+            for (key, value) in &file.info {
+                for s in [key, value] {
+                    out.extend_from_slice(s.as_bytes());
+                    out.push(0);
+                }
+            }
+        });
+    }
+    if file.is_vbr() || file.priming_frames != 0 || file.remainder_frames != 0 {
+        chunk(out, b"pakt", |out| {
+            out.extend_from_slice(&file.packet_count().to_be_bytes());
+            out.extend_from_slice(&file.valid_frames().to_be_bytes());
+            out.extend_from_slice(&file.priming_frames.to_be_bytes());
+            out.extend_from_slice(&file.remainder_frames.to_be_bytes());
+            if file.is_vbr() {
+                for p in &file.packets {
+                    if file.format.bytes_per_packet == 0 {
+                        write_varint(out, p.data_byte_size as u64);
+                    }
+                    if file.format.frames_per_packet == 0 {
+                        write_varint(out, p.variable_frames_in_packet as u64);
+                    }
+                }
+            }
+        });
+    }
+    chunk(out, b"data", |out| {
+        // edit count
+        out.extend_from_slice(&0u32.to_be_bytes());
+        out.extend_from_slice(&file.data);
+    });
+    Ok(())
+}
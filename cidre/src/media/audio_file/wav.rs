@@ -0,0 +1,307 @@
+//! RIFF WAVE and RF64
+
+use std::collections::BTreeMap;
+
+use crate::{
+    cat::audio::{ChannelBitmap, ChannelLayoutN, Format, FormatFlags, StreamBasicDesc},
+    media::{Error, Result, bits::ByteReader},
+};
+
+use super::{
+    AudioFile, FileType, INFO_ARTIST, INFO_COMMENTS, INFO_COPYRIGHT, INFO_ENCODING_APPLICATION,
+    INFO_GENRE, INFO_RECORDED_DATE, INFO_TITLE, pcm_container, pcm_format, zstring,
+};
+
+const PCM: u16 = 1;
+const FLOAT: u16 = 3;
+const ALAW: u16 = 6;
+const ULAW: u16 = 7;
+const EXTENSIBLE: u16 = 0xfffe;
+
+/// `KSDATAFORMAT_SUBTYPE_*` GUID bytes after the format tag
+const GUID_TAIL: [u8; 14] = [
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
+];
+
+/// `LIST` `INFO` ids
+const INFO: [(&[u8; 4], &str); 7] = [
+    (b"INAM", INFO_TITLE),
+    (b"IART", INFO_ARTIST),
+    (b"ICMT", INFO_COMMENTS),
+    (b"ICOP", INFO_COPYRIGHT),
+    (b"IGNR", INFO_GENRE),
+    (b"ICRD", INFO_RECORDED_DATE),
+    (b"ISFT", INFO_ENCODING_APPLICATION),
+];
+
+/// Format tag for `format`
+pub(super) fn check_format(format: &StreamBasicDesc) -> Result<u16> {
+    if format.channels_per_frame == 0 || format.channels_per_frame > u16::MAX as u32 {
+        return Err(Error::Invalid("channels per frame"));
+    }
+    match format.format {
+        Format::U_LAW | Format::A_LAW => {
+            if format.bytes_per_frame != format.channels_per_frame {
+                return Err(Error::Invalid("bytes per frame"));
+            }
+            Ok(if format.format == Format::U_LAW {
+                ULAW
+            } else {
+                ALAW
+            })
+        }
+        Format::LINEAR_PCM => {
+            let container = pcm_container(format)?;
+            if container > 1 && format.is_big_endian() {
+                return Err(Error::Unsupported("big endian wave samples"));
+            }
+            if format.is_float() {
+                return Ok(FLOAT);
+            }
+            let signed = format.format_flags.contains(FormatFlags::IS_SIGNED_INTEGER);
+            if signed != (container > 1) {
+                // unsigned in 8 bits, signed otherwise
+                return Err(Error::Unsupported("wave sample signedness"));
+            }
+            Ok(PCM)
+        }
+        _ => Err(Error::Unsupported("wave format")),
+    }
+}
+
+pub(super) fn read(data: &[u8]) -> Result<AudioFile> {
+    let mut r = ByteReader::new(data);
+    let file_type = if r.array::<4>()? == *b"RIFF" {
+        FileType::Wave
+    } else {
+        FileType::Rf64
+    };
+    // RIFF size, unreliable in streamed files
+    r.skip(4)?;
+    if r.array::<4>()? != *b"WAVE" {
+        return Err(Error::Invalid("wave header"));
+    }
+
+    let mut data_size64 = None;
+    let mut fmt = None;
+    let mut audio = None;
+    let mut info = BTreeMap::new();
+    while r.remaining().len() >= 8 {
+        let id = r.array::<4>()?;
+        let mut size = r.u32_le()? as u64;
+        if id == *b"data" && size == u32::MAX as u64 {
+            size = data_size64.unwrap_or(size);
+        }
+        // last chunk of streamed file may be cut
+        let size = size.min(r.remaining().len() as u64) as usize;
+        let body = r.bytes(size)?;
+        match &id {
+            b"ds64" => {
+                let mut ds64 = ByteReader::new(body);
+                ds64.skip(8)?;
+                data_size64 = Some(ds64.u64_le()?);
+            }
+            b"fmt " => fmt = Some(read_fmt(body)?),
+            b"data" => audio = Some(body),
+            b"LIST" => read_info(body, &mut info),
+            _ => {}
+        }
+        if size % 2 == 1 {
+            r.skip(1).ok();
+        }
+    }
+
+    let Some((format, channel_layout)) = fmt else {
+        return Err(Error::Invalid("missing fmt chunk"));
+    };
+    let Some(audio) = audio else {
+        return Err(Error::Invalid("missing data chunk"));
+    };
+    let mut file = AudioFile::create(file_type, format)?;
+    let whole = audio.len() - audio.len() % format.bytes_per_packet as usize;
+    file.data = audio[..whole].to_vec();
+    file.channel_layout = channel_layout;
+    file.info = info;
+    Ok(file)
+}
+
+fn read_fmt(body: &[u8]) -> Result<(StreamBasicDesc, Option<ChannelLayoutN>)> {
+    let mut r = ByteReader::new(body);
+    let mut tag = r.u16_le()?;
+    let channels = r.u16_le()? as u32;
+    let sample_rate = r.u32_le()? as f64;
+    // avg bytes per second
+    r.skip(4)?;
+    let block_align = r.u16_le()? as u32;
+    let mut bits = r.u16_le()? as u32;
+    let mut layout = None;
+    if tag == EXTENSIBLE {
+        // cbSize
+        r.skip(2)?;
+        let valid_bits = r.u16_le()? as u32;
+        let mask = r.u32_le()?;
+        let guid = r.array::<16>()?;
+        if guid[2..] != GUID_TAIL {
+            return Err(Error::Unsupported("wave sub format"));
+        }
+        tag = u16::from_le_bytes([guid[0], guid[1]]);
+        if valid_bits != 0 {
+            bits = valid_bits;
+        }
+        if mask != 0 {
+            layout = Some(ChannelLayoutN::with_bitmap(ChannelBitmap(mask)));
+        }
+    }
+    if channels == 0 || block_align == 0 || block_align % channels != 0 {
+        return Err(Error::Invalid("block align"));
+    }
+    let container = block_align / channels;
+    let format = match tag {
+        PCM => pcm_format(
+            sample_rate,
+            channels,
+            bits,
+            container,
+            false,
+            container > 1,
+            false,
+        )?,
+        FLOAT => pcm_format(sample_rate, channels, bits, container, true, false, false)?,
+        ULAW | ALAW => StreamBasicDesc {
+            sample_rate,
+            format: if tag == ULAW {
+                Format::U_LAW
+            } else {
+                Format::A_LAW
+            },
+            format_flags: FormatFlags(0),
+            bytes_per_packet: block_align,
+            frames_per_packet: 1,
+            bytes_per_frame: block_align,
+            channels_per_frame: channels,
+            bits_per_channel: 8,
+            reserved: 0,
+        },
+        _ => return Err(Error::Unsupported("wave format tag")),
+    };
+    Ok((format, layout))
+}
+
+fn read_info(body: &[u8], info: &mut BTreeMap<String, String>) {
+    let Some(chunks) = body.strip_prefix(b"INFO") else {
+        return;
+    };
+    let mut r = ByteReader::new(chunks);
+    while let Ok(id) = r.array::<4>() {
+        let Ok(size) = r.u32_le() else {
+            break;
+        };
+        let Ok(value) = r.bytes(size as usize) else {
+            break;
+        };
+        if size % 2 == 1 {
+            r.skip(1).ok();
+        }
+        if let Some((_, key)) = INFO.iter().find(|(i, _)| **i == id) {
+            info.insert(key.to_string(), zstring(value));
+        }
+    }
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+pub(super) fn write(file: &AudioFile, out: &mut Vec<u8>) -> Result {
+    let format = &file.format;
+    let tag = check_format(format)?;
+    let channels = format.channels_per_frame;
+    let block_align = format.bytes_per_frame;
+    let container = block_align / channels;
+    let mask = file
+        .channel_layout
+        .as_ref()
+        .and_then(ChannelLayoutN::to_bitmap)
+        .map_or(0, |b| b.0);
+    let extensible = (tag == PCM || tag == FLOAT)
+        && (channels > 2 || container > 2 || format.bits_per_channel != container * 8 || mask != 0);
+
+    let mut fmt = Vec::with_capacity(40);
+    fmt.extend_from_slice(&(if extensible { EXTENSIBLE } else { tag }).to_le_bytes());
+    fmt.extend_from_slice(&(channels as u16).to_le_bytes());
+    let sample_rate = format.sample_rate.round() as u32;
+    fmt.extend_from_slice(&sample_rate.to_le_bytes());
+    fmt.extend_from_slice(&(sample_rate * block_align).to_le_bytes());
+    fmt.extend_from_slice(&(block_align as u16).to_le_bytes());
+    if extensible {
+        fmt.extend_from_slice(&(container as u16 * 8).to_le_bytes());
+        fmt.extend_from_slice(&22u16.to_le_bytes());
+        fmt.extend_from_slice(&(format.bits_per_channel as u16).to_le_bytes());
+        fmt.extend_from_slice(&mask.to_le_bytes());
+        fmt.extend_from_slice(&tag.to_le_bytes());
+        fmt.extend_from_slice(&GUID_TAIL);
+    } else {
+        fmt.extend_from_slice(&(format.bits_per_channel as u16).to_le_bytes());
+        if tag != PCM {
+            fmt.extend_from_slice(&0u16.to_le_bytes());
+        }
+    }
+
+    let frames = file.data.len() as u64 / block_align as u64;
+    // headroom for header chunks
+    let rf64 = file.file_type == FileType::Rf64 || file.data.len() as u64 > 0xffff_0000;
+    let start = out.len();
+    out.extend_from_slice(if rf64 { b"RF64" } else { b"RIFF" });
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(b"WAVE");
+    let ds64 = out.len() + 8;
+    if rf64 {
+        chunk(out, b"ds64", &[0; 28]);
+    }
+    chunk(out, b"fmt ", &fmt);
+    if tag != PCM {
+        chunk(
+            out,
+            b"fact",
+            &(frames.min(u32::MAX as u64) as u32).to_le_bytes(),
+        );
+    }
+    if !file.info.is_empty() {
+        let mut list = b"INFO".to_vec();
+        for (id, key) in INFO {
+            if let Some(value) = file.info.get(key) {
+                let mut text = value.as_bytes().to_vec();
+                text.push(0);
+                chunk(&mut list, id, &text);
+            }
+        }
+        chunk(out, b"LIST", &list);
+    }
+    out.extend_from_slice(b"data");
+    let data_size = if rf64 {
+        u32::MAX
+    } else {
+        file.data.len() as u32
+    };
+    out.extend_from_slice(&data_size.to_le_bytes());
+    out.extend_from_slice(&file.data);
+    if file.data.len() % 2 == 1 {
+        out.push(0);
+    }
+
+    let riff_size = (out.len() - start - 8) as u64;
+    if rf64 {
+        out[start + 4..start + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+        out[ds64..ds64 + 8].copy_from_slice(&riff_size.to_le_bytes());
+        out[ds64 + 8..ds64 + 16].copy_from_slice(&(file.data.len() as u64).to_le_bytes());
+        out[ds64 + 16..ds64 + 24].copy_from_slice(&frames.to_le_bytes());
+    } else {
+        out[start + 4..start + 8].copy_from_slice(&(riff_size as u32).to_le_bytes());
+    }
+    Ok(())
+}
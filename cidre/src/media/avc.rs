@@ -0,0 +1,499 @@
+//! H.264 parameter sets and `avcC` (AVCDecoderConfigurationRecord)
+
+use super::{
+    Error, Result, Vui,
+    bits::{BitReader, ByteReader, rbsp},
+};
+
+pub const NAL_SPS: u8 = 7;
+pub const NAL_PPS: u8 = 8;
+
+/// Profiles with chroma format and bit depth in SPS and `avcC` extension.
+const fn is_high(profile_idc: u8) -> bool {
+    matches!(
+        profile_idc,
+        100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+    )
+}
+
+fn nal_payload(nal: &[u8], nal_type: u8) -> Result<&[u8]> {
+    match nal.split_first() {
+        Some((header, payload)) if header & 0x1f == nal_type => Ok(payload),
+        Some(_) => Err(Error::Invalid("unexpected nal unit type")),
+        None => Err(Error::Eof),
+    }
+}
+
+/// Sequence parameter set
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Sps {
+    pub profile_idc: u8,
+    /// `constraint_set0_flag`..`constraint_set5_flag` and reserved bits
+    pub constraint_flags: u8,
+    pub level_idc: u8,
+    pub id: u32,
+    pub chroma_format_idc: u8,
+    pub separate_colour_plane: bool,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub max_num_ref_frames: u32,
+    pub pic_width_in_mbs: u32,
+    pub pic_height_in_map_units: u32,
+    pub frame_mbs_only: bool,
+    /// left, right, top, bottom in crop units
+    pub frame_crop: [u32; 4],
+    pub vui: Option<Vui>,
+}
+
+impl Sps {
+    /// Parses NAL unit with header.
+    pub fn parse(nal: &[u8]) -> Result<Self> {
+        let data = rbsp(nal_payload(nal, NAL_SPS)?);
+        let mut r = BitReader::new(&data);
+        let profile_idc = r.u8(8)?;
+        let constraint_flags = r.u8(8)?;
+        let level_idc = r.u8(8)?;
+        let id = r.ue_max(31, "seq_parameter_set_id")?;
+
+        let mut chroma_format_idc = 1;
+        let mut separate_colour_plane = false;
+        let mut bit_depth_luma = 8;
+        let mut bit_depth_chroma = 8;
+        if is_high(profile_idc) {
+            chroma_format_idc = r.ue_max(3, "chroma_format_idc")? as u8;
+            if chroma_format_idc == 3 {
+                separate_colour_plane = r.bit()?;
+            }
+            bit_depth_luma = r.ue_max(6, "bit_depth_luma_minus8")? as u8 + 8;
+            bit_depth_chroma = r.ue_max(6, "bit_depth_chroma_minus8")? as u8 + 8;
+            // qpprime_y_zero_transform_bypass_flag
+            r.skip(1)?;
+            if r.bit()? {
+                let lists = if chroma_format_idc == 3 { 12 } else { 8 };
+                for i in 0..lists {
+                    if r.bit()? {
+                        skip_scaling_list(&mut r, if i < 6 { 16 } else { 64 })?;
+                    }
+                }
+            }
+        }
+
+        // log2_max_frame_num_minus4
+        r.ue_max(12, "log2_max_frame_num_minus4")?;
+        match r.ue_max(2, "pic_order_cnt_type")? {
+            0 => {
+                r.ue_max(12, "log2_max_pic_order_cnt_lsb_minus4")?;
+            }
+            1 => {
+                // delta_pic_order_always_zero_flag, offset_for_non_ref_pic,
+                // offset_for_top_to_bottom_field
+                r.skip(1)?;
+                r.se()?;
+                r.se()?;
+                let n = r.ue_max(255, "num_ref_frames_in_pic_order_cnt_cycle")?;
+                for _ in 0..n {
+                    r.se()?;
+                }
+            }
+            _ => {}
+        }
+        let max_num_ref_frames = r.ue()?;
+        // gaps_in_frame_num_value_allowed_flag
+        r.skip(1)?;
+        let pic_width_in_mbs = r.ue()? + 1;
+        let pic_height_in_map_units = r.ue()? + 1;
+        let frame_mbs_only = r.bit()?;
+        if !frame_mbs_only {
+            // mb_adaptive_frame_field_flag
+            r.skip(1)?;
+        }
+        // direct_8x8_inference_flag
+        r.skip(1)?;
+        let mut frame_crop = [0; 4];
+        if r.bit()? {
+            for c in frame_crop.iter_mut() {
+                *c = r.ue()?;
+            }
+        }
+        let vui = if r.bit()? {
+            Some(Vui::parse_avc(&mut r)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            profile_idc,
+            constraint_flags,
+            level_idc,
+            id,
+            chroma_format_idc,
+            separate_colour_plane,
+            bit_depth_luma,
+            bit_depth_chroma,
+            max_num_ref_frames,
+            pic_width_in_mbs,
+            pic_height_in_map_units,
+            frame_mbs_only,
+            frame_crop,
+            vui,
+        })
+    }
+
+    /// `ChromaArrayType`
+    #[inline]
+    pub fn chroma_array_type(&self) -> u8 {
+        if self.separate_colour_plane {
+            0
+        } else {
+            self.chroma_format_idc
+        }
+    }
+
+    fn crop_units(&self) -> (u32, u32) {
+        let (sub_width, sub_height) = match self.chroma_array_type() {
+            0 => (1, 1),
+            1 => (2, 2),
+            2 => (2, 1),
+            _ => (1, 1),
+        };
+        (sub_width, sub_height * (2 - self.frame_mbs_only as u32))
+    }
+
+    /// Cropped width in pixels
+    pub fn width(&self) -> u32 {
+        let (x, _) = self.crop_units();
+        let [left, right, ..] = self.frame_crop;
+        (self.pic_width_in_mbs * 16).saturating_sub(x * (left + right))
+    }
+
+    /// Cropped height in pixels
+    pub fn height(&self) -> u32 {
+        let (_, y) = self.crop_units();
+        let [_, _, top, bottom] = self.frame_crop;
+        let height = (2 - self.frame_mbs_only as u32) * self.pic_height_in_map_units * 16;
+        height.saturating_sub(y * (top + bottom))
+    }
+
+    /// Frame rate from VUI timing, two ticks per frame.
+    pub fn frame_rate(&self) -> Option<f64> {
+        let timing = self.vui?.timing?;
+        if timing.num_units_in_tick == 0 {
+            return None;
+        }
+        Some(timing.time_scale as f64 / (2.0 * timing.num_units_in_tick as f64))
+    }
+
+    /// RFC 6381 codec string, `avc1.640028`
+    pub fn codec_string(&self) -> String {
+        codec_string(
+            "avc1",
+            self.profile_idc,
+            self.constraint_flags,
+            self.level_idc,
+        )
+    }
+}
+
+fn skip_scaling_list(r: &mut BitReader, size: usize) -> Result {
+    let mut last = 8i32;
+    let mut next = 8i32;
+    for _ in 0..size {
+        if next != 0 {
+            next = (last + r.se()? + 256) % 256;
+        }
+        if next != 0 {
+            last = next;
+        }
+    }
+    Ok(())
+}
+
+fn codec_string(entry: &str, profile: u8, compatibility: u8, level: u8) -> String {
+    format!("{entry}.{profile:02X}{compatibility:02X}{level:02X}")
+}
+
+/// Picture parameter set, leading fields only.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Pps {
+    pub id: u32,
+    pub sps_id: u32,
+    pub entropy_coding_mode: bool,
+}
+
+impl Pps {
+    /// Parses NAL unit with header.
+    pub fn parse(nal: &[u8]) -> Result<Self> {
+        let data = rbsp(nal_payload(nal, NAL_PPS)?);
+        let mut r = BitReader::new(&data);
+        Ok(Self {
+            id: r.ue_max(255, "pic_parameter_set_id")?,
+            sps_id: r.ue_max(31, "seq_parameter_set_id")?,
+            entropy_coding_mode: r.bit()?,
+        })
+    }
+}
+
+/// High profiles part of `avcC`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConfigExt {
+    pub chroma_format: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub sps_ext: Vec<Vec<u8>>,
+}
+
+/// `avcC` box payload
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Config {
+    pub profile: u8,
+    pub compatibility: u8,
+    pub level: u8,
+    /// Size of NAL unit length prefix, 1, 2 or 4
+    pub nal_unit_len: u8,
+    pub sps: Vec<Vec<u8>>,
+    pub pps: Vec<Vec<u8>>,
+    /// Only for high profiles, often omitted by encoders
+    pub ext: Option<ConfigExt>,
+}
+
+fn read_nals(r: &mut ByteReader, n: usize) -> Result<Vec<Vec<u8>>> {
+    (0..n)
+        .map(|_| {
+            let len = r.u16()? as usize;
+            Ok(r.bytes(len)?.to_vec())
+        })
+        .collect()
+}
+
+fn write_nals(buf: &mut Vec<u8>, nals: &[Vec<u8>]) {
+    for nal in nals {
+        buf.extend_from_slice(&(nal.len() as u16).to_be_bytes());
+        buf.extend_from_slice(nal);
+    }
+}
+
+impl Config {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut r = ByteReader::new(data);
+        if r.u8()? != 1 {
+            return Err(Error::Unsupported("avcC configuration version"));
+        }
+        let profile = r.u8()?;
+        let compatibility = r.u8()?;
+        let level = r.u8()?;
+        let nal_unit_len = (r.u8()? & 0b11) + 1;
+        if nal_unit_len == 3 {
+            return Err(Error::Invalid("avcC length size"));
+        }
+        let n = (r.u8()? & 0x1f) as usize;
+        let sps = read_nals(&mut r, n)?;
+        let n = r.u8()? as usize;
+        let pps = read_nals(&mut r, n)?;
+
+        let ext = if is_high(profile) && r.remaining().len() >= 4 {
+            let chroma_format = r.u8()? & 0b11;
+            let bit_depth_luma = (r.u8()? & 0b111) + 8;
+            let bit_depth_chroma = (r.u8()? & 0b111) + 8;
+            let n = r.u8()? as usize;
+            Some(ConfigExt {
+                chroma_format,
+                bit_depth_luma,
+                bit_depth_chroma,
+                sps_ext: read_nals(&mut r, n)?,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            profile,
+            compatibility,
+            level,
+            nal_unit_len,
+            sps,
+            pps,
+            ext,
+        })
+    }
+
+    /// Builds record from SPS and PPS NAL units, profile fields come from the first SPS.
+    pub fn with_param_sets(sps: &[&[u8]], pps: &[&[u8]], nal_unit_len: u8) -> Result<Self> {
+        if !matches!(nal_unit_len, 1 | 2 | 4) {
+            return Err(Error::Invalid("nal unit length"));
+        }
+        let Some(first) = sps.first() else {
+            return Err(Error::Invalid("no sps"));
+        };
+        let parsed = Sps::parse(first)?;
+        let ext = is_high(parsed.profile_idc).then(|| ConfigExt {
+            chroma_format: parsed.chroma_format_idc,
+            bit_depth_luma: parsed.bit_depth_luma,
+            bit_depth_chroma: parsed.bit_depth_chroma,
+            sps_ext: Vec::new(),
+        });
+        Ok(Self {
+            profile: parsed.profile_idc,
+            compatibility: parsed.constraint_flags,
+            level: parsed.level_idc,
+            nal_unit_len,
+            sps: sps.iter().map(|v| v.to_vec()).collect(),
+            pps: pps.iter().map(|v| v.to_vec()).collect(),
+            ext,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![
+            1,
+            self.profile,
+            self.compatibility,
+            self.level,
+            0b1111_1100 | (self.nal_unit_len - 1),
+            0b1110_0000 | self.sps.len() as u8,
+        ];
+        write_nals(&mut buf, &self.sps);
+        buf.push(self.pps.len() as u8);
+        write_nals(&mut buf, &self.pps);
+        if let Some(ext) = &self.ext {
+            buf.push(0b1111_1100 | ext.chroma_format);
+            buf.push(0b1111_1000 | (ext.bit_depth_luma - 8));
+            buf.push(0b1111_1000 | (ext.bit_depth_chroma - 8));
+            buf.push(ext.sps_ext.len() as u8);
+            write_nals(&mut buf, &ext.sps_ext);
+        }
+        buf
+    }
+
+    /// First SPS decoded
+    pub fn first_sps(&self) -> Result<Sps> {
+        match self.sps.first() {
+            Some(sps) => Sps::parse(sps),
+            None => Err(Error::Invalid("no sps")),
+        }
+    }
+
+    /// RFC 6381 codec string, `avc1.640028`
+    pub fn codec_string(&self) -> String {
+        codec_string("avc1", self.profile, self.compatibility, self.level)
+    }
+
+    /// Codec string for other sample entry types like `avc3`
+    pub fn codec_string_for(&self, entry: &str) -> String {
+        codec_string(entry, self.profile, self.compatibility, self.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        Error,
+        avc::{Config, Pps, Sps},
+        vui::{Color, Timing},
+    };
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// High@4.0 1920x1080 bt709 29.97
+    const HIGH: &str = "67640028acd940780227e5c05a808080a000007d20001d4c1080";
+    /// Constrained baseline@3.0 640x480 without VUI
+    const BASELINE: &str = "6742c01eda0280f640";
+    /// High 4:2:2@4.1 10-bit interlaced with scaling lists and poc type 1
+    const HIGH_422: &str = "677a00294db6bfff80a153309603c0223e5ffc0010000ce8";
+    const PPS: &str = "68ee3c80";
+
+    #[test]
+    fn sps() {
+        let sps = Sps::parse(&hex(HIGH)).unwrap();
+        assert_eq!(sps.profile_idc, 100);
+        assert_eq!(sps.level_idc, 40);
+        assert_eq!((sps.width(), sps.height()), (1920, 1080));
+        assert_eq!(sps.max_num_ref_frames, 4);
+        assert_eq!(sps.codec_string(), "avc1.640028");
+        let vui = sps.vui.unwrap();
+        assert_eq!(vui.sar, Some((1, 1)));
+        assert_eq!(vui.video_format, Some(5));
+        assert!(!vui.full_range);
+        let color = Color {
+            primaries: 1,
+            transfer: 1,
+            matrix: 1,
+        };
+        assert_eq!(vui.color, Some(color));
+        let timing = Timing {
+            num_units_in_tick: 1001,
+            time_scale: 60000,
+            fixed_frame_rate: true,
+        };
+        assert_eq!(vui.timing, Some(timing));
+        assert!((sps.frame_rate().unwrap() - 29.97).abs() < 0.001);
+
+        let sps = Sps::parse(&hex(BASELINE)).unwrap();
+        assert_eq!((sps.width(), sps.height()), (640, 480));
+        assert_eq!((sps.bit_depth_luma, sps.chroma_format_idc), (8, 1));
+        assert_eq!(sps.codec_string(), "avc1.42C01E");
+        assert!(sps.vui.is_none());
+        assert!(sps.frame_rate().is_none());
+
+        let sps = Sps::parse(&hex(HIGH_422)).unwrap();
+        assert_eq!(sps.id, 1);
+        assert_eq!(sps.chroma_format_idc, 2);
+        assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (10, 10));
+        assert!(!sps.frame_mbs_only);
+        assert_eq!((sps.width(), sps.height()), (1920, 1080));
+        assert_eq!(sps.vui.unwrap().sar, Some((4, 3)));
+        assert_eq!(sps.codec_string(), "avc1.7A0029");
+
+        assert_eq!(
+            Sps::parse(&hex(PPS)),
+            Err(Error::Invalid("unexpected nal unit type"))
+        );
+        assert_eq!(Sps::parse(&hex(&HIGH[..12])), Err(Error::Eof));
+    }
+
+    #[test]
+    fn pps() {
+        let pps = Pps::parse(&hex(PPS)).unwrap();
+        assert_eq!(pps.id, 0);
+        assert_eq!(pps.sps_id, 0);
+        assert!(pps.entropy_coding_mode);
+    }
+
+    #[test]
+    fn config() {
+        let sps = hex(HIGH);
+        let pps = hex(PPS);
+        let config = Config::with_param_sets(&[&sps], &[&pps], 4).unwrap();
+        assert_eq!(config.codec_string(), "avc1.640028");
+        assert_eq!(config.codec_string_for("avc3"), "avc3.640028");
+        let ext = config.ext.as_ref().unwrap();
+        assert_eq!((ext.chroma_format, ext.bit_depth_luma), (1, 8));
+
+        let bytes = config.to_bytes();
+        assert_eq!(&bytes[..6], &[1, 0x64, 0x00, 0x28, 0xff, 0xe1]);
+        assert_eq!(bytes.len(), 6 + 2 + sps.len() + 1 + 2 + pps.len() + 4);
+        let parsed = Config::parse(&bytes).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.first_sps().unwrap().width(), 1920);
+
+        // high profile record without extension
+        let parsed = Config::parse(&bytes[..bytes.len() - 4]).unwrap();
+        assert!(parsed.ext.is_none());
+        assert_eq!(parsed.sps, config.sps);
+
+        let sps = hex(BASELINE);
+        let config = Config::with_param_sets(&[&sps], &[&pps], 2).unwrap();
+        assert!(config.ext.is_none());
+        let bytes = config.to_bytes();
+        assert_eq!(bytes[4], 0xfd);
+        assert_eq!(Config::parse(&bytes).unwrap(), config);
+
+        assert!(Config::with_param_sets(&[], &[&pps], 4).is_err());
+        assert!(Config::with_param_sets(&[&sps], &[&pps], 3).is_err());
+        assert_eq!(Config::parse(&bytes[..9]), Err(Error::Eof));
+    }
+}
@@ -0,0 +1,245 @@
+use std::borrow::Cow;
+
+use super::{Error, Result};
+
+/// Removes emulation prevention bytes (`00 00 03` -> `00 00`).
+pub fn rbsp(ebsp: &[u8]) -> Cow<'_, [u8]> {
+    let has_epb = ebsp.windows(3).any(|w| w[0] == 0 && w[1] == 0 && w[2] == 3);
+    if !has_epb {
+        return Cow::Borrowed(ebsp);
+    }
+    let mut res = Vec::with_capacity(ebsp.len());
+    let mut zeros = 0;
+    for &b in ebsp {
+        if zeros >= 2 && b == 3 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if b == 0 { zeros + 1 } else { 0 };
+        res.push(b);
+    }
+    Cow::Owned(res)
+}
+
+/// MSB first bit reader with Exp-Golomb codes.
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    #[inline]
+    pub fn bits_left(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    #[inline]
+    pub fn bit(&mut self) -> Result<bool> {
+        let Some(byte) = self.data.get(self.pos / 8) else {
+            return Err(Error::Eof);
+        };
+        let bit = (byte >> (7 - self.pos % 8)) & 1;
+        self.pos += 1;
+        Ok(bit == 1)
+    }
+
+    /// Reads up to 64 bits.
+    pub fn bits(&mut self, n: u32) -> Result<u64> {
+        debug_assert!(n <= 64);
+        if self.bits_left() < n as usize {
+            return Err(Error::Eof);
+        }
+        let mut res = 0u64;
+        for _ in 0..n {
+            res = (res << 1) | self.bit()? as u64;
+        }
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn u8(&mut self, n: u32) -> Result<u8> {
+        Ok(self.bits(n)? as u8)
+    }
+
+    #[inline]
+    pub fn u16(&mut self, n: u32) -> Result<u16> {
+        Ok(self.bits(n)? as u16)
+    }
+
+    #[inline]
+    pub fn u32(&mut self, n: u32) -> Result<u32> {
+        Ok(self.bits(n)? as u32)
+    }
+
+    pub fn skip(&mut self, n: usize) -> Result {
+        if self.bits_left() < n {
+            return Err(Error::Eof);
+        }
+        self.pos += n;
+        Ok(())
+    }
+
+    /// ue(v)
+    pub fn ue(&mut self) -> Result<u32> {
+        let mut zeros = 0;
+        while !self.bit()? {
+            zeros += 1;
+            if zeros > 31 {
+                return Err(Error::Invalid("exp-golomb code is too long"));
+            }
+        }
+        let rest = self.bits(zeros)?;
+        Ok(((1u64 << zeros) - 1 + rest) as u32)
+    }
+
+    /// se(v)
+    pub fn se(&mut self) -> Result<i32> {
+        let k = self.ue()? as i64;
+        let v = if k & 1 == 1 { (k + 1) / 2 } else { -(k / 2) };
+        Ok(v as i32)
+    }
+
+    /// ue(v) limited to `max`.
+    pub fn ue_max(&mut self, max: u32, what: &'static str) -> Result<u32> {
+        let v = self.ue()?;
+        if v > max {
+            return Err(Error::Invalid(what));
+        }
+        Ok(v)
+    }
+}
+
+/// MSB first bit writer, pads last byte with zeros.
+#[derive(Debug, Default, Clone)]
+pub struct BitWriter {
+    buf: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes low `n` bits of `v`.
+    pub fn bits(&mut self, v: u64, n: u32) {
+        for i in (0..n).rev() {
+            if self.len % 8 == 0 {
+                self.buf.push(0);
+            }
+            let bit = ((v >> i) & 1) as u8;
+            *self.buf.last_mut().unwrap() |= bit << (7 - self.len % 8);
+            self.len += 1;
+        }
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Big-endian byte cursor, `_le` reads are little-endian.
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    #[inline]
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        let Some((head, tail)) = self.data.split_at_checked(n) else {
+            return Err(Error::Eof);
+        };
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    #[inline]
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    #[inline]
+    pub fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    #[inline]
+    pub fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    #[inline]
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    // little endian ones are for RIFF chunks of `audio_file`
+    #[cfg(feature = "cat")]
+    #[inline]
+    pub fn u16_le(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    #[cfg(feature = "cat")]
+    #[inline]
+    pub fn u32_le(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    #[cfg(feature = "cat")]
+    #[inline]
+    pub fn u64_le(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn skip(&mut self, n: usize) -> Result {
+        self.bytes(n).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exp_golomb() {
+        // 1 | 010 | 011 | 00100 | 00100 | 0001000
+        let data = [0b1010_0110, 0b0100_0010, 0b0000_1000];
+        let mut r = BitReader::new(&data);
+        assert_eq!(r.ue().unwrap(), 0);
+        assert_eq!(r.ue().unwrap(), 1);
+        assert_eq!(r.se().unwrap(), -1);
+        assert_eq!(r.ue().unwrap(), 3);
+        assert_eq!(r.se().unwrap(), 2);
+        assert_eq!(r.ue().unwrap(), 7);
+        assert_eq!(r.bits_left(), 0);
+        assert_eq!(r.bit(), Err(Error::Eof));
+    }
+
+    #[test]
+    fn emulation_prevention() {
+        assert!(matches!(rbsp(&[1, 2, 3]), Cow::Borrowed(_)));
+        assert_eq!(
+            rbsp(&[0, 0, 3, 1, 0, 0, 3, 0, 0, 3]).as_ref(),
+            &[0, 0, 1, 0, 0, 0, 0]
+        );
+    }
+}
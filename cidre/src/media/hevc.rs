@@ -0,0 +1,662 @@
+//! HEVC parameter sets and `hvcC` (HEVCDecoderConfigurationRecord)
+
+use super::{
+    Error, Result, Vui,
+    bits::{BitReader, ByteReader, rbsp},
+};
+
+pub const NAL_VPS: u8 = 32;
+pub const NAL_SPS: u8 = 33;
+pub const NAL_PPS: u8 = 34;
+
+/// NAL unit type from the two byte header.
+#[inline]
+pub fn nal_type(nal: &[u8]) -> Option<u8> {
+    nal.first().map(|b| (b >> 1) & 0x3f)
+}
+
+fn nal_payload(nal: &[u8], nal_type: u8) -> Result<&[u8]> {
+    if nal.len() < 2 {
+        return Err(Error::Eof);
+    }
+    if self::nal_type(nal) != Some(nal_type) {
+        return Err(Error::Invalid("unexpected nal unit type"));
+    }
+    Ok(&nal[2..])
+}
+
+/// General `profile_tier_level`
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Ptl {
+    pub profile_space: u8,
+    /// `false` is Main tier, `true` is High tier
+    pub tier: bool,
+    pub profile_idc: u8,
+    /// `general_profile_compatibility_flag[j]` is bit `31 - j`
+    pub compat_flags: u32,
+    /// 48 bits starting with `general_progressive_source_flag`
+    pub constraint_flags: u64,
+    pub level_idc: u8,
+}
+
+impl Ptl {
+    fn parse(r: &mut BitReader, max_sub_layers_minus1: u8) -> Result<Self> {
+        let res = Self {
+            profile_space: r.u8(2)?,
+            tier: r.bit()?,
+            profile_idc: r.u8(5)?,
+            compat_flags: r.u32(32)?,
+            constraint_flags: r.bits(48)?,
+            level_idc: r.u8(8)?,
+        };
+        let mut present = [(false, false); 8];
+        for p in present.iter_mut().take(max_sub_layers_minus1 as usize) {
+            *p = (r.bit()?, r.bit()?);
+        }
+        if max_sub_layers_minus1 > 0 {
+            r.skip(2 * (8 - max_sub_layers_minus1 as usize))?;
+        }
+        for (profile, level) in present.iter().take(max_sub_layers_minus1 as usize) {
+            if *profile {
+                r.skip(88)?;
+            }
+            if *level {
+                r.skip(8)?;
+            }
+        }
+        Ok(res)
+    }
+
+    /// RFC 6381 codec string per ISO/IEC 14496-15 E.3, `hvc1.2.4.L153.B0`
+    pub fn codec_string(&self, entry: &str) -> String {
+        let space = match self.profile_space {
+            1 => "A",
+            2 => "B",
+            3 => "C",
+            _ => "",
+        };
+        let tier = if self.tier { 'H' } else { 'L' };
+        let mut res = format!(
+            "{entry}.{space}{}.{:X}.{tier}{}",
+            self.profile_idc,
+            self.compat_flags.reverse_bits(),
+            self.level_idc
+        );
+        let bytes = &self.constraint_flags.to_be_bytes()[2..];
+        let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+        for b in &bytes[..len] {
+            res.push_str(&format!(".{b:X}"));
+        }
+        res
+    }
+}
+
+/// Video parameter set
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Vps {
+    pub id: u8,
+    pub max_sub_layers: u8,
+    pub temporal_id_nesting: bool,
+    pub ptl: Ptl,
+    /// `vps_num_units_in_tick`, `vps_time_scale`
+    pub timing: Option<(u32, u32)>,
+}
+
+impl Vps {
+    /// Parses NAL unit with header.
+    pub fn parse(nal: &[u8]) -> Result<Self> {
+        let data = rbsp(nal_payload(nal, NAL_VPS)?);
+        let mut r = BitReader::new(&data);
+        let id = r.u8(4)?;
+        // base_layer_internal_flag, base_layer_available_flag, max_layers_minus1
+        r.skip(8)?;
+        let max_sub_layers_minus1 = r.u8(3)?;
+        let temporal_id_nesting = r.bit()?;
+        // vps_reserved_0xffff_16bits
+        r.skip(16)?;
+        let ptl = Ptl::parse(&mut r, max_sub_layers_minus1)?;
+        skip_sub_layer_ordering_info(&mut r, max_sub_layers_minus1)?;
+        let max_layer_id = r.u8(6)?;
+        let num_layer_sets_minus1 = r.ue_max(1023, "vps_num_layer_sets_minus1")?;
+        r.skip(num_layer_sets_minus1 as usize * (max_layer_id as usize + 1))?;
+        let timing = if r.bit()? {
+            Some((r.u32(32)?, r.u32(32)?))
+        } else {
+            None
+        };
+        Ok(Self {
+            id,
+            max_sub_layers: max_sub_layers_minus1 + 1,
+            temporal_id_nesting,
+            ptl,
+            timing,
+        })
+    }
+}
+
+fn skip_sub_layer_ordering_info(r: &mut BitReader, max_sub_layers_minus1: u8) -> Result {
+    let all = r.bit()?;
+    let n = if all { max_sub_layers_minus1 + 1 } else { 1 };
+    for _ in 0..n {
+        // max_dec_pic_buffering_minus1, max_num_reorder_pics, max_latency_increase_plus1
+        r.ue()?;
+        r.ue()?;
+        r.ue()?;
+    }
+    Ok(())
+}
+
+/// Sequence parameter set
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Sps {
+    pub vps_id: u8,
+    pub max_sub_layers: u8,
+    pub temporal_id_nesting: bool,
+    pub ptl: Ptl,
+    pub id: u32,
+    pub chroma_format_idc: u8,
+    pub separate_colour_plane: bool,
+    pub pic_width: u32,
+    pub pic_height: u32,
+    /// left, right, top, bottom in chroma units
+    pub conf_window: [u32; 4],
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub vui: Option<Vui>,
+}
+
+impl Sps {
+    /// Parses NAL unit with header.
+    pub fn parse(nal: &[u8]) -> Result<Self> {
+        let data = rbsp(nal_payload(nal, NAL_SPS)?);
+        let mut r = BitReader::new(&data);
+        let vps_id = r.u8(4)?;
+        let max_sub_layers_minus1 = r.u8(3)?;
+        let temporal_id_nesting = r.bit()?;
+        let ptl = Ptl::parse(&mut r, max_sub_layers_minus1)?;
+        let id = r.ue_max(15, "sps_seq_parameter_set_id")?;
+        let chroma_format_idc = r.ue_max(3, "chroma_format_idc")? as u8;
+        let separate_colour_plane = chroma_format_idc == 3 && r.bit()?;
+        let pic_width = r.ue()?;
+        let pic_height = r.ue()?;
+        let mut conf_window = [0; 4];
+        if r.bit()? {
+            for c in conf_window.iter_mut() {
+                *c = r.ue()?;
+            }
+        }
+        let bit_depth_luma = r.ue_max(8, "bit_depth_luma_minus8")? as u8 + 8;
+        let bit_depth_chroma = r.ue_max(8, "bit_depth_chroma_minus8")? as u8 + 8;
+        let log2_max_poc_lsb = r.ue_max(12, "log2_max_pic_order_cnt_lsb_minus4")? + 4;
+        skip_sub_layer_ordering_info(&mut r, max_sub_layers_minus1)?;
+        // log2_min_luma_coding_block_size_minus3, log2_diff_max_min_luma_coding_block_size,
+        // log2_min_luma_transform_block_size_minus2, log2_diff_max_min_luma_transform_block_size,
+        // max_transform_hierarchy_depth_inter, max_transform_hierarchy_depth_intra
+        for _ in 0..6 {
+            r.ue()?;
+        }
+        if r.bit()? && r.bit()? {
+            skip_scaling_list_data(&mut r)?;
+        }
+        // amp_enabled_flag, sample_adaptive_offset_enabled_flag
+        r.skip(2)?;
+        if r.bit()? {
+            // pcm bit depths, sizes, loop filter disabled flag
+            r.skip(8)?;
+            r.ue()?;
+            r.ue()?;
+            r.skip(1)?;
+        }
+        let num_sets = r.ue_max(64, "num_short_term_ref_pic_sets")? as usize;
+        let mut num_delta_pocs = Vec::with_capacity(num_sets);
+        for idx in 0..num_sets {
+            let n = st_ref_pic_set(&mut r, idx, &num_delta_pocs)?;
+            num_delta_pocs.push(n);
+        }
+        if r.bit()? {
+            let n = r.ue_max(32, "num_long_term_ref_pics_sps")?;
+            r.skip(n as usize * (log2_max_poc_lsb as usize + 1))?;
+        }
+        // sps_temporal_mvp_enabled_flag, strong_intra_smoothing_enabled_flag
+        r.skip(2)?;
+        let vui = if r.bit()? {
+            Some(Vui::parse_hevc(&mut r)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            vps_id,
+            max_sub_layers: max_sub_layers_minus1 + 1,
+            temporal_id_nesting,
+            ptl,
+            id,
+            chroma_format_idc,
+            separate_colour_plane,
+            pic_width,
+            pic_height,
+            conf_window,
+            bit_depth_luma,
+            bit_depth_chroma,
+            vui,
+        })
+    }
+
+    fn chroma_units(&self) -> (u32, u32) {
+        if self.separate_colour_plane {
+            return (1, 1);
+        }
+        match self.chroma_format_idc {
+            1 => (2, 2),
+            2 => (2, 1),
+            _ => (1, 1),
+        }
+    }
+
+    /// Cropped width in pixels
+    pub fn width(&self) -> u32 {
+        let [left, right, ..] = self.conf_window;
+        let (x, _) = self.chroma_units();
+        self.pic_width.saturating_sub(x * (left + right))
+    }
+
+    /// Cropped height in pixels
+    pub fn height(&self) -> u32 {
+        let [_, _, top, bottom] = self.conf_window;
+        let (_, y) = self.chroma_units();
+        self.pic_height.saturating_sub(y * (top + bottom))
+    }
+
+    /// Frame rate from VUI timing.
+    pub fn frame_rate(&self) -> Option<f64> {
+        let timing = self.vui?.timing?;
+        if timing.num_units_in_tick == 0 {
+            return None;
+        }
+        Some(timing.time_scale as f64 / timing.num_units_in_tick as f64)
+    }
+
+    /// RFC 6381 codec string, `hvc1.1.6.L120.90`
+    pub fn codec_string(&self) -> String {
+        self.ptl.codec_string("hvc1")
+    }
+}
+
+fn skip_scaling_list_data(r: &mut BitReader) -> Result {
+    for size_id in 0..4 {
+        let step = if size_id == 3 { 3 } else { 1 };
+        for _ in (0..6).step_by(step) {
+            if !r.bit()? {
+                // scaling_list_pred_matrix_id_delta
+                r.ue()?;
+                continue;
+            }
+            let coefs = 64.min(1 << (4 + (size_id << 1)));
+            if size_id > 1 {
+                // scaling_list_dc_coef_minus8
+                r.se()?;
+            }
+            for _ in 0..coefs {
+                r.se()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses `st_ref_pic_set(idx)` of SPS and returns its `NumDeltaPocs`.
+fn st_ref_pic_set(r: &mut BitReader, idx: usize, num_delta_pocs: &[u32]) -> Result<u32> {
+    let inter_rps_pred = idx != 0 && r.bit()?;
+    if inter_rps_pred {
+        // delta_rps_sign, abs_delta_rps_minus1
+        r.skip(1)?;
+        r.ue()?;
+        let mut n = 0;
+        for _ in 0..=num_delta_pocs[idx - 1] {
+            let used_by_curr_pic = r.bit()?;
+            let use_delta = used_by_curr_pic || r.bit()?;
+            n += use_delta as u32;
+        }
+        return Ok(n);
+    }
+    let negative = r.ue_max(16, "num_negative_pics")?;
+    let positive = r.ue_max(16, "num_positive_pics")?;
+    for _ in 0..negative + positive {
+        // delta_poc_minus1, used_by_curr_pic_flag
+        r.ue()?;
+        r.skip(1)?;
+    }
+    Ok(negative + positive)
+}
+
+/// Picture parameter set, leading fields only.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Pps {
+    pub id: u32,
+    pub sps_id: u32,
+}
+
+impl Pps {
+    /// Parses NAL unit with header.
+    pub fn parse(nal: &[u8]) -> Result<Self> {
+        let data = rbsp(nal_payload(nal, NAL_PPS)?);
+        let mut r = BitReader::new(&data);
+        Ok(Self {
+            id: r.ue_max(63, "pps_pic_parameter_set_id")?,
+            sps_id: r.ue_max(15, "pps_seq_parameter_set_id")?,
+        })
+    }
+}
+
+/// NAL units of one type in `hvcC`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NalArray {
+    pub complete: bool,
+    pub nal_type: u8,
+    pub units: Vec<Vec<u8>>,
+}
+
+/// `hvcC` box payload
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Config {
+    pub ptl: Ptl,
+    pub min_spatial_segmentation: u16,
+    pub parallelism_type: u8,
+    pub chroma_format: u8,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    /// Frames per 256 seconds, 0 if unspecified
+    pub avg_frame_rate: u16,
+    pub constant_frame_rate: u8,
+    pub num_temporal_layers: u8,
+    pub temporal_id_nested: bool,
+    /// Size of NAL unit length prefix, 1, 2 or 4
+    pub nal_unit_len: u8,
+    pub arrays: Vec<NalArray>,
+}
+
+impl Config {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut r = ByteReader::new(data);
+        if r.u8()? != 1 {
+            return Err(Error::Unsupported("hvcC configuration version"));
+        }
+        let b = r.u8()?;
+        let compat_flags = r.u32()?;
+        let mut constraint = [0u8; 8];
+        constraint[2..].copy_from_slice(r.bytes(6)?);
+        let ptl = Ptl {
+            profile_space: b >> 6,
+            tier: b & 0x20 != 0,
+            profile_idc: b & 0x1f,
+            compat_flags,
+            constraint_flags: u64::from_be_bytes(constraint),
+            level_idc: r.u8()?,
+        };
+        let min_spatial_segmentation = r.u16()? & 0x0fff;
+        let parallelism_type = r.u8()? & 0b11;
+        let chroma_format = r.u8()? & 0b11;
+        let bit_depth_luma = (r.u8()? & 0b111) + 8;
+        let bit_depth_chroma = (r.u8()? & 0b111) + 8;
+        let avg_frame_rate = r.u16()?;
+        let b = r.u8()?;
+        let nal_unit_len = (b & 0b11) + 1;
+        if nal_unit_len == 3 {
+            return Err(Error::Invalid("hvcC length size"));
+        }
+        let n = r.u8()?;
+        let mut arrays = Vec::with_capacity(n as usize);
+        for _ in 0..n {
+            let t = r.u8()?;
+            let count = r.u16()?;
+            let units = (0..count)
+                .map(|_| {
+                    let len = r.u16()? as usize;
+                    Ok(r.bytes(len)?.to_vec())
+                })
+                .collect::<Result<_>>()?;
+            arrays.push(NalArray {
+                complete: t & 0x80 != 0,
+                nal_type: t & 0x3f,
+                units,
+            });
+        }
+        Ok(Self {
+            ptl,
+            min_spatial_segmentation,
+            parallelism_type,
+            chroma_format,
+            bit_depth_luma,
+            bit_depth_chroma,
+            avg_frame_rate,
+            constant_frame_rate: b >> 6,
+            num_temporal_layers: (b >> 3) & 0b111,
+            temporal_id_nested: b & 0b100 != 0,
+            nal_unit_len,
+            arrays,
+        })
+    }
+
+    /// Builds record from VPS, SPS and PPS NAL units, profile fields come from the first SPS.
+    pub fn with_param_sets(
+        vps: &[&[u8]],
+        sps: &[&[u8]],
+        pps: &[&[u8]],
+        nal_unit_len: u8,
+    ) -> Result<Self> {
+        if !matches!(nal_unit_len, 1 | 2 | 4) {
+            return Err(Error::Invalid("nal unit length"));
+        }
+        let Some(first) = sps.first() else {
+            return Err(Error::Invalid("no sps"));
+        };
+        let parsed = Sps::parse(first)?;
+        let avg_frame_rate = parsed
+            .frame_rate()
+            .map_or(0, |fps| (fps * 256.0).round().min(u16::MAX as f64) as u16);
+        let array = |nal_type, units: &[&[u8]]| NalArray {
+            complete: true,
+            nal_type,
+            units: units.iter().map(|v| v.to_vec()).collect(),
+        };
+        Ok(Self {
+            ptl: parsed.ptl,
+            min_spatial_segmentation: 0,
+            parallelism_type: 0,
+            chroma_format: parsed.chroma_format_idc,
+            bit_depth_luma: parsed.bit_depth_luma,
+            bit_depth_chroma: parsed.bit_depth_chroma,
+            avg_frame_rate,
+            constant_frame_rate: 0,
+            num_temporal_layers: parsed.max_sub_layers,
+            temporal_id_nested: parsed.temporal_id_nesting,
+            nal_unit_len,
+            arrays: vec![
+                array(NAL_VPS, vps),
+                array(NAL_SPS, sps),
+                array(NAL_PPS, pps),
+            ],
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let ptl = &self.ptl;
+        let mut buf = Vec::with_capacity(64);
+        buf.push(1);
+        buf.push((ptl.profile_space << 6) | ((ptl.tier as u8) << 5) | ptl.profile_idc);
+        buf.extend_from_slice(&ptl.compat_flags.to_be_bytes());
+        buf.extend_from_slice(&ptl.constraint_flags.to_be_bytes()[2..]);
+        buf.push(ptl.level_idc);
+        buf.extend_from_slice(&(0xf000 | self.min_spatial_segmentation).to_be_bytes());
+        buf.push(0b1111_1100 | self.parallelism_type);
+        buf.push(0b1111_1100 | self.chroma_format);
+        buf.push(0b1111_1000 | (self.bit_depth_luma - 8));
+        buf.push(0b1111_1000 | (self.bit_depth_chroma - 8));
+        buf.extend_from_slice(&self.avg_frame_rate.to_be_bytes());
+        buf.push(
+            (self.constant_frame_rate << 6)
+                | (self.num_temporal_layers << 3)
+                | ((self.temporal_id_nested as u8) << 2)
+                | (self.nal_unit_len - 1),
+        );
+        buf.push(self.arrays.len() as u8);
+        for array in &self.arrays {
+            buf.push(((array.complete as u8) << 7) | array.nal_type);
+            buf.extend_from_slice(&(array.units.len() as u16).to_be_bytes());
+            for unit in &array.units {
+                buf.extend_from_slice(&(unit.len() as u16).to_be_bytes());
+                buf.extend_from_slice(unit);
+            }
+        }
+        buf
+    }
+
+    /// NAL units of `nal_type`
+    pub fn units(&self, nal_type: u8) -> impl Iterator<Item = &[u8]> {
+        self.arrays
+            .iter()
+            .filter(move |a| a.nal_type == nal_type)
+            .flat_map(|a| a.units.iter().map(Vec::as_slice))
+    }
+
+    /// First SPS decoded
+    pub fn first_sps(&self) -> Result<Sps> {
+        match self.units(NAL_SPS).next() {
+            Some(sps) => Sps::parse(sps),
+            None => Err(Error::Invalid("no sps")),
+        }
+    }
+
+    /// RFC 6381 codec string, `hvc1.2.4.L153.B0`
+    pub fn codec_string(&self) -> String {
+        self.ptl.codec_string("hvc1")
+    }
+
+    /// Codec string for other sample entry types like `hev1`
+    pub fn codec_string_for(&self, entry: &str) -> String {
+        self.ptl.codec_string(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        Error,
+        hevc::{self, Config, Pps, Ptl, Sps, Vps},
+        vui::Color,
+    };
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // x265 Main 1920x1080 30fps bt709
+    const VPS: &str = "40010c01ffff016000000300900000030000030078959809";
+    const SPS: &str =
+        "420101016000000300900000030000030078a003c08010e596566924cae6a0202020800001f400003a9804";
+    const PPS: &str = "4401c172b46240";
+
+    // x265 Main 4:4:4 1280x720 29.97 full range
+    const SPS_444: &str =
+        "4201010408000003009e0800000300005d9000501005a2cb2b3492657360400000fa40001d4c02";
+
+    #[test]
+    fn sps() {
+        let sps = Sps::parse(&hex(SPS)).unwrap();
+        assert_eq!(sps.ptl.profile_idc, 1);
+        assert_eq!(sps.ptl.level_idc, 120);
+        assert_eq!(sps.chroma_format_idc, 1);
+        assert_eq!((sps.pic_width, sps.pic_height), (1920, 1080));
+        assert_eq!(sps.conf_window, [0; 4]);
+        assert_eq!((sps.width(), sps.height()), (1920, 1080));
+        let padded = Sps {
+            pic_height: 1088,
+            conf_window: [0, 0, 0, 4],
+            ..sps.clone()
+        };
+        assert_eq!(padded.height(), 1080);
+        assert_eq!(sps.bit_depth_luma, 8);
+        let vui = sps.vui.unwrap();
+        let color = Color {
+            primaries: 1,
+            transfer: 1,
+            matrix: 1,
+        };
+        assert_eq!(vui.color, Some(color));
+        assert!(!vui.full_range);
+        assert_eq!(sps.frame_rate(), Some(30.0));
+        assert_eq!(sps.codec_string(), "hvc1.1.6.L120.90");
+
+        let sps = Sps::parse(&hex(SPS_444)).unwrap();
+        assert_eq!(sps.ptl.profile_idc, 4);
+        assert_eq!(sps.chroma_format_idc, 3);
+        assert_eq!((sps.width(), sps.height()), (1280, 720));
+        assert!(sps.vui.unwrap().full_range);
+        assert!((sps.frame_rate().unwrap() - 29.97).abs() < 0.001);
+        assert_eq!(sps.codec_string(), "hvc1.4.10.L93.9E.8");
+
+        assert_eq!(
+            Sps::parse(&hex(PPS)),
+            Err(Error::Invalid("unexpected nal unit type"))
+        );
+    }
+
+    #[test]
+    fn vps_pps() {
+        let vps = Vps::parse(&hex(VPS)).unwrap();
+        assert_eq!(vps.id, 0);
+        assert_eq!(vps.max_sub_layers, 1);
+        assert_eq!(vps.ptl, Sps::parse(&hex(SPS)).unwrap().ptl);
+        assert_eq!(vps.timing, None);
+
+        let pps = Pps::parse(&hex(PPS)).unwrap();
+        assert_eq!((pps.id, pps.sps_id), (0, 0));
+        assert_eq!(hevc::nal_type(&hex(PPS)), Some(hevc::NAL_PPS));
+    }
+
+    #[test]
+    fn codec_string() {
+        let ptl = Ptl {
+            profile_space: 0,
+            tier: false,
+            profile_idc: 2,
+            compat_flags: 0x2000_0000,
+            constraint_flags: 0xb000_0000_0000,
+            level_idc: 153,
+        };
+        assert_eq!(ptl.codec_string("hvc1"), "hvc1.2.4.L153.B0");
+        let ptl = Ptl {
+            profile_space: 1,
+            tier: true,
+            constraint_flags: 0,
+            ..ptl
+        };
+        assert_eq!(ptl.codec_string("hev1"), "hev1.A2.4.H153");
+    }
+
+    #[test]
+    fn config() {
+        let (vps, sps, pps) = (hex(VPS), hex(SPS), hex(PPS));
+        let config = Config::with_param_sets(&[&vps], &[&sps], &[&pps], 4).unwrap();
+        assert_eq!(config.codec_string(), "hvc1.1.6.L120.90");
+        assert_eq!(config.codec_string_for("hev1"), "hev1.1.6.L120.90");
+        assert_eq!(config.avg_frame_rate, 30 * 256);
+        assert_eq!(config.num_temporal_layers, 1);
+        assert!(config.temporal_id_nested);
+
+        let bytes = config.to_bytes();
+        assert_eq!(bytes.len(), 23 + 3 * 5 + vps.len() + sps.len() + pps.len());
+        assert_eq!(&bytes[..2], &[1, 0x01]);
+        assert_eq!(bytes[21], 0b0000_1111);
+        let parsed = Config::parse(&bytes).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.units(hevc::NAL_PPS).next(), Some(&pps[..]));
+        assert_eq!(parsed.first_sps().unwrap().height(), 1080);
+
+        assert_eq!(Config::parse(&bytes[..30]), Err(Error::Eof));
+        assert!(Config::with_param_sets(&[&vps], &[], &[&pps], 4).is_err());
+    }
+}
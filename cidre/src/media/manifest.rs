@@ -0,0 +1,352 @@
+//! HLS playlists and DASH MPD for fragmented MP4 segments
+
+use std::fmt;
+
+use super::{Result, mp4::Track};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HlsSegment {
+    pub uri: String,
+    /// Seconds
+    pub duration: f64,
+}
+
+/// HLS media playlist with `EXT-X-MAP` init segment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HlsPlaylist {
+    pub init_uri: String,
+    /// Lower bound of `EXT-X-TARGETDURATION`, it grows to fit the longest segment.
+    pub target_duration: u32,
+    pub media_sequence: u64,
+    pub segments: Vec<HlsSegment>,
+    /// Writes `EXT-X-ENDLIST`
+    pub ended: bool,
+}
+
+impl HlsPlaylist {
+    pub fn new(init_uri: impl Into<String>, target_duration: u32) -> Self {
+        Self {
+            init_uri: init_uri.into(),
+            target_duration,
+            media_sequence: 0,
+            segments: Vec::new(),
+            ended: false,
+        }
+    }
+
+    pub fn push(&mut self, uri: impl Into<String>, duration: f64) {
+        self.segments.push(HlsSegment {
+            uri: uri.into(),
+            duration,
+        });
+    }
+
+    /// Drops oldest segments over `max` for sliding window live playlists.
+    pub fn truncate_front(&mut self, max: usize) {
+        let n = self.segments.len().saturating_sub(max);
+        self.segments.drain(..n);
+        self.media_sequence += n as u64;
+    }
+
+    pub fn effective_target_duration(&self) -> u32 {
+        self.segments
+            .iter()
+            .map(|s| s.duration.round() as u32)
+            .fold(self.target_duration.max(1), u32::max)
+    }
+}
+
+impl fmt::Display for HlsPlaylist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#EXTM3U")?;
+        writeln!(f, "#EXT-X-VERSION:7")?;
+        writeln!(
+            f,
+            "#EXT-X-TARGETDURATION:{}",
+            self.effective_target_duration()
+        )?;
+        writeln!(f, "#EXT-X-MEDIA-SEQUENCE:{}", self.media_sequence)?;
+        writeln!(f, "#EXT-X-INDEPENDENT-SEGMENTS")?;
+        writeln!(f, "#EXT-X-MAP:URI=\"{}\"", self.init_uri)?;
+        for segment in &self.segments {
+            writeln!(f, "#EXTINF:{:.5},", segment.duration)?;
+            writeln!(f, "{}", segment.uri)?;
+        }
+        if self.ended {
+            writeln!(f, "#EXT-X-ENDLIST")?;
+        }
+        Ok(())
+    }
+}
+
+/// `EXT-X-STREAM-INF` entry of multivariant playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HlsVariant {
+    pub uri: String,
+    /// Peak bits per second
+    pub bandwidth: u32,
+    /// Codec strings of all tracks, see [`super::mp4::SampleEntry::codec_string`]
+    pub codecs: Vec<String>,
+    pub resolution: Option<(u16, u16)>,
+    pub frame_rate: Option<f64>,
+}
+
+/// HLS multivariant playlist
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HlsMultivariant {
+    pub variants: Vec<HlsVariant>,
+}
+
+impl fmt::Display for HlsMultivariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#EXTM3U")?;
+        writeln!(f, "#EXT-X-VERSION:7")?;
+        writeln!(f, "#EXT-X-INDEPENDENT-SEGMENTS")?;
+        for v in &self.variants {
+            write!(
+                f,
+                "#EXT-X-STREAM-INF:BANDWIDTH={},CODECS=\"{}\"",
+                v.bandwidth,
+                v.codecs.join(",")
+            )?;
+            if let Some((width, height)) = v.resolution {
+                write!(f, ",RESOLUTION={width}x{height}")?;
+            }
+            if let Some(fps) = v.frame_rate {
+                write!(f, ",FRAME-RATE={fps:.3}")?;
+            }
+            writeln!(f)?;
+            writeln!(f, "{}", v.uri)?;
+        }
+        Ok(())
+    }
+}
+
+/// DASH representation addressed with `SegmentTemplate` and `SegmentTimeline`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashRepresentation {
+    pub id: String,
+    pub codecs: String,
+    pub bandwidth: u32,
+    pub dimensions: Option<(u16, u16)>,
+    /// `(channels, sample_rate)`
+    pub audio: Option<(u16, u32)>,
+    pub timescale: u32,
+    pub init: String,
+    /// Template with `$Number$`
+    pub media: String,
+    pub start_number: u64,
+    /// Segment durations in `timescale` units
+    pub segments: Vec<u64>,
+}
+
+impl DashRepresentation {
+    pub fn with_track(
+        track: &Track,
+        bandwidth: u32,
+        init: impl Into<String>,
+        media: impl Into<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            id: track.id.to_string(),
+            codecs: track.entry.codec_string()?,
+            bandwidth,
+            dimensions: track.entry.dimensions(),
+            audio: track.entry.audio(),
+            timescale: track.timescale,
+            init: init.into(),
+            media: media.into(),
+            start_number: 1,
+            segments: Vec::new(),
+        })
+    }
+
+    /// Seconds
+    pub fn duration(&self) -> f64 {
+        self.segments.iter().sum::<u64>() as f64 / self.timescale.max(1) as f64
+    }
+}
+
+/// Static DASH manifest (`isoff-live` profile), one adaptation set per representation.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DashMpd {
+    pub representations: Vec<DashRepresentation>,
+    /// Seconds
+    pub min_buffer_time: f64,
+}
+
+impl fmt::Display for DashMpd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let duration = self
+            .representations
+            .iter()
+            .map(DashRepresentation::duration)
+            .fold(0.0, f64::max);
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            f,
+            r#"<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" profiles="urn:mpeg:dash:profile:isoff-live:2011" type="static" mediaPresentationDuration="PT{duration:.3}S" minBufferTime="PT{:.3}S">"#,
+            self.min_buffer_time
+        )?;
+        writeln!(f, "  <Period>")?;
+        for r in &self.representations {
+            let (kind, attrs) = match (r.dimensions, r.audio) {
+                (Some((width, height)), _) => {
+                    ("video", format!(r#" width="{width}" height="{height}""#))
+                }
+                (None, Some((_, rate))) => ("audio", format!(r#" audioSamplingRate="{rate}""#)),
+                (None, None) => ("application", String::new()),
+            };
+            writeln!(
+                f,
+                r#"    <AdaptationSet contentType="{kind}" mimeType="{kind}/mp4" segmentAlignment="true">"#
+            )?;
+            writeln!(
+                f,
+                r#"      <Representation id="{}" codecs="{}" bandwidth="{}"{attrs}>"#,
+                xml_escape(&r.id),
+                xml_escape(&r.codecs),
+                r.bandwidth
+            )?;
+            if let Some((channels, _)) = r.audio {
+                writeln!(
+                    f,
+                    r#"        <AudioChannelConfiguration schemeIdUri="urn:mpeg:dash:23003:3:audio_channel_configuration:2011" value="{channels}"/>"#
+                )?;
+            }
+            writeln!(
+                f,
+                r#"        <SegmentTemplate timescale="{}" initialization="{}" media="{}" startNumber="{}">"#,
+                r.timescale,
+                xml_escape(&r.init),
+                xml_escape(&r.media),
+                r.start_number
+            )?;
+            writeln!(f, "          <SegmentTimeline>")?;
+            let mut i = 0;
+            while i < r.segments.len() {
+                let d = r.segments[i];
+                let repeat = r.segments[i + 1..].iter().take_while(|x| **x == d).count();
+                if i == 0 {
+                    write!(f, r#"            <S t="0" d="{d}""#)?;
+                } else {
+                    write!(f, r#"            <S d="{d}""#)?;
+                }
+                if repeat > 0 {
+                    write!(f, r#" r="{repeat}""#)?;
+                }
+                writeln!(f, "/>")?;
+                i += repeat + 1;
+            }
+            writeln!(f, "          </SegmentTimeline>")?;
+            writeln!(f, "        </SegmentTemplate>")?;
+            writeln!(f, "      </Representation>")?;
+            writeln!(f, "    </AdaptationSet>")?;
+        }
+        writeln!(f, "  </Period>")?;
+        writeln!(f, "</MPD>")
+    }
+}
+
+fn xml_escape(s: &str) -> std::borrow::Cow<'_, str> {
+    if !s.contains(['&', '<', '>', '"']) {
+        return s.into();
+    }
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        manifest::{DashMpd, DashRepresentation, HlsMultivariant, HlsPlaylist, HlsVariant},
+        mp4::{SampleEntry, Track},
+    };
+
+    #[test]
+    fn hls() {
+        let mut playlist = HlsPlaylist::new("init.mp4", 2);
+        playlist.push("seg0.m4s", 2.002);
+        playlist.push("seg1.m4s", 2.6);
+        playlist.push("seg2.m4s", 1.0);
+        playlist.truncate_front(2);
+        playlist.ended = true;
+        assert_eq!(
+            playlist.to_string(),
+            "#EXTM3U
+#EXT-X-VERSION:7
+#EXT-X-TARGETDURATION:3
+#EXT-X-MEDIA-SEQUENCE:1
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-MAP:URI=\"init.mp4\"
+#EXTINF:2.60000,
+seg1.m4s
+#EXTINF:1.00000,
+seg2.m4s
+#EXT-X-ENDLIST
+"
+        );
+
+        let multivariant = HlsMultivariant {
+            variants: vec![HlsVariant {
+                uri: "video.m3u8".to_string(),
+                bandwidth: 2_000_000,
+                codecs: vec!["avc1.640028".to_string(), "mp4a.40.2".to_string()],
+                resolution: Some((1920, 1080)),
+                frame_rate: Some(29.97),
+            }],
+        };
+        assert_eq!(
+            multivariant.to_string(),
+            "#EXTM3U
+#EXT-X-VERSION:7
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,CODECS=\"avc1.640028,mp4a.40.2\",RESOLUTION=1920x1080,FRAME-RATE=29.970
+video.m3u8
+"
+        );
+    }
+
+    #[test]
+    fn dash() {
+        let track = Track::new(
+            2,
+            48_000,
+            SampleEntry::Aac {
+                channels: 2,
+                sample_rate: 48_000,
+                config: vec![0x11, 0x90],
+            },
+        );
+        let mut audio =
+            DashRepresentation::with_track(&track, 128_000, "a.mp4", "a$Number$.m4s").unwrap();
+        audio.segments = vec![96_000, 96_000, 96_000, 48_000];
+        let mpd = DashMpd {
+            representations: vec![audio],
+            min_buffer_time: 2.0,
+        };
+        assert_eq!(
+            mpd.to_string(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" profiles="urn:mpeg:dash:profile:isoff-live:2011" type="static" mediaPresentationDuration="PT7.000S" minBufferTime="PT2.000S">
+  <Period>
+    <AdaptationSet contentType="audio" mimeType="audio/mp4" segmentAlignment="true">
+      <Representation id="2" codecs="mp4a.40.2" bandwidth="128000" audioSamplingRate="48000">
+        <AudioChannelConfiguration schemeIdUri="urn:mpeg:dash:23003:3:audio_channel_configuration:2011" value="2"/>
+        <SegmentTemplate timescale="48000" initialization="a.mp4" media="a$Number$.m4s" startNumber="1">
+          <SegmentTimeline>
+            <S t="0" d="96000" r="2"/>
+            <S d="48000"/>
+          </SegmentTimeline>
+        </SegmentTemplate>
+      </Representation>
+    </AdaptationSet>
+  </Period>
+</MPD>
+"#
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! ISO base media file format (MP4, CMAF)
+
+use super::{Error, Result, avc, bits::ByteReader, hevc};
+
+pub mod demux;
+pub mod fmp4;
+
+pub type FourCc = [u8; 4];
+
+/// Codec configuration of a track, the `stsd` entry.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SampleEntry {
+    /// `avc1` with `avcC` payload
+    Avc {
+        width: u16,
+        height: u16,
+        avcc: Vec<u8>,
+    },
+    /// `hvc1` with `hvcC` payload
+    Hevc {
+        width: u16,
+        height: u16,
+        hvcc: Vec<u8>,
+    },
+    /// `mp4a` with AudioSpecificConfig
+    Aac {
+        channels: u16,
+        sample_rate: u32,
+        config: Vec<u8>,
+    },
+    /// `Opus`, always 48 kHz
+    Opus {
+        channels: u16,
+        pre_skip: u16,
+        /// Sample rate of the original input, informational
+        input_sample_rate: u32,
+    },
+}
+
+impl SampleEntry {
+    pub fn fourcc(&self) -> FourCc {
+        match self {
+            Self::Avc { .. } => *b"avc1",
+            Self::Hevc { .. } => *b"hvc1",
+            Self::Aac { .. } => *b"mp4a",
+            Self::Opus { .. } => *b"Opus",
+        }
+    }
+
+    #[inline]
+    pub fn is_video(&self) -> bool {
+        matches!(self, Self::Avc { .. } | Self::Hevc { .. })
+    }
+
+    /// `(width, height)` of video entries
+    pub fn dimensions(&self) -> Option<(u16, u16)> {
+        match self {
+            Self::Avc { width, height, .. } | Self::Hevc { width, height, .. } => {
+                Some((*width, *height))
+            }
+            _ => None,
+        }
+    }
+
+    /// `(channels, sample_rate)` of audio entries
+    pub fn audio(&self) -> Option<(u16, u32)> {
+        match self {
+            Self::Aac {
+                channels,
+                sample_rate,
+                ..
+            } => Some((*channels, *sample_rate)),
+            Self::Opus { channels, .. } => Some((*channels, 48_000)),
+            _ => None,
+        }
+    }
+
+    /// RFC 6381 codec string for HLS `CODECS` and DASH `codecs` attributes.
+    pub fn codec_string(&self) -> Result<String> {
+        Ok(match self {
+            Self::Avc { avcc, .. } => avc::Config::parse(avcc)?.codec_string(),
+            Self::Hevc { hvcc, .. } => hevc::Config::parse(hvcc)?.codec_string(),
+            Self::Aac { config, .. } => match config.first() {
+                Some(b) => format!("mp4a.40.{}", b >> 3),
+                None => return Err(Error::Eof),
+            },
+            Self::Opus { .. } => "opus".to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Track {
+    pub id: u32,
+    /// Units per second of sample timing
+    pub timescale: u32,
+    /// ISO 639-2/T code, `und` if unknown
+    pub language: [u8; 3],
+    pub entry: SampleEntry,
+}
+
+impl Track {
+    pub fn new(id: u32, timescale: u32, entry: SampleEntry) -> Self {
+        Self {
+            id,
+            timescale,
+            language: *b"und",
+            entry,
+        }
+    }
+}
+
+/// Timing, size and sync flag of one sample in track timescale.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Sample {
+    pub dts: i64,
+    pub pts: i64,
+    pub duration: u32,
+    pub size: u32,
+    pub is_sync: bool,
+}
+
+impl Sample {
+    /// `pts - dts`
+    #[inline]
+    pub fn cts_offset(&self) -> i64 {
+        self.pts - self.dts
+    }
+}
+
+/// Serializes boxes, sizes are patched when a box closes.
+#[derive(Debug)]
+pub(crate) struct BoxWriter<'a> {
+    pub buf: &'a mut Vec<u8>,
+}
+
+impl<'a> BoxWriter<'a> {
+    pub fn new(buf: &'a mut Vec<u8>) -> Self {
+        Self { buf }
+    }
+
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn bx(&mut self, typ: &FourCc, f: impl FnOnce(&mut Self)) {
+        let start = self.buf.len();
+        self.u32(0);
+        self.bytes(typ);
+        f(self);
+        let size = (self.buf.len() - start) as u32;
+        self.buf[start..start + 4].copy_from_slice(&size.to_be_bytes());
+    }
+
+    /// Full box with `version` and 24 bit `flags`
+    pub fn full(&mut self, typ: &FourCc, version: u8, flags: u32, f: impl FnOnce(&mut Self)) {
+        self.bx(typ, |w| {
+            w.u32(((version as u32) << 24) | (flags & 0xff_ffff));
+            f(w)
+        })
+    }
+
+    #[inline]
+    pub fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    #[inline]
+    pub fn u16(&mut self, v: u16) {
+        self.bytes(&v.to_be_bytes());
+    }
+
+    #[inline]
+    pub fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_be_bytes());
+    }
+
+    #[inline]
+    pub fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_be_bytes());
+    }
+
+    #[inline]
+    pub fn bytes(&mut self, v: &[u8]) {
+        self.buf.extend_from_slice(v);
+    }
+
+    #[inline]
+    pub fn zeros(&mut self, n: usize) {
+        self.buf.resize(self.buf.len() + n, 0);
+    }
+
+    pub fn set_u32(&mut self, pos: usize, v: u32) {
+        self.buf[pos..pos + 4].copy_from_slice(&v.to_be_bytes());
+    }
+
+    /// MPEG-4 descriptor (ISO/IEC 14496-1) with expandable size
+    pub fn descriptor(&mut self, tag: u8, f: impl FnOnce(&mut BoxWriter<'_>)) {
+        let mut body = Vec::new();
+        f(&mut BoxWriter::new(&mut body));
+        self.u8(tag);
+        let len = body.len();
+        for shift in [21, 14, 7] {
+            if len >> shift != 0 {
+                self.u8(0x80 | (len >> shift) as u8 & 0x7f);
+            }
+        }
+        self.u8(len as u8 & 0x7f);
+        self.bytes(&body);
+    }
+
+    /// AAC `ES_Descriptor` around AudioSpecificConfig
+    pub fn es_descriptor(&mut self, asc: &[u8]) {
+        self.descriptor(0x03, |w| {
+            // ES_ID, flags
+            w.u16(0);
+            w.u8(0);
+            w.descriptor(0x04, |w| {
+                // Audio ISO/IEC 14496-3, AudioStream
+                w.u8(0x40);
+                w.u8(0x15);
+                // bufferSizeDB, maxBitrate, avgBitrate
+                w.zeros(11);
+                w.descriptor(0x05, |w| w.bytes(asc));
+            });
+            // SLConfigDescriptor, predefined MP4
+            w.descriptor(0x06, |w| w.u8(0x02));
+        });
+    }
+}
+
+/// `esds` payload or AAC magic cookie for AudioSpecificConfig
+pub fn es_descriptor(asc: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    BoxWriter::new(&mut buf).es_descriptor(asc);
+    buf
+}
+
+/// Walks `ES_Descriptor` → `DecoderConfigDescriptor` → `DecoderSpecificInfo`
+/// of `esds` payload or AAC magic cookie and returns AudioSpecificConfig.
+pub fn decoder_specific_info(es: &[u8]) -> Option<&[u8]> {
+    fn descriptor<'a>(r: &mut ByteReader<'a>) -> Result<(u8, &'a [u8])> {
+        let tag = r.u8()?;
+        let mut len = 0usize;
+        for _ in 0..4 {
+            let b = r.u8()?;
+            len = (len << 7) | (b & 0x7f) as usize;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok((tag, r.bytes(len)?))
+    }
+
+    fn find(data: &[u8], tag: u8) -> Option<&[u8]> {
+        let mut r = ByteReader::new(data);
+        while let Ok((t, body)) = descriptor(&mut r) {
+            if t == tag {
+                return Some(body);
+            }
+        }
+        None
+    }
+
+    let es_desc = find(es, 0x03)?;
+    let mut r = ByteReader::new(es_desc);
+    // ES_ID
+    r.u16().ok()?;
+    let flags = r.u8().ok()?;
+    if flags & 0x80 != 0 {
+        r.u16().ok()?;
+    }
+    if flags & 0x40 != 0 {
+        let n = r.u8().ok()?;
+        r.bytes(n as usize).ok()?;
+    }
+    if flags & 0x20 != 0 {
+        r.u16().ok()?;
+    }
+    let config = find(r.remaining(), 0x04)?;
+    // objectTypeIndication, streamType, bufferSizeDB, maxBitrate, avgBitrate
+    find(config.get(13..)?, 0x05)
+}
+
+/// Pre-skip and input sample rate of an `OpusHead` identification header,
+/// which is also the Opus magic cookie of AudioToolbox.
+pub fn opus_head(head: &[u8]) -> Option<(u16, u32)> {
+    let head = head.strip_prefix(b"OpusHead")?;
+    // version, channel count
+    let pre_skip = u16::from_le_bytes(head.get(2..4)?.try_into().ok()?);
+    let input_sample_rate = u32::from_le_bytes(head.get(4..8)?.try_into().ok()?);
+    Some((pre_skip, input_sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::mp4::{self, BoxWriter, SampleEntry};
+
+    #[test]
+    fn box_writer() {
+        let mut buf = Vec::new();
+        let mut w = BoxWriter::new(&mut buf);
+        w.bx(b"moov", |w| {
+            w.full(b"mvhd", 1, 0x0a0b0c, |w| w.u16(7));
+        });
+        assert_eq!(
+            buf,
+            [
+                0, 0, 0, 22, b'm', b'o', b'o', b'v', //
+                0, 0, 0, 14, b'm', b'v', b'h', b'd', 1, 0x0a, 0x0b, 0x0c, 0, 7
+            ]
+        );
+    }
+
+    #[test]
+    fn descriptors() {
+        let asc = [0x12, 0x10];
+        let mut buf = Vec::new();
+        let mut w = BoxWriter::new(&mut buf);
+        w.descriptor(0x03, |w| {
+            w.u16(1);
+            w.u8(0);
+            w.descriptor(0x04, |w| {
+                w.u8(0x40);
+                w.u8(0x15);
+                w.zeros(11);
+                w.descriptor(0x05, |w| w.bytes(&asc));
+            });
+        });
+        assert_eq!(&buf[..2], &[0x03, 22]);
+        assert_eq!(mp4::decoder_specific_info(&buf), Some(&asc[..]));
+
+        // 4 byte sizes as written by some encoders
+        let long = [
+            0x03, 0x80, 0x80, 0x80, 0x1c, 0, 0, 0, 0x04, 0x80, 0x80, 0x80, 0x14, 0x40, 0x15, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0x05, 0x80, 0x80, 0x80, 0x02, 0x12, 0x10,
+        ];
+        assert_eq!(mp4::decoder_specific_info(&long), Some(&asc[..]));
+        assert_eq!(mp4::decoder_specific_info(&long[..20]), None);
+
+        let mut big = Vec::new();
+        BoxWriter::new(&mut big).descriptor(0x05, |w| w.zeros(200));
+        assert_eq!(&big[..3], &[0x05, 0x81, 0x48]);
+    }
+
+    #[test]
+    fn opus_head() {
+        let head = [
+            b'O', b'p', b'u', b's', b'H', b'e', b'a', b'd', 1, 2, 0x38, 0x01, 0x80, 0xbb, 0, 0, 0,
+            0, 0,
+        ];
+        assert_eq!(mp4::opus_head(&head), Some((312, 48_000)));
+        assert_eq!(mp4::opus_head(&head[..12]), None);
+        assert_eq!(mp4::opus_head(b"OpusTags"), None);
+    }
+
+    #[test]
+    fn codec_strings() {
+        let aac = SampleEntry::Aac {
+            channels: 2,
+            sample_rate: 44100,
+            config: vec![0x12, 0x10],
+        };
+        assert_eq!(aac.codec_string().unwrap(), "mp4a.40.2");
+        assert_eq!(aac.audio(), Some((2, 44100)));
+        assert!(!aac.is_video());
+        let opus = SampleEntry::Opus {
+            channels: 1,
+            pre_skip: 312,
+            input_sample_rate: 16000,
+        };
+        assert_eq!(opus.codec_string().unwrap(), "opus");
+        assert_eq!(opus.audio(), Some((1, 48_000)));
+        assert_eq!(&opus.fourcc(), b"Opus");
+    }
+}
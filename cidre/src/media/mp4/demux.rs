@@ -0,0 +1,974 @@
+//! MP4 and QuickTime demuxer
+//!
+//! Sample timing comes from sample tables of `moov` and from `moof` fragments.
+//! Edit lists are not applied.
+
+use crate::media::{Error, Result, bits::ByteReader};
+
+use super::{FourCc, Sample, SampleEntry};
+
+/// Box with its absolute offset.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BoxRef<'a> {
+    pub typ: FourCc,
+    /// Offset of box header
+    pub offset: u64,
+    /// Size including header
+    pub size: u64,
+    pub body: &'a [u8],
+}
+
+impl<'a> BoxRef<'a> {
+    pub fn children(&self) -> Boxes<'a> {
+        let header_len = self.size - self.body.len() as u64;
+        Boxes::new(self.body, self.offset + header_len)
+    }
+
+    /// First child of type, malformed children are skipped.
+    pub fn child(&self, typ: &FourCc) -> Option<BoxRef<'a>> {
+        self.children()
+            .map_while(Result::ok)
+            .find(|b| &b.typ == typ)
+    }
+}
+
+/// Sibling boxes, stops after the first error.
+#[derive(Debug, Clone)]
+pub struct Boxes<'a> {
+    data: &'a [u8],
+    base: u64,
+    pos: usize,
+}
+
+impl<'a> Boxes<'a> {
+    /// `base` is offset of `data` in the file.
+    pub fn new(data: &'a [u8], base: u64) -> Self {
+        Self { data, base, pos: 0 }
+    }
+
+    fn parse(&mut self) -> Result<BoxRef<'a>> {
+        let rest = &self.data[self.pos..];
+        let mut r = ByteReader::new(rest);
+        let size = r.u32()?;
+        let typ = r.array()?;
+        let (size, header_len) = match size {
+            0 => (rest.len() as u64, 8),
+            1 => (r.u64()?, 16),
+            n => (n as u64, 8),
+        };
+        if size < header_len as u64 {
+            return Err(Error::Invalid("box size"));
+        }
+        if size > rest.len() as u64 {
+            return Err(Error::Eof);
+        }
+        let res = BoxRef {
+            typ,
+            offset: self.base + self.pos as u64,
+            size,
+            body: &rest[header_len..size as usize],
+        };
+        self.pos += size as usize;
+        Ok(res)
+    }
+}
+
+impl<'a> Iterator for Boxes<'a> {
+    type Item = Result<BoxRef<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.data.len() {
+            return None;
+        }
+        let res = self.parse();
+        if res.is_err() {
+            self.pos = self.data.len();
+        }
+        Some(res)
+    }
+}
+
+/// Version, flags and the rest of full box.
+fn full_box<'a>(b: &BoxRef<'a>) -> Result<(u8, u32, ByteReader<'a>)> {
+    let mut r = ByteReader::new(b.body);
+    let v = r.u32()?;
+    Ok(((v >> 24) as u8, v & 0xff_ffff, r))
+}
+
+fn required<'a>(parent: &BoxRef<'a>, typ: &FourCc) -> Result<BoxRef<'a>> {
+    parent.child(typ).ok_or(Error::Invalid("missing box"))
+}
+
+/// Sample with its offset in the file.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SampleRef {
+    pub offset: u64,
+    pub sample: Sample,
+}
+
+/// `trex` defaults
+#[derive(Debug, Default, Copy, Clone)]
+struct Defaults {
+    duration: u32,
+    size: u32,
+    flags: u32,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Tables {
+    /// sample_count, sample_delta
+    stts: Vec<(u32, u32)>,
+    /// sample_count, sample_offset
+    ctts: Vec<(u32, i32)>,
+    sample_size: u32,
+    sizes: Vec<u32>,
+    count: u32,
+    /// first_chunk, samples_per_chunk
+    stsc: Vec<(u32, u32)>,
+    chunks: Vec<u64>,
+    stss: Option<Vec<u32>>,
+}
+
+impl Tables {
+    fn parse(stbl: &BoxRef) -> Result<Self> {
+        let mut res = Self::default();
+        for b in stbl.children() {
+            let b = b?;
+            match &b.typ {
+                b"stts" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    let n = r.u32()?;
+                    res.stts = (0..n)
+                        .map(|_| Ok((r.u32()?, r.u32()?)))
+                        .collect::<Result<_>>()?;
+                }
+                b"ctts" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    let n = r.u32()?;
+                    res.ctts = (0..n)
+                        .map(|_| Ok((r.u32()?, r.u32()? as i32)))
+                        .collect::<Result<_>>()?;
+                }
+                b"stsz" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    res.sample_size = r.u32()?;
+                    res.count = r.u32()?;
+                    if res.sample_size == 0 {
+                        res.sizes = (0..res.count).map(|_| r.u32()).collect::<Result<_>>()?;
+                    }
+                }
+                b"stz2" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    let field_size = r.u32()? & 0xff;
+                    res.count = r.u32()?;
+                    let n = res.count as usize;
+                    let data = r.remaining();
+                    res.sizes = match field_size {
+                        4 => (0..n)
+                            .map(|i| data.get(i / 2).map(|b| (b >> (4 - i % 2 * 4)) as u32 & 0xf))
+                            .collect::<Option<_>>(),
+                        8 => (0..n).map(|i| data.get(i).map(|b| *b as u32)).collect(),
+                        16 => (0..n)
+                            .map(|i| {
+                                Some(u16::from_be_bytes(
+                                    data.get(i * 2..i * 2 + 2)?.try_into().ok()?,
+                                ) as u32)
+                            })
+                            .collect(),
+                        _ => return Err(Error::Invalid("stz2 field size")),
+                    }
+                    .ok_or(Error::Eof)?;
+                }
+                b"stsc" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    let n = r.u32()?;
+                    res.stsc = (0..n)
+                        .map(|_| {
+                            let first_chunk = r.u32()?;
+                            let samples_per_chunk = r.u32()?;
+                            // sample_description_index
+                            r.u32()?;
+                            Ok((first_chunk, samples_per_chunk))
+                        })
+                        .collect::<Result<_>>()?;
+                }
+                b"stco" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    let n = r.u32()?;
+                    res.chunks = (0..n).map(|_| Ok(r.u32()? as u64)).collect::<Result<_>>()?;
+                }
+                b"co64" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    let n = r.u32()?;
+                    res.chunks = (0..n).map(|_| r.u64()).collect::<Result<_>>()?;
+                }
+                b"stss" => {
+                    let (_, _, mut r) = full_box(&b)?;
+                    let n = r.u32()?;
+                    res.stss = Some((0..n).map(|_| r.u32()).collect::<Result<_>>()?);
+                }
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+
+    fn total_duration(&self) -> i64 {
+        self.stts
+            .iter()
+            .map(|(count, delta)| *count as i64 * *delta as i64)
+            .sum()
+    }
+}
+
+/// Track of a parsed movie.
+#[derive(Debug, Clone)]
+pub struct TrackInfo {
+    pub id: u32,
+    pub timescale: u32,
+    /// In `timescale` units, 0 for fragmented files
+    pub duration: u64,
+    /// `vide`, `soun`, ...
+    pub handler: FourCc,
+    pub language: [u8; 3],
+    /// `tkhd` presentation size
+    pub width: u32,
+    pub height: u32,
+    /// `stsd` entries as whole boxes, the shape of `cm::FormatDesc::verbatim_iso_sample_entry`
+    pub sample_entries: Vec<Vec<u8>>,
+    tables: Tables,
+    defaults: Defaults,
+    fragments: Vec<SampleRef>,
+    next_fragment_dts: i64,
+}
+
+impl TrackInfo {
+    fn parse(trak: &BoxRef) -> Result<Self> {
+        let (version, _, mut r) = full_box(&required(trak, b"tkhd")?)?;
+        // creation_time, modification_time
+        r.skip(if version == 1 { 16 } else { 8 })?;
+        let id = r.u32()?;
+        // reserved, duration, reserved, layer, alternate_group, volume, reserved, matrix
+        r.skip(if version == 1 { 12 } else { 8 } + 8 + 8 + 36)?;
+        let width = r.u32()? >> 16;
+        let height = r.u32()? >> 16;
+
+        let mdia = required(trak, b"mdia")?;
+        let (version, _, mut r) = full_box(&required(&mdia, b"mdhd")?)?;
+        r.skip(if version == 1 { 16 } else { 8 })?;
+        let timescale = r.u32()?;
+        let duration = if version == 1 {
+            r.u64()?
+        } else {
+            r.u32()? as u64
+        };
+        let lang = r.u16()?;
+        let language = [10, 5, 0].map(|shift| ((lang >> shift) & 0x1f) as u8 + 0x60);
+
+        let handler = match mdia.child(b"hdlr") {
+            Some(hdlr) => {
+                let (_, _, mut r) = full_box(&hdlr)?;
+                r.u32()?;
+                r.array()?
+            }
+            None => [0; 4],
+        };
+
+        let stbl = required(&required(&mdia, b"minf")?, b"stbl")?;
+        let stsd = required(&stbl, b"stsd")?;
+        let (_, _, mut r) = full_box(&stsd)?;
+        r.u32()?;
+        let entries = r.remaining();
+        let sample_entries = Boxes::new(entries, 0)
+            .map(|b| b.map(|b| entries[b.offset as usize..][..b.size as usize].to_vec()))
+            .collect::<Result<_>>()?;
+        let tables = Tables::parse(&stbl)?;
+
+        Ok(Self {
+            id,
+            timescale,
+            duration,
+            handler,
+            language,
+            width,
+            height,
+            sample_entries,
+            next_fragment_dts: tables.total_duration(),
+            tables,
+            defaults: Default::default(),
+            fragments: Vec::new(),
+        })
+    }
+
+    #[inline]
+    pub fn is_video(&self) -> bool {
+        &self.handler == b"vide"
+    }
+
+    #[inline]
+    pub fn is_audio(&self) -> bool {
+        &self.handler == b"soun"
+    }
+
+    /// Type of first sample entry like `avc1` or `mp4a`
+    pub fn entry_type(&self) -> Option<FourCc> {
+        self.sample_entries.first()?.get(4..8)?.try_into().ok()
+    }
+
+    /// Child boxes of first sample entry after the fixed visual or audio fields.
+    fn entry_children(&self) -> Option<(&[u8], Boxes<'_>)> {
+        let entry = self.sample_entries.first()?;
+        let body = entry.get(8..)?;
+        let fixed = if self.is_video() {
+            78
+        } else if self.is_audio() {
+            // QuickTime sound description versions
+            match u16::from_be_bytes(body.get(8..10)?.try_into().ok()?) {
+                0 => 28,
+                1 => 44,
+                2 => 64,
+                _ => return None,
+            }
+        } else {
+            return None;
+        };
+        Some((body, Boxes::new(body.get(fixed..)?, 0)))
+    }
+
+    /// Payload of `avcC`, `hvcC`, `esds`, `dOps`, ... inside of first sample entry.
+    pub fn config(&self, typ: &FourCc) -> Option<&[u8]> {
+        let (_, boxes) = self.entry_children()?;
+        boxes
+            .map_while(Result::ok)
+            .find(|b| &b.typ == typ)
+            .map(|b| b.body)
+    }
+
+    /// First sample entry decoded, `None` for unsupported codecs.
+    pub fn entry(&self) -> Option<SampleEntry> {
+        let (body, _) = self.entry_children()?;
+        let u16_at = |i: usize| Some(u16::from_be_bytes(body.get(i..i + 2)?.try_into().ok()?));
+        match &self.entry_type()? {
+            b"avc1" | b"avc3" => Some(SampleEntry::Avc {
+                width: u16_at(24)?,
+                height: u16_at(26)?,
+                avcc: self.config(b"avcC")?.to_vec(),
+            }),
+            b"hvc1" | b"hev1" => Some(SampleEntry::Hevc {
+                width: u16_at(24)?,
+                height: u16_at(26)?,
+                hvcc: self.config(b"hvcC")?.to_vec(),
+            }),
+            b"mp4a" => {
+                let (channels, sample_rate) = if u16_at(8)? == 2 {
+                    let rate = f64::from_be_bytes(body.get(32..40)?.try_into().ok()?);
+                    let channels = u32::from_be_bytes(body.get(40..44)?.try_into().ok()?);
+                    (channels as u16, rate as u32)
+                } else {
+                    (u16_at(16)?, u16_at(24)? as u32)
+                };
+                // esds is a full box
+                let esds = self.config(b"esds")?.get(4..)?;
+                Some(SampleEntry::Aac {
+                    channels,
+                    sample_rate,
+                    config: super::decoder_specific_info(esds)?.to_vec(),
+                })
+            }
+            b"Opus" => {
+                let dops = self.config(b"dOps")?;
+                Some(SampleEntry::Opus {
+                    channels: *dops.get(1)? as u16,
+                    pre_skip: u16::from_be_bytes(dops.get(2..4)?.try_into().ok()?),
+                    input_sample_rate: u32::from_be_bytes(dops.get(4..8)?.try_into().ok()?),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.tables.count as usize + self.fragments.len()
+    }
+
+    /// Samples from sample tables, then from fragments.
+    pub fn samples(&self) -> Samples<'_> {
+        Samples {
+            tables: &self.tables,
+            fragments: &self.fragments,
+            index: 0,
+            dts: 0,
+            stts: (0, 0),
+            ctts: (0, 0),
+            stsc: 0,
+            chunk: 0,
+            in_chunk: 0,
+            offset: 0,
+            stss: 0,
+        }
+    }
+}
+
+/// Lazy sample iterator, see [`TrackInfo::samples`].
+#[derive(Debug, Clone)]
+pub struct Samples<'a> {
+    tables: &'a Tables,
+    fragments: &'a [SampleRef],
+    index: u32,
+    dts: i64,
+    /// entry index, samples used of entry
+    stts: (usize, u32),
+    ctts: (usize, u32),
+    stsc: usize,
+    chunk: usize,
+    in_chunk: u32,
+    offset: u64,
+    stss: usize,
+}
+
+/// Advances run-length table cursor and returns the value.
+fn run_value<T: Copy + Default>(table: &[(u32, T)], cursor: &mut (usize, u32)) -> T {
+    while let Some((count, _)) = table.get(cursor.0) {
+        if cursor.1 < *count {
+            break;
+        }
+        *cursor = (cursor.0 + 1, 0);
+    }
+    cursor.1 += 1;
+    table.get(cursor.0).map(|e| e.1).unwrap_or_default()
+}
+
+impl Iterator for Samples<'_> {
+    type Item = SampleRef;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.tables;
+        if self.index >= t.count {
+            let res = self.fragments.first().copied();
+            self.fragments = self.fragments.get(1..).unwrap_or_default();
+            return res;
+        }
+        loop {
+            while self.stsc + 1 < t.stsc.len() && t.stsc[self.stsc + 1].0 as usize <= self.chunk + 1
+            {
+                self.stsc += 1;
+            }
+            let per_chunk = t.stsc.get(self.stsc).map_or(0, |e| e.1);
+            if self.in_chunk < per_chunk {
+                break;
+            }
+            self.chunk += 1;
+            self.in_chunk = 0;
+            if self.chunk >= t.chunks.len() {
+                // tables are inconsistent
+                self.index = t.count;
+                return self.next();
+            }
+        }
+        if self.in_chunk == 0 {
+            self.offset = *t.chunks.get(self.chunk)?;
+        }
+
+        let size = if t.sample_size != 0 {
+            t.sample_size
+        } else {
+            *t.sizes.get(self.index as usize)?
+        };
+        let duration = run_value(&t.stts, &mut self.stts);
+        let cts = run_value(&t.ctts, &mut self.ctts);
+        let number = self.index + 1;
+        let is_sync = match &t.stss {
+            None => true,
+            Some(stss) => {
+                while stss.get(self.stss).is_some_and(|n| *n < number) {
+                    self.stss += 1;
+                }
+                stss.get(self.stss) == Some(&number)
+            }
+        };
+        let res = SampleRef {
+            offset: self.offset,
+            sample: Sample {
+                dts: self.dts,
+                pts: self.dts + cts as i64,
+                duration,
+                size,
+                is_sync,
+            },
+        };
+        self.offset += size as u64;
+        self.in_chunk += 1;
+        self.dts += duration as i64;
+        self.index += 1;
+        Some(res)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.tables.count.saturating_sub(self.index) as usize + self.fragments.len();
+        (0, Some(n))
+    }
+}
+
+/// Parsed `moov` with samples of fragments added so far.
+#[derive(Debug, Clone)]
+pub struct Movie {
+    pub timescale: u32,
+    /// In `timescale` units
+    pub duration: u64,
+    pub tracks: Vec<TrackInfo>,
+}
+
+impl Movie {
+    /// Walks top level boxes of whole file, `moov` may come after `mdat`.
+    ///
+    /// Data truncated after `moov` is accepted, fragments up to that point are read.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut movie = None;
+        let mut moofs = Vec::new();
+        for b in Boxes::new(data, 0) {
+            let b = match b {
+                Ok(b) => b,
+                Err(Error::Eof) if movie.is_some() => break,
+                Err(e) => return Err(e),
+            };
+            match &b.typ {
+                b"moov" => movie = Some(Self::with_moov(&b)?),
+                b"moof" => moofs.push(b),
+                _ => {}
+            }
+        }
+        let Some(mut movie) = movie else {
+            return Err(Error::Invalid("missing moov"));
+        };
+        for moof in &moofs {
+            movie.add_fragment(moof)?;
+        }
+        Ok(movie)
+    }
+
+    pub fn with_moov(moov: &BoxRef) -> Result<Self> {
+        let (version, _, mut r) = full_box(&required(moov, b"mvhd")?)?;
+        r.skip(if version == 1 { 16 } else { 8 })?;
+        let timescale = r.u32()?;
+        let duration = if version == 1 {
+            r.u64()?
+        } else {
+            r.u32()? as u64
+        };
+        let mut tracks = Vec::new();
+        for b in moov.children() {
+            let b = b?;
+            if &b.typ == b"trak" {
+                tracks.push(TrackInfo::parse(&b)?);
+            }
+        }
+        let mut res = Self {
+            timescale,
+            duration,
+            tracks,
+        };
+        if let Some(mvex) = moov.child(b"mvex") {
+            for b in mvex.children() {
+                let b = b?;
+                if &b.typ != b"trex" {
+                    continue;
+                }
+                let (_, _, mut r) = full_box(&b)?;
+                let id = r.u32()?;
+                // default_sample_description_index
+                r.u32()?;
+                let defaults = Defaults {
+                    duration: r.u32()?,
+                    size: r.u32()?,
+                    flags: r.u32()?,
+                };
+                if let Some(track) = res.track_mut(id) {
+                    track.defaults = defaults;
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn track(&self, id: u32) -> Option<&TrackInfo> {
+        self.tracks.iter().find(|t| t.id == id)
+    }
+
+    fn track_mut(&mut self, id: u32) -> Option<&mut TrackInfo> {
+        self.tracks.iter_mut().find(|t| t.id == id)
+    }
+
+    /// Appends samples of `moof`, its offset must be absolute in the file.
+    pub fn add_fragment(&mut self, moof: &BoxRef) -> Result {
+        let mut prev_end = None;
+        for traf in moof.children() {
+            let traf = traf?;
+            if &traf.typ != b"traf" {
+                continue;
+            }
+            let Some(tfhd) = traf.child(b"tfhd") else {
+                return Err(Error::Invalid("missing tfhd"));
+            };
+            let (_, flags, mut r) = full_box(&tfhd)?;
+            let id = r.u32()?;
+            let Some(track) = self.track_mut(id) else {
+                return Err(Error::Invalid("fragment of unknown track"));
+            };
+            let base = if flags & 0x1 != 0 {
+                r.u64()?
+            } else if flags & 0x02_0000 != 0 {
+                moof.offset
+            } else {
+                prev_end.unwrap_or(moof.offset)
+            };
+            if flags & 0x2 != 0 {
+                // sample_description_index
+                r.u32()?;
+            }
+            let mut defaults = track.defaults;
+            if flags & 0x8 != 0 {
+                defaults.duration = r.u32()?;
+            }
+            if flags & 0x10 != 0 {
+                defaults.size = r.u32()?;
+            }
+            if flags & 0x20 != 0 {
+                defaults.flags = r.u32()?;
+            }
+
+            if let Some(tfdt) = traf.child(b"tfdt") {
+                let (version, _, mut r) = full_box(&tfdt)?;
+                track.next_fragment_dts = if version == 1 {
+                    r.u64()? as i64
+                } else {
+                    r.u32()? as i64
+                };
+            }
+
+            let mut pos = base;
+            for trun in traf.children() {
+                let trun = trun?;
+                if &trun.typ != b"trun" {
+                    continue;
+                }
+                let (_, flags, mut r) = full_box(&trun)?;
+                let count = r.u32()?;
+                if flags & 0x1 != 0 {
+                    pos = base.wrapping_add_signed(r.u32()? as i32 as i64);
+                }
+                let first_flags = if flags & 0x4 != 0 {
+                    Some(r.u32()?)
+                } else {
+                    None
+                };
+                for i in 0..count {
+                    let duration = if flags & 0x100 != 0 {
+                        r.u32()?
+                    } else {
+                        defaults.duration
+                    };
+                    let size = if flags & 0x200 != 0 {
+                        r.u32()?
+                    } else {
+                        defaults.size
+                    };
+                    let sample_flags = if flags & 0x400 != 0 {
+                        r.u32()?
+                    } else {
+                        first_flags.filter(|_| i == 0).unwrap_or(defaults.flags)
+                    };
+                    let cts = if flags & 0x800 != 0 {
+                        r.u32()? as i32 as i64
+                    } else {
+                        0
+                    };
+                    let dts = track.next_fragment_dts;
+                    track.fragments.push(SampleRef {
+                        offset: pos,
+                        sample: Sample {
+                            dts,
+                            pts: dts + cts,
+                            duration,
+                            size,
+                            is_sync: sample_flags & 0x1_0000 == 0,
+                        },
+                    });
+                    track.next_fragment_dts += duration as i64;
+                    pos += size as u64;
+                }
+            }
+            prev_end = Some(pos);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        Error,
+        mp4::{
+            BoxWriter, Sample, SampleEntry, Track,
+            demux::{Boxes, Movie, SampleRef},
+            fmp4::{self, Run},
+        },
+    };
+
+    const AVCC: &[u8] = &[
+        0x01, 0x42, 0xc0, 0x1e, 0xff, 0xe1, 0x00, 0x09, 0x67, 0x42, 0xc0, 0x1e, 0xda, 0x02, 0x80,
+        0xf6, 0x40, 0x01, 0x00, 0x04, 0x68, 0xce, 0x3c, 0x80,
+    ];
+
+    fn avc() -> SampleEntry {
+        SampleEntry::Avc {
+            width: 640,
+            height: 480,
+            avcc: AVCC.to_vec(),
+        }
+    }
+
+    fn sample(dts: i64, pts: i64, duration: u32, size: u32, is_sync: bool) -> Sample {
+        Sample {
+            dts,
+            pts,
+            duration,
+            size,
+            is_sync,
+        }
+    }
+
+    #[test]
+    fn boxes() {
+        let mut buf = Vec::new();
+        let mut w = BoxWriter::new(&mut buf);
+        w.bx(b"free", |w| w.zeros(2));
+        // largesize
+        w.u32(1);
+        w.bytes(b"skip");
+        w.u64(17);
+        w.u8(7);
+        let boxes: Vec<_> = Boxes::new(&buf, 100).collect();
+        assert_eq!(boxes.len(), 2);
+        let b = boxes[1].unwrap();
+        assert_eq!(
+            (&b.typ, b.offset, b.size, b.body),
+            (b"skip", 110, 17, &[7][..])
+        );
+
+        buf.truncate(buf.len() - 1);
+        let boxes: Vec<_> = Boxes::new(&buf, 0).collect();
+        assert_eq!(boxes[1], Err(Error::Eof));
+    }
+
+    #[test]
+    fn fragmented() {
+        let aac = SampleEntry::Aac {
+            channels: 2,
+            sample_rate: 44_100,
+            config: vec![0x12, 0x10],
+        };
+        let tracks = [
+            Track::new(1, 90_000, avc()),
+            Track::new(2, 44_100, aac.clone()),
+        ];
+        let mut file = Vec::new();
+        fmp4::write_init_segment(&tracks, &mut file).unwrap();
+
+        let video = [
+            sample(0, 3000, 3000, 5, true),
+            sample(3000, 9000, 3000, 3, false),
+            sample(6000, 6000, 3000, 2, false),
+        ];
+        let audio = [
+            sample(0, 0, 1024, 4, true),
+            sample(1024, 1024, 1024, 1, true),
+        ];
+        let video_data = [1, 1, 1, 1, 1, 2, 2, 2, 3, 3];
+        let audio_data = [4, 4, 4, 4, 5];
+        for seq in 1..=2 {
+            let shift = |samples: &[Sample], by: i64| {
+                samples
+                    .iter()
+                    .map(|s| Sample {
+                        dts: s.dts + by * (seq - 1),
+                        pts: s.pts + by * (seq - 1),
+                        ..*s
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let (video, audio) = (shift(&video, 9000), shift(&audio, 2048));
+            let runs = [
+                Run {
+                    track_id: 1,
+                    samples: &video,
+                    data: &video_data,
+                },
+                Run {
+                    track_id: 2,
+                    samples: &audio,
+                    data: &audio_data,
+                },
+            ];
+            fmp4::write_fragment(seq as u32, &runs, &mut file).unwrap();
+        }
+
+        let movie = Movie::parse(&file).unwrap();
+        assert_eq!(movie.tracks.len(), 2);
+        let track = movie.track(1).unwrap();
+        assert!(track.is_video());
+        assert_eq!(&track.entry_type().unwrap(), b"avc1");
+        assert_eq!(track.entry(), Some(avc()));
+        assert_eq!(track.config(b"avcC"), Some(AVCC));
+        assert_eq!((track.width, track.height), (640, 480));
+        assert_eq!(&track.language, b"und");
+        assert_eq!(track.sample_count(), 6);
+
+        // second fragment has no tfdt gap
+        let samples: Vec<_> = track.samples().collect();
+        for (i, s) in samples.iter().enumerate() {
+            let mut expected = video[i % 3];
+            expected.dts += 9000 * (i / 3) as i64;
+            expected.pts += 9000 * (i / 3) as i64;
+            assert_eq!(s.sample, expected);
+            let data = &file[s.offset as usize..][..s.sample.size as usize];
+            assert!(data.iter().all(|b| *b == [1, 2, 3][i % 3]));
+        }
+
+        let track = movie.track(2).unwrap();
+        assert!(track.is_audio());
+        assert_eq!(track.entry(), Some(aac));
+        let last = track.samples().last().unwrap();
+        assert_eq!(last.sample, sample(3072, 3072, 1024, 1, true));
+        assert_eq!(file[last.offset as usize], 5);
+    }
+
+    /// Progressive file with three chunks of 2, 2 and 1 samples.
+    fn progressive() -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut w = BoxWriter::new(&mut buf);
+        w.bx(b"ftyp", |w| w.bytes(b"isom\0\0\0\0isom"));
+        w.bx(b"mdat", |w| w.bytes(&[1, 1, 2, 3, 3, 3, 4, 5]));
+        w.bx(b"moov", |w| {
+            w.full(b"mvhd", 0, 0, |w| {
+                w.zeros(8);
+                w.u32(600);
+                w.u32(1500);
+                w.zeros(80);
+            });
+            w.bx(b"trak", |w| {
+                w.full(b"tkhd", 0, 3, |w| {
+                    w.zeros(8);
+                    w.u32(1);
+                    w.zeros(60);
+                    w.u32(640 << 16);
+                    w.u32(480 << 16);
+                });
+                w.bx(b"mdia", |w| {
+                    w.full(b"mdhd", 0, 0, |w| {
+                        w.zeros(8);
+                        w.u32(30);
+                        w.u32(5);
+                        // eng
+                        w.u16(0x15c7);
+                        w.u16(0);
+                    });
+                    w.full(b"hdlr", 0, 0, |w| {
+                        w.u32(0);
+                        w.bytes(b"vide");
+                        w.zeros(13);
+                    });
+                    w.bx(b"minf", |w| {
+                        w.bx(b"stbl", |w| {
+                            w.full(b"stsd", 0, 0, |w| {
+                                w.u32(1);
+                                fmp4::sample_entry(w, &avc());
+                            });
+                            w.full(b"stts", 0, 0, |w| {
+                                w.u32(2);
+                                w.bytes(&[0, 0, 0, 4, 0, 0, 0, 1]);
+                                w.bytes(&[0, 0, 0, 1, 0, 0, 0, 2]);
+                            });
+                            w.full(b"ctts", 1, 0, |w| {
+                                w.u32(3);
+                                w.bytes(&[0, 0, 0, 1, 0, 0, 0, 1]);
+                                w.bytes(&[0, 0, 0, 1, 0, 0, 0, 2]);
+                                w.bytes(&[0, 0, 0, 3, 0xff, 0xff, 0xff, 0xff]);
+                            });
+                            w.full(b"stsc", 0, 0, |w| {
+                                w.u32(2);
+                                w.bytes(&[0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1]);
+                                w.bytes(&[0, 0, 0, 3, 0, 0, 0, 1, 0, 0, 0, 1]);
+                            });
+                            w.full(b"stsz", 0, 0, |w| {
+                                w.u32(0);
+                                w.u32(5);
+                                for size in [2, 1, 3, 1, 1] {
+                                    w.u32(size);
+                                }
+                            });
+                            w.full(b"co64", 0, 0, |w| {
+                                w.u32(3);
+                                w.u64(28);
+                                w.u64(31);
+                                w.u64(35);
+                            });
+                            w.full(b"stss", 0, 0, |w| {
+                                w.u32(2);
+                                w.u32(1);
+                                w.u32(4);
+                            });
+                        });
+                    });
+                });
+            });
+        });
+        buf
+    }
+
+    #[test]
+    fn sample_tables() {
+        let file = progressive();
+        let movie = Movie::parse(&file).unwrap();
+        assert_eq!((movie.timescale, movie.duration), (600, 1500));
+        let track = &movie.tracks[0];
+        assert_eq!((track.timescale, track.duration), (30, 5));
+        assert_eq!(&track.language, b"eng");
+        assert_eq!(track.entry(), Some(avc()));
+        let samples: Vec<_> = track.samples().collect();
+        assert_eq!(
+            samples,
+            [
+                SampleRef {
+                    offset: 28,
+                    sample: sample(0, 1, 1, 2, true)
+                },
+                SampleRef {
+                    offset: 30,
+                    sample: sample(1, 3, 1, 1, false)
+                },
+                SampleRef {
+                    offset: 31,
+                    sample: sample(2, 1, 1, 3, false)
+                },
+                SampleRef {
+                    offset: 34,
+                    sample: sample(3, 2, 1, 1, true)
+                },
+                SampleRef {
+                    offset: 35,
+                    sample: sample(4, 3, 2, 1, false)
+                },
+            ]
+        );
+        for (s, expected) in samples.iter().zip([1, 2, 3, 4, 5]) {
+            assert_eq!(file[s.offset as usize], expected);
+        }
+
+        // truncated before moov
+        assert_eq!(Movie::parse(&file[..30]).unwrap_err(), Error::Eof);
+        assert_eq!(
+            Movie::parse(&file[..20]).unwrap_err(),
+            Error::Invalid("missing moov")
+        );
+    }
+}
@@ -0,0 +1,448 @@
+//! Fragmented MP4 (CMAF) init segments and `moof`/`mdat` fragments
+
+use crate::media::{Error, Result};
+
+use super::{BoxWriter, Sample, SampleEntry, Track};
+
+const MATRIX: [u32; 9] = [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000];
+
+/// `sample_depends_on` = 2, key frame
+const SYNC_SAMPLE_FLAGS: u32 = 0x0200_0000;
+/// `sample_depends_on` = 1, `sample_is_non_sync_sample`
+const NON_SYNC_SAMPLE_FLAGS: u32 = 0x0101_0000;
+
+/// Writes `ftyp` and `moov` with `mvex` for `tracks`.
+pub fn write_init_segment(tracks: &[Track], out: &mut Vec<u8>) -> Result {
+    if tracks.is_empty() {
+        return Err(Error::Invalid("no tracks"));
+    }
+    for track in tracks {
+        if track.id == 0 || track.timescale == 0 {
+            return Err(Error::Invalid("track id or timescale"));
+        }
+        if let SampleEntry::Opus { channels, .. } = track.entry {
+            if !(1..=2).contains(&channels) {
+                return Err(Error::Unsupported("opus channel mapping"));
+            }
+        }
+    }
+    let next_track_id = tracks.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+
+    let mut w = BoxWriter::new(out);
+    w.bx(b"ftyp", |w| {
+        w.bytes(b"iso6");
+        w.u32(0);
+        w.bytes(b"iso6cmfcmp41");
+    });
+    w.bx(b"moov", |w| {
+        w.full(b"mvhd", 0, 0, |w| {
+            // creation_time, modification_time
+            w.zeros(8);
+            w.u32(1000);
+            // duration
+            w.u32(0);
+            // rate, volume
+            w.u32(0x0001_0000);
+            w.u16(0x0100);
+            w.zeros(10);
+            matrix(w);
+            // pre_defined
+            w.zeros(24);
+            w.u32(next_track_id);
+        });
+        for track in tracks {
+            trak(w, track);
+        }
+        w.bx(b"mvex", |w| {
+            for track in tracks {
+                w.full(b"trex", 0, 0, |w| {
+                    w.u32(track.id);
+                    // default_sample_description_index
+                    w.u32(1);
+                    // default duration, size and flags
+                    w.zeros(12);
+                });
+            }
+        });
+    });
+    Ok(())
+}
+
+fn matrix(w: &mut BoxWriter) {
+    for v in MATRIX {
+        w.u32(v);
+    }
+}
+
+fn trak(w: &mut BoxWriter, track: &Track) {
+    let video = track.entry.is_video();
+    w.bx(b"trak", |w| {
+        // track_enabled | track_in_movie
+        w.full(b"tkhd", 0, 0x3, |w| {
+            w.zeros(8);
+            w.u32(track.id);
+            // reserved, duration, reserved
+            w.zeros(4 + 4 + 8);
+            // layer, alternate_group
+            w.zeros(4);
+            w.u16(if video { 0 } else { 0x0100 });
+            w.zeros(2);
+            matrix(w);
+            let (width, height) = track.entry.dimensions().unwrap_or_default();
+            w.u32((width as u32) << 16);
+            w.u32((height as u32) << 16);
+        });
+        w.bx(b"mdia", |w| {
+            w.full(b"mdhd", 0, 0, |w| {
+                w.zeros(8);
+                w.u32(track.timescale);
+                w.u32(0);
+                let lang = track.language.iter().fold(0u16, |acc, c| {
+                    (acc << 5) | (c.wrapping_sub(0x60) as u16 & 0x1f)
+                });
+                w.u16(lang);
+                w.u16(0);
+            });
+            w.full(b"hdlr", 0, 0, |w| {
+                w.u32(0);
+                w.bytes(if video { b"vide" } else { b"soun" });
+                w.zeros(12);
+                w.bytes(if video {
+                    b"VideoHandler\0"
+                } else {
+                    b"SoundHandler\0"
+                });
+            });
+            w.bx(b"minf", |w| {
+                if video {
+                    // graphicsmode, opcolor
+                    w.full(b"vmhd", 0, 1, |w| w.zeros(8));
+                } else {
+                    // balance, reserved
+                    w.full(b"smhd", 0, 0, |w| w.zeros(4));
+                }
+                w.bx(b"dinf", |w| {
+                    w.full(b"dref", 0, 0, |w| {
+                        w.u32(1);
+                        // self contained
+                        w.full(b"url ", 0, 1, |_| {});
+                    });
+                });
+                w.bx(b"stbl", |w| {
+                    w.full(b"stsd", 0, 0, |w| {
+                        w.u32(1);
+                        sample_entry(w, &track.entry);
+                    });
+                    for typ in [b"stts", b"stsc", b"stco"] {
+                        w.full(typ, 0, 0, |w| w.u32(0));
+                    }
+                    // sample_size, sample_count
+                    w.full(b"stsz", 0, 0, |w| w.zeros(8));
+                });
+            });
+        });
+    });
+}
+
+pub(super) fn sample_entry(w: &mut BoxWriter, entry: &SampleEntry) {
+    w.bx(&entry.fourcc(), |w| {
+        // reserved, data_reference_index
+        w.zeros(6);
+        w.u16(1);
+        match entry {
+            SampleEntry::Avc {
+                width,
+                height,
+                avcc: config,
+            }
+            | SampleEntry::Hevc {
+                width,
+                height,
+                hvcc: config,
+            } => {
+                // pre_defined, reserved, pre_defined
+                w.zeros(16);
+                w.u16(*width);
+                w.u16(*height);
+                // 72 dpi
+                w.u32(0x0048_0000);
+                w.u32(0x0048_0000);
+                w.u32(0);
+                // frame_count
+                w.u16(1);
+                // compressorname
+                w.zeros(32);
+                w.u16(0x0018);
+                w.u16(0xffff);
+                let typ = if matches!(entry, SampleEntry::Avc { .. }) {
+                    b"avcC"
+                } else {
+                    b"hvcC"
+                };
+                w.bx(typ, |w| w.bytes(config));
+            }
+            SampleEntry::Aac {
+                channels,
+                sample_rate,
+                config,
+            } => {
+                audio_entry(w, *channels, *sample_rate);
+                w.full(b"esds", 0, 0, |w| w.es_descriptor(config));
+            }
+            SampleEntry::Opus {
+                channels,
+                pre_skip,
+                input_sample_rate,
+            } => {
+                audio_entry(w, *channels, 48_000);
+                w.bx(b"dOps", |w| {
+                    w.u8(0);
+                    w.u8(*channels as u8);
+                    w.u16(*pre_skip);
+                    w.u32(*input_sample_rate);
+                    // OutputGain, ChannelMappingFamily
+                    w.u16(0);
+                    w.u8(0);
+                });
+            }
+        }
+    });
+}
+
+fn audio_entry(w: &mut BoxWriter, channels: u16, sample_rate: u32) {
+    w.zeros(8);
+    w.u16(channels);
+    // samplesize
+    w.u16(16);
+    w.zeros(4);
+    w.u32(sample_rate.min(u16::MAX as u32) << 16);
+}
+
+/// Samples of one track in a fragment with their data.
+#[derive(Debug, Copy, Clone)]
+pub struct Run<'a> {
+    pub track_id: u32,
+    pub samples: &'a [Sample],
+    /// Sample data in decode order, sizes must add up to its length
+    pub data: &'a [u8],
+}
+
+/// Writes `moof` and `mdat` with one `traf` per run.
+///
+/// Sample `dts` of the first sample becomes `baseMediaDecodeTime` and must not be negative.
+pub fn write_fragment(sequence_number: u32, runs: &[Run], out: &mut Vec<u8>) -> Result {
+    for run in runs {
+        let Some(first) = run.samples.first() else {
+            return Err(Error::Invalid("empty run"));
+        };
+        if first.dts < 0 {
+            return Err(Error::Invalid("negative decode time"));
+        }
+        let size: u64 = run.samples.iter().map(|s| s.size as u64).sum();
+        if size != run.data.len() as u64 {
+            return Err(Error::Invalid("sample sizes do not match data"));
+        }
+        if run
+            .samples
+            .iter()
+            .any(|s| i32::try_from(s.cts_offset()).is_err())
+        {
+            return Err(Error::Invalid("composition offset"));
+        }
+    }
+
+    let start = out.len();
+    let mut w = BoxWriter::new(out);
+    let mut data_offsets = Vec::with_capacity(runs.len());
+    w.bx(b"moof", |w| {
+        w.full(b"mfhd", 0, 0, |w| w.u32(sequence_number));
+        for run in runs {
+            w.bx(b"traf", |w| {
+                // default-base-is-moof
+                w.full(b"tfhd", 0, 0x02_0000, |w| w.u32(run.track_id));
+                w.full(b"tfdt", 1, 0, |w| w.u64(run.samples[0].dts as u64));
+                // data-offset, duration, size, flags, composition time offset
+                w.full(b"trun", 1, 0x000f01, |w| {
+                    w.u32(run.samples.len() as u32);
+                    data_offsets.push(w.pos());
+                    w.u32(0);
+                    for sample in run.samples {
+                        w.u32(sample.duration);
+                        w.u32(sample.size);
+                        w.u32(if sample.is_sync {
+                            SYNC_SAMPLE_FLAGS
+                        } else {
+                            NON_SYNC_SAMPLE_FLAGS
+                        });
+                        w.u32(sample.cts_offset() as i32 as u32);
+                    }
+                });
+            });
+        }
+    });
+    let moof_len = w.pos() - start;
+    let total: usize = runs.iter().map(|r| r.data.len()).sum();
+    let mut offset = moof_len + 8;
+    for (pos, run) in data_offsets.into_iter().zip(runs) {
+        w.set_u32(pos, offset as u32);
+        offset += run.data.len();
+    }
+    if 8 + total > u32::MAX as usize {
+        return Err(Error::Unsupported("mdat larger than 4 GiB"));
+    }
+    w.bx(b"mdat", |w| {
+        for run in runs {
+            w.bytes(run.data);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        Error,
+        mp4::{
+            Sample, SampleEntry, Track,
+            fmp4::{self, Run},
+        },
+    };
+
+    const AVCC: &[u8] = &[
+        0x01, 0x42, 0xc0, 0x1e, 0xff, 0xe1, 0x00, 0x09, 0x67, 0x42, 0xc0, 0x1e, 0xda, 0x02, 0x80,
+        0xf6, 0x40, 0x01, 0x00, 0x04, 0x68, 0xce, 0x3c, 0x80,
+    ];
+
+    fn tracks() -> [Track; 2] {
+        let video = SampleEntry::Avc {
+            width: 640,
+            height: 480,
+            avcc: AVCC.to_vec(),
+        };
+        let audio = SampleEntry::Aac {
+            channels: 2,
+            sample_rate: 48_000,
+            config: vec![0x11, 0x90],
+        };
+        [Track::new(1, 90_000, video), Track::new(2, 48_000, audio)]
+    }
+
+    /// (size, type) of top level boxes
+    fn boxes(mut data: &[u8]) -> Vec<(usize, [u8; 4])> {
+        let mut res = Vec::new();
+        while data.len() >= 8 {
+            let size = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+            res.push((size, data[4..8].try_into().unwrap()));
+            data = &data[size..];
+        }
+        res
+    }
+
+    fn find<'a>(data: &'a [u8], typ: &[u8; 4]) -> &'a [u8] {
+        let pos = data.windows(4).position(|w| w == typ).unwrap();
+        let size = u32::from_be_bytes(data[pos - 4..pos].try_into().unwrap()) as usize;
+        &data[pos - 4..pos - 4 + size]
+    }
+
+    #[test]
+    fn init_segment() {
+        let mut buf = Vec::new();
+        fmp4::write_init_segment(&tracks(), &mut buf).unwrap();
+        assert_eq!(buf, include_bytes!("testdata/init.mp4"));
+        let top = boxes(&buf);
+        assert_eq!(top, [(28, *b"ftyp"), (buf.len() - 28, *b"moov")]);
+        assert_eq!(&find(&buf, b"avcC")[8..], AVCC);
+        assert_eq!(&find(&buf, b"mvhd")[104..108], &[0, 0, 0, 3]);
+        // und
+        assert_eq!(&find(&buf, b"mdhd")[28..30], &[0x55, 0xc4]);
+        let avc1 = find(&buf, b"avc1");
+        assert_eq!(&avc1[32..36], &[0x02, 0x80, 0x01, 0xe0]);
+        let esds = find(&buf, b"esds");
+        assert_eq!(
+            crate::media::mp4::decoder_specific_info(&esds[12..]),
+            Some(&[0x11, 0x90][..])
+        );
+        assert_eq!(
+            fmp4::write_init_segment(&[], &mut buf),
+            Err(Error::Invalid("no tracks"))
+        );
+    }
+
+    #[test]
+    fn fragment() {
+        // I P B with reordering, 30 fps
+        let video = [
+            Sample {
+                dts: 0,
+                pts: 3000,
+                duration: 3000,
+                size: 5,
+                is_sync: true,
+            },
+            Sample {
+                dts: 3000,
+                pts: 9000,
+                duration: 3000,
+                size: 3,
+                is_sync: false,
+            },
+            Sample {
+                dts: 6000,
+                pts: 6000,
+                duration: 3000,
+                size: 2,
+                is_sync: false,
+            },
+        ];
+        let audio = [Sample {
+            dts: 0,
+            pts: 0,
+            duration: 1024,
+            size: 4,
+            is_sync: true,
+        }];
+        let runs = [
+            Run {
+                track_id: 1,
+                samples: &video,
+                data: &[1, 1, 1, 1, 1, 2, 2, 2, 3, 3],
+            },
+            Run {
+                track_id: 2,
+                samples: &audio,
+                data: &[4, 4, 4, 4],
+            },
+        ];
+        let mut buf = Vec::new();
+        fmp4::write_fragment(7, &runs, &mut buf).unwrap();
+        assert_eq!(buf, include_bytes!("testdata/fragment.m4s"));
+
+        let top = boxes(&buf);
+        assert_eq!(top[0].1, *b"moof");
+        assert_eq!(top[1], (8 + 14, *b"mdat"));
+        let moof_len = top[0].0;
+
+        assert_eq!(&find(&buf, b"mfhd")[12..], &[0, 0, 0, 7]);
+        let trun = find(&buf, b"trun");
+        assert_eq!(&trun[8..12], &[1, 0, 0x0f, 0x01]);
+        let offset = u32::from_be_bytes(trun[16..20].try_into().unwrap()) as usize;
+        assert_eq!(offset, moof_len + 8);
+        assert_eq!(&buf[offset..offset + 10], runs[0].data);
+        // second sample composition offset
+        assert_eq!(&trun[20 + 16 + 12..20 + 16 + 16], &6000u32.to_be_bytes());
+
+        let audio_traf = &buf[find(&buf, b"traf").len() + 8 + 16..];
+        let trun = find(audio_traf, b"trun");
+        let offset = u32::from_be_bytes(trun[16..20].try_into().unwrap()) as usize;
+        assert_eq!(&buf[offset..], runs[1].data);
+
+        let bad = Run {
+            data: &[1],
+            ..runs[0]
+        };
+        assert_eq!(
+            fmp4::write_fragment(1, &[bad], &mut Vec::new()),
+            Err(Error::Invalid("sample sizes do not match data"))
+        );
+    }
+}
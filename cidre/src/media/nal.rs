@@ -0,0 +1,359 @@
+//! NAL unit iteration and Annex-B ⇄ length-prefixed conversion
+
+use super::{Error, Result, avc, hevc};
+
+pub const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Codec {
+    Avc,
+    Hevc,
+}
+
+impl Codec {
+    /// Type from NAL unit header.
+    #[inline]
+    pub fn nal_type(self, nal: &[u8]) -> Option<u8> {
+        match self {
+            Self::Avc => nal.first().map(|b| b & 0x1f),
+            Self::Hevc => hevc::nal_type(nal),
+        }
+    }
+
+    /// IDR for H.264, IRAP (BLA, IDR, CRA) for HEVC
+    #[inline]
+    pub fn is_key_frame(self, nal_type: u8) -> bool {
+        match self {
+            Self::Avc => nal_type == 5,
+            Self::Hevc => (16..=23).contains(&nal_type),
+        }
+    }
+
+    /// Coded slice data
+    #[inline]
+    pub fn is_vcl(self, nal_type: u8) -> bool {
+        match self {
+            Self::Avc => (1..=5).contains(&nal_type),
+            Self::Hevc => nal_type < 32,
+        }
+    }
+
+    /// SPS and PPS, plus VPS for HEVC
+    #[inline]
+    pub fn is_param_set(self, nal_type: u8) -> bool {
+        match self {
+            Self::Avc => nal_type == avc::NAL_SPS || nal_type == avc::NAL_PPS,
+            Self::Hevc => (hevc::NAL_VPS..=hevc::NAL_PPS).contains(&nal_type),
+        }
+    }
+
+    /// Whether any NAL unit starts a key frame.
+    pub fn has_key_frame<'a>(self, mut nals: impl Iterator<Item = &'a [u8]>) -> bool {
+        nals.any(|nal| self.nal_type(nal).is_some_and(|t| self.is_key_frame(t)))
+    }
+}
+
+/// NAL units prefixed with big-endian length of `nal_unit_len` bytes (AVCC, HVCC).
+///
+/// Yields an error and stops if length runs past the end of data.
+#[derive(Debug, Clone)]
+pub struct LengthPrefixed<'a> {
+    data: &'a [u8],
+    nal_unit_len: u8,
+}
+
+impl<'a> LengthPrefixed<'a> {
+    /// `nal_unit_len` is 1, 2, 3 or 4, as `NALUnitHeaderLength` of format description.
+    pub fn new(data: &'a [u8], nal_unit_len: u8) -> Self {
+        debug_assert!((1..=4).contains(&nal_unit_len));
+        Self { data, nal_unit_len }
+    }
+}
+
+impl<'a> Iterator for LengthPrefixed<'a> {
+    type Item = Result<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let n = self.nal_unit_len as usize;
+        let res = self.data.split_at_checked(n).and_then(|(len, rest)| {
+            let len = len.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            rest.split_at_checked(len)
+        });
+        match res {
+            Some((nal, rest)) => {
+                self.data = rest;
+                Some(Ok(nal))
+            }
+            None => {
+                self.data = &[];
+                Some(Err(Error::Eof))
+            }
+        }
+    }
+}
+
+/// NAL units separated with 3 or 4 byte start codes (Annex B byte stream).
+///
+/// Bytes before the first start code and trailing zero bytes are dropped.
+#[derive(Debug, Clone)]
+pub struct AnnexB<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AnnexB<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        let start = find_start_code(data).map_or(data.len(), |(_, end)| end);
+        Self {
+            data: &data[start..],
+        }
+    }
+}
+
+/// Position of first `00 00 01` and position after it.
+fn find_start_code(data: &[u8]) -> Option<(usize, usize)> {
+    let mut i = 0;
+    while i + 2 < data.len() {
+        if data[i + 2] > 1 {
+            i += 3;
+        } else if data[i] == 0 && data[i + 1] == 0 && data[i + 2] == 1 {
+            return Some((i, i + 3));
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+fn trim_zeros(mut nal: &[u8]) -> &[u8] {
+    while let [rest @ .., 0] = nal {
+        nal = rest;
+    }
+    nal
+}
+
+impl<'a> Iterator for AnnexB<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.data.is_empty() {
+                return None;
+            }
+            let (nal, rest) = match find_start_code(self.data) {
+                Some((start, end)) => (&self.data[..start], &self.data[end..]),
+                None => (self.data, &[][..]),
+            };
+            self.data = rest;
+            let nal = trim_zeros(nal);
+            if !nal.is_empty() {
+                return Some(nal);
+            }
+        }
+    }
+}
+
+/// Appends NAL unit with length prefix.
+pub fn push_length_prefixed(out: &mut Vec<u8>, nal: &[u8], nal_unit_len: u8) -> Result {
+    let n = nal_unit_len as usize;
+    if n == 0 || n > 4 || (n < 4 && nal.len() >> (n * 8) != 0) {
+        return Err(Error::Invalid("nal unit length"));
+    }
+    out.extend_from_slice(&(nal.len() as u32).to_be_bytes()[4 - n..]);
+    out.extend_from_slice(nal);
+    Ok(())
+}
+
+/// Appends NAL unit with 4 byte start code.
+#[inline]
+pub fn push_annex_b(out: &mut Vec<u8>, nal: &[u8]) {
+    out.extend_from_slice(&START_CODE);
+    out.extend_from_slice(nal);
+}
+
+/// Converts length-prefixed access unit to Annex B.
+///
+/// When `param_sets` are not empty they are inserted before the first slice
+/// of key frames unless the access unit already carries parameter sets.
+pub fn to_annex_b(
+    src: &[u8],
+    nal_unit_len: u8,
+    codec: Codec,
+    param_sets: &[&[u8]],
+    out: &mut Vec<u8>,
+) -> Result {
+    out.reserve(src.len() + param_sets.iter().map(|p| p.len() + 4).sum::<usize>());
+    let mut need_param_sets = !param_sets.is_empty();
+    for nal in LengthPrefixed::new(src, nal_unit_len) {
+        let nal = nal?;
+        if let Some(t) = codec.nal_type(nal) {
+            if codec.is_param_set(t) {
+                need_param_sets = false;
+            } else if need_param_sets && codec.is_key_frame(t) {
+                for p in param_sets {
+                    push_annex_b(out, p);
+                }
+                need_param_sets = false;
+            }
+        }
+        push_annex_b(out, nal);
+    }
+    Ok(())
+}
+
+/// Converts Annex B stream to length-prefixed NAL units.
+pub fn to_length_prefixed(src: &[u8], nal_unit_len: u8, out: &mut Vec<u8>) -> Result {
+    out.reserve(src.len());
+    for nal in AnnexB::new(src) {
+        push_length_prefixed(out, nal, nal_unit_len)?;
+    }
+    Ok(())
+}
+
+/// Replaces 4 byte length prefixes with start codes without copying.
+pub fn to_annex_b_in_place(buf: &mut [u8]) -> Result {
+    let mut i = 0;
+    while i < buf.len() {
+        let Some(prefix) = buf.get_mut(i..i + 4) else {
+            return Err(Error::Eof);
+        };
+        let len = u32::from_be_bytes(prefix.try_into().unwrap()) as usize;
+        prefix.copy_from_slice(&START_CODE);
+        i += 4 + len;
+    }
+    if i > buf.len() {
+        return Err(Error::Eof);
+    }
+    Ok(())
+}
+
+/// Replaces 4 byte start codes with length prefixes without copying.
+///
+/// Fails on 3 byte start codes that have no room for the prefix. Zero bytes
+/// trailing a NAL unit are kept as part of it.
+pub fn to_length_prefixed_in_place(buf: &mut [u8]) -> Result {
+    let mut starts = Vec::new();
+    let mut pos = 0;
+    while let Some((start, end)) = find_start_code(&buf[pos..]) {
+        let (start, end) = (pos + start, pos + end);
+        if start == 0 || buf[start - 1] != 0 {
+            return Err(Error::Unsupported("3 byte start code"));
+        }
+        starts.push(start - 1);
+        pos = end;
+    }
+    if starts.first() != Some(&0) {
+        return Err(Error::Invalid("annex b stream start"));
+    }
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(buf.len());
+        let len = (end - start - 4) as u32;
+        buf[start..start + 4].copy_from_slice(&len.to_be_bytes());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        Error,
+        nal::{self, AnnexB, Codec, LengthPrefixed},
+    };
+
+    const SPS: &[u8] = &[0x67, 0x42, 0xc0, 0x1e];
+    const PPS: &[u8] = &[0x68, 0xce, 0x3c, 0x80];
+    const IDR: &[u8] = &[0x65, 0x88, 0x84, 0x00, 0x00, 0x03, 0x01];
+    const SLICE: &[u8] = &[0x41, 0x9a, 0x02];
+
+    fn prefixed(nals: &[&[u8]], n: u8) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for nal in nals {
+            nal::push_length_prefixed(&mut buf, nal, n).unwrap();
+        }
+        buf
+    }
+
+    #[test]
+    fn iterate() {
+        for n in 1..=4 {
+            let buf = prefixed(&[IDR, SLICE], n);
+            let nals: Vec<_> = LengthPrefixed::new(&buf, n).collect();
+            assert_eq!(nals, [Ok(IDR), Ok(SLICE)]);
+        }
+        let buf = prefixed(&[IDR], 4);
+        let nals: Vec<_> = LengthPrefixed::new(&buf[..buf.len() - 1], 4).collect();
+        assert_eq!(nals, [Err(Error::Eof)]);
+
+        let stream = [
+            &[0xff, 0, 0, 1][..],
+            SPS,
+            &[0, 0, 0, 1],
+            PPS,
+            &[0, 0, 0, 0, 1],
+            SLICE,
+            &[0, 0],
+        ]
+        .concat();
+        let nals: Vec<_> = AnnexB::new(&stream).collect();
+        assert_eq!(nals, [SPS, PPS, SLICE]);
+        assert_eq!(AnnexB::new(&[0, 0, 0, 0]).count(), 0);
+    }
+
+    #[test]
+    fn nal_types() {
+        assert_eq!(Codec::Avc.nal_type(IDR), Some(5));
+        assert!(Codec::Avc.has_key_frame([SPS, IDR].into_iter()));
+        assert!(!Codec::Avc.has_key_frame([SLICE].into_iter()));
+        assert!(Codec::Avc.is_param_set(7));
+
+        // IDR_W_RADL, TRAIL_R, VPS
+        assert!(Codec::Hevc.has_key_frame([&[0x26, 0x01][..]].into_iter()));
+        assert!(!Codec::Hevc.has_key_frame([&[0x02, 0x01][..]].into_iter()));
+        assert!(Codec::Hevc.is_param_set(Codec::Hevc.nal_type(&[0x40, 0x01]).unwrap()));
+        assert!(Codec::Hevc.is_vcl(1));
+    }
+
+    #[test]
+    fn convert() {
+        let src = prefixed(&[IDR], 4);
+        let mut out = Vec::new();
+        nal::to_annex_b(&src, 4, Codec::Avc, &[SPS, PPS], &mut out).unwrap();
+        assert_eq!(AnnexB::new(&out).collect::<Vec<_>>(), [SPS, PPS, IDR]);
+
+        // already has parameter sets or is not a key frame
+        for nals in [&[SPS, PPS, IDR][..], &[SLICE]] {
+            let src = prefixed(nals, 2);
+            out.clear();
+            nal::to_annex_b(&src, 2, Codec::Avc, &[SPS, PPS], &mut out).unwrap();
+            assert_eq!(AnnexB::new(&out).collect::<Vec<_>>(), nals);
+        }
+
+        let mut back = Vec::new();
+        nal::to_length_prefixed(&out, 4, &mut back).unwrap();
+        assert_eq!(back, prefixed(&[SLICE], 4));
+        assert_eq!(
+            nal::push_length_prefixed(&mut back, &[0; 256], 1),
+            Err(Error::Invalid("nal unit length"))
+        );
+    }
+
+    #[test]
+    fn in_place() {
+        let src = prefixed(&[SPS, PPS, IDR], 4);
+        let mut buf = src.clone();
+        nal::to_annex_b_in_place(&mut buf).unwrap();
+        assert_eq!(AnnexB::new(&buf).collect::<Vec<_>>(), [SPS, PPS, IDR]);
+        nal::to_length_prefixed_in_place(&mut buf).unwrap();
+        assert_eq!(buf, src);
+
+        let mut short = src[..src.len() - 1].to_vec();
+        assert_eq!(nal::to_annex_b_in_place(&mut short), Err(Error::Eof));
+        let mut three = [&[0, 0, 1][..], SLICE].concat();
+        assert_eq!(
+            nal::to_length_prefixed_in_place(&mut three),
+            Err(Error::Unsupported("3 byte start code"))
+        );
+    }
+}
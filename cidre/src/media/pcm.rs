@@ -0,0 +1,585 @@
+//! Linear PCM conversion without AudioToolbox
+//!
+//! Samples go through `f64` blocks on the stack, so any integer width up to 32 bits
+//! converts exactly when it doesn't narrow.
+
+use crate::cat::audio::{BufList, StreamBasicDesc};
+
+use super::{Error, Result};
+
+/// Frames per intermediate block
+const BLOCK: usize = 64;
+
+/// Dither added when reducing resolution to integer samples.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Dither {
+    /// White noise of ±1 LSB with triangular probability density
+    #[doc(alias = "kDitherAlgorithm_TPDF")]
+    Tpdf,
+}
+
+#[cfg(feature = "at")]
+impl TryFrom<crate::at::audio::DitherAlgorithm> for Dither {
+    type Error = Error;
+
+    fn try_from(value: crate::at::audio::DitherAlgorithm) -> Result<Self> {
+        match value {
+            crate::at::audio::DitherAlgorithm::TPDF => Ok(Self::Tpdf),
+            _ => Err(Error::Unsupported("dither algorithm")),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Sample {
+    /// Container size
+    bytes: usize,
+    bits: u32,
+    /// Position of the significant bits in the container
+    shift: u32,
+    float: bool,
+    signed: bool,
+    big_endian: bool,
+}
+
+impl Sample {
+    fn with_asbd(asbd: &StreamBasicDesc) -> Result<Self> {
+        if !asbd.is_pcm() {
+            return Err(Error::Unsupported("non pcm format"));
+        }
+        if asbd.validate().is_err() {
+            return Err(Error::Invalid("stream basic desc"));
+        }
+        if asbd.fraction_bits() != 0 {
+            return Err(Error::Unsupported("fixed point samples"));
+        }
+        // validated
+        let bytes = asbd.sample_bytes().unwrap();
+        let bits = asbd.bits_per_channel;
+        if !asbd.is_float() && bytes > 4 {
+            return Err(Error::Unsupported("integer samples wider than 32 bits"));
+        }
+        let shift = if asbd.is_aligned_high() {
+            bytes * 8 - bits
+        } else {
+            0
+        };
+        Ok(Self {
+            bytes: bytes as usize,
+            bits,
+            shift,
+            float: asbd.is_float(),
+            signed: asbd.is_signed_int(),
+            big_endian: asbd.is_big_endian(),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Layout {
+    sample: Sample,
+    interleaved: bool,
+    frame_bytes: usize,
+}
+
+impl Layout {
+    fn with_asbd(asbd: &StreamBasicDesc) -> Result<Self> {
+        Ok(Self {
+            sample: Sample::with_asbd(asbd)?,
+            interleaved: asbd.is_interleaved(),
+            frame_bytes: asbd.bytes_per_frame as usize,
+        })
+    }
+
+    /// Buffer index, byte offset and stride of channel `c`
+    #[inline]
+    fn channel(&self, c: usize) -> (usize, usize, usize) {
+        if self.interleaved {
+            (0, c * self.sample.bytes, self.frame_bytes)
+        } else {
+            (c, 0, self.frame_bytes)
+        }
+    }
+}
+
+/// Converts between linear PCM formats of the same rate and channel count.
+///
+/// Handles integer and float widths, signedness, endianness, alignment and
+/// interleaving. Doesn't allocate while converting.
+///
+/// ```
+/// use cidre::{cat::audio::StreamBasicDesc, media::pcm::PcmConverter};
+///
+/// let src = StreamBasicDesc::common_f32(48_000.0, 2, false);
+/// let dst = StreamBasicDesc::common_f32(48_000.0, 2, true);
+/// let mut converter = PcmConverter::new(&src, &dst).unwrap();
+///
+/// let l = 1.0f32.to_ne_bytes();
+/// let r = 2.0f32.to_ne_bytes();
+/// let mut lr = [0u8; 8];
+/// converter.convert(1, &[&l, &r], &mut [&mut lr]).unwrap();
+/// assert_eq!(lr[4..], r);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PcmConverter {
+    src: Layout,
+    dst: Layout,
+    channels: usize,
+    dither: Option<Dither>,
+    /// xorshift32 state of dither noise
+    rng: u32,
+}
+
+impl PcmConverter {
+    pub fn new(src: &StreamBasicDesc, dst: &StreamBasicDesc) -> Result<Self> {
+        if src.channels_per_frame != dst.channels_per_frame {
+            return Err(Error::Unsupported("channel count conversion"));
+        }
+        if src.sample_rate != dst.sample_rate {
+            return Err(Error::Unsupported("sample rate conversion"));
+        }
+        Ok(Self {
+            src: Layout::with_asbd(src)?,
+            dst: Layout::with_asbd(dst)?,
+            channels: src.channels_per_frame as usize,
+            dither: None,
+            rng: 0x9e37_79b9,
+        })
+    }
+
+    /// Dither when converting float or wider integers to integer samples
+    pub fn with_dither(mut self, dither: Option<Dither>) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    #[inline]
+    pub fn dither(&self) -> Option<Dither> {
+        self.dither
+    }
+
+    /// Number of source buffers, one per channel when non-interleaved
+    #[inline]
+    pub fn src_buffers(&self) -> usize {
+        if self.src.interleaved {
+            1
+        } else {
+            self.channels
+        }
+    }
+
+    /// Number of destination buffers, one per channel when non-interleaved
+    #[inline]
+    pub fn dst_buffers(&self) -> usize {
+        if self.dst.interleaved {
+            1
+        } else {
+            self.channels
+        }
+    }
+
+    fn needs_dither(&self) -> bool {
+        let (src, dst) = (self.src.sample, self.dst.sample);
+        self.dither.is_some() && !dst.float && (src.float || src.bits > dst.bits)
+    }
+
+    /// Converts `frames` from `src` to `dst` buffers.
+    pub fn convert(&mut self, frames: usize, src: &[&[u8]], dst: &mut [&mut [u8]]) -> Result {
+        if src.len() != self.src_buffers() || dst.len() != self.dst_buffers() {
+            return Err(Error::Invalid("buffer count"));
+        }
+        if src.iter().any(|b| b.len() < frames * self.src.frame_bytes)
+            || dst.iter().any(|b| b.len() < frames * self.dst.frame_bytes)
+        {
+            return Err(Error::Invalid("buffer size"));
+        }
+        if frames == 0 {
+            return Ok(());
+        }
+
+        let dither = self.needs_dither();
+        let copy = self.src.sample == self.dst.sample;
+        let lsb = 1.0 / (1u64 << (self.dst.sample.bits - 1)) as f64;
+        let mut block = [0f64; BLOCK];
+        for c in 0..self.channels {
+            let (si, so, ss) = self.src.channel(c);
+            let (di, d_off, ds) = self.dst.channel(c);
+            let s = &src[si][so..];
+            let d = &mut dst[di][d_off..];
+            if copy {
+                copy_samples(s, ss, d, ds, frames, self.src.sample.bytes);
+                continue;
+            }
+            for start in (0..frames).step_by(BLOCK) {
+                let block = &mut block[..BLOCK.min(frames - start)];
+                load(self.src.sample, &s[start * ss..], ss, block);
+                if dither {
+                    for v in block.iter_mut() {
+                        *v += (self.noise() - self.noise()) * lsb;
+                    }
+                }
+                store(self.dst.sample, block, &mut d[start * ds..], ds);
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts `frames` between buffer lists.
+    ///
+    /// # Safety
+    ///
+    /// Every buffer has to point to `data_bytes_size` valid bytes and `src`
+    /// buffers can't overlap `dst` ones.
+    pub unsafe fn convert_buf_list<const N: usize, const M: usize>(
+        &mut self,
+        frames: u32,
+        src: &BufList<N>,
+        dst: &mut BufList<M>,
+    ) -> Result {
+        if src.number_buffers as usize != N || dst.number_buffers as usize != M {
+            return Err(Error::Invalid("buffer count"));
+        }
+        let src: [&[u8]; N] = std::array::from_fn(|i| {
+            let buf = &src.buffers[i];
+            unsafe { std::slice::from_raw_parts(buf.data, buf.data_bytes_size as usize) }
+        });
+        let mut dst: [&mut [u8]; M] = std::array::from_fn(|i| {
+            let buf = &dst.buffers[i];
+            unsafe { std::slice::from_raw_parts_mut(buf.data, buf.data_bytes_size as usize) }
+        });
+        self.convert(frames as usize, &src, &mut dst)
+    }
+
+    /// Uniform in [0, 1)
+    #[inline]
+    fn noise(&mut self) -> f64 {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng = x;
+        (x >> 8) as f64 / (1 << 24) as f64
+    }
+}
+
+fn copy_samples(src: &[u8], ss: usize, dst: &mut [u8], ds: usize, frames: usize, bytes: usize) {
+    for i in 0..frames {
+        dst[i * ds..i * ds + bytes].copy_from_slice(&src[i * ss..i * ss + bytes]);
+    }
+}
+
+#[inline(always)]
+fn raw<const B: usize, const BE: bool>(bytes: [u8; B]) -> u32 {
+    let mut res = 0u32;
+    if BE {
+        for b in bytes {
+            res = (res << 8) | b as u32;
+        }
+    } else {
+        for b in bytes.into_iter().rev() {
+            res = (res << 8) | b as u32;
+        }
+    }
+    res
+}
+
+#[inline(always)]
+fn to_raw<const B: usize, const BE: bool>(mut v: u32) -> [u8; B] {
+    let mut res = [0u8; B];
+    for i in 0..B {
+        res[if BE { B - 1 - i } else { i }] = v as u8;
+        v >>= 8;
+    }
+    res
+}
+
+#[inline(always)]
+fn load_with<const B: usize>(
+    src: &[u8],
+    stride: usize,
+    out: &mut [f64],
+    f: impl Fn([u8; B]) -> f64,
+) {
+    for (i, o) in out.iter_mut().enumerate() {
+        let at = i * stride;
+        *o = f(src[at..at + B].try_into().unwrap());
+    }
+}
+
+#[inline(always)]
+fn store_with<const B: usize>(
+    src: &[f64],
+    dst: &mut [u8],
+    stride: usize,
+    f: impl Fn(f64) -> [u8; B],
+) {
+    for (i, v) in src.iter().enumerate() {
+        let at = i * stride;
+        dst[at..at + B].copy_from_slice(&f(*v));
+    }
+}
+
+fn load_int<const B: usize, const BE: bool>(
+    sample: Sample,
+    src: &[u8],
+    stride: usize,
+    out: &mut [f64],
+) {
+    let bits = sample.bits;
+    let scale = 1.0 / (1u64 << (bits - 1)) as f64;
+    let unused = 32 - bits;
+    let shift = sample.shift;
+    if sample.signed {
+        load_with(src, stride, out, |b| {
+            let v = raw::<B, BE>(b) >> shift;
+            (((v << unused) as i32) >> unused) as f64 * scale
+        });
+    } else {
+        let mask = u32::MAX >> unused;
+        let offset = (1u64 << (bits - 1)) as f64;
+        load_with(src, stride, out, |b| {
+            (((raw::<B, BE>(b) >> shift) & mask) as f64 - offset) * scale
+        });
+    }
+}
+
+fn store_int<const B: usize, const BE: bool>(
+    sample: Sample,
+    src: &[f64],
+    dst: &mut [u8],
+    stride: usize,
+) {
+    let scale = (1u64 << (sample.bits - 1)) as f64;
+    let offset = if sample.signed { 0.0 } else { scale };
+    let mask = u32::MAX >> (32 - sample.bits);
+    let shift = sample.shift;
+    store_with(src, dst, stride, |v| {
+        let v = (v * scale).round().clamp(-scale, scale - 1.0) + offset;
+        to_raw::<B, BE>(((v as i64 as u32) & mask) << shift)
+    });
+}
+
+fn load(sample: Sample, src: &[u8], stride: usize, out: &mut [f64]) {
+    match (sample.float, sample.bytes, sample.big_endian) {
+        (true, 4, false) => load_with(src, stride, out, |b| f32::from_le_bytes(b) as f64),
+        (true, 4, true) => load_with(src, stride, out, |b| f32::from_be_bytes(b) as f64),
+        (true, _, false) => load_with(src, stride, out, f64::from_le_bytes),
+        (true, _, true) => load_with(src, stride, out, f64::from_be_bytes),
+        (false, 1, _) => load_int::<1, false>(sample, src, stride, out),
+        (false, 2, false) => load_int::<2, false>(sample, src, stride, out),
+        (false, 2, true) => load_int::<2, true>(sample, src, stride, out),
+        (false, 3, false) => load_int::<3, false>(sample, src, stride, out),
+        (false, 3, true) => load_int::<3, true>(sample, src, stride, out),
+        (false, _, false) => load_int::<4, false>(sample, src, stride, out),
+        (false, _, true) => load_int::<4, true>(sample, src, stride, out),
+    }
+}
+
+fn store(sample: Sample, src: &[f64], dst: &mut [u8], stride: usize) {
+    match (sample.float, sample.bytes, sample.big_endian) {
+        (true, 4, false) => store_with(src, dst, stride, |v| (v as f32).to_le_bytes()),
+        (true, 4, true) => store_with(src, dst, stride, |v| (v as f32).to_be_bytes()),
+        (true, _, false) => store_with(src, dst, stride, f64::to_le_bytes),
+        (true, _, true) => store_with(src, dst, stride, f64::to_be_bytes),
+        (false, 1, _) => store_int::<1, false>(sample, src, dst, stride),
+        (false, 2, false) => store_int::<2, false>(sample, src, dst, stride),
+        (false, 2, true) => store_int::<2, true>(sample, src, dst, stride),
+        (false, 3, false) => store_int::<3, false>(sample, src, dst, stride),
+        (false, 3, true) => store_int::<3, true>(sample, src, dst, stride),
+        (false, _, false) => store_int::<4, false>(sample, src, dst, stride),
+        (false, _, true) => store_int::<4, true>(sample, src, dst, stride),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cat::audio::{BufList, PcmBuilder, PcmSample, StreamBasicDesc},
+        media::{
+            Error,
+            pcm::{Dither, PcmConverter},
+        },
+    };
+
+    fn pcm(sample: PcmSample, channels: u32) -> PcmBuilder {
+        PcmBuilder::new(sample, 48_000.0, channels).big_endian(false)
+    }
+
+    #[test]
+    fn interleave() {
+        let src = StreamBasicDesc::common_f32(48_000.0, 2, false);
+        let dst = StreamBasicDesc::common_f32(48_000.0, 2, true);
+        let mut converter = PcmConverter::new(&src, &dst).unwrap();
+        let l: Vec<u8> = (0..100).flat_map(|i| (i as f32).to_ne_bytes()).collect();
+        let r: Vec<u8> = (0..100).flat_map(|i| (-i as f32).to_ne_bytes()).collect();
+        let mut lr = vec![0u8; 800];
+        converter.convert(100, &[&l, &r], &mut [&mut lr]).unwrap();
+        for i in 0..100 {
+            assert_eq!(lr[i * 8..i * 8 + 4], l[i * 4..i * 4 + 4]);
+            assert_eq!(lr[i * 8 + 4..i * 8 + 8], r[i * 4..i * 4 + 4]);
+        }
+
+        // back through buffer lists
+        let mut l2 = vec![0u8; 400];
+        let mut r2 = vec![0u8; 400];
+        let mut src_list = BufList::<1>::default();
+        src_list.buffers[0].data = lr.as_mut_ptr();
+        src_list.buffers[0].data_bytes_size = lr.len() as u32;
+        let mut dst_list = BufList::<2>::default();
+        for (buf, data) in dst_list.buffers.iter_mut().zip([&mut l2, &mut r2]) {
+            buf.data = data.as_mut_ptr();
+            buf.data_bytes_size = data.len() as u32;
+        }
+        let mut converter = PcmConverter::new(&dst, &src).unwrap();
+        unsafe {
+            converter
+                .convert_buf_list(100, &src_list, &mut dst_list)
+                .unwrap()
+        };
+        assert_eq!((l2, r2), (l, r));
+    }
+
+    #[test]
+    fn widths() {
+        let i16s: Vec<i16> = vec![0, 1, -1, i16::MAX, i16::MIN, 12345];
+        let src: Vec<u8> = i16s.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let n = i16s.len();
+
+        // i16 -> f32 -> i16 is exact
+        let f32le = pcm(PcmSample::F32, 1).build();
+        let i16le = pcm(PcmSample::I16, 1).build();
+        let mut floats = vec![0u8; n * 4];
+        PcmConverter::new(&i16le, &f32le)
+            .unwrap()
+            .convert(n, &[&src], &mut [&mut floats])
+            .unwrap();
+        assert_eq!(
+            f32::from_le_bytes(floats[12..16].try_into().unwrap()),
+            32767.0 / 32768.0
+        );
+        let mut back = vec![0u8; n * 2];
+        PcmConverter::new(&f32le, &i16le)
+            .unwrap()
+            .convert(n, &[&floats], &mut [&mut back])
+            .unwrap();
+        assert_eq!(back, src);
+
+        // i16 -> packed big endian i24 -> i24 in high bits of little endian i32
+        let i24be = pcm(PcmSample::I24, 1).big_endian(true).build();
+        let mut packed = vec![0u8; n * 3];
+        PcmConverter::new(&i16le, &i24be)
+            .unwrap()
+            .convert(n, &[&src], &mut [&mut packed])
+            .unwrap();
+        assert_eq!(packed[3..6], [0x00, 0x01, 0x00]);
+        assert_eq!(packed[6..9], [0xff, 0xff, 0x00]);
+        let i24in32 = pcm(PcmSample::I24, 1).aligned_high(4).build();
+        let mut aligned = vec![0u8; n * 4];
+        PcmConverter::new(&i24be, &i24in32)
+            .unwrap()
+            .convert(n, &[&packed], &mut [&mut aligned])
+            .unwrap();
+        assert_eq!(aligned[12..16], [0x00, 0x00, 0xff, 0x7f]);
+        let mut back = vec![0u8; n * 2];
+        PcmConverter::new(&i24in32, &i16le)
+            .unwrap()
+            .convert(n, &[&aligned], &mut [&mut back])
+            .unwrap();
+        assert_eq!(back, src);
+
+        // unsigned 8 bit
+        let u8s = pcm(PcmSample::U8, 1).build();
+        let mut bytes = vec![0u8; 3];
+        let src: Vec<u8> = [0i16, i16::MAX, i16::MIN]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        PcmConverter::new(&i16le, &u8s)
+            .unwrap()
+            .convert(3, &[&src], &mut [&mut bytes])
+            .unwrap();
+        assert_eq!(bytes, [0x80, 0xff, 0x00]);
+
+        // f64 big endian to i32 clips
+        let f64be = pcm(PcmSample::F64, 2).big_endian(true).build();
+        let i32le = pcm(PcmSample::I32, 2).build();
+        let src: Vec<u8> = [1.5f64, -1.5]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let mut ints = vec![0u8; 8];
+        PcmConverter::new(&f64be, &i32le)
+            .unwrap()
+            .convert(1, &[&src], &mut [&mut ints])
+            .unwrap();
+        assert_eq!(ints[..4], i32::MAX.to_le_bytes());
+        assert_eq!(ints[4..], i32::MIN.to_le_bytes());
+    }
+
+    #[test]
+    fn dither() {
+        let f32le = pcm(PcmSample::F32, 1).build();
+        let i16le = pcm(PcmSample::I16, 1).build();
+        let n = 4096;
+        // 0.3 LSB of i16
+        let src: Vec<u8> = (0..n)
+            .flat_map(|_| (0.3f32 / 32768.0).to_le_bytes())
+            .collect();
+        let mut plain = vec![0u8; n * 2];
+        PcmConverter::new(&f32le, &i16le)
+            .unwrap()
+            .convert(n, &[&src], &mut [&mut plain])
+            .unwrap();
+        assert!(plain.iter().all(|b| *b == 0));
+
+        let mut dithered = vec![0u8; n * 2];
+        let mut converter = PcmConverter::new(&f32le, &i16le)
+            .unwrap()
+            .with_dither(Some(Dither::Tpdf));
+        converter.convert(n, &[&src], &mut [&mut dithered]).unwrap();
+        let values: Vec<i16> = dithered
+            .chunks(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        assert!(values.iter().all(|v| (-1..=1).contains(v)));
+        let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n as f64;
+        assert!((mean - 0.3).abs() < 0.05, "{mean}");
+
+        // widening doesn't dither
+        let i32le = pcm(PcmSample::I32, 1).build();
+        let src: Vec<u8> = (0..n).flat_map(|_| 7i16.to_le_bytes()).collect();
+        let mut wide = vec![0u8; n * 4];
+        PcmConverter::new(&i16le, &i32le)
+            .unwrap()
+            .with_dither(Some(Dither::Tpdf))
+            .convert(n, &[&src], &mut [&mut wide])
+            .unwrap();
+        assert!(wide.chunks(4).all(|b| b == (7i32 << 16).to_le_bytes()));
+    }
+
+    #[test]
+    fn errors() {
+        let mono = pcm(PcmSample::I16, 1).build();
+        let stereo = pcm(PcmSample::I16, 2).build();
+        assert_eq!(
+            PcmConverter::new(&mono, &stereo).err(),
+            Some(Error::Unsupported("channel count conversion"))
+        );
+        let mut other_rate = mono;
+        other_rate.sample_rate = 44_100.0;
+        assert_eq!(
+            PcmConverter::new(&mono, &other_rate).err(),
+            Some(Error::Unsupported("sample rate conversion"))
+        );
+        let mut converter = PcmConverter::new(&mono, &mono).unwrap();
+        let mut dst = [0u8; 2];
+        assert_eq!(
+            converter.convert(2, &[&[0; 4]], &mut [&mut dst]),
+            Err(Error::Invalid("buffer size"))
+        );
+        assert_eq!(
+            converter.convert(1, &[&[0; 2], &[0; 2]], &mut [&mut dst]),
+            Err(Error::Invalid("buffer count"))
+        );
+    }
+}
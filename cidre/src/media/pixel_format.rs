@@ -0,0 +1,1930 @@
+//! Pixel format layouts without CoreVideo or Metal
+//!
+//! Static descriptors for every `cv::PixelFormat` and `mtl::PixelFormat` constant,
+//! so plane and row sizes can be computed and checked on any target.
+//!
+//! ```
+//! use cidre::media::pixel_format;
+//!
+//! let nv12 = pixel_format::cv_desc(u32::from_be_bytes(*b"420v")).unwrap();
+//! assert_eq!(nv12.plane_count(), 2);
+//! assert_eq!(nv12.bytes_per_row(1, 1921, 64), Some(1984));
+//! assert_eq!(nv12.plane_height(1, 1081), Some(541));
+//! ```
+
+use crate::define_opts;
+
+define_opts!(pub Flags(u32));
+
+impl Flags {
+    /// Red, green and blue components, or some of them
+    pub const RGB: Self = Self(1 << 0);
+
+    /// Luma and chroma components
+    pub const YCBCR: Self = Self(1 << 1);
+
+    /// Single luminance component
+    pub const GRAY: Self = Self(1 << 2);
+
+    /// Has an alpha component
+    pub const ALPHA: Self = Self(1 << 3);
+
+    /// Samples are indices into a color table
+    pub const INDEXED: Self = Self(1 << 4);
+
+    /// Luma and chroma use video range, otherwise samples are full range
+    pub const VIDEO_RANGE: Self = Self(1 << 5);
+
+    /// Floating point components
+    pub const FLOAT: Self = Self(1 << 6);
+
+    /// Integer components that are not normalized
+    pub const INTEGER: Self = Self(1 << 7);
+
+    /// Signed components
+    pub const SIGNED: Self = Self(1 << 8);
+
+    /// Multi-byte samples are stored big endian
+    pub const BIG_ENDIAN: Self = Self(1 << 9);
+
+    /// Color components are sRGB encoded
+    pub const SRGB: Self = Self(1 << 10);
+
+    /// Has a depth component
+    pub const DEPTH: Self = Self(1 << 11);
+
+    /// Has a stencil component
+    pub const STENCIL: Self = Self(1 << 12);
+
+    /// Components go beyond [0, 1], `BGR10_XR` and friends
+    pub const EXTENDED_RANGE: Self = Self(1 << 13);
+}
+
+/// Chroma subsampling of a Y'CbCr format
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Subsampling {
+    /// Chroma at full resolution, also used by non Y'CbCr formats
+    #[default]
+    S444,
+
+    /// Chroma at half horizontal resolution
+    S422,
+
+    /// Chroma at half horizontal and vertical resolution
+    S420,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Compression {
+    #[default]
+    None,
+
+    /// Apple lossless compressed buffer, layout is opaque
+    Lossless,
+
+    /// Apple lossy compressed buffer, layout is opaque
+    Lossy,
+
+    /// Fixed size blocks, BC and ASTC
+    Block,
+}
+
+/// One plane of a pixel format.
+///
+/// A plane is a grid of blocks. A block covers `block_width` × `block_height` samples
+/// of the plane, which is `h_sub` and `v_sub` times smaller than the image.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Plane {
+    pub bits_per_block: u32,
+    pub block_width: u8,
+    pub block_height: u8,
+    pub h_sub: u8,
+    pub v_sub: u8,
+}
+
+impl Plane {
+    /// Width of the plane in samples for an image `width` pixels wide
+    #[inline]
+    pub const fn width(&self, width: usize) -> usize {
+        width.div_ceil(self.h_sub as usize)
+    }
+
+    /// Height of the plane in samples for an image `height` pixels high
+    #[inline]
+    pub const fn height(&self, height: usize) -> usize {
+        height.div_ceil(self.v_sub as usize)
+    }
+
+    /// Rows of blocks for an image `height` pixels high
+    #[inline]
+    pub const fn rows(&self, height: usize) -> usize {
+        self.height(height).div_ceil(self.block_height as usize)
+    }
+
+    /// Unpadded bytes of one row of blocks for an image `width` pixels wide
+    #[inline]
+    pub const fn row_bytes(&self, width: usize) -> usize {
+        let blocks = self.width(width).div_ceil(self.block_width as usize);
+        (blocks * self.bits_per_block as usize).div_ceil(8)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Desc {
+    /// Name without the `kCVPixelFormatType_` or `MTLPixelFormat` prefix
+    pub name: &'static str,
+    pub planes: &'static [Plane],
+    /// Bits of the widest component
+    pub bit_depth: u8,
+    pub subsampling: Subsampling,
+    pub compression: Compression,
+    pub flags: Flags,
+}
+
+impl Desc {
+    #[inline]
+    pub const fn plane_count(&self) -> usize {
+        self.planes.len()
+    }
+
+    #[inline]
+    pub const fn is_planar(&self) -> bool {
+        self.planes.len() > 1
+    }
+
+    #[inline]
+    pub const fn has_alpha(&self) -> bool {
+        self.flags.0 & Flags::ALPHA.0 != 0
+    }
+
+    #[inline]
+    pub const fn is_ycbcr(&self) -> bool {
+        self.flags.0 & Flags::YCBCR.0 != 0
+    }
+
+    #[inline]
+    pub const fn is_rgb(&self) -> bool {
+        self.flags.0 & Flags::RGB.0 != 0
+    }
+
+    #[inline]
+    pub const fn is_gray(&self) -> bool {
+        self.flags.0 & Flags::GRAY.0 != 0
+    }
+
+    #[inline]
+    pub const fn is_full_range(&self) -> bool {
+        self.flags.0 & Flags::VIDEO_RANGE.0 == 0
+    }
+
+    #[inline]
+    pub const fn is_srgb(&self) -> bool {
+        self.flags.0 & Flags::SRGB.0 != 0
+    }
+
+    #[inline]
+    pub const fn is_depth(&self) -> bool {
+        self.flags.0 & Flags::DEPTH.0 != 0
+    }
+
+    #[inline]
+    pub const fn is_stencil(&self) -> bool {
+        self.flags.0 & Flags::STENCIL.0 != 0
+    }
+
+    /// Lossless and lossy compressed buffers have no linear layout
+    #[inline]
+    pub const fn is_opaque(&self) -> bool {
+        matches!(self.compression, Compression::Lossless | Compression::Lossy)
+    }
+
+    #[inline]
+    pub fn plane_width(&self, plane: usize, width: usize) -> Option<usize> {
+        Some(self.planes.get(plane)?.width(width))
+    }
+
+    #[inline]
+    pub fn plane_height(&self, plane: usize, height: usize) -> Option<usize> {
+        Some(self.planes.get(plane)?.height(height))
+    }
+
+    /// Bytes per row of `plane` rounded up to a multiple of `alignment`.
+    ///
+    /// A row is a row of blocks, so for block compressed formats it covers several
+    /// pixel rows. `None` for a missing plane or opaque formats.
+    pub fn bytes_per_row(&self, plane: usize, width: usize, alignment: usize) -> Option<usize> {
+        if self.is_opaque() {
+            return None;
+        }
+        let bytes = self.planes.get(plane)?.row_bytes(width);
+        Some(bytes.next_multiple_of(alignment.max(1)))
+    }
+
+    /// Bytes of `plane` with rows aligned to `alignment`
+    pub fn plane_size(
+        &self,
+        plane: usize,
+        width: usize,
+        height: usize,
+        alignment: usize,
+    ) -> Option<usize> {
+        let bpr = self.bytes_per_row(plane, width, alignment)?;
+        Some(bpr * self.planes[plane].rows(height))
+    }
+
+    /// Bytes of all planes with rows aligned to `alignment`, without padding between planes
+    pub fn size(&self, width: usize, height: usize, alignment: usize) -> Option<usize> {
+        let mut size = 0;
+        for i in 0..self.planes.len() {
+            size += self.plane_size(i, width, height, alignment)?;
+        }
+        Some(size)
+    }
+
+    const fn subsampled(mut self, subsampling: Subsampling) -> Self {
+        self.subsampling = subsampling;
+        self
+    }
+
+    const fn compressed(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+const fn desc(
+    name: &'static str,
+    bit_depth: u8,
+    flags: &[Flags],
+    planes: &'static [Plane],
+) -> Desc {
+    let mut bits = 0;
+    let mut i = 0;
+    while i < flags.len() {
+        bits |= flags[i].0;
+        i += 1;
+    }
+    Desc {
+        name,
+        planes,
+        bit_depth,
+        subsampling: Subsampling::S444,
+        compression: Compression::None,
+        flags: Flags(bits),
+    }
+}
+
+const fn block(bits_per_block: u32, block_width: u8, block_height: u8) -> Plane {
+    Plane {
+        bits_per_block,
+        block_width,
+        block_height,
+        h_sub: 1,
+        v_sub: 1,
+    }
+}
+
+/// Plane with one sample per pixel
+const fn px(bits: u32) -> Plane {
+    block(bits, 1, 1)
+}
+
+/// Interleaved chroma plane, `bits` per sample pair
+const fn chroma(bits: u32, h_sub: u8, v_sub: u8) -> Plane {
+    Plane {
+        bits_per_block: bits,
+        block_width: 1,
+        block_height: 1,
+        h_sub,
+        v_sub,
+    }
+}
+
+const fn fcc(code: &[u8; 4]) -> u32 {
+    u32::from_be_bytes(*code)
+}
+
+/// `cv::PixelFormat` four char codes and their layouts
+pub const CV: &[(u32, Desc)] = &[
+    (0x01, desc("1Monochrome", 1, &[Flags::INDEXED], &[px(1)])),
+    (0x02, desc("2Indexed", 2, &[Flags::INDEXED], &[px(2)])),
+    (0x04, desc("4Indexed", 4, &[Flags::INDEXED], &[px(4)])),
+    (0x08, desc("8Indexed", 8, &[Flags::INDEXED], &[px(8)])),
+    (
+        0x21,
+        desc(
+            "1IndexedGray_WhiteIsZero",
+            1,
+            &[Flags::GRAY, Flags::INDEXED],
+            &[px(1)],
+        ),
+    ),
+    (
+        0x22,
+        desc(
+            "2IndexedGray_WhiteIsZero",
+            2,
+            &[Flags::GRAY, Flags::INDEXED],
+            &[px(2)],
+        ),
+    ),
+    (
+        0x24,
+        desc(
+            "4IndexedGray_WhiteIsZero",
+            4,
+            &[Flags::GRAY, Flags::INDEXED],
+            &[px(4)],
+        ),
+    ),
+    (
+        0x28,
+        desc(
+            "8IndexedGray_WhiteIsZero",
+            8,
+            &[Flags::GRAY, Flags::INDEXED],
+            &[px(8)],
+        ),
+    ),
+    (
+        0x10,
+        desc("16BE555", 5, &[Flags::RGB, Flags::BIG_ENDIAN], &[px(16)]),
+    ),
+    (fcc(b"L555"), desc("16LE555", 5, &[Flags::RGB], &[px(16)])),
+    (
+        fcc(b"5551"),
+        desc("16LE5551", 5, &[Flags::RGB, Flags::ALPHA], &[px(16)]),
+    ),
+    (
+        fcc(b"B565"),
+        desc("16BE565", 6, &[Flags::RGB, Flags::BIG_ENDIAN], &[px(16)]),
+    ),
+    (fcc(b"L565"), desc("16LE565", 6, &[Flags::RGB], &[px(16)])),
+    (0x18, desc("24RGB", 8, &[Flags::RGB], &[px(24)])),
+    (fcc(b"24BG"), desc("24BGR", 8, &[Flags::RGB], &[px(24)])),
+    (
+        0x20,
+        desc("32ARGB", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        fcc(b"BGRA"),
+        desc("32BGRA", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        fcc(b"ABGR"),
+        desc("32ABGR", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        fcc(b"RGBA"),
+        desc("32RGBA", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        fcc(b"b64a"),
+        desc(
+            "64ARGB",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::BIG_ENDIAN],
+            &[px(64)],
+        ),
+    ),
+    (
+        fcc(b"l64r"),
+        desc("64RGBALE", 16, &[Flags::RGB, Flags::ALPHA], &[px(64)]),
+    ),
+    (
+        fcc(b"R10k"),
+        desc("30RGB", 10, &[Flags::RGB, Flags::BIG_ENDIAN], &[px(32)]),
+    ),
+    (
+        fcc(b"r210"),
+        desc(
+            "30RGB_r210",
+            10,
+            &[Flags::RGB, Flags::BIG_ENDIAN, Flags::VIDEO_RANGE],
+            &[px(32)],
+        ),
+    ),
+    (
+        fcc(b"2vuy"),
+        desc(
+            "422YpCbCr8",
+            8,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[block(32, 2, 1)],
+        )
+        .subsampled(Subsampling::S422),
+    ),
+    (
+        fcc(b"yuvs"),
+        desc(
+            "422YpCbCr8_yuvs",
+            8,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[block(32, 2, 1)],
+        )
+        .subsampled(Subsampling::S422),
+    ),
+    (
+        fcc(b"v408"),
+        desc(
+            "4444YpCbCrA8",
+            8,
+            &[Flags::YCBCR, Flags::ALPHA, Flags::VIDEO_RANGE],
+            &[px(32)],
+        ),
+    ),
+    (
+        fcc(b"r408"),
+        desc("4444YpCbCrA8R", 8, &[Flags::YCBCR, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        fcc(b"y408"),
+        desc(
+            "4444AYpCbCr8",
+            8,
+            &[Flags::YCBCR, Flags::ALPHA, Flags::VIDEO_RANGE],
+            &[px(32)],
+        ),
+    ),
+    (
+        fcc(b"y416"),
+        desc(
+            "4444AYpCbCr16",
+            16,
+            &[Flags::YCBCR, Flags::ALPHA, Flags::VIDEO_RANGE],
+            &[px(64)],
+        ),
+    ),
+    (
+        fcc(b"r4fl"),
+        desc(
+            "4444AYpCbCrFloat",
+            32,
+            &[Flags::YCBCR, Flags::ALPHA, Flags::FLOAT],
+            &[px(128)],
+        ),
+    ),
+    (
+        fcc(b"v308"),
+        desc(
+            "444YpCbCr8",
+            8,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(24)],
+        ),
+    ),
+    (
+        fcc(b"v216"),
+        desc(
+            "422YpCbCr16",
+            16,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[block(64, 2, 1)],
+        )
+        .subsampled(Subsampling::S422),
+    ),
+    (
+        fcc(b"v210"),
+        desc(
+            "422YpCbCr10",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[block(128, 6, 1)],
+        )
+        .subsampled(Subsampling::S422),
+    ),
+    (
+        fcc(b"v410"),
+        desc(
+            "444YpCbCr10",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(32)],
+        ),
+    ),
+    (
+        fcc(b"f420"),
+        desc(
+            "420YpCbCr8PlanarFullRange",
+            8,
+            &[Flags::YCBCR],
+            &[px(8), chroma(8, 2, 2), chroma(8, 2, 2)],
+        )
+        .subsampled(Subsampling::S420),
+    ),
+    (
+        fcc(b"a2vy"),
+        desc(
+            "422YpCbCr_4A_8BiPlanar",
+            8,
+            &[Flags::YCBCR, Flags::ALPHA, Flags::VIDEO_RANGE],
+            &[block(32, 2, 1), px(8)],
+        )
+        .subsampled(Subsampling::S422),
+    ),
+    (
+        fcc(b"420v"),
+        desc(
+            "420YpCbCr8BiPlanarVideoRange",
+            8,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(8), chroma(16, 2, 2)],
+        )
+        .subsampled(Subsampling::S420),
+    ),
+    (
+        fcc(b"420f"),
+        desc(
+            "420YpCbCr8BiPlanarFullRange",
+            8,
+            &[Flags::YCBCR],
+            &[px(8), chroma(16, 2, 2)],
+        )
+        .subsampled(Subsampling::S420),
+    ),
+    (
+        fcc(b"x420"),
+        desc(
+            "420YpCbCr10BiPlanarVideoRange",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(16), chroma(32, 2, 2)],
+        )
+        .subsampled(Subsampling::S420),
+    ),
+    (
+        fcc(b"x422"),
+        desc(
+            "422YpCbCr10BiPlanarVideoRange",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(16), chroma(32, 2, 1)],
+        )
+        .subsampled(Subsampling::S422),
+    ),
+    (
+        fcc(b"x444"),
+        desc(
+            "444YpCbCr10BiPlanarVideoRange",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(16), chroma(32, 1, 1)],
+        ),
+    ),
+    (
+        fcc(b"xf20"),
+        desc(
+            "420YpCbCr10BiPlanarFullRange",
+            10,
+            &[Flags::YCBCR],
+            &[px(16), chroma(32, 2, 2)],
+        )
+        .subsampled(Subsampling::S420),
+    ),
+    (
+        fcc(b"xf22"),
+        desc(
+            "422YpCbCr10BiPlanarFullRange",
+            10,
+            &[Flags::YCBCR],
+            &[px(16), chroma(32, 2, 1)],
+        )
+        .subsampled(Subsampling::S422),
+    ),
+    (
+        fcc(b"xf44"),
+        desc(
+            "444YpCbCr10BiPlanarFullRange",
+            10,
+            &[Flags::YCBCR],
+            &[px(16), chroma(32, 1, 1)],
+        ),
+    ),
+    (
+        fcc(b"l10r"),
+        desc(
+            "ARGB2101010LEPacked",
+            10,
+            &[Flags::RGB, Flags::ALPHA],
+            &[px(32)],
+        ),
+    ),
+    (
+        fcc(b"L008"),
+        desc("OneComponent8", 8, &[Flags::GRAY], &[px(8)]),
+    ),
+    (
+        fcc(b"L00h"),
+        desc(
+            "OneComponent16Half",
+            16,
+            &[Flags::GRAY, Flags::FLOAT],
+            &[px(16)],
+        ),
+    ),
+    (
+        fcc(b"L00f"),
+        desc(
+            "OneComponent32Float",
+            32,
+            &[Flags::GRAY, Flags::FLOAT],
+            &[px(32)],
+        ),
+    ),
+    (
+        fcc(b"2C0h"),
+        desc("TwoComponent16Half", 16, &[Flags::FLOAT], &[px(32)]),
+    ),
+    (
+        fcc(b"2C0f"),
+        desc("TwoComponent32Float", 32, &[Flags::FLOAT], &[px(64)]),
+    ),
+    (
+        fcc(b"RGhA"),
+        desc(
+            "64RGBAHalf",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[px(64)],
+        ),
+    ),
+    (
+        fcc(b"RGfA"),
+        desc(
+            "128RGBAFloat",
+            32,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[px(128)],
+        ),
+    ),
+    (
+        fcc(b"&BGA"),
+        desc("Lossless_32BGRA", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)])
+            .compressed(Compression::Lossless),
+    ),
+    (
+        fcc(b"&RhA"),
+        desc(
+            "Lossless_64RGBAHalf",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[px(64)],
+        )
+        .compressed(Compression::Lossless),
+    ),
+    (
+        fcc(b"&8v0"),
+        desc(
+            "Lossless_420YpCbCr8BiPlanarVideoRange",
+            8,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(8), chroma(16, 2, 2)],
+        )
+        .subsampled(Subsampling::S420)
+        .compressed(Compression::Lossless),
+    ),
+    (
+        fcc(b"&8f0"),
+        desc(
+            "Lossless_420YpCbCr8BiPlanarFullRange",
+            8,
+            &[Flags::YCBCR],
+            &[px(8), chroma(16, 2, 2)],
+        )
+        .subsampled(Subsampling::S420)
+        .compressed(Compression::Lossless),
+    ),
+    (
+        fcc(b"&xv0"),
+        desc(
+            "Lossless_420YpCbCr10PackedBiPlanarVideoRange",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(10), chroma(20, 2, 2)],
+        )
+        .subsampled(Subsampling::S420)
+        .compressed(Compression::Lossless),
+    ),
+    (
+        fcc(b"&xv2"),
+        desc(
+            "Lossless_422YpCbCr10PackedBiPlanarVideoRange",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(10), chroma(20, 2, 1)],
+        )
+        .subsampled(Subsampling::S422)
+        .compressed(Compression::Lossless),
+    ),
+    (
+        fcc(b"-BGA"),
+        desc("Lossy_32BGRA", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)])
+            .compressed(Compression::Lossy),
+    ),
+    (
+        fcc(b"-8v0"),
+        desc(
+            "Lossy_420YpCbCr8BiPlanarVideoRange",
+            8,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(8), chroma(16, 2, 2)],
+        )
+        .subsampled(Subsampling::S420)
+        .compressed(Compression::Lossy),
+    ),
+    (
+        fcc(b"-8f0"),
+        desc(
+            "Lossy_420YpCbCr8BiPlanarFullRange",
+            8,
+            &[Flags::YCBCR],
+            &[px(8), chroma(16, 2, 2)],
+        )
+        .subsampled(Subsampling::S420)
+        .compressed(Compression::Lossy),
+    ),
+    (
+        fcc(b"-xv0"),
+        desc(
+            "Lossy_420YpCbCr10PackedBiPlanarVideoRange",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(10), chroma(20, 2, 2)],
+        )
+        .subsampled(Subsampling::S420)
+        .compressed(Compression::Lossy),
+    ),
+    (
+        fcc(b"-xv2"),
+        desc(
+            "Lossy_422YpCbCr10PackedBiPlanarVideoRange",
+            10,
+            &[Flags::YCBCR, Flags::VIDEO_RANGE],
+            &[px(10), chroma(20, 2, 1)],
+        )
+        .subsampled(Subsampling::S422)
+        .compressed(Compression::Lossy),
+    ),
+];
+
+/// Raw `mtl::PixelFormat` values and their layouts
+pub const MTL: &[(usize, Desc)] = &[
+    (1, desc("A8Unorm", 8, &[Flags::ALPHA], &[px(8)])),
+    (10, desc("R8Unorm", 8, &[Flags::RGB], &[px(8)])),
+    (
+        11,
+        desc("R8Unorm_sRGB", 8, &[Flags::RGB, Flags::SRGB], &[px(8)]),
+    ),
+    (
+        12,
+        desc("R8Snorm", 8, &[Flags::RGB, Flags::SIGNED], &[px(8)]),
+    ),
+    (
+        13,
+        desc("R8Uint", 8, &[Flags::RGB, Flags::INTEGER], &[px(8)]),
+    ),
+    (
+        14,
+        desc(
+            "R8Sint",
+            8,
+            &[Flags::RGB, Flags::INTEGER, Flags::SIGNED],
+            &[px(8)],
+        ),
+    ),
+    (20, desc("R16Unorm", 16, &[Flags::RGB], &[px(16)])),
+    (
+        22,
+        desc("R16Snorm", 16, &[Flags::RGB, Flags::SIGNED], &[px(16)]),
+    ),
+    (
+        23,
+        desc("R16Uint", 16, &[Flags::RGB, Flags::INTEGER], &[px(16)]),
+    ),
+    (
+        24,
+        desc(
+            "R16Sint",
+            16,
+            &[Flags::RGB, Flags::INTEGER, Flags::SIGNED],
+            &[px(16)],
+        ),
+    ),
+    (
+        25,
+        desc(
+            "R16Float",
+            16,
+            &[Flags::RGB, Flags::FLOAT, Flags::SIGNED],
+            &[px(16)],
+        ),
+    ),
+    (30, desc("RG8Unorm", 8, &[Flags::RGB], &[px(16)])),
+    (
+        31,
+        desc("RG8Unorm_sRGB", 8, &[Flags::RGB, Flags::SRGB], &[px(16)]),
+    ),
+    (
+        32,
+        desc("RG8Snorm", 8, &[Flags::RGB, Flags::SIGNED], &[px(16)]),
+    ),
+    (
+        33,
+        desc("RG8Uint", 8, &[Flags::RGB, Flags::INTEGER], &[px(16)]),
+    ),
+    (
+        34,
+        desc(
+            "RG8Sint",
+            8,
+            &[Flags::RGB, Flags::INTEGER, Flags::SIGNED],
+            &[px(16)],
+        ),
+    ),
+    (40, desc("B5G6R5Unorm", 6, &[Flags::RGB], &[px(16)])),
+    (
+        41,
+        desc("A1BGR5Unorm", 5, &[Flags::RGB, Flags::ALPHA], &[px(16)]),
+    ),
+    (
+        42,
+        desc("ABGR4Unorm", 4, &[Flags::RGB, Flags::ALPHA], &[px(16)]),
+    ),
+    (
+        43,
+        desc("BGR5A1Unorm", 5, &[Flags::RGB, Flags::ALPHA], &[px(16)]),
+    ),
+    (
+        53,
+        desc("R32Uint", 32, &[Flags::RGB, Flags::INTEGER], &[px(32)]),
+    ),
+    (
+        54,
+        desc(
+            "R32Sint",
+            32,
+            &[Flags::RGB, Flags::INTEGER, Flags::SIGNED],
+            &[px(32)],
+        ),
+    ),
+    (
+        55,
+        desc(
+            "R32Float",
+            32,
+            &[Flags::RGB, Flags::FLOAT, Flags::SIGNED],
+            &[px(32)],
+        ),
+    ),
+    (60, desc("RG16Unorm", 16, &[Flags::RGB], &[px(32)])),
+    (
+        62,
+        desc("RG16Snorm", 16, &[Flags::RGB, Flags::SIGNED], &[px(32)]),
+    ),
+    (
+        63,
+        desc("RG16Uint", 16, &[Flags::RGB, Flags::INTEGER], &[px(32)]),
+    ),
+    (
+        64,
+        desc(
+            "RG16Sint",
+            16,
+            &[Flags::RGB, Flags::INTEGER, Flags::SIGNED],
+            &[px(32)],
+        ),
+    ),
+    (
+        65,
+        desc(
+            "RG16Float",
+            16,
+            &[Flags::RGB, Flags::FLOAT, Flags::SIGNED],
+            &[px(32)],
+        ),
+    ),
+    (
+        70,
+        desc("RGBA8Unorm", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        71,
+        desc(
+            "RGBA8Unorm_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[px(32)],
+        ),
+    ),
+    (
+        72,
+        desc(
+            "RGBA8Snorm",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SIGNED],
+            &[px(32)],
+        ),
+    ),
+    (
+        73,
+        desc(
+            "RGBA8Uint",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::INTEGER],
+            &[px(32)],
+        ),
+    ),
+    (
+        74,
+        desc(
+            "RGBA8Sint",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::INTEGER, Flags::SIGNED],
+            &[px(32)],
+        ),
+    ),
+    (
+        80,
+        desc("BGRA8Unorm", 8, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        81,
+        desc(
+            "BGRA8Unorm_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[px(32)],
+        ),
+    ),
+    (
+        90,
+        desc("RGB10A2Unorm", 10, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        91,
+        desc(
+            "RGB10A2Uint",
+            10,
+            &[Flags::RGB, Flags::ALPHA, Flags::INTEGER],
+            &[px(32)],
+        ),
+    ),
+    (
+        92,
+        desc("RG11B10Float", 11, &[Flags::RGB, Flags::FLOAT], &[px(32)]),
+    ),
+    (
+        93,
+        desc("RGB9E5Float", 9, &[Flags::RGB, Flags::FLOAT], &[px(32)]),
+    ),
+    (
+        94,
+        desc("BGR10A2Unorm", 10, &[Flags::RGB, Flags::ALPHA], &[px(32)]),
+    ),
+    (
+        554,
+        desc(
+            "BGR10_XR",
+            10,
+            &[Flags::RGB, Flags::EXTENDED_RANGE, Flags::SIGNED],
+            &[px(32)],
+        ),
+    ),
+    (
+        555,
+        desc(
+            "BGR10_XR_sRGB",
+            10,
+            &[
+                Flags::RGB,
+                Flags::EXTENDED_RANGE,
+                Flags::SIGNED,
+                Flags::SRGB,
+            ],
+            &[px(32)],
+        ),
+    ),
+    (
+        103,
+        desc("RG32Uint", 32, &[Flags::RGB, Flags::INTEGER], &[px(64)]),
+    ),
+    (
+        104,
+        desc(
+            "RG32Sint",
+            32,
+            &[Flags::RGB, Flags::INTEGER, Flags::SIGNED],
+            &[px(64)],
+        ),
+    ),
+    (
+        105,
+        desc(
+            "RG32Float",
+            32,
+            &[Flags::RGB, Flags::FLOAT, Flags::SIGNED],
+            &[px(64)],
+        ),
+    ),
+    (
+        110,
+        desc("RGBA16Unorm", 16, &[Flags::RGB, Flags::ALPHA], &[px(64)]),
+    ),
+    (
+        112,
+        desc(
+            "RGBA16Snorm",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::SIGNED],
+            &[px(64)],
+        ),
+    ),
+    (
+        113,
+        desc(
+            "RGBA16Uint",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::INTEGER],
+            &[px(64)],
+        ),
+    ),
+    (
+        114,
+        desc(
+            "RGBA16Sint",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::INTEGER, Flags::SIGNED],
+            &[px(64)],
+        ),
+    ),
+    (
+        115,
+        desc(
+            "RGBA16Float",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT, Flags::SIGNED],
+            &[px(64)],
+        ),
+    ),
+    (
+        552,
+        desc(
+            "BGRA10_XR",
+            10,
+            &[
+                Flags::RGB,
+                Flags::ALPHA,
+                Flags::EXTENDED_RANGE,
+                Flags::SIGNED,
+            ],
+            &[px(64)],
+        ),
+    ),
+    (
+        553,
+        desc(
+            "BGRA10_XR_sRGB",
+            10,
+            &[
+                Flags::RGB,
+                Flags::ALPHA,
+                Flags::EXTENDED_RANGE,
+                Flags::SIGNED,
+                Flags::SRGB,
+            ],
+            &[px(64)],
+        ),
+    ),
+    (
+        123,
+        desc(
+            "RGBA32Uint",
+            32,
+            &[Flags::RGB, Flags::ALPHA, Flags::INTEGER],
+            &[px(128)],
+        ),
+    ),
+    (
+        124,
+        desc(
+            "RGBA32Sint",
+            32,
+            &[Flags::RGB, Flags::ALPHA, Flags::INTEGER, Flags::SIGNED],
+            &[px(128)],
+        ),
+    ),
+    (
+        125,
+        desc(
+            "RGBA32Float",
+            32,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT, Flags::SIGNED],
+            &[px(128)],
+        ),
+    ),
+    (
+        130,
+        desc(
+            "BC1_RGBA",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(64, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        131,
+        desc(
+            "BC1_RGBA_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(64, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        132,
+        desc(
+            "BC2_RGBA",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        133,
+        desc(
+            "BC2_RGBA_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        134,
+        desc(
+            "BC3_RGBA",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        135,
+        desc(
+            "BC3_RGBA_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        150,
+        desc(
+            "BC6H_RGBFloat",
+            16,
+            &[Flags::RGB, Flags::FLOAT, Flags::SIGNED],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        151,
+        desc(
+            "BC6H_RGBUfloat",
+            16,
+            &[Flags::RGB, Flags::FLOAT],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        152,
+        desc(
+            "BC7_RGBAUnorm",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        153,
+        desc(
+            "BC7_RGBAUnorm_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        186,
+        desc(
+            "ASTC_4x4_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        187,
+        desc(
+            "ASTC_5x4_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 5, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        188,
+        desc(
+            "ASTC_5x5_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 5, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        189,
+        desc(
+            "ASTC_6x5_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 6, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        190,
+        desc(
+            "ASTC_6x6_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 6, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        192,
+        desc(
+            "ASTC_8x5_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 8, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        193,
+        desc(
+            "ASTC_8x6_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 8, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        194,
+        desc(
+            "ASTC_8x8_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 8, 8)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        195,
+        desc(
+            "ASTC_10x5_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 10, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        196,
+        desc(
+            "ASTC_10x6_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 10, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        197,
+        desc(
+            "ASTC_10x8_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 10, 8)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        198,
+        desc(
+            "ASTC_10x10_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 10, 10)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        199,
+        desc(
+            "ASTC_12x10_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 12, 10)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        200,
+        desc(
+            "ASTC_12x12_sRGB",
+            8,
+            &[Flags::RGB, Flags::ALPHA, Flags::SRGB],
+            &[block(128, 12, 12)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        204,
+        desc(
+            "ASTC_4x4_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        205,
+        desc(
+            "ASTC_5x4_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 5, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        206,
+        desc(
+            "ASTC_5x5_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 5, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        207,
+        desc(
+            "ASTC_6x5_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 6, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        208,
+        desc(
+            "ASTC_6x6_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 6, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        210,
+        desc(
+            "ASTC_8x5_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 8, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        211,
+        desc(
+            "ASTC_8x6_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 8, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        212,
+        desc(
+            "ASTC_8x8_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 8, 8)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        213,
+        desc(
+            "ASTC_10x5_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 10, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        214,
+        desc(
+            "ASTC_10x6_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 10, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        215,
+        desc(
+            "ASTC_10x8_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 10, 8)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        216,
+        desc(
+            "ASTC_10x10_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 10, 10)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        217,
+        desc(
+            "ASTC_12x10_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 12, 10)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        218,
+        desc(
+            "ASTC_12x12_LDR",
+            8,
+            &[Flags::RGB, Flags::ALPHA],
+            &[block(128, 12, 12)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        222,
+        desc(
+            "ASTC_4x4_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 4, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        223,
+        desc(
+            "ASTC_5x4_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 5, 4)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        224,
+        desc(
+            "ASTC_5x5_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 5, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        225,
+        desc(
+            "ASTC_6x5_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 6, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        226,
+        desc(
+            "ASTC_6x6_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 6, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        228,
+        desc(
+            "ASTC_8x5_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 8, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        229,
+        desc(
+            "ASTC_8x6_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 8, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        230,
+        desc(
+            "ASTC_8x8_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 8, 8)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        231,
+        desc(
+            "ASTC_10x5_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 10, 5)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        232,
+        desc(
+            "ASTC_10x6_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 10, 6)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        233,
+        desc(
+            "ASTC_10x8_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 10, 8)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        234,
+        desc(
+            "ASTC_10x10_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 10, 10)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        235,
+        desc(
+            "ASTC_12x10_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 12, 10)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        236,
+        desc(
+            "ASTC_12x12_HDR",
+            16,
+            &[Flags::RGB, Flags::ALPHA, Flags::FLOAT],
+            &[block(128, 12, 12)],
+        )
+        .compressed(Compression::Block),
+    ),
+    (
+        240,
+        desc("GBGR422", 8, &[Flags::YCBCR], &[block(32, 2, 1)]).subsampled(Subsampling::S422),
+    ),
+    (
+        241,
+        desc("BGRG422", 8, &[Flags::YCBCR], &[block(32, 2, 1)]).subsampled(Subsampling::S422),
+    ),
+    (250, desc("Depth16Unorm", 16, &[Flags::DEPTH], &[px(16)])),
+    (
+        252,
+        desc("Depth32Float", 32, &[Flags::DEPTH, Flags::FLOAT], &[px(32)]),
+    ),
+    (
+        253,
+        desc("Stencil8", 8, &[Flags::STENCIL, Flags::INTEGER], &[px(8)]),
+    ),
+    (
+        255,
+        desc(
+            "Depth24Unorm_Stencil8",
+            24,
+            &[Flags::DEPTH, Flags::STENCIL],
+            &[px(32)],
+        ),
+    ),
+    (
+        260,
+        desc(
+            "Depth32Float_Stencil8",
+            32,
+            &[Flags::DEPTH, Flags::STENCIL, Flags::FLOAT],
+            &[px(64)],
+        ),
+    ),
+    (
+        261,
+        desc(
+            "X32_Stencil8",
+            8,
+            &[Flags::STENCIL, Flags::INTEGER],
+            &[px(64)],
+        ),
+    ),
+    (
+        262,
+        desc(
+            "X24_Stencil8",
+            8,
+            &[Flags::STENCIL, Flags::INTEGER],
+            &[px(32)],
+        ),
+    ),
+];
+
+const R8_UNORM: usize = 10;
+const R16_UNORM: usize = 20;
+const R16_FLOAT: usize = 25;
+const RG8_UNORM: usize = 30;
+const B5G6R5_UNORM: usize = 40;
+const R32_FLOAT: usize = 55;
+const RG16_UNORM: usize = 60;
+const RG16_FLOAT: usize = 65;
+const RGBA8_UNORM: usize = 70;
+const BGRA8_UNORM: usize = 80;
+const BGR10A2_UNORM: usize = 94;
+const RG32_FLOAT: usize = 105;
+const RGBA16_UNORM: usize = 110;
+const RGBA16_FLOAT: usize = 115;
+const RGBA32_FLOAT: usize = 125;
+const GBGR422: usize = 240;
+const BGRG422: usize = 241;
+
+/// Metal format of each plane of a `cv::PixelFormat` when wrapped by `cv::MetalTextureCache`.
+///
+/// Formats whose only plane maps 1:1 come first, so [`cv_for_mtl`] finds them.
+pub const CV_MTL: &[(u32, &[usize])] = &[
+    (fcc(b"BGRA"), &[BGRA8_UNORM]),
+    (fcc(b"RGBA"), &[RGBA8_UNORM]),
+    (fcc(b"l64r"), &[RGBA16_UNORM]),
+    (fcc(b"RGhA"), &[RGBA16_FLOAT]),
+    (fcc(b"RGfA"), &[RGBA32_FLOAT]),
+    (fcc(b"l10r"), &[BGR10A2_UNORM]),
+    (fcc(b"L565"), &[B5G6R5_UNORM]),
+    (fcc(b"L008"), &[R8_UNORM]),
+    (fcc(b"L00h"), &[R16_FLOAT]),
+    (fcc(b"L00f"), &[R32_FLOAT]),
+    (fcc(b"2C0h"), &[RG16_FLOAT]),
+    (fcc(b"2C0f"), &[RG32_FLOAT]),
+    (fcc(b"2vuy"), &[BGRG422]),
+    (fcc(b"yuvs"), &[GBGR422]),
+    (fcc(b"v408"), &[RGBA8_UNORM]),
+    (fcc(b"r408"), &[RGBA8_UNORM]),
+    (fcc(b"y408"), &[RGBA8_UNORM]),
+    (fcc(b"y416"), &[RGBA16_UNORM]),
+    (fcc(b"r4fl"), &[RGBA32_FLOAT]),
+    (fcc(b"420v"), &[R8_UNORM, RG8_UNORM]),
+    (fcc(b"420f"), &[R8_UNORM, RG8_UNORM]),
+    (fcc(b"f420"), &[R8_UNORM, R8_UNORM, R8_UNORM]),
+    (fcc(b"a2vy"), &[BGRG422, R8_UNORM]),
+    (fcc(b"x420"), &[R16_UNORM, RG16_UNORM]),
+    (fcc(b"x422"), &[R16_UNORM, RG16_UNORM]),
+    (fcc(b"x444"), &[R16_UNORM, RG16_UNORM]),
+    (fcc(b"xf20"), &[R16_UNORM, RG16_UNORM]),
+    (fcc(b"xf22"), &[R16_UNORM, RG16_UNORM]),
+    (fcc(b"xf44"), &[R16_UNORM, RG16_UNORM]),
+];
+
+/// Layout of a `cv::PixelFormat` four char code
+pub fn cv_desc(code: u32) -> Option<&'static Desc> {
+    CV.iter().find(|(c, _)| *c == code).map(|(_, d)| d)
+}
+
+/// Layout of a raw `mtl::PixelFormat` value
+pub fn mtl_desc(raw: usize) -> Option<&'static Desc> {
+    MTL.iter().find(|(r, _)| *r == raw).map(|(_, d)| d)
+}
+
+/// Raw Metal formats to view each plane of `code` as a texture
+pub fn mtl_planes(code: u32) -> Option<&'static [usize]> {
+    CV_MTL.iter().find(|(c, _)| *c == code).map(|(_, p)| *p)
+}
+
+/// Single plane `cv::PixelFormat` with the same layout as Metal format `raw`
+pub fn cv_for_mtl(raw: usize) -> Option<u32> {
+    CV_MTL.iter().find(|(_, p)| *p == [raw]).map(|(c, _)| *c)
+}
+
+#[cfg(feature = "cv")]
+impl crate::cv::PixelFormat {
+    /// Static layout, no CoreVideo call
+    #[inline]
+    pub fn desc(&self) -> Option<&'static Desc> {
+        cv_desc(self.0)
+    }
+}
+
+#[cfg(feature = "mtl")]
+impl crate::mtl::PixelFormat {
+    /// Static layout, no device needed
+    #[inline]
+    pub fn desc(&self) -> Option<&'static Desc> {
+        mtl_desc(*self as usize)
+    }
+}
+
+#[cfg(all(feature = "cv", feature = "mtl"))]
+impl crate::cv::PixelFormat {
+    /// Metal format of each plane, as used by `cv::MetalTextureCache`
+    pub fn mtl_planes(&self) -> Option<impl Iterator<Item = crate::mtl::PixelFormat>> {
+        let planes = mtl_planes(self.0)?;
+        // SAFETY: CV_MTL only holds values of `mtl::PixelFormat` variants, checked by tests
+        Some(
+            planes
+                .iter()
+                .map(|raw| unsafe { std::mem::transmute::<usize, crate::mtl::PixelFormat>(*raw) }),
+        )
+    }
+}
+
+#[cfg(all(feature = "cv", feature = "mtl"))]
+impl crate::mtl::PixelFormat {
+    /// Single plane CoreVideo format with the same layout
+    pub fn cv_pixel_format(&self) -> Option<crate::cv::PixelFormat> {
+        cv_for_mtl(*self as usize).map(crate::cv::PixelFormat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cv(code: &[u8; 4]) -> &'static Desc {
+        cv_desc(fcc(code)).unwrap()
+    }
+
+    #[test]
+    fn tables() {
+        for (i, (code, d)) in CV.iter().enumerate() {
+            assert!(CV[..i].iter().all(|(c, _)| c != code), "{}", d.name);
+            assert!(!d.planes.is_empty(), "{}", d.name);
+            assert_eq!(d.subsampling != Subsampling::S444, {
+                d.planes.iter().any(|p| p.h_sub > 1) || d.planes[0].block_width > 1
+            });
+        }
+        for (i, (raw, d)) in MTL.iter().enumerate() {
+            assert!(MTL[..i].iter().all(|(r, _)| r != raw), "{}", d.name);
+            assert_eq!(d.plane_count(), 1, "{}", d.name);
+            assert_eq!(
+                d.compression == Compression::Block,
+                d.planes[0].block_height > 1,
+                "{}",
+                d.name
+            );
+        }
+        assert_eq!(MTL.len(), 116);
+        assert!(mtl_desc(0).is_none());
+    }
+
+    #[test]
+    fn mapping() {
+        for (code, planes) in CV_MTL {
+            let d = cv_desc(*code).unwrap();
+            assert_eq!(d.plane_count(), planes.len(), "{}", d.name);
+            for (i, raw) in planes.iter().enumerate() {
+                let m = mtl_desc(*raw).unwrap();
+                // a texel of the Metal view is a block of the plane
+                let p = &d.planes[i];
+                assert_eq!(m.planes[0].bits_per_block, p.bits_per_block, "{}", d.name);
+                assert_eq!(m.planes[0].block_width, p.block_width, "{}", d.name);
+                for w in [1, 2, 7, 1920, 1921] {
+                    assert_eq!(
+                        m.bytes_per_row(0, p.width(w), 64),
+                        d.bytes_per_row(i, w, 64),
+                        "{}",
+                        d.name
+                    );
+                }
+            }
+        }
+        assert_eq!(mtl_planes(fcc(b"420v")), Some(&[10, 30][..]));
+        assert_eq!(mtl_planes(fcc(b"&8v0")), None);
+        assert_eq!(cv_for_mtl(80), Some(fcc(b"BGRA")));
+        assert_eq!(cv_for_mtl(70), Some(fcc(b"RGBA")));
+        assert_eq!(cv_for_mtl(10), Some(fcc(b"L008")));
+        assert_eq!(cv_for_mtl(30), None);
+    }
+
+    #[test]
+    fn sizes() {
+        let bgra = cv(b"BGRA");
+        assert_eq!(bgra.bytes_per_row(0, 1920, 64), Some(7680));
+        assert_eq!(bgra.size(1920, 1080, 64), Some(7680 * 1080));
+        assert_eq!(bgra.bytes_per_row(1, 1920, 64), None);
+
+        let nv12 = cv(b"420v");
+        assert!(nv12.is_ycbcr() && !nv12.is_full_range() && nv12.is_planar());
+        assert_eq!(nv12.plane_width(1, 1921), Some(961));
+        assert_eq!(nv12.bytes_per_row(0, 1921, 16), Some(1936));
+        assert_eq!(nv12.bytes_per_row(1, 1921, 16), Some(1936));
+        assert_eq!(nv12.size(1921, 1081, 16), Some(1936 * 1081 + 1936 * 541));
+        assert!(cv(b"420f").is_full_range());
+
+        let p010 = cv(b"x420");
+        assert_eq!(p010.bytes_per_row(0, 1920, 1), Some(3840));
+        assert_eq!(p010.plane_size(1, 1920, 1080, 1), Some(3840 * 540));
+
+        let i420 = cv(b"f420");
+        assert_eq!(i420.size(4, 4, 1), Some(16 + 4 + 4));
+        assert_eq!(i420.size(5, 5, 1), Some(25 + 9 + 9));
+
+        assert_eq!(cv(b"2vuy").bytes_per_row(0, 1921, 1), Some(3844));
+        assert_eq!(cv(b"v210").bytes_per_row(0, 1920, 128), Some(5120));
+        assert_eq!(cv(b"v210").bytes_per_row(0, 1280, 128), Some(3456));
+        assert_eq!(cv(b"a2vy").size(2, 2, 1), Some(8 + 4));
+
+        assert_eq!(cv_desc(1).unwrap().bytes_per_row(0, 9, 0), Some(2));
+        assert_eq!(cv_desc(0x24).unwrap().bytes_per_row(0, 3, 1), Some(2));
+
+        let lossless = cv(b"&8v0");
+        assert!(lossless.is_opaque());
+        assert_eq!(lossless.bytes_per_row(0, 1920, 64), None);
+        assert_eq!(lossless.size(1920, 1080, 64), None);
+        assert_eq!(lossless.plane_height(1, 1080), Some(540));
+    }
+
+    #[test]
+    fn mtl_sizes() {
+        let astc = mtl_desc(190).unwrap();
+        assert_eq!(astc.name, "ASTC_6x6_sRGB");
+        assert!(astc.is_srgb());
+        assert_eq!(astc.bytes_per_row(0, 100, 1), Some(17 * 16));
+        assert_eq!(astc.size(100, 100, 1), Some(17 * 17 * 16));
+
+        let bc1 = mtl_desc(130).unwrap();
+        assert_eq!(bc1.size(6, 6, 1), Some(4 * 8));
+
+        let d24s8 = mtl_desc(255).unwrap();
+        assert!(d24s8.is_depth() && d24s8.is_stencil());
+        assert_eq!(d24s8.bytes_per_row(0, 3, 256), Some(256));
+
+        let rgb9e5 = mtl_desc(93).unwrap();
+        assert_eq!(rgb9e5.bytes_per_row(0, 10, 1), Some(40));
+        assert!(!rgb9e5.has_alpha());
+    }
+}
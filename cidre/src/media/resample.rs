@@ -0,0 +1,797 @@
+//! Band-limited sample rate conversion without AudioToolbox
+//!
+//! Kaiser windowed sinc. When the rate ratio reduces to at most [`MAX_EXACT_PHASES`]
+//! phases every phase gets its own row of taps, otherwise rows are interpolated.
+
+use std::f64::consts::PI;
+
+#[cfg(feature = "cat")]
+use crate::cat::audio::BufList;
+
+use super::{Error, Result};
+
+/// Largest phase count of a filter bank computed for the exact ratio
+pub const MAX_EXACT_PHASES: u64 = 1024;
+
+/// Largest ratio between input and output rates in either direction
+const MAX_RATIO: f64 = 256.0;
+
+/// Converter quality, same scale as `at::audio::ConverterQuality`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[repr(transparent)]
+pub struct Quality(pub u32);
+
+impl Quality {
+    /// 140 dB stopband, passband to 95% of Nyquist
+    #[doc(alias = "kAudioConverterQuality_Max")]
+    pub const MAX: Self = Self(0x7F);
+
+    /// 120 dB stopband, passband to 93% of Nyquist
+    #[doc(alias = "kAudioConverterQuality_High")]
+    pub const HIGH: Self = Self(0x60);
+
+    /// 100 dB stopband, passband to 90% of Nyquist
+    #[doc(alias = "kAudioConverterQuality_Medium")]
+    pub const MEDIUM: Self = Self(0x40);
+
+    /// 80 dB stopband, passband to 85% of Nyquist
+    #[doc(alias = "kAudioConverterQuality_Low")]
+    pub const LOW: Self = Self(0x20);
+
+    /// 60 dB stopband, passband to 80% of Nyquist
+    #[doc(alias = "kAudioConverterQuality_Min")]
+    pub const MIN: Self = Self(0);
+
+    fn design(self, complexity: Complexity) -> Design {
+        let (pass, atten, phases) = match self {
+            q if q >= Self::MAX => (0.95, 140.0, 1024),
+            q if q >= Self::HIGH => (0.93, 120.0, 1024),
+            q if q >= Self::MEDIUM => (0.90, 100.0, 512),
+            q if q >= Self::LOW => (0.85, 80.0, 256),
+            _ => (0.80, 60.0, 128),
+        };
+        let atten = if complexity == Complexity::Mastering {
+            atten + 20.0
+        } else {
+            atten
+        };
+        Design {
+            pass,
+            atten,
+            phases,
+        }
+    }
+}
+
+impl Default for Quality {
+    fn default() -> Self {
+        Self::MEDIUM
+    }
+}
+
+#[cfg(feature = "at")]
+impl From<crate::at::audio::ConverterQuality> for Quality {
+    fn from(value: crate::at::audio::ConverterQuality) -> Self {
+        Self(value.0)
+    }
+}
+
+/// Filter family, mirrors `at::audio::SampleRateConverterComplexity`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Complexity {
+    /// Linear interpolation without anti-aliasing, quality is ignored.
+    #[doc(alias = "kAudioConverterSampleRateConverterComplexity_Linear")]
+    Linear,
+
+    /// Linear phase windowed sinc.
+    #[default]
+    #[doc(alias = "kAudioConverterSampleRateConverterComplexity_Normal")]
+    Normal,
+
+    /// Longer windowed sinc with 20 dB more stopband attenuation than `Normal`.
+    #[doc(alias = "kAudioConverterSampleRateConverterComplexity_Mastering")]
+    Mastering,
+}
+
+#[cfg(feature = "at")]
+impl TryFrom<crate::at::audio::SampleRateConverterComplexity> for Complexity {
+    type Error = Error;
+
+    fn try_from(value: crate::at::audio::SampleRateConverterComplexity) -> Result<Self> {
+        use crate::at::audio::SampleRateConverterComplexity as C;
+        match value {
+            C::LINEAR => Ok(Self::Linear),
+            C::NORMAL => Ok(Self::Normal),
+            C::MASTERING => Ok(Self::Mastering),
+            _ => Err(Error::Unsupported("sample rate converter complexity")),
+        }
+    }
+}
+
+/// How the filter history is filled at start, see `at::audio::ConverterPrimeMethod`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PrimeMethod {
+    /// Input starts with `leading_frames` frames preceding the desired start.
+    Pre,
+
+    /// Leading frames are silence, output is aligned with input.
+    #[default]
+    Normal,
+
+    /// Leading and trailing frames are silence, output is late by `trailing_frames`.
+    None,
+}
+
+#[cfg(feature = "at")]
+impl From<crate::at::audio::ConverterPrimeMethod> for PrimeMethod {
+    fn from(value: crate::at::audio::ConverterPrimeMethod) -> Self {
+        use crate::at::audio::ConverterPrimeMethod as M;
+        match value {
+            M::Pre => Self::Pre,
+            M::Normal => Self::Normal,
+            M::None => Self::None,
+        }
+    }
+}
+
+/// Input frames the filter reads around each output frame.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PrimeInfo {
+    pub leading_frames: u32,
+    pub trailing_frames: u32,
+}
+
+#[cfg(feature = "at")]
+impl From<PrimeInfo> for crate::at::audio::ConverterPrimeInfo {
+    fn from(value: PrimeInfo) -> Self {
+        Self {
+            leading_frames: value.leading_frames,
+            trailing_frames: value.trailing_frames,
+        }
+    }
+}
+
+struct Design {
+    /// Passband edge relative to the lower Nyquist frequency
+    pass: f64,
+    /// Stopband attenuation in dB
+    atten: f64,
+    /// Rows of an interpolated bank
+    phases: u64,
+}
+
+/// Streaming sample rate converter for native `f32` samples.
+///
+/// ```
+/// use cidre::media::resample::{Complexity, Quality, Resampler};
+///
+/// let mut src = Resampler::new(48_000.0, 44_100.0, 1, Quality::HIGH, Complexity::Normal).unwrap();
+/// let input = vec![0.0f32; 4800];
+/// let mut output = vec![0.0f32; 4410];
+/// let mut n = src.process(&input, &mut output);
+/// n += src.flush(&mut output[n..]);
+/// assert_eq!(n, 4410);
+/// ```
+pub struct Resampler {
+    in_rate: f64,
+    out_rate: f64,
+    channels: usize,
+    prime_method: PrimeMethod,
+    /// Input frames on each side of an output instant
+    half: usize,
+    /// Rows of `2 * half` taps, oldest input frame first
+    bank: Vec<f32>,
+    /// One row per phase, otherwise `phases + 1` rows to interpolate between
+    exact: bool,
+    phases: u64,
+    /// Input frames advanced per output frame, `step + step_frac / den`
+    den: u64,
+    step: usize,
+    step_frac: u64,
+    /// Position of the next output frame in `buf`
+    pos: usize,
+    pos_frac: u64,
+    /// Interleaved input from the oldest frame still read
+    buf: Vec<f32>,
+    taps: Vec<f32>,
+    acc: Vec<f32>,
+    input_frames: u64,
+    output_frames: u64,
+    /// Total output frames once flushed
+    end: Option<u64>,
+}
+
+impl Resampler {
+    pub fn new(
+        in_rate: f64,
+        out_rate: f64,
+        channels: u32,
+        quality: Quality,
+        complexity: Complexity,
+    ) -> Result<Self> {
+        if !(in_rate.is_finite() && in_rate > 0.0 && out_rate.is_finite() && out_rate > 0.0) {
+            return Err(Error::Invalid("sample rate"));
+        }
+        if in_rate / out_rate > MAX_RATIO || out_rate / in_rate > MAX_RATIO {
+            return Err(Error::Unsupported("sample rate ratio"));
+        }
+        if channels == 0 {
+            return Err(Error::Invalid("channel count"));
+        }
+
+        let design = quality.design(complexity);
+        let narrow = (out_rate / in_rate).min(1.0);
+        // half length and cutoff in input frames
+        let (half, kernel): (f64, Box<dyn Fn(f64) -> f64>) = if complexity == Complexity::Linear {
+            (1.0, Box::new(|x: f64| 1.0 - x.abs()))
+        } else {
+            // Kaiser's estimate with the transition band ending at the lower Nyquist frequency
+            let transition = (1.0 - design.pass) * 0.5;
+            let taps = (design.atten - 8.0) / (2.285 * 2.0 * PI * transition);
+            let half = taps * 0.5 / narrow;
+            let cutoff = 0.25 * (1.0 + design.pass) * narrow;
+            let beta = 0.1102 * (design.atten - 8.7);
+            let i0_beta = bessel_i0(beta);
+            let kernel = move |x: f64| {
+                let r = x / half;
+                if r.abs() >= 1.0 {
+                    return 0.0;
+                }
+                let a = 2.0 * PI * cutoff * x;
+                let sinc = if a == 0.0 { 1.0 } else { a.sin() / a };
+                sinc * bessel_i0(beta * (1.0 - r * r).sqrt()) / i0_beta
+            };
+            (half, Box::new(kernel))
+        };
+        let half = half.ceil() as usize;
+
+        let exact = rational(in_rate, out_rate).filter(|(_, den)| *den <= MAX_EXACT_PHASES);
+        let (den, step, step_frac, rows, phases) = match exact {
+            Some((num, den)) => (den, num / den, num % den, den, den),
+            None => {
+                let step = (in_rate / out_rate * (1u64 << 32) as f64).round() as u64;
+                let phases = design.phases;
+                (1 << 32, step >> 32, step & 0xffff_ffff, phases + 1, phases)
+            }
+        };
+
+        let len = 2 * half;
+        let mut bank = Vec::with_capacity(rows as usize * len);
+        let mut row = vec![0f64; len];
+        for r in 0..rows {
+            let frac = r as f64 / phases as f64;
+            for (m, t) in row.iter_mut().enumerate() {
+                *t = kernel(half as f64 - 1.0 - m as f64 + frac);
+            }
+            // unity gain at DC for every phase
+            let sum: f64 = row.iter().sum();
+            bank.extend(row.iter().map(|t| (t / sum) as f32));
+        }
+
+        let channels = channels as usize;
+        let mut res = Self {
+            in_rate,
+            out_rate,
+            channels,
+            prime_method: PrimeMethod::default(),
+            half,
+            bank,
+            exact: exact.is_some(),
+            phases,
+            den,
+            step: step as usize,
+            step_frac,
+            pos: 0,
+            pos_frac: 0,
+            buf: Vec::new(),
+            taps: vec![0.0; len],
+            acc: vec![0.0; channels],
+            input_frames: 0,
+            output_frames: 0,
+            end: None,
+        };
+        res.reset();
+        Ok(res)
+    }
+
+    /// Resets the converter with another prime method.
+    pub fn with_prime_method(mut self, prime_method: PrimeMethod) -> Self {
+        self.prime_method = prime_method;
+        self.reset();
+        self
+    }
+
+    #[inline]
+    pub fn in_rate(&self) -> f64 {
+        self.in_rate
+    }
+
+    #[inline]
+    pub fn out_rate(&self) -> f64 {
+        self.out_rate
+    }
+
+    #[inline]
+    pub fn channels(&self) -> u32 {
+        self.channels as u32
+    }
+
+    #[inline]
+    pub fn prime_method(&self) -> PrimeMethod {
+        self.prime_method
+    }
+
+    /// Filter reach in input frames, independent of the prime method.
+    #[doc(alias = "kAudioConverterPrimeInfo")]
+    pub fn prime_info(&self) -> PrimeInfo {
+        PrimeInfo {
+            leading_frames: self.half as u32 - 1,
+            trailing_frames: self.half as u32,
+        }
+    }
+
+    /// Output delay in seconds, non zero only for `PrimeMethod::None`.
+    pub fn latency(&self) -> f64 {
+        match self.prime_method {
+            PrimeMethod::None => self.half as f64 / self.in_rate,
+            _ => 0.0,
+        }
+    }
+
+    /// Drops buffered input and starts a new stream.
+    pub fn reset(&mut self) {
+        let silence = match self.prime_method {
+            PrimeMethod::Pre => 0,
+            PrimeMethod::Normal => self.half - 1,
+            PrimeMethod::None => 2 * self.half - 1,
+        };
+        self.buf.clear();
+        self.buf.resize(silence * self.channels, 0.0);
+        self.pos = self.half - 1;
+        self.pos_frac = 0;
+        self.input_frames = 0;
+        self.output_frames = 0;
+        self.end = None;
+    }
+
+    /// Converts interleaved frames, returns output frames written.
+    ///
+    /// All of `input` is taken, frames that don't fit `output` stay buffered
+    /// for the next call.
+    pub fn process(&mut self, input: &[f32], output: &mut [f32]) -> usize {
+        self.push(input);
+        self.produce(output)
+    }
+
+    /// Converts one slice per channel, returns output frames written to each.
+    pub fn process_planar(&mut self, input: &[&[f32]], output: &mut [&mut [f32]]) -> usize {
+        self.push_planar(input);
+        self.produce_planar(output)
+    }
+
+    /// Ends the stream with trailing silence, returns output frames written.
+    ///
+    /// Call again while `output` fills up, then `reset` for the next stream.
+    pub fn flush(&mut self, output: &mut [f32]) -> usize {
+        self.finish();
+        self.produce(output)
+    }
+
+    pub fn flush_planar(&mut self, output: &mut [&mut [f32]]) -> usize {
+        self.finish();
+        self.produce_planar(output)
+    }
+
+    /// Converts native `f32` samples in one interleaved buffer or one buffer per channel
+    /// and sets `dst` sizes to the frames produced.
+    ///
+    /// # Safety
+    ///
+    /// Buffers must point to `data_bytes_size` bytes of `f32` aligned memory.
+    #[cfg(feature = "cat")]
+    pub unsafe fn process_buf_list<const N: usize, const M: usize>(
+        &mut self,
+        frames: u32,
+        src: &BufList<N>,
+        dst: &mut BufList<M>,
+    ) -> Result<u32> {
+        let ch = self.channels;
+        if src.number_buffers as usize != N
+            || dst.number_buffers as usize != M
+            || !(N == 1 || N == ch)
+            || !(M == 1 || M == ch)
+        {
+            return Err(Error::Invalid("buffer count"));
+        }
+        let frames = frames as usize;
+        let src_samples = frames * ch / N;
+        let src: [&[f32]; N] = std::array::from_fn(|i| {
+            let buf = &src.buffers[i];
+            let len = buf.data_bytes_size as usize / 4;
+            unsafe { std::slice::from_raw_parts(buf.data as *const f32, len) }
+        });
+        if src.iter().any(|s| s.len() < src_samples) {
+            return Err(Error::Invalid("buffer size"));
+        }
+        if N == 1 {
+            self.push(&src[0][..src_samples]);
+        } else {
+            let src: [&[f32]; N] = std::array::from_fn(|i| &src[i][..frames]);
+            self.push_planar(&src);
+        }
+
+        let mut out: [&mut [f32]; M] = std::array::from_fn(|i| {
+            let buf = &dst.buffers[i];
+            let len = buf.data_bytes_size as usize / 4;
+            unsafe { std::slice::from_raw_parts_mut(buf.data as *mut f32, len) }
+        });
+        let produced = if M == 1 {
+            self.produce(out[0])
+        } else {
+            self.produce_planar(&mut out)
+        };
+        for buf in dst.buffers.iter_mut() {
+            buf.data_bytes_size = (produced * ch / M * 4) as u32;
+        }
+        Ok(produced as u32)
+    }
+
+    fn push(&mut self, input: &[f32]) {
+        let frames = input.len() / self.channels;
+        self.buf.extend_from_slice(&input[..frames * self.channels]);
+        self.input_frames += frames as u64;
+    }
+
+    fn push_planar(&mut self, input: &[&[f32]]) {
+        assert_eq!(input.len(), self.channels, "channel count");
+        let frames = input.iter().map(|c| c.len()).min().unwrap_or(0);
+        self.buf.reserve(frames * self.channels);
+        for i in 0..frames {
+            self.buf.extend(input.iter().map(|c| c[i]));
+        }
+        self.input_frames += frames as u64;
+    }
+
+    fn produce(&mut self, output: &mut [f32]) -> usize {
+        let ch = self.channels;
+        self.run(output.len() / ch, |i, frame| {
+            output[i * ch..(i + 1) * ch].copy_from_slice(frame)
+        })
+    }
+
+    fn produce_planar(&mut self, output: &mut [&mut [f32]]) -> usize {
+        assert_eq!(output.len(), self.channels, "channel count");
+        let capacity = output.iter().map(|c| c.len()).min().unwrap_or(0);
+        self.run(capacity, |i, frame| {
+            for (c, s) in output.iter_mut().zip(frame) {
+                c[i] = *s;
+            }
+        })
+    }
+
+    fn finish(&mut self) {
+        if self.end.is_some() {
+            return;
+        }
+        let h = self.half as u64;
+        let frames = match self.prime_method {
+            PrimeMethod::Pre => self.input_frames.saturating_sub(h - 1),
+            PrimeMethod::Normal => self.input_frames,
+            PrimeMethod::None => self.input_frames + h,
+        };
+        let step = self.step as u128 * self.den as u128 + self.step_frac as u128;
+        self.end = Some((frames as u128 * self.den as u128).div_ceil(step) as u64);
+        self.buf
+            .resize(self.buf.len() + self.half * self.channels, 0.0);
+    }
+
+    fn run(&mut self, capacity: usize, mut write: impl FnMut(usize, &[f32])) -> usize {
+        let ch = self.channels;
+        let len = 2 * self.half;
+        let frames = self.buf.len() / ch;
+        let mut n = 0;
+        while n < capacity && self.pos + self.half < frames {
+            if self.end.is_some_and(|end| self.output_frames >= end) {
+                break;
+            }
+            let taps: &[f32] = if self.exact {
+                &self.bank[self.pos_frac as usize * len..][..len]
+            } else {
+                let x = self.pos_frac * self.phases;
+                let row = (x >> 32) as usize;
+                let w = (x & 0xffff_ffff) as f32 / (1u64 << 32) as f32;
+                let (a, b) = self.bank[row * len..][..2 * len].split_at(len);
+                for ((t, a), b) in self.taps.iter_mut().zip(a).zip(b) {
+                    *t = a + (b - a) * w;
+                }
+                &self.taps
+            };
+
+            self.acc.fill(0.0);
+            let start = (self.pos + 1 - self.half) * ch;
+            let window = &self.buf[start..start + len * ch];
+            for (frame, t) in window.chunks_exact(ch).zip(taps) {
+                for (a, s) in self.acc.iter_mut().zip(frame) {
+                    *a += s * t;
+                }
+            }
+            write(n, &self.acc);
+            n += 1;
+            self.output_frames += 1;
+
+            self.pos += self.step;
+            self.pos_frac += self.step_frac;
+            if self.pos_frac >= self.den {
+                self.pos_frac -= self.den;
+                self.pos += 1;
+            }
+        }
+
+        // keep history of the next output frame only
+        let drop = (self.pos + 1 - self.half).min(frames);
+        if drop > 0 {
+            self.buf.drain(..drop * ch);
+            self.pos -= drop;
+        }
+        n
+    }
+}
+
+/// `in_rate / out_rate` as a reduced fraction of integer rates
+fn rational(in_rate: f64, out_rate: f64) -> Option<(u64, u64)> {
+    let int = |r: f64| (r.fract() == 0.0 && r < u32::MAX as f64).then_some(r as u64);
+    let (num, den) = (int(in_rate)?, int(out_rate)?);
+    let (mut a, mut b) = (num, den);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    Some((num / a, den / a))
+}
+
+/// Modified Bessel function of the first kind, order zero
+fn bessel_i0(x: f64) -> f64 {
+    let q = x * x * 0.25;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for k in 1..500 {
+        term *= q / (k * k) as f64;
+        sum += term;
+        if term < sum * 1e-17 {
+            break;
+        }
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::{
+        Error,
+        resample::{Complexity, PrimeMethod, Quality, Resampler},
+    };
+
+    fn tone(rate: f64, freq: f64, amp: f64, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|i| (amp * (2.0 * std::f64::consts::PI * freq * i as f64 / rate).sin()) as f32)
+            .collect()
+    }
+
+    /// Hann windowed single bin DFT, amplitude of `freq`
+    fn amplitude(x: &[f32], rate: f64, freq: f64) -> f64 {
+        let n = x.len() as f64;
+        let (mut re, mut im, mut norm) = (0.0, 0.0, 0.0);
+        for (i, s) in x.iter().enumerate() {
+            let w = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n).cos();
+            let a = 2.0 * std::f64::consts::PI * freq * i as f64 / rate;
+            re += w * *s as f64 * a.cos();
+            im += w * *s as f64 * a.sin();
+            norm += w;
+        }
+        2.0 * (re * re + im * im).sqrt() / norm
+    }
+
+    fn db(v: f64) -> f64 {
+        20.0 * v.log10()
+    }
+
+    fn convert(src: &mut Resampler, input: &[f32]) -> Vec<f32> {
+        let ch = src.channels() as usize;
+        let mut out = vec![
+            0f32;
+            (input.len() as f64 * src.out_rate() / src.in_rate()) as usize * 2
+                + 64 * ch
+        ];
+        let mut n = src.process(input, &mut out);
+        n += src.flush(&mut out[n * ch..]);
+        out.truncate(n * ch);
+        out
+    }
+
+    /// Steady middle part of a converted tone
+    fn steady(x: &[f32]) -> &[f32] {
+        &x[x.len() / 4..x.len() * 3 / 4]
+    }
+
+    #[test]
+    fn passband() {
+        for (from, to) in [
+            (48_000.0, 44_100.0),
+            (44_100.0, 48_000.0),
+            (44_100.5, 48_000.0),
+        ] {
+            let mut src = Resampler::new(from, to, 1, Quality::MEDIUM, Complexity::Normal).unwrap();
+            for freq in [1_000.0, 19_000.0] {
+                src.reset();
+                let out = convert(&mut src, &tone(from, freq, 0.5, 20_000));
+                let gain = db(amplitude(steady(&out), to, freq) / 0.5);
+                assert!(gain.abs() < 0.01, "{from} -> {to} {freq} Hz: {gain} dB");
+            }
+        }
+    }
+
+    #[test]
+    fn stopband() {
+        for (quality, atten) in [
+            (Quality::MIN, 58.0),
+            (Quality::LOW, 78.0),
+            (Quality::MEDIUM, 97.0),
+            (Quality::HIGH, 115.0),
+        ] {
+            let mut src =
+                Resampler::new(96_000.0, 44_100.0, 1, quality, Complexity::Normal).unwrap();
+            for freq in [23_500.0, 30_000.0, 40_000.0] {
+                src.reset();
+                let out = convert(&mut src, &tone(96_000.0, freq, 1.0, 24_000));
+                let alias = 44_100.0 - freq;
+                let gain = db(amplitude(steady(&out), 44_100.0, alias.abs()));
+                assert!(gain < -atten, "{quality:?} {freq} Hz: {gain} dB");
+            }
+        }
+    }
+
+    #[test]
+    fn linear() {
+        let mut src =
+            Resampler::new(22_050.0, 44_100.0, 1, Quality::MAX, Complexity::Linear).unwrap();
+        assert_eq!(src.prime_info().leading_frames, 0);
+        assert_eq!(src.prime_info().trailing_frames, 1);
+        let out = convert(&mut src, &[1.0, 3.0, -1.0]);
+        assert_eq!(out, [1.0, 2.0, 3.0, 1.0, -1.0, -0.5]);
+    }
+
+    #[test]
+    fn frames() {
+        let input = vec![0.25f32; 4410];
+        let mut src =
+            Resampler::new(44_100.0, 48_000.0, 1, Quality::HIGH, Complexity::Normal).unwrap();
+        let mut out = vec![0f32; 5000];
+        let mut n = 0;
+        for chunk in input.chunks(100) {
+            n += src.process(chunk, &mut out[n..]);
+        }
+        n += src.flush(&mut out[n..]);
+        assert_eq!(n, 4800);
+        assert_eq!(src.flush(&mut out[n..]), 0);
+
+        // bit exact regardless of chunking
+        src.reset();
+        let whole = convert(&mut src, &input);
+        assert_eq!(&out[..n], &whole[..]);
+
+        let info = src.prime_info();
+        let mut src = src.with_prime_method(PrimeMethod::None);
+        assert_eq!(
+            convert(&mut src, &input).len(),
+            4800 + (info.trailing_frames as usize * 48_000).div_ceil(44_100)
+        );
+
+        let mut src = src.with_prime_method(PrimeMethod::Pre);
+        let mut primed = vec![0f32; info.leading_frames as usize];
+        primed.extend_from_slice(&input);
+        assert_eq!(convert(&mut src, &primed).len(), 4800);
+    }
+
+    #[test]
+    fn alignment() {
+        let mut input = vec![0f32; 4410];
+        input[441] = 1.0;
+        for method in [PrimeMethod::Normal, PrimeMethod::None] {
+            let mut src = Resampler::new(
+                44_100.0,
+                48_000.0,
+                1,
+                Quality::MEDIUM,
+                Complexity::Mastering,
+            )
+            .unwrap()
+            .with_prime_method(method);
+            let out = convert(&mut src, &input);
+            let peak = (0..out.len())
+                .max_by(|a, b| out[*a].total_cmp(&out[*b]))
+                .unwrap();
+            let expected = (0.01 + src.latency()) * 48_000.0;
+            assert!((peak as f64 - expected).abs() <= 0.5, "{method:?}: {peak}");
+        }
+    }
+
+    #[test]
+    fn channels() {
+        let left = tone(48_000.0, 440.0, 0.5, 4800);
+        let right = vec![0f32; 4800];
+        let interleaved: Vec<f32> = left.iter().flat_map(|l| [*l, 0.0]).collect();
+
+        let mut src =
+            Resampler::new(48_000.0, 32_000.0, 2, Quality::LOW, Complexity::Normal).unwrap();
+        let out = convert(&mut src, &interleaved);
+        assert_eq!(out.len(), 3200 * 2);
+        assert!(out.iter().skip(1).step_by(2).all(|s| *s == 0.0));
+
+        src.reset();
+        let mut l = vec![0f32; 4000];
+        let mut r = vec![1f32; 4000];
+        let mut n = src.process_planar(&[&left, &right], &mut [&mut l, &mut r]);
+        n += src.flush_planar(&mut [&mut l[n..], &mut r[n..]]);
+        assert_eq!(n, 3200);
+        assert!(r[..n].iter().all(|s| *s == 0.0));
+        assert!(
+            l[..n]
+                .iter()
+                .zip(out.iter().step_by(2))
+                .all(|(a, b)| a == b)
+        );
+    }
+
+    #[cfg(feature = "cat")]
+    #[test]
+    fn buf_list() {
+        use crate::cat::audio::BufList;
+
+        let input: Vec<f32> = (0..960).flat_map(|i| [i as f32, -(i as f32)]).collect();
+        let mut l = vec![0f32; 1000];
+        let mut r = vec![0f32; 1000];
+        let mut src_list = BufList::<1>::default();
+        src_list.buffers[0].number_channels = 2;
+        src_list.buffers[0].data = input.as_ptr() as _;
+        src_list.buffers[0].data_bytes_size = (input.len() * 4) as u32;
+        let mut dst_list = BufList::<2>::default();
+        for (buf, data) in dst_list.buffers.iter_mut().zip([&mut l, &mut r]) {
+            buf.number_channels = 1;
+            buf.data = data.as_mut_ptr() as _;
+            buf.data_bytes_size = (data.len() * 4) as u32;
+        }
+
+        let mut src =
+            Resampler::new(48_000.0, 44_100.0, 2, Quality::MEDIUM, Complexity::Normal).unwrap();
+        let n = unsafe { src.process_buf_list(960, &src_list, &mut dst_list) }.unwrap();
+        assert!(n > 0);
+        assert_eq!(dst_list.buffers[1].data_bytes_size, n * 4);
+        assert!(l[..n as usize].iter().zip(&r).all(|(l, r)| *l == -*r));
+
+        let mut mono =
+            Resampler::new(48_000.0, 44_100.0, 3, Quality::MEDIUM, Complexity::Normal).unwrap();
+        assert_eq!(
+            unsafe { mono.process_buf_list(960, &src_list, &mut dst_list) },
+            Err(Error::Invalid("buffer count"))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let new = |from, to, ch| {
+            Resampler::new(from, to, ch, Quality::default(), Complexity::default()).err()
+        };
+        assert_eq!(new(0.0, 48_000.0, 1), Some(Error::Invalid("sample rate")));
+        assert_eq!(
+            new(48_000.0, f64::NAN, 1),
+            Some(Error::Invalid("sample rate"))
+        );
+        assert_eq!(
+            new(48_000.0, 44_100.0, 0),
+            Some(Error::Invalid("channel count"))
+        );
+        assert_eq!(
+            new(192_000.0, 100.0, 1),
+            Some(Error::Unsupported("sample rate ratio"))
+        );
+    }
+}
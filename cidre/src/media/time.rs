@@ -0,0 +1,991 @@
+//! `CMTime` and `CMTimeRange` arithmetic without CoreMedia, re-exported by `cm`.
+
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use crate::define_opts;
+
+pub mod range;
+pub use range::Mapping as TimeMapping;
+pub use range::Range as TimeRange;
+
+#[doc(alias = "CMTimeValue")]
+pub type TimeValue = i64;
+
+#[doc(alias = "CMTimeScale")]
+pub type TimeScale = i32;
+
+#[doc(alias = "CMTimeEpoch")]
+pub type TimeEpoch = i64;
+
+define_opts!(
+    #[doc(alias = "CMTimeFlags")]
+    pub TimeFlags(u32)
+);
+
+impl TimeFlags {
+    pub const VALID: Self = Self(1 << 0);
+    pub const HAS_BEEN_ROUNDED: Self = Self(1 << 1);
+    pub const POS_INFINITY: Self = Self(1 << 2);
+    pub const NEG_INFINITY: Self = Self(1 << 3);
+    pub const INDEFINITE: Self = Self(1 << 4);
+    pub const IMPLIED_VALUE_FLAGS_MASK: Self =
+        Self(Self::POS_INFINITY.0 | Self::NEG_INFINITY.0 | Self::INDEFINITE.0);
+}
+
+#[doc(alias = "CMTime")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Time {
+    pub value: TimeValue,
+    pub scale: TimeScale,
+    pub flags: TimeFlags,
+    pub epoch: TimeEpoch,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TimeRoundingMethod {
+    RoundHalfAwayFromZero = 1,
+    RoundTowardZero = 2,
+    RoundAwayFromZero = 3,
+    /// Rounds toward zero when converting to a smaller timescale,
+    /// away from zero when converting to a larger one.
+    /// Negative values are never rounded to zero.
+    QuickTime = 4,
+    RoundTowardPositiveInfinity = 5,
+    RoundTowardNegativeInfinity = 6,
+}
+
+impl Default for TimeRoundingMethod {
+    #[doc(alias = "kCMTimeRoundingMethod_Default")]
+    #[inline]
+    fn default() -> Self {
+        Self::RoundHalfAwayFromZero
+    }
+}
+
+/// `n / d` rounded with `method`, `d` should be positive.
+///
+/// Returns quotient and whether rounding happened.
+fn div_round(n: i128, d: i128, method: TimeRoundingMethod, upscale: bool) -> (i128, bool) {
+    let (q, r) = (n / d, n % d);
+    if r == 0 {
+        return (q, false);
+    }
+    let away = q + n.signum();
+    let res = match method {
+        TimeRoundingMethod::RoundHalfAwayFromZero if 2 * r.abs() >= d => away,
+        TimeRoundingMethod::RoundHalfAwayFromZero => q,
+        TimeRoundingMethod::RoundTowardZero => q,
+        TimeRoundingMethod::RoundAwayFromZero => away,
+        TimeRoundingMethod::QuickTime if upscale => away,
+        TimeRoundingMethod::QuickTime if q == 0 && n < 0 => -1,
+        TimeRoundingMethod::QuickTime => q,
+        TimeRoundingMethod::RoundTowardPositiveInfinity => q.max(away),
+        TimeRoundingMethod::RoundTowardNegativeInfinity => q.min(away),
+    };
+    (res, true)
+}
+
+/// `value` in `from` timescale converted to `to` timescale
+fn rescale(
+    value: i128,
+    from: TimeScale,
+    to: TimeScale,
+    method: TimeRoundingMethod,
+) -> (i128, bool) {
+    if from == to {
+        return (value, false);
+    }
+    div_round(value * to as i128, from as i128, method, to > from)
+}
+
+const fn gcd(mut a: i64, mut b: i64) -> i64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Timescale both operands can be converted to, capped by `Time::MAX_SCALE`
+const fn common_scale(a: TimeScale, b: TimeScale) -> TimeScale {
+    if a == b {
+        return a;
+    }
+    let (a, b) = (a as i64, b as i64);
+    let lcm = a / gcd(a, b) * b;
+    if lcm > Time::MAX_SCALE as i64 {
+        Time::MAX_SCALE
+    } else {
+        lcm as TimeScale
+    }
+}
+
+impl Time {
+    #[doc(alias = "kCMTimeMaxTimescale")]
+    pub const MAX_SCALE: TimeScale = 1_000_000_000;
+
+    #[doc(alias = "kCMTimeInvalid")]
+    pub const INVALID: Self = Self::with_flags(TimeFlags(0));
+
+    #[doc(alias = "kCMTimeIndefinite")]
+    pub const INDEFINITE: Self =
+        Self::with_flags(TimeFlags(TimeFlags::VALID.0 | TimeFlags::INDEFINITE.0));
+
+    #[doc(alias = "kCMTimePositiveInfinity")]
+    pub const INFINITY: Self =
+        Self::with_flags(TimeFlags(TimeFlags::VALID.0 | TimeFlags::POS_INFINITY.0));
+
+    #[doc(alias = "kCMTimeNegativeInfinity")]
+    pub const NEG_INFINITY: Self =
+        Self::with_flags(TimeFlags(TimeFlags::VALID.0 | TimeFlags::NEG_INFINITY.0));
+
+    #[doc(alias = "kCMTimeZero")]
+    pub const ZERO: Self = Self::new(0, 1);
+
+    const fn with_flags(flags: TimeFlags) -> Self {
+        Self {
+            value: 0,
+            scale: 0,
+            flags,
+            epoch: 0,
+        }
+    }
+
+    /// Numeric time from wide `value`, timescale is halved until value fits,
+    /// infinity if it doesn't fit even with timescale 1.
+    fn fit(mut value: i128, mut scale: TimeScale, epoch: TimeEpoch, mut rounded: bool) -> Self {
+        while TimeValue::try_from(value).is_err() {
+            if scale == 1 {
+                return if value > 0 {
+                    Self::INFINITY
+                } else {
+                    Self::NEG_INFINITY
+                };
+            }
+            let (v, r) = rescale(value, scale, scale / 2, TimeRoundingMethod::default());
+            value = v;
+            scale /= 2;
+            rounded |= r;
+        }
+        let mut res = Self::with_epoch(value as TimeValue, scale, epoch);
+        res.flags.set(TimeFlags::HAS_BEEN_ROUNDED, rounded);
+        res
+    }
+
+    /// 1 for positive, -1 for negative infinity, 0 otherwise
+    const fn infinity_sign(&self) -> i32 {
+        if self.is_pos_infinity() {
+            1
+        } else if self.is_neg_infinity() {
+            -1
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    pub const fn has_been_rounded(&self) -> bool {
+        (self.flags.0 & TimeFlags::HAS_BEEN_ROUNDED.0) != 0
+    }
+
+    /// Returns the absolute value of a Time.
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let t1 = Time::with_secs(-5.0, 10);
+    /// let t2 = t1.abs();
+    /// assert_eq!(t2.scale, 10);
+    /// assert_eq!(t2.as_secs(), 5.0);
+    /// ```
+    #[doc(alias = "CMTimeAbsoluteValue")]
+    #[inline]
+    pub fn abs(self) -> Time {
+        if self.is_neg_infinity() {
+            Self::INFINITY
+        } else if self.is_numeric() && self.value < 0 {
+            self.mul_i32(-1)
+        } else {
+            self
+        }
+    }
+
+    fn add_sub(self, rhs: Time, sign: i32) -> Time {
+        if self.is_invalid() || rhs.is_invalid() {
+            return Self::INVALID;
+        }
+        match (self.infinity_sign(), rhs.infinity_sign() * sign) {
+            (0, 0) => {}
+            (l, r) if l != 0 && r != 0 && l != r => return Self::INVALID,
+            (l, r) => {
+                return if l + r > 0 {
+                    Self::INFINITY
+                } else {
+                    Self::NEG_INFINITY
+                };
+            }
+        }
+        if self.is_indefinite() || rhs.is_indefinite() {
+            return Self::INDEFINITE;
+        }
+        if self.scale <= 0 || rhs.scale <= 0 {
+            return Self::INVALID;
+        }
+        // times in epoch zero are durations
+        let epoch = match (self.epoch, rhs.epoch) {
+            (l, r) if l == r => {
+                if sign > 0 {
+                    l
+                } else {
+                    0
+                }
+            }
+            (l, 0) => l,
+            (0, r) if sign > 0 => r,
+            _ => return Self::INVALID,
+        };
+        let scale = common_scale(self.scale, rhs.scale);
+        let method = TimeRoundingMethod::default();
+        let (l, l_rounded) = rescale(self.value as i128, self.scale, scale, method);
+        let (r, r_rounded) = rescale(rhs.value as i128, rhs.scale, scale, method);
+        let rounded = l_rounded || r_rounded || self.has_been_rounded() || rhs.has_been_rounded();
+        Self::fit(l + r * sign as i128, scale, epoch, rounded)
+    }
+
+    /// Sum of two times in the least common multiple of their timescales.
+    ///
+    /// Timescale is capped by [`Self::MAX_SCALE`] and halved on overflow,
+    /// [`Self::has_been_rounded`] is set if any rounding happens.
+    /// `+inf + -inf` is invalid, times in different nonzero epochs can't be added.
+    ///
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let t1 = Time::with_secs(100.0, 10);
+    /// let t2 = Time::with_secs(200.0, 10);
+    /// let t3 = t1.add(t2);
+    /// assert!(t3.is_valid());
+    /// assert_eq!(t3.scale, 10);
+    /// assert_eq!(t3.as_secs(), 300.0);
+    /// ```
+    #[doc(alias = "CMTimeAdd")]
+    #[allow(clippy::should_implement_trait)] // kept for `CMTimeAdd` callers, same as `Add`
+    #[inline]
+    pub fn add(self, rhs: Time) -> Time {
+        std::ops::Add::add(self, rhs)
+    }
+
+    /// Converts time to `new_time_scale`.
+    ///
+    /// Non numeric times are returned as is, overflow results in infinity.
+    ///
+    /// ```
+    /// use cidre::media::time::{Time, TimeRoundingMethod};
+    ///
+    /// let time = Time::default().convert_scale(100, TimeRoundingMethod::default());
+    /// assert!(time.is_valid());
+    /// assert_eq!(time.scale, 100);
+    /// ```
+    #[doc(alias = "CMTimeConvertScale")]
+    pub fn convert_scale(
+        self,
+        new_time_scale: TimeScale,
+        rounding_method: TimeRoundingMethod,
+    ) -> Time {
+        if !self.is_numeric() {
+            return self;
+        }
+        if new_time_scale <= 0 || self.scale <= 0 {
+            return Self::INVALID;
+        }
+        let (value, rounded) = rescale(
+            self.value as i128,
+            self.scale,
+            new_time_scale,
+            rounding_method,
+        );
+        let Ok(value) = TimeValue::try_from(value) else {
+            return if value > 0 {
+                Self::INFINITY
+            } else {
+                Self::NEG_INFINITY
+            };
+        };
+        let mut res = Self::with_epoch(value, new_time_scale, self.epoch);
+        res.flags.set(
+            TimeFlags::HAS_BEEN_ROUNDED,
+            rounded || self.has_been_rounded(),
+        );
+        res
+    }
+
+    /// Converts a Time to seconds.
+    ///
+    /// Infinities are converted to `f64` infinities, invalid and indefinite times to NaN.
+    #[doc(alias = "CMTimeGetSeconds")]
+    #[inline]
+    pub fn as_secs(self) -> f64 {
+        if self.is_pos_infinity() {
+            f64::INFINITY
+        } else if self.is_neg_infinity() {
+            f64::NEG_INFINITY
+        } else if self.is_numeric() && self.scale > 0 {
+            self.value as f64 / self.scale as f64
+        } else {
+            f64::NAN
+        }
+    }
+
+    #[inline]
+    pub const fn indefinit() -> Time {
+        Self::INDEFINITE
+    }
+
+    #[inline]
+    pub const fn invalid() -> Time {
+        Self::INVALID
+    }
+
+    #[inline]
+    pub const fn is_indefinite(&self) -> bool {
+        self.is_valid() && (self.flags.0 & TimeFlags::INDEFINITE.0) != 0
+    }
+
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let time = Time::invalid();
+    /// assert!(!time.is_valid());
+    /// assert!(time.is_invalid());
+    /// ```
+    #[inline]
+    pub const fn is_invalid(&self) -> bool {
+        !self.is_valid()
+    }
+
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// assert!(Time::neg_infinity().is_neg_infinity())
+    /// ```
+    #[inline]
+    pub const fn is_neg_infinity(&self) -> bool {
+        self.is_valid() && (self.flags.0 & TimeFlags::NEG_INFINITY.0) != 0
+    }
+
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// assert!(Time::infinity().is_pos_infinity());
+    /// ```
+    #[inline]
+    pub const fn is_pos_infinity(&self) -> bool {
+        self.is_valid() && (self.flags.0 & TimeFlags::POS_INFINITY.0) != 0
+    }
+
+    #[inline]
+    pub const fn is_numeric(&self) -> bool {
+        (self.flags.0 & (TimeFlags::VALID.0 | TimeFlags::IMPLIED_VALUE_FLAGS_MASK.0))
+            == TimeFlags::VALID.0
+    }
+
+    /// Returns Time from a f64 number of seconds, and a preferred timescale.
+    ///
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let time = Time::with_secs(100.0, 40_000);
+    /// assert!(time.is_valid());
+    /// ```
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        (self.flags.0 & TimeFlags::VALID.0) != 0
+    }
+
+    #[doc(alias = "is_valid")]
+    #[inline]
+    pub const fn is_ok(&self) -> bool {
+        self.is_valid()
+    }
+
+    /// Infinities change sign with negative multiplier
+    fn mul_non_numeric(self, negative: bool) -> Time {
+        match self.infinity_sign() {
+            0 => self,
+            sign if (sign < 0) != negative => Self::NEG_INFINITY,
+            _ => Self::INFINITY,
+        }
+    }
+
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let t1 = Time::with_secs(5.0, 10);
+    /// let t2 = t1.mul_i32(2);
+    /// assert!(t2.is_valid());
+    /// assert_eq!(t2.scale, 10);
+    /// assert_eq!(t2.as_secs(), 10.0);
+    /// ```
+    #[doc(alias = "CMTimeMultiply")]
+    #[inline]
+    pub fn mul_i32(self, multiplier: i32) -> Time {
+        if !self.is_numeric() {
+            return self.mul_non_numeric(multiplier < 0);
+        }
+        if self.scale <= 0 {
+            return Self::INVALID;
+        }
+        let value = self.value as i128 * multiplier as i128;
+        Self::fit(value, self.scale, self.epoch, self.has_been_rounded())
+    }
+
+    /// Multiplies time by `multiplier / divisor` with default rounding
+    ///
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let t = Time::new(10, 3).mul_ratio(1, 4);
+    /// assert_eq!(t.value, 3);
+    /// assert!(t.has_been_rounded());
+    /// ```
+    #[doc(alias = "CMTimeMultiplyByRatio")]
+    pub fn mul_ratio(self, multiplier: i32, divisor: i32) -> Time {
+        if divisor == 0 {
+            return Self::INVALID;
+        }
+        if !self.is_numeric() {
+            return self.mul_non_numeric((multiplier < 0) != (divisor < 0));
+        }
+        if self.scale <= 0 {
+            return Self::INVALID;
+        }
+        let (n, d) = if divisor < 0 {
+            (-(multiplier as i128), -(divisor as i128))
+        } else {
+            (multiplier as i128, divisor as i128)
+        };
+        let (value, rounded) = div_round(
+            self.value as i128 * n,
+            d,
+            TimeRoundingMethod::default(),
+            false,
+        );
+        Self::fit(
+            value,
+            self.scale,
+            self.epoch,
+            rounded || self.has_been_rounded(),
+        )
+    }
+
+    /// Value in `scale` rounded half away from zero,
+    /// timescale is halved until it fits
+    fn fit_f64(value: f64, mut scale: TimeScale, epoch: TimeEpoch, rounded: bool) -> Time {
+        if value.is_nan() || scale <= 0 {
+            return Self::INVALID;
+        }
+        let mut value = value;
+        // 2^63 as f64, everything below fits i64
+        const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+        while value.round().abs() >= LIMIT {
+            if scale == 1 {
+                return if value > 0.0 {
+                    Self::INFINITY
+                } else {
+                    Self::NEG_INFINITY
+                };
+            }
+            value = value * (scale / 2) as f64 / scale as f64;
+            scale /= 2;
+        }
+        let rounded = rounded || value.fract() != 0.0;
+        let mut res = Self::with_epoch(value.round() as TimeValue, scale, epoch);
+        res.flags.set(TimeFlags::HAS_BEEN_ROUNDED, rounded);
+        res
+    }
+
+    #[doc(alias = "CMTimeMultiplyByFloat64")]
+    #[inline]
+    pub fn mul_f64(self, multiplier: f64) -> Time {
+        if multiplier.is_nan() {
+            return Self::INVALID;
+        }
+        if !self.is_numeric() {
+            return self.mul_non_numeric(multiplier < 0.0);
+        }
+        Self::fit_f64(
+            self.value as f64 * multiplier,
+            self.scale,
+            self.epoch,
+            self.has_been_rounded(),
+        )
+    }
+
+    /// Returns valid Time with value and timescale. Epoch is implied to be 0.
+    ///
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let time = Time::new(100, 10);
+    /// assert!(time.is_valid());
+    /// assert_eq!(time.epoch, 0);
+    /// ```
+    #[doc(alias = "CMTimeMake")]
+    #[inline]
+    pub const fn new(value: TimeValue, timescale: i32) -> Time {
+        Self::with_epoch(value, timescale, 0)
+    }
+
+    #[doc(alias = "kCMTimePositiveInfinity")]
+    #[inline]
+    pub const fn infinity() -> Time {
+        Self::INFINITY
+    }
+
+    #[doc(alias = "kCMTimeNegativeInfinity")]
+    #[inline]
+    pub const fn neg_infinity() -> Time {
+        Self::NEG_INFINITY
+    }
+
+    /// Difference of two times, see [`Self::add`] for timescale and rounding.
+    ///
+    /// `+inf - +inf` is invalid. Times in the same epoch result in epoch zero (a duration),
+    /// durations can be subtracted from times in any epoch.
+    ///
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let t1 = Time::with_secs(100.0, 10);
+    /// let t2 = Time::with_secs(100.0, 10);
+    /// let t3 = t1.sub(t2);
+    /// assert!(t3.is_valid());
+    /// assert_eq!(t3.scale, 10);
+    /// assert_eq!(t3.as_secs(), 0.0);
+    /// ```
+    #[doc(alias = "CMTimeSubtract")]
+    #[allow(clippy::should_implement_trait)] // kept for `CMTimeSubtract` callers, same as `Sub`
+    #[inline]
+    pub fn sub(self, rhs: Time) -> Time {
+        std::ops::Sub::sub(self, rhs)
+    }
+
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let time = Time::with_epoch(100, 10, 5);
+    /// assert!(time.is_valid());
+    /// assert_eq!(time.epoch, 5);
+    /// ```
+    #[doc(alias = "CMTimeMakeWithEpoch")]
+    #[inline]
+    pub const fn with_epoch(value: TimeValue, timescale: i32, epoch: TimeEpoch) -> Time {
+        Time {
+            value,
+            scale: timescale,
+            flags: TimeFlags::VALID,
+            epoch,
+        }
+    }
+
+    /// Returns Time from a f64 number of seconds, and a preferred timescale.
+    ///
+    /// Timescale is halved while value doesn't fit.
+    ///
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let time = Time::with_secs(100.0, 10);
+    /// assert!(time.is_valid());
+    /// assert_eq!(time.scale, 10);
+    /// assert_eq!(time.as_secs(), 100.0);
+    /// ```
+    #[doc(alias = "CMTimeMakeWithSeconds")]
+    #[inline]
+    pub fn with_secs(seconds: f64, preferred_timescale: TimeScale) -> Time {
+        if seconds.is_infinite() {
+            return if seconds > 0.0 {
+                Self::INFINITY
+            } else {
+                Self::NEG_INFINITY
+            };
+        }
+        Self::fit_f64(
+            seconds * preferred_timescale as f64,
+            preferred_timescale,
+            0,
+            false,
+        )
+    }
+
+    #[doc(alias = "kCMTimeZero")]
+    #[inline]
+    pub const fn zero() -> Time {
+        Self::ZERO
+    }
+
+    /// Greater of two times, invalid if any is invalid
+    #[doc(alias = "CMTimeMaximum")]
+    #[inline]
+    pub fn max(l: Time, r: Time) -> Time {
+        if l.is_invalid() || r.is_invalid() {
+            return Self::INVALID;
+        }
+        if r > l { r } else { l }
+    }
+
+    /// Lesser of two times, invalid if any is invalid
+    #[doc(alias = "CMTimeMinimum")]
+    #[inline]
+    pub fn min(l: Time, r: Time) -> Time {
+        if l.is_invalid() || r.is_invalid() {
+            return Self::INVALID;
+        }
+        if r < l { r } else { l }
+    }
+
+    /// Rank in CoreMedia total order:
+    /// -infinity < numeric < indefinite < +infinity < invalid
+    const fn rank(&self) -> u8 {
+        if self.is_invalid() {
+            4
+        } else if self.is_pos_infinity() {
+            3
+        } else if self.is_indefinite() {
+            2
+        } else if self.is_neg_infinity() {
+            0
+        } else {
+            1
+        }
+    }
+}
+
+impl std::ops::Add for Time {
+    type Output = Time;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        self.add_sub(rhs, 1)
+    }
+}
+
+impl std::ops::Sub for Time {
+    type Output = Time;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.add_sub(rhs, -1)
+    }
+}
+
+impl std::ops::Neg for Time {
+    type Output = Time;
+
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// assert_eq!(-Time::new(1, 2), Time::new(-1, 2));
+    /// assert!((-Time::infinity()).is_neg_infinity());
+    /// ```
+    #[inline]
+    fn neg(self) -> Self::Output {
+        self.mul_i32(-1)
+    }
+}
+
+impl PartialEq for Time {
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let t1 = Time::with_secs(5.0, 10);
+    /// let t2 = t1.mul_i32(2);
+    /// let t3 = Time::with_secs(5.0, 100);
+    /// assert!(t1 != t2);
+    /// assert!(t1 == t1);
+    /// assert!(t1 == t3);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Time {}
+
+impl Hash for Time {
+    /// Consistent with `Eq`, numeric times hash their reduced fraction
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        if self.rank() != 1 {
+            return;
+        }
+        self.epoch.hash(state);
+        let (mut a, mut b) = (self.value.unsigned_abs(), self.scale.unsigned_abs() as u64);
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        let gcd = a.max(1) as i128;
+        let (value, scale) = (self.value as i128 / gcd, self.scale as i128 / gcd);
+        let sign = if scale < 0 { -1 } else { 1 };
+        (value * sign, scale * sign).hash(state);
+    }
+}
+
+impl PartialOrd for Time {
+    /// ```
+    /// use cidre::media::time::Time;
+    ///
+    /// let t1 = Time::with_secs(5.0, 10);
+    /// let t2 = Time::with_secs(5.5, 10);
+    /// assert!(t1 < t2);
+    /// assert!(Time::neg_infinity() < Time::zero());
+    /// assert!(Time::neg_infinity() < Time::infinity());
+    /// assert!(Time::zero() < Time::infinity());
+    /// ```
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Time {
+    /// Total order of `CMTimeCompare`.
+    ///
+    /// Times in numerically larger epochs are greater, all invalid times are equal.
+    #[doc(alias = "CMTimeCompare")]
+    fn cmp(&self, other: &Self) -> Ordering {
+        let rank = self.rank().cmp(&other.rank());
+        if rank.is_ne() || self.rank() != 1 {
+            return rank;
+        }
+        self.epoch.cmp(&other.epoch).then_with(|| {
+            let l = self.value as i128 * other.scale as i128;
+            let r = other.value as i128 * self.scale as i128;
+            l.cmp(&r)
+        })
+    }
+}
+
+impl Default for Time {
+    /// ```
+    /// use cidre::media::time::{Time, TimeRoundingMethod};
+    ///
+    /// let t1 = Time::default();
+    /// assert_eq!(t1, Time::zero());
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Time, TimeRoundingMethod as Rounding};
+
+    #[test]
+    fn basics() {
+        let invalid = Time::invalid();
+        assert!(invalid.is_invalid());
+        assert!(!invalid.is_numeric());
+
+        let valid = Time::default();
+        assert!(valid.is_valid());
+        assert!(valid.is_numeric());
+    }
+
+    #[test]
+    fn add() {
+        let t = Time::new(1, 3) + Time::new(1, 4);
+        assert_eq!((t.value, t.scale), (7, 12));
+        assert!(!t.has_been_rounded());
+
+        // LCM above max timescale is capped and rounded
+        let t = Time::new(1, 999_999_937) + Time::new(1, 3);
+        assert_eq!(t.scale, Time::MAX_SCALE);
+        assert!(t.has_been_rounded());
+
+        // rounded flag propagates
+        let rounded = Time::new(10, 3).mul_ratio(1, 4);
+        assert!((rounded + Time::new(1, 3)).has_been_rounded());
+
+        // overflow halves timescale
+        let t = Time::new(i64::MAX, 2) + Time::new(i64::MAX, 2);
+        assert_eq!((t.value, t.scale), (i64::MAX, 1));
+        assert!(!t.has_been_rounded());
+        let t = Time::new(i64::MAX, 1) + Time::new(1, 1);
+        assert!(t.is_pos_infinity());
+        let t = Time::new(i64::MIN, 1) - Time::new(1, 1);
+        assert!(t.is_neg_infinity());
+    }
+
+    #[test]
+    fn non_numeric() {
+        let inf = Time::infinity();
+        let neg_inf = Time::neg_infinity();
+        let one = Time::new(1, 1);
+
+        assert!((inf + inf).is_pos_infinity());
+        assert!((neg_inf + neg_inf).is_neg_infinity());
+        assert!((inf + neg_inf).is_invalid());
+        assert!((inf - inf).is_invalid());
+        assert!((inf - neg_inf).is_pos_infinity());
+        assert!((one - inf).is_neg_infinity());
+        assert!((Time::indefinit() + inf).is_pos_infinity());
+        assert!((Time::indefinit() + one).is_indefinite());
+        assert!((Time::invalid() + inf).is_invalid());
+        assert!((Time::invalid() + Time::indefinit()).is_invalid());
+
+        assert!(inf.mul_i32(-2).is_neg_infinity());
+        assert!(neg_inf.mul_f64(-0.5).is_pos_infinity());
+        assert!(Time::indefinit().mul_i32(-1).is_indefinite());
+        assert!(one.mul_ratio(1, 0).is_invalid());
+        assert!(one.mul_f64(f64::NAN).is_invalid());
+        assert!(neg_inf.abs().is_pos_infinity());
+
+        assert!(Time::with_secs(f64::INFINITY, 1).is_pos_infinity());
+        assert!(Time::with_secs(f64::NAN, 1).is_invalid());
+        assert!(Time::indefinit().as_secs().is_nan());
+        assert_eq!(neg_inf.as_secs(), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn epochs() {
+        let a = Time::with_epoch(10, 1, 1);
+        let b = Time::with_epoch(3, 1, 1);
+        let c = Time::with_epoch(3, 1, 2);
+        let d = Time::new(3, 1);
+
+        assert_eq!((a + d).epoch, 1);
+        assert_eq!((d + a).epoch, 1);
+        assert_eq!((a - d).epoch, 1);
+        let duration = a - b;
+        assert_eq!((duration.value, duration.epoch), (7, 0));
+        assert!((a + c).is_invalid());
+        assert!((a - c).is_invalid());
+        assert!((d - a).is_invalid());
+
+        // larger epoch is always later
+        assert!(a < c);
+        assert!(Time::with_epoch(-100, 1, 0) > Time::with_epoch(100, 1, -1));
+    }
+
+    #[test]
+    fn rounding() {
+        let cases = [
+            (Rounding::RoundHalfAwayFromZero, [2, -2, 1, -1]),
+            (Rounding::RoundTowardZero, [1, -1, 1, -1]),
+            (Rounding::RoundAwayFromZero, [2, -2, 2, -2]),
+            (Rounding::RoundTowardPositiveInfinity, [2, -1, 2, -1]),
+            (Rounding::RoundTowardNegativeInfinity, [1, -2, 1, -2]),
+            (Rounding::QuickTime, [1, -1, 1, -1]),
+        ];
+        for (method, expected) in cases {
+            // 1.5, -1.5, 1.25, -1.25
+            let values = [(15, 10), (-15, 10), (125, 100), (-125, 100)];
+            for ((value, scale), expected) in values.into_iter().zip(expected) {
+                let t = Time::new(value, scale).convert_scale(1, method);
+                assert_eq!(t.value, expected, "{method:?} {value}/{scale}");
+                assert!(t.has_been_rounded());
+                assert_eq!(t.scale, 1);
+            }
+        }
+
+        // kCMTimeRoundingMethod_Default, 1.25 rounds to 1 instead of 2
+        assert_eq!(Rounding::default(), Rounding::RoundHalfAwayFromZero);
+        let t = Time::new(125, 100).convert_scale(1, Rounding::default());
+        assert_eq!(t.value, 1);
+
+        let t = Time::new(3, 4).convert_scale(8, Rounding::default());
+        assert_eq!(t.value, 6);
+        assert!(!t.has_been_rounded());
+
+        // QuickTime rounds away from zero on upscale and never rounds negative to zero
+        let t = Time::new(1, 3).convert_scale(10, Rounding::QuickTime);
+        assert_eq!(t.value, 4);
+        let t = Time::new(-1, 30).convert_scale(10, Rounding::QuickTime);
+        assert_eq!(t.value, -1);
+        let t = Time::new(1, 30).convert_scale(10, Rounding::QuickTime);
+        assert_eq!(t.value, 0);
+
+        assert!(
+            Time::new(i64::MAX, 1)
+                .convert_scale(2, Rounding::default())
+                .is_pos_infinity()
+        );
+        assert!(
+            Time::new(1, 1)
+                .convert_scale(0, Rounding::default())
+                .is_invalid()
+        );
+        assert!(
+            Time::infinity()
+                .convert_scale(10, Rounding::default())
+                .is_pos_infinity()
+        );
+    }
+
+    #[test]
+    fn mul() {
+        let t = Time::new(3, 10).mul_f64(0.5);
+        assert_eq!((t.value, t.scale), (2, 10));
+        assert!(t.has_been_rounded());
+
+        let t = Time::new(i64::MAX / 2 + 1, 4).mul_i32(2);
+        assert_eq!((t.value, t.scale), (i64::MAX / 2 + 1, 2));
+
+        let t = Time::new(-7, 10).mul_ratio(1, -2);
+        assert_eq!(t.value, 4);
+
+        let t = Time::with_secs(1e17, 600);
+        assert_eq!(t.scale, 75);
+        assert!(Time::with_secs(1e30, 600).is_pos_infinity());
+
+        let t = Time::with_secs(1.0 / 3.0, 600);
+        assert_eq!((t.value, t.scale), (200, 600));
+
+        // overflow with a broken timescale can't be fixed by halving it
+        assert!(Time::new(i64::MAX, 0).mul_i32(2).is_invalid());
+        assert!(Time::new(i64::MAX, -4).mul_ratio(3, 1).is_invalid());
+        assert!(Time::new(1, 0).mul_i32(2).is_invalid());
+
+        assert!(Time::new(i64::MIN, 1).abs().is_pos_infinity());
+        assert_eq!(Time::new(i64::MIN, 2).abs(), Time::new(i64::MAX / 2 + 1, 1));
+    }
+
+    #[test]
+    fn order() {
+        let mut times = [
+            Time::invalid(),
+            Time::infinity(),
+            Time::indefinit(),
+            Time::new(1, 2),
+            Time::neg_infinity(),
+            Time::new(-1, 2),
+        ];
+        times.sort();
+        assert!(times[0].is_neg_infinity());
+        assert_eq!(times[1], Time::new(-2, 4));
+        assert_eq!(times[2], Time::new(2, 4));
+        assert!(times[3].is_indefinite());
+        assert!(times[4].is_pos_infinity());
+        assert!(times[5].is_invalid());
+
+        assert_eq!(Time::invalid(), Time::invalid());
+        assert!(Time::max(Time::new(1, 1), Time::invalid()).is_invalid());
+        assert!(Time::min(Time::new(1, 1), Time::invalid()).is_invalid());
+        assert_eq!(
+            Time::min(Time::new(1, 1), Time::indefinit()),
+            Time::new(1, 1)
+        );
+        assert!(Time::max(Time::new(1, 1), Time::indefinit()).is_indefinite());
+
+        let set: std::collections::HashSet<_> = [Time::new(1, 2), Time::invalid()].into();
+        assert!(set.contains(&Time::new(2, 4)));
+        assert!(set.contains(&Time::new(-3, -6)));
+        assert!(set.contains(&Time::INVALID.mul_i32(2)));
+        assert!(!set.contains(&Time::new(1, 3)));
+    }
+}
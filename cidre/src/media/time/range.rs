@@ -0,0 +1,333 @@
+use super::Time;
+
+#[doc(alias = "CMTimeRange")]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[repr(C)]
+pub struct Range {
+    pub start: Time,
+    pub duration: Time,
+}
+
+impl Range {
+    #[doc(alias = "CMTimeRangeMake")]
+    #[inline]
+    pub const fn new(start: Time, duration: Time) -> Self {
+        Self { start, duration }
+    }
+
+    /// Range from `start` to `end`, `end` is not included
+    ///
+    /// ```
+    /// use cidre::media::time::{Time, TimeRange};
+    ///
+    /// let range = TimeRange::with_end(Time::new(1, 2), Time::new(3, 4));
+    /// assert_eq!(range.duration, Time::new(1, 4));
+    /// assert_eq!(range.end(), Time::new(3, 4));
+    /// ```
+    #[doc(alias = "CMTimeRangeFromTimeToTime")]
+    #[inline]
+    pub fn with_end(start: Time, end: Time) -> Self {
+        Self::new(start, end.sub(start))
+    }
+
+    #[doc(alias = "CMTIMERANGE_IS_INVALID")]
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.start.is_valid()
+            && self.duration.is_valid()
+            && self.duration.epoch == 0
+            && self.duration.value >= 0
+    }
+
+    #[doc(alias = "CMTIMERANGE_IS_INDEFINITE")]
+    #[inline]
+    pub const fn is_indefinite(&self) -> bool {
+        self.is_valid() && (self.start.is_indefinite() || self.duration.is_indefinite())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.is_valid() && self.duration == Time::zero()
+    }
+
+    #[doc(alias = "CMTimeRangeGetEnd")]
+    #[inline]
+    pub fn end(&self) -> Time {
+        self.start.add(self.duration)
+    }
+
+    /// `start <= time < end`
+    #[doc(alias = "CMTimeRangeContainsTime")]
+    #[inline]
+    pub fn contains_time(&self, time: &Time) -> bool {
+        self.is_valid() && time.is_valid() && *time >= self.start && *time < self.end()
+    }
+
+    /// ```
+    /// use cidre::media::time::{Time, TimeRange};
+    ///
+    /// let range = TimeRange::new(Time::new(0, 1), Time::new(10, 1));
+    /// let inner = TimeRange::new(Time::new(5, 1), Time::new(5, 1));
+    /// assert!(range.contains_range(&inner));
+    /// assert!(!inner.contains_range(&range));
+    /// ```
+    #[doc(alias = "CMTimeRangeContainsTimeRange")]
+    #[inline]
+    pub fn contains_range(&self, other: &Self) -> bool {
+        self.is_valid()
+            && other.is_valid()
+            && other.start >= self.start
+            && other.end() <= self.end()
+    }
+
+    /// Smallest range containing both ranges
+    #[doc(alias = "CMTimeRangeGetUnion")]
+    pub fn union(&self, other: &Self) -> Self {
+        if !self.is_valid() || !other.is_valid() {
+            return Self::invalid();
+        }
+        Self::with_end(
+            Time::min(self.start, other.start),
+            Time::max(self.end(), other.end()),
+        )
+    }
+
+    /// Overlap of two ranges, [`Self::zero`] if they don't overlap
+    ///
+    /// ```
+    /// use cidre::media::time::{Time, TimeRange};
+    ///
+    /// let a = TimeRange::new(Time::new(0, 1), Time::new(10, 1));
+    /// let b = TimeRange::new(Time::new(5, 1), Time::new(10, 1));
+    /// let c = a.intersection(&b);
+    /// assert_eq!(c.start, Time::new(5, 1));
+    /// assert_eq!(c.end(), Time::new(10, 1));
+    /// assert_eq!(a.union(&b).end(), Time::new(15, 1));
+    /// ```
+    #[doc(alias = "CMTimeRangeGetIntersection")]
+    pub fn intersection(&self, other: &Self) -> Self {
+        if !self.is_valid() || !other.is_valid() {
+            return Self::invalid();
+        }
+        let start = Time::max(self.start, other.start);
+        let end = Time::min(self.end(), other.end());
+        if end <= start {
+            return Self::zero();
+        }
+        Self::with_end(start, end)
+    }
+
+    /// Time clamped to the range, `end` is included
+    #[doc(alias = "CMTimeClampToRange")]
+    pub fn clamp(&self, time: Time) -> Time {
+        if !self.is_valid() || time.is_invalid() {
+            return Time::invalid();
+        }
+        if time < self.start {
+            self.start
+        } else {
+            Time::min(time, self.end())
+        }
+    }
+
+    /// Maps time to `to` range keeping its relative position
+    ///
+    /// ```
+    /// use cidre::media::time::{Time, TimeRange};
+    ///
+    /// let from = TimeRange::new(Time::new(10, 1), Time::new(10, 1));
+    /// let to = TimeRange::new(Time::new(0, 600), Time::new(2400, 600));
+    /// assert_eq!(from.map_time(Time::new(15, 1), &to), Time::new(2, 1));
+    /// ```
+    #[doc(alias = "CMTimeMapTimeFromRangeToRange")]
+    pub fn map_time(&self, time: Time, to: &Self) -> Time {
+        if time.is_invalid() || !self.is_valid() || !to.is_valid() {
+            return Time::invalid();
+        }
+        to.start.add(scale_duration(time.sub(self.start), self, to))
+    }
+
+    /// Scales duration by `to / self` durations ratio
+    #[doc(alias = "CMTimeMapDurationFromRangeToRange")]
+    pub fn map_duration(&self, duration: Time, to: &Self) -> Time {
+        if duration.is_invalid() || !self.is_valid() || !to.is_valid() {
+            return Time::invalid();
+        }
+        scale_duration(duration, self, to)
+    }
+
+    #[doc(alias = "kCMTimeRangeZero")]
+    #[inline]
+    pub const fn zero() -> Self {
+        Self::new(Time::ZERO, Time::ZERO)
+    }
+
+    #[doc(alias = "kCMTimeRangeInvalid")]
+    #[inline]
+    pub const fn invalid() -> Self {
+        Self::new(Time::INVALID, Time::INVALID)
+    }
+}
+
+/// `duration * to.duration / from.duration` in `to.duration` timescale
+fn scale_duration(duration: Time, from: &Range, to: &Range) -> Time {
+    if !duration.is_numeric() {
+        return duration;
+    }
+    let (from, to) = (from.duration, to.duration);
+    if !from.is_numeric() || !to.is_numeric() || from.value == 0 {
+        return Time::invalid();
+    }
+    if duration.scale <= 0 || from.scale <= 0 || to.scale <= 0 {
+        return Time::invalid();
+    }
+    let n = duration.value as i128 * to.value as i128 * from.scale as i128;
+    let d = duration.scale as i128 * from.value as i128;
+    let (value, rounded) = super::div_round(n, d, Default::default(), false);
+    Time::fit(value, to.scale, 0, rounded || duration.has_been_rounded())
+}
+
+/// Edit of track timeline, `source` media range is played at `target` track range
+#[doc(alias = "CMTimeMapping")]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[repr(C)]
+pub struct Mapping {
+    pub source: Range,
+    pub target: Range,
+}
+
+impl Mapping {
+    #[doc(alias = "CMTimeMappingMake")]
+    #[inline]
+    pub const fn new(source: Range, target: Range) -> Self {
+        Self { source, target }
+    }
+
+    /// Mapping of target range to nothing
+    #[doc(alias = "CMTimeMappingMakeEmpty")]
+    #[inline]
+    pub const fn empty(target: Range) -> Self {
+        Self::new(Range::invalid(), target)
+    }
+
+    #[doc(alias = "CMTIMEMAPPING_IS_VALID")]
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.target.is_valid()
+    }
+
+    #[doc(alias = "CMTIMEMAPPING_IS_EMPTY")]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        !self.source.start.is_numeric() && self.target.is_valid()
+    }
+
+    /// Maps source time to target time, invalid for empty mappings
+    ///
+    /// ```
+    /// use cidre::media::time::{Time, TimeMapping, TimeRange};
+    ///
+    /// // 2x slow motion
+    /// let mapping = TimeMapping::new(
+    ///     TimeRange::new(Time::new(0, 1), Time::new(5, 1)),
+    ///     TimeRange::new(Time::new(10, 1), Time::new(10, 1)),
+    /// );
+    /// assert_eq!(mapping.map_time(Time::new(1, 1)), Time::new(12, 1));
+    /// assert_eq!(mapping.map_duration(Time::new(1, 2)), Time::new(1, 1));
+    /// ```
+    #[inline]
+    pub fn map_time(&self, time: Time) -> Time {
+        self.source.map_time(time, &self.target)
+    }
+
+    #[inline]
+    pub fn map_duration(&self, duration: Time) -> Time {
+        self.source.map_duration(duration, &self.target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Time, TimeMapping, TimeRange};
+
+    fn range(start: i64, duration: i64) -> TimeRange {
+        TimeRange::new(Time::new(start, 1), Time::new(duration, 1))
+    }
+
+    #[test]
+    fn basics() {
+        let range = TimeRange::zero();
+        assert!(range.is_valid());
+        assert!(range.is_empty());
+
+        let range = TimeRange::invalid();
+        assert!(!range.is_valid());
+        assert!(!range.is_empty());
+    }
+
+    #[test]
+    fn contains() {
+        let r = range(10, 10);
+        assert!(r.contains_time(&Time::new(10, 1)));
+        assert!(r.contains_time(&Time::new(1999, 100)));
+        assert!(!r.contains_time(&Time::new(20, 1)));
+        assert!(!r.contains_time(&Time::invalid()));
+        assert!(!TimeRange::invalid().contains_time(&Time::zero()));
+
+        assert!(r.contains_range(&r));
+        assert!(r.contains_range(&range(20, 0)));
+        assert!(!r.contains_range(&range(15, 10)));
+
+        let r = TimeRange::new(Time::zero(), Time::infinity());
+        assert!(r.end().is_pos_infinity());
+        assert!(r.contains_time(&Time::new(i64::MAX, 1)));
+        assert!(!r.contains_time(&Time::infinity()));
+    }
+
+    #[test]
+    fn union_intersection() {
+        let a = range(0, 10);
+        let b = range(20, 10);
+        assert_eq!(a.union(&b), range(0, 30));
+        assert_eq!(a.intersection(&b), TimeRange::zero());
+        assert_eq!(a.intersection(&range(10, 5)), TimeRange::zero());
+        assert_eq!(a.intersection(&range(5, 2)), range(5, 2));
+        assert!(!a.union(&TimeRange::invalid()).is_valid());
+
+        let c = TimeRange::new(Time::new(1, 3), Time::new(1, 4));
+        let u = a.union(&c);
+        assert_eq!(u.duration, Time::new(10, 1));
+        let i = a.intersection(&c);
+        assert_eq!(i.end(), Time::new(7, 12));
+
+        assert_eq!(a.clamp(Time::new(-1, 1)), Time::zero());
+        assert_eq!(a.clamp(Time::new(11, 1)), Time::new(10, 1));
+        assert_eq!(a.clamp(Time::new(3, 1)), Time::new(3, 1));
+    }
+
+    #[test]
+    fn mapping() {
+        let mapping = TimeMapping::new(range(10, 30), range(0, 10));
+        assert_eq!(mapping.map_time(Time::new(10, 1)), Time::zero());
+        assert_eq!(mapping.map_time(Time::new(40, 1)), Time::new(10, 1));
+        let t = mapping.map_time(Time::new(11, 1));
+        assert_eq!(t, Time::new(1, 3).convert_scale(1, Default::default()));
+        assert!(t.has_been_rounded());
+
+        let mapping = TimeMapping::new(
+            range(0, 1),
+            TimeRange::new(Time::new(0, 600), Time::new(1200, 600)),
+        );
+        let t = mapping.map_time(Time::new(1, 3));
+        assert_eq!((t.value, t.scale), (400, 600));
+        assert!(!t.has_been_rounded());
+
+        let empty = TimeMapping::empty(range(0, 10));
+        assert!(empty.is_empty());
+        assert!(empty.is_valid());
+        assert!(empty.map_time(Time::zero()).is_invalid());
+        assert!(!mapping.is_empty());
+
+        let zero = TimeMapping::new(range(0, 0), range(0, 10));
+        assert!(zero.map_duration(Time::new(1, 1)).is_invalid());
+    }
+}
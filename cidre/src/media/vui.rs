@@ -0,0 +1,116 @@
+use super::{Result, bits::BitReader};
+
+/// `colour_description` of VUI, ISO/IEC 23091-2 code points.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Color {
+    pub primaries: u8,
+    pub transfer: u8,
+    pub matrix: u8,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Timing {
+    pub num_units_in_tick: u32,
+    pub time_scale: u32,
+    /// H.264 `fixed_frame_rate_flag`, always `false` for HEVC.
+    pub fixed_frame_rate: bool,
+}
+
+/// Video usability information, the parts shared by H.264 and HEVC.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Vui {
+    /// Sample aspect ratio
+    pub sar: Option<(u16, u16)>,
+    pub video_format: Option<u8>,
+    pub full_range: bool,
+    pub color: Option<Color>,
+    pub timing: Option<Timing>,
+}
+
+const SAR: [(u16, u16); 17] = [
+    (0, 0),
+    (1, 1),
+    (12, 11),
+    (10, 11),
+    (16, 11),
+    (40, 33),
+    (24, 11),
+    (20, 11),
+    (32, 11),
+    (80, 33),
+    (18, 11),
+    (15, 11),
+    (64, 33),
+    (160, 99),
+    (4, 3),
+    (3, 2),
+    (2, 1),
+];
+
+impl Vui {
+    /// Parses up to and including `chroma_loc_info`.
+    fn parse_head(r: &mut BitReader) -> Result<Self> {
+        let mut res = Self::default();
+        if r.bit()? {
+            let idc = r.u8(8)?;
+            res.sar = match idc {
+                255 => Some((r.u16(16)?, r.u16(16)?)),
+                1..=16 => Some(SAR[idc as usize]),
+                _ => None,
+            };
+        }
+        if r.bit()? {
+            // overscan_appropriate_flag
+            r.skip(1)?;
+        }
+        if r.bit()? {
+            res.video_format = Some(r.u8(3)?);
+            res.full_range = r.bit()?;
+            if r.bit()? {
+                res.color = Some(Color {
+                    primaries: r.u8(8)?,
+                    transfer: r.u8(8)?,
+                    matrix: r.u8(8)?,
+                });
+            }
+        }
+        if r.bit()? {
+            // chroma_sample_loc_type_top_field, bottom_field
+            r.ue()?;
+            r.ue()?;
+        }
+        Ok(res)
+    }
+
+    pub(crate) fn parse_avc(r: &mut BitReader) -> Result<Self> {
+        let mut res = Self::parse_head(r)?;
+        if r.bit()? {
+            res.timing = Some(Timing {
+                num_units_in_tick: r.u32(32)?,
+                time_scale: r.u32(32)?,
+                fixed_frame_rate: r.bit()?,
+            });
+        }
+        Ok(res)
+    }
+
+    pub(crate) fn parse_hevc(r: &mut BitReader) -> Result<Self> {
+        let mut res = Self::parse_head(r)?;
+        // neutral_chroma_indication_flag, field_seq_flag, frame_field_info_present_flag
+        r.skip(3)?;
+        if r.bit()? {
+            // default display window offsets
+            for _ in 0..4 {
+                r.ue()?;
+            }
+        }
+        if r.bit()? {
+            res.timing = Some(Timing {
+                num_units_in_tick: r.u32(32)?,
+                time_scale: r.u32(32)?,
+                fixed_frame_rate: false,
+            });
+        }
+        Ok(res)
+    }
+}
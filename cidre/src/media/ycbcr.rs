@@ -0,0 +1,888 @@
+//! Reference Y'CbCr ⇄ RGB conversion on the CPU
+//!
+//! Works on plane slices with explicit bytes per row, the shape of
+//! `cv::PixelBuf::plane_base_address` and `plane_bytes_per_row` on a locked buffer.
+//! Chroma is point sampled when upsampling and box filtered when downsampling.
+//!
+//! ```
+//! use cidre::media::ycbcr::{Converter, Matrix, Plane, PlaneMut, RgbFormat, YcbcrFormat};
+//!
+//! let (w, h) = (4, 2);
+//! let luma = [235u8; 8];
+//! let chroma = [128u8; 4];
+//! let src = [Plane::new(&luma, w), Plane::new(&chroma, w)];
+//!
+//! let mut bgra = [0u8; 32];
+//! let mut dst = PlaneMut::new(&mut bgra, w * 4);
+//! let conv = Converter::new(Matrix::Bt709);
+//! conv.to_rgb(YcbcrFormat::BiPlanar8Video, &src, RgbFormat::Bgra8, &mut dst, w, h)
+//!     .unwrap();
+//! assert!(bgra.iter().all(|&b| b == 255));
+//! ```
+
+use super::{Error, Result, pixel_format};
+
+/// Y'CbCr to RGB matrix
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Matrix {
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_601_4")]
+    Bt601,
+
+    #[default]
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_709_2")]
+    Bt709,
+
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_2020")]
+    Bt2020,
+}
+
+impl Matrix {
+    /// Red and blue luma weights
+    pub const fn kr_kb(self) -> (f32, f32) {
+        match self {
+            Self::Bt601 => (0.299, 0.114),
+            Self::Bt709 => (0.2126, 0.0722),
+            Self::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+}
+
+/// Y'CbCr layouts the converter reads and writes
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum YcbcrFormat {
+    /// `420v`, NV12
+    BiPlanar8Video,
+
+    /// `420f`, NV12
+    BiPlanar8Full,
+
+    /// `x420`, P010
+    BiPlanar10Video,
+
+    /// `xf20`, P010
+    BiPlanar10Full,
+
+    /// `2vuy`, UYVY
+    Uyvy,
+
+    /// `yuvs`, YUY2
+    Yuyv,
+
+    /// `v210`
+    V210,
+}
+
+impl YcbcrFormat {
+    /// `cv::PixelFormat` four char code
+    pub const fn code(self) -> u32 {
+        u32::from_be_bytes(*match self {
+            Self::BiPlanar8Video => b"420v",
+            Self::BiPlanar8Full => b"420f",
+            Self::BiPlanar10Video => b"x420",
+            Self::BiPlanar10Full => b"xf20",
+            Self::Uyvy => b"2vuy",
+            Self::Yuyv => b"yuvs",
+            Self::V210 => b"v210",
+        })
+    }
+
+    pub fn with_code(code: u32) -> Option<Self> {
+        [
+            Self::BiPlanar8Video,
+            Self::BiPlanar8Full,
+            Self::BiPlanar10Video,
+            Self::BiPlanar10Full,
+            Self::Uyvy,
+            Self::Yuyv,
+            Self::V210,
+        ]
+        .into_iter()
+        .find(|f| f.code() == code)
+    }
+
+    pub fn desc(self) -> &'static pixel_format::Desc {
+        pixel_format::cv_desc(self.code()).unwrap()
+    }
+
+    pub const fn bit_depth(self) -> u32 {
+        match self {
+            Self::BiPlanar8Video | Self::BiPlanar8Full | Self::Uyvy | Self::Yuyv => 8,
+            Self::BiPlanar10Video | Self::BiPlanar10Full | Self::V210 => 10,
+        }
+    }
+
+    pub const fn is_full_range(self) -> bool {
+        matches!(self, Self::BiPlanar8Full | Self::BiPlanar10Full)
+    }
+
+    /// Chroma subsampling divisors
+    const fn sub(self) -> (usize, usize) {
+        match self {
+            Self::BiPlanar8Video
+            | Self::BiPlanar8Full
+            | Self::BiPlanar10Video
+            | Self::BiPlanar10Full => (2, 2),
+            Self::Uyvy | Self::Yuyv | Self::V210 => (2, 1),
+        }
+    }
+
+    fn luma(self, planes: &[Plane], x: usize, y: usize) -> u32 {
+        let row = planes[0].row(y);
+        match self {
+            Self::BiPlanar8Video | Self::BiPlanar8Full => row[x] as u32,
+            Self::BiPlanar10Video | Self::BiPlanar10Full => u16_le(row, x * 2) as u32 >> 6,
+            Self::Uyvy => row[x / 2 * 4 + 1 + x % 2 * 2] as u32,
+            Self::Yuyv => row[x / 2 * 4 + x % 2 * 2] as u32,
+            Self::V210 => v210(row, x / 6, x % 6 * 2 + 1),
+        }
+    }
+
+    fn chroma(self, planes: &[Plane], cx: usize, cy: usize) -> (u32, u32) {
+        match self {
+            Self::BiPlanar8Video | Self::BiPlanar8Full => {
+                let row = planes[1].row(cy);
+                (row[cx * 2] as u32, row[cx * 2 + 1] as u32)
+            }
+            Self::BiPlanar10Video | Self::BiPlanar10Full => {
+                let row = planes[1].row(cy);
+                let cb = u16_le(row, cx * 4) >> 6;
+                let cr = u16_le(row, cx * 4 + 2) >> 6;
+                (cb as u32, cr as u32)
+            }
+            Self::Uyvy => {
+                let row = planes[0].row(cy);
+                (row[cx * 4] as u32, row[cx * 4 + 2] as u32)
+            }
+            Self::Yuyv => {
+                let row = planes[0].row(cy);
+                (row[cx * 4 + 1] as u32, row[cx * 4 + 3] as u32)
+            }
+            Self::V210 => {
+                let row = planes[0].row(cy);
+                let (group, j) = (cx / 3, cx % 3 * 4);
+                (v210(row, group, j), v210(row, group, j + 2))
+            }
+        }
+    }
+
+    fn put_luma(self, planes: &mut [PlaneMut], x: usize, y: usize, code: u32) {
+        let row = planes[0].row_mut(y);
+        match self {
+            Self::BiPlanar8Video | Self::BiPlanar8Full => row[x] = code as u8,
+            Self::BiPlanar10Video | Self::BiPlanar10Full => put_u16_le(row, x * 2, code << 6),
+            Self::Uyvy => row[x / 2 * 4 + 1 + x % 2 * 2] = code as u8,
+            Self::Yuyv => row[x / 2 * 4 + x % 2 * 2] = code as u8,
+            Self::V210 => put_v210(row, x / 6, x % 6 * 2 + 1, code),
+        }
+    }
+
+    fn put_chroma(self, planes: &mut [PlaneMut], cx: usize, cy: usize, cb: u32, cr: u32) {
+        match self {
+            Self::BiPlanar8Video | Self::BiPlanar8Full => {
+                let row = planes[1].row_mut(cy);
+                row[cx * 2] = cb as u8;
+                row[cx * 2 + 1] = cr as u8;
+            }
+            Self::BiPlanar10Video | Self::BiPlanar10Full => {
+                let row = planes[1].row_mut(cy);
+                put_u16_le(row, cx * 4, cb << 6);
+                put_u16_le(row, cx * 4 + 2, cr << 6);
+            }
+            Self::Uyvy => {
+                let row = planes[0].row_mut(cy);
+                row[cx * 4] = cb as u8;
+                row[cx * 4 + 2] = cr as u8;
+            }
+            Self::Yuyv => {
+                let row = planes[0].row_mut(cy);
+                row[cx * 4 + 1] = cb as u8;
+                row[cx * 4 + 3] = cr as u8;
+            }
+            Self::V210 => {
+                let row = planes[0].row_mut(cy);
+                let (group, j) = (cx / 3, cx % 3 * 4);
+                put_v210(row, group, j, cb);
+                put_v210(row, group, j + 2, cr);
+            }
+        }
+    }
+}
+
+/// RGB layouts the converter reads and writes
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RgbFormat {
+    /// `BGRA`
+    Bgra8,
+
+    /// `RGBA`
+    Rgba8,
+
+    /// `RGhA`, little endian half floats
+    Rgba16Half,
+}
+
+impl RgbFormat {
+    /// `cv::PixelFormat` four char code
+    pub const fn code(self) -> u32 {
+        u32::from_be_bytes(*match self {
+            Self::Bgra8 => b"BGRA",
+            Self::Rgba8 => b"RGBA",
+            Self::Rgba16Half => b"RGhA",
+        })
+    }
+
+    pub fn with_code(code: u32) -> Option<Self> {
+        [Self::Bgra8, Self::Rgba8, Self::Rgba16Half]
+            .into_iter()
+            .find(|f| f.code() == code)
+    }
+
+    pub fn desc(self) -> &'static pixel_format::Desc {
+        pixel_format::cv_desc(self.code()).unwrap()
+    }
+
+    fn get(self, row: &[u8], x: usize) -> [f32; 4] {
+        match self {
+            Self::Bgra8 => {
+                let p = &row[x * 4..x * 4 + 4];
+                [p[2], p[1], p[0], p[3]].map(|v| v as f32 / 255.0)
+            }
+            Self::Rgba8 => {
+                let p = &row[x * 4..x * 4 + 4];
+                [p[0], p[1], p[2], p[3]].map(|v| v as f32 / 255.0)
+            }
+            Self::Rgba16Half => std::array::from_fn(|i| f16_to_f32(u16_le(row, x * 8 + i * 2))),
+        }
+    }
+
+    fn put(self, row: &mut [u8], x: usize, rgba: [f32; 4]) {
+        let unorm = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        match self {
+            Self::Bgra8 => {
+                let [r, g, b, a] = rgba.map(unorm);
+                row[x * 4..x * 4 + 4].copy_from_slice(&[b, g, r, a]);
+            }
+            Self::Rgba8 => row[x * 4..x * 4 + 4].copy_from_slice(&rgba.map(unorm)),
+            Self::Rgba16Half => {
+                for (i, v) in rgba.into_iter().enumerate() {
+                    put_u16_le(row, x * 8 + i * 2, f32_to_f16(v) as u32);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "cv")]
+impl TryFrom<crate::cv::PixelFormat> for YcbcrFormat {
+    type Error = Error;
+
+    fn try_from(value: crate::cv::PixelFormat) -> Result<Self> {
+        Self::with_code(value.0).ok_or(Error::Unsupported("pixel format"))
+    }
+}
+
+#[cfg(feature = "cv")]
+impl From<YcbcrFormat> for crate::cv::PixelFormat {
+    fn from(value: YcbcrFormat) -> Self {
+        Self(value.code())
+    }
+}
+
+#[cfg(feature = "cv")]
+impl TryFrom<crate::cv::PixelFormat> for RgbFormat {
+    type Error = Error;
+
+    fn try_from(value: crate::cv::PixelFormat) -> Result<Self> {
+        Self::with_code(value.0).ok_or(Error::Unsupported("pixel format"))
+    }
+}
+
+#[cfg(feature = "cv")]
+impl From<RgbFormat> for crate::cv::PixelFormat {
+    fn from(value: RgbFormat) -> Self {
+        Self(value.code())
+    }
+}
+
+/// Read only plane of an image
+#[derive(Debug, Copy, Clone)]
+pub struct Plane<'a> {
+    pub data: &'a [u8],
+    pub bytes_per_row: usize,
+}
+
+impl<'a> Plane<'a> {
+    #[inline]
+    pub const fn new(data: &'a [u8], bytes_per_row: usize) -> Self {
+        Self {
+            data,
+            bytes_per_row,
+        }
+    }
+
+    fn row(&self, y: usize) -> &[u8] {
+        &self.data[y * self.bytes_per_row..]
+    }
+}
+
+/// Writable plane of an image
+#[derive(Debug)]
+pub struct PlaneMut<'a> {
+    pub data: &'a mut [u8],
+    pub bytes_per_row: usize,
+}
+
+impl<'a> PlaneMut<'a> {
+    #[inline]
+    pub const fn new(data: &'a mut [u8], bytes_per_row: usize) -> Self {
+        Self {
+            data,
+            bytes_per_row,
+        }
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u8] {
+        &mut self.data[y * self.bytes_per_row..]
+    }
+}
+
+/// Checks plane count, row lengths and data lengths against the format layout
+fn check(
+    desc: &pixel_format::Desc,
+    planes: impl ExactSizeIterator<Item = (usize, usize)>,
+    width: usize,
+    height: usize,
+) -> Result {
+    if planes.len() != desc.plane_count() {
+        return Err(Error::Invalid("plane count"));
+    }
+    for (i, (len, bytes_per_row)) in planes.enumerate() {
+        let row = desc.bytes_per_row(i, width, 1).unwrap();
+        if bytes_per_row < row {
+            return Err(Error::Invalid("bytes per row"));
+        }
+        let rows = desc.planes[i].rows(height);
+        if rows > 0 && len < bytes_per_row * (rows - 1) + row {
+            return Err(Error::Invalid("plane size"));
+        }
+    }
+    Ok(())
+}
+
+/// Converts between Y'CbCr and RGB with one matrix
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Converter {
+    matrix: Matrix,
+    kr: f32,
+    kb: f32,
+}
+
+impl Default for Converter {
+    fn default() -> Self {
+        Self::new(Matrix::default())
+    }
+}
+
+impl Converter {
+    pub fn new(matrix: Matrix) -> Self {
+        let (kr, kb) = matrix.kr_kb();
+        Self { matrix, kr, kb }
+    }
+
+    #[inline]
+    pub fn matrix(&self) -> Matrix {
+        self.matrix
+    }
+
+    /// Non linear R'G'B' of normalized Y' in [0, 1] and Cb, Cr in [-0.5, 0.5]
+    pub fn ycbcr_to_rgb(&self, y: f32, cb: f32, cr: f32) -> [f32; 3] {
+        let r = y + 2.0 * (1.0 - self.kr) * cr;
+        let b = y + 2.0 * (1.0 - self.kb) * cb;
+        let g = (y - self.kr * r - self.kb * b) / (1.0 - self.kr - self.kb);
+        [r, g, b]
+    }
+
+    /// Normalized Y', Cb, Cr of non linear R'G'B'
+    pub fn rgb_to_ycbcr(&self, r: f32, g: f32, b: f32) -> [f32; 3] {
+        let y = self.kr * r + (1.0 - self.kr - self.kb) * g + self.kb * b;
+        let cb = (b - y) / (2.0 * (1.0 - self.kb));
+        let cr = (r - y) / (2.0 * (1.0 - self.kr));
+        [y, cb, cr]
+    }
+
+    /// Converts `width` × `height` pixels of `src` to `dst`.
+    ///
+    /// Alpha is opaque. 8-bit outputs are clamped, half float outputs keep values
+    /// outside [0, 1] such as video range footroom.
+    pub fn to_rgb(
+        &self,
+        src_format: YcbcrFormat,
+        src: &[Plane],
+        dst_format: RgbFormat,
+        dst: &mut PlaneMut,
+        width: usize,
+        height: usize,
+    ) -> Result {
+        check(
+            src_format.desc(),
+            src.iter().map(|p| (p.data.len(), p.bytes_per_row)),
+            width,
+            height,
+        )?;
+        check(
+            dst_format.desc(),
+            std::iter::once((dst.data.len(), dst.bytes_per_row)),
+            width,
+            height,
+        )?;
+        let range = Range::new(src_format);
+        let (h_sub, v_sub) = src_format.sub();
+        for y in 0..height {
+            for x in 0..width {
+                let luma = src_format.luma(src, x, y);
+                let (cb, cr) = src_format.chroma(src, x / h_sub, y / v_sub);
+                let [l, cb, cr] = range.normalize(luma, cb, cr);
+                let [r, g, b] = self.ycbcr_to_rgb(l, cb, cr);
+                dst_format.put(dst.row_mut(y), x, [r, g, b, 1.0]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts `width` × `height` pixels of `src` to `dst`, ignoring alpha.
+    ///
+    /// Chroma is the average of the pixels each sample covers.
+    pub fn to_ycbcr(
+        &self,
+        src_format: RgbFormat,
+        src: &Plane,
+        dst_format: YcbcrFormat,
+        dst: &mut [PlaneMut],
+        width: usize,
+        height: usize,
+    ) -> Result {
+        check(
+            src_format.desc(),
+            std::iter::once((src.data.len(), src.bytes_per_row)),
+            width,
+            height,
+        )?;
+        check(
+            dst_format.desc(),
+            dst.iter().map(|p| (p.data.len(), p.bytes_per_row)),
+            width,
+            height,
+        )?;
+        let range = Range::new(dst_format);
+        let mut ycc = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row = src.row(y);
+            for x in 0..width {
+                let [r, g, b, _] = src_format.get(row, x);
+                ycc.push(self.rgb_to_ycbcr(r, g, b));
+            }
+        }
+
+        // v210 groups are 6 pixels wide, padding repeats the last pixel
+        let padded = if dst_format == YcbcrFormat::V210 {
+            width.next_multiple_of(6)
+        } else {
+            width
+        };
+        for y in 0..height {
+            for x in 0..padded {
+                let l = ycc[y * width + x.min(width - 1)][0];
+                dst_format.put_luma(dst, x, y, range.luma(l));
+            }
+        }
+
+        let (h_sub, v_sub) = dst_format.sub();
+        for cy in 0..height.div_ceil(v_sub) {
+            for cx in 0..padded.div_ceil(h_sub) {
+                let (mut cb, mut cr, mut n) = (0.0, 0.0, 0.0);
+                for y in cy * v_sub..((cy + 1) * v_sub).min(height) {
+                    for x in cx * h_sub..((cx + 1) * h_sub).min(width) {
+                        let [_, b, r] = ycc[y * width + x];
+                        cb += b;
+                        cr += r;
+                        n += 1.0;
+                    }
+                }
+                if n == 0.0 {
+                    [_, cb, cr] = ycc[cy * v_sub * width + width - 1];
+                    n = 1.0;
+                }
+                dst_format.put_chroma(dst, cx, cy, range.chroma(cb / n), range.chroma(cr / n));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Code value scaling of one format
+struct Range {
+    y_off: f32,
+    y_scale: f32,
+    c_off: f32,
+    c_scale: f32,
+    max: f32,
+}
+
+impl Range {
+    fn new(format: YcbcrFormat) -> Self {
+        let bits = format.bit_depth();
+        let max = ((1u32 << bits) - 1) as f32;
+        let c_off = (1u32 << (bits - 1)) as f32;
+        if format.is_full_range() {
+            Self {
+                y_off: 0.0,
+                y_scale: max,
+                c_off,
+                c_scale: max,
+                max,
+            }
+        } else {
+            let unit = (1u32 << (bits - 8)) as f32;
+            Self {
+                y_off: 16.0 * unit,
+                y_scale: 219.0 * unit,
+                c_off,
+                c_scale: 224.0 * unit,
+                max,
+            }
+        }
+    }
+
+    fn normalize(&self, y: u32, cb: u32, cr: u32) -> [f32; 3] {
+        [
+            (y as f32 - self.y_off) / self.y_scale,
+            (cb as f32 - self.c_off) / self.c_scale,
+            (cr as f32 - self.c_off) / self.c_scale,
+        ]
+    }
+
+    fn luma(&self, y: f32) -> u32 {
+        (y * self.y_scale + self.y_off).round().clamp(0.0, self.max) as u32
+    }
+
+    fn chroma(&self, c: f32) -> u32 {
+        (c * self.c_scale + self.c_off).round().clamp(0.0, self.max) as u32
+    }
+}
+
+#[inline]
+fn u16_le(row: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([row[offset], row[offset + 1]])
+}
+
+#[inline]
+fn put_u16_le(row: &mut [u8], offset: usize, value: u32) {
+    row[offset..offset + 2].copy_from_slice(&(value as u16).to_le_bytes());
+}
+
+/// Component `index` of a 6 pixel v210 group, ordered Cb0 Y0 Cr0 Y1 Cb1 Y2 Cr1 Y3 Cb2 Y4 Cr2 Y5
+fn v210(row: &[u8], group: usize, index: usize) -> u32 {
+    let offset = group * 16 + index / 3 * 4;
+    let word = u32::from_le_bytes(row[offset..offset + 4].try_into().unwrap());
+    (word >> (index % 3 * 10)) & 0x3ff
+}
+
+fn put_v210(row: &mut [u8], group: usize, index: usize, value: u32) {
+    let offset = group * 16 + index / 3 * 4;
+    let shift = index % 3 * 10;
+    let word = u32::from_le_bytes(row[offset..offset + 4].try_into().unwrap());
+    let word = (word & !(0x3ff << shift)) | ((value & 0x3ff) << shift);
+    row[offset..offset + 4].copy_from_slice(&word.to_le_bytes());
+}
+
+fn f16_to_f32(h: u16) -> f32 {
+    let sign = ((h as u32) & 0x8000) << 16;
+    let exp = (h >> 10) & 0x1f;
+    let man = (h & 0x3ff) as u32;
+    let bits = match exp {
+        0 if man == 0 => sign,
+        0 => {
+            // subnormal, value is man * 2^-24
+            let v = man as f32 * f32::from_bits(0x3380_0000);
+            return if sign != 0 { -v } else { v };
+        }
+        0x1f => sign | 0x7f80_0000 | (man << 13),
+        _ => sign | ((exp as u32 + 112) << 23) | (man << 13),
+    };
+    f32::from_bits(bits)
+}
+
+/// Round to nearest even, overflow goes to infinity
+fn f32_to_f16(v: f32) -> u16 {
+    let bits = v.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let man = bits & 0x7f_ffff;
+    if exp == 0xff {
+        let nan = if man != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let exp = exp - 127 + 15;
+    if exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if exp <= 0 {
+        if exp < -10 {
+            return sign;
+        }
+        // subnormal, keep the implicit bit and shift it down
+        let man = man | 0x80_0000;
+        let shift = (14 - exp) as u32;
+        let half = 1 << (shift - 1);
+        let rest = man & ((1 << shift) - 1);
+        let mut h = (man >> shift) as u16;
+        if rest > half || (rest == half && h & 1 == 1) {
+            h += 1;
+        }
+        return sign | h;
+    }
+    let mut h = ((exp as u32) << 10 | (man >> 13)) as u16;
+    let rest = man & 0x1fff;
+    if rest > 0x1000 || (rest == 0x1000 && h & 1 == 1) {
+        // carry into the exponent is the correct rounding, up to infinity
+        h += 1;
+    }
+    sign | h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YCBCR: [YcbcrFormat; 7] = [
+        YcbcrFormat::BiPlanar8Video,
+        YcbcrFormat::BiPlanar8Full,
+        YcbcrFormat::BiPlanar10Video,
+        YcbcrFormat::BiPlanar10Full,
+        YcbcrFormat::Uyvy,
+        YcbcrFormat::Yuyv,
+        YcbcrFormat::V210,
+    ];
+
+    const RGB: [RgbFormat; 3] = [RgbFormat::Bgra8, RgbFormat::Rgba8, RgbFormat::Rgba16Half];
+
+    /// Buffers sized for `format` with 16 byte aligned rows
+    fn alloc(desc: &pixel_format::Desc, width: usize, height: usize) -> Vec<(Vec<u8>, usize)> {
+        (0..desc.plane_count())
+            .map(|i| {
+                let bpr = desc.bytes_per_row(i, width, 16).unwrap();
+                (
+                    vec![0u8; desc.plane_size(i, width, height, 16).unwrap()],
+                    bpr,
+                )
+            })
+            .collect()
+    }
+
+    fn encode(
+        conv: &Converter,
+        format: YcbcrFormat,
+        rgba: &[u8],
+        width: usize,
+        height: usize,
+    ) -> Vec<(Vec<u8>, usize)> {
+        let mut planes = alloc(format.desc(), width, height);
+        let mut dst: Vec<_> = planes
+            .iter_mut()
+            .map(|(d, bpr)| PlaneMut::new(d, *bpr))
+            .collect();
+        let src = Plane::new(rgba, width * 4);
+        conv.to_ycbcr(RgbFormat::Rgba8, &src, format, &mut dst, width, height)
+            .unwrap();
+        planes
+    }
+
+    fn decode(
+        conv: &Converter,
+        format: YcbcrFormat,
+        planes: &[(Vec<u8>, usize)],
+        width: usize,
+        height: usize,
+    ) -> Vec<u8> {
+        let src: Vec<_> = planes.iter().map(|(d, bpr)| Plane::new(d, *bpr)).collect();
+        let mut rgba = vec![0u8; width * height * 4];
+        let mut dst = PlaneMut::new(&mut rgba, width * 4);
+        conv.to_rgb(format, &src, RgbFormat::Rgba8, &mut dst, width, height)
+            .unwrap();
+        rgba
+    }
+
+    #[test]
+    fn matrices() {
+        for m in [Matrix::Bt601, Matrix::Bt709, Matrix::Bt2020] {
+            let conv = Converter::new(m);
+            for rgb in [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.2, 0.5, 0.9], [1.0; 3]] {
+                let [y, cb, cr] = conv.rgb_to_ycbcr(rgb[0], rgb[1], rgb[2]);
+                assert!((-0.5..=0.5).contains(&cb) && (-0.5..=0.5).contains(&cr));
+                let back = conv.ycbcr_to_rgb(y, cb, cr);
+                for i in 0..3 {
+                    assert!((back[i] - rgb[i]).abs() < 1e-5, "{m:?} {rgb:?} {back:?}");
+                }
+            }
+        }
+        let [y, cb, cr] = Converter::new(Matrix::Bt601).rgb_to_ycbcr(1.0, 0.0, 0.0);
+        assert!((y - 0.299).abs() < 1e-6);
+        assert!((cb + 0.168_736).abs() < 1e-5);
+        assert!((cr - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn codes() {
+        let conv = Converter::new(Matrix::Bt709);
+        let white = [255u8, 255, 255, 255];
+        let black = [0u8, 0, 0, 255];
+        let (w, h) = (2, 2);
+        let img: Vec<u8> = [white, black, black, white].concat();
+
+        let nv12 = encode(&conv, YcbcrFormat::BiPlanar8Video, &img, w, h);
+        assert_eq!(&nv12[0].0[..2], &[235, 16]);
+        assert_eq!(&nv12[0].0[16..18], &[16, 235]);
+        assert_eq!(&nv12[1].0[..2], &[128, 128]);
+
+        let nv12 = encode(&conv, YcbcrFormat::BiPlanar8Full, &img, w, h);
+        assert_eq!(&nv12[0].0[..2], &[255, 0]);
+
+        let p010 = encode(&conv, YcbcrFormat::BiPlanar10Video, &img, w, h);
+        assert_eq!(u16_le(&p010[0].0, 0), 940 << 6);
+        assert_eq!(u16_le(&p010[0].0, 2), 64 << 6);
+        assert_eq!(u16_le(&p010[1].0, 0), 512 << 6);
+
+        let uyvy = encode(&conv, YcbcrFormat::Uyvy, &img, w, h);
+        assert_eq!(&uyvy[0].0[..4], &[128, 235, 128, 16]);
+        let yuyv = encode(&conv, YcbcrFormat::Yuyv, &img, w, h);
+        assert_eq!(&yuyv[0].0[..4], &[235, 128, 16, 128]);
+
+        let packed = encode(&conv, YcbcrFormat::V210, &img, w, h);
+        let word = u32::from_le_bytes(packed[0].0[..4].try_into().unwrap());
+        assert_eq!(word, 512 | 940 << 10 | 512 << 20);
+        // padding repeats the last pixel
+        assert_eq!(v210(&packed[0].0, 0, 11), 64);
+    }
+
+    #[test]
+    fn round_trip() {
+        let (w, h) = (13, 7);
+        let img: Vec<u8> = (0..w * h)
+            .flat_map(|i| {
+                let (x, y) = (i % w, i / w);
+                [(x * 19) as u8, (y * 36) as u8, ((x + y) * 12) as u8, 255]
+            })
+            .collect();
+        // flat 2×2 blocks so subsampling loses nothing but rounding
+        let flat: Vec<u8> = (0..w * h)
+            .flat_map(|i| {
+                let (x, y) = (i % w / 2, i / w / 2);
+                let p = (y * w + x * 2) * 4;
+                img[p..p + 4].to_vec()
+            })
+            .collect();
+        for m in [Matrix::Bt601, Matrix::Bt709, Matrix::Bt2020] {
+            let conv = Converter::new(m);
+            for f in YCBCR {
+                let planes = encode(&conv, f, &flat, w, h);
+                let back = decode(&conv, f, &planes, w, h);
+                let tolerance = if f.bit_depth() == 8 { 2 } else { 1 };
+                for (a, b) in flat.iter().zip(back.iter()) {
+                    assert!(a.abs_diff(*b) <= tolerance, "{m:?} {f:?} {a} {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn half_float() {
+        let conv = Converter::new(Matrix::Bt709);
+        let luma = [16u8, 235, 0, 255];
+        let chroma = [128u8, 128];
+        let src = [Plane::new(&luma, 2), Plane::new(&chroma, 2)];
+        let mut out = [0u8; 32];
+        let mut dst = PlaneMut::new(&mut out, 16);
+        conv.to_rgb(
+            YcbcrFormat::BiPlanar8Video,
+            &src,
+            RgbFormat::Rgba16Half,
+            &mut dst,
+            2,
+            2,
+        )
+        .unwrap();
+        let px = |x: usize| {
+            std::array::from_fn::<f32, 4, _>(|i| f16_to_f32(u16_le(&out, x * 8 + i * 2)))
+        };
+        assert_eq!(px(0), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(px(1), [1.0, 1.0, 1.0, 1.0]);
+        // footroom and headroom are kept
+        assert!(px(2)[0] < -0.07);
+        assert!(px(3)[0] > 1.08);
+
+        for v in [
+            0.0f32, -0.0, 1.0, -2.5, 0.333, 65504.0, 6.1e-5, 3.0e-6, 1e-9,
+        ] {
+            let h = f32_to_f16(v);
+            assert!((f16_to_f32(h) - v).abs() <= v.abs() / 1024.0 + 6e-8, "{v}");
+        }
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        assert_eq!(f32_to_f16(65520.0), 0x7c00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        for h in 0..0x7c00u16 {
+            assert_eq!(f32_to_f16(f16_to_f32(h)), h);
+        }
+    }
+
+    #[test]
+    fn formats() {
+        for f in YCBCR {
+            assert_eq!(YcbcrFormat::with_code(f.code()), Some(f));
+            assert!(f.desc().is_ycbcr());
+            assert_eq!(f.desc().is_full_range(), f.is_full_range());
+            assert_eq!(f.desc().bit_depth as u32, f.bit_depth());
+        }
+        for f in RGB {
+            assert_eq!(RgbFormat::with_code(f.code()), Some(f));
+            assert!(f.desc().is_rgb());
+        }
+        assert_eq!(YcbcrFormat::with_code(RgbFormat::Bgra8.code()), None);
+    }
+
+    #[test]
+    fn errors() {
+        let conv = Converter::default();
+        let luma = [0u8; 16];
+        let chroma = [0u8; 8];
+        let mut out = [0u8; 64];
+        let mut dst = PlaneMut::new(&mut out, 16);
+        let f = YcbcrFormat::BiPlanar8Video;
+
+        let src = [Plane::new(&luma, 4)];
+        let res = conv.to_rgb(f, &src, RgbFormat::Bgra8, &mut dst, 4, 4);
+        assert_eq!(res, Err(Error::Invalid("plane count")));
+
+        let src = [Plane::new(&luma, 3), Plane::new(&chroma, 4)];
+        let res = conv.to_rgb(f, &src, RgbFormat::Bgra8, &mut dst, 4, 4);
+        assert_eq!(res, Err(Error::Invalid("bytes per row")));
+
+        let src = [Plane::new(&luma, 4), Plane::new(&chroma[..7], 4)];
+        let res = conv.to_rgb(f, &src, RgbFormat::Bgra8, &mut dst, 4, 4);
+        assert_eq!(res, Err(Error::Invalid("plane size")));
+
+        let src = [Plane::new(&luma, 4), Plane::new(&chroma, 4)];
+        let mut small = PlaneMut::new(&mut out[..63], 16);
+        let res = conv.to_rgb(f, &src, RgbFormat::Bgra8, &mut small, 4, 4);
+        assert_eq!(res, Err(Error::Invalid("plane size")));
+
+        let mut dst = PlaneMut::new(&mut out, 16);
+        assert!(
+            conv.to_rgb(f, &src, RgbFormat::Bgra8, &mut dst, 4, 4)
+                .is_ok()
+        );
+        assert!(
+            conv.to_rgb(f, &src, RgbFormat::Bgra8, &mut dst, 0, 0)
+                .is_ok()
+        );
+    }
+}
@@ -1,8 +1,8 @@
-use crate::ns;
+use crate::{ns, objc};
 
 /// A set of dimensions to declare the size of an object, such as an image, texture, threadgroup, or grid.
 #[doc(alias = "MTLSize")]
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, objc::Encode)]
 #[repr(C)]
 pub struct Size {
     pub width: ns::UInteger,
@@ -32,7 +32,7 @@ impl Size {
 
 /// Identify a pixel in an image. MTLOrigin is ususally used as the upper-left corner of a region of a texture.
 #[doc(alias = "MTLOrigin")]
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, objc::Encode)]
 #[repr(C)]
 pub struct Origin {
     pub x: usize,
@@ -57,7 +57,7 @@ pub struct ResId {
 
 /// Identify a region in an image or texture.
 #[doc(alias = "MTLRegion")]
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, objc::Encode)]
 #[repr(C)]
 pub struct Region {
     pub origin: Origin,
@@ -1,7 +1,8 @@
 use crate::{arc, ns, objc};
 
 #[doc(alias = "NSRange")]
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, objc::Encode)]
+#[encode(name = "_NSRange")]
 #[repr(C)]
 pub struct Range {
     pub loc: ns::UInteger,
@@ -133,7 +133,9 @@ impl<T: Obj> Class<T> {
     pub unsafe fn new(&self) -> arc::Retained<T>;
 }
 
-impl<T: Obj> Obj for Class<T> {}
+impl<T: Obj> Obj for Class<T> {
+    const ENCODING: Encoding = Encoding::Class;
+}
 
 impl<T: Obj> arc::Release for T {
     #[inline]
@@ -149,6 +151,9 @@ impl<T: Obj> arc::Retain for T {
 }
 
 pub trait Obj: Sized + arc::Retain {
+    /// Encoding of `&Self`, all objects are `@` except classes and blocks
+    const ENCODING: Encoding = Encoding::Object;
+
     #[inline]
     unsafe fn retain(id: &Self) -> arc::R<Self> {
         unsafe {
@@ -285,8 +290,12 @@ impl std::fmt::Debug for Id {
 pub struct Sel(NonNull<c_void>);
 
 pub mod autorelease_pool;
+pub mod encode;
 pub mod ns;
 pub use autorelease_pool::AutoreleasePoolPage;
+pub use encode::Encode;
+pub use encode::Encoding;
+pub use encode::RefEncode;
 
 pub fn ar_pool<R, F>(f: F) -> R
 where
@@ -482,7 +491,7 @@ macro_rules! define_obj_type {
                     unsafe {
                         let sel = $crate::objc::sel_reg_name(c"init".as_ptr() as _);
                         let imp: extern "C" fn() = init_fn_ptr;
-                        $crate::objc::class_addMethod(cls, sel, imp, std::ptr::null());
+                        $crate::objc::class_addMethod(cls, sel, imp, c"@@:".as_ptr().cast());
                     }
                 }
 
@@ -494,7 +503,7 @@ macro_rules! define_obj_type {
                     unsafe {
                         let sel = $crate::objc::sel_reg_name(c"dealloc".as_ptr() as _);
                         let imp: extern "C" fn() = std::mem::transmute(impl_dealloc as *const u8);
-                        $crate::objc::class_addMethod(cls, sel, imp, std::ptr::null());
+                        $crate::objc::class_addMethod(cls, sel, imp, c"v@:".as_ptr().cast());
                     }
                 }
                 unsafe { $crate::objc::objc_registerClassPair(cls) };
@@ -718,6 +727,7 @@ mod tests {
         let _ptr: &cf::Type = unsafe { std::mem::transmute(ptr) };
     }
 }
+pub use cidre_macros::Encode;
 pub use cidre_macros::add_methods;
 pub use cidre_macros::api_available as available;
 pub use cidre_macros::optional;
@@ -0,0 +1,509 @@
+use std::{
+    ffi::{CString, c_void},
+    fmt::{self, Write},
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+use crate::{arc, cg, objc};
+
+/// Objective-C type encoding, same as `@encode(T)` in clang
+///
+/// ```
+/// use cidre::{cg, objc::Encode};
+///
+/// assert_eq!(cg::Rect::ENCODING.to_string(), "{CGRect={CGPoint=dd}{CGSize=dd}}");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// `c`
+    Char,
+    /// `s`
+    Short,
+    /// `i`
+    Int,
+    /// `q`, `long` is also encoded as `q` on 64-bit platforms
+    LongLong,
+    /// `C`
+    UChar,
+    /// `S`
+    UShort,
+    /// `I`
+    UInt,
+    /// `Q`
+    ULongLong,
+    /// `f`
+    Float,
+    /// `d`
+    Double,
+    /// `B`
+    Bool,
+    /// `v`
+    Void,
+    /// `*`
+    CStr,
+    /// `@`
+    Object,
+    /// `@?`
+    Block,
+    /// `#`
+    Class,
+    /// `:`
+    Sel,
+    /// `?`, function pointers and other unknown types
+    Unknown,
+    /// clang doesn't encode vector types, so they are encoded as empty string
+    Vector,
+    /// `^type`
+    Ptr(&'static Encoding),
+    /// `[len type]`
+    Array(usize, &'static Encoding),
+    /// `{name=fields}`, anonymous structs are named `?`
+    Struct(&'static str, &'static [Encoding]),
+    /// `(name=fields)`
+    Union(&'static str, &'static [Encoding]),
+}
+
+impl Encoding {
+    fn write<W: Write>(&self, w: &mut W, ptr_depth: usize) -> fmt::Result {
+        let str = match self {
+            Self::Char => "c",
+            Self::Short => "s",
+            Self::Int => "i",
+            Self::LongLong => "q",
+            Self::UChar => "C",
+            Self::UShort => "S",
+            Self::UInt => "I",
+            Self::ULongLong => "Q",
+            Self::Float => "f",
+            Self::Double => "d",
+            Self::Bool => "B",
+            Self::Void => "v",
+            Self::CStr => "*",
+            Self::Object => "@",
+            Self::Block => "@?",
+            Self::Class => "#",
+            Self::Sel => ":",
+            Self::Unknown => "?",
+            Self::Vector => "",
+            Self::Ptr(e) => {
+                w.write_char('^')?;
+                return e.write(w, ptr_depth + 1);
+            }
+            Self::Array(len, e) => {
+                write!(w, "[{len}")?;
+                e.write(w, ptr_depth)?;
+                return w.write_char(']');
+            }
+            Self::Struct(name, fields) => {
+                return Self::write_fields(w, ptr_depth, '{', name, fields, '}');
+            }
+            Self::Union(name, fields) => {
+                return Self::write_fields(w, ptr_depth, '(', name, fields, ')');
+            }
+        };
+        w.write_str(str)
+    }
+
+    /// Like clang, fields are omitted behind two or more pointers: `^^{CGRect}`
+    fn write_fields<W: Write>(
+        w: &mut W,
+        ptr_depth: usize,
+        open: char,
+        name: &str,
+        fields: &[Encoding],
+        close: char,
+    ) -> fmt::Result {
+        w.write_char(open)?;
+        w.write_str(name)?;
+        if ptr_depth < 2 {
+            w.write_char('=')?;
+            for f in fields {
+                f.write(w, ptr_depth)?;
+            }
+        }
+        w.write_char(close)
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// Type with known Objective-C encoding
+///
+/// Use `#[derive(objc::Encode)]` for `#[repr(C)]` structs:
+///
+/// ```
+/// use cidre::objc::{self, Encode};
+///
+/// #[derive(objc::Encode)]
+/// #[encode(name = "CGPoint")]
+/// #[repr(C)]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// assert_eq!(Point::ENCODING.to_string(), "{CGPoint=dd}");
+/// ```
+///
+/// # Safety
+///
+/// Encoding should match the memory layout of the type
+pub unsafe trait Encode {
+    const ENCODING: Encoding;
+}
+
+/// Encoding of the reference (or pointer) to the type
+///
+/// Implemented for all `objc::Obj` types, so `&ns::String` is encoded as `@`
+///
+/// # Safety
+///
+/// Encoding should match the memory layout of the pointer to the type
+pub unsafe trait RefEncode {
+    const ENCODING_REF: Encoding;
+}
+
+unsafe impl<T: objc::Obj> RefEncode for T {
+    const ENCODING_REF: Encoding = <T as objc::Obj>::ENCODING;
+}
+
+macro_rules! encode {
+    ($($T:ty => $e:expr),* $(,)?) => {
+        $(
+            unsafe impl Encode for $T {
+                const ENCODING: Encoding = $e;
+            }
+
+            unsafe impl RefEncode for $T {
+                const ENCODING_REF: Encoding = Encoding::Ptr(&$e);
+            }
+        )*
+    };
+}
+
+encode!(
+    u8 => Encoding::UChar,
+    i16 => Encoding::Short,
+    u16 => Encoding::UShort,
+    i32 => Encoding::Int,
+    u32 => Encoding::UInt,
+    i64 => Encoding::LongLong,
+    u64 => Encoding::ULongLong,
+    isize => Encoding::LongLong,
+    usize => Encoding::ULongLong,
+    f32 => Encoding::Float,
+    f64 => Encoding::Double,
+    bool => Encoding::Bool,
+);
+
+unsafe impl Encode for i8 {
+    const ENCODING: Encoding = Encoding::Char;
+}
+
+/// `char *` is encoded as `*`
+unsafe impl RefEncode for i8 {
+    const ENCODING_REF: Encoding = Encoding::CStr;
+}
+
+unsafe impl Encode for () {
+    const ENCODING: Encoding = Encoding::Void;
+}
+
+unsafe impl RefEncode for c_void {
+    const ENCODING_REF: Encoding = Encoding::Ptr(&Encoding::Void);
+}
+
+unsafe impl Encode for objc::Sel {
+    const ENCODING: Encoding = Encoding::Sel;
+}
+
+/// `&Sel` is already `SEL`
+unsafe impl RefEncode for objc::Sel {
+    const ENCODING_REF: Encoding = Encoding::Sel;
+}
+
+unsafe impl<T: Encode, const N: usize> Encode for [T; N] {
+    const ENCODING: Encoding = Encoding::Array(N, &T::ENCODING);
+}
+
+unsafe impl<T: Encode, const N: usize> RefEncode for [T; N] {
+    const ENCODING_REF: Encoding = Encoding::Ptr(&Self::ENCODING);
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for &T {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for &mut T {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for *const T {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for *mut T {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for NonNull<T> {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for Option<&T> {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for Option<&mut T> {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: RefEncode + ?Sized> Encode for Option<NonNull<T>> {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+// `&T` is fundamental and could be `objc::Obj`, so only pointers and options are here
+macro_rules! ref_encode_ptr {
+    ($($T:ty),*) => {
+        $(
+            unsafe impl<T: RefEncode + ?Sized> RefEncode for $T {
+                const ENCODING_REF: Encoding = Encoding::Ptr(&<Self as Encode>::ENCODING);
+            }
+        )*
+    };
+}
+
+ref_encode_ptr!(*const T, *mut T, Option<&T>, Option<&mut T>);
+
+unsafe impl<T: objc::Obj> Encode for arc::R<T> {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+unsafe impl<T: objc::Obj> Encode for Option<arc::R<T>> {
+    const ENCODING: Encoding = T::ENCODING_REF;
+}
+
+macro_rules! encode_struct {
+    ($($T:ty => $name:literal [$($field:expr),*]),* $(,)?) => {
+        encode!($($T => Encoding::Struct($name, &[$($field),*])),*);
+    };
+}
+
+encode_struct!(
+    cg::Point => "CGPoint" [cg::Float::ENCODING, cg::Float::ENCODING],
+    cg::Size => "CGSize" [cg::Float::ENCODING, cg::Float::ENCODING],
+    cg::Vector => "CGVector" [cg::Float::ENCODING, cg::Float::ENCODING],
+    cg::Rect => "CGRect" [cg::Point::ENCODING, cg::Size::ENCODING],
+    cg::AffineTransform => "CGAffineTransform" [
+        cg::Float::ENCODING,
+        cg::Float::ENCODING,
+        cg::Float::ENCODING,
+        cg::Float::ENCODING,
+        cg::Float::ENCODING,
+        cg::Float::ENCODING
+    ],
+);
+
+#[cfg(feature = "cm")]
+encode_struct!(
+    crate::cm::Time => "?" [
+        Encoding::LongLong,
+        Encoding::Int,
+        Encoding::UInt,
+        Encoding::LongLong
+    ],
+    crate::cm::TimeRange => "?" [crate::cm::Time::ENCODING, crate::cm::Time::ENCODING],
+);
+
+/// clang emits empty encoding for vector types
+#[cfg(feature = "simd")]
+unsafe impl<T, const N: usize, const L: usize> Encode for crate::simd::Simd<T, N, L> {
+    const ENCODING: Encoding = Encoding::Vector;
+}
+
+#[cfg(feature = "simd")]
+unsafe impl<T, const N: usize, const L: usize> RefEncode for crate::simd::Simd<T, N, L> {
+    const ENCODING_REF: Encoding = Encoding::Ptr(&Encoding::Vector);
+}
+
+// simd matrices are structs of columns: `{?=[4]}`
+#[cfg(feature = "simd")]
+encode_struct!(
+    crate::simd::f32x2x2 => "?" [<[crate::simd::f32x2; 2]>::ENCODING],
+    crate::simd::f32x3x2 => "?" [<[crate::simd::f32x2; 3]>::ENCODING],
+    crate::simd::f32x4x2 => "?" [<[crate::simd::f32x2; 4]>::ENCODING],
+    crate::simd::f32x2x3 => "?" [<[crate::simd::f32x3; 2]>::ENCODING],
+    crate::simd::f32x3x3 => "?" [<[crate::simd::f32x3; 3]>::ENCODING],
+    crate::simd::f32x4x4 => "?" [<[crate::simd::f32x4; 4]>::ENCODING],
+);
+
+/// Method types for `class_addMethod`: return type, `self`, `_cmd` and arguments
+///
+/// Returns `None` if any of types has unknown encoding.
+pub fn method_types(ret: Option<&Encoding>, args: &[Option<&Encoding>]) -> Option<CString> {
+    let mut res = String::with_capacity(8 + args.len() * 2);
+    write!(res, "{}@:", ret?).ok()?;
+    for arg in args.iter().copied() {
+        write!(res, "{}", arg?).ok()?;
+    }
+    CString::new(res).ok()
+}
+
+/// Autoref based probe, used by `#[objc::protocol]` and `#[objc::add_methods]`
+/// to get encoding of types which may not implement `Encode`
+///
+/// ```ignore
+/// use objc::encode::{ProbeKnown as _, ProbeUnknown as _};
+/// (&objc::encode::Probe::<T>::new()).encoding()
+/// ```
+#[doc(hidden)]
+pub struct Probe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> Probe<T> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ?Sized> Default for Probe<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[doc(hidden)]
+pub trait ProbeKnown {
+    fn encoding(&self) -> Option<&'static Encoding>;
+}
+
+impl<T: Encode> ProbeKnown for Probe<T> {
+    #[inline]
+    fn encoding(&self) -> Option<&'static Encoding> {
+        Some(&T::ENCODING)
+    }
+}
+
+#[doc(hidden)]
+pub trait ProbeUnknown {
+    fn encoding(&self) -> Option<&'static Encoding>;
+}
+
+impl<T: ?Sized> ProbeUnknown for &Probe<T> {
+    #[inline]
+    fn encoding(&self) -> Option<&'static Encoding> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Encode, Encoding, Probe, ProbeKnown as _, ProbeUnknown as _, method_types};
+    use crate::{cg, ns, objc};
+
+    fn enc<T: Encode>() -> String {
+        T::ENCODING.to_string()
+    }
+
+    #[test]
+    fn primitives() {
+        assert_eq!(enc::<i8>(), "c");
+        assert_eq!(enc::<u8>(), "C");
+        assert_eq!(enc::<i16>(), "s");
+        assert_eq!(enc::<u16>(), "S");
+        assert_eq!(enc::<i32>(), "i");
+        assert_eq!(enc::<u32>(), "I");
+        assert_eq!(enc::<ns::Integer>(), "q");
+        assert_eq!(enc::<ns::UInteger>(), "Q");
+        assert_eq!(enc::<f32>(), "f");
+        assert_eq!(enc::<f64>(), "d");
+        assert_eq!(enc::<bool>(), "B");
+        assert_eq!(enc::<()>(), "v");
+        assert_eq!(enc::<*const i8>(), "*");
+        assert_eq!(enc::<*const std::ffi::c_void>(), "^v");
+        assert_eq!(enc::<&mut f32>(), "^f");
+        assert_eq!(enc::<*const *const u8>(), "^^C");
+        assert_eq!(enc::<[u32; 4]>(), "[4I]");
+    }
+
+    #[test]
+    fn objects() {
+        assert_eq!(enc::<&ns::Id>(), "@");
+        assert_eq!(enc::<Option<&ns::String>>(), "@");
+        assert_eq!(enc::<crate::arc::R<ns::Array<ns::Number>>>(), "@");
+        assert_eq!(enc::<&objc::Class<ns::Id>>(), "#");
+        assert_eq!(enc::<&objc::Sel>(), ":");
+        assert_eq!(enc::<*mut Option<&ns::Error>>(), "^@");
+    }
+
+    #[test]
+    fn structs() {
+        assert_eq!(enc::<cg::Point>(), "{CGPoint=dd}");
+        assert_eq!(enc::<cg::Rect>(), "{CGRect={CGPoint=dd}{CGSize=dd}}");
+        assert_eq!(enc::<&cg::Rect>(), "^{CGRect={CGPoint=dd}{CGSize=dd}}");
+        assert_eq!(enc::<*const *const cg::Rect>(), "^^{CGRect}");
+        assert_eq!(enc::<cg::AffineTransform>(), "{CGAffineTransform=dddddd}");
+        assert_eq!(enc::<ns::Range>(), "{_NSRange=QQ}");
+        assert_eq!(
+            Encoding::Union("?", &[Encoding::Int, Encoding::Float]).to_string(),
+            "(?=if)"
+        );
+    }
+
+    #[cfg(feature = "mtl")]
+    #[test]
+    fn metal() {
+        use crate::mtl;
+
+        assert_eq!(enc::<mtl::Size>(), "{?=QQQ}");
+        assert_eq!(enc::<mtl::Region>(), "{?={?=QQQ}{?=QQQ}}");
+    }
+
+    #[cfg(feature = "cm")]
+    #[test]
+    fn cm() {
+        use crate::cm;
+
+        assert_eq!(enc::<cm::Time>(), "{?=qiIq}");
+        assert_eq!(enc::<cm::TimeRange>(), "{?={?=qiIq}{?=qiIq}}");
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd() {
+        use crate::simd;
+
+        assert_eq!(enc::<simd::f32x4>(), "");
+        assert_eq!(enc::<simd::f32x4x4>(), "{?=[4]}");
+    }
+
+    #[test]
+    fn methods() {
+        let types = method_types(
+            Some(&<()>::ENCODING),
+            &[Some(&cg::Rect::ENCODING), Some(&ns::Integer::ENCODING)],
+        );
+        assert_eq!(
+            types.unwrap().to_str().unwrap(),
+            "v@:{CGRect={CGPoint=dd}{CGSize=dd}}q"
+        );
+
+        struct Opaque;
+        assert!((&Probe::<Opaque>::new()).encoding().is_none());
+        assert_eq!(
+            Probe::<&ns::String>::new().encoding(),
+            Some(&Encoding::Object)
+        );
+        let types = method_types(
+            Probe::<usize>::new().encoding(),
+            &[(&Probe::<Opaque>::new()).encoding()],
+        );
+        assert!(types.is_none());
+    }
+}
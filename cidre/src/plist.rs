@@ -0,0 +1,768 @@
+//! Property lists without CoreFoundation
+//!
+//! Reads and writes all three `cf::PlistFormat` variants, so Info.plists, entitlements
+//! and lockdown messages work on any target. Values convert to and from `cf::Plist`
+//! on Apple targets and to and from any serde type.
+//!
+//! ```
+//! use cidre::plist::{self, Format, Value};
+//!
+//! let info = br#"<?xml version="1.0" encoding="UTF-8"?>
+//! <plist version="1.0">
+//! <dict>
+//!     <key>CFBundleIdentifier</key>
+//!     <string>org.cidre.demo</string>
+//!     <key>UIDeviceFamily</key>
+//!     <array><integer>1</integer><integer>2</integer></array>
+//! </dict>
+//! </plist>"#;
+//!
+//! let (value, format) = Value::from_data_with_format(info).unwrap();
+//! assert_eq!(format, Format::XmlV1_0);
+//! assert_eq!(value["CFBundleIdentifier"].as_str(), Some("org.cidre.demo"));
+//!
+//! #[derive(serde::Deserialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Info {
+//!     #[serde(rename = "CFBundleIdentifier")]
+//!     id: String,
+//!     #[serde(rename = "UIDeviceFamily")]
+//!     families: Vec<u8>,
+//! }
+//!
+//! let bin = value.to_data(Format::BinaryV1_0).unwrap();
+//! let info: Info = plist::from_slice(&bin).unwrap();
+//! assert_eq!(info.id, "org.cidre.demo");
+//! assert_eq!(info.families, [1, 2]);
+//! ```
+
+use std::{fmt, ops::Index, time::SystemTime};
+
+mod binary;
+#[cfg(feature = "cf")]
+mod bridge;
+mod de;
+pub mod keyed_archive;
+mod open_step;
+mod ser;
+mod xml;
+
+pub use de::from_slice;
+pub use de::from_value;
+pub use ser::to_value;
+pub use ser::to_vec;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Error {
+    /// Data ended early
+    Eof,
+    /// Malformed data
+    Invalid(&'static str),
+    /// Valid but can't be represented
+    Unsupported(&'static str),
+    /// Error reported by a serde implementation
+    Message(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eof => f.write_str("unexpected end of data"),
+            Self::Invalid(what) => write!(f, "invalid {what}"),
+            Self::Unsupported(what) => write!(f, "unsupported {what}"),
+            Self::Message(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+/// Same values as `cf::PlistFormat`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[repr(isize)]
+pub enum Format {
+    /// Old-style ASCII, only strings, data, arrays and dictionaries
+    #[doc(alias = "kCFPropertyListOpenStepFormat")]
+    OpenStep = 1,
+
+    #[doc(alias = "kCFPropertyListXMLFormat_v1_0")]
+    XmlV1_0 = 100,
+
+    /// `bplist00`
+    #[doc(alias = "kCFPropertyListBinaryFormat_v1_0")]
+    BinaryV1_0 = 200,
+}
+
+impl Format {
+    /// Format of `data` by its first bytes, without validating the rest
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(b"bplist") {
+            return Self::BinaryV1_0;
+        }
+        let data = data.strip_prefix("\u{feff}".as_bytes()).unwrap_or(data);
+        let start = data.iter().position(|b| !b.is_ascii_whitespace());
+        match start.map(|i| &data[i..]) {
+            Some([b'<', b'?' | b'!', ..]) => Self::XmlV1_0,
+            // OpenStep data is `<` and hex digits, which may spell a tag name prefix
+            Some([b'<', rest @ ..]) => {
+                let len = rest.iter().take_while(|b| b.is_ascii_alphabetic()).count();
+                let after = rest.get(len).copied().unwrap_or(b'>');
+                let tag = matches!(
+                    &rest[..len],
+                    b"plist"
+                        | b"dict"
+                        | b"array"
+                        | b"key"
+                        | b"string"
+                        | b"data"
+                        | b"date"
+                        | b"integer"
+                        | b"real"
+                        | b"true"
+                        | b"false"
+                );
+                if tag && (after == b'>' || after == b'/' || after.is_ascii_whitespace()) {
+                    Self::XmlV1_0
+                } else {
+                    Self::OpenStep
+                }
+            }
+            _ => Self::OpenStep,
+        }
+    }
+}
+
+#[cfg(feature = "cf")]
+impl From<Format> for crate::cf::PlistFormat {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::OpenStep => Self::OpenStep,
+            Format::XmlV1_0 => Self::XmlV1_0,
+            Format::BinaryV1_0 => Self::BinaryV1_0,
+        }
+    }
+}
+
+#[cfg(feature = "cf")]
+impl From<crate::cf::PlistFormat> for Format {
+    fn from(value: crate::cf::PlistFormat) -> Self {
+        match value {
+            crate::cf::PlistFormat::OpenStep => Self::OpenStep,
+            crate::cf::PlistFormat::XmlV1_0 => Self::XmlV1_0,
+            crate::cf::PlistFormat::BinaryV1_0 => Self::BinaryV1_0,
+        }
+    }
+}
+
+/// Integer in `i64::MIN..=u64::MAX`, the range binary plists store
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Integer(i128);
+
+impl Integer {
+    pub const MIN: Self = Self(i64::MIN as i128);
+    pub const MAX: Self = Self(u64::MAX as i128);
+
+    #[inline]
+    pub fn as_i64(self) -> Option<i64> {
+        i64::try_from(self.0).ok()
+    }
+
+    #[inline]
+    pub fn as_u64(self) -> Option<u64> {
+        u64::try_from(self.0).ok()
+    }
+
+    #[inline]
+    pub const fn as_i128(self) -> i128 {
+        self.0
+    }
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+macro_rules! integer_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Integer {
+                #[inline]
+                fn from(value: $t) -> Self {
+                    Self(value as i128)
+                }
+            }
+
+            impl From<$t> for Value {
+                #[inline]
+                fn from(value: $t) -> Self {
+                    Self::Integer(value.into())
+                }
+            }
+        )*
+    };
+}
+
+integer_from!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl TryFrom<i128> for Integer {
+    type Error = Error;
+
+    fn try_from(value: i128) -> Result<Self> {
+        if (Self::MIN.0..=Self::MAX.0).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(Error::Unsupported("integer out of range"))
+        }
+    }
+}
+
+/// Seconds since 2001-01-01 00:00:00 UTC, same as `cf::AbsTime`
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Date(pub f64);
+
+/// Seconds between the unix epoch and 2001-01-01
+const UNIX_TO_ABS: f64 = 978_307_200.0;
+
+impl Date {
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// `YYYY-MM-DDTHH:MM:SSZ` as written in XML plists
+    pub fn parse(str: &str) -> Option<Self> {
+        let b = str.as_bytes();
+        if b.len() != 20 || b[19] != b'Z' {
+            return None;
+        }
+        let num = |range: std::ops::Range<usize>| -> Option<i64> {
+            let digits = str.get(range)?;
+            if !digits.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            digits.parse().ok()
+        };
+        let seps = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':')];
+        if seps.iter().any(|&(i, c)| b[i] != c) {
+            return None;
+        }
+        let (y, m, d) = (num(0..4)?, num(5..7)?, num(8..10)?);
+        let (h, min, s) = (num(11..13)?, num(14..16)?, num(17..19)?);
+        if !(1..=12).contains(&m) || !(1..=31).contains(&d) || h > 23 || min > 59 || s > 60 {
+            return None;
+        }
+        let days = days_from_civil(y, m, d) - days_from_civil(2001, 1, 1);
+        Some(Self((days * 86_400 + h * 3600 + min * 60 + s) as f64))
+    }
+
+    /// `YYYY-MM-DDTHH:MM:SSZ`, fractions of a second are dropped the way CoreFoundation does
+    pub fn to_xml_string(&self) -> String {
+        let secs = self.0.floor() as i64;
+        let days = secs.div_euclid(86_400) + days_from_civil(2001, 1, 1);
+        let time = secs.rem_euclid(86_400);
+        let (y, m, d) = civil_from_days(days);
+        format!(
+            "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}Z",
+            time / 3600,
+            time / 60 % 60,
+            time % 60
+        )
+    }
+}
+
+impl From<SystemTime> for Date {
+    fn from(value: SystemTime) -> Self {
+        let unix = match value.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => d.as_secs_f64(),
+            Err(e) => -e.duration().as_secs_f64(),
+        };
+        Self(unix - UNIX_TO_ABS)
+    }
+}
+
+impl From<Date> for SystemTime {
+    fn from(value: Date) -> Self {
+        let unix = value.0 + UNIX_TO_ABS;
+        if unix >= 0.0 {
+            Self::UNIX_EPOCH + std::time::Duration::from_secs_f64(unix)
+        } else {
+            Self::UNIX_EPOCH - std::time::Duration::from_secs_f64(-unix)
+        }
+    }
+}
+
+/// Days since 1970-01-01, <http://howardhinnant.github.io/date_algorithms.html>
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = y - (m <= 2) as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + (m <= 2) as i64, m, d)
+}
+
+/// `NSKeyedArchiver` object reference, `CF$UID` in XML
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Uid(pub u64);
+
+/// Dictionary keeping keys in insertion order.
+///
+/// Equality ignores the order, like `cf::Dictionary`.
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    entries: Vec<(String, Value)>,
+}
+
+impl Dictionary {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Replaces the value in place if `key` exists, appends otherwise
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        let key = key.into();
+        let value = value.into();
+        match self.get_mut(&key) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let i = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(i).1)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&String, &Value)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = (&String, &mut Value)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    #[inline]
+    pub fn keys(&self) -> impl ExactSizeIterator<Item = &String> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn values(&self) -> impl ExactSizeIterator<Item = &Value> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// Sorts keys the way CoreFoundation writes XML
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+}
+
+impl PartialEq for Dictionary {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Dictionary {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut res = Self::new();
+        for (k, v) in iter {
+            res.insert(k, v);
+        }
+        res
+    }
+}
+
+impl IntoIterator for Dictionary {
+    type Item = (String, Value);
+    type IntoIter = std::vec::IntoIter<(String, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl Index<&str> for Dictionary {
+    type Output = Value;
+
+    /// Panics if `key` is missing
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).expect("no such key")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Array(Vec<Value>),
+    Dictionary(Dictionary),
+    Bool(bool),
+    Data(Vec<u8>),
+    Date(Date),
+    Real(f64),
+    Integer(Integer),
+    String(String),
+    /// Binary format only, XML writes it as a `CF$UID` dictionary
+    Uid(Uid),
+}
+
+impl Value {
+    /// Reads any of the three formats
+    pub fn from_data(data: &[u8]) -> Result<Self> {
+        Self::from_data_with_format(data).map(|(v, _)| v)
+    }
+
+    pub fn from_data_with_format(data: &[u8]) -> Result<(Self, Format)> {
+        let format = Format::detect(data);
+        let value = match format {
+            Format::BinaryV1_0 => binary::read(data)?,
+            Format::XmlV1_0 => xml::read(data)?,
+            Format::OpenStep => open_step::read(data)?,
+        };
+        Ok((value, format))
+    }
+
+    pub fn to_data(&self, format: Format) -> Result<Vec<u8>> {
+        match format {
+            Format::BinaryV1_0 => binary::write(self),
+            Format::XmlV1_0 => xml::write(self),
+            Format::OpenStep => open_step::write(self),
+        }
+    }
+
+    #[inline]
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Self::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Self::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_dictionary(&self) -> Option<&Dictionary> {
+        match self {
+            Self::Dictionary(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_dictionary_mut(&mut self) -> Option<&mut Dictionary> {
+        match self {
+            Self::Dictionary(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_data(&self) -> Option<&[u8]> {
+        match self {
+            Self::Data(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_date(&self) -> Option<Date> {
+        match self {
+            Self::Date(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Reals and integers as `f64`
+    #[inline]
+    pub fn as_real(&self) -> Option<f64> {
+        match self {
+            Self::Real(v) => Some(*v),
+            Self::Integer(v) => Some(v.0 as f64),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_integer(&self) -> Option<Integer> {
+        match self {
+            Self::Integer(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_integer()?.as_i64()
+    }
+
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_integer()?.as_u64()
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_uid(&self) -> Option<Uid> {
+        match self {
+            Self::Uid(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Dictionary value for `key`
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_dictionary()?.get(key)
+    }
+}
+
+impl Index<&str> for Value {
+    type Output = Value;
+
+    /// Panics if not a dictionary or `key` is missing
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).expect("no such key")
+    }
+}
+
+impl Index<usize> for Value {
+    type Output = Value;
+
+    /// Panics if not an array or `index` is out of bounds
+    fn index(&self, index: usize) -> &Value {
+        &self.as_array().expect("not an array")[index]
+    }
+}
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for Value {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Self::Real(value)
+    }
+}
+
+impl From<f32> for Value {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Self::Real(value as f64)
+    }
+}
+
+impl From<Integer> for Value {
+    #[inline]
+    fn from(value: Integer) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<&str> for Value {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    #[inline]
+    fn from(value: Vec<u8>) -> Self {
+        Self::Data(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    #[inline]
+    fn from(value: Vec<Value>) -> Self {
+        Self::Array(value)
+    }
+}
+
+impl From<Dictionary> for Value {
+    #[inline]
+    fn from(value: Dictionary) -> Self {
+        Self::Dictionary(value)
+    }
+}
+
+impl From<Date> for Value {
+    #[inline]
+    fn from(value: Date) -> Self {
+        Self::Date(value)
+    }
+}
+
+impl From<Uid> for Value {
+    #[inline]
+    fn from(value: Uid) -> Self {
+        Self::Uid(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        assert_eq!(Format::detect(b"bplist00"), Format::BinaryV1_0);
+        assert_eq!(Format::detect(b"  <?xml"), Format::XmlV1_0);
+        assert_eq!(Format::detect(b"\xef\xbb\xbf<plist>"), Format::XmlV1_0);
+        assert_eq!(Format::detect(b"<dict/>"), Format::XmlV1_0);
+        assert_eq!(Format::detect(b"<array></array>"), Format::XmlV1_0);
+        assert_eq!(Format::detect(b"<0fbd77>"), Format::OpenStep);
+        assert_eq!(Format::detect(b"<ab cd>"), Format::OpenStep);
+        assert_eq!(Format::detect(b"<>"), Format::OpenStep);
+        assert_eq!(Format::detect(b"// !$*UTF8*$!\n{"), Format::OpenStep);
+        assert_eq!(Format::detect(b""), Format::OpenStep);
+    }
+
+    #[test]
+    fn dates() {
+        let date = Date::parse("2001-01-01T00:00:00Z").unwrap();
+        assert_eq!(date, Date(0.0));
+        let date = Date::parse("2024-02-29T12:34:56Z").unwrap();
+        assert_eq!(date.to_xml_string(), "2024-02-29T12:34:56Z");
+        assert_eq!(Date(-1.5).to_xml_string(), "2000-12-31T23:59:58Z");
+        assert_eq!(Date(0.999).to_xml_string(), "2001-01-01T00:00:00Z");
+        assert_eq!(
+            Date::parse("1970-01-01T00:00:00Z"),
+            Some(Date(-UNIX_TO_ABS))
+        );
+        assert_eq!(Date::from(SystemTime::UNIX_EPOCH), Date(-UNIX_TO_ABS));
+        assert_eq!(
+            SystemTime::from(Date(-UNIX_TO_ABS + 1.0)),
+            SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1)
+        );
+        for bad in [
+            "2001-01-01T00:00:00",
+            "2001-13-01T00:00:00Z",
+            "2001-01-01 00:00:00Z",
+            "2001-01-01T0a:00:00Z",
+            "+001-01-01T00:00:00Z",
+        ] {
+            assert_eq!(Date::parse(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(Integer::from(u64::MAX).as_u64(), Some(u64::MAX));
+        assert_eq!(Integer::from(u64::MAX).as_i64(), None);
+        assert_eq!(Integer::from(-1).as_u64(), None);
+        assert!(Integer::try_from(u64::MAX as i128 + 1).is_err());
+        assert!(Integer::try_from(i64::MIN as i128 - 1).is_err());
+        assert_eq!(Integer::try_from(-5i128), Ok(Integer::from(-5)));
+    }
+
+    #[test]
+    fn dictionary() {
+        let mut dict: Dictionary = [("b", 1), ("a", 2)].into_iter().collect();
+        assert_eq!(dict.insert("b", 3), Some(Value::from(1)));
+        assert_eq!(dict.keys().collect::<Vec<_>>(), ["b", "a"]);
+        let mut sorted = dict.clone();
+        sorted.sort_keys();
+        assert_eq!(sorted.keys().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(sorted, dict);
+        assert_eq!(dict["b"], Value::from(3));
+        assert_eq!(dict.remove("b"), Some(Value::from(3)));
+        assert_ne!(sorted, dict);
+        assert!(!dict.contains_key("b"));
+
+        let value = Value::Dictionary(sorted);
+        assert_eq!(value["a"].as_i64(), Some(2));
+        assert_eq!(value.get("c"), None);
+        assert_eq!(
+            Value::from(vec![Value::from(true)])[0].as_bool(),
+            Some(true)
+        );
+    }
+}
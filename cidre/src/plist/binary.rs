@@ -0,0 +1,514 @@
+//! `bplist00`, the format of CoreFoundation's `kCFPropertyListBinaryFormat_v1_0`
+//!
+//! Objects are followed by an offset table and a 32 byte trailer. Containers refer to
+//! objects by index, so equal scalars are written once.
+
+use std::collections::HashMap;
+
+use super::{Date, Dictionary, Error, Integer, Result, Uid, Value};
+
+const HEADER: &[u8] = b"bplist00";
+const TRAILER_LEN: usize = 32;
+
+pub(super) fn read(data: &[u8]) -> Result<Value> {
+    if !data.starts_with(HEADER) {
+        return Err(Error::Invalid("binary plist header"));
+    }
+    if data.len() < HEADER.len() + 1 + TRAILER_LEN {
+        return Err(Error::Eof);
+    }
+    let trailer = &data[data.len() - TRAILER_LEN..];
+    let offset_size = trailer[6] as usize;
+    let ref_size = trailer[7] as usize;
+    let count = be_uint(&trailer[8..16]);
+    let top = be_uint(&trailer[16..24]);
+    let table = be_uint(&trailer[24..32]);
+
+    if !matches!(offset_size, 1..=8) || !matches!(ref_size, 1..=8) {
+        return Err(Error::Invalid("binary plist trailer"));
+    }
+    let objects_end = data.len() - TRAILER_LEN;
+    let table_len = count.checked_mul(offset_size as u64);
+    let table_end = table_len.and_then(|len| len.checked_add(table));
+    if count == 0
+        || top >= count
+        || table < HEADER.len() as u64
+        || table_end.is_none_or(|end| end > objects_end as u64)
+    {
+        return Err(Error::Invalid("binary plist trailer"));
+    }
+
+    let mut reader = Reader {
+        data: &data[..table as usize],
+        offsets: &data[table as usize..objects_end],
+        offset_size,
+        ref_size,
+        count,
+        parents: Vec::new(),
+    };
+    reader.object(top)
+}
+
+struct Reader<'a> {
+    /// Header and objects
+    data: &'a [u8],
+    offsets: &'a [u8],
+    offset_size: usize,
+    ref_size: usize,
+    count: u64,
+    /// Containers being read, a reference back to one of them is a cycle
+    parents: Vec<u64>,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&self, pos: usize, len: usize) -> Result<&'a [u8]> {
+        let end = pos.checked_add(len).ok_or(Error::Eof)?;
+        self.data.get(pos..end).ok_or(Error::Eof)
+    }
+
+    fn object(&mut self, index: u64) -> Result<Value> {
+        if index >= self.count {
+            return Err(Error::Invalid("object reference"));
+        }
+        let i = index as usize * self.offset_size;
+        let pos = be_uint(&self.offsets[i..i + self.offset_size]);
+        if pos < HEADER.len() as u64 || pos >= self.data.len() as u64 {
+            return Err(Error::Invalid("object offset"));
+        }
+        let pos = pos as usize;
+        let marker = self.data[pos];
+        let (kind, low) = (marker >> 4, (marker & 0xf) as usize);
+        let body = pos + 1;
+        let value = match kind {
+            0x0 => match marker {
+                0x08 => Value::Bool(false),
+                0x09 => Value::Bool(true),
+                _ => return Err(Error::Unsupported("null or fill object")),
+            },
+            0x1 => {
+                let bytes = self.bytes(body, 1 << low.min(5))?;
+                Value::Integer(int(bytes)?)
+            }
+            0x2 => match low {
+                2 => {
+                    Value::Real(f32::from_be_bytes(self.bytes(body, 4)?.try_into().unwrap()) as f64)
+                }
+                3 => Value::Real(f64::from_be_bytes(self.bytes(body, 8)?.try_into().unwrap())),
+                _ => return Err(Error::Invalid("real size")),
+            },
+            0x3 if marker == 0x33 => Value::Date(Date(f64::from_be_bytes(
+                self.bytes(body, 8)?.try_into().unwrap(),
+            ))),
+            0x4 => {
+                let (len, start) = self.count_at(low, body)?;
+                Value::Data(self.bytes(start, len)?.to_vec())
+            }
+            0x5 => {
+                let (len, start) = self.count_at(low, body)?;
+                let bytes = self.bytes(start, len)?;
+                // ASCII by spec, anything above is read as Latin-1
+                Value::String(bytes.iter().map(|&b| b as char).collect())
+            }
+            0x6 => {
+                let (len, start) = self.count_at(low, body)?;
+                let bytes = self.bytes(start, len.checked_mul(2).ok_or(Error::Eof)?)?;
+                let units = bytes
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]));
+                let str: std::result::Result<String, _> = char::decode_utf16(units).collect();
+                Value::String(str.map_err(|_| Error::Invalid("utf-16 string"))?)
+            }
+            0x8 if low < 8 => Value::Uid(Uid(be_uint(self.bytes(body, low + 1)?))),
+            0xa => {
+                let (len, start) = self.count_at(low, body)?;
+                let refs = self.refs(start, len)?;
+                self.enter(index)?;
+                let items = refs.map(|r| self.object(r)).collect::<Result<_>>()?;
+                self.parents.pop();
+                Value::Array(items)
+            }
+            0xd => {
+                let (len, start) = self.count_at(low, body)?;
+                let keys = self.refs(start, len)?;
+                let values = self.refs(start + len * self.ref_size, len)?;
+                self.enter(index)?;
+                let mut dict = Dictionary::with_capacity(len);
+                for (k, v) in keys.zip(values) {
+                    let Value::String(key) = self.object(k)? else {
+                        return Err(Error::Unsupported("non string dictionary key"));
+                    };
+                    let value = self.object(v)?;
+                    dict.insert(key, value);
+                }
+                self.parents.pop();
+                Value::Dictionary(dict)
+            }
+            _ => return Err(Error::Invalid("object marker")),
+        };
+        Ok(value)
+    }
+
+    fn enter(&mut self, index: u64) -> Result {
+        if self.parents.contains(&index) {
+            return Err(Error::Invalid("object cycle"));
+        }
+        self.parents.push(index);
+        Ok(())
+    }
+
+    /// Count in the marker's low nibble or in the int object after it
+    fn count_at(&self, low: usize, pos: usize) -> Result<(usize, usize)> {
+        if low != 0xf {
+            return Ok((low, pos));
+        }
+        let marker = *self.bytes(pos, 1)?.first().unwrap();
+        if marker >> 4 != 0x1 || marker & 0xf > 3 {
+            return Err(Error::Invalid("object count"));
+        }
+        let size = 1 << (marker & 0xf);
+        let len = be_uint(self.bytes(pos + 1, size)?);
+        // every item takes at least a byte, so bigger counts can't fit
+        if len > self.data.len() as u64 {
+            return Err(Error::Eof);
+        }
+        Ok((len as usize, pos + 1 + size))
+    }
+
+    fn refs(&self, pos: usize, len: usize) -> Result<impl Iterator<Item = u64> + 'a> {
+        let size = self.ref_size;
+        let bytes = self.bytes(pos, len.checked_mul(size).ok_or(Error::Eof)?)?;
+        Ok(bytes.chunks_exact(size).map(be_uint))
+    }
+}
+
+fn be_uint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |acc, &b| acc << 8 | b as u64)
+}
+
+/// 1, 2 and 4 byte integers are unsigned, 8 and 16 byte ones are signed
+fn int(bytes: &[u8]) -> Result<Integer> {
+    match bytes.len() {
+        1 | 2 | 4 => Ok(be_uint(bytes).into()),
+        8 => Ok((be_uint(bytes) as i64).into()),
+        16 => Integer::try_from(i128::from_be_bytes(bytes.try_into().unwrap())),
+        _ => Err(Error::Invalid("integer size")),
+    }
+}
+
+pub(super) fn write(value: &Value) -> Result<Vec<u8>> {
+    let mut writer = Writer::default();
+    writer.add(value)?;
+
+    let ref_size = int_size(writer.objects.len() as u64);
+    let mut res = HEADER.to_vec();
+    let mut offsets = Vec::with_capacity(writer.objects.len());
+    for obj in &writer.objects {
+        offsets.push(res.len() as u64);
+        match obj {
+            Object::Scalar(bytes) => res.extend_from_slice(bytes),
+            Object::Array(refs) => {
+                push_marker(&mut res, 0xa0, refs.len());
+                refs.iter().for_each(|&r| push_uint(&mut res, r, ref_size));
+            }
+            Object::Dictionary(refs) => {
+                push_marker(&mut res, 0xd0, refs.len() / 2);
+                refs.iter().for_each(|&r| push_uint(&mut res, r, ref_size));
+            }
+        }
+    }
+    let table = res.len() as u64;
+    let offset_size = int_size(table);
+    for offset in offsets {
+        push_uint(&mut res, offset, offset_size);
+    }
+    // trailer: 5 unused bytes, sort version, offset size, ref size,
+    // objects count, root object and offsets table position
+    res.extend_from_slice(&[0; 6]);
+    res.push(offset_size as u8);
+    res.push(ref_size as u8);
+    res.extend_from_slice(&(writer.objects.len() as u64).to_be_bytes());
+    res.extend_from_slice(&0u64.to_be_bytes());
+    res.extend_from_slice(&table.to_be_bytes());
+    Ok(res)
+}
+
+enum Object {
+    Scalar(Vec<u8>),
+    Array(Vec<u64>),
+    /// Key references, then value references
+    Dictionary(Vec<u64>),
+}
+
+#[derive(Default)]
+struct Writer {
+    objects: Vec<Object>,
+    /// Encoded scalars already written, CoreFoundation writes equal ones once
+    scalars: HashMap<Vec<u8>, u64>,
+}
+
+impl Writer {
+    fn push(&mut self, obj: Object) -> u64 {
+        self.objects.push(obj);
+        self.objects.len() as u64 - 1
+    }
+
+    fn scalar(&mut self, bytes: Vec<u8>) -> u64 {
+        if let Some(&index) = self.scalars.get(&bytes) {
+            return index;
+        }
+        let index = self.objects.len() as u64;
+        self.scalars.insert(bytes.clone(), index);
+        self.push(Object::Scalar(bytes))
+    }
+
+    /// Containers are reserved before their items, so the root is object 0
+    fn add(&mut self, value: &Value) -> Result<u64> {
+        let mut buf = vec![];
+        match value {
+            Value::Array(items) => {
+                let index = self.push(Object::Array(vec![]));
+                let refs = items.iter().map(|v| self.add(v)).collect::<Result<_>>()?;
+                self.objects[index as usize] = Object::Array(refs);
+                return Ok(index);
+            }
+            Value::Dictionary(dict) => {
+                let index = self.push(Object::Dictionary(vec![]));
+                let mut refs = Vec::with_capacity(dict.len() * 2);
+                for key in dict.keys() {
+                    refs.push(self.add_str(key));
+                }
+                for value in dict.values() {
+                    refs.push(self.add(value)?);
+                }
+                self.objects[index as usize] = Object::Dictionary(refs);
+                return Ok(index);
+            }
+            Value::String(str) => return Ok(self.add_str(str)),
+            Value::Bool(b) => buf.push(if *b { 0x09 } else { 0x08 }),
+            Value::Integer(i) => push_int(&mut buf, *i),
+            Value::Real(r) => {
+                buf.push(0x23);
+                buf.extend_from_slice(&r.to_be_bytes());
+            }
+            Value::Date(d) => {
+                buf.push(0x33);
+                buf.extend_from_slice(&d.0.to_be_bytes());
+            }
+            Value::Data(data) => {
+                push_marker(&mut buf, 0x40, data.len());
+                buf.extend_from_slice(data);
+            }
+            Value::Uid(uid) => {
+                let size = int_size(uid.0);
+                buf.push(0x80 | (size - 1) as u8);
+                push_uint(&mut buf, uid.0, size);
+            }
+        }
+        Ok(self.scalar(buf))
+    }
+
+    fn add_str(&mut self, str: &str) -> u64 {
+        let mut buf = vec![];
+        if str.is_ascii() {
+            push_marker(&mut buf, 0x50, str.len());
+            buf.extend_from_slice(str.as_bytes());
+        } else {
+            let utf16: Vec<u16> = str.encode_utf16().collect();
+            push_marker(&mut buf, 0x60, utf16.len());
+            utf16
+                .iter()
+                .for_each(|c| buf.extend_from_slice(&c.to_be_bytes()));
+        }
+        self.scalar(buf)
+    }
+}
+
+fn int_size(max: u64) -> usize {
+    match max {
+        0..=0xff => 1,
+        0x100..=0xffff => 2,
+        0x1_0000..=0xffff_ffff => 4,
+        _ => 8,
+    }
+}
+
+fn push_uint(buf: &mut Vec<u8>, v: u64, size: usize) {
+    buf.extend_from_slice(&v.to_be_bytes()[8 - size..]);
+}
+
+/// Marker with 4 bit length, bigger lengths follow as int object
+fn push_marker(buf: &mut Vec<u8>, marker: u8, len: usize) {
+    if len < 0xf {
+        buf.push(marker | len as u8);
+    } else {
+        buf.push(marker | 0xf);
+        push_int(buf, (len as u64).into());
+    }
+}
+
+/// Negative numbers take 8 bytes, numbers above `i64::MAX` take 16
+fn push_int(buf: &mut Vec<u8>, v: Integer) {
+    match (v.as_u64(), v.as_i64()) {
+        (Some(u), _) if u <= i64::MAX as u64 => {
+            let size = int_size(u);
+            buf.push(0x10 | size.trailing_zeros() as u8);
+            push_uint(buf, u, size);
+        }
+        (_, Some(i)) => {
+            buf.push(0x13);
+            buf.extend_from_slice(&i.to_be_bytes());
+        }
+        _ => {
+            buf.push(0x14);
+            buf.extend_from_slice(&v.as_i128().to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Written by Python `plistlib` from a keyed archive with every object kind
+    const ARCHIVE: &[u8] = include_bytes!("testdata/archive.bplist");
+    /// Written by Python `plistlib` from `Info.plist`
+    const INFO: &[u8] = include_bytes!("testdata/Info.bplist");
+
+    #[test]
+    fn fixtures() {
+        for fixture in [ARCHIVE, INFO] {
+            let value = read(fixture).unwrap();
+            assert_eq!(write(&value).unwrap(), fixture);
+        }
+
+        let archive = read(ARCHIVE).unwrap();
+        assert_eq!(archive["$archiver"].as_str(), Some("NSKeyedArchiver"));
+        assert_eq!(archive["$version"].as_i64(), Some(100_000));
+        assert_eq!(archive["$top"]["root"].as_uid(), Some(Uid(1)));
+        let objects = archive["$objects"].as_array().unwrap();
+        assert_eq!(objects[0].as_str(), Some("$null"));
+        assert_eq!(objects[1]["NS.string"].as_str(), Some("héllo wörld ✓"));
+        assert_eq!(objects[1]["$class"].as_uid(), Some(Uid(2)));
+        assert_eq!(objects[2]["$classes"][1].as_str(), Some("NSObject"));
+
+        let extra = &archive["extra"];
+        assert_eq!(extra["big"].as_u64(), Some(u64::MAX));
+        assert_eq!(extra["neg"].as_i64(), Some(-1));
+        assert_eq!(extra["min"].as_i64(), Some(i64::MIN));
+        assert_eq!(extra["half"].as_real(), Some(0.5));
+        assert_eq!(extra["true"].as_bool(), Some(true));
+        assert_eq!(
+            extra["data"].as_data(),
+            Some(&(0..20).collect::<Vec<u8>>()[..])
+        );
+        assert_eq!(extra["long"].as_str().map(str::len), Some(40));
+        assert_eq!(extra["uid"].as_uid(), Some(Uid(0x1_0000)));
+        let date = extra["date"].as_date().unwrap();
+        assert_eq!(date.to_xml_string(), "2024-10-01T10:00:00Z");
+
+        let info = read(INFO).unwrap();
+        assert_eq!(info["CFBundleIdentifier"].as_str(), Some("org.cidre.demo"));
+    }
+
+    #[test]
+    fn layout() {
+        let dict: Dictionary = [("a", true)].into_iter().collect();
+        assert_eq!(
+            write(&dict.into()).unwrap(),
+            b"bplist00\xd1\x01\x02\x51a\x09\x08\x0b\x0d\
+              \0\0\0\0\0\0\x01\x01\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x0e"
+        );
+
+        // 256 objects need 2 byte references
+        let items: Vec<Value> = (0..255).map(Value::from).collect();
+        let bytes = write(&Value::Array(items.clone())).unwrap();
+        assert_eq!(bytes[bytes.len() - 25], 2);
+        assert_eq!(read(&bytes).unwrap(), Value::Array(items));
+
+        // equal scalars are written once
+        let items = vec![
+            Value::from("x"),
+            Value::from("x"),
+            Value::from(1),
+            Value::from(1.0),
+        ];
+        let bytes = write(&Value::Array(items.clone())).unwrap();
+        assert_eq!(bytes[bytes.len() - 17], 4);
+        assert_eq!(read(&bytes).unwrap(), Value::Array(items));
+
+        let big = Value::Data(vec![7; 70_000]);
+        let bytes = write(&big).unwrap();
+        assert_eq!(&bytes[8..12], b"\x4f\x12\x00\x01");
+        assert_eq!(bytes[bytes.len() - 26], 4);
+        assert_eq!(read(&bytes).unwrap(), big);
+    }
+
+    #[test]
+    fn scalars() {
+        for value in [
+            Value::from(0),
+            Value::from(255),
+            Value::from(256),
+            Value::from(-1),
+            Value::from(i64::MAX),
+            Value::from(i64::MIN),
+            Value::from(u64::MAX),
+            Value::from(f64::INFINITY),
+            Value::from(-0.0),
+            Value::Date(Date(-1e9)),
+            Value::Uid(Uid(u64::MAX)),
+            Value::from(""),
+            Value::from("ascii"),
+            Value::from("𝄞 surrogate pair"),
+            Value::Data(vec![]),
+            Value::Dictionary(Dictionary::new()),
+            Value::Array(vec![]),
+        ] {
+            let bytes = write(&value).unwrap();
+            assert_eq!(read(&bytes).unwrap(), value, "{value:?}");
+        }
+        let bytes = write(&Value::from(u64::MAX)).unwrap();
+        assert_eq!(&bytes[8..9], b"\x14");
+        let nan = read(&write(&Value::Real(f64::NAN)).unwrap()).unwrap();
+        assert!(nan.as_real().unwrap().is_nan());
+    }
+
+    #[test]
+    fn errors() {
+        let mut ok = write(&Value::Array(vec![Value::from(1)])).unwrap();
+        assert!(read(&ok).is_ok());
+        assert_eq!(read(&ok[..20]), Err(Error::Eof));
+        assert_eq!(
+            read(b"bplist01"),
+            Err(Error::Invalid("binary plist header"))
+        );
+
+        // array referring to itself
+        let mut cyclic = ok.clone();
+        cyclic[9] = 0;
+        assert_eq!(read(&cyclic), Err(Error::Invalid("object cycle")));
+
+        // reference past the objects count
+        let mut bad = ok.clone();
+        bad[9] = 5;
+        assert_eq!(read(&bad), Err(Error::Invalid("object reference")));
+
+        // offsets table past the end
+        let len = ok.len();
+        ok[len - 1] = 0xff;
+        assert_eq!(read(&ok), Err(Error::Invalid("binary plist trailer")));
+
+        let data = write(&Value::Data(vec![0; 20])).unwrap();
+        let mut short = data.clone();
+        short[10] = 0xff;
+        assert_eq!(read(&short), Err(Error::Eof));
+
+        let key = write(&Value::Dictionary([("k", 1)].into_iter().collect())).unwrap();
+        let mut int_key = key.clone();
+        int_key[9] = 2;
+        assert_eq!(
+            read(&int_key),
+            Err(Error::Unsupported("non string dictionary key"))
+        );
+    }
+}
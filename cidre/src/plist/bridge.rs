@@ -0,0 +1,115 @@
+use std::mem::transmute;
+
+use crate::{arc, cf};
+
+use super::{Date, Dictionary, Error, Integer, Result, Uid, Value};
+
+impl Value {
+    /// Fails for data CoreFoundation can't allocate and UIDs above `u32::MAX`
+    pub fn to_cf(&self) -> Result<arc::R<cf::Plist>> {
+        let res: arc::R<cf::Plist> = match self {
+            Self::Array(arr) => {
+                let mut res = cf::ArrayMut::with_capacity(arr.len() as cf::Index);
+                for v in arr {
+                    res.push(&v.to_cf()?);
+                }
+                unsafe { transmute(res) }
+            }
+            Self::Dictionary(dict) => {
+                let mut res = cf::DictionaryMut::with_capacity(dict.len());
+                for (k, v) in dict.iter() {
+                    res.insert(&cf::String::from_str(k), &v.to_cf()?);
+                }
+                unsafe { transmute(res) }
+            }
+            Self::Bool(true) => cf::Boolean::value_true().retained().into(),
+            Self::Bool(false) => cf::Boolean::value_false().retained().into(),
+            Self::Data(data) => cf::Data::from_slice(data)
+                .ok_or(Error::Unsupported("data size"))?
+                .into(),
+            Self::Date(date) => unsafe { transmute(cf::Date::new_at(date.0)) },
+            Self::Real(r) => cf::Number::from_f64(*r).into(),
+            Self::Integer(i) => match i.as_i64() {
+                Some(i) => cf::Number::from_i64(i).into(),
+                None => cf::Number::from_i128(i.as_i128()).into(),
+            },
+            Self::String(s) => cf::String::from_str(s).into(),
+            Self::Uid(uid) => {
+                let uid = u32::try_from(uid.0).map_err(|_| Error::Unsupported("uid"))?;
+                let res = unsafe { _CFKeyedArchiverUIDCreate(None, uid) };
+                unsafe { transmute(res.ok_or(Error::Unsupported("uid"))?) }
+            }
+        };
+        Ok(res)
+    }
+
+    /// Dictionary keys are sorted, CoreFoundation doesn't keep their order
+    pub fn from_cf(plist: &cf::Plist) -> Result<Self> {
+        if let Some(s) = plist.try_as_string() {
+            return Ok(Self::String(s.to_string()));
+        }
+        if let Some(n) = plist.try_as_number() {
+            if n.is_float_type() {
+                return Ok(Self::Real(n.to_f64().unwrap_or_default()));
+            }
+            let i = n.to_i128().ok_or(Error::Invalid("number"))?;
+            return Ok(Self::Integer(Integer::try_from(i)?));
+        }
+        if let Some(b) = plist.try_as_boolean() {
+            return Ok(Self::Bool(b.value()));
+        }
+        if let Some(d) = plist.try_as_data() {
+            return Ok(Self::Data(d.as_slice().to_vec()));
+        }
+        if let Some(d) = plist.try_as_date() {
+            return Ok(Self::Date(Date(d.abs_time())));
+        }
+        if let Some(arr) = plist.try_as_array() {
+            let res: Result<Vec<_>> = arr.iter().map(Self::from_cf).collect();
+            return res.map(Self::Array);
+        }
+        if let Some(dict) = plist.try_as_raw_dictionary() {
+            let (keys, values) = dict.keys_with_values();
+            let mut res = Dictionary::with_capacity(keys.len());
+            for (k, v) in keys.into_iter().zip(values) {
+                let k: &cf::Plist = unsafe { transmute(k) };
+                let k = k
+                    .try_as_string()
+                    .ok_or(Error::Unsupported("non string dictionary key"))?;
+                res.insert(k.to_string(), Self::from_cf(unsafe { transmute(v) })?);
+            }
+            res.sort_keys();
+            return Ok(Self::Dictionary(res));
+        }
+        if plist.get_type_id() == unsafe { _CFKeyedArchiverUIDGetTypeID() } {
+            let uid = unsafe { _CFKeyedArchiverUIDGetValue(plist) };
+            return Ok(Self::Uid(Uid(uid as u64)));
+        }
+        Err(Error::Unsupported("property list type"))
+    }
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+unsafe extern "C-unwind" {
+    fn _CFKeyedArchiverUIDCreate(
+        allocator: Option<&cf::Allocator>,
+        value: u32,
+    ) -> Option<arc::R<cf::Type>>;
+    fn _CFKeyedArchiverUIDGetTypeID() -> cf::TypeId;
+    fn _CFKeyedArchiverUIDGetValue(uid: &cf::Type) -> u32;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plist::{Format, Value};
+
+    #[test]
+    fn basics() {
+        let value = Value::from_data(include_bytes!("testdata/archive.bplist")).unwrap();
+        let cf = value.to_cf().unwrap();
+        assert_eq!(Value::from_cf(&cf).unwrap(), value);
+
+        let data = cf.to_cf_data(Format::BinaryV1_0.into()).unwrap();
+        assert_eq!(Value::from_data(data.as_slice()).unwrap(), value);
+    }
+}
@@ -0,0 +1,543 @@
+use serde::de::{self, Deserialize, DeserializeOwned, IntoDeserializer, Visitor};
+
+use super::{
+    Date, Dictionary, Error, Integer, Result, Uid, Value,
+    ser::{DATE_NAME, UID_NAME},
+};
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Message(msg.to_string())
+    }
+}
+
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    T::deserialize(value)
+}
+
+/// Reads any of the three formats
+pub fn from_slice<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    from_value(Value::from_data(data)?)
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Self::Array(arr) => visitor.visit_seq(SeqAccess(arr.into_iter())),
+            Self::Dictionary(dict) => visitor.visit_map(MapAccess {
+                iter: dict.into_iter(),
+                value: None,
+            }),
+            Self::Bool(b) => visitor.visit_bool(b),
+            Self::Data(data) => visitor.visit_byte_buf(data),
+            Self::Real(r) => visitor.visit_f64(r),
+            Self::Integer(i) => match i.as_i64() {
+                Some(i) => visitor.visit_i64(i),
+                None => visitor.visit_u64(i.0 as u64),
+            },
+            Self::String(s) => visitor.visit_string(s),
+            // self-describing formats see a single entry map, `Value` detects it
+            Self::Date(date) => visitor.visit_map(MapAccess {
+                iter: vec![(DATE_NAME.into(), Self::Real(date.0))].into_iter(),
+                value: None,
+            }),
+            Self::Uid(uid) => visitor.visit_map(MapAccess {
+                iter: vec![(UID_NAME.into(), uid.0.into())].into_iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // missing keys are the only `None`
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        match (name, self) {
+            (DATE_NAME, Self::Date(date)) => visitor.visit_newtype_struct(Self::Real(date.0)),
+            (UID_NAME, Self::Uid(uid)) => visitor.visit_newtype_struct(Self::from(uid.0)),
+            (DATE_NAME, _) => Err(Error::Invalid("date")),
+            (UID_NAME, _) => Err(Error::Invalid("uid")),
+            (_, value) => visitor.visit_newtype_struct(value),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            // `Vec<u8>` fields read data
+            Self::Data(data) => {
+                let arr: Vec<Value> = data.into_iter().map(Value::from).collect();
+                visitor.visit_seq(SeqAccess(arr.into_iter()))
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            Self::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Self::Dictionary(dict) if dict.len() == 1 => {
+                let (variant, value) = dict.into_iter().next().unwrap();
+                visitor.visit_enum(EnumAccess { variant, value })
+            }
+            _ => Err(Error::Invalid("enum")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+    }
+}
+
+impl IntoDeserializer<'_, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct SeqAccess(std::vec::IntoIter<Value>);
+
+impl<'de> de::SeqAccess<'de> for SeqAccess {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        self.0.next().map(|v| seed.deserialize(v)).transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapAccess {
+    iter: std::vec::IntoIter<(String, Value)>,
+    value: Option<Value>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some((key, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(Key(key)).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .value
+            .take()
+            .ok_or(Error::Invalid("value without key"))?;
+        seed.deserialize(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Dictionary key, parses back integer keys `to_value` stringified
+struct Key(String);
+
+macro_rules! deserialize_key_int {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                match self.0.parse() {
+                    Ok(i) => visitor.$visit(i),
+                    Err(_) => visitor.visit_string(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Key {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_key_int! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        Value::String(self.0).deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumAccess {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = Error;
+    type Variant = Value;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Value)> {
+        let variant = seed.deserialize(Value::String(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Value {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result {
+        Err(Error::Invalid("unit variant"))
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a property list value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> std::result::Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> std::result::Result<Value, E> {
+        Integer::try_from(v).map(Value::Integer).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> std::result::Result<Value, E> {
+        let v = i128::try_from(v).map_err(E::custom)?;
+        self.visit_i128(v)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Value, E> {
+        Ok(Value::Real(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Value, E> {
+        Ok(Value::String(v.into()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> std::result::Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> std::result::Result<Value, E> {
+        Ok(Value::Data(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> std::result::Result<Value, E> {
+        Ok(Value::Data(v))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> std::result::Result<Value, D::Error> {
+        Value::deserialize(d)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        d: D,
+    ) -> std::result::Result<Value, D::Error> {
+        Value::deserialize(d)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Value, A::Error> {
+        let mut arr = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(v) = seq.next_element()? {
+            arr.push(v);
+        }
+        Ok(Value::Array(arr))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> std::result::Result<Value, A::Error> {
+        let mut dict = Dictionary::with_capacity(map.size_hint().unwrap_or_default());
+        let Some(first) = map.next_key::<String>()? else {
+            return Ok(Value::Dictionary(dict));
+        };
+        match first.as_str() {
+            DATE_NAME => return Ok(Value::Date(Date(map.next_value()?))),
+            UID_NAME => return Ok(Value::Uid(Uid(map.next_value()?))),
+            _ => {}
+        }
+        dict.insert(first, map.next_value::<Value>()?);
+        while let Some((k, v)) = map.next_entry::<String, Value>()? {
+            dict.insert(k, v);
+        }
+        Ok(Value::Dictionary(dict))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        d.deserialize_any(ValueVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dictionary {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        match Value::deserialize(d)? {
+            Value::Dictionary(dict) => Ok(dict),
+            _ => Err(de::Error::custom("expected a dictionary")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Integer {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        match i128::deserialize(d)?.try_into() {
+            Ok(i) => Ok(i),
+            Err(e) => Err(de::Error::custom(e)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        d.deserialize_newtype_struct(DATE_NAME, DateVisitor)
+    }
+}
+
+/// Unwraps the plist newtype, other formats give plain seconds
+struct DateVisitor;
+
+impl<'de> Visitor<'de> for DateVisitor {
+    type Value = Date;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a date")
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        d: D,
+    ) -> std::result::Result<Date, D::Error> {
+        f64::deserialize(d).map(Date)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Date, E> {
+        Ok(Date(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Date, E> {
+        Ok(Date(v as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Date, E> {
+        Ok(Date(v as f64))
+    }
+}
+
+impl<'de> Deserialize<'de> for Uid {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        d.deserialize_newtype_struct(UID_NAME, UidVisitor)
+    }
+}
+
+struct UidVisitor;
+
+impl<'de> Visitor<'de> for UidVisitor {
+    type Value = Uid;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a uid")
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        d: D,
+    ) -> std::result::Result<Uid, D::Error> {
+        u64::deserialize(d).map(Uid)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Uid, E> {
+        Ok(Uid(v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::plist::{self, Date, Dictionary, Error, Format, Uid, Value};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Plain,
+        Named(String),
+        Pair(u8, i8),
+        Sized { w: u32, h: u32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Item {
+        name: String,
+        count: u64,
+        ratio: f32,
+        enabled: bool,
+        #[serde(with = "bytes")]
+        payload: Vec<u8>,
+        tags: Vec<String>,
+        created: Date,
+        parent: Option<Uid>,
+        note: Option<String>,
+        kinds: Vec<Kind>,
+        ids: BTreeMap<u32, i64>,
+    }
+
+    /// `Vec<u8>` serializes as an array unless asked otherwise
+    mod bytes {
+        pub fn serialize<S: serde::Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_bytes(v)
+        }
+
+        pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+            serde::Deserialize::deserialize(d)
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let item = Item {
+            name: "cam".into(),
+            count: u64::MAX,
+            ratio: 0.5,
+            enabled: true,
+            payload: vec![1, 2, 3],
+            tags: vec!["a".into(), "b".into()],
+            created: Date(750_000_000.0),
+            parent: Some(Uid(4)),
+            note: None,
+            kinds: vec![
+                Kind::Plain,
+                Kind::Named("x".into()),
+                Kind::Pair(1, -1),
+                Kind::Sized { w: 2, h: 3 },
+            ],
+            ids: [(1, -5), (20, 7)].into_iter().collect(),
+        };
+        let value = plist::to_value(&item).unwrap();
+        let dict = value.as_dictionary().unwrap();
+        assert!(!dict.contains_key("Note"));
+        assert_eq!(value["Payload"], Value::Data(vec![1, 2, 3]));
+        assert_eq!(value["Created"], Value::Date(Date(750_000_000.0)));
+        assert_eq!(value["Parent"], Value::Uid(Uid(4)));
+        assert_eq!(value["Ids"]["20"].as_i64(), Some(7));
+        assert_eq!(value["Kinds"][0].as_str(), Some("Plain"));
+        assert_eq!(value["Kinds"][3]["Sized"]["h"].as_u64(), Some(3));
+
+        for format in [Format::BinaryV1_0, Format::XmlV1_0] {
+            let data = plist::to_vec(&item, format).unwrap();
+            let back: Item = plist::from_slice(&data).unwrap();
+            assert_eq!(back, item);
+        }
+        assert_eq!(
+            plist::to_vec(&item, Format::OpenStep),
+            Err(Error::Unsupported("integer in OpenStep plist"))
+        );
+    }
+
+    #[test]
+    fn values() {
+        let value = Value::from_data(include_bytes!("testdata/archive.bplist")).unwrap();
+        // `Value` through its own serializer and deserializer keeps dates and uids
+        assert_eq!(plist::to_value(&value).unwrap(), value);
+        let back: Value = plist::from_value(value.clone()).unwrap();
+        assert_eq!(back, value);
+        let dict: Dictionary = plist::from_value(value.clone()).unwrap();
+        assert_eq!(dict["$top"]["root"], Value::Uid(Uid(1)));
+
+        // `Vec<u8>` fields read data without help
+        let data: Vec<u8> = plist::from_value(Value::Data(vec![9, 8])).unwrap();
+        assert_eq!(data, [9, 8]);
+
+        assert_eq!(
+            plist::to_value(&None::<u8>),
+            Err(Error::Unsupported("empty top level value"))
+        );
+        assert_eq!(
+            plist::to_value(&vec![None, Some(1)]),
+            Err(Error::Unsupported("empty value in array"))
+        );
+        assert!(plist::from_value::<Date>(Value::Real(1.0)).is_err());
+        assert!(plist::from_value::<u8>(Value::from(256)).is_err());
+    }
+}
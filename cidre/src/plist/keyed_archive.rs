@@ -0,0 +1,738 @@
+//! `NSKeyedArchiver` archives without Foundation
+//!
+//! An archive is a property list whose `$objects` array holds every archived object,
+//! with references between them stored as [`Uid`]s. [`Archive`] keeps that graph
+//! as is, so shared and cyclic references survive decoding, and [`Archive::resolve`]
+//! turns a subtree into a plain [`Value`] for inspection or serde.
+//!
+//! ```
+//! use cidre::plist::{Value, keyed_archive::{Archive, Object}};
+//!
+//! let tags = Value::Array(vec!["a".into(), "b".into()]);
+//! let archive = Archive::from_root(&tags).unwrap();
+//! let root = archive.root().unwrap();
+//! assert!(matches!(&archive[root], Object::Array(items) if items.len() == 2));
+//!
+//! let data = archive.to_data(cidre::plist::Format::BinaryV1_0).unwrap();
+//! let archive = Archive::from_data(&data).unwrap();
+//! assert_eq!(archive.resolve(archive.root().unwrap()).unwrap(), tags);
+//! ```
+
+use std::ops::Index;
+
+use super::{Date, Dictionary, Error, Format, Integer, Result, Uid, Value};
+
+const ARCHIVER: &str = "NSKeyedArchiver";
+const VERSION: u64 = 100_000;
+const NULL: &str = "$null";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    /// `$null`, always at `Uid(0)`
+    Null,
+    Bool(bool),
+    Integer(Integer),
+    Real(f64),
+    String(String),
+    Data(Vec<u8>),
+    Date(Date),
+    Url {
+        base: Option<Uid>,
+        relative: Uid,
+    },
+    Uuid([u8; 16]),
+    Array(Vec<Uid>),
+    Set(Vec<Uid>),
+    Dictionary(Vec<(Uid, Uid)>),
+    /// Object of any other class.
+    ///
+    /// `fields` holds inline values as encoded and references as `Value::Uid`.
+    Instance {
+        classes: Vec<String>,
+        fields: Dictionary,
+    },
+    /// `$classes` of the instances referencing it, most derived first
+    Class(Vec<String>),
+}
+
+impl Object {
+    /// Foundation class an instance of this object archives as
+    pub fn class_name(&self) -> Option<&str> {
+        match self {
+            Self::Null | Self::Class(_) => None,
+            Self::Bool(_) | Self::Integer(_) | Self::Real(_) => Some("NSNumber"),
+            Self::String(_) => Some("NSString"),
+            Self::Data(_) => Some("NSData"),
+            Self::Date(_) => Some("NSDate"),
+            Self::Url { .. } => Some("NSURL"),
+            Self::Uuid(_) => Some("NSUUID"),
+            Self::Array(_) => Some("NSArray"),
+            Self::Set(_) => Some("NSSet"),
+            Self::Dictionary(_) => Some("NSDictionary"),
+            Self::Instance { classes, .. } => classes.first().map(String::as_str),
+        }
+    }
+}
+
+/// Object graph of a keyed archive, objects are indexed by their `Uid`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Archive {
+    objects: Vec<Object>,
+    top: Vec<(String, Uid)>,
+}
+
+impl Default for Archive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Archive {
+    /// Empty archive with only `$null`
+    pub fn new() -> Self {
+        Self {
+            objects: vec![Object::Null],
+            top: vec![],
+        }
+    }
+
+    /// Archive of a property list tree the way `NSKeyedArchiver` lays it out
+    pub fn from_root(value: &Value) -> Result<Self> {
+        let mut res = Self::new();
+        let root = res.push_value(value)?;
+        res.set_root(root);
+        Ok(res)
+    }
+
+    pub fn from_data(data: &[u8]) -> Result<Self> {
+        Self::from_value(&Value::from_data(data)?)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self> {
+        if value.get("$archiver").is_some_and(|a| a.as_str().is_none()) {
+            return Err(Error::Invalid("$archiver"));
+        }
+        let raw = value
+            .get("$objects")
+            .and_then(Value::as_array)
+            .ok_or(Error::Invalid("$objects"))?;
+        let top = value
+            .get("$top")
+            .and_then(Value::as_dictionary)
+            .ok_or(Error::Invalid("$top"))?;
+        if raw.first().and_then(Value::as_str) != Some(NULL) {
+            return Err(Error::Invalid("$null object"));
+        }
+
+        let decoder = Decoder { raw };
+        let top = top
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), decoder.uid(v)?)))
+            .collect::<Result<_>>()?;
+        let objects = raw
+            .iter()
+            .map(|v| decoder.object(v))
+            .collect::<Result<_>>()?;
+        Ok(Self { objects, top })
+    }
+
+    pub fn to_data(&self, format: Format) -> Result<Vec<u8>> {
+        self.to_value().to_data(format)
+    }
+
+    /// Property list `NSKeyedUnarchiver` reads.
+    ///
+    /// Class descriptions are reused when the archive has them, otherwise appended.
+    pub fn to_value(&self) -> Value {
+        let mut encoder = Encoder {
+            len: self.objects.len(),
+            extra: vec![],
+            classes: vec![],
+        };
+        for (i, obj) in self.objects.iter().enumerate() {
+            if let Object::Class(classes) = obj {
+                encoder.classes.push((classes.clone(), Uid(i as u64)));
+            }
+        }
+        let mut objects: Vec<Value> = self.objects.iter().map(|o| encoder.object(o)).collect();
+        objects.append(&mut encoder.extra);
+        let top: Dictionary = self
+            .top
+            .iter()
+            .map(|(k, uid)| (k.as_str(), Value::Uid(*uid)))
+            .collect();
+        [
+            ("$version", Value::from(VERSION)),
+            ("$archiver", ARCHIVER.into()),
+            ("$top", top.into()),
+            ("$objects", objects.into()),
+        ]
+        .into_iter()
+        .collect::<Dictionary>()
+        .into()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// `$null` alone
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.objects.len() == 1
+    }
+
+    #[inline]
+    pub fn objects(&self) -> &[Object] {
+        &self.objects
+    }
+
+    #[inline]
+    pub fn get(&self, uid: Uid) -> Option<&Object> {
+        self.objects.get(uid.0 as usize)
+    }
+
+    /// Adds an object and returns its reference, `Object::Null` is always `Uid(0)`
+    pub fn push(&mut self, obj: Object) -> Uid {
+        if obj == Object::Null {
+            return Uid(0);
+        }
+        self.objects.push(obj);
+        Uid(self.objects.len() as u64 - 1)
+    }
+
+    /// Replaces an object, for graphs with cycles
+    pub fn set(&mut self, uid: Uid, obj: Object) -> Option<Object> {
+        let slot = self.objects.get_mut(uid.0 as usize)?;
+        Some(std::mem::replace(slot, obj))
+    }
+
+    pub fn top(&self, key: &str) -> Option<Uid> {
+        self.top.iter().find(|(k, _)| k == key).map(|(_, v)| *v)
+    }
+
+    pub fn set_top(&mut self, key: impl Into<String>, uid: Uid) {
+        let key = key.into();
+        match self.top.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = uid,
+            None => self.top.push((key, uid)),
+        }
+    }
+
+    /// `$top` key `NSKeyedArchiver.archivedData(withRootObject:)` uses
+    #[inline]
+    pub fn root(&self) -> Option<Uid> {
+        self.top("root")
+    }
+
+    #[inline]
+    pub fn set_root(&mut self, uid: Uid) {
+        self.set_top("root", uid);
+    }
+
+    /// Plain property list of the object graph under `uid`.
+    ///
+    /// Sets become arrays, UUIDs uppercase strings and URLs without a base their
+    /// string. Other instances become dictionaries with a `$classname` key, and
+    /// fields referencing `$null` are left out. Fails on cycles.
+    pub fn resolve(&self, uid: Uid) -> Result<Value> {
+        self.resolve_in(uid, &mut vec![])
+    }
+
+    fn resolve_in(&self, uid: Uid, parents: &mut Vec<Uid>) -> Result<Value> {
+        if parents.contains(&uid) {
+            return Err(Error::Invalid("object cycle"));
+        }
+        let obj = self.get(uid).ok_or(Error::Invalid("object reference"))?;
+        parents.push(uid);
+        let res = match obj {
+            Object::Null => return Err(Error::Unsupported("null object")),
+            Object::Bool(b) => Value::Bool(*b),
+            Object::Integer(i) => Value::Integer(*i),
+            Object::Real(r) => Value::Real(*r),
+            Object::String(s) => Value::String(s.clone()),
+            Object::Data(d) => Value::Data(d.clone()),
+            Object::Date(d) => Value::Date(*d),
+            Object::Url {
+                base: None,
+                relative,
+            } => self.resolve_in(*relative, parents)?,
+            Object::Url {
+                base: Some(base),
+                relative,
+            } => [
+                ("$classname", Value::from("NSURL")),
+                ("NS.base", self.resolve_in(*base, parents)?),
+                ("NS.relative", self.resolve_in(*relative, parents)?),
+            ]
+            .into_iter()
+            .collect::<Dictionary>()
+            .into(),
+            Object::Uuid(bytes) => Value::String(uuid_string(bytes)),
+            Object::Array(items) | Object::Set(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|uid| self.resolve_in(*uid, parents))
+                    .collect::<Result<_>>()?,
+            ),
+            Object::Dictionary(entries) => {
+                let mut dict = Dictionary::with_capacity(entries.len());
+                for (k, v) in entries {
+                    let Value::String(k) = self.resolve_in(*k, parents)? else {
+                        return Err(Error::Unsupported("non string dictionary key"));
+                    };
+                    dict.insert(k, self.resolve_in(*v, parents)?);
+                }
+                dict.into()
+            }
+            Object::Instance { classes, fields } => {
+                let mut dict = Dictionary::with_capacity(fields.len() + 1);
+                if let Some(name) = classes.first() {
+                    dict.insert("$classname", name.as_str());
+                }
+                for (k, v) in fields.iter() {
+                    match v {
+                        Value::Uid(Uid(0)) => {}
+                        Value::Uid(uid) => {
+                            _ = dict.insert(k.as_str(), self.resolve_in(*uid, parents)?)
+                        }
+                        v => _ = dict.insert(k.as_str(), v.clone()),
+                    }
+                }
+                dict.into()
+            }
+            Object::Class(classes) => class_value(classes),
+        };
+        parents.pop();
+        Ok(res)
+    }
+
+    fn push_value(&mut self, value: &Value) -> Result<Uid> {
+        // parents take their uid before children, like `NSKeyedArchiver`
+        let res = match value {
+            Value::Array(items) => {
+                let uid = self.push(Object::Array(vec![]));
+                let items = items
+                    .iter()
+                    .map(|v| self.push_value(v))
+                    .collect::<Result<_>>()?;
+                self.set(uid, Object::Array(items));
+                uid
+            }
+            Value::Dictionary(dict) => {
+                let uid = self.push(Object::Dictionary(vec![]));
+                let keys: Vec<Uid> = dict
+                    .keys()
+                    .map(|k| self.push(Object::String(k.clone())))
+                    .collect();
+                let mut entries = Vec::with_capacity(keys.len());
+                for (k, v) in keys.into_iter().zip(dict.values()) {
+                    entries.push((k, self.push_value(v)?));
+                }
+                self.set(uid, Object::Dictionary(entries));
+                uid
+            }
+            Value::Bool(b) => self.push(Object::Bool(*b)),
+            Value::Data(d) => self.push(Object::Data(d.clone())),
+            Value::Date(d) => self.push(Object::Date(*d)),
+            Value::Real(r) => self.push(Object::Real(*r)),
+            Value::Integer(i) => self.push(Object::Integer(*i)),
+            Value::String(s) => self.push(Object::String(s.clone())),
+            Value::Uid(_) => return Err(Error::Unsupported("uid in archived value")),
+        };
+        Ok(res)
+    }
+}
+
+impl Index<Uid> for Archive {
+    type Output = Object;
+
+    /// Panics if `uid` is out of range
+    fn index(&self, uid: Uid) -> &Object {
+        self.get(uid).expect("no such object")
+    }
+}
+
+fn uuid_string(bytes: &[u8; 16]) -> String {
+    let mut res = String::with_capacity(36);
+    for (i, b) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            res.push('-');
+        }
+        res.push_str(&format!("{b:02X}"));
+    }
+    res
+}
+
+struct Decoder<'a> {
+    raw: &'a [Value],
+}
+
+impl Decoder<'_> {
+    fn uid(&self, value: &Value) -> Result<Uid> {
+        match value {
+            Value::Uid(uid) if (uid.0 as usize) < self.raw.len() => Ok(*uid),
+            _ => Err(Error::Invalid("object reference")),
+        }
+    }
+
+    fn field<'v>(&self, dict: &'v Dictionary, key: &'static str) -> Result<&'v Value> {
+        dict.get(key).ok_or(Error::Invalid(key))
+    }
+
+    fn uids(&self, dict: &Dictionary, key: &'static str) -> Result<Vec<Uid>> {
+        let items = self
+            .field(dict, key)?
+            .as_array()
+            .ok_or(Error::Invalid(key))?;
+        items.iter().map(|v| self.uid(v)).collect()
+    }
+
+    /// `$classes` of the description `class` references
+    fn classes(&self, class: &Value) -> Result<Vec<String>> {
+        let class = self.raw[self.uid(class)?.0 as usize]
+            .as_dictionary()
+            .ok_or(Error::Invalid("$class"))?;
+        class_names(class)
+    }
+
+    fn object(&self, value: &Value) -> Result<Object> {
+        let res = match value {
+            Value::String(s) if s == NULL => Object::Null,
+            Value::String(s) => Object::String(s.clone()),
+            Value::Bool(b) => Object::Bool(*b),
+            Value::Integer(i) => Object::Integer(*i),
+            Value::Real(r) => Object::Real(*r),
+            Value::Data(d) => Object::Data(d.clone()),
+            Value::Date(d) => Object::Date(*d),
+            Value::Dictionary(dict) => match dict.get("$class") {
+                Some(class) => self.instance(self.classes(class)?, dict)?,
+                None => Object::Class(class_names(dict)?),
+            },
+            Value::Array(_) | Value::Uid(_) => return Err(Error::Invalid("object")),
+        };
+        Ok(res)
+    }
+
+    fn instance(&self, classes: Vec<String>, dict: &Dictionary) -> Result<Object> {
+        let res = match classes.first().map(String::as_str).unwrap_or_default() {
+            "NSString" | "NSMutableString" => match dict.get("NS.string") {
+                Some(Value::String(s)) => Object::String(s.clone()),
+                _ => {
+                    let bytes = self.field(dict, "NS.bytes")?;
+                    let bytes = bytes.as_data().ok_or(Error::Invalid("NS.bytes"))?;
+                    let s = std::str::from_utf8(bytes).map_err(|_| Error::Invalid("NS.bytes"))?;
+                    Object::String(s.into())
+                }
+            },
+            "NSData" | "NSMutableData" => {
+                let data = self.field(dict, "NS.data")?;
+                Object::Data(data.as_data().ok_or(Error::Invalid("NS.data"))?.to_vec())
+            }
+            "NSDate" => {
+                let time = self.field(dict, "NS.time")?;
+                Object::Date(Date(time.as_real().ok_or(Error::Invalid("NS.time"))?))
+            }
+            "NSURL" => {
+                let base = self.uid(self.field(dict, "NS.base")?)?;
+                Object::Url {
+                    base: (base.0 != 0).then_some(base),
+                    relative: self.uid(self.field(dict, "NS.relative")?)?,
+                }
+            }
+            "NSUUID" => {
+                let bytes = self.field(dict, "NS.uuidbytes")?.as_data();
+                let bytes = bytes.and_then(|b| b.try_into().ok());
+                Object::Uuid(bytes.ok_or(Error::Invalid("NS.uuidbytes"))?)
+            }
+            "NSArray" | "NSMutableArray" => Object::Array(self.uids(dict, "NS.objects")?),
+            "NSSet" | "NSMutableSet" => Object::Set(self.uids(dict, "NS.objects")?),
+            "NSDictionary" | "NSMutableDictionary" => {
+                let keys = self.uids(dict, "NS.keys")?;
+                let values = self.uids(dict, "NS.objects")?;
+                if keys.len() != values.len() {
+                    return Err(Error::Invalid("NS.keys"));
+                }
+                Object::Dictionary(keys.into_iter().zip(values).collect())
+            }
+            _ => {
+                let mut fields = dict.clone();
+                fields.remove("$class");
+                for v in fields.values() {
+                    if let Value::Uid(_) = v {
+                        self.uid(v)?;
+                    }
+                }
+                Object::Instance { classes, fields }
+            }
+        };
+        Ok(res)
+    }
+}
+
+fn class_names(class: &Dictionary) -> Result<Vec<String>> {
+    match (class.get("$classes"), class.get("$classname")) {
+        (Some(Value::Array(classes)), _) => classes
+            .iter()
+            .map(|c| c.as_str().map(String::from))
+            .collect::<Option<_>>()
+            .ok_or(Error::Invalid("$classes")),
+        (None, Some(Value::String(name))) => Ok(vec![name.clone()]),
+        _ => Err(Error::Invalid("class description")),
+    }
+}
+
+/// Turns objects back into `$objects` entries
+struct Encoder {
+    /// Objects of the archive, missing class descriptions go after them
+    len: usize,
+    extra: Vec<Value>,
+    classes: Vec<(Vec<String>, Uid)>,
+}
+
+impl Encoder {
+    fn class(&mut self, classes: &[&str]) -> Value {
+        let found = self.classes.iter().find(|(c, _)| c.iter().eq(classes));
+        if let Some((_, uid)) = found {
+            return Value::Uid(*uid);
+        }
+        let uid = Uid((self.len + self.extra.len()) as u64);
+        let classes: Vec<String> = classes.iter().map(|c| c.to_string()).collect();
+        self.extra.push(class_value(&classes));
+        self.classes.push((classes, uid));
+        Value::Uid(uid)
+    }
+
+    fn object(&mut self, obj: &Object) -> Value {
+        let uids = |items: &[Uid]| Value::Array(items.iter().map(|u| Value::Uid(*u)).collect());
+        let (mut fields, classes): (Dictionary, &[&str]) = match obj {
+            Object::Null => return NULL.into(),
+            Object::Bool(b) => return Value::Bool(*b),
+            Object::Integer(i) => return Value::Integer(*i),
+            Object::Real(r) => return Value::Real(*r),
+            Object::String(s) => return Value::String(s.clone()),
+            Object::Data(d) => return Value::Data(d.clone()),
+            Object::Class(classes) => return class_value(classes),
+            Object::Date(d) => (
+                [("NS.time", d.0)].into_iter().collect(),
+                &["NSDate", "NSObject"],
+            ),
+            Object::Url { base, relative } => (
+                [
+                    ("NS.base", base.unwrap_or(Uid(0))),
+                    ("NS.relative", *relative),
+                ]
+                .into_iter()
+                .collect(),
+                &["NSURL", "NSObject"],
+            ),
+            Object::Uuid(bytes) => (
+                [("NS.uuidbytes", bytes.to_vec())].into_iter().collect(),
+                &["NSUUID", "NSObject"],
+            ),
+            Object::Array(items) => (
+                [("NS.objects", uids(items))].into_iter().collect(),
+                &["NSArray", "NSObject"],
+            ),
+            Object::Set(items) => (
+                [("NS.objects", uids(items))].into_iter().collect(),
+                &["NSSet", "NSObject"],
+            ),
+            Object::Dictionary(entries) => {
+                let (keys, values): (Vec<Uid>, Vec<Uid>) = entries.iter().copied().unzip();
+                (
+                    [("NS.keys", uids(&keys)), ("NS.objects", uids(&values))]
+                        .into_iter()
+                        .collect(),
+                    &["NSDictionary", "NSObject"],
+                )
+            }
+            Object::Instance { classes, fields } => {
+                let classes: Vec<&str> = classes.iter().map(String::as_str).collect();
+                let mut fields = fields.clone();
+                fields.insert("$class", self.class(&classes));
+                return fields.into();
+            }
+        };
+        fields.insert("$class", self.class(classes));
+        fields.into()
+    }
+}
+
+fn class_value(classes: &[String]) -> Value {
+    [
+        (
+            "$classname",
+            Value::from(classes.first().cloned().unwrap_or_default()),
+        ),
+        (
+            "$classes",
+            Value::Array(classes.iter().map(|c| c.as_str().into()).collect()),
+        ),
+    ]
+    .into_iter()
+    .collect::<Dictionary>()
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYED: &[u8] = include_bytes!("testdata/keyed.bplist");
+
+    /// Value under `key` in the dictionary object `dict`
+    fn entry(archive: &Archive, dict: Uid, key: &str) -> Uid {
+        let Object::Dictionary(entries) = &archive[dict] else {
+            panic!("not a dictionary");
+        };
+        let found = entries
+            .iter()
+            .find(|(k, _)| archive[*k] == Object::String(key.into()));
+        found.expect("no such key").1
+    }
+
+    #[test]
+    fn fixture() {
+        let archive = Archive::from_data(KEYED).unwrap();
+        assert_eq!(archive.len(), 41);
+        let root = archive.root().unwrap();
+        assert_eq!(archive[root].class_name(), Some("NSDictionary"));
+
+        let value = |key| archive.resolve(entry(&archive, root, key)).unwrap();
+        assert_eq!(value("name").as_str(), Some("cam"));
+        assert_eq!(value("count").as_i64(), Some(42));
+        assert_eq!(value("ratio").as_real(), Some(0.5));
+        assert_eq!(value("enabled").as_bool(), Some(true));
+        assert_eq!(value("created"), Value::Date(Date(750_000_000.25)));
+        assert_eq!(
+            value("id").as_str(),
+            Some("10111213-1415-1617-1819-1A1B1C1D1E1F")
+        );
+        assert_eq!(value("tags"), Value::Array(vec!["a".into(), "b".into()]));
+        assert_eq!(value("set"), Value::Array(vec![1.into()]));
+        assert_eq!(value("blob").as_data(), Some(&[0u8, 1, 2, 254, 255][..]));
+        assert_eq!(value("title").as_str(), Some("Cámara"));
+
+        let url = value("url");
+        assert_eq!(url["$classname"].as_str(), Some("NSURL"));
+        assert_eq!(url["NS.base"].as_str(), Some("https://example.com/"));
+        assert_eq!(url["NS.relative"].as_str(), Some("media/clip.mov"));
+
+        let camera = entry(&archive, root, "camera");
+        let Object::Instance { classes, fields } = &archive[camera] else {
+            panic!("camera is not an instance");
+        };
+        assert_eq!(classes, &["Camera", "NSObject"]);
+        assert_eq!(fields["zoom"].as_real(), Some(2.5));
+        assert_eq!(fields["owner"], Value::Uid(root));
+        assert_eq!(fields["delegate"], Value::Uid(Uid(0)));
+
+        // the camera points back at the root
+        assert_eq!(archive.resolve(root), Err(Error::Invalid("object cycle")));
+        assert_eq!(archive.resolve(camera), Err(Error::Invalid("object cycle")));
+    }
+
+    #[test]
+    fn round_trip() {
+        let archive = Archive::from_data(KEYED).unwrap();
+        let data = archive.to_data(Format::BinaryV1_0).unwrap();
+        let back = Archive::from_data(&data).unwrap();
+        // mutable dictionaries and sets get the immutable class descriptions appended
+        assert_eq!(&back.objects()[..archive.len()], archive.objects());
+        assert_eq!(
+            &back.objects()[archive.len()..],
+            [
+                Object::Class(vec!["NSDictionary".into(), "NSObject".into()]),
+                Object::Class(vec!["NSSet".into(), "NSObject".into()]),
+            ]
+        );
+        assert_eq!(back.root(), archive.root());
+
+        let xml = back.to_data(Format::XmlV1_0).unwrap();
+        assert_eq!(Archive::from_data(&xml).unwrap(), back);
+    }
+
+    #[test]
+    fn layout() {
+        // what `NSKeyedArchiver.archivedData(withRootObject: ["a", "b"])` writes
+        let archive = Archive::from_root(&Value::Array(vec!["a".into(), "b".into()])).unwrap();
+        let value = archive.to_value();
+        assert_eq!(value["$archiver"].as_str(), Some("NSKeyedArchiver"));
+        assert_eq!(value["$version"].as_u64(), Some(100_000));
+        assert_eq!(value["$top"]["root"], Value::Uid(Uid(1)));
+        let objects = value["$objects"].as_array().unwrap();
+        assert_eq!(objects[0].as_str(), Some("$null"));
+        assert_eq!(
+            objects[1]["NS.objects"],
+            Value::Array(vec![Value::Uid(Uid(2)), Value::Uid(Uid(3))])
+        );
+        assert_eq!(objects[1]["$class"], Value::Uid(Uid(4)));
+        assert_eq!(objects[2].as_str(), Some("a"));
+        assert_eq!(objects[4]["$classname"].as_str(), Some("NSArray"));
+        assert_eq!(objects.len(), 5);
+
+        let info = Value::from_data(include_bytes!("testdata/Info.plist")).unwrap();
+        let archive = Archive::from_root(&info).unwrap();
+        let data = archive.to_data(Format::BinaryV1_0).unwrap();
+        let back = Archive::from_data(&data).unwrap();
+        assert_eq!(back.resolve(back.root().unwrap()).unwrap(), info);
+        assert_eq!(
+            Archive::from_root(&Value::Uid(Uid(1))),
+            Err(Error::Unsupported("uid in archived value"))
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        let mut archive = Archive::new();
+        let node = archive.push(Object::Array(vec![]));
+        let name = archive.push(Object::String("node".into()));
+        let fields = [("name", Value::Uid(name)), ("parent", Value::Uid(node))];
+        let child = archive.push(Object::Instance {
+            classes: vec!["Node".into(), "NSObject".into()],
+            fields: fields.into_iter().collect(),
+        });
+        archive.set(node, Object::Array(vec![child]));
+        archive.set_root(node);
+
+        let back = Archive::from_data(&archive.to_data(Format::BinaryV1_0).unwrap()).unwrap();
+        assert_eq!(&back.objects()[..archive.len()], archive.objects());
+        assert_eq!(back.resolve(node), Err(Error::Invalid("object cycle")));
+        assert_eq!(back.resolve(name).unwrap().as_str(), Some("node"));
+    }
+
+    #[test]
+    fn errors() {
+        let archive = |objects: Vec<Value>| {
+            let top: Dictionary = [("root", Value::Uid(Uid(1)))].into_iter().collect();
+            let value: Dictionary = [("$top", Value::from(top)), ("$objects", objects.into())]
+                .into_iter()
+                .collect();
+            Archive::from_value(&value.into())
+        };
+        assert_eq!(
+            archive(vec!["x".into(), "y".into()]),
+            Err(Error::Invalid("$null object"))
+        );
+        assert_eq!(
+            archive(vec!["$null".into()]),
+            Err(Error::Invalid("object reference"))
+        );
+        let array: Dictionary = [("NS.objects", Value::Array(vec![Value::Uid(Uid(9))]))]
+            .into_iter()
+            .chain([("$class", Value::Uid(Uid(2)))])
+            .collect();
+        let class: Dictionary = [("$classname", "NSArray")].into_iter().collect();
+        assert_eq!(
+            archive(vec!["$null".into(), array.into(), class.into()]),
+            Err(Error::Invalid("object reference"))
+        );
+        let orphan: Dictionary = [("key", 1)].into_iter().collect();
+        assert_eq!(
+            archive(vec!["$null".into(), orphan.into()]),
+            Err(Error::Invalid("class description"))
+        );
+    }
+}
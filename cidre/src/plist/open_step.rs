@@ -0,0 +1,425 @@
+//! Old-style ASCII property lists, `kCFPropertyListOpenStepFormat`.
+//!
+//! Only strings, data, arrays and dictionaries exist in this format. Xcode projects
+//! and `.strings` files use it.
+
+use std::fmt::Write;
+
+use super::{Dictionary, Error, Result, Value};
+
+pub(super) fn read(data: &[u8]) -> Result<Value> {
+    let text = std::str::from_utf8(data).map_err(|_| Error::Invalid("utf-8"))?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut reader = Reader {
+        rest: text.as_bytes(),
+    };
+    if !reader.skip_space()? {
+        return Ok(Value::Dictionary(Dictionary::new()));
+    }
+    let root = reader.value()?;
+    if reader.skip_space()? {
+        // `.strings` files are a dictionary without the braces
+        let Value::String(key) = root else {
+            return Err(Error::Invalid("data after plist"));
+        };
+        let mut dict = Dictionary::new();
+        reader.entry(&mut dict, key)?;
+        while reader.skip_space()? {
+            let key = reader.string()?;
+            reader.entry(&mut dict, key)?;
+        }
+        return Ok(Value::Dictionary(dict));
+    }
+    Ok(root)
+}
+
+struct Reader<'a> {
+    rest: &'a [u8],
+}
+
+impl Reader<'_> {
+    /// Skips whitespace and comments, returns `false` at the end of data
+    fn skip_space(&mut self) -> Result<bool> {
+        loop {
+            match self.rest {
+                [c, rest @ ..] if c.is_ascii_whitespace() => self.rest = rest,
+                [b'/', b'/', rest @ ..] => {
+                    let end = rest.iter().position(|&c| c == b'\n' || c == b'\r');
+                    self.rest = &rest[end.unwrap_or(rest.len())..];
+                }
+                [b'/', b'*', rest @ ..] => {
+                    let Some(end) = rest.windows(2).position(|w| w == b"*/") else {
+                        return Err(Error::Eof);
+                    };
+                    self.rest = &rest[end + 2..];
+                }
+                [] => return Ok(false),
+                _ => return Ok(true),
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Result<u8> {
+        if self.skip_space()? {
+            Ok(self.rest[0])
+        } else {
+            Err(Error::Eof)
+        }
+    }
+
+    fn expect(&mut self, c: u8, what: &'static str) -> Result {
+        if self.peek()? != c {
+            return Err(Error::Invalid(what));
+        }
+        self.rest = &self.rest[1..];
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek()? {
+            b'{' => {
+                self.rest = &self.rest[1..];
+                let mut dict = Dictionary::new();
+                while self.peek()? != b'}' {
+                    let key = self.string()?;
+                    self.entry(&mut dict, key)?;
+                }
+                self.rest = &self.rest[1..];
+                Ok(Value::Dictionary(dict))
+            }
+            b'(' => {
+                self.rest = &self.rest[1..];
+                let mut arr = vec![];
+                while self.peek()? != b')' {
+                    arr.push(self.value()?);
+                    if self.peek()? != b',' {
+                        break;
+                    }
+                    self.rest = &self.rest[1..];
+                }
+                self.expect(b')', "array separator")?;
+                Ok(Value::Array(arr))
+            }
+            b'<' => {
+                self.rest = &self.rest[1..];
+                let mut data = vec![];
+                let mut high = None;
+                loop {
+                    let Some((&c, rest)) = self.rest.split_first() else {
+                        return Err(Error::Eof);
+                    };
+                    self.rest = rest;
+                    let nibble = match c {
+                        b'>' => break,
+                        c if c.is_ascii_whitespace() => continue,
+                        c => (c as char).to_digit(16).ok_or(Error::Invalid("data"))? as u8,
+                    };
+                    match high.take() {
+                        Some(h) => data.push(h << 4 | nibble),
+                        None => high = Some(nibble),
+                    }
+                }
+                if high.is_some() {
+                    return Err(Error::Invalid("data"));
+                }
+                Ok(Value::Data(data))
+            }
+            _ => self.string().map(Value::String),
+        }
+    }
+
+    /// `= value;` after a dictionary key, `key;` alone means `key = key;`
+    fn entry(&mut self, dict: &mut Dictionary, key: String) -> Result {
+        let value = match self.peek()? {
+            b';' => Value::String(key.clone()),
+            b'=' => {
+                self.rest = &self.rest[1..];
+                self.value()?
+            }
+            _ => return Err(Error::Invalid("dictionary separator")),
+        };
+        self.expect(b';', "dictionary separator")?;
+        dict.insert(key, value);
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let c = self.peek()?;
+        if c == b'"' || c == b'\'' {
+            self.rest = &self.rest[1..];
+            return self.quoted(c);
+        }
+        let len = self.rest.iter().take_while(|&&c| is_unquoted(c)).count();
+        if len == 0 {
+            return Err(Error::Invalid("string"));
+        }
+        let (str, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        // only ascii is allowed unquoted
+        Ok(String::from_utf8(str.to_vec()).unwrap())
+    }
+
+    fn quoted(&mut self, quote: u8) -> Result<String> {
+        let mut res = Vec::new();
+        let mut utf16 = Vec::new();
+        loop {
+            let Some((&c, rest)) = self.rest.split_first() else {
+                return Err(Error::Eof);
+            };
+            self.rest = rest;
+            if c != b'\\' {
+                if !utf16.is_empty() {
+                    flush_utf16(&mut res, &mut utf16)?;
+                }
+                if c == quote {
+                    break;
+                }
+                res.push(c);
+                continue;
+            }
+            let Some((&c, rest)) = self.rest.split_first() else {
+                return Err(Error::Eof);
+            };
+            self.rest = rest;
+            if c == b'U' {
+                let hex_digits = self
+                    .rest
+                    .iter()
+                    .take(4)
+                    .take_while(|c| c.is_ascii_hexdigit());
+                let len = hex_digits.count();
+                let hex = std::str::from_utf8(&self.rest[..len]).unwrap();
+                utf16.push(u16::from_str_radix(hex, 16).map_err(|_| Error::Invalid("escape"))?);
+                self.rest = &self.rest[len..];
+                continue;
+            }
+            if !utf16.is_empty() {
+                flush_utf16(&mut res, &mut utf16)?;
+            }
+            let ch = match c {
+                b'a' => '\x07',
+                b'b' => '\x08',
+                b'f' => '\x0c',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'v' => '\x0b',
+                b'0'..=b'7' => {
+                    let mut n = (c - b'0') as u32;
+                    for _ in 0..2 {
+                        match self.rest.first() {
+                            Some(&d @ b'0'..=b'7') => {
+                                n = n * 8 + (d - b'0') as u32;
+                                self.rest = &self.rest[1..];
+                            }
+                            _ => break,
+                        }
+                    }
+                    char::from_u32(n).ok_or(Error::Invalid("escape"))?
+                }
+                c => {
+                    res.push(c);
+                    continue;
+                }
+            };
+            res.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+        String::from_utf8(res).map_err(|_| Error::Invalid("utf-8"))
+    }
+}
+
+fn flush_utf16(res: &mut Vec<u8>, utf16: &mut Vec<u16>) -> Result {
+    let str = String::from_utf16(utf16).map_err(|_| Error::Invalid("escape"))?;
+    res.extend_from_slice(str.as_bytes());
+    utf16.clear();
+    Ok(())
+}
+
+fn is_unquoted(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, b'_' | b'$' | b'/' | b':' | b'.' | b'-')
+}
+
+pub(super) fn write(value: &Value) -> Result<Vec<u8>> {
+    let mut res = String::new();
+    write_value(&mut res, value, 0)?;
+    res.push('\n');
+    Ok(res.into_bytes())
+}
+
+fn indent(buf: &mut String, level: usize) {
+    for _ in 0..level {
+        buf.push('\t');
+    }
+}
+
+fn write_string(buf: &mut String, str: &str) {
+    if !str.is_empty() && str.bytes().all(is_unquoted) {
+        buf.push_str(str);
+        return;
+    }
+    buf.push('"');
+    for ch in str.chars() {
+        match ch {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            ch if ch.is_control() && (ch as u32) < 0x80 => {
+                _ = write!(buf, "\\U{:04x}", ch as u32);
+            }
+            ch => buf.push(ch),
+        }
+    }
+    buf.push('"');
+}
+
+fn write_value(buf: &mut String, value: &Value, level: usize) -> Result {
+    match value {
+        Value::String(s) => write_string(buf, s),
+        Value::Data(data) => {
+            buf.push('<');
+            for (i, chunk) in data.chunks(4).enumerate() {
+                if i > 0 {
+                    buf.push(' ');
+                }
+                for b in chunk {
+                    _ = write!(buf, "{b:02x}");
+                }
+            }
+            buf.push('>');
+        }
+        Value::Array(arr) => {
+            buf.push_str("(\n");
+            for v in arr {
+                indent(buf, level + 1);
+                write_value(buf, v, level + 1)?;
+                buf.push_str(",\n");
+            }
+            indent(buf, level);
+            buf.push(')');
+        }
+        Value::Dictionary(dict) => {
+            buf.push_str("{\n");
+            for (k, v) in dict.iter() {
+                indent(buf, level + 1);
+                write_string(buf, k);
+                buf.push_str(" = ");
+                write_value(buf, v, level + 1)?;
+                buf.push_str(";\n");
+            }
+            indent(buf, level);
+            buf.push('}');
+        }
+        Value::Bool(_) => return Err(Error::Unsupported("bool in OpenStep plist")),
+        Value::Date(_) => return Err(Error::Unsupported("date in OpenStep plist")),
+        Value::Real(_) => return Err(Error::Unsupported("real in OpenStep plist")),
+        Value::Integer(_) => return Err(Error::Unsupported("integer in OpenStep plist")),
+        Value::Uid(_) => return Err(Error::Unsupported("uid in OpenStep plist")),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Xcode iOS app project, with a data value Xcode itself doesn't write
+    #[test]
+    fn pbxproj() {
+        let project = read(include_bytes!("testdata/project.pbxproj")).unwrap();
+        assert_eq!(project["archiveVersion"].as_str(), Some("1"));
+        assert_eq!(project["objectVersion"].as_str(), Some("60"));
+        assert_eq!(project["classes"], Value::Dictionary(Dictionary::new()));
+        assert_eq!(
+            project["rootObject"].as_str(),
+            Some("4C1F0A252B9E6D5200C4E8A1")
+        );
+
+        let objects = project["objects"].as_dictionary().unwrap();
+        let file = &objects["4C1F0A372B9E6D5300C4E8A1"];
+        assert_eq!(file["isa"].as_str(), Some("PBXFileReference"));
+        assert_eq!(file["path"].as_str(), Some("Preview Assets.xcassets"));
+        assert_eq!(file["sourceTree"].as_str(), Some("<group>"));
+        let embed = &objects["4C1F0A412B9E6E1000C4E8A1"];
+        assert_eq!(
+            embed["settings"]["ATTRIBUTES"],
+            Value::Array(vec!["CodeSignOnCopy".into(), "RemoveHeadersOnCopy".into()])
+        );
+        let target = &objects["4C1F0A2C2B9E6D5200C4E8A1"];
+        assert_eq!(target["buildRules"], Value::Array(vec![]));
+        assert_eq!(target["buildPhases"].as_array().map(Vec::len), Some(4));
+
+        let attributes = &objects["4C1F0A252B9E6D5200C4E8A1"]["attributes"];
+        assert_eq!(attributes["ORGANIZATIONNAME"].as_str(), Some("Cidre & Co"));
+        assert_eq!(
+            attributes["cidre.iconDigest"].as_data().map(<[u8]>::len),
+            Some(16)
+        );
+
+        let settings = &objects["4C1F0A3D2B9E6D5300C4E8A1"]["buildSettings"];
+        assert_eq!(
+            settings["DEVELOPMENT_ASSET_PATHS"].as_str(),
+            Some("\"Demo/Preview Content\"")
+        );
+        assert_eq!(
+            settings["INFOPLIST_KEY_NSCameraUsageDescription"].as_str(),
+            Some("Testing \"Camera\"\n\tand microphone")
+        );
+        assert_eq!(
+            settings["LD_RUNPATH_SEARCH_PATHS"][1].as_str(),
+            Some("@executable_path/Frameworks")
+        );
+
+        let data = write(&project).unwrap();
+        assert_eq!(read(&data).unwrap(), project);
+    }
+
+    #[test]
+    fn strings_file() {
+        let strings = read(
+            "/* Title */\n\"title\" = \"Caf\\U00e9 \\\"\\U00e9\\\"\";\nok;\n\
+             'single' = \"tab\\tnew\\nline\\101\\\\\";\n// done\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(strings["title"].as_str(), Some("Café \"é\""));
+        assert_eq!(strings["ok"].as_str(), Some("ok"));
+        assert_eq!(strings["single"].as_str(), Some("tab\tnew\nlineA\\"));
+
+        assert_eq!(read(b"").unwrap(), Value::Dictionary(Dictionary::new()));
+        assert_eq!(
+            read(b"\"\\UD83D\\UDE00\"").unwrap(),
+            Value::String("😀".into())
+        );
+    }
+
+    #[test]
+    fn output() {
+        let value: Dictionary = [
+            ("name", Value::from("a b")),
+            ("path", "x/y.z".into()),
+            ("empty", "".into()),
+            ("data", vec![0u8, 1, 2, 3, 0xff].into()),
+            ("list", Value::Array(vec!["q\"\n".into()])),
+        ]
+        .into_iter()
+        .collect();
+        let value = Value::Dictionary(value);
+        let text = write(&value).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&text).unwrap(),
+            "{\n\tname = \"a b\";\n\tpath = x/y.z;\n\tempty = \"\";\n\tdata = <00010203 ff>;\n\
+             \tlist = (\n\t\t\"q\\\"\\n\",\n\t);\n}\n"
+        );
+        assert_eq!(read(&text).unwrap(), value);
+
+        assert_eq!(
+            write(&Value::Bool(true)),
+            Err(Error::Unsupported("bool in OpenStep plist"))
+        );
+        for bad in [&b"{a = b"[..], b"(a b)", b"<0g>", b"{a b;}", b"a = ;"] {
+            assert!(read(bad).is_err(), "{}", String::from_utf8_lossy(bad));
+        }
+    }
+}
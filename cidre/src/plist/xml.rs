@@ -0,0 +1,506 @@
+//! XML property lists, `kCFPropertyListXMLFormat_v1_0`
+
+use std::fmt::Write;
+
+use super::{Date, Dictionary, Error, Integer, Result, Uid, Value};
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+"#;
+
+/// Key of the single entry dictionary XML uses for UIDs
+const UID_KEY: &str = "CF$UID";
+
+pub(super) fn read(data: &[u8]) -> Result<Value> {
+    let xml = std::str::from_utf8(data).map_err(|_| Error::Invalid("utf-8"))?;
+    let xml = xml.strip_prefix('\u{feff}').unwrap_or(xml);
+    let mut reader = Reader { rest: xml };
+    let root = match reader.tag()? {
+        Tag::Open("plist") => {
+            let root = reader.value()?;
+            match reader.tag()? {
+                Tag::Close("plist") => root,
+                _ => return Err(Error::Invalid("plist end tag")),
+            }
+        }
+        tag => reader.value_of(tag)?,
+    };
+    if !reader.at_end()? {
+        return Err(Error::Invalid("data after plist"));
+    }
+    Ok(root)
+}
+
+#[derive(Debug)]
+enum Tag<'a> {
+    Open(&'a str),
+    Close(&'a str),
+    Empty(&'a str),
+}
+
+struct Reader<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Reader<'a> {
+    fn skip_to(&mut self, end: &str) -> Result<&'a str> {
+        let Some(pos) = self.rest.find(end) else {
+            return Err(Error::Eof);
+        };
+        let res = &self.rest[..pos];
+        self.rest = &self.rest[pos + end.len()..];
+        Ok(res)
+    }
+
+    /// Skips whitespace, prolog, doctype and comments
+    fn skip_misc(&mut self) -> Result {
+        loop {
+            self.rest = self.rest.trim_start();
+            if self.rest.starts_with("<?") {
+                self.skip_to("?>")?;
+            } else if self.rest.starts_with("<!--") {
+                self.skip_to("-->")?;
+            } else if self.rest.starts_with("<!") {
+                self.skip_to(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn at_end(&mut self) -> Result<bool> {
+        self.skip_misc()?;
+        Ok(self.rest.is_empty())
+    }
+
+    fn tag(&mut self) -> Result<Tag<'a>> {
+        self.skip_misc()?;
+        let Some(rest) = self.rest.strip_prefix('<') else {
+            return Err(if self.rest.is_empty() {
+                Error::Eof
+            } else {
+                Error::Invalid("text outside of element")
+            });
+        };
+        self.rest = rest;
+        let inner = self.skip_to(">")?;
+        let name = |s: &'a str| s.split_whitespace().next().unwrap_or_default();
+        Ok(if let Some(inner) = inner.strip_prefix('/') {
+            Tag::Close(name(inner))
+        } else if let Some(inner) = inner.strip_suffix('/') {
+            Tag::Empty(name(inner))
+        } else {
+            Tag::Open(name(inner))
+        })
+    }
+
+    /// Text up to `</name>` with entities and CDATA sections resolved
+    fn text(&mut self, name: &str) -> Result<String> {
+        let mut res = String::new();
+        loop {
+            let Some(pos) = self.rest.find('<') else {
+                return Err(Error::Eof);
+            };
+            res.push_str(&unescape(&self.rest[..pos])?);
+            self.rest = &self.rest[pos..];
+            if let Some(rest) = self.rest.strip_prefix("<![CDATA[") {
+                self.rest = rest;
+                res.push_str(self.skip_to("]]>")?);
+            } else if let Some(rest) = self.rest.strip_prefix("<!--") {
+                self.rest = rest;
+                self.skip_to("-->")?;
+            } else {
+                break;
+            }
+        }
+        match self.tag()? {
+            Tag::Close(close) if close == name => Ok(res),
+            _ => Err(Error::Invalid("element text")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let tag = self.tag()?;
+        self.value_of(tag)
+    }
+
+    fn value_of(&mut self, tag: Tag<'a>) -> Result<Value> {
+        let res = match tag {
+            Tag::Empty("dict") => Value::Dictionary(Dictionary::new()),
+            Tag::Empty("array") => Value::Array(vec![]),
+            Tag::Empty("string") => Value::String(String::new()),
+            Tag::Empty("data") => Value::Data(vec![]),
+            Tag::Empty("true") => Value::Bool(true),
+            Tag::Empty("false") => Value::Bool(false),
+            Tag::Open("dict") => {
+                let mut dict = Dictionary::new();
+                loop {
+                    match self.tag()? {
+                        Tag::Close("dict") => break,
+                        Tag::Open("key") => {
+                            let key = self.text("key")?;
+                            let value = self.value()?;
+                            dict.insert(key, value);
+                        }
+                        Tag::Empty("key") => {
+                            let value = self.value()?;
+                            dict.insert("", value);
+                        }
+                        _ => return Err(Error::Invalid("dictionary key")),
+                    }
+                }
+                // CoreFoundation writes UIDs as `{CF$UID = n}`
+                match dict.get(UID_KEY) {
+                    Some(Value::Integer(i)) if dict.len() == 1 => match i.as_u64() {
+                        Some(uid) => Value::Uid(Uid(uid)),
+                        None => Value::Dictionary(dict),
+                    },
+                    _ => Value::Dictionary(dict),
+                }
+            }
+            Tag::Open("array") => {
+                let mut arr = vec![];
+                loop {
+                    match self.tag()? {
+                        Tag::Close("array") => break,
+                        tag => arr.push(self.value_of(tag)?),
+                    }
+                }
+                Value::Array(arr)
+            }
+            Tag::Open("string") => Value::String(self.text("string")?),
+            Tag::Open("data") => Value::Data(base64_decode(&self.text("data")?)?),
+            Tag::Open("date") => {
+                let text = self.text("date")?;
+                Value::Date(Date::parse(text.trim()).ok_or(Error::Invalid("date"))?)
+            }
+            Tag::Open("integer") => Value::Integer(parse_int(self.text("integer")?.trim())?),
+            Tag::Open("real") => Value::Real(parse_real(self.text("real")?.trim())?),
+            Tag::Open(name @ ("true" | "false")) => {
+                self.text(name)?;
+                Value::Bool(name == "true")
+            }
+            _ => return Err(Error::Invalid("element")),
+        };
+        Ok(res)
+    }
+}
+
+fn unescape(str: &str) -> Result<String> {
+    let mut res = String::with_capacity(str.len());
+    let mut rest = str;
+    while let Some(pos) = rest.find('&') {
+        res.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        let Some(end) = rest.find(';') else {
+            return Err(Error::Invalid("entity"));
+        };
+        let ch = match &rest[..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity => match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+            }
+            .and_then(char::from_u32),
+        };
+        res.push(ch.ok_or(Error::Invalid("entity"))?);
+        rest = &rest[end + 1..];
+    }
+    res.push_str(rest);
+    Ok(res)
+}
+
+/// Decimal or `0x` hex with an optional sign
+fn parse_int(text: &str) -> Result<Integer> {
+    let (neg, digits) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let value = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse::<u64>(),
+    }
+    .map_err(|_| Error::Invalid("integer"))? as i128;
+    Integer::try_from(if neg { -value } else { value })
+}
+
+fn parse_real(text: &str) -> Result<f64> {
+    match text.to_ascii_lowercase().as_str() {
+        "nan" => Ok(f64::NAN),
+        "inf" | "+inf" | "infinity" | "+infinity" => Ok(f64::INFINITY),
+        "-inf" | "-infinity" => Ok(f64::NEG_INFINITY),
+        _ => text.parse().map_err(|_| Error::Invalid("real")),
+    }
+}
+
+/// `%.17g` the way CoreFoundation prints reals
+fn format_real(v: f64) -> String {
+    if v.is_nan() {
+        return "nan".into();
+    }
+    if v.is_infinite() {
+        return if v > 0.0 { "+infinity" } else { "-infinity" }.into();
+    }
+    if v == 0.0 {
+        return if v.is_sign_negative() { "-0" } else { "0" }.into();
+    }
+    let sci = format!("{v:.16e}");
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    if (-4..17).contains(&exp) {
+        let fixed = format!("{v:.*}", (16 - exp) as usize);
+        trim_zeros(&fixed).to_string()
+    } else {
+        let sign = if exp < 0 { '-' } else { '+' };
+        format!("{}e{sign}{:02}", trim_zeros(mantissa), exp.abs())
+    }
+}
+
+fn trim_zeros(num: &str) -> &str {
+    if num.contains('.') {
+        num.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        num
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut res = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+/// Ignores whitespace, padding is optional
+fn base64_decode(text: &str) -> Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(text.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in text.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            c if c.is_ascii_whitespace() => continue,
+            _ => return Err(Error::Invalid("base64 data")),
+        };
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            res.push((acc >> bits) as u8);
+        }
+    }
+    Ok(res)
+}
+
+pub(super) fn write(value: &Value) -> Result<Vec<u8>> {
+    let mut res = String::from(HEADER);
+    write_value(&mut res, value, 0)?;
+    res.push_str("</plist>\n");
+    Ok(res.into_bytes())
+}
+
+fn indent(buf: &mut String, level: usize) {
+    for _ in 0..level {
+        buf.push('\t');
+    }
+}
+
+fn escape(str: &str) -> String {
+    let mut res = String::with_capacity(str.len());
+    for ch in str.chars() {
+        match ch {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            ch => res.push(ch),
+        }
+    }
+    res
+}
+
+fn write_value(buf: &mut String, value: &Value, level: usize) -> Result {
+    indent(buf, level);
+    match value {
+        Value::Dictionary(dict) if dict.is_empty() => buf.push_str("<dict/>\n"),
+        Value::Dictionary(dict) => {
+            buf.push_str("<dict>\n");
+            for (key, value) in dict.iter() {
+                indent(buf, level + 1);
+                _ = writeln!(buf, "<key>{}</key>", escape(key));
+                write_value(buf, value, level + 1)?;
+            }
+            indent(buf, level);
+            buf.push_str("</dict>\n");
+        }
+        Value::Array(arr) if arr.is_empty() => buf.push_str("<array/>\n"),
+        Value::Array(arr) => {
+            buf.push_str("<array>\n");
+            for v in arr {
+                write_value(buf, v, level + 1)?;
+            }
+            indent(buf, level);
+            buf.push_str("</array>\n");
+        }
+        Value::Data(data) => {
+            buf.push_str("<data>\n");
+            // lines shrink with indentation, tabs count as 8 columns
+            let line = 76usize.saturating_sub(level * 8).max(16) / 4 * 3;
+            for chunk in data.chunks(line) {
+                indent(buf, level);
+                buf.push_str(&base64_encode(chunk));
+                buf.push('\n');
+            }
+            indent(buf, level);
+            buf.push_str("</data>\n");
+        }
+        Value::Uid(uid) => {
+            let dict: Dictionary = [(UID_KEY, uid.0)].into_iter().collect();
+            buf.truncate(buf.len() - level);
+            return write_value(buf, &Value::Dictionary(dict), level);
+        }
+        Value::String(s) => _ = writeln!(buf, "<string>{}</string>", escape(s)),
+        Value::Integer(i) => _ = writeln!(buf, "<integer>{i}</integer>"),
+        Value::Real(r) => _ = writeln!(buf, "<real>{}</real>", format_real(*r)),
+        Value::Bool(true) => buf.push_str("<true/>\n"),
+        Value::Bool(false) => buf.push_str("<false/>\n"),
+        Value::Date(d) => _ = writeln!(buf, "<date>{}</date>", d.to_xml_string()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Written by Python `plistlib`, as is `Info.bplist`
+    const INFO: &[u8] = include_bytes!("testdata/Info.plist");
+
+    #[test]
+    fn fixture() {
+        let info = read(INFO).unwrap();
+        assert_eq!(write(&info).unwrap(), INFO);
+        assert_eq!(info["CFBundleDisplayName"].as_str(), Some("Démo ✓"));
+        assert_eq!(
+            info["NSCameraUsageDescription"].as_str(),
+            Some("Testing \"Camera\" & <microphone>")
+        );
+        assert_eq!(info["UIDeviceFamily"][1].as_i64(), Some(2));
+        assert_eq!(info["IconData"].as_data().map(<[u8]>::len), Some(512));
+        assert_eq!(info["LSRequiresIPhoneOS"].as_bool(), Some(true));
+        assert_eq!(
+            info["BuildDate"].as_date().map(|d| d.to_xml_string()),
+            Some("2024-10-01T10:00:00Z".into())
+        );
+
+        let binary = include_bytes!("testdata/Info.bplist");
+        assert_eq!(super::super::binary::read(binary).unwrap(), info);
+    }
+
+    #[test]
+    fn lenient() {
+        let value = read(
+            b"<?xml version=\"1.0\"?>\n<!DOCTYPE plist>\n<plist version=\"1.0\"><dict>\
+              <!-- comment --><key>d</key><data>\n\tAAEC\n\tAw==\n</data>\
+              <key>e</key><string/><key>s</key><string>&#x41;&lt;<![CDATA[<&>]]>&#66;</string>\
+              <key>i</key><integer> -0x10 </integer><key>t</key><true></true>\
+              <key>u</key><dict><key>CF$UID</key><integer>7</integer></dict>\
+              </dict></plist>\n<!-- trailing -->\n",
+        )
+        .unwrap();
+        assert_eq!(value["d"].as_data(), Some(&[0u8, 1, 2, 3][..]));
+        assert_eq!(value["e"].as_str(), Some(""));
+        assert_eq!(value["s"].as_str(), Some("A<<&>B"));
+        assert_eq!(value["i"].as_i64(), Some(-16));
+        assert_eq!(value["t"].as_bool(), Some(true));
+        assert_eq!(value["u"].as_uid(), Some(Uid(7)));
+
+        // a bare value without the plist element
+        assert_eq!(read(b"<array/>").unwrap(), Value::Array(vec![]));
+
+        for bad in [
+            &b"<plist><dict><string>a</string></dict></plist>"[..],
+            b"<plist><array></plist>",
+            b"<plist><integer>1.5</integer></plist>",
+            b"<plist><date>yesterday</date></plist>",
+            b"<plist><string>&bogus;</string></plist>",
+            b"<plist><data>!!</data></plist>",
+            b"<plist><true/></plist><true/>",
+            b"<plist><unknown/></plist>",
+        ] {
+            assert!(read(bad).is_err(), "{}", String::from_utf8_lossy(bad));
+        }
+        assert_eq!(read(b"<plist><array>"), Err(Error::Eof));
+    }
+
+    #[test]
+    fn output() {
+        let value = Value::Array(vec![
+            Value::Real(1.0),
+            Value::Real(0.1),
+            Value::Real(-2.5e-7),
+            Value::Real(1e300),
+            Value::Real(f64::NEG_INFINITY),
+            Value::Integer(Integer::MAX),
+            Value::Uid(Uid(3)),
+            Value::Data(vec![]),
+        ]);
+        let xml = String::from_utf8(write(&value).unwrap()).unwrap();
+        assert_eq!(
+            &xml[HEADER.len()..],
+            "<array>
+	<real>1</real>
+	<real>0.10000000000000001</real>
+	<real>-2.4999999999999999e-07</real>
+	<real>1.0000000000000001e+300</real>
+	<real>-infinity</real>
+	<integer>18446744073709551615</integer>
+	<dict>
+		<key>CF$UID</key>
+		<integer>3</integer>
+	</dict>
+	<data>
+	</data>
+</array>
+</plist>
+"
+        );
+        assert_eq!(read(xml.as_bytes()).unwrap(), value);
+    }
+
+    #[test]
+    fn base64() {
+        for len in 0..10 {
+            let data: Vec<u8> = (0..len).map(|i: u8| i.wrapping_mul(37)).collect();
+            assert_eq!(base64_decode(&base64_encode(&data)).unwrap(), data);
+        }
+        assert_eq!(
+            base64_encode(b"any carnal pleas"),
+            "YW55IGNhcm5hbCBwbGVhcw=="
+        );
+    }
+}
@@ -220,7 +220,7 @@ unsafe extern "C-unwind" {
     fn cfsetospeed(termios: *mut Termios, val: BaudRate) -> os::Status;
 }
 
-#[cfg(test)]
+#[cfg(all(test, target_vendor = "apple"))]
 mod tests {
     use crate::sys::termios as t;
 
@@ -1,5 +1,16 @@
 use std::ptr::NonNull;
 
+#[cfg(any(feature = "vdsp-soft", not(target_vendor = "apple")))]
+pub mod soft;
+
+// Compared against Accelerate in tests
+#[cfg(all(test, target_vendor = "apple", not(feature = "vdsp-soft")))]
+mod soft;
+
+// Accelerate is not available or the portable implementation is requested.
+#[cfg(any(feature = "vdsp-soft", not(target_vendor = "apple")))]
+pub use soft::*;
+
 #[doc(alias = "vDSP_Length")]
 pub type Len = usize;
 
@@ -86,6 +97,7 @@ impl<'r, 'i> SplitComplex<'r, 'i, f32> {
 #[repr(transparent)]
 pub struct FftSetup<T>(std::ffi::c_void, std::marker::PhantomData<T>);
 
+#[allow(non_snake_case)]
 struct FftVt<T> {
     transform_io: unsafe extern "C-unwind" fn(
         __Setup: *mut FftSetup<T>,
@@ -109,6 +121,24 @@ struct FftVt<T> {
         __Log2N: Len,
         __Direction: FftDirection,
     ),
+    zop3: unsafe extern "C-unwind" fn(
+        __Setup: *mut FftSetup<T>,
+        __A: *const SplitComplex<T>,
+        __IA: Stride,
+        __C: *const SplitComplex<T>,
+        __IC: Stride,
+        __Log2N: Len,
+        __Direction: FftDirection,
+    ),
+    zop5: unsafe extern "C-unwind" fn(
+        __Setup: *mut FftSetup<T>,
+        __A: *const SplitComplex<T>,
+        __IA: Stride,
+        __C: *const SplitComplex<T>,
+        __IC: Stride,
+        __Log2N: Len,
+        __Direction: FftDirection,
+    ),
 
     destroy: unsafe extern "C-unwind" fn(*mut FftSetup<T>),
 }
@@ -119,6 +149,8 @@ impl FftVt<f32> {
             transform_io: _fft_zip_f32,
             transform: _fft_zipt_f32,
             zrip: _fft_zrip_f32,
+            zop3: _fft3_zop_f32,
+            zop5: _fft5_zop_f32,
             destroy: _destroy_fftsetup_f32,
         }
     }
@@ -130,6 +162,8 @@ impl FftVt<f64> {
             transform_io: _fft_zip_f64,
             transform: _fft_zipt_f64,
             zrip: _fft_zrip_f64,
+            zop3: _fft3_zop_f64,
+            zop5: _fft5_zop_f64,
             destroy: _destroy_fftsetup_f64,
         }
     }
@@ -138,9 +172,10 @@ impl FftVt<f64> {
 pub struct Fft<T>(NonNull<FftSetup<T>>, FftVt<T>);
 
 impl<T> Fft<T> {
+    /// Real transform of `2 * re_io.len()` values packed as even/odd split complex.
     #[inline]
     pub fn zr_io(&mut self, re_io: &mut [T], im_io: &mut [T], direction: FftDirection) {
-        let log2n = (re_io.len() as f64).log2().ceil();
+        let log2n = ((re_io.len() * 2) as f64).log2().ceil();
         let mut split = SplitComplex::new_mut(re_io, im_io);
         unsafe { (self.1.zrip)(self.0.as_mut(), &mut split, 1, log2n as _, direction) }
     }
@@ -187,6 +222,52 @@ impl<T> Fft<T> {
     pub fn inverse(&mut self, re: &mut [T], im: &mut [T], buf_re: &mut [T], buf_im: &mut [T]) {
         self.transform(re, im, buf_re, buf_im, FftDirection::Inverse)
     }
+
+    /// Out-of-place transform of `3 * 2^n` elements.
+    ///
+    /// Setup must be created with [`FftRadix::_3`].
+    #[inline]
+    pub fn transform3(
+        &mut self,
+        re: &[T],
+        im: &[T],
+        out_re: &mut [T],
+        out_im: &mut [T],
+        direction: FftDirection,
+    ) {
+        let log2n = Self::log2n_radix(re.len(), 3);
+        assert_eq!(re.len(), out_re.len());
+        let a = SplitComplex::new(re, im);
+        let c = SplitComplex::new_mut(out_re, out_im);
+        unsafe { (self.1.zop3)(self.0.as_mut(), &a, 1, &c, 1, log2n, direction) }
+    }
+
+    /// Out-of-place transform of `5 * 2^n` elements.
+    ///
+    /// Setup must be created with [`FftRadix::_5`].
+    #[inline]
+    pub fn transform5(
+        &mut self,
+        re: &[T],
+        im: &[T],
+        out_re: &mut [T],
+        out_im: &mut [T],
+        direction: FftDirection,
+    ) {
+        let log2n = Self::log2n_radix(re.len(), 5);
+        assert_eq!(re.len(), out_re.len());
+        let a = SplitComplex::new(re, im);
+        let c = SplitComplex::new_mut(out_re, out_im);
+        unsafe { (self.1.zop5)(self.0.as_mut(), &a, 1, &c, 1, log2n, direction) }
+    }
+
+    fn log2n_radix(n: usize, radix: usize) -> Len {
+        assert!(
+            n % radix == 0 && (n / radix).is_power_of_two(),
+            "length {n} is not {radix} * 2^n"
+        );
+        (n / radix).trailing_zeros() as Len
+    }
 }
 
 impl Fft<f32> {
@@ -1269,6 +1350,7 @@ pub fn u16_f32(a: &[u16], c: &mut [f32]) {
     unsafe { _u16_f32(a.as_ptr(), 1, c.as_mut_ptr(), 1, n) };
 }
 
+#[cfg(all(target_vendor = "apple", not(feature = "vdsp-soft")))]
 #[link(name = "Accelerate", kind = "framework")]
 unsafe extern "C-unwind" {
     #[link_name = "vDSP_vadd"]
@@ -1961,6 +2043,54 @@ unsafe extern "C-unwind" {
         __Direction: FftDirection,
     );
 
+    #[doc(alias = "vDSP_fft3_zop")]
+    #[link_name = "vDSP_fft3_zop"]
+    pub fn _fft3_zop_f32(
+        __Setup: *mut FftSetup<f32>,
+        __A: *const SplitComplex<f32>,
+        __IA: Stride,
+        __C: *const SplitComplex<f32>,
+        __IC: Stride,
+        __Log2N: Len,
+        __Direction: FftDirection,
+    );
+
+    #[doc(alias = "vDSP_fft5_zop")]
+    #[link_name = "vDSP_fft5_zop"]
+    pub fn _fft5_zop_f32(
+        __Setup: *mut FftSetup<f32>,
+        __A: *const SplitComplex<f32>,
+        __IA: Stride,
+        __C: *const SplitComplex<f32>,
+        __IC: Stride,
+        __Log2N: Len,
+        __Direction: FftDirection,
+    );
+
+    #[doc(alias = "vDSP_fft3_zopD")]
+    #[link_name = "vDSP_fft3_zopD"]
+    pub fn _fft3_zop_f64(
+        __Setup: *mut FftSetup<f64>,
+        __A: *const SplitComplex<f64>,
+        __IA: Stride,
+        __C: *const SplitComplex<f64>,
+        __IC: Stride,
+        __Log2N: Len,
+        __Direction: FftDirection,
+    );
+
+    #[doc(alias = "vDSP_fft5_zopD")]
+    #[link_name = "vDSP_fft5_zopD"]
+    pub fn _fft5_zop_f64(
+        __Setup: *mut FftSetup<f64>,
+        __A: *const SplitComplex<f64>,
+        __IA: Stride,
+        __C: *const SplitComplex<f64>,
+        __IC: Stride,
+        __Log2N: Len,
+        __Direction: FftDirection,
+    );
+
     /// Complex-split accumulating autospectrum
     #[doc(alias = "vDSP_zaspec")]
     #[link_name = "vDSP_zaspec"]
@@ -2172,6 +2302,22 @@ mod tests {
         vdsp::zaspec_f32(&split, &mut spec);
     }
 
+    /// `zr_io` transforms all `2 * re_io.len()` packed real values
+    #[test]
+    fn fft_zr_io_len() {
+        let x = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let mut re = [0.0f32; 4];
+        let mut im = [0.0f32; 4];
+        vdsp::actoz_f32(&x, &mut re, &mut im);
+
+        let mut fft = vdsp::Fft::new_f32(3, vdsp::FftRadix::_2).unwrap();
+        fft.zr_io(&mut re, &mut im, vdsp::FftDirection::Forward);
+
+        // DC and Nyquist of all 8 values, scaled by 2
+        assert_eq!(re[0], 72.0);
+        assert_eq!(im[0], -8.0);
+    }
+
     #[test]
     fn ztoc() {
         let re = [1.0, 2.0, 3.0];
@@ -0,0 +1,1079 @@
+//! Pure Rust implementation of the vDSP functions bound in [`crate::vdsp`].
+//!
+//! Used instead of Accelerate off Apple platforms and with the `vdsp-soft`
+//! feature. Functions keep vDSP signatures, strides (including negative and
+//! zero ones) and in-place semantics, so the safe wrappers work unchanged.
+//!
+//! Safety of every function here is the one of its vDSP counterpart: pointers
+//! must be valid for `n` elements at the given strides.
+#![allow(clippy::missing_safety_doc)]
+
+use std::{f64::consts::TAU, ptr::NonNull};
+
+use super::{Complex, FftDirection, FftRadix, FftSetup, Len, SplitComplex, Stride};
+
+#[inline]
+unsafe fn get<T: Copy>(p: *const T, stride: Stride, i: Len) -> T {
+    unsafe { *p.offset(i as Stride * stride) }
+}
+
+#[inline]
+unsafe fn set<T>(p: *mut T, stride: Stride, i: Len, val: T) {
+    unsafe { p.offset(i as Stride * stride).write(val) }
+}
+
+/// C[n] = f(A[n], B[n])
+macro_rules! vv_v {
+    ($($name:ident: $t:ty => |$a:ident, $b:ident| $e:expr;)*) => {$(
+        #[inline]
+        pub unsafe extern "C-unwind" fn $name(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            for i in 0..n {
+                unsafe {
+                    let $a = get(a, ia, i);
+                    let $b = get(b, ib, i);
+                    set(c, ic, i, $e);
+                }
+            }
+        }
+    )*};
+}
+
+/// C[n] = f(A[n])
+macro_rules! v_v {
+    ($($name:ident: $ta:ty => $tc:ty, |$a:ident| $e:expr;)*) => {$(
+        #[inline]
+        pub unsafe extern "C-unwind" fn $name(
+            a: *const $ta,
+            ia: Stride,
+            c: *mut $tc,
+            ic: Stride,
+            n: Len,
+        ) {
+            for i in 0..n {
+                unsafe {
+                    let $a = get(a, ia, i);
+                    set(c, ic, i, $e);
+                }
+            }
+        }
+    )*};
+}
+
+/// C[n] = f(A[n], *B)
+macro_rules! vs_v {
+    ($($name:ident: $t:ty => |$a:ident, $b:ident| $e:expr;)*) => {$(
+        #[inline]
+        pub unsafe extern "C-unwind" fn $name(
+            a: *const $t,
+            ia: Stride,
+            b: &$t,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            let $b = *b;
+            for i in 0..n {
+                unsafe {
+                    let $a = get(a, ia, i);
+                    set(c, ic, i, $e);
+                }
+            }
+        }
+    )*};
+}
+
+/// *C = finish(fold(init, A[n]))
+macro_rules! v_s {
+    ($($name:ident: $t:ty => $init:expr, |$acc:ident, $a:ident| $fold:expr, |$res:ident, $n:ident| $finish:expr;)*) => {$(
+        #[inline]
+        pub unsafe extern "C-unwind" fn $name(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            let mut $acc: $t = $init;
+            for i in 0..n {
+                let $a = unsafe { get(a, ia, i) };
+                $acc = $fold;
+            }
+            let $res = $acc;
+            let $n = n as $t;
+            unsafe { c.write($finish) }
+        }
+    )*};
+}
+
+/// *C = sum(f(A[n], B[n]))
+macro_rules! vv_s {
+    ($($name:ident: $t:ty => |$a:ident, $b:ident| $e:expr;)*) => {$(
+        #[inline]
+        pub unsafe extern "C-unwind" fn $name(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            n: Len,
+        ) {
+            let mut sum: $t = 0.0;
+            for i in 0..n {
+                unsafe {
+                    let $a = get(a, ia, i);
+                    let $b = get(b, ib, i);
+                    sum += $e;
+                }
+            }
+            unsafe { c.write(sum) }
+        }
+    )*};
+}
+
+/// D[n] = f(A[n], B[n], C[n])
+macro_rules! vvv_v {
+    ($($name:ident: $t:ty => |$a:ident, $b:ident, $c:ident| $e:expr;)*) => {$(
+        #[inline]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C-unwind" fn $name(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *const $t,
+            ic: Stride,
+            d: *mut $t,
+            id: Stride,
+            n: Len,
+        ) {
+            for i in 0..n {
+                unsafe {
+                    let $a = get(a, ia, i);
+                    let $b = get(b, ib, i);
+                    let $c = get(c, ic, i);
+                    set(d, id, i, $e);
+                }
+            }
+        }
+    )*};
+}
+
+vv_v! {
+    _add_f32: f32 => |a, b| a + b;
+    _add_f64: f64 => |a, b| a + b;
+    _add_i32: i32 => |a, b| a.wrapping_add(b);
+    _sub_f32: f32 => |b, a| a - b;
+    _sub_f64: f64 => |b, a| a - b;
+    _sub_i32: i32 => |b, a| a.wrapping_sub(b);
+    _mul_f32: f32 => |a, b| a * b;
+    _mul_f64: f64 => |a, b| a * b;
+    _div_f32: f32 => |b, a| a / b;
+    _div_f64: f64 => |b, a| a / b;
+    _div_i32: i32 => |b, a| a.wrapping_div(b);
+    _eqv_i32: i32 => |a, b| !(a ^ b);
+}
+
+v_v! {
+    _sq_f32: f32 => f32, |a| a * a;
+    _sq_f64: f64 => f64, |a| a * a;
+    _ssq_f32: f32 => f32, |a| a * a.abs();
+    _ssq_f64: f64 => f64, |a| a * a.abs();
+    _abs_f32: f32 => f32, |a| a.abs();
+    _abs_f64: f64 => f64, |a| a.abs();
+    _abs_i32: i32 => i32, |a| a.wrapping_abs();
+    _neg_f32: f32 => f32, |a| -a;
+    _neg_f64: f64 => f64, |a| -a;
+    _f64_f32: f64 => f32, |a| a as f32;
+    _f32_f64: f32 => f64, |a| a as f64;
+    _i16_f32: i16 => f32, |a| a as f32;
+    _u16_f32: u16 => f32, |a| a as f32;
+}
+
+vs_v! {
+    _smul_f32: f32 => |a, b| a * b;
+    _smul_f64: f64 => |a, b| a * b;
+    _sadd_f32: f32 => |a, b| a + b;
+    _sadd_f64: f64 => |a, b| a + b;
+    _sadd_i32: i32 => |a, b| a.wrapping_add(b);
+}
+
+v_s! {
+    _mean_f32: f32 => 0.0, |acc, a| acc + a, |sum, n| sum / n;
+    _mean_f64: f64 => 0.0, |acc, a| acc + a, |sum, n| sum / n;
+    _meansq_f32: f32 => 0.0, |acc, a| acc + a * a, |sum, n| sum / n;
+    _meansq_f64: f64 => 0.0, |acc, a| acc + a * a, |sum, n| sum / n;
+    _se_f32: f32 => 0.0, |acc, a| acc + a, |sum, _n| sum;
+    _se_f64: f64 => 0.0, |acc, a| acc + a, |sum, _n| sum;
+    _semg_f32: f32 => 0.0, |acc, a| acc + a.abs(), |sum, _n| sum;
+    _semg_f64: f64 => 0.0, |acc, a| acc + a.abs(), |sum, _n| sum;
+    _sesq_f32: f32 => 0.0, |acc, a| acc + a * a, |sum, _n| sum;
+    _sesq_f64: f64 => 0.0, |acc, a| acc + a * a, |sum, _n| sum;
+    _svs_f32: f32 => 0.0, |acc, a| acc + a * a.abs(), |sum, _n| sum;
+    _svs_f64: f64 => 0.0, |acc, a| acc + a * a.abs(), |sum, _n| sum;
+    _maxmg_f32: f32 => 0.0, |acc, a| if a.abs() > acc { a.abs() } else { acc }, |m, _n| m;
+    _maxmg_f64: f64 => 0.0, |acc, a| if a.abs() > acc { a.abs() } else { acc }, |m, _n| m;
+    _minmg_f32: f32 => f32::INFINITY, |acc, a| if a.abs() < acc { a.abs() } else { acc }, |m, _n| m;
+    _minmg_f64: f64 => f64::INFINITY, |acc, a| if a.abs() < acc { a.abs() } else { acc }, |m, _n| m;
+    _max_f32: f32 => f32::NEG_INFINITY, |acc, a| if a > acc { a } else { acc }, |m, _n| m;
+    _max_f64: f64 => f64::NEG_INFINITY, |acc, a| if a > acc { a } else { acc }, |m, _n| m;
+    _min_f32: f32 => f32::INFINITY, |acc, a| if a < acc { a } else { acc }, |m, _n| m;
+    _min_f64: f64 => f64::INFINITY, |acc, a| if a < acc { a } else { acc }, |m, _n| m;
+}
+
+vv_s! {
+    _distance_sq_f32: f32 => |a, b| (a - b) * (a - b);
+    _distance_sq_f64: f64 => |a, b| (a - b) * (a - b);
+    _dotpr_f32: f32 => |a, b| a * b;
+    _dotpr_f64: f64 => |a, b| a * b;
+}
+
+vvv_v! {
+    _am_f32: f32 => |a, b, c| (a + b) * c;
+    _am_f64: f64 => |a, b, c| (a + b) * c;
+    _ma_f32: f32 => |a, b, c| a * b + c;
+    _ma_f64: f64 => |a, b, c| a * b + c;
+}
+
+macro_rules! float {
+    ($t:ty, $fill:ident, $clr:ident, $gen:ident, $ramp:ident, $rampmul:ident, $rampmul2:ident, $tmerg:ident, $ctoz:ident, $ztoc:ident, $zaspec:ident, $dbcon:ident, $clip:ident) => {
+        #[inline]
+        pub unsafe extern "C-unwind" fn $fill(a: &$t, c: *mut $t, ic: Stride, n: Len) {
+            for i in 0..n {
+                unsafe { set(c, ic, i, *a) }
+            }
+        }
+
+        #[inline]
+        pub unsafe extern "C-unwind" fn $clr(c: *mut $t, ic: Stride, n: Len) {
+            unsafe { $fill(&0.0, c, ic, n) }
+        }
+
+        /// C[n] = A + (B - A) * n / (N - 1)
+        #[inline]
+        pub unsafe extern "C-unwind" fn $gen(a: &$t, b: &$t, c: *mut $t, ic: Stride, n: Len) {
+            let step = if n > 1 { (*b - *a) / (n - 1) as $t } else { 0.0 };
+            for i in 0..n {
+                unsafe { set(c, ic, i, *a + step * i as $t) }
+            }
+        }
+
+        /// C[n] = A + B * n
+        #[inline]
+        pub unsafe extern "C-unwind" fn $ramp(a: &$t, b: &$t, c: *mut $t, ic: Stride, n: Len) {
+            for i in 0..n {
+                unsafe { set(c, ic, i, *a + *b * i as $t) }
+            }
+        }
+
+        #[inline]
+        pub unsafe extern "C-unwind" fn $rampmul(
+            i: *const $t,
+            is: Stride,
+            start: &mut $t,
+            step: &$t,
+            o: *mut $t,
+            os: Stride,
+            n: Len,
+        ) {
+            for k in 0..n {
+                unsafe { set(o, os, k, *start * get(i, is, k)) };
+                *start += *step;
+            }
+        }
+
+        #[inline]
+        #[allow(clippy::too_many_arguments)]
+        pub unsafe extern "C-unwind" fn $rampmul2(
+            i0: *const $t,
+            i1: *const $t,
+            is: Stride,
+            start: &mut $t,
+            step: &$t,
+            o0: *mut $t,
+            o1: *mut $t,
+            os: Stride,
+            n: Len,
+        ) {
+            for k in 0..n {
+                unsafe {
+                    set(o0, os, k, *start * get(i0, is, k));
+                    set(o1, os, k, *start * get(i1, is, k));
+                }
+                *start += *step;
+            }
+        }
+
+        /// C[n] = A[n] + (B[n] - A[n]) * n / (N - 1)
+        #[inline]
+        pub unsafe extern "C-unwind" fn $tmerg(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            let scale = if n > 1 { 1.0 / (n - 1) as $t } else { 0.0 };
+            for i in 0..n {
+                unsafe {
+                    let a = get(a, ia, i);
+                    let b = get(b, ib, i);
+                    set(c, ic, i, a + (b - a) * (i as $t * scale));
+                }
+            }
+        }
+
+        /// `ic` is counted in reals, 2 for contiguous complex values.
+        #[inline]
+        pub unsafe extern "C-unwind" fn $ctoz(
+            c: *const Complex<$t>,
+            ic: Stride,
+            z: *const SplitComplex<$t>,
+            iz: Stride,
+            n: Len,
+        ) {
+            let c = c as *const $t;
+            unsafe {
+                let z = &*z;
+                for i in 0..n {
+                    let re = get(c, ic, i);
+                    let im = *c.offset(i as Stride * ic + 1);
+                    set(z.re, iz, i, re);
+                    set(z.im, iz, i, im);
+                }
+            }
+        }
+
+        /// `ic` is counted in reals, 2 for contiguous complex values.
+        #[inline]
+        pub unsafe extern "C-unwind" fn $ztoc(
+            z: *const SplitComplex<$t>,
+            iz: Stride,
+            c: *mut Complex<$t>,
+            ic: Stride,
+            n: Len,
+        ) {
+            let c = c as *mut $t;
+            unsafe {
+                let z = &*z;
+                for i in 0..n {
+                    let re = get(z.re, iz, i);
+                    let im = get(z.im, iz, i);
+                    set(c, ic, i, re);
+                    c.offset(i as Stride * ic + 1).write(im);
+                }
+            }
+        }
+
+        /// C[n] += A.re[n]^2 + A.im[n]^2
+        #[inline]
+        pub unsafe extern "C-unwind" fn $zaspec(a: *const SplitComplex<$t>, c: *mut $t, n: Len) {
+            unsafe {
+                let a = &*a;
+                for i in 0..n {
+                    let re = get(a.re, 1, i);
+                    let im = get(a.im, 1, i);
+                    set(c, 1, i, get(c, 1, i) + re * re + im * im);
+                }
+            }
+        }
+
+        /// C[n] = alpha * log10(A[n] / B), alpha is 20 for amplitude (F = 1)
+        /// and 10 for power (F = 0).
+        #[inline]
+        pub unsafe extern "C-unwind" fn $dbcon(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+            f: u32,
+        ) {
+            let alpha: $t = if f == 1 { 20.0 } else { 10.0 };
+            let b = unsafe { *b };
+            for i in 0..n {
+                unsafe { set(c, ic, i, alpha * (get(a, ia, i) / b).log10()) }
+            }
+        }
+
+        /// D[n] = clamp(A[n], B, C)
+        #[inline]
+        pub unsafe extern "C-unwind" fn $clip(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            c: *const $t,
+            d: *mut $t,
+            id: Stride,
+            n: Len,
+        ) {
+            let (low, high) = unsafe { (*b, *c) };
+            for i in 0..n {
+                let a = unsafe { get(a, ia, i) };
+                let v = if a < low {
+                    low
+                } else if a > high {
+                    high
+                } else {
+                    a
+                };
+                unsafe { set(d, id, i, v) }
+            }
+        }
+    };
+}
+
+float!(
+    f32,
+    _fill_f32,
+    _clr_f32,
+    _gen_f32,
+    _ramp_f32,
+    _rampmul_f32,
+    _rampmul2_f32,
+    _tmerg_f32,
+    _ctoz_f32,
+    _ztoc_f32,
+    _zaspec_f32,
+    _dbcon_f32,
+    _clip_f32
+);
+
+float!(
+    f64,
+    _fill_f64,
+    _clr_f64,
+    _gen_f64,
+    _ramp_f64,
+    _rampmul_f64,
+    _rampmul2_f64,
+    _tmerg_f64,
+    _ctoz_f64,
+    _ztoc_f64,
+    _zaspec_f64,
+    _dbcon_f64,
+    _clip_f64
+);
+
+#[inline]
+pub unsafe extern "C-unwind" fn _fill_i32(a: &i32, c: *mut i32, ic: Stride, n: Len) {
+    for i in 0..n {
+        unsafe { set(c, ic, i, *a) }
+    }
+}
+
+/// FFT setup behind the opaque [`FftSetup`] pointers.
+///
+/// Transforms are computed in `f64` for both precisions.
+struct Setup {
+    /// Largest supported length, `radix * 2^log2n`.
+    n: usize,
+    /// `exp(-2πik/n)`
+    twiddles: Vec<(f64, f64)>,
+}
+
+impl Setup {
+    fn new(log2n: Len, radix: FftRadix) -> Option<Self> {
+        let r = match radix {
+            FftRadix::_2 => 1,
+            FftRadix::_3 => 3,
+            FftRadix::_5 => 5,
+        };
+        let n = 1usize.checked_shl(log2n.try_into().ok()?)?.checked_mul(r)?;
+        let twiddles = (0..n)
+            .map(|k| {
+                let (sin, cos) = (TAU * k as f64 / n as f64).sin_cos();
+                (cos, -sin)
+            })
+            .collect();
+        Some(Self { n, twiddles })
+    }
+
+    /// `exp(∓2πik/len)`
+    #[inline]
+    fn twiddle(&self, k: usize, len: usize, inverse: bool) -> (f64, f64) {
+        let (re, im) = self.twiddles[(k % len) * (self.n / len)];
+        if inverse { (re, -im) } else { (re, im) }
+    }
+
+    /// Unscaled in-place DFT of `2^k`, `3 * 2^k` or `5 * 2^k` values.
+    fn transform(&self, re: &mut [f64], im: &mut [f64], inverse: bool) {
+        let n = re.len();
+        assert!(
+            n != 0 && self.n % n == 0,
+            "fft length {n} is not supported by the setup"
+        );
+        let r = n >> n.trailing_zeros();
+        if r == 1 {
+            self.radix2(re, im, inverse);
+            return;
+        }
+
+        let m = n / r;
+        let mut sub_re = vec![0.0; n];
+        let mut sub_im = vec![0.0; n];
+        for q in 0..r {
+            let (s_re, s_im) = (&mut sub_re[q * m..][..m], &mut sub_im[q * m..][..m]);
+            for j in 0..m {
+                s_re[j] = re[j * r + q];
+                s_im[j] = im[j * r + q];
+            }
+            self.radix2(s_re, s_im, inverse);
+        }
+        for k in 0..n {
+            let (mut acc_re, mut acc_im) = (0.0, 0.0);
+            for q in 0..r {
+                let (w_re, w_im) = self.twiddle(q * k, n, inverse);
+                let (s_re, s_im) = (sub_re[q * m + k % m], sub_im[q * m + k % m]);
+                acc_re += s_re * w_re - s_im * w_im;
+                acc_im += s_re * w_im + s_im * w_re;
+            }
+            re[k] = acc_re;
+            im[k] = acc_im;
+        }
+    }
+
+    fn radix2(&self, re: &mut [f64], im: &mut [f64], inverse: bool) {
+        let n = re.len();
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                re.swap(i, j);
+                im.swap(i, j);
+            }
+        }
+
+        let mut len = 2;
+        while len <= n {
+            let half = len / 2;
+            for start in (0..n).step_by(len) {
+                for k in 0..half {
+                    let (w_re, w_im) = self.twiddle(k, len, inverse);
+                    let (a, b) = (start + k, start + k + half);
+                    let t_re = re[b] * w_re - im[b] * w_im;
+                    let t_im = re[b] * w_im + im[b] * w_re;
+                    re[b] = re[a] - t_re;
+                    im[b] = im[a] - t_im;
+                    re[a] += t_re;
+                    im[a] += t_im;
+                }
+            }
+            len <<= 1;
+        }
+    }
+}
+
+trait Real: Copy {
+    fn to_f64(self) -> f64;
+    fn from_f64(val: f64) -> Self;
+}
+
+impl Real for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn from_f64(val: f64) -> Self {
+        val as f32
+    }
+}
+
+impl Real for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn from_f64(val: f64) -> Self {
+        val
+    }
+}
+
+unsafe fn read_split<T: Real>(
+    z: *const SplitComplex<T>,
+    iz: Stride,
+    n: Len,
+) -> (Vec<f64>, Vec<f64>) {
+    unsafe {
+        let z = &*z;
+        (0..n)
+            .map(|i| (get(z.re, iz, i).to_f64(), get(z.im, iz, i).to_f64()))
+            .unzip()
+    }
+}
+
+unsafe fn write_split<T: Real>(z: *const SplitComplex<T>, iz: Stride, re: &[f64], im: &[f64]) {
+    unsafe {
+        let z = &*z;
+        for (i, (re, im)) in re.iter().zip(im).enumerate() {
+            set(z.re, iz, i, T::from_f64(*re));
+            set(z.im, iz, i, T::from_f64(*im));
+        }
+    }
+}
+
+fn create<T>(log2n: Len, radix: FftRadix) -> Option<NonNull<FftSetup<T>>> {
+    let setup = Box::new(Setup::new(log2n, radix)?);
+    NonNull::new(Box::into_raw(setup) as *mut FftSetup<T>)
+}
+
+unsafe fn destroy<T>(setup: *mut FftSetup<T>) {
+    if !setup.is_null() {
+        drop(unsafe { Box::from_raw(setup as *mut Setup) });
+    }
+}
+
+unsafe fn zop<T: Real>(
+    setup: *mut FftSetup<T>,
+    a: *const SplitComplex<T>,
+    ia: Stride,
+    c: *const SplitComplex<T>,
+    ic: Stride,
+    n: Len,
+    direction: FftDirection,
+) {
+    unsafe {
+        let setup = &*(setup as *const Setup);
+        let (mut re, mut im) = read_split(a, ia, n);
+        setup.transform(&mut re, &mut im, direction == FftDirection::Inverse);
+        write_split(c, ic, &re, &im);
+    }
+}
+
+/// Real transform of `2^log2n` values packed as even/odd split complex.
+///
+/// Forward writes twice the DFT, with the real Nyquist value in `im[0]`.
+/// Inverse expects that layout and writes the unscaled inverse DFT,
+/// so a round trip scales by `2N`.
+unsafe fn zrip<T: Real>(
+    setup: *mut FftSetup<T>,
+    c: *const SplitComplex<T>,
+    ic: Stride,
+    log2n: Len,
+    direction: FftDirection,
+) {
+    let n = 1usize << log2n;
+    let half = n / 2;
+    if half == 0 {
+        return;
+    }
+    unsafe {
+        let setup = &*(setup as *const Setup);
+        let (p_re, p_im) = read_split(c, ic, half);
+        let mut re = vec![0.0; n];
+        let mut im = vec![0.0; n];
+
+        if direction == FftDirection::Forward {
+            for j in 0..half {
+                re[2 * j] = p_re[j];
+                re[2 * j + 1] = p_im[j];
+            }
+            setup.transform(&mut re, &mut im, false);
+            let mut o_re: Vec<f64> = re[..half].iter().map(|v| v * 2.0).collect();
+            let mut o_im: Vec<f64> = im[..half].iter().map(|v| v * 2.0).collect();
+            o_re[0] = 2.0 * re[0];
+            o_im[0] = 2.0 * re[half];
+            write_split(c, ic, &o_re, &o_im);
+        } else {
+            re[0] = p_re[0];
+            re[half] = p_im[0];
+            for k in 1..half {
+                re[k] = p_re[k];
+                im[k] = p_im[k];
+                re[n - k] = p_re[k];
+                im[n - k] = -p_im[k];
+            }
+            setup.transform(&mut re, &mut im, true);
+            let (o_re, o_im): (Vec<f64>, Vec<f64>) =
+                (0..half).map(|j| (re[2 * j], re[2 * j + 1])).unzip();
+            write_split(c, ic, &o_re, &o_im);
+        }
+    }
+}
+
+macro_rules! fft {
+    ($t:ty, $create:ident, $destroy:ident, $zip:ident, $zipt:ident, $zrip:ident, $fft3:ident, $fft5:ident) => {
+        pub unsafe extern "C-unwind" fn $create(
+            log2n: Len,
+            radix: FftRadix,
+        ) -> Option<NonNull<FftSetup<$t>>> {
+            create(log2n, radix)
+        }
+
+        pub unsafe extern "C-unwind" fn $destroy(setup: *mut FftSetup<$t>) {
+            unsafe { destroy(setup) }
+        }
+
+        pub unsafe extern "C-unwind" fn $zip(
+            setup: *mut FftSetup<$t>,
+            c: *mut SplitComplex<$t>,
+            ic: Stride,
+            log2n: Len,
+            direction: FftDirection,
+        ) {
+            unsafe { zop(setup, c, ic, c, ic, 1 << log2n, direction) }
+        }
+
+        pub unsafe extern "C-unwind" fn $zipt(
+            setup: *mut FftSetup<$t>,
+            c: *const SplitComplex<$t>,
+            ic: Stride,
+            _buffer: *mut SplitComplex<$t>,
+            log2n: Len,
+            direction: FftDirection,
+        ) {
+            unsafe { zop(setup, c, ic, c, ic, 1 << log2n, direction) }
+        }
+
+        pub unsafe extern "C-unwind" fn $zrip(
+            setup: *mut FftSetup<$t>,
+            c: *const SplitComplex<$t>,
+            ic: Stride,
+            log2n: Len,
+            direction: FftDirection,
+        ) {
+            unsafe { zrip(setup, c, ic, log2n, direction) }
+        }
+
+        pub unsafe extern "C-unwind" fn $fft3(
+            setup: *mut FftSetup<$t>,
+            a: *const SplitComplex<$t>,
+            ia: Stride,
+            c: *const SplitComplex<$t>,
+            ic: Stride,
+            log2n: Len,
+            direction: FftDirection,
+        ) {
+            unsafe { zop(setup, a, ia, c, ic, 3 << log2n, direction) }
+        }
+
+        pub unsafe extern "C-unwind" fn $fft5(
+            setup: *mut FftSetup<$t>,
+            a: *const SplitComplex<$t>,
+            ia: Stride,
+            c: *const SplitComplex<$t>,
+            ic: Stride,
+            log2n: Len,
+            direction: FftDirection,
+        ) {
+            unsafe { zop(setup, a, ia, c, ic, 5 << log2n, direction) }
+        }
+    };
+}
+
+fft!(
+    f32,
+    _create_fftsetup_f32,
+    _destroy_fftsetup_f32,
+    _fft_zip_f32,
+    _fft_zipt_f32,
+    _fft_zrip_f32,
+    _fft3_zop_f32,
+    _fft5_zop_f32
+);
+
+fft!(
+    f64,
+    _create_fftsetup_f64,
+    _destroy_fftsetup_f64,
+    _fft_zip_f64,
+    _fft_zipt_f64,
+    _fft_zrip_f64,
+    _fft3_zop_f64,
+    _fft5_zop_f64
+);
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use crate::vdsp::{self, Complex, FftDirection, FftRadix, SplitComplex, soft};
+
+    /// Reference DFT
+    fn dft(re: &[f64], im: &[f64], inverse: bool) -> (Vec<f64>, Vec<f64>) {
+        let n = re.len();
+        let sign = if inverse { 1.0 } else { -1.0 };
+        (0..n)
+            .map(|k| {
+                (0..n).fold((0.0, 0.0), |(acc_re, acc_im), j| {
+                    let (sin, cos) = (sign * TAU * (j * k % n) as f64 / n as f64).sin_cos();
+                    (
+                        acc_re + re[j] * cos - im[j] * sin,
+                        acc_im + re[j] * sin + im[j] * cos,
+                    )
+                })
+            })
+            .unzip()
+    }
+
+    fn signal(n: usize, seed: f64) -> Vec<f64> {
+        (0..n)
+            .map(|i| ((i as f64 + seed) * 0.37).sin() + (i as f64 * seed * 0.11).cos() * 0.5)
+            .collect()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64], eps: f64) {
+        assert_eq!(a.len(), b.len());
+        for (i, (a, b)) in a.iter().zip(b).enumerate() {
+            assert!((a - b).abs() <= eps, "{i}: {a} != {b}");
+        }
+    }
+
+    #[test]
+    fn strides() {
+        let a = [1.0f32, -1.0, 2.0, -2.0, 3.0, -3.0];
+        let b = [10.0f32, 20.0, 30.0];
+        let mut c = [0.0f32; 6];
+        unsafe { soft::_add_f32(a.as_ptr(), 2, b.as_ptr(), 1, c.as_mut_ptr().add(1), 2, 3) };
+        assert_eq!(c, [0.0, 11.0, 0.0, 22.0, 0.0, 33.0]);
+
+        // negative stride starts at the given element and walks backwards
+        unsafe { soft::_sub_f32(b.as_ptr().add(2), -1, b.as_ptr(), 1, c.as_mut_ptr(), 1, 3) };
+        assert_eq!(&c[..3], &[-20.0, 0.0, 20.0]);
+
+        let mut m = 0.0f32;
+        unsafe { soft::_max_f32(a.as_ptr().add(1), 2, &mut m, 3) };
+        assert_eq!(m, -1.0);
+
+        let mut io = [1.0f32, 2.0, 3.0, 4.0];
+        unsafe { soft::_mul_f32(io.as_ptr(), 1, io.as_ptr(), 1, io.as_mut_ptr(), 1, 4) };
+        assert_eq!(io, [1.0, 4.0, 9.0, 16.0]);
+
+        let mut z = [0.0f32; 2];
+        unsafe { soft::_fill_f32(&5.0, z.as_mut_ptr(), 0, 3) };
+        assert_eq!(z, [5.0, 0.0]);
+    }
+
+    #[test]
+    fn reductions() {
+        let a = [3.0f64, -4.0, 1.0, -0.5];
+        assert_eq!(vdsp::se_f64(&a), -0.5);
+        assert_eq!(vdsp::semg_f64(&a), 8.5);
+        assert_eq!(vdsp::sesq_f64(&a), 26.25);
+        assert_eq!(vdsp::svs_f64(&a), 9.0 - 16.0 + 1.0 - 0.25);
+        assert_eq!(vdsp::mean_sq_f64(&a), 26.25 / 4.0);
+        assert_eq!(vdsp::maxmg_f64(&a), 4.0);
+        assert_eq!(vdsp::minmg_f64(&a), 0.5);
+        assert_eq!(vdsp::max_f64(&a), 3.0);
+        assert_eq!(vdsp::min_f64(&a), -4.0);
+        assert_eq!(vdsp::max_f64(&[]), f64::NEG_INFINITY);
+        assert_eq!(vdsp::maxmg_f64(&[]), 0.0);
+
+        let mut c = [0.0f32; 4];
+        vdsp::tmerg_f32(&[0.0; 4], &[3.0; 4], &mut c);
+        assert_eq!(c, [0.0, 1.0, 2.0, 3.0]);
+        vdsp::ramp_f32(&1.0, &0.5, &mut c);
+        assert_eq!(c, [1.0, 1.5, 2.0, 2.5]);
+
+        let mut db = [0.0f32; 2];
+        unsafe { soft::_dbcon_f32([10.0, 100.0].as_ptr(), 1, &1.0, db.as_mut_ptr(), 1, 2, 1) };
+        assert_eq!(db, [20.0, 40.0]);
+
+        let mut clipped = [0.0f64; 3];
+        unsafe {
+            soft::_clip_f64(
+                [-2.0, 0.5, 2.0].as_ptr(),
+                1,
+                &-1.0,
+                &1.0,
+                clipped.as_mut_ptr(),
+                1,
+                3,
+            )
+        };
+        assert_eq!(clipped, [-1.0, 0.5, 1.0]);
+
+        let mut eqv = [0i32; 1];
+        vdsp::eqv_i32(&[0b1010], &[0b0110], &mut eqv);
+        assert_eq!(eqv[0], !0b1100);
+    }
+
+    #[test]
+    fn packing() {
+        let a = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let mut re = [0.0f32; 3];
+        let mut im = [0.0f32; 3];
+        vdsp::actoz_f32(&a, &mut re, &mut im);
+        assert_eq!(re, [1.0, 3.0, 5.0]);
+        assert_eq!(im, [2.0, 4.0, 6.0]);
+
+        let mut c = [Complex::<f32>::default(); 3];
+        vdsp::ztoc_f32(&re, &im, &mut c);
+        assert_eq!((c[2].re, c[2].im), (5.0, 6.0));
+
+        let mut spec = [1.0f32; 3];
+        vdsp::zaspec_f32(&SplitComplex::new(&re, &im), &mut spec);
+        assert_eq!(spec, [6.0, 26.0, 62.0]);
+    }
+
+    #[test]
+    fn fft_complex() {
+        for (radix, sizes) in [
+            (FftRadix::_2, [1usize, 2, 4, 8, 64]),
+            (FftRadix::_3, [2, 3, 6, 12, 48]),
+            (FftRadix::_5, [4, 5, 10, 20, 80]),
+        ] {
+            let mut fft = vdsp::Fft::new_f64(6, radix).unwrap();
+            for n in sizes {
+                let x_re = signal(n, 1.0);
+                let x_im = signal(n, 2.5);
+                let (e_re, e_im) = dft(&x_re, &x_im, false);
+
+                let mut re = vec![0.0; n];
+                let mut im = vec![0.0; n];
+                if n.is_power_of_two() {
+                    re.copy_from_slice(&x_re);
+                    im.copy_from_slice(&x_im);
+                    fft.forward_io(&mut re, &mut im);
+                } else if n % 3 == 0 {
+                    fft.transform3(&x_re, &x_im, &mut re, &mut im, FftDirection::Forward);
+                } else {
+                    fft.transform5(&x_re, &x_im, &mut re, &mut im, FftDirection::Forward);
+                }
+                assert_close(&re, &e_re, 1e-9);
+                assert_close(&im, &e_im, 1e-9);
+
+                // unscaled inverse
+                let (i_re, i_im) = dft(&e_re, &e_im, true);
+                let n_f = n as f64;
+                assert_close(
+                    &i_re,
+                    &x_re.iter().map(|v| v * n_f).collect::<Vec<_>>(),
+                    1e-9,
+                );
+                assert_close(
+                    &i_im,
+                    &x_im.iter().map(|v| v * n_f).collect::<Vec<_>>(),
+                    1e-9,
+                );
+                if n.is_power_of_two() {
+                    fft.inverse_io(&mut re, &mut im);
+                    assert_close(&re, &i_re, 1e-9);
+                    assert_close(&im, &i_im, 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fft_real() {
+        let n = 32;
+        let x: Vec<f32> = signal(n, 3.0).iter().map(|v| *v as f32).collect();
+        let (e_re, e_im) = dft(
+            &x.iter().map(|v| *v as f64).collect::<Vec<_>>(),
+            &vec![0.0; n],
+            false,
+        );
+
+        let mut re = vec![0.0f32; n / 2];
+        let mut im = vec![0.0f32; n / 2];
+        vdsp::actoz_f32(&x, &mut re, &mut im);
+
+        let mut fft = vdsp::Fft::new_f32(5, FftRadix::_2).unwrap();
+        fft.zr_io(&mut re, &mut im, FftDirection::Forward);
+
+        assert!((re[0] as f64 - 2.0 * e_re[0]).abs() < 1e-4);
+        assert!((im[0] as f64 - 2.0 * e_re[n / 2]).abs() < 1e-4);
+        for k in 1..n / 2 {
+            assert!((re[k] as f64 - 2.0 * e_re[k]).abs() < 1e-4, "{k}");
+            assert!((im[k] as f64 - 2.0 * e_im[k]).abs() < 1e-4, "{k}");
+        }
+
+        fft.zr_io(&mut re, &mut im, FftDirection::Inverse);
+        let mut y = vec![Complex::<f32>::default(); n / 2];
+        vdsp::ztoc_f32(&re, &im, &mut y);
+        for j in 0..n / 2 {
+            let scale = 2.0 * n as f32;
+            assert!((y[j].re / scale - x[2 * j]).abs() < 1e-5);
+            assert!((y[j].im / scale - x[2 * j + 1]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn fft_setup() {
+        assert!(vdsp::Fft::new_f32(usize::MAX, FftRadix::_2).is_none());
+        let mut fft = vdsp::Fft::new_f32(2, FftRadix::_2).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            fft.forward_io(&mut [0.0; 8], &mut [0.0; 8]);
+        }));
+        assert!(result.is_err());
+    }
+
+    /// Compares with Accelerate where both backends are available.
+    #[cfg(all(target_vendor = "apple", not(feature = "vdsp-soft")))]
+    #[test]
+    fn accelerate() {
+        let n = 64;
+        let a: Vec<f32> = signal(n, 1.0).iter().map(|v| *v as f32).collect();
+        let b: Vec<f32> = signal(n, 2.0).iter().map(|v| *v as f32 + 2.0).collect();
+        let mut c0 = vec![0.0f32; n];
+        let mut c1 = vec![0.0f32; n];
+
+        macro_rules! same_vv {
+            ($($name:ident),*) => {$(
+                unsafe {
+                    vdsp::$name(a.as_ptr(), 2, b.as_ptr(), -1, c0.as_mut_ptr(), 1, n / 2);
+                    soft::$name(a.as_ptr(), 2, b.as_ptr(), -1, c1.as_mut_ptr(), 1, n / 2);
+                }
+                assert_eq!(c0, c1, stringify!($name));
+            )*};
+        }
+        same_vv!(_add_f32, _sub_f32, _mul_f32, _div_f32, _tmerg_f32);
+
+        macro_rules! same_s {
+            ($($name:ident),*) => {$(
+                let (mut s0, mut s1) = (0.0f32, 0.0f32);
+                unsafe {
+                    vdsp::$name(a.as_ptr(), 3, &mut s0, n / 3);
+                    soft::$name(a.as_ptr(), 3, &mut s1, n / 3);
+                }
+                assert!((s0 - s1).abs() <= 1e-4 * s0.abs().max(1.0), stringify!($name));
+            )*};
+        }
+        same_s!(
+            _mean_f32,
+            _meansq_f32,
+            _se_f32,
+            _semg_f32,
+            _sesq_f32,
+            _svs_f32,
+            _maxmg_f32,
+            _minmg_f32,
+            _max_f32,
+            _min_f32
+        );
+
+        for (log2n, radix) in [(6, FftRadix::_2), (4, FftRadix::_3), (4, FftRadix::_5)] {
+            let len = n;
+            let mut re0 = a[..len].to_vec();
+            let mut im0 = b[..len].to_vec();
+            let mut re1 = re0.clone();
+            let mut im1 = im0.clone();
+            let mut fft = vdsp::Fft::new_f32(log2n, radix).unwrap();
+            fft.forward_io(&mut re0, &mut im0);
+            unsafe {
+                let setup = soft::_create_fftsetup_f32(log2n, radix).unwrap().as_ptr();
+                let mut split = SplitComplex::new_mut(&mut re1, &mut im1);
+                soft::_fft_zip_f32(setup, &mut split, 1, 6, FftDirection::Forward);
+                soft::_destroy_fftsetup_f32(setup);
+            }
+            for k in 0..len {
+                assert!((re0[k] - re1[k]).abs() < 1e-3);
+                assert!((im0[k] - im1[k]).abs() < 1e-3);
+            }
+        }
+    }
+}